@@ -0,0 +1,63 @@
+//! Records which build this is: `git describe`, the commit and the build
+//! date, for `xwlm --version` and the files xwlm writes. Each is empty
+//! when it cannot be found out, e.g. in a tarball without `.git`.
+
+use std::{
+    env,
+    path::Path,
+    process::Command,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+fn main() {
+    println!("cargo:rerun-if-changed=build.rs");
+    println!("cargo:rerun-if-env-changed=SOURCE_DATE_EPOCH");
+    // Missing files would make Cargo rerun this on every build.
+    for file in [".git/HEAD", ".git/index"] {
+        if Path::new(file).exists() {
+            println!("cargo:rerun-if-changed={file}");
+        }
+    }
+
+    let describe = git(&["describe", "--tags", "--always", "--dirty"]);
+    let commit = git(&["rev-parse", "HEAD"]);
+    println!("cargo:rustc-env=XWLM_GIT_DESCRIBE={describe}");
+    println!("cargo:rustc-env=XWLM_GIT_COMMIT={commit}");
+    println!("cargo:rustc-env=XWLM_BUILD_DATE={}", build_date());
+}
+
+fn git(args: &[&str]) -> String {
+    Command::new("git")
+        .args(args)
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .and_then(|output| String::from_utf8(output.stdout).ok())
+        .map(|out| out.trim().to_string())
+        .unwrap_or_default()
+}
+
+/// `YYYY-MM-DD`, from `SOURCE_DATE_EPOCH` for reproducible builds.
+fn build_date() -> String {
+    let secs = match env::var("SOURCE_DATE_EPOCH") {
+        Ok(epoch) => epoch.trim().parse().ok(),
+        Err(_) => SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .ok()
+            .map(|d| d.as_secs() as i64),
+    };
+    let Some(secs) = secs else {
+        return String::new();
+    };
+    // Howard Hinnant's days-to-civil conversion.
+    let days = secs.div_euclid(86_400) + 719_468;
+    let era = days.div_euclid(146_097);
+    let doe = days - era * 146_097;
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    format!("{year:04}-{month:02}-{day:02}")
+}
@@ -0,0 +1,299 @@
+use std::fmt;
+
+use crate::geometry::Rect;
+
+/// A way focus can leave a monitor.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    Left,
+    Right,
+    Up,
+    Down,
+}
+
+impl Direction {
+    pub const ALL: [Direction; 4] = [
+        Direction::Left,
+        Direction::Right,
+        Direction::Up,
+        Direction::Down,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Direction::Left => "left",
+            Direction::Right => "right",
+            Direction::Up => "up",
+            Direction::Down => "down",
+        }
+    }
+
+    fn relation(self) -> &'static str {
+        match self {
+            Direction::Left => "left of",
+            Direction::Right => "right of",
+            Direction::Up => "above",
+            Direction::Down => "below",
+        }
+    }
+
+    fn horizontal(self) -> bool {
+        matches!(self, Direction::Left | Direction::Right)
+    }
+
+    /// Whether `other` lies wholly past `from`'s edge this way.
+    fn beyond(self, from: &Rect, other: &Rect) -> bool {
+        match self {
+            Direction::Left => other.right() <= from.x,
+            Direction::Right => other.x >= from.right(),
+            Direction::Up => other.bottom() <= from.y,
+            Direction::Down => other.y >= from.bottom(),
+        }
+    }
+
+    /// The extent of `rect` across this direction.
+    fn span(self, rect: &Rect) -> (i32, i32) {
+        if self.horizontal() {
+            (rect.y, rect.bottom())
+        } else {
+            (rect.x, rect.right())
+        }
+    }
+
+    /// How far `other` is past `from`'s edge.
+    fn distance(self, from: &Rect, other: &Rect) -> i32 {
+        match self {
+            Direction::Left => from.x - other.right(),
+            Direction::Right => other.x - from.right(),
+            Direction::Up => from.y - other.bottom(),
+            Direction::Down => other.y - from.bottom(),
+        }
+    }
+}
+
+/// A direction focus cannot leave a monitor in, although another monitor
+/// lies that way.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Gap {
+    pub from: String,
+    pub direction: Direction,
+    /// The closest monitor that way.
+    pub toward: String,
+    /// How far `from` has to slide sideways to line up with `toward`.
+    pub offset: i32,
+    /// Where `from` lines up with `toward`.
+    pub fix: (i32, i32),
+}
+
+/// e.g. "from DP-2, focus-up reaches nothing although eDP-1 is above it
+/// but offset by 2200px"
+impl fmt::Display for Gap {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "from {}, focus-{} reaches nothing although {} is {} it but \
+             offset by {}px",
+            self.from,
+            self.direction.label(),
+            self.toward,
+            self.direction.relation(),
+            self.offset
+        )
+    }
+}
+
+/// Finds every direction focus gets stuck in. Compositors like Hyprland
+/// only move focus to a monitor that lies past the edge and shares part
+/// of it, so a monitor above but shifted fully sideways is out of reach.
+pub fn gaps(monitors: &[(String, Rect)]) -> Vec<Gap> {
+    let mut gaps = Vec::new();
+    for (name, rect) in monitors {
+        for direction in Direction::ALL {
+            let (start, end) = direction.span(rect);
+            let ahead: Vec<&(String, Rect)> = monitors
+                .iter()
+                .filter(|(n, r)| n != name && direction.beyond(rect, r))
+                .collect();
+            let reachable = ahead.iter().any(|(_, r)| {
+                let (s, e) = direction.span(r);
+                s < end && e > start
+            });
+            if reachable {
+                continue;
+            }
+            let nearest = ahead.into_iter().min_by_key(|(_, r)| {
+                let (s, e) = direction.span(r);
+                (direction.distance(rect, r), (s - end).max(start - e))
+            });
+            let Some((toward, other)) = nearest else {
+                continue;
+            };
+            let (s, e) = direction.span(other);
+            // Line up the near edges, as far as the sizes allow.
+            let aligned = if s >= end {
+                s
+            } else {
+                (e - (end - start)).max(s)
+            };
+            let fix = if direction.horizontal() {
+                (rect.x, aligned)
+            } else {
+                (aligned, rect.y)
+            };
+            gaps.push(Gap {
+                from: name.clone(),
+                direction,
+                toward: toward.clone(),
+                offset: (aligned - start).abs(),
+                fix,
+            });
+        }
+    }
+    gaps
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn layout(rects: &[(&str, i32, i32, i32, i32)]) -> Vec<(String, Rect)> {
+        rects
+            .iter()
+            .map(|&(name, x, y, w, h)| {
+                (name.to_string(), Rect::new(x, y, w, h))
+            })
+            .collect()
+    }
+
+    fn summary(gaps: &[Gap]) -> Vec<(&str, Direction, &str)> {
+        gaps.iter()
+            .map(|g| (g.from.as_str(), g.direction, g.toward.as_str()))
+            .collect()
+    }
+
+    #[test]
+    fn test_aligned_layouts_have_no_gaps() {
+        let table = [
+            vec![],
+            vec![("DP-1", 0, 0, 1920, 1080)],
+            vec![("DP-1", 0, 0, 1920, 1080), ("DP-2", 1920, 0, 2560, 1440)],
+            // Stacked, sharing only part of the edge.
+            vec![("DP-1", 0, 0, 1920, 1080), ("eDP-1", 1500, 1080, 1920, 1200)],
+            // A 2×2 grid; the diagonals are reached through the sides.
+            vec![
+                ("A", 0, 0, 1920, 1080),
+                ("B", 1920, 0, 1920, 1080),
+                ("C", 0, 1080, 1920, 1080),
+                ("D", 1920, 1080, 1920, 1080),
+            ],
+            // A gap along the way is fine as long as the edges line up.
+            vec![("DP-1", 0, 0, 1920, 1080), ("DP-2", 2000, 200, 1920, 1080)],
+        ];
+        for rects in table {
+            assert_eq!(gaps(&layout(&rects)), [], "{rects:?}");
+        }
+    }
+
+    #[test]
+    fn test_monitor_shifted_past_the_edge_is_out_of_reach() {
+        let monitors = layout(&[
+            ("DP-2", 0, 1080, 1920, 1080),
+            ("eDP-1", 2200, 0, 1920, 1080),
+        ]);
+        let found = gaps(&monitors);
+        assert_eq!(
+            summary(&found),
+            [
+                ("DP-2", Direction::Right, "eDP-1"),
+                ("DP-2", Direction::Up, "eDP-1"),
+                ("eDP-1", Direction::Left, "DP-2"),
+                ("eDP-1", Direction::Down, "DP-2"),
+            ]
+        );
+        let up = &found[1];
+        assert_eq!(up.offset, 2200);
+        assert_eq!(up.fix, (2200, 1080));
+        assert_eq!(
+            up.to_string(),
+            "from DP-2, focus-up reaches nothing although eDP-1 is above it \
+             but offset by 2200px"
+        );
+    }
+
+    #[test]
+    fn test_corner_to_corner_counts_as_a_gap() {
+        let monitors = layout(&[
+            ("A", 0, 0, 1920, 1080),
+            ("B", 1920, 1080, 1920, 1080),
+        ]);
+        let found = gaps(&monitors);
+        assert_eq!(found.len(), 4);
+        let right = found
+            .iter()
+            .find(|g| g.from == "A" && g.direction == Direction::Right)
+            .unwrap();
+        assert_eq!(right.fix, (0, 1080));
+        assert_eq!(right.offset, 1080);
+    }
+
+    #[test]
+    fn test_nearest_monitor_that_way_is_suggested() {
+        let monitors = layout(&[
+            ("A", 0, 2000, 1920, 1080),
+            ("far", 3000, 0, 1920, 1080),
+            ("near", 2500, 900, 1920, 1080),
+        ]);
+        let up = gaps(&monitors)
+            .into_iter()
+            .find(|g| g.from == "A" && g.direction == Direction::Up)
+            .unwrap();
+        assert_eq!(up.toward, "near");
+        assert_eq!(up.fix, (2500, 2000));
+    }
+
+    #[test]
+    fn test_fix_lines_up_the_far_edge_when_the_monitor_is_behind() {
+        // The target sits left of A, so A's right edge meets its right edge.
+        let monitors = layout(&[
+            ("A", 3000, 1080, 1920, 1080),
+            ("B", 0, 0, 2560, 1080),
+        ]);
+        let up = gaps(&monitors)
+            .into_iter()
+            .find(|g| g.from == "A" && g.direction == Direction::Up)
+            .unwrap();
+        assert_eq!(up.fix, (640, 1080));
+        assert_eq!(up.offset, 2360);
+
+        // Wider than the target: keep its left edge instead of going past.
+        let monitors = layout(&[
+            ("A", 3000, 1080, 3840, 1080),
+            ("B", 0, 0, 1920, 1080),
+        ]);
+        let up = gaps(&monitors)
+            .into_iter()
+            .find(|g| g.from == "A" && g.direction == Direction::Up)
+            .unwrap();
+        assert_eq!(up.fix, (0, 1080));
+    }
+
+    #[test]
+    fn test_every_fix_makes_the_direction_reachable() {
+        let monitors = layout(&[
+            ("A", 0, 1080, 1920, 1080),
+            ("B", 2200, 0, 1920, 1080),
+            ("C", 5000, 3000, 1280, 1024),
+        ]);
+        for gap in gaps(&monitors) {
+            let mut moved = monitors.clone();
+            let entry = moved.iter_mut().find(|(n, _)| *n == gap.from).unwrap();
+            entry.1.x = gap.fix.0;
+            entry.1.y = gap.fix.1;
+            assert!(
+                !gaps(&moved).iter().any(|g| g.from == gap.from
+                    && g.direction == gap.direction),
+                "{gap}"
+            );
+        }
+    }
+}
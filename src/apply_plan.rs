@@ -0,0 +1,271 @@
+use std::fmt;
+
+use wlx_monitors::WlTransform;
+
+use crate::{geometry::Rect, state::ModeSpec, utils::transform_label};
+
+/// One monitor's staged changes, as `W` sees them.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Staged {
+    pub name: String,
+    /// Where it is now in logical pixels, `None` while disabled.
+    pub now: Option<Rect>,
+    /// Where it ends up, `None` when it ends up disabled.
+    pub after: Option<Rect>,
+    pub mode: Option<ModeSpec>,
+    pub scale: Option<f64>,
+    pub transform: Option<WlTransform>,
+}
+
+/// A single change `W` sends, in the order [`plan`] puts them.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Step {
+    Enable { name: String, x: i32, y: i32 },
+    Mode { name: String, mode: ModeSpec },
+    Scale { name: String, scale: f64 },
+    Transform { name: String, transform: WlTransform },
+    Position { name: String, x: i32, y: i32 },
+    Disable { name: String },
+}
+
+impl Step {
+    pub fn name(&self) -> &str {
+        match self {
+            Step::Enable { name, .. }
+            | Step::Mode { name, .. }
+            | Step::Scale { name, .. }
+            | Step::Transform { name, .. }
+            | Step::Position { name, .. }
+            | Step::Disable { name } => name,
+        }
+    }
+}
+
+/// e.g. "enable DP-2 at 1920,0"
+impl fmt::Display for Step {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Step::Enable { name, x, y } => {
+                write!(f, "enable {name} at {x},{y}")
+            }
+            Step::Mode { name, mode } => write!(f, "switch {name} to {mode}"),
+            Step::Scale { name, scale } => {
+                write!(f, "scale {name} to {scale:.2}")
+            }
+            Step::Transform { name, transform } => {
+                write!(f, "transform {name} to {}", transform_label(*transform))
+            }
+            Step::Position { name, x, y } => {
+                write!(f, "move {name} to {x},{y}")
+            }
+            Step::Disable { name } => write!(f, "disable {name}"),
+        }
+    }
+}
+
+/// Orders every staged change so the compositor never sees zero enabled
+/// monitors or two overlapping ones on the way: enables first, then
+/// modes, scales, transforms and positions, disables last.
+///
+/// A monitor enabled where another one still is comes up right of
+/// everything and moves into place once the way is clear. Moves into a
+/// spot that a monitor being disabled still covers wait for the disable.
+pub fn plan(staged: &[Staged]) -> Vec<Step> {
+    let mut park_x = staged
+        .iter()
+        .flat_map(|s| s.now.iter().chain(s.after.iter()))
+        .map(Rect::right)
+        .max()
+        .unwrap_or(0);
+    let covered_by_other = |name: &str, rect: &Rect| {
+        staged
+            .iter()
+            .any(|o| o.name != name && o.now.is_some_and(|n| n.overlaps(rect)))
+    };
+
+    let mut enables = Vec::new();
+    let mut settings = Vec::new();
+    // Moves with where the monitor is when the move is due.
+    let mut moves: Vec<(Rect, Rect, &str)> = Vec::new();
+    let mut disables = Vec::new();
+    for s in staged {
+        let Some(after) = s.after else {
+            if s.now.is_some() {
+                disables.push(Step::Disable {
+                    name: s.name.clone(),
+                });
+            }
+            continue;
+        };
+        match s.now {
+            None if covered_by_other(&s.name, &after) => {
+                let parked = Rect::new(park_x, 0, after.w, after.h);
+                park_x += after.w;
+                enables.push(Step::Enable {
+                    name: s.name.clone(),
+                    x: parked.x,
+                    y: parked.y,
+                });
+                moves.push((parked, after, &s.name));
+            }
+            None => enables.push(Step::Enable {
+                name: s.name.clone(),
+                x: after.x,
+                y: after.y,
+            }),
+            Some(now) if (now.x, now.y) != (after.x, after.y) => {
+                moves.push((now, after, &s.name));
+            }
+            Some(_) => {}
+        }
+        if let Some(mode) = s.mode {
+            settings.push((
+                0,
+                Step::Mode {
+                    name: s.name.clone(),
+                    mode,
+                },
+            ));
+        }
+        if let Some(scale) = s.scale {
+            settings.push((
+                1,
+                Step::Scale {
+                    name: s.name.clone(),
+                    scale,
+                },
+            ));
+        }
+        if let Some(transform) = s.transform {
+            settings.push((
+                2,
+                Step::Transform {
+                    name: s.name.clone(),
+                    transform,
+                },
+            ));
+        }
+    }
+    settings.sort_by_key(|(rank, _)| *rank);
+
+    let leaving: Vec<Rect> = staged
+        .iter()
+        .filter(|s| s.after.is_none())
+        .filter_map(|s| s.now)
+        .collect();
+    let (deferred, moves): (Vec<_>, Vec<_>) = moves
+        .into_iter()
+        .partition(|(_, after, _)| leaving.iter().any(|r| r.overlaps(after)));
+
+    let mut steps = enables;
+    steps.extend(settings.into_iter().map(|(_, step)| step));
+    steps.extend(order_moves(moves));
+    steps.extend(disables);
+    steps.extend(order_moves(deferred));
+    steps
+}
+
+/// Puts a move after the moves of monitors standing where it goes. Moves
+/// that wait on each other, like two monitors swapping places, keep their
+/// order.
+fn order_moves(mut moves: Vec<(Rect, Rect, &str)>) -> Vec<Step> {
+    let mut steps = Vec::new();
+    while !moves.is_empty() {
+        let ready = (0..moves.len())
+            .find(|&i| {
+                let (_, after, _) = moves[i];
+                moves
+                    .iter()
+                    .enumerate()
+                    .all(|(j, (now, _, _))| i == j || !now.overlaps(&after))
+            })
+            .unwrap_or(0);
+        let (_, after, name) = moves.remove(ready);
+        steps.push(Step::Position {
+            name: name.to_string(),
+            x: after.x,
+            y: after.y,
+        });
+    }
+    steps
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn staged(name: &str, now: Option<Rect>, after: Option<Rect>) -> Staged {
+        Staged {
+            name: name.to_string(),
+            now,
+            after,
+            mode: None,
+            scale: None,
+            transform: None,
+        }
+    }
+
+    fn at(x: i32, y: i32) -> Option<Rect> {
+        Some(Rect::new(x, y, 1920, 1080))
+    }
+
+    fn lines(steps: &[Step]) -> Vec<String> {
+        steps.iter().map(Step::to_string).collect()
+    }
+
+    #[test]
+    fn test_enables_first_settings_then_moves_disables_last() {
+        let mut dp1 = staged("DP-1", at(0, 0), at(0, 0));
+        dp1.scale = Some(1.5);
+        dp1.mode = Some(ModeSpec {
+            width: 2560,
+            height: 1440,
+            refresh_rate: 144,
+        });
+        let steps = plan(&[
+            staged("DP-3", at(3840, 0), None),
+            dp1,
+            staged("DP-2", None, at(1920, 0)),
+        ]);
+        assert_eq!(
+            lines(&steps),
+            [
+                "enable DP-2 at 1920,0",
+                "switch DP-1 to 2560x1440@144Hz",
+                "scale DP-1 to 1.50",
+                "disable DP-3",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_enable_into_the_spot_of_a_disabled_monitor_waits() {
+        // A takes B's place while B is switched off.
+        let steps = plan(&[
+            staged("A", None, at(1920, 0)),
+            staged("B", at(1920, 0), None),
+            staged("C", at(0, 0), at(0, 0)),
+        ]);
+        assert_eq!(
+            lines(&steps),
+            ["enable A at 3840,0", "disable B", "move A to 1920,0"]
+        );
+    }
+
+    #[test]
+    fn test_moves_wait_for_the_monitor_in_their_way() {
+        // B moves right out of the way, then A moves where B was.
+        let steps = plan(&[
+            staged("A", at(0, 0), at(1920, 0)),
+            staged("B", at(1920, 0), at(3840, 0)),
+        ]);
+        assert_eq!(lines(&steps), ["move B to 3840,0", "move A to 1920,0"]);
+
+        // A swap has no clean order, so both still go out.
+        let steps = plan(&[
+            staged("A", at(0, 0), at(1920, 0)),
+            staged("B", at(1920, 0), at(0, 0)),
+        ]);
+        assert_eq!(steps.len(), 2);
+    }
+}
@@ -0,0 +1,171 @@
+use std::fmt;
+
+use wlx_monitors::WlMonitorMode;
+
+/// Ratios monitors are sold by. None is within [`NAMED_TOLERANCE`] of
+/// another.
+const NAMED: [(i32, i32); 8] = [
+    (16, 9),
+    (16, 10),
+    (4, 3),
+    (5, 4),
+    (21, 9),
+    (32, 9),
+    (3, 2),
+    (1, 1),
+];
+
+/// How far off a named ratio a resolution may be and still go by it,
+/// enough for 1366x768 to be 16:9 and 3440x1440 to be 21:9.
+const NAMED_TOLERANCE: f64 = 0.03;
+
+/// A width:height ratio, e.g. 16:9.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Aspect {
+    pub width: i32,
+    pub height: i32,
+}
+
+impl Aspect {
+    /// The ratio of a resolution: the reduced fraction when it is one of
+    /// [`NAMED`], else the named ratio it is close to, else the reduced
+    /// fraction as is.
+    pub fn of(width: i32, height: i32) -> Self {
+        let divisor = gcd(width, height).max(1);
+        let reduced = Aspect {
+            width: width / divisor,
+            height: height / divisor,
+        };
+        if height <= 0 || NAMED.contains(&(reduced.width, reduced.height)) {
+            return reduced;
+        }
+        let ratio = width as f64 / height as f64;
+        NAMED
+            .iter()
+            .find(|&&(w, h)| {
+                let named = w as f64 / h as f64;
+                (ratio / named - 1.0).abs() <= NAMED_TOLERANCE
+            })
+            .map(|&(width, height)| Aspect { width, height })
+            .unwrap_or(reduced)
+    }
+}
+
+/// e.g. "16:9"
+impl fmt::Display for Aspect {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.width, self.height)
+    }
+}
+
+/// What the Modes panel says about an advertised mode.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ModeTags {
+    pub aspect: Aspect,
+    /// Same aspect as the preferred mode.
+    pub native: bool,
+    pub interlaced: bool,
+}
+
+/// Tags every mode in `modes`. The native aspect is the preferred mode's,
+/// or the current one's when none is preferred.
+///
+/// The output management protocol does not flag interlaced modes, and
+/// rounds their refresh rate to that of the progressive mode they
+/// double. The kernel lists the progressive one first, so a repeat of an
+/// earlier resolution and rate is taken as interlaced.
+pub fn tags(modes: &[WlMonitorMode]) -> Vec<ModeTags> {
+    let reference = modes
+        .iter()
+        .find(|m| m.preferred)
+        .or_else(|| modes.iter().find(|m| m.is_current))
+        .or(modes.first());
+    let native = reference
+        .map(|m| Aspect::of(m.resolution.width, m.resolution.height));
+    modes
+        .iter()
+        .enumerate()
+        .map(|(i, mode)| {
+            let aspect =
+                Aspect::of(mode.resolution.width, mode.resolution.height);
+            let interlaced = modes[..i].iter().any(|earlier| {
+                earlier.resolution.width == mode.resolution.width
+                    && earlier.resolution.height == mode.resolution.height
+                    && earlier.refresh_rate == mode.refresh_rate
+            });
+            ModeTags {
+                aspect,
+                native: native.is_none_or(|n| n == aspect),
+                interlaced,
+            }
+        })
+        .collect()
+}
+
+fn gcd(a: i32, b: i32) -> i32 {
+    if b == 0 { a.abs() } else { gcd(b, a % b) }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::mode;
+
+    #[test]
+    fn test_aspect_of_common_resolutions() {
+        let table = [
+            (1920, 1080, "16:9"),
+            (1366, 768, "16:9"),
+            (1920, 1200, "16:10"),
+            (1024, 768, "4:3"),
+            (1280, 1024, "5:4"),
+            (2560, 1080, "21:9"),
+            (3440, 1440, "21:9"),
+            (5120, 1440, "32:9"),
+            (1280, 768, "5:3"),
+            (720, 480, "3:2"),
+            (0, 0, "0:0"),
+        ];
+        for (width, height, want) in table {
+            let got = Aspect::of(width, height).to_string();
+            assert_eq!(got, want, "{width}x{height}");
+        }
+    }
+
+    #[test]
+    fn test_tags_flag_other_aspects_and_interlaced_repeats() {
+        let mut preferred = mode(1920, 1080, 60);
+        preferred.preferred = true;
+        let modes = [
+            preferred,
+            mode(1920, 1080, 50),
+            mode(1920, 1080, 60),
+            mode(1280, 720, 60),
+            mode(1024, 768, 60),
+            mode(720, 576, 50),
+        ];
+        let got: Vec<(String, bool, bool)> = tags(&modes)
+            .into_iter()
+            .map(|t| (t.aspect.to_string(), t.native, t.interlaced))
+            .collect();
+        let want = [
+            ("16:9", true, false),
+            ("16:9", true, false),
+            ("16:9", true, true),
+            ("16:9", true, false),
+            ("4:3", false, false),
+            ("5:4", false, false),
+        ];
+        let want: Vec<(String, bool, bool)> =
+            want.iter().map(|&(a, n, i)| (a.to_string(), n, i)).collect();
+        assert_eq!(got, want);
+
+        // Without a preferred mode the current one sets the aspect.
+        let mut current = mode(1024, 768, 60);
+        current.is_current = true;
+        let tagged = tags(&[mode(1920, 1080, 60), current]);
+        assert!(!tagged[0].native);
+        assert!(tagged[1].native);
+        assert_eq!(tags(&[]), []);
+    }
+}
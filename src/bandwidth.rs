@@ -0,0 +1,144 @@
+use crate::edid::LinkHints;
+
+/// Horizontal blanking of CVT reduced blanking v2, in pixels.
+const RB_HBLANK: u64 = 80;
+
+/// Shortest vertical blanking of CVT reduced blanking v2, in microseconds.
+const RB_MIN_VBLANK_US: f64 = 460.0;
+
+/// TMDS clock assumed for HDMI when the EDID says nothing: HDMI 2.0, the
+/// fastest a TMDS link goes, so the guess only ever errs towards silence.
+const HDMI_DEFAULT_TMDS_KHZ: u64 = 600_000;
+
+/// Pixel clock DisplayPort 2.1 (UHBR20, 77.58 Gbit/s of payload) carries
+/// at 24 bits per pixel without DSC. Nothing tells which version a link
+/// runs, so like HDMI the guess is the fastest one and only errs towards
+/// silence.
+const DP_MAX_PIXEL_KHZ: u64 = 3_232_000;
+
+/// The link a connector most likely runs over.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Link {
+    /// TMDS HDMI, up to this clock.
+    Hdmi { max_tmds_khz: u64 },
+    /// DisplayPort, up to UHBR20 without DSC.
+    DisplayPort,
+    /// Anything the estimate has nothing to say about, including links
+    /// with DSC or HDMI FRL.
+    Unknown,
+}
+
+impl Link {
+    /// Judges the link by the connector name, narrowed down by what the
+    /// EDID says when it could be read.
+    pub fn infer(connector: &str, hints: Option<LinkHints>) -> Self {
+        let hints = hints.unwrap_or_default();
+        if hints.dsc || hints.frl {
+            return Link::Unknown;
+        }
+        if connector.starts_with("HDMI-A-") {
+            let max_tmds_khz = hints
+                .max_tmds_mhz
+                .map_or(HDMI_DEFAULT_TMDS_KHZ, |mhz| u64::from(mhz) * 1000);
+            Link::Hdmi { max_tmds_khz }
+        } else if connector.starts_with("DP-") {
+            Link::DisplayPort
+        } else {
+            Link::Unknown
+        }
+    }
+
+    /// Whether `width`x`height`@`refresh_rate` likely needs more than the
+    /// link carries at 8 bits per colour.
+    pub fn exceeded_by(
+        self,
+        width: i32,
+        height: i32,
+        refresh_rate: i32,
+    ) -> bool {
+        let Some(clock) = pixel_clock_khz(width, height, refresh_rate) else {
+            return false;
+        };
+        match self {
+            Link::Hdmi { max_tmds_khz } => clock > max_tmds_khz,
+            Link::DisplayPort => clock > DP_MAX_PIXEL_KHZ,
+            Link::Unknown => false,
+        }
+    }
+}
+
+/// Pixel clock of a mode with CVT reduced blanking v2, the least blanking
+/// a monitor is likely to ask for. `None` for nonsensical modes.
+fn pixel_clock_khz(
+    width: i32,
+    height: i32,
+    refresh_rate: i32,
+) -> Option<u64> {
+    if width <= 0 || height <= 0 || refresh_rate <= 0 {
+        return None;
+    }
+    let hz = f64::from(refresh_rate);
+    let active = 1.0 - RB_MIN_VBLANK_US * hz / 1_000_000.0;
+    if active <= 0.0 {
+        return None;
+    }
+    let vtotal = (f64::from(height) / active).ceil() as u64;
+    let htotal = width as u64 + RB_HBLANK;
+    Some(htotal * vtotal * refresh_rate as u64 / 1000)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HDMI_14: Link = Link::Hdmi {
+        max_tmds_khz: 340_000,
+    };
+    const HDMI_20: Link = Link::Hdmi {
+        max_tmds_khz: 600_000,
+    };
+
+    #[test]
+    fn test_known_good_and_bad_combinations() {
+        let table = [
+            (HDMI_14, (1920, 1080, 60), false),
+            (HDMI_14, (1920, 1080, 144), false),
+            (HDMI_14, (2560, 1440, 60), false),
+            (HDMI_14, (3840, 2160, 30), false),
+            (HDMI_14, (3840, 2160, 60), true),
+            (HDMI_20, (3840, 2160, 60), false),
+            (HDMI_20, (2560, 1440, 144), false),
+            (HDMI_20, (3840, 2160, 120), true),
+            (HDMI_20, (3840, 2160, 144), true),
+            (Link::DisplayPort, (3840, 2160, 144), false),
+            (Link::DisplayPort, (5120, 1440, 240), false),
+            (Link::DisplayPort, (7680, 4320, 60), false),
+            (Link::DisplayPort, (7680, 4320, 120), true),
+            (Link::Unknown, (7680, 4320, 120), false),
+        ];
+        for (link, (w, h, hz), exceeds) in table {
+            assert_eq!(
+                link.exceeded_by(w, h, hz),
+                exceeds,
+                "{w}x{h}@{hz} over {link:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_infer_link_from_connector_and_edid() {
+        assert_eq!(Link::infer("HDMI-A-1", None), HDMI_20);
+        let hdmi_14 = LinkHints {
+            max_tmds_mhz: Some(340),
+            ..LinkHints::default()
+        };
+        assert_eq!(Link::infer("HDMI-A-1", Some(hdmi_14)), HDMI_14);
+        assert_eq!(Link::infer("DP-3", None), Link::DisplayPort);
+        let dsc = LinkHints {
+            dsc: true,
+            ..LinkHints::default()
+        };
+        assert_eq!(Link::infer("DP-3", Some(dsc)), Link::Unknown);
+        assert_eq!(Link::infer("eDP-1", None), Link::Unknown);
+    }
+}
@@ -0,0 +1,84 @@
+//! Which build of xwlm this is, as `build.rs` found it out. The git parts
+//! are empty when it was built outside a git checkout.
+
+use serde::Serialize;
+
+#[derive(Debug, Serialize)]
+pub struct BuildInfo {
+    pub version: &'static str,
+    pub describe: &'static str,
+    pub commit: &'static str,
+    pub date: &'static str,
+}
+
+pub const BUILD: BuildInfo = BuildInfo {
+    version: env!("CARGO_PKG_VERSION"),
+    describe: env!("XWLM_GIT_DESCRIBE"),
+    commit: env!("XWLM_GIT_COMMIT"),
+    date: env!("XWLM_BUILD_DATE"),
+};
+
+impl BuildInfo {
+    /// `xwlm 0.2.0 (v0.2.0-3-gabc1234, built 2026-10-16)`, leaving out
+    /// what is unknown.
+    pub fn summary(&self) -> String {
+        let mut extra = Vec::new();
+        if !self.describe.is_empty() {
+            extra.push(self.describe.to_string());
+        }
+        if !self.date.is_empty() {
+            extra.push(format!("built {}", self.date));
+        }
+        if extra.is_empty() {
+            format!("xwlm {}", self.version)
+        } else {
+            format!("xwlm {} ({})", self.version, extra.join(", "))
+        }
+    }
+
+    /// `xwlm 0.2.0 (abc1234)`, for the header of written files. Without
+    /// the date, rebuilding the same commit writes the same header.
+    pub fn header(&self) -> String {
+        match self.commit.get(..7) {
+            Some(commit) => format!("xwlm {} ({commit})", self.version),
+            None => format!("xwlm {}", self.version),
+        }
+    }
+
+    pub fn json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_summary_leaves_out_what_is_unknown() {
+        let bare = BuildInfo {
+            version: "0.2.0",
+            describe: "",
+            commit: "",
+            date: "",
+        };
+        assert_eq!(bare.summary(), "xwlm 0.2.0");
+        assert_eq!(bare.header(), "xwlm 0.2.0");
+        let json: serde_json::Value =
+            serde_json::from_str(&bare.json()).unwrap();
+        assert_eq!(json["version"], "0.2.0");
+        assert_eq!(json["commit"], "");
+
+        let full = BuildInfo {
+            describe: "v0.2.0-3-gabc1234",
+            commit: "abc1234def5678",
+            date: "2026-10-16",
+            ..bare
+        };
+        assert_eq!(full.header(), "xwlm 0.2.0 (abc1234)");
+        assert_eq!(
+            full.summary(),
+            "xwlm 0.2.0 (v0.2.0-3-gabc1234, built 2026-10-16)"
+        );
+    }
+}
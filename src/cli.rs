@@ -0,0 +1,1078 @@
+use std::{
+    iter,
+    path::PathBuf,
+    process::Command as Process,
+    sync::mpsc::{Receiver, RecvTimeoutError},
+    time::{Duration, Instant},
+};
+
+use thiserror::Error;
+use wlx_monitors::WlMonitorEvent;
+
+use crate::{
+    compositor::{
+        self, Compositor,
+        check::{ConfigStatus, check_monitor_config},
+        extraction::{main_config_path, stray_monitor_rules},
+        format::{
+            apply_command, reload, run_command, save_monitor_config,
+            transform_from_sway,
+        },
+        modeline::parse_mode,
+    },
+    control::{self, Request},
+    history, layout,
+    remote::{MonitorEdit, RemoteAction, RemoteOptions},
+    service::{self, ServiceError},
+    state::{App, Panel},
+    xwlm_config::{self, Config},
+};
+
+pub const INITIAL_STATE_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// How long `recover` waits without a monitor event before it takes the
+/// changes as done.
+const RECOVER_SETTLE_TIME: Duration = Duration::from_secs(1);
+
+const DEFAULT_DUMP_PATH: &str = "xwlm-dump.toml";
+
+#[derive(Error, Debug, PartialEq)]
+pub enum CliError {
+    #[error("unknown command: {0}")]
+    UnknownCommand(String),
+
+    #[error("unknown option for `{command}`: {option}")]
+    UnknownOption {
+        command: &'static str,
+        option: String,
+    },
+
+    #[error("`set` needs at least one option (e.g. --distribute-workspaces)")]
+    NothingToSet,
+
+    #[error("timed out waiting for monitors from the compositor")]
+    NoInitialState,
+
+    #[error(
+        "{0} does not offer wlr-output-management \
+         (zwlr_output_manager_v1), which xwlm needs to see and change \
+         monitors"
+    )]
+    NoOutputManagement(&'static str),
+
+    #[error("`{0}` needs a value")]
+    MissingValue(&'static str),
+
+    #[error(
+        "unknown panel: {0} (monitor, mode, workspace, scale, transform or \
+         options)"
+    )]
+    UnknownPanel(String),
+
+    #[cfg(not(feature = "replay"))]
+    #[error(
+        "this xwlm was built without `replay`; rebuild it with \
+         `--features replay`"
+    )]
+    NoReplay,
+
+    #[error("`{0}` has no dry run")]
+    NoDryRun(&'static str),
+
+    #[error("invalid value for `{option}`: {value}")]
+    InvalidValue {
+        option: &'static str,
+        value: String,
+    },
+
+    #[error("{0}")]
+    Failed(String),
+}
+
+#[derive(Debug, PartialEq)]
+pub enum Command {
+    Tui(TuiOptions),
+    Plain,
+    Set(SetOptions),
+    Apply,
+    Recover,
+    Doctor(DoctorOptions),
+    Watch(WatchOptions),
+    DebugDump(DumpOptions),
+    Replay(PathBuf),
+    InstallService(ServiceOptions),
+    UninstallService(ServiceOptions),
+    Remote(RemoteOptions),
+    /// `--version`, with `--json` for scripts.
+    Version { json: bool },
+}
+
+/// Where the TUI starts, applied once the monitors arrive.
+#[derive(Debug, Default, PartialEq)]
+pub struct TuiOptions {
+    pub monitor: Option<String>,
+    pub panel: Option<Panel>,
+    /// `--hold-on-error on|off`, over `hold_on_error` in the config.
+    pub hold_on_error: Option<bool>,
+    /// `--observe`: watch the monitors without changing anything.
+    pub observe: bool,
+    /// `--tour`: show the first-launch tour again.
+    pub tour: bool,
+}
+
+#[derive(Debug, Default, PartialEq)]
+pub struct SetOptions {
+    pub distribute_workspaces: bool,
+}
+
+#[derive(Debug, Default, PartialEq)]
+pub struct DoctorOptions {
+    /// Checks the live layout for directions focus cannot move in.
+    pub layout: bool,
+}
+
+#[derive(Debug, Default, PartialEq)]
+pub struct WatchOptions {
+    pub summary: bool,
+}
+
+#[derive(Debug, Default, PartialEq)]
+pub struct DumpOptions {
+    pub redact: bool,
+    pub output: Option<PathBuf>,
+}
+
+#[derive(Debug, Default, PartialEq)]
+pub struct ServiceOptions {
+    pub dry_run: bool,
+    pub autostart: bool,
+}
+
+/// Like [`parse`], after a leading `--dry-run`, which asks for every
+/// action and write to be logged instead of performed.
+pub fn parse_with_dry_run(
+    args: impl IntoIterator<Item = String>,
+) -> Result<(Command, bool), CliError> {
+    let mut args = args.into_iter().peekable();
+    let dry_run = args.next_if(|arg| arg == "--dry-run").is_some();
+    let mut command = parse(args)?;
+    if dry_run {
+        match &mut command {
+            Command::Apply => return Err(CliError::NoDryRun("apply")),
+            Command::InstallService(opts)
+            | Command::UninstallService(opts) => opts.dry_run = true,
+            Command::Remote(opts) => opts.dry_run = true,
+            _ => {}
+        }
+    }
+    Ok((command, dry_run))
+}
+
+pub fn parse(
+    args: impl IntoIterator<Item = String>,
+) -> Result<Command, CliError> {
+    let mut args = args.into_iter();
+    let Some(command) = args.next() else {
+        return Ok(Command::Tui(TuiOptions::default()));
+    };
+
+    match command.as_str() {
+        "--monitor" | "--panel" | "--hold-on-error" | "--observe"
+        | "--tour" => parse_tui(iter::once(command).chain(args)),
+        "--remote" => parse_remote(args),
+        "--version" | "-V" => match args.next().as_deref() {
+            None => Ok(Command::Version { json: false }),
+            Some("--json") => Ok(Command::Version { json: true }),
+            Some(option) => Err(CliError::UnknownOption {
+                command: "--version",
+                option: option.to_string(),
+            }),
+        },
+        "--plain" | "repl" => match args.next() {
+            Some(option) => Err(CliError::UnknownOption {
+                command: "repl",
+                option,
+            }),
+            None => Ok(Command::Plain),
+        },
+        "set" => {
+            let mut opts = SetOptions::default();
+            for arg in args {
+                match arg.as_str() {
+                    "--distribute-workspaces" => {
+                        opts.distribute_workspaces = true
+                    }
+                    _ => {
+                        return Err(CliError::UnknownOption {
+                            command: "set",
+                            option: arg,
+                        });
+                    }
+                }
+            }
+            if opts == SetOptions::default() {
+                return Err(CliError::NothingToSet);
+            }
+            Ok(Command::Set(opts))
+        }
+        "apply" => {
+            // Applying is always one-shot; `--once` is accepted so service
+            // files read the same as they will once a daemon mode exists.
+            for arg in args {
+                if arg != "--once" {
+                    return Err(CliError::UnknownOption {
+                        command: "apply",
+                        option: arg,
+                    });
+                }
+            }
+            Ok(Command::Apply)
+        }
+        "recover" => match args.next() {
+            Some(option) => Err(CliError::UnknownOption {
+                command: "recover",
+                option,
+            }),
+            None => Ok(Command::Recover),
+        },
+        "doctor" => {
+            let mut opts = DoctorOptions::default();
+            for arg in args {
+                match arg.as_str() {
+                    "--layout" => opts.layout = true,
+                    _ => {
+                        return Err(CliError::UnknownOption {
+                            command: "doctor",
+                            option: arg,
+                        });
+                    }
+                }
+            }
+            Ok(Command::Doctor(opts))
+        }
+        "watch" => {
+            let mut opts = WatchOptions::default();
+            for arg in args {
+                match arg.as_str() {
+                    "--summary" => opts.summary = true,
+                    _ => {
+                        return Err(CliError::UnknownOption {
+                            command: "watch",
+                            option: arg,
+                        });
+                    }
+                }
+            }
+            Ok(Command::Watch(opts))
+        }
+        "debug-dump" => {
+            let mut opts = DumpOptions::default();
+            for arg in args {
+                match arg.as_str() {
+                    "--redact" => opts.redact = true,
+                    _ if !arg.starts_with('-') && opts.output.is_none() => {
+                        opts.output = Some(PathBuf::from(arg))
+                    }
+                    _ => {
+                        return Err(CliError::UnknownOption {
+                            command: "debug-dump",
+                            option: arg,
+                        });
+                    }
+                }
+            }
+            Ok(Command::DebugDump(opts))
+        }
+        "replay" => match (args.next(), args.next()) {
+            (Some(path), None) => Ok(Command::Replay(PathBuf::from(path))),
+            (None, _) => Err(CliError::MissingValue("replay")),
+            (Some(_), Some(option)) => Err(CliError::UnknownOption {
+                command: "replay",
+                option,
+            }),
+        },
+        "install-service" | "uninstall-service" => {
+            let install = command == "install-service";
+            let name = if install {
+                "install-service"
+            } else {
+                "uninstall-service"
+            };
+            let mut opts = ServiceOptions::default();
+            for arg in args {
+                match arg.as_str() {
+                    "--dry-run" => opts.dry_run = true,
+                    "--autostart" if install => opts.autostart = true,
+                    _ => {
+                        return Err(CliError::UnknownOption {
+                            command: name,
+                            option: arg,
+                        });
+                    }
+                }
+            }
+            Ok(if install {
+                Command::InstallService(opts)
+            } else {
+                Command::UninstallService(opts)
+            })
+        }
+        _ => Err(CliError::UnknownCommand(command)),
+    }
+}
+
+fn parse_tui(
+    mut args: impl Iterator<Item = String>,
+) -> Result<Command, CliError> {
+    let mut opts = TuiOptions::default();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--monitor" => {
+                let name =
+                    args.next().ok_or(CliError::MissingValue("--monitor"))?;
+                opts.monitor = Some(name);
+            }
+            "--panel" => {
+                let name =
+                    args.next().ok_or(CliError::MissingValue("--panel"))?;
+                let panel = Panel::from_name(&name)
+                    .ok_or(CliError::UnknownPanel(name))?;
+                opts.panel = Some(panel);
+            }
+            "--observe" => opts.observe = true,
+            "--tour" => opts.tour = true,
+            "--hold-on-error" => {
+                let value = args
+                    .next()
+                    .ok_or(CliError::MissingValue("--hold-on-error"))?;
+                opts.hold_on_error = match value.as_str() {
+                    "on" => Some(true),
+                    "off" => Some(false),
+                    _ => {
+                        return Err(CliError::InvalidValue {
+                            option: "--hold-on-error",
+                            value,
+                        });
+                    }
+                };
+            }
+            _ => {
+                return Err(CliError::UnknownOption {
+                    command: "xwlm",
+                    option: arg,
+                });
+            }
+        }
+    }
+    Ok(Command::Tui(opts))
+}
+
+/// `--remote HOST [--compositor NAME] [--config PATH] [--reload]` and
+/// then `show` (the default), `set --output NAME ...` or `apply-file PATH`.
+fn parse_remote(
+    mut args: impl Iterator<Item = String>,
+) -> Result<Command, CliError> {
+    let host = args.next().ok_or(CliError::MissingValue("--remote"))?;
+    // ssh would take it for an option.
+    if host.starts_with('-') {
+        return Err(CliError::InvalidValue {
+            option: "--remote",
+            value: host,
+        });
+    }
+    let mut opts = RemoteOptions {
+        host,
+        compositor: None,
+        config: None,
+        reload: false,
+        dry_run: false,
+        action: RemoteAction::Show,
+    };
+    let mut action = None;
+    let mut edit = MonitorEdit::default();
+    let value = |args: &mut dyn Iterator<Item = String>, option| {
+        args.next().ok_or(CliError::MissingValue(option))
+    };
+    let invalid = |option, value: String| CliError::InvalidValue {
+        option,
+        value,
+    };
+    while let Some(arg) = args.next() {
+        match (arg.as_str(), action) {
+            ("--compositor", _) => {
+                let name = value(&mut args, "--compositor")?;
+                let comp = Compositor::ALL
+                    .into_iter()
+                    .filter(|&c| c != Compositor::Unknown)
+                    .find(|c| c.label().eq_ignore_ascii_case(&name))
+                    .ok_or_else(|| invalid("--compositor", name))?;
+                opts.compositor = Some(comp);
+            }
+            ("--config", _) => {
+                opts.config = Some(value(&mut args, "--config")?);
+            }
+            ("--reload", _) => opts.reload = true,
+            ("show", None) => action = Some("show"),
+            ("set", None) => action = Some("set"),
+            ("apply-file", None) => {
+                let path = value(&mut args, "apply-file")?;
+                opts.action = RemoteAction::ApplyFile(PathBuf::from(path));
+                action = Some("apply-file");
+            }
+            ("--output", Some("set")) => {
+                edit.output = value(&mut args, "--output")?;
+            }
+            ("--mode", Some("set")) => {
+                let mode = value(&mut args, "--mode")?;
+                edit.mode = Some(
+                    parse_mode(&mode).ok_or_else(|| invalid("--mode", mode))?,
+                );
+            }
+            ("--position", Some("set")) => {
+                let pos = value(&mut args, "--position")?;
+                let parsed = pos.split_once(',').and_then(|(x, y)| {
+                    Some((x.trim().parse().ok()?, y.trim().parse().ok()?))
+                });
+                edit.position =
+                    Some(parsed.ok_or_else(|| invalid("--position", pos))?);
+            }
+            ("--scale", Some("set")) => {
+                let scale = value(&mut args, "--scale")?;
+                let parsed = scale.parse::<f64>().ok().filter(|s| *s > 0.0);
+                edit.scale =
+                    Some(parsed.ok_or_else(|| invalid("--scale", scale))?);
+            }
+            ("--transform", Some("set")) => {
+                let name = value(&mut args, "--transform")?;
+                edit.transform = Some(
+                    transform_from_sway(&name)
+                        .ok_or_else(|| invalid("--transform", name))?,
+                );
+            }
+            ("--enable", Some("set")) => edit.enabled = Some(true),
+            ("--disable", Some("set")) => edit.enabled = Some(false),
+            _ => {
+                return Err(CliError::UnknownOption {
+                    command: "--remote",
+                    option: arg,
+                });
+            }
+        }
+    }
+    if action == Some("set") {
+        if edit.output.is_empty() {
+            return Err(CliError::MissingValue("--output"));
+        }
+        let unchanged = MonitorEdit {
+            output: edit.output.clone(),
+            ..MonitorEdit::default()
+        };
+        if edit == unchanged {
+            return Err(CliError::Failed(
+                "`--remote ... set` needs something to change (e.g. --scale)"
+                    .to_string(),
+            ));
+        }
+        opts.action = RemoteAction::Set(edit);
+    }
+    Ok(Command::Remote(opts))
+}
+
+/// Re-applies the saved monitor config: River, Niri and labwc run the
+/// generated script, Hyprland and Sway reload their config which sources
+/// it, and Wayfire and KWin pick up their config by themselves. With
+/// `canonical_layout` the config is regenerated from layout.toml first,
+/// or on KWin sent with kscreen-doctor.
+pub fn run_apply(config: &Config) -> Result<(), CliError> {
+    let comp = compositor::detect();
+    if config.canonical_layout
+        && let Some(mut layout) = layout::load_layout()
+            .map_err(|e| CliError::Failed(e.to_string()))?
+    {
+        let unmanaged = &config.unmanaged_monitors;
+        let saved = if comp.capabilities().applies_by_command {
+            layout.monitors.retain(|m| !unmanaged.contains(&m.name));
+            let modes = &config.custom_modes;
+            let command = apply_command(comp, &layout, modes);
+            run_command(&command.unwrap_or_default())
+        } else {
+            save_monitor_config(
+                comp,
+                &config.monitor_config_path,
+                &layout,
+                &config.custom_modes,
+                unmanaged,
+                config.backup_count,
+            )
+        };
+        saved.map_err(|e| CliError::Failed(e.to_string()))?;
+    }
+    let caps = comp.capabilities();
+    if caps.reload_command.is_some() {
+        reload(comp, &config.monitor_config_path)
+            .map_err(|e| CliError::Failed(e.to_string()))?;
+        return Ok(());
+    }
+    match caps.config_style {
+        Some(style) if style.is_script() => {
+            let status = Process::new("sh")
+                .arg(&config.monitor_config_path)
+                .status()
+                .map_err(|e| CliError::Failed(e.to_string()))?;
+            if status.success() {
+                Ok(())
+            } else {
+                Err(CliError::Failed(format!(
+                    "{} exited with {status}",
+                    config.monitor_config_path.display()
+                )))
+            }
+        }
+        Some(style) if style.is_shared() => Ok(()),
+        None if caps.applies_by_command => Ok(()),
+        _ => Err(CliError::Failed(
+            "no supported compositor detected".to_string(),
+        )),
+    }
+}
+
+/// Prints what xwlm reads from the monitor config, or fails with the
+/// problem the TUI would warn about at startup.
+pub fn run_doctor() -> Result<(), CliError> {
+    let comp = compositor::detect();
+    println!("Compositor: {}", comp.label());
+    let config = xwlm_config::load_config()
+        .map_err(|e| CliError::Failed(e.to_string()))?;
+    let path = &config.monitor_config_path;
+    println!("Monitor config: {}", path.display());
+
+    let status = check_monitor_config(comp, path);
+    if let Some(problem) = status.problem(comp, path) {
+        return Err(CliError::Failed(problem));
+    }
+    if let ConfigStatus::Ok {
+        workspaces,
+        positions,
+    } = status
+    {
+        println!("Workspace rules: {workspaces}");
+        for (name, pos) in positions {
+            println!("Saved position: {name} at {},{}", pos.x, pos.y);
+        }
+    }
+    if let Some(main) = main_config_path(comp) {
+        for rule in stray_monitor_rules(comp, &main, path) {
+            let first = rule.text.lines().next().unwrap_or_default();
+            println!(
+                "Stray monitor rule: {}:{}: {first}",
+                rule.path.display(),
+                rule.line
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Prints every direction focus cannot move in between the enabled
+/// monitors, with the position that lines each one up.
+pub fn run_layout_doctor(
+    app: &mut App,
+    wlx_events: &Receiver<WlMonitorEvent>,
+) -> Result<(), CliError> {
+    wait_for_monitors(app, wlx_events)?;
+    let gaps = app.focus_gaps();
+    if gaps.is_empty() {
+        println!("Focus reaches every neighbouring monitor");
+    }
+    for gap in gaps {
+        let (x, y) = gap.fix;
+        println!("{gap}; moving {} to {x},{y} lines it up", gap.from);
+    }
+    Ok(())
+}
+
+pub fn run_service(
+    install: bool,
+    opts: &ServiceOptions,
+) -> Result<(), ServiceError> {
+    let comp = compositor::detect();
+    let steps = if install {
+        service::plan_install(comp, opts.autostart)?
+    } else {
+        service::plan_uninstall(comp)?
+    };
+    if steps.is_empty() {
+        println!("Nothing to do");
+        return Ok(());
+    }
+    for step in &steps {
+        let prefix = if opts.dry_run { "would " } else { "" };
+        println!("{prefix}{}", step.describe());
+    }
+    if opts.dry_run {
+        return Ok(());
+    }
+    service::execute(&steps)
+}
+
+pub fn run_set(
+    app: &mut App,
+    wlx_events: &Receiver<WlMonitorEvent>,
+    opts: &SetOptions,
+) -> Result<(), CliError> {
+    wait_for_monitors(app, wlx_events)?;
+
+    if opts.distribute_workspaces {
+        app.distribute_workspaces();
+    }
+
+    if let Some(err) = app.error_message.take() {
+        return Err(CliError::Failed(err));
+    }
+    if let Some(status) = app.status_message.take() {
+        println!("{status}");
+    }
+    Ok(())
+}
+
+/// Hands `set` to a running TUI through its control socket instead of
+/// opening a second Wayland connection next to it. `None` when no TUI
+/// listens.
+pub fn proxy_set(opts: &SetOptions) -> Option<Result<(), CliError>> {
+    let path = control::socket_path()?;
+    if !opts.distribute_workspaces {
+        return None;
+    }
+    let answer = control::request(&path, &Request::DistributeWorkspaces)?;
+    Some(match answer {
+        Ok(Ok(_)) => {
+            println!("Done by the running xwlm");
+            Ok(())
+        }
+        Ok(Err(e)) => Err(CliError::Failed(e)),
+        Err(e) => Err(CliError::Failed(e.to_string())),
+    })
+}
+
+/// Applies the recovery config live. Once the compositor has reported
+/// the changes back they are saved like any other, so the next reload
+/// does not undo them.
+pub fn run_recover(
+    app: &mut App,
+    wlx_events: &Receiver<WlMonitorEvent>,
+) -> Result<(), CliError> {
+    wait_for_monitors(app, wlx_events)?;
+    app.recover().map_err(|e| CliError::Failed(e.to_string()))?;
+    if let Some(err) = app.error_message.take() {
+        return Err(CliError::Failed(err));
+    }
+    if let Some(status) = app.status_message.take() {
+        println!("{status}");
+    }
+    while let Ok(event) = wlx_events.recv_timeout(RECOVER_SETTLE_TIME) {
+        app.handle_event(event);
+    }
+    app.save_config();
+    app.finish_saves();
+    if let Some(err) = app.error_message.take() {
+        return Err(CliError::Failed(err));
+    }
+    Ok(())
+}
+
+/// Writes a debug dump to the given file, `xwlm-dump.toml` in the
+/// current directory by default.
+pub fn run_debug_dump(
+    app: &mut App,
+    wlx_events: &Receiver<WlMonitorEvent>,
+    opts: &DumpOptions,
+) -> Result<(), CliError> {
+    wait_for_monitors(app, wlx_events)?;
+    let path = opts
+        .output
+        .clone()
+        .unwrap_or_else(|| PathBuf::from(DEFAULT_DUMP_PATH));
+    app.write_debug_dump(&path, opts.redact);
+    if let Some(err) = app.error_message.take() {
+        return Err(CliError::Failed(err));
+    }
+    if let Some(status) = app.status_message.take() {
+        println!("{status}");
+    }
+    Ok(())
+}
+
+/// Prints monitor events as they arrive until the compositor goes away.
+/// With `--summary`, prints each monitor's reconnect count instead. New
+/// monitors are placed as `on_new_monitor` says, except that ask leaves
+/// them alone since nobody is there to answer.
+pub fn run_watch(
+    app: &mut App,
+    wlx_events: &Receiver<WlMonitorEvent>,
+    opts: &WatchOptions,
+) -> Result<(), CliError> {
+    wait_for_monitors(app, wlx_events)?;
+
+    let mut seen = app.timeline.total();
+    loop {
+        // A save that waited for the compositor to settle goes out once
+        // it has, even when no further event arrives.
+        let received = match app.settle_time(Instant::now()) {
+            Some(wait) => wlx_events.recv_timeout(wait),
+            None => wlx_events.recv().map_err(RecvTimeoutError::from),
+        };
+        let event = match received {
+            Ok(event) => event,
+            Err(RecvTimeoutError::Timeout) => {
+                app.flush_saves(Instant::now());
+                if let Some(status) = app.status_message.take() {
+                    println!("{status}");
+                }
+                continue;
+            }
+            Err(RecvTimeoutError::Disconnected) => break,
+        };
+        app.handle_event(event);
+        app.new_monitor = None;
+        app.save_config();
+        if let Some(status) = app.status_message.take() {
+            println!("{status}");
+        }
+        let new = app.timeline.total() - seen;
+        seen = app.timeline.total();
+        if new == 0 {
+            continue;
+        }
+        if opts.summary {
+            let now = history::now();
+            for name in app.timeline.monitors() {
+                let summary = app.timeline.summary(name, now);
+                let summary = summary.as_deref().unwrap_or("stable");
+                println!("{name}: {summary}");
+            }
+        } else {
+            let skip = app.timeline.len().saturating_sub(new);
+            for entry in app.timeline.entries().skip(skip) {
+                println!("{}", entry.line());
+            }
+        }
+        if let Some(warning) = app.error_message.take() {
+            eprintln!("Warning: {warning}");
+        }
+    }
+    Ok(())
+}
+
+pub fn wait_for_monitors(
+    app: &mut App,
+    wlx_events: &Receiver<WlMonitorEvent>,
+) -> Result<(), CliError> {
+    let deadline = Instant::now() + INITIAL_STATE_TIMEOUT;
+    loop {
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        match wlx_events.recv_timeout(remaining) {
+            Ok(WlMonitorEvent::InitialState(monitors)) => {
+                app.set_monitors(monitors);
+                return Ok(());
+            }
+            Ok(_) => {}
+            // KWin only offers the protocol in some versions, and without
+            // it no monitor ever arrives.
+            Err(RecvTimeoutError::Timeout | RecvTimeoutError::Disconnected)
+                if app.compositor == Compositor::Kwin =>
+            {
+                return Err(CliError::NoOutputManagement(
+                    app.compositor.label(),
+                ));
+            }
+            Err(RecvTimeoutError::Timeout | RecvTimeoutError::Disconnected) => {
+                return Err(CliError::NoInitialState);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(list: &[&str]) -> Vec<String> {
+        list.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_no_args_runs_tui() {
+        assert_eq!(
+            parse(args(&[])),
+            Ok(Command::Tui(TuiOptions::default()))
+        );
+    }
+
+    #[test]
+    fn test_tui_startup_focus() {
+        assert_eq!(
+            parse(args(&["--monitor", "DP-2", "--panel", "SCALE"])),
+            Ok(Command::Tui(TuiOptions {
+                monitor: Some("DP-2".into()),
+                panel: Some(Panel::Scale),
+                hold_on_error: None,
+                observe: false,
+                tour: false,
+            }))
+        );
+        assert_eq!(
+            parse(args(&["--hold-on-error", "off", "--monitor", "DP-2"])),
+            Ok(Command::Tui(TuiOptions {
+                monitor: Some("DP-2".into()),
+                panel: None,
+                hold_on_error: Some(false),
+                observe: false,
+                tour: false,
+            }))
+        );
+        assert_eq!(
+            parse(args(&["--observe", "--panel", "mode"])),
+            Ok(Command::Tui(TuiOptions {
+                panel: Some(Panel::Mode),
+                observe: true,
+                ..TuiOptions::default()
+            }))
+        );
+        assert_eq!(
+            parse(args(&["--tour"])),
+            Ok(Command::Tui(TuiOptions {
+                tour: true,
+                ..TuiOptions::default()
+            }))
+        );
+        assert_eq!(
+            parse(args(&["--hold-on-error", "yes"])),
+            Err(CliError::InvalidValue {
+                option: "--hold-on-error",
+                value: "yes".into()
+            })
+        );
+        assert_eq!(
+            parse(args(&["--panel", "zoom"])),
+            Err(CliError::UnknownPanel("zoom".into()))
+        );
+        assert_eq!(
+            parse(args(&["--monitor"])),
+            Err(CliError::MissingValue("--monitor"))
+        );
+    }
+
+    #[test]
+    fn test_plain_mode() {
+        assert_eq!(parse(args(&["--plain"])), Ok(Command::Plain));
+        assert_eq!(parse(args(&["repl"])), Ok(Command::Plain));
+    }
+
+    #[test]
+    fn test_version() {
+        assert_eq!(
+            parse(args(&["--version"])),
+            Ok(Command::Version { json: false })
+        );
+        assert_eq!(
+            parse(args(&["-V", "--json"])),
+            Ok(Command::Version { json: true })
+        );
+        assert!(matches!(
+            parse(args(&["--version", "--yaml"])),
+            Err(CliError::UnknownOption { .. })
+        ));
+    }
+
+    #[test]
+    fn test_watch() {
+        assert_eq!(
+            parse(args(&["watch"])),
+            Ok(Command::Watch(WatchOptions::default()))
+        );
+        assert_eq!(
+            parse(args(&["watch", "--summary"])),
+            Ok(Command::Watch(WatchOptions { summary: true }))
+        );
+    }
+
+    #[test]
+    fn test_doctor() {
+        assert_eq!(
+            parse(args(&["doctor"])),
+            Ok(Command::Doctor(DoctorOptions::default()))
+        );
+        assert_eq!(
+            parse(args(&["doctor", "--layout"])),
+            Ok(Command::Doctor(DoctorOptions { layout: true }))
+        );
+        assert!(parse(args(&["doctor", "--fix"])).is_err());
+    }
+
+    #[test]
+    fn test_set_distribute_workspaces() {
+        assert_eq!(
+            parse(args(&["set", "--distribute-workspaces"])),
+            Ok(Command::Set(SetOptions {
+                distribute_workspaces: true
+            }))
+        );
+    }
+
+    #[test]
+    fn test_set_without_options_is_rejected() {
+        assert_eq!(parse(args(&["set"])), Err(CliError::NothingToSet));
+    }
+
+    #[test]
+    fn test_apply_and_service_commands() {
+        assert_eq!(parse(args(&["apply", "--once"])), Ok(Command::Apply));
+        assert_eq!(parse(args(&["recover"])), Ok(Command::Recover));
+        assert_eq!(
+            parse(args(&["install-service", "--dry-run", "--autostart"])),
+            Ok(Command::InstallService(ServiceOptions {
+                dry_run: true,
+                autostart: true
+            }))
+        );
+        assert_eq!(
+            parse(args(&["uninstall-service", "--autostart"])),
+            Err(CliError::UnknownOption {
+                command: "uninstall-service",
+                option: "--autostart".into()
+            })
+        );
+    }
+
+    #[test]
+    fn test_leading_dry_run() {
+        assert_eq!(
+            parse_with_dry_run(args(&["--dry-run"])),
+            Ok((Command::Tui(TuiOptions::default()), true))
+        );
+        assert_eq!(
+            parse_with_dry_run(args(&["--dry-run", "uninstall-service"])),
+            Ok((
+                Command::UninstallService(ServiceOptions {
+                    dry_run: true,
+                    autostart: false
+                }),
+                true
+            ))
+        );
+        assert_eq!(
+            parse_with_dry_run(args(&["--dry-run", "apply"])),
+            Err(CliError::NoDryRun("apply"))
+        );
+        assert_eq!(
+            parse_with_dry_run(args(&["repl"])),
+            Ok((Command::Plain, false))
+        );
+    }
+
+    #[test]
+    fn test_debug_dump_and_replay() {
+        assert_eq!(
+            parse(args(&["debug-dump", "--redact", "/tmp/dump.toml"])),
+            Ok(Command::DebugDump(DumpOptions {
+                redact: true,
+                output: Some("/tmp/dump.toml".into())
+            }))
+        );
+        assert_eq!(
+            parse(args(&["replay", "dump.toml"])),
+            Ok(Command::Replay("dump.toml".into()))
+        );
+        assert_eq!(
+            parse(args(&["replay"])),
+            Err(CliError::MissingValue("replay"))
+        );
+    }
+
+    #[test]
+    fn test_remote() {
+        let parsed = parse_with_dry_run(args(&[
+            "--dry-run",
+            "--remote",
+            "me@signage",
+            "--compositor",
+            "sway",
+            "set",
+            "--output",
+            "HDMI-A-1",
+            "--mode",
+            "3840x2160@30",
+            "--position",
+            "1920,0",
+            "--reload",
+        ]));
+        assert_eq!(
+            parsed,
+            Ok((
+                Command::Remote(RemoteOptions {
+                    host: "me@signage".into(),
+                    compositor: Some(Compositor::Sway),
+                    config: None,
+                    reload: true,
+                    dry_run: true,
+                    action: RemoteAction::Set(MonitorEdit {
+                        output: "HDMI-A-1".into(),
+                        mode: Some((3840, 2160, 30_000)),
+                        position: Some((1920, 0)),
+                        ..MonitorEdit::default()
+                    }),
+                }),
+                true
+            ))
+        );
+        let Ok(Command::Remote(opts)) = parse(args(&[
+            "--remote",
+            "signage",
+            "--config",
+            "/etc/xdg/hypr/monitors.conf",
+        ])) else {
+            panic!("not a remote command");
+        };
+        assert_eq!(opts.action, RemoteAction::Show);
+        assert_eq!(
+            parse(args(&["--remote", "signage", "set", "--scale", "0"])),
+            Err(CliError::InvalidValue {
+                option: "--scale",
+                value: "0".into()
+            })
+        );
+        assert_eq!(
+            parse(args(&["--remote", "signage", "show", "--output", "DP-1"])),
+            Err(CliError::UnknownOption {
+                command: "--remote",
+                option: "--output".into()
+            })
+        );
+        assert_eq!(
+            parse(args(&["--remote"])),
+            Err(CliError::MissingValue("--remote"))
+        );
+        assert_eq!(
+            parse(args(&["--remote", "-oProxyCommand=x", "show"])),
+            Err(CliError::InvalidValue {
+                option: "--remote",
+                value: "-oProxyCommand=x".into()
+            })
+        );
+    }
+
+    #[test]
+    fn test_unknown_command_and_option() {
+        assert_eq!(
+            parse(args(&["frobnicate"])),
+            Err(CliError::UnknownCommand("frobnicate".into()))
+        );
+        assert_eq!(
+            parse(args(&["set", "--bogus"])),
+            Err(CliError::UnknownOption {
+                command: "set",
+                option: "--bogus".into()
+            })
+        );
+    }
+}
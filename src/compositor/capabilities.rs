@@ -0,0 +1,248 @@
+use super::Compositor;
+
+/// The syntax of the monitor config xwlm writes.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ConfigStyle {
+    /// `monitor = ...` and `workspace = ...` lines.
+    Hyprland,
+    /// `output ...` and `workspace ... output ...` lines.
+    Sway,
+    /// A shell script of `wlr-randr` calls.
+    WlrRandr,
+    /// A shell script of `niri msg output` calls.
+    NiriMsg,
+    /// `[output:NAME]` sections of an INI file.
+    WayfireIni,
+}
+
+impl ConfigStyle {
+    /// The config is a script that is run rather than read.
+    pub fn is_script(self) -> bool {
+        matches!(self, ConfigStyle::WlrRandr | ConfigStyle::NiriMsg)
+    }
+
+    /// The monitor config is the compositor's own config file, so xwlm
+    /// writes only its monitor sections and keeps everything else.
+    pub fn is_shared(self) -> bool {
+        self == ConfigStyle::WayfireIni
+    }
+}
+
+/// What xwlm can do on a compositor. Code that depends on the compositor
+/// asks this instead of matching on [`Compositor`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CompositorCapabilities {
+    /// How the monitor config is written, `None` when xwlm writes none.
+    pub config_style: Option<ConfigStyle>,
+    /// Workspaces can be assigned to monitors in the monitor config.
+    pub supports_workspace_rules: bool,
+    /// Workspace rules can also mark a monitor's default workspace and
+    /// keep workspaces around while empty.
+    pub supports_workspace_defaults: bool,
+    /// A workspace rule can name further monitors to fall back on when
+    /// the first is missing.
+    pub supports_workspace_fallbacks: bool,
+    /// Monitors can be powered off (DPMS) through [`super::ipc`].
+    pub supports_power: bool,
+    /// The command that makes the compositor read its config again, so
+    /// saving applies.
+    pub reload_command: Option<&'static [&'static str]>,
+    /// Saving runs the monitor config instead, for a compositor that has
+    /// no config of its own to reload.
+    pub reload_runs_config: bool,
+    /// Saving sends the layout as a command instead, for a compositor
+    /// that keeps its own output settings and reads no text config.
+    pub applies_by_command: bool,
+    /// The largest scale the compositor takes without complaint, when it
+    /// has one below xwlm's own limit.
+    pub max_scale: Option<f64>,
+}
+
+impl Compositor {
+    pub fn capabilities(self) -> CompositorCapabilities {
+        match self {
+            Compositor::Hyprland => CompositorCapabilities {
+                config_style: Some(ConfigStyle::Hyprland),
+                supports_workspace_rules: true,
+                supports_workspace_defaults: true,
+                supports_workspace_fallbacks: false,
+                supports_power: true,
+                reload_command: Some(&["hyprctl", "reload"]),
+                reload_runs_config: false,
+                applies_by_command: false,
+                max_scale: Some(3.0),
+            },
+            Compositor::Sway => CompositorCapabilities {
+                config_style: Some(ConfigStyle::Sway),
+                supports_workspace_rules: true,
+                supports_workspace_defaults: false,
+                supports_workspace_fallbacks: true,
+                supports_power: true,
+                reload_command: Some(&["swaymsg", "reload"]),
+                reload_runs_config: false,
+                applies_by_command: false,
+                max_scale: None,
+            },
+            Compositor::River => CompositorCapabilities {
+                config_style: Some(ConfigStyle::WlrRandr),
+                supports_workspace_rules: false,
+                supports_workspace_defaults: false,
+                supports_workspace_fallbacks: false,
+                supports_power: false,
+                reload_command: None,
+                reload_runs_config: false,
+                applies_by_command: false,
+                max_scale: None,
+            },
+            Compositor::Niri => CompositorCapabilities {
+                config_style: Some(ConfigStyle::NiriMsg),
+                supports_workspace_rules: false,
+                supports_workspace_defaults: false,
+                supports_workspace_fallbacks: false,
+                supports_power: false,
+                reload_command: None,
+                reload_runs_config: false,
+                applies_by_command: false,
+                max_scale: None,
+            },
+            // Wayfire reads wayfire.ini again whenever it changes.
+            Compositor::Wayfire => CompositorCapabilities {
+                config_style: Some(ConfigStyle::WayfireIni),
+                supports_workspace_rules: false,
+                supports_workspace_defaults: false,
+                supports_workspace_fallbacks: false,
+                supports_power: false,
+                reload_command: None,
+                reload_runs_config: false,
+                applies_by_command: false,
+                max_scale: None,
+            },
+            // labwc has no output config; the wlr-randr script is run on
+            // save and from its autostart.
+            Compositor::Labwc => CompositorCapabilities {
+                config_style: Some(ConfigStyle::WlrRandr),
+                supports_workspace_rules: false,
+                supports_workspace_defaults: false,
+                supports_workspace_fallbacks: false,
+                supports_power: false,
+                reload_command: None,
+                reload_runs_config: true,
+                applies_by_command: false,
+                max_scale: None,
+            },
+            // KWin ignores text configs; kscreen-doctor applies and keeps
+            // the layout.
+            Compositor::Kwin => CompositorCapabilities {
+                config_style: None,
+                supports_workspace_rules: false,
+                supports_workspace_defaults: false,
+                supports_workspace_fallbacks: false,
+                supports_power: false,
+                reload_command: None,
+                reload_runs_config: false,
+                applies_by_command: true,
+                max_scale: None,
+            },
+            // Any other wlroots compositor: changes go out live, and the
+            // wlr-randr script keeps them for whoever runs it at login.
+            Compositor::Unknown => CompositorCapabilities {
+                config_style: Some(ConfigStyle::WlrRandr),
+                supports_workspace_rules: false,
+                supports_workspace_defaults: false,
+                supports_workspace_fallbacks: false,
+                supports_power: false,
+                reload_command: None,
+                reload_runs_config: false,
+                applies_by_command: false,
+                max_scale: None,
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        compositor::{
+            format::render_monitor_config, workspace_config::WorkspaceRule,
+        },
+        layout::{Layout, LayoutMonitor},
+    };
+    use wlx_monitors::WlTransform;
+
+    #[test]
+    fn test_every_compositor_writes_what_it_supports() {
+        let layout = Layout {
+            monitors: vec![LayoutMonitor {
+                name: "DP-1".into(),
+                description: String::new(),
+                enabled: true,
+                width: 1920,
+                height: 1080,
+                refresh_rate: 60,
+                refresh_mhz: None,
+                x: 0,
+                y: 0,
+                scale: 1.0,
+                transform: WlTransform::Normal,
+            }],
+            workspaces: vec![WorkspaceRule {
+                id: 1,
+                monitor: "DP-1".into(),
+                fallbacks: vec!["HDMI-A-1".into()],
+                is_default: true,
+                is_persistent: false,
+                line: None,
+            }],
+        };
+        for comp in Compositor::ALL {
+            let caps = comp.capabilities();
+            assert!(
+                caps.supports_workspace_rules
+                    || !caps.supports_workspace_defaults
+                        && !caps.supports_workspace_fallbacks,
+                "{comp:?}"
+            );
+            assert!(
+                caps.config_style.is_some() || caps.reload_command.is_none(),
+                "{comp:?}"
+            );
+            assert!(
+                !caps.reload_runs_config
+                    || caps.reload_command.is_none()
+                        && caps.config_style.is_some_and(|s| s.is_script()),
+                "{comp:?}"
+            );
+
+            assert!(
+                !caps.applies_by_command || caps.config_style.is_none(),
+                "{comp:?}"
+            );
+            // Saving always keeps the layout somewhere.
+            assert!(
+                caps.config_style.is_some() || caps.applies_by_command,
+                "{comp:?}"
+            );
+
+            let content = render_monitor_config(comp, &layout, &[]);
+            assert_eq!(content.is_some(), caps.config_style.is_some());
+            let content = content.unwrap_or_default();
+            assert_eq!(
+                content.contains("workspace"),
+                caps.supports_workspace_rules,
+                "{comp:?}: {content}"
+            );
+            assert_eq!(
+                content.contains("default:true"),
+                caps.supports_workspace_defaults,
+                "{comp:?}: {content}"
+            );
+            assert_eq!(
+                content.contains("HDMI-A-1"),
+                caps.supports_workspace_fallbacks,
+                "{comp:?}: {content}"
+            );
+        }
+    }
+}
@@ -0,0 +1,186 @@
+use std::{collections::HashMap, fs, io, path::Path};
+
+use crate::{
+    compositor::{
+        Compositor, diff,
+        position::{ConfigPosition, config_positions},
+        workspace_config::parse_workspaces,
+    },
+    i18n::tf,
+};
+
+/// What xwlm found at the configured monitor config path.
+#[derive(Debug, PartialEq)]
+pub enum ConfigStatus {
+    Ok {
+        workspaces: usize,
+        positions: Vec<(String, ConfigPosition)>,
+    },
+    Missing,
+    Unreadable(String),
+    /// The file reads like another compositor's config.
+    WrongSyntax(Compositor),
+    /// The file has content but no monitor or workspace lines.
+    Unrecognized,
+}
+
+impl ConfigStatus {
+    pub fn is_ok(&self) -> bool {
+        matches!(self, ConfigStatus::Ok { .. })
+    }
+
+    /// A one-line explanation for anything but `Ok`.
+    pub fn problem(
+        &self,
+        compositor: Compositor,
+        path: &Path,
+    ) -> Option<String> {
+        let path = path.display();
+        Some(match self {
+            ConfigStatus::Ok { .. } => return None,
+            ConfigStatus::Missing => {
+                tf("Monitor config not found: {}", &[&path])
+            }
+            ConfigStatus::Unreadable(e) => {
+                tf("Cannot read monitor config {}: {}", &[&path, e])
+            }
+            ConfigStatus::WrongSyntax(found) => tf(
+                "{} looks like a {} config, but {} is running",
+                &[&path, &found.label(), &compositor.label()],
+            ),
+            ConfigStatus::Unrecognized => tf(
+                "{} has no monitor lines xwlm understands for {}",
+                &[&path, &compositor.label()],
+            ),
+        })
+    }
+}
+
+pub fn check_monitor_config(
+    compositor: Compositor,
+    path: &Path,
+) -> ConfigStatus {
+    check_config_content(compositor, fs::read_to_string(path).as_deref())
+}
+
+/// [`check_monitor_config`] on the result of reading the file.
+pub fn check_config_content(
+    compositor: Compositor,
+    content: Result<&str, &io::Error>,
+) -> ConfigStatus {
+    // KWin keeps its own output settings, so there is no file to miss.
+    if compositor.capabilities().applies_by_command {
+        return ConfigStatus::Ok {
+            workspaces: 0,
+            positions: Vec::new(),
+        };
+    }
+    let content = match content {
+        Ok(content) => content,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => {
+            return ConfigStatus::Missing;
+        }
+        Err(e) => return ConfigStatus::Unreadable(e.to_string()),
+    };
+    if compositor.capabilities().config_style.is_none() {
+        return ConfigStatus::Ok {
+            workspaces: 0,
+            positions: Vec::new(),
+        };
+    }
+
+    let workspaces = parse_workspaces(compositor, content);
+    let monitors = diff::configured_monitors(compositor, content);
+    if monitors.is_empty() && workspaces.is_empty() {
+        let other = Compositor::ALL
+            .into_iter()
+            .filter(|&c| c != compositor)
+            .find(|&c| !diff::configured_monitors(c, content).is_empty());
+        if let Some(other) = other {
+            return ConfigStatus::WrongSyntax(other);
+        }
+        let meaningful = content.lines().map(str::trim).any(|l| {
+            !l.is_empty() && !l.starts_with('#')
+        });
+        let shared = compositor
+            .capabilities()
+            .config_style
+            .is_some_and(|s| s.is_shared());
+        if meaningful && !shared {
+            return ConfigStatus::Unrecognized;
+        }
+    }
+
+    let mut found: HashMap<String, ConfigPosition> =
+        config_positions(compositor, content).into_iter().collect();
+    let positions = monitors
+        .into_iter()
+        .filter_map(|name| {
+            let pos = found.remove(&name)?;
+            Some((name, pos))
+        })
+        .collect();
+    ConfigStatus::Ok {
+        workspaces: workspaces.len(),
+        positions,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn check(
+        name: &str,
+        compositor: Compositor,
+        content: &str,
+    ) -> ConfigStatus {
+        let path = std::env::temp_dir()
+            .join(format!("xwlm-check-{name}-{}.conf", std::process::id()));
+        fs::write(&path, content).unwrap();
+        let status = check_monitor_config(compositor, &path);
+        fs::remove_file(path).unwrap();
+        status
+    }
+
+    #[test]
+    fn test_missing_file() {
+        let path = Path::new("/nonexistent/xwlm-monitors.conf");
+        let status = check_monitor_config(Compositor::Hyprland, path);
+        assert_eq!(status, ConfigStatus::Missing);
+        let problem = status.problem(Compositor::Hyprland, path).unwrap();
+        assert!(problem.contains("/nonexistent/xwlm-monitors.conf"));
+    }
+
+    #[test]
+    fn test_sway_config_under_hyprland() {
+        let sway = "output DP-1 {\n    pos 0 0\n}\n";
+        assert_eq!(
+            check("sway", Compositor::Hyprland, sway),
+            ConfigStatus::WrongSyntax(Compositor::Sway)
+        );
+        let general = "general {\n  gaps_in = 5\n}\n";
+        assert_eq!(
+            check("gaps", Compositor::Hyprland, general),
+            ConfigStatus::Unrecognized
+        );
+        assert!(check("empty", Compositor::Hyprland, "# empty\n").is_ok());
+    }
+
+    #[test]
+    fn test_reports_workspaces_and_positions() {
+        let hypr = "monitor = DP-1, 1920x1080@60, 1920x0, 1\n\
+                    monitor = HDMI-A-1, disable\n\
+                    workspace = 1, monitor:DP-1\n";
+        assert_eq!(
+            check("hypr", Compositor::Hyprland, hypr),
+            ConfigStatus::Ok {
+                workspaces: 1,
+                positions: vec![(
+                    "DP-1".into(),
+                    ConfigPosition { x: 1920, y: 0 }
+                )],
+            }
+        );
+    }
+}
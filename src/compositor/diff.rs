@@ -0,0 +1,1014 @@
+use std::{collections::HashSet, ops::Range};
+
+use super::{
+    Compositor,
+    workspace_config::{WorkspaceRule, parse_workspaces},
+};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct MonitorChange {
+    pub name: String,
+    pub old: Vec<String>,
+    pub new: Vec<String>,
+}
+
+/// Lines of `content` that configure `name`, whitespace-normalised.
+pub fn monitor_lines(
+    compositor: Compositor,
+    content: &str,
+    name: &str,
+) -> Vec<String> {
+    let lines: Vec<&str> = content.lines().collect();
+    sections(compositor, &lines, name)
+        .into_iter()
+        .flat_map(|r| lines[r].iter().map(|l| normalise(l)))
+        .collect()
+}
+
+/// Names of the monitors `content` configures, in order of appearance.
+pub fn configured_monitors(
+    compositor: Compositor,
+    content: &str,
+) -> Vec<String> {
+    let mut names: Vec<String> = Vec::new();
+    let mut seen = HashSet::new();
+    for line in content.lines() {
+        if let Some(name) = monitor_name(compositor, line.trim())
+            && seen.insert(name)
+        {
+            names.push(name.to_string());
+        }
+    }
+    names
+}
+
+/// The monitor a config line is about, if it is a monitor line for
+/// `compositor`.
+pub fn monitor_name(compositor: Compositor, line: &str) -> Option<&str> {
+    let mut words = line.split_whitespace();
+    match compositor {
+        Compositor::Hyprland => hyprland_monitor(line),
+        Compositor::Sway => {
+            if words.next()? != "output" {
+                return None;
+            }
+            words.next().map(|w| w.trim_matches('"'))
+        }
+        Compositor::River | Compositor::Labwc | Compositor::Unknown => {
+            words.find(|&w| w == "--output")?;
+            words.next()
+        }
+        Compositor::Niri => {
+            let command = [words.next()?, words.next()?, words.next()?];
+            if command != ["niri", "msg", "output"] {
+                return None;
+            }
+            words.next()
+        }
+        Compositor::Wayfire => {
+            let name = line.strip_prefix("[output:")?.strip_suffix(']')?;
+            Some(name.trim())
+        }
+        Compositor::Kwin => None,
+    }
+}
+
+/// Monitors in `names` whose lines differ between `old` and `new`.
+pub fn changed_monitors<'a>(
+    compositor: Compositor,
+    old: &str,
+    new: &str,
+    names: impl IntoIterator<Item = &'a str>,
+) -> Vec<MonitorChange> {
+    names
+        .into_iter()
+        .filter_map(|name| {
+            let old = monitor_lines(compositor, old, name);
+            let new = monitor_lines(compositor, new, name);
+            (old != new).then(|| MonitorChange {
+                name: name.to_string(),
+                old,
+                new,
+            })
+        })
+        .collect()
+}
+
+/// Puts the previous lines of each changed monitor back into `new`.
+pub fn keep_old_lines(
+    compositor: Compositor,
+    new: &str,
+    changes: &[MonitorChange],
+) -> String {
+    let mut lines: Vec<String> = new.lines().map(String::from).collect();
+    for change in changes {
+        let borrowed: Vec<&str> = lines.iter().map(String::as_str).collect();
+        let ranges = sections(compositor, &borrowed, &change.name);
+        let Some(first) = ranges.first().map(|r| r.start) else {
+            continue;
+        };
+        for range in ranges.into_iter().rev() {
+            lines.drain(range);
+        }
+        lines.splice(first..first, change.old.iter().cloned());
+    }
+    let mut content = lines.join("\n");
+    if new.ends_with('\n') {
+        content.push('\n');
+    }
+    content
+}
+
+/// The rendered config `new` merged into the file `old` as every save
+/// writes it: the `unmanaged` monitors' lines, output directives xwlm
+/// does not set, where the workspace rules stood, and whatever surrounds
+/// xwlm's part of a script or INI file are kept from `old`.
+pub fn merge_into(
+    compositor: Compositor,
+    old: &str,
+    new: &str,
+    unmanaged: &[String],
+) -> String {
+    let new = keep_monitor_sections(compositor, old, new, unmanaged);
+    let new = keep_output_directives(compositor, old, &new);
+    let new = keep_workspace_places(compositor, old, &new);
+    let new = keep_script_around(compositor, old, &new);
+    keep_ini_around(compositor, old, &new)
+}
+
+/// Writes the lines `old` has for each of `names` into `new` exactly as
+/// they were, in place of any `new` has for them: after the lines of the
+/// monitor they followed in `old`, or above every monitor line.
+pub fn keep_monitor_sections(
+    compositor: Compositor,
+    old: &str,
+    new: &str,
+    names: &[String],
+) -> String {
+    let old_lines: Vec<&str> = old.lines().collect();
+    let mut lines: Vec<String> = new.lines().map(String::from).collect();
+    let mut kept: Vec<(usize, &str)> = Vec::new();
+    for name in names {
+        let borrowed: Vec<&str> = lines.iter().map(String::as_str).collect();
+        let ranges = sections(compositor, &borrowed, name);
+        for range in ranges.into_iter().rev() {
+            lines.drain(range);
+        }
+        if let Some(first) = sections(compositor, &old_lines, name).first() {
+            kept.push((first.start, name.as_str()));
+        }
+    }
+    // In the order `old` has them, so one kept monitor can follow another.
+    kept.sort();
+    for (start, name) in kept {
+        let before = old_lines[..start]
+            .iter()
+            .rev()
+            .find_map(|l| monitor_name(compositor, l.trim()));
+        let borrowed: Vec<&str> = lines.iter().map(String::as_str).collect();
+        let at = before
+            .and_then(|b| sections(compositor, &borrowed, b).pop())
+            .map(|r| r.end)
+            .or_else(|| {
+                borrowed
+                    .iter()
+                    .position(|l| monitor_name(compositor, l.trim()).is_some())
+            })
+            .unwrap_or_else(|| {
+                let blank = borrowed.iter().rev();
+                lines.len() - blank.take_while(|l| l.trim().is_empty()).count()
+            });
+        let section: Vec<String> = sections(compositor, &old_lines, name)
+            .into_iter()
+            .flat_map(|r| old_lines[r].iter().map(|l| l.to_string()))
+            .collect();
+        lines.splice(at..at, section);
+    }
+    let mut content = lines.join("\n");
+    if new.ends_with('\n') {
+        content.push('\n');
+    }
+    content
+}
+
+/// Workspace lines `old` had next to a monitor's lines, written back in
+/// the same place.
+struct Run {
+    /// The monitor whose lines come before, `None` above all of them.
+    after: Option<String>,
+    blank_before: bool,
+    blank_after: bool,
+    lines: Vec<String>,
+}
+
+/// Moves the workspace lines of `new` to where `old` had them, after the
+/// same monitor's lines and with the blank lines around them, so a config
+/// arranged by hand only changes where it has to. A rule that says the
+/// same as before keeps its old line as written. Rules `old` lacks, and
+/// those whose monitor lines are gone, stay at the bottom.
+pub fn keep_workspace_places(
+    compositor: Compositor,
+    old: &str,
+    new: &str,
+) -> String {
+    let rule = |line: &str| parse_workspaces(compositor, line).pop();
+    let old_lines: Vec<&str> = old.lines().collect();
+    if !old_lines.iter().any(|l| rule(l).is_some()) {
+        return new.to_string();
+    }
+
+    // `new` with its workspace lines, and the blank line setting them
+    // off, taken out.
+    let mut lines: Vec<&str> = Vec::new();
+    let mut rendered: Vec<(WorkspaceRule, &str)> = Vec::new();
+    let new_lines: Vec<&str> = new.lines().collect();
+    for (i, &line) in new_lines.iter().enumerate() {
+        if let Some(r) = rule(line) {
+            rendered.push((r, line));
+            let ends_group =
+                new_lines.get(i + 1).is_none_or(|next| rule(next).is_none());
+            let blank_follows = new_lines
+                .get(i + 1)
+                .is_none_or(|next| next.trim().is_empty());
+            if ends_group
+                && blank_follows
+                && lines.last().is_some_and(|l| l.trim().is_empty())
+            {
+                lines.pop();
+            }
+        } else {
+            lines.push(line);
+        }
+    }
+
+    let mut runs: Vec<Run> = Vec::new();
+    let mut after = None;
+    let mut in_run = false;
+    for (i, &line) in old_lines.iter().enumerate() {
+        let Some(old_rule) = rule(line) else {
+            if let Some(name) = monitor_name(compositor, line.trim()) {
+                after = Some(name.to_string());
+            }
+            in_run = false;
+            continue;
+        };
+        if !in_run {
+            runs.push(Run {
+                after: after.clone(),
+                blank_before: i > 0 && old_lines[i - 1].trim().is_empty(),
+                blank_after: false,
+                lines: Vec::new(),
+            });
+            in_run = true;
+        }
+        let run = runs.last_mut().expect("just pushed");
+        run.blank_after =
+            old_lines.get(i + 1).is_some_and(|l| l.trim().is_empty());
+        let Some(idx) = rendered.iter().position(|(r, _)| r.id == old_rule.id)
+        else {
+            continue;
+        };
+        let (new_rule, text) = rendered.remove(idx);
+        run.lines
+            .push(if new_rule == old_rule { line } else { text }.into());
+    }
+
+    let place = |run: &Run| match &run.after {
+        Some(name) => sections(compositor, &lines, name).last().map(|r| r.end),
+        None => Some(lines.iter().take_while(|l| l.starts_with('#')).count()),
+    };
+    let mut placed: Vec<(usize, &Run)> = Vec::new();
+    let mut leftover: Vec<String> = Vec::new();
+    for run in runs.iter().filter(|r| !r.lines.is_empty()) {
+        match place(run) {
+            Some(at) => placed.push((at, run)),
+            None => leftover.extend(run.lines.iter().cloned()),
+        }
+    }
+    leftover.extend(rendered.into_iter().map(|(_, text)| text.to_string()));
+
+    let blank =
+        |line: Option<&String>| line.is_some_and(|l| l.trim().is_empty());
+    let mut out: Vec<String> = Vec::new();
+    for i in 0..=lines.len() {
+        for (_, run) in placed.iter().filter(|(at, _)| *at == i) {
+            if run.blank_before && !out.is_empty() && !blank(out.last()) {
+                out.push(String::new());
+            }
+            out.extend(run.lines.iter().cloned());
+            if run.blank_after
+                && lines.get(i).is_some_and(|l| !l.trim().is_empty())
+            {
+                out.push(String::new());
+            }
+        }
+        if let Some(line) = lines.get(i) {
+            out.push(line.to_string());
+        }
+    }
+    if !leftover.is_empty() {
+        let end = out.len()
+            - out.iter().rev().take_while(|l| l.trim().is_empty()).count();
+        let follows_rules = end > 0 && rule(&out[end - 1]).is_some();
+        let mut tail = Vec::new();
+        if end > 0 && !follows_rules {
+            tail.push(String::new());
+        }
+        tail.extend(leftover);
+        out.splice(end..end, tail);
+    }
+
+    let mut content = out.join("\n");
+    if new.ends_with('\n') {
+        content.push('\n');
+    }
+    content
+}
+
+/// Sway output subcommands xwlm does not write but keeps as they were.
+const SWAY_PASSTHROUGH: [&str; 2] = ["scale_filter", "subpixel"];
+
+/// Where a kept output subcommand goes in the new block.
+#[derive(Clone, Debug, PartialEq)]
+enum Anchor {
+    /// Right after `output NAME {`.
+    Opening,
+    /// After the line starting with this subcommand, e.g. `mode`.
+    After(String),
+    /// Last, before the closing brace.
+    End,
+}
+
+/// A kept subcommand with the comments right above it, as written.
+struct Kept {
+    anchor: Anchor,
+    lines: Vec<String>,
+    /// The subcommand alone, e.g. "scale_filter nearest".
+    directive: String,
+}
+
+fn is_passthrough(directive: &str) -> bool {
+    let word = directive.split_whitespace().next().unwrap_or_default();
+    SWAY_PASSTHROUGH.contains(&word)
+}
+
+/// The passthrough subcommands of `name` in `lines`, in order.
+fn passthrough(lines: &[&str], name: &str) -> Vec<Kept> {
+    let mut kept = Vec::new();
+    for range in sections(Compositor::Sway, lines, name) {
+        let section = &lines[range];
+        let first = section[0].trim();
+        if !first.contains('{') {
+            // One line: `output NAME mode ... scale_filter nearest`.
+            let words: Vec<&str> = first.split_whitespace().collect();
+            for pair in words.windows(2) {
+                if SWAY_PASSTHROUGH.contains(&pair[0]) {
+                    let directive = pair.join(" ");
+                    kept.push(Kept {
+                        anchor: Anchor::End,
+                        lines: vec![format!("    {directive}")],
+                        directive,
+                    });
+                }
+            }
+            continue;
+        }
+        let mut anchor = Anchor::Opening;
+        let mut comments = Vec::new();
+        for line in &section[1..] {
+            let trimmed = line.trim();
+            if trimmed.starts_with('}') {
+                break;
+            }
+            if trimmed.starts_with('#') {
+                comments.push(line.to_string());
+                continue;
+            }
+            if is_passthrough(trimmed) {
+                comments.push(line.to_string());
+                // The next one shares the anchor and follows this one.
+                kept.push(Kept {
+                    anchor: anchor.clone(),
+                    lines: std::mem::take(&mut comments),
+                    directive: normalise(trimmed),
+                });
+                continue;
+            }
+            comments.clear();
+            if let Some(word) = trimmed.split_whitespace().next() {
+                anchor = Anchor::After(word.to_string());
+            }
+        }
+    }
+    kept
+}
+
+/// Keys xwlm writes in a Wayfire `[output:NAME]` section.
+const WAYFIRE_KEYS: [&str; 4] = ["mode", "position", "scale", "transform"];
+
+fn is_wayfire_key(line: &str) -> bool {
+    let key = line.split('=').next().unwrap_or_default().trim();
+    WAYFIRE_KEYS.contains(&key)
+}
+
+/// The other keys `old` has in each output section, like `vrr`, and the
+/// comments between them, added to the end of the section in `new`.
+fn keep_ini_keys(old: &str, new: &str) -> String {
+    let compositor = Compositor::Wayfire;
+    let old_lines: Vec<&str> = old.lines().collect();
+    let mut lines: Vec<String> = new.lines().map(String::from).collect();
+    for name in configured_monitors(compositor, old) {
+        let kept: Vec<String> = sections(compositor, &old_lines, &name)
+            .into_iter()
+            .flat_map(|r| &old_lines[r.start + 1..r.end])
+            .filter(|l| !l.trim().is_empty() && !is_wayfire_key(l))
+            .map(|l| l.to_string())
+            .collect();
+        let borrowed: Vec<&str> = lines.iter().map(String::as_str).collect();
+        let Some(range) = sections(compositor, &borrowed, &name).pop() else {
+            continue;
+        };
+        let has_own = borrowed[range.start + 1..range.end]
+            .iter()
+            .any(|l| !is_wayfire_key(l));
+        if kept.is_empty() || has_own {
+            continue;
+        }
+        lines.splice(range.end..range.end, kept);
+    }
+    let mut content = lines.join("\n");
+    if new.ends_with('\n') {
+        content.push('\n');
+    }
+    content
+}
+
+/// Puts the `scale_filter` and `subpixel` subcommands `old` has for each
+/// output back into the output's lines in `new`, which xwlm wrote without
+/// them: in a block after the line they followed before, with the
+/// comments right above them, and at the end of a one-line command. Only
+/// Sway has them, and Wayfire keys xwlm does not write.
+pub fn keep_output_directives(
+    compositor: Compositor,
+    old: &str,
+    new: &str,
+) -> String {
+    if compositor == Compositor::Wayfire {
+        return keep_ini_keys(old, new);
+    }
+    if compositor != Compositor::Sway {
+        return new.to_string();
+    }
+    let old_lines: Vec<&str> = old.lines().collect();
+    let mut lines: Vec<String> = new.lines().map(String::from).collect();
+    for name in configured_monitors(compositor, old) {
+        let kept = passthrough(&old_lines, &name);
+        let borrowed: Vec<&str> = lines.iter().map(String::as_str).collect();
+        let Some(range) = sections(compositor, &borrowed, &name).pop() else {
+            continue;
+        };
+        if kept.is_empty() || !passthrough(&borrowed, &name).is_empty() {
+            continue;
+        }
+        let first = range.start;
+        if !lines[first].contains('{') {
+            let directives: Vec<&str> =
+                kept.iter().map(|k| k.directive.as_str()).collect();
+            lines[first] = format!("{} {}", lines[first], directives.join(" "));
+            continue;
+        }
+        // Later ones go in first, so those after the same line keep their
+        // order; those at the end go in last, in order.
+        let mut end = range.end - 1;
+        for k in kept.iter().filter(|k| k.anchor == Anchor::End) {
+            lines.splice(end..end, k.lines.iter().cloned());
+            end += k.lines.len();
+        }
+        for k in kept.iter().rev().filter(|k| k.anchor != Anchor::End) {
+            let at = match &k.anchor {
+                Anchor::After(word) => (first + 1..end).find(|&i| {
+                    lines[i].split_whitespace().next() == Some(word.as_str())
+                }),
+                _ => None,
+            }
+            .map_or(first + 1, |i| i + 1);
+            lines.splice(at..at, k.lines.iter().cloned());
+            end += k.lines.len();
+        }
+    }
+    let mut content = lines.join("\n");
+    if new.ends_with('\n') {
+        content.push('\n');
+    }
+    content
+}
+
+/// The lines around the part of a monitor script xwlm writes.
+pub const SCRIPT_BLOCK: (&str, &str) = ("# >>> xwlm", "# <<< xwlm");
+
+/// The first line of a [`SCRIPT_BLOCK`] and its last, where `lines` has
+/// one.
+fn script_block(lines: &[&str]) -> Option<(usize, usize)> {
+    let (start, end) = SCRIPT_BLOCK;
+    let first = lines.iter().position(|l| l.trim() == start)?;
+    let last = lines[first..].iter().position(|l| l.trim() == end)?;
+    Some((first, first + last))
+}
+
+/// Writes the block `new` has between the [`SCRIPT_BLOCK`] markers into
+/// the script `old`, keeping everything else `old` has: its shebang, its
+/// comments, a `set -e`, and commands before and after. Only the build
+/// line xwlm wrote there is brought up to date. A script without the
+/// markers gets the block where its first monitor line was, and those
+/// lines are dropped. Only River's and Niri's configs are scripts.
+pub fn keep_script_around(
+    compositor: Compositor,
+    old: &str,
+    new: &str,
+) -> String {
+    let style = compositor.capabilities().config_style;
+    if !style.is_some_and(|s| s.is_script()) || old.trim().is_empty() {
+        return new.to_string();
+    }
+    let new_lines: Vec<&str> = new.lines().collect();
+    let block: Vec<&str> = match script_block(&new_lines) {
+        Some((first, last)) => new_lines[first..=last].to_vec(),
+        None => {
+            let (start, end) = SCRIPT_BLOCK;
+            let managed = new_lines
+                .iter()
+                .filter(|l| !l.trim().is_empty() && !l.starts_with('#'));
+            [start].into_iter().chain(managed.copied()).chain([end]).collect()
+        }
+    };
+    let build = "# Written by ";
+    let written = new_lines.iter().find(|l| l.starts_with(build));
+
+    let old_lines: Vec<&str> = old.lines().collect();
+    let (before, after): (Vec<&str>, Vec<&str>) =
+        match script_block(&old_lines) {
+            Some((first, last)) => {
+                (old_lines[..first].to_vec(), old_lines[last + 1..].to_vec())
+            }
+            None => {
+                let written = |l: &&str| monitor_name(compositor, l).is_some();
+                let at = old_lines.iter().position(written);
+                let at = at.unwrap_or(old_lines.len());
+                let after = old_lines[at..].iter().filter(|l| !written(l));
+                (old_lines[..at].to_vec(), after.copied().collect())
+            }
+        };
+    let mut lines = Vec::new();
+    if !before.first().is_some_and(|l| l.starts_with("#!")) {
+        lines.extend(new_lines.first().filter(|l| l.starts_with("#!")));
+    }
+    lines.extend(before.into_iter().map(|l| match written {
+        Some(written) if l.starts_with(build) => written,
+        _ => l,
+    }));
+    lines.extend(block);
+    lines.extend(after);
+    let mut content = lines.join("\n");
+    if old.ends_with('\n') || new.ends_with('\n') {
+        content.push('\n');
+    }
+    content
+}
+
+/// Writes the `[output:*]` sections of `new` into the INI file `old` in
+/// place of the ones it has, keeping every other section and comment as
+/// they were. Sections for monitors `old` lacks follow its last output
+/// section, or go at the end. Only Wayfire's config is shared this way.
+pub fn keep_ini_around(
+    compositor: Compositor,
+    old: &str,
+    new: &str,
+) -> String {
+    let style = compositor.capabilities().config_style;
+    if !style.is_some_and(|s| s.is_shared()) || old.trim().is_empty() {
+        return new.to_string();
+    }
+    let new_lines: Vec<&str> = new.lines().collect();
+    let mut written: Vec<(&str, Vec<&str>)> = new_lines
+        .iter()
+        .enumerate()
+        .filter_map(|(i, l)| {
+            let name = monitor_name(compositor, l.trim())?;
+            Some((name, new_lines[i..ini_section_end(&new_lines, i)].to_vec()))
+        })
+        .collect();
+
+    let old_lines: Vec<&str> = old.lines().collect();
+    let mut lines: Vec<&str> = Vec::new();
+    let mut first = None;
+    let mut after_last = None;
+    let mut i = 0;
+    while i < old_lines.len() {
+        let Some(name) = monitor_name(compositor, old_lines[i].trim()) else {
+            lines.push(old_lines[i]);
+            i += 1;
+            continue;
+        };
+        first.get_or_insert(lines.len());
+        i = ini_section_end(&old_lines, i);
+        match written.iter().position(|(n, _)| *n == name) {
+            Some(idx) => {
+                lines.extend(written.remove(idx).1);
+                after_last = Some(lines.len());
+            }
+            // A dropped section takes the blank lines after it along.
+            None if lines.last().is_none_or(|l| l.trim().is_empty()) => {
+                while old_lines.get(i).is_some_and(|l| l.trim().is_empty()) {
+                    i += 1;
+                }
+            }
+            None => {}
+        }
+    }
+    let at = after_last.or(first).unwrap_or_else(|| {
+        let blank = lines.iter().rev();
+        lines.len() - blank.take_while(|l| l.trim().is_empty()).count()
+    });
+    let blank = |line: Option<&&str>| line.is_none_or(|l| l.trim().is_empty());
+    let mut added = Vec::new();
+    for (_, section) in written {
+        if !added.is_empty() || (at > 0 && !blank(lines.get(at - 1))) {
+            added.push("");
+        }
+        added.extend(section);
+    }
+    if !added.is_empty() && !blank(lines.get(at)) {
+        added.push("");
+    }
+    lines.splice(at..at, added);
+    let mut content = lines.join("\n");
+    if old.ends_with('\n') || new.ends_with('\n') {
+        content.push('\n');
+    }
+    content
+}
+
+/// Where the INI section headed at `start` ends: before the next header,
+/// leaving out the blank lines and comments above that one.
+fn ini_section_end(lines: &[&str], start: usize) -> usize {
+    let next = lines[start + 1..]
+        .iter()
+        .position(|l| l.trim_start().starts_with('['))
+        .map_or(lines.len(), |i| start + 1 + i);
+    let trailing = lines[start + 1..next].iter().rev().take_while(|l| {
+        let l = l.trim();
+        l.is_empty() || l.starts_with('#')
+    });
+    next - trailing.count()
+}
+
+fn normalise(line: &str) -> String {
+    line.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+fn sections(
+    compositor: Compositor,
+    lines: &[&str],
+    name: &str,
+) -> Vec<Range<usize>> {
+    let mut ranges = Vec::new();
+    let mut i = 0;
+    while i < lines.len() {
+        let line = lines[i].trim();
+        let start = i;
+        i += 1;
+        if monitor_name(compositor, line) != Some(name) {
+            continue;
+        }
+        if compositor == Compositor::Wayfire {
+            i = ini_section_end(lines, start);
+        }
+        if matches!(compositor, Compositor::Sway)
+            && line.contains('{')
+            && !line.contains('}')
+        {
+            while i < lines.len() {
+                i += 1;
+                if lines[i - 1].trim_start().starts_with('}') {
+                    break;
+                }
+            }
+        }
+        ranges.push(start..i);
+    }
+    ranges
+}
+
+fn hyprland_monitor(line: &str) -> Option<&str> {
+    let rest = line.strip_prefix("monitor")?.trim_start();
+    let value = rest.strip_prefix('=')?;
+    value.split(',').next().map(str::trim).filter(|n| !n.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        compositor::format::render_monitor_config, layout::Layout,
+        test_support::monitor,
+    };
+
+    const SWAY: &str = "\
+output DP-1 {
+    mode 1920x1080@60Hz
+    pos 0 0
+}
+
+output HDMI-A-1 disable
+";
+
+    #[test]
+    fn test_collects_per_monitor_lines() {
+        let hypr = "monitor=DP-1,preferred,auto,1\n\
+                    monitor = DP-10, 1920x1080@60, 0x0, 1\n\
+                    monitor = DP-1, disable\n";
+        assert_eq!(
+            monitor_lines(Compositor::Hyprland, hypr, "DP-1"),
+            ["monitor=DP-1,preferred,auto,1", "monitor = DP-1, disable"]
+        );
+
+        assert_eq!(
+            monitor_lines(Compositor::Sway, SWAY, "DP-1"),
+            ["output DP-1 {", "mode 1920x1080@60Hz", "pos 0 0", "}"]
+        );
+
+        let river = "wlr-randr --output DP-1 --off\n";
+        assert_eq!(
+            monitor_lines(Compositor::River, river, "DP-1"),
+            ["wlr-randr --output DP-1 --off"]
+        );
+        assert!(monitor_lines(Compositor::River, river, "DP-2").is_empty());
+    }
+
+    #[test]
+    fn test_changed_monitors_ignore_whitespace() {
+        let old = "monitor = DP-1,  1920x1080@60, 0x0, 1\n";
+        let new = "monitor = DP-1, 1920x1080@60, 0x0, 1\n\
+                   monitor = DP-2, disable\n";
+        let changes = changed_monitors(
+            Compositor::Hyprland,
+            old,
+            new,
+            ["DP-1", "DP-2"],
+        );
+        assert_eq!(
+            changes,
+            [MonitorChange {
+                name: "DP-2".into(),
+                old: vec![],
+                new: vec!["monitor = DP-2, disable".into()],
+            }]
+        );
+    }
+
+    #[test]
+    fn test_keep_old_lines_restores_skipped_monitors() {
+        let new = "\
+output DP-1 {
+    mode 1920x1080@60Hz
+    pos 1920 0
+}
+
+output HDMI-A-1 disable
+";
+        let changes = changed_monitors(
+            Compositor::Sway,
+            SWAY,
+            new,
+            ["DP-1", "HDMI-A-1"],
+        );
+        assert_eq!(changes.len(), 1);
+
+        let kept = keep_old_lines(Compositor::Sway, new, &changes);
+        assert!(kept.contains("pos 0 0"));
+        assert!(!kept.contains("pos 1920 0"));
+        assert!(kept.contains("output HDMI-A-1 disable"));
+        assert!(kept.ends_with('\n'));
+
+        let dropped = MonitorChange {
+            name: "HDMI-A-1".into(),
+            old: vec![],
+            new: vec!["output HDMI-A-1 disable".into()],
+        };
+        let kept = keep_old_lines(Compositor::Sway, new, &[dropped]);
+        assert!(!kept.contains("HDMI-A-1"));
+    }
+
+    #[test]
+    fn test_kept_sections_come_back_as_written() {
+        let old = "\
+output DP-1 pos 0 0
+output  DP-2 {
+    modeline 241.5 2560 2608 2640 2720 1440 1443 1448 1481 +hsync
+    pos 1920 0
+}
+output HDMI-A-1 disable
+";
+        let new = "\
+# This file is managed by xwlm. Do not edit manually.
+
+output DP-1 pos 0 0
+output DP-2 pos 1920 0
+output HDMI-A-1 pos 4480 0
+";
+        let names = ["DP-2".to_string()];
+        let kept = keep_monitor_sections(Compositor::Sway, old, new, &names);
+        assert_eq!(
+            kept,
+            "\
+# This file is managed by xwlm. Do not edit manually.
+
+output DP-1 pos 0 0
+output  DP-2 {
+    modeline 241.5 2560 2608 2640 2720 1440 1443 1448 1481 +hsync
+    pos 1920 0
+}
+output HDMI-A-1 pos 4480 0
+"
+        );
+        assert_eq!(
+            keep_monitor_sections(Compositor::Sway, old, &kept, &names),
+            kept
+        );
+
+        // A monitor that led the old file goes above the generated ones;
+        // one the old file lacks is left out.
+        let names = ["DP-1".to_string(), "DP-9".to_string()];
+        let new = "output DP-9 pos 0 0\noutput DP-2 pos 1920 0\n";
+        assert_eq!(
+            keep_monitor_sections(Compositor::Sway, old, new, &names),
+            "output DP-1 pos 0 0\noutput DP-2 pos 1920 0\n"
+        );
+    }
+
+    #[test]
+    fn test_scale_filter_and_subpixel_survive_a_save() {
+        let old = "\
+output DP-1 {
+    mode 2560x1440@144.000Hz
+    # crisp text on the laptop panel
+    subpixel rgb
+    scale_filter nearest
+    pos 0 0
+    scale 1.00
+    transform normal
+}
+output DP-2 disable subpixel bgr
+";
+        let new = "\
+output DP-1 {
+    mode 2560x1440@144.000Hz
+    pos 0 0
+    scale 1.00
+    transform normal
+}
+output DP-2 disable
+";
+        let kept = keep_output_directives(Compositor::Sway, old, new);
+        assert_eq!(kept, old);
+        assert_eq!(keep_output_directives(Compositor::Sway, old, &kept), kept);
+
+        // A changed scale leaves them where they were, in order.
+        let new = new.replace("scale 1.00", "scale 1.50");
+        assert_eq!(
+            keep_output_directives(Compositor::Sway, old, &new),
+            old.replace("scale 1.00", "scale 1.50")
+        );
+
+        // Enabling the one-line monitor moves its subcommand into the block.
+        let new = "\
+output DP-2 {
+    mode 1920x1080@60.000Hz
+    pos 2560 0
+}
+";
+        assert_eq!(
+            keep_output_directives(Compositor::Sway, old, new),
+            "\
+output DP-2 {
+    mode 1920x1080@60.000Hz
+    pos 2560 0
+    subpixel bgr
+}
+"
+        );
+
+        let hypr = "monitor = DP-1, 1920x1080@60, 0x0, 1\n";
+        let kept = keep_output_directives(Compositor::Hyprland, old, hypr);
+        assert_eq!(kept, hypr);
+    }
+
+    #[test]
+    fn test_workspace_lines_stay_where_they_were() {
+        let old = "\
+# my monitors
+monitor = DP-1, 1920x1080@60, 0x0, 1
+workspace=1,monitor:\"DP-1\",default:true
+
+workspace = 2, monitor:DP-1
+monitor = DP-2, 1920x1080@60, 1920x0, 1
+workspace = 3, monitor:DP-2
+";
+        let new = "\
+# This file is managed by xwlm. Do not edit manually.
+
+monitor = DP-1, 1920x1080@60, 0x0, 1
+monitor = DP-2, 2560x1440@60, 1920x0, 1
+
+workspace = 1, monitor:DP-1,default:true
+workspace = 2, monitor:DP-2
+workspace = 3, monitor:DP-2
+workspace = 4, monitor:DP-1
+";
+        let kept = keep_workspace_places(Compositor::Hyprland, old, new);
+        assert_eq!(
+            kept,
+            "\
+# This file is managed by xwlm. Do not edit manually.
+
+monitor = DP-1, 1920x1080@60, 0x0, 1
+workspace=1,monitor:\"DP-1\",default:true
+
+workspace = 2, monitor:DP-2
+monitor = DP-2, 2560x1440@60, 1920x0, 1
+workspace = 3, monitor:DP-2
+workspace = 4, monitor:DP-1
+"
+        );
+
+        // A file xwlm wrote itself comes out as rendered.
+        assert_eq!(keep_workspace_places(Compositor::Hyprland, new, new), new);
+        let sway = "output DP-1 {\n    pos 0 0\n}\n\n\
+                    workspace 1 output DP-1\n\n";
+        assert_eq!(keep_workspace_places(Compositor::Sway, sway, sway), sway);
+    }
+
+    #[test]
+    fn test_river_scripts_keep_what_surrounds_the_block() {
+        let render = |x| {
+            let layout = Layout::capture(&[monitor("DP-1", x, 0)], &[]);
+            render_monitor_config(Compositor::River, &layout, &[]).unwrap()
+        };
+        let line = |x| {
+            let content = render(x);
+            let line = content.lines().find(|l| l.starts_with("wlr-randr"));
+            line.unwrap().to_string()
+        };
+        let new = render(10);
+        assert!(new.starts_with("#!/bin/sh\n# This file is managed by xwlm"));
+
+        let old = format!(
+            "#!/bin/bash\n# My outputs.\nset -e\nswaybg -i ~/wall.png &\n\
+             # >>> xwlm\n{}\n# <<< xwlm\nwaybar -o DP-1 &\n",
+            line(0)
+        );
+        let kept = keep_script_around(Compositor::River, &old, &new);
+        assert_eq!(
+            kept,
+            format!(
+                "#!/bin/bash\n# My outputs.\nset -e\nswaybg -i ~/wall.png &\n\
+                 # >>> xwlm\n{}\n# <<< xwlm\nwaybar -o DP-1 &\n",
+                line(10)
+            )
+        );
+        // Saving again changes nothing.
+        assert_eq!(keep_script_around(Compositor::River, &kept, &new), kept);
+        // A script xwlm wrote itself comes out as rendered.
+        let own = render(0);
+        assert_eq!(keep_script_around(Compositor::River, &own, &new), new);
+
+        // A script from before the markers keeps its own commands.
+        let unmarked = format!("set -e\n{}\nwaybar -o DP-1 &\n", line(0));
+        assert_eq!(
+            keep_script_around(Compositor::River, &unmarked, &new),
+            format!(
+                "#!/bin/sh\nset -e\n# >>> xwlm\n{}\n# <<< xwlm\n\
+                 waybar -o DP-1 &\n",
+                line(10)
+            )
+        );
+        assert_eq!(keep_script_around(Compositor::River, "", &new), new);
+        assert_eq!(keep_script_around(Compositor::Sway, &old, &new), new);
+    }
+
+    #[test]
+    fn test_keep_ini_around_adds_output_sections_to_a_plain_ini() {
+        let old = "[core]\nplugins = move\n\n[input]\nxkb_layout = us\n";
+        let new = "# This file is managed by xwlm.\n\n\
+                   [output:DP-1]\nposition = 0,0\n\n\
+                   [output:DP-2]\nmode = off\n";
+        let kept = keep_ini_around(Compositor::Wayfire, old, new);
+        assert_eq!(
+            kept,
+            "[core]\nplugins = move\n\n[input]\nxkb_layout = us\n\n\
+             [output:DP-1]\nposition = 0,0\n\n[output:DP-2]\nmode = off\n"
+        );
+        assert_eq!(keep_ini_around(Compositor::Wayfire, &kept, new), kept);
+        assert_eq!(keep_ini_around(Compositor::Sway, old, new), new);
+    }
+}
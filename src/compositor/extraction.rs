@@ -1,6 +1,15 @@
-use std::{env, path::PathBuf};
+use std::{
+    cmp::Reverse,
+    collections::BTreeMap,
+    fs,
+    path::{Path, PathBuf},
+};
 
-use crate::compositor::{Compositor, hyprland, sway};
+use crate::{
+    build_info::BUILD,
+    compositor::{Compositor, hyprland, river, sway},
+    utils,
+};
 
 #[derive(Debug)]
 pub struct ExtractionPlan {
@@ -8,7 +17,21 @@ pub struct ExtractionPlan {
     pub modified_files: Vec<(PathBuf, String)>,
     pub source_line: Option<String>,
     pub main_config: PathBuf,
+    /// The file the rules move to, named as asked for extraction.
+    pub output_path: PathBuf,
     pub source_exists: bool,
+    /// Where each monitor or output rule was found.
+    pub monitor_rules: Vec<ConfigLine>,
+}
+
+/// A monitor or output rule in a compositor config file.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConfigLine {
+    pub path: PathBuf,
+    /// 1-based number of the rule's first line.
+    pub line: usize,
+    /// The rule's lines, trimmed; more than one for a Sway output block.
+    pub text: String,
 }
 
 impl ExtractionPlan {
@@ -30,9 +53,11 @@ impl ExtractionPlan {
         let output_path = output_dir.join(output_filename);
 
         // Step 1: Write the monitors.conf file first
-        let comment = "# This file is managed by xwlm. Do not edit manually.\n\n";
-        let final_content = format!("{}{}", comment, self.output_content);
-        std::fs::write(&output_path, final_content)
+        let comment = "# This file is managed by xwlm. Do not edit manually.";
+        let written = format!("# Written by {}", BUILD.header());
+        let final_content =
+            format!("{comment}\n{written}\n\n{}", self.output_content);
+        utils::write_atomic(&output_path, &final_content)
             .map_err(|e| format!("Failed to write {}: {e}", output_path.display()))?;
 
         // Step 2: Write modified files, adding source line to main_config if needed
@@ -43,15 +68,18 @@ impl ExtractionPlan {
                 if !final_content.ends_with('\n') {
                     final_content.push('\n');
                 }
-                if let Some(ref line) = self.source_line {
+                // River's call already stands where its commands were.
+                if let Some(ref line) = self.source_line
+                    && !final_content.lines().any(|l| l.trim() == line)
+                {
                     final_content.push('\n');
                     final_content.push_str(line);
                     final_content.push('\n');
                 }
-                std::fs::write(path, final_content)
+                utils::write_atomic(path, &final_content)
                     .map_err(|e| format!("Failed to write {}: {e}", path.display()))?;
             } else {
-                std::fs::write(path, content)
+                utils::write_atomic(path, content)
                     .map_err(|e| format!("Failed to write {}: {e}", path.display()))?;
             }
         }
@@ -71,7 +99,7 @@ impl ExtractionPlan {
             content.push('\n');
             content.push_str(line);
             content.push('\n');
-            std::fs::write(&self.main_config, content)
+            utils::write_atomic(&self.main_config, &content)
                 .map_err(|e| format!("Failed to write {}: {e}", self.main_config.display()))?;
         }
 
@@ -86,19 +114,29 @@ impl ExtractionPlan {
             if let Some(path) = line.strip_prefix("include ") {
                 return extract_filename(path);
             }
+            if let Some(path) = line.strip_prefix("sh ") {
+                return extract_filename(path);
+            }
         }
-        "monitors.conf"
+        // The config already sources the file.
+        self.output_path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .unwrap_or("monitors.conf")
     }
 }
 
 pub fn main_config_path(compositor: Compositor) -> Option<PathBuf> {
-    let home = env::var("HOME").ok()?;
-    let path = match compositor {
-        Compositor::Hyprland => format!("{home}/.config/hypr/hyprland.conf"),
-        Compositor::Sway => format!("{home}/.config/sway/config"),
+    let home = utils::home_dir()?;
+    let p = match compositor {
+        Compositor::Hyprland => home.join(".config/hypr/hyprland.conf"),
+        Compositor::Sway => home.join(".config/sway/config"),
+        Compositor::River => home.join(".config/river/init"),
+        Compositor::Niri => home.join(".config/niri/config.kdl"),
+        Compositor::Wayfire => home.join(".config/wayfire.ini"),
+        Compositor::Labwc => home.join(".config/labwc/autostart"),
         _ => return None,
     };
-    let p = PathBuf::from(path);
     if p.exists() { Some(p) } else { None }
 }
 
@@ -115,6 +153,9 @@ pub fn extract_monitors(
     match compositor {
         Compositor::Hyprland => hyprland::extract(config_path, output_filename),
         Compositor::Sway => sway::extract(config_path, output_filename),
+        Compositor::River | Compositor::Labwc => {
+            river::extract(config_path, output_filename)
+        }
         _ => Err(format!(
             "Config extraction not supported for {}",
             compositor.label()
@@ -122,20 +163,251 @@ pub fn extract_monitors(
     }
 }
 
+/// Monitor rules in `main_config` and the files it sources that live
+/// outside `managed`, the file xwlm writes. Depending on source order they
+/// override xwlm's changes or get overridden by them.
+pub fn stray_monitor_rules(
+    compositor: Compositor,
+    main_config: &Path,
+    managed: &Path,
+) -> Vec<ConfigLine> {
+    let filename = managed
+        .file_name()
+        .and_then(|f| f.to_str())
+        .unwrap_or("monitors.conf");
+    let Ok(plan) = extract_monitors(main_config, compositor, filename) else {
+        return Vec::new();
+    };
+    let managed = managed
+        .canonicalize()
+        .unwrap_or_else(|_| managed.to_path_buf());
+    plan.monitor_rules
+        .into_iter()
+        .filter(|rule| rule.path != managed)
+        .collect()
+}
+
+/// Deletes `rules` from the files they were found in, leaving the rest of
+/// each file as it was. Fails without writing a file whose rules moved
+/// since they were found.
+pub fn remove_rules(rules: &[ConfigLine]) -> Result<(), String> {
+    let mut by_file: BTreeMap<&Path, Vec<&ConfigLine>> = BTreeMap::new();
+    for rule in rules {
+        by_file.entry(&rule.path).or_default().push(rule);
+    }
+    for (path, mut rules) in by_file {
+        let content = fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read {}: {e}", path.display()))?;
+        let mut lines: Vec<&str> = content.lines().collect();
+        rules.sort_by_key(|rule| Reverse(rule.line));
+        for rule in rules {
+            let start = rule.line.saturating_sub(1);
+            let end = start + rule.text.lines().count();
+            let found = lines.get(start..end).map(|found| {
+                found.iter().map(|l| l.trim()).collect::<Vec<_>>().join("\n")
+            });
+            if found.as_deref() != Some(rule.text.as_str()) {
+                return Err(format!(
+                    "{} changed since it was scanned",
+                    path.display()
+                ));
+            }
+            lines.drain(start..end);
+        }
+        let mut updated = lines.join("\n");
+        if content.ends_with('\n') {
+            updated.push('\n');
+        }
+        utils::write_atomic(path, &updated)
+            .map_err(|e| format!("Failed to write {}: {e}", path.display()))?;
+    }
+    Ok(())
+}
+
 pub fn resolve_path(base_dir: &std::path::Path, path: &str) -> PathBuf {
     let path = path.trim();
     if let Some(rest) = path.strip_prefix("~/")
-        && let Ok(home) = std::env::var("HOME")
+        && let Some(home) = utils::home_dir()
     {
-        return PathBuf::from(format!("{home}/{rest}"));
+        return home.join(rest);
     }
     let p = PathBuf::from(path);
     if p.is_absolute() { p } else { base_dir.join(p) }
 }
 
+/// The files a `source` or `include` of `path` reads, in the order the
+/// compositor reads them: the `.conf` files of a directory, or the files
+/// a `*` or `?` in the last part of the path matches, sorted by name.
+/// Any other path is read as it is.
+pub fn resolve_includes(base_dir: &Path, path: &str) -> Vec<PathBuf> {
+    let resolved = resolve_path(base_dir, path);
+    if resolved.is_dir() {
+        return files_in(&resolved, |name| name.ends_with(".conf"));
+    }
+    let pattern = resolved.file_name().and_then(|name| name.to_str());
+    if let Some(pattern) = pattern
+        && pattern.contains(['*', '?'])
+        && let Some(dir) = resolved.parent()
+    {
+        return files_in(dir, |name| wildcard_match(pattern, name));
+    }
+    vec![resolved]
+}
+
+/// The files in `dir` whose names `keep` takes, sorted, leaving out
+/// hidden ones as a shell glob does.
+fn files_in(dir: &Path, keep: impl Fn(&str) -> bool) -> Vec<PathBuf> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut files: Vec<PathBuf> = entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.is_file()
+                && path
+                    .file_name()
+                    .and_then(|name| name.to_str())
+                    .is_some_and(|name| !name.starts_with('.') && keep(name))
+        })
+        .collect();
+    files.sort();
+    files
+}
+
+/// Whether `name` matches `pattern`, where `*` stands for any run of
+/// characters and `?` for any one.
+fn wildcard_match(pattern: &str, name: &str) -> bool {
+    match pattern.chars().next() {
+        None => name.is_empty(),
+        Some('*') => {
+            let rest = &pattern[1..];
+            name.char_indices()
+                .map(|(i, _)| i)
+                .chain([name.len()])
+                .any(|i| wildcard_match(rest, &name[i..]))
+        }
+        Some(c) => name.chars().next().is_some_and(|n| {
+            let rest = (&pattern[c.len_utf8()..], &name[n.len_utf8()..]);
+            (c == '?' || c == n) && wildcard_match(rest.0, rest.1)
+        }),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::env;
+
+    fn scratch_dir(name: &str) -> PathBuf {
+        let dir = env::temp_dir()
+            .join(format!("xwlm-stray-{name}-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_stray_hyprland_rules_are_found_and_removed() {
+        let dir = scratch_dir("hypr");
+        let main = dir.join("hyprland.conf");
+        let managed = dir.join("monitors.conf");
+        fs::write(&managed, "monitor = DP-1, 2560x1440@144, 0x0, 1\n").unwrap();
+        fs::write(
+            &main,
+            format!(
+                "source = {}\nmonitor = DP-1, 1920x1080@60, 0x0, 1\n\
+                 input {{\n}}\n",
+                managed.display()
+            ),
+        )
+        .unwrap();
+
+        let rules = stray_monitor_rules(Compositor::Hyprland, &main, &managed);
+        assert_eq!(rules.len(), 1);
+        assert_eq!(rules[0].line, 2);
+        assert_eq!(rules[0].text, "monitor = DP-1, 1920x1080@60, 0x0, 1");
+
+        remove_rules(&rules).unwrap();
+        let content = fs::read_to_string(&main).unwrap();
+        assert!(content.starts_with("source = "));
+        assert!(!content.contains("1920x1080"));
+        assert!(content.ends_with("input {\n}\n"));
+        assert!(fs::read_to_string(&managed).unwrap().contains("2560x1440"));
+        assert!(
+            stray_monitor_rules(Compositor::Hyprland, &main, &managed)
+                .is_empty()
+        );
+        assert!(remove_rules(&rules).is_err());
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_included_directories_are_read_file_by_file() {
+        let dir = scratch_dir("dirs").canonicalize().unwrap();
+        let conf_d = dir.join("conf.d");
+        fs::create_dir_all(&conf_d).unwrap();
+        fs::write(conf_d.join("10-binds.conf"), "bind = SUPER, Q, exit\n")
+            .unwrap();
+        fs::write(
+            conf_d.join("20-outputs.conf"),
+            "monitor = DP-2, 1920x1080@60, 2560x0, 1\n\
+             output DP-2 pos 2560 0\n",
+        )
+        .unwrap();
+        fs::write(conf_d.join("notes.txt"), "monitor = DP-9, disable\n")
+            .unwrap();
+        let managed = dir.join("monitors.conf");
+        fs::write(&managed, "").unwrap();
+
+        let hypr = dir.join("hyprland.conf");
+        fs::write(&hypr, format!("source = {}/\n", conf_d.display())).unwrap();
+        let rules = stray_monitor_rules(Compositor::Hyprland, &hypr, &managed);
+        assert_eq!(rules.len(), 1);
+        assert_eq!(rules[0].path, conf_d.join("20-outputs.conf"));
+        assert_eq!(rules[0].line, 1);
+
+        let sway = dir.join("config");
+        fs::write(&sway, format!("include {}/*\n", conf_d.display())).unwrap();
+        let plan = extract_monitors(&sway, Compositor::Sway, "monitors.conf")
+            .unwrap();
+        assert_eq!(plan.monitor_rules.len(), 1);
+        assert_eq!(plan.monitor_rules[0].path, conf_d.join("20-outputs.conf"));
+        assert_eq!(plan.monitor_rules[0].line, 2);
+        assert_eq!(
+            resolve_includes(&dir, "conf.d/*.conf"),
+            [conf_d.join("10-binds.conf"), conf_d.join("20-outputs.conf")]
+        );
+        assert_eq!(
+            resolve_includes(&dir, "conf.d/?0-outputs.*"),
+            [conf_d.join("20-outputs.conf")]
+        );
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_stray_sway_block_is_one_rule() {
+        let dir = scratch_dir("sway");
+        let main = dir.join("config");
+        let managed = dir.join("output.conf");
+        fs::write(&managed, "output DP-1 pos 0 0\n").unwrap();
+        fs::write(
+            &main,
+            format!(
+                "output DP-1 {{\n    pos 1920 0\n}}\ninclude {}\n",
+                managed.display()
+            ),
+        )
+        .unwrap();
+
+        let rules = stray_monitor_rules(Compositor::Sway, &main, &managed);
+        assert_eq!(rules.len(), 1);
+        assert_eq!(rules[0].text, "output DP-1 {\npos 1920 0\n}");
+
+        remove_rules(&rules).unwrap();
+        let content = fs::read_to_string(&main).unwrap();
+        assert_eq!(content, format!("include {}\n", managed.display()));
+        fs::remove_dir_all(dir).unwrap();
+    }
 
     #[test]
     fn test_extract_filename_with_tilde() {
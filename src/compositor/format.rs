@@ -1,45 +1,298 @@
-use std::process::Command;
-use std::{io, path::PathBuf};
+use std::process::{Command, Output};
+use std::{
+    io,
+    path::{Path, PathBuf},
+};
 
-use wlx_monitors::{WlMonitor, WlTransform};
+use wlx_monitors::WlTransform;
 
-use crate::compositor::{workspace_config::WorkspaceRule, Compositor};
+use crate::{
+    build_info::BUILD,
+    compositor::{
+        Compositor, capabilities::ConfigStyle, diff,
+        modeline::cvt_reduced_blanking,
+        workspace_config::WorkspaceRule,
+    },
+    constants::TRANSFORMS,
+    layout::{Layout, LayoutMonitor},
+    utils,
+    xwlm_config::CustomMode,
+};
 
-pub fn reload(compositor: Compositor) {
-    let result = match compositor {
-        Compositor::Hyprland => Command::new("hyprctl").arg("reload").output(),
-        Compositor::Sway => Command::new("swaymsg").arg("reload").output(),
-        _ => return,
+/// Returns once the compositor has answered, or false when it has no
+/// reload command. labwc runs the monitor config at `config` instead.
+/// Fails when the command cannot run or exits unhappy.
+pub fn reload(compositor: Compositor, config: &Path) -> io::Result<bool> {
+    let caps = compositor.capabilities();
+    let output = match caps.reload_command {
+        Some([program, args @ ..]) => Command::new(program).args(args).output(),
+        _ if caps.reload_runs_config => Command::new("sh").arg(config).output(),
+        _ => return Ok(false),
+    }?;
+    succeeded(&output)?;
+    Ok(true)
+}
+
+/// The `hyprctl keyword monitor` calls that bring the compositor from the
+/// monitor config `old` to `new` without a reload, one per changed
+/// monitor line. `None` when only a reload will do: on compositors other
+/// than Hyprland, or when a monitor was added or dropped or anything
+/// besides monitor lines changed.
+pub fn live_commands(
+    compositor: Compositor,
+    old: &str,
+    new: &str,
+) -> Option<Vec<Vec<String>>> {
+    if compositor.capabilities().config_style != Some(ConfigStyle::Hyprland)
+    {
+        return None;
+    }
+    let monitors = |content| {
+        let mut names = diff::configured_monitors(compositor, content);
+        names.sort();
+        names
+    };
+    let others = |content: &str| -> Vec<String> {
+        content
+            .lines()
+            .filter(|l| diff::monitor_name(compositor, l.trim()).is_none())
+            .map(str::to_string)
+            .collect()
+    };
+    let names = monitors(old);
+    if names != monitors(new) || others(old) != others(new) {
+        return None;
+    }
+    let names = names.iter().map(String::as_str);
+    let commands = diff::changed_monitors(compositor, old, new, names)
+        .into_iter()
+        .flat_map(|change| change.new)
+        .filter_map(|line| {
+            let (_, value) = line.split_once('=')?;
+            let command = ["hyprctl", "keyword", "monitor", value.trim()];
+            Some(command.map(String::from).to_vec())
+        })
+        .collect();
+    Some(commands)
+}
+
+/// Runs `command`, e.g. the one [`apply_command`] gives. Fails like
+/// [`reload`].
+pub fn run_command(command: &[String]) -> io::Result<()> {
+    let Some((program, args)) = command.split_first() else {
+        return Ok(());
     };
-    if let Err(e) = result {
-        eprintln!("Failed to reload compositor: {e}");
+    succeeded(&Command::new(program).args(args).output()?)
+}
+
+/// Fails with what the command printed to stderr when it exited unhappy.
+fn succeeded(output: &Output) -> io::Result<()> {
+    if output.status.success() {
+        return Ok(());
+    }
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    let reason = stderr.trim();
+    Err(io::Error::other(if reason.is_empty() {
+        output.status.to_string()
+    } else {
+        reason.to_string()
+    }))
+}
+
+/// The command that applies `layout` on a compositor that takes it as
+/// arguments instead of from a file, `None` on the others.
+pub fn apply_command(
+    compositor: Compositor,
+    layout: &Layout,
+    custom_modes: &[CustomMode],
+) -> Option<Vec<String>> {
+    compositor
+        .capabilities()
+        .applies_by_command
+        .then(|| kscreen_doctor(&layout.monitors, custom_modes))
+}
+
+/// `kscreen-doctor output.DP-1.mode.1920x1080@60 output.DP-1.position.0,0
+/// ...`, one argument per setting.
+fn kscreen_doctor(
+    monitors: &[LayoutMonitor],
+    custom_modes: &[CustomMode],
+) -> Vec<String> {
+    let mut command = vec!["kscreen-doctor".to_string()];
+    for m in monitors {
+        let output = format!("output.{}", m.name);
+        if !m.enabled {
+            command.push(format!("{output}.disable"));
+            continue;
+        }
+        command.push(format!("{output}.enable"));
+        if let Some((mode, _)) = written_mode(m, custom_modes, "") {
+            command.push(format!("{output}.mode.{mode}"));
+        }
+        command.push(format!("{output}.position.{},{}", m.x, m.y));
+        command.push(format!("{output}.scale.{}", format_scale(m.scale)));
+        let rotation = kscreen_rotation(m.transform);
+        command.push(format!("{output}.rotation.{rotation}"));
     }
+    command
 }
 
+/// KScreen names rotations by where the top of the picture ends up.
+fn kscreen_rotation(t: WlTransform) -> &'static str {
+    match t {
+        WlTransform::Normal => "none",
+        WlTransform::Rotate90 => "left",
+        WlTransform::Rotate180 => "inverted",
+        WlTransform::Rotate270 => "right",
+        WlTransform::Flipped => "flipped",
+        WlTransform::Flipped90 => "flipped90",
+        WlTransform::Flipped180 => "flipped180",
+        WlTransform::Flipped270 => "flipped270",
+    }
+}
+
+/// Writes `layout` to `path`, keeping the lines the file has for the
+/// `unmanaged` monitors as they are. A file that changes is first backed
+/// up as `<path>.bak.1`, keeping `backup_count` backups.
 pub fn save_monitor_config(
     compositor: Compositor,
     path: &PathBuf,
-    monitors: &[WlMonitor],
-    workspaces: &[WorkspaceRule],
+    layout: &Layout,
+    custom_modes: &[CustomMode],
+    unmanaged: &[String],
+    backup_count: usize,
 ) -> io::Result<()> {
-    let content = match compositor {
-        Compositor::Hyprland => format_hyprland(monitors, workspaces),
-        Compositor::Sway => format_sway(monitors, workspaces),
-        Compositor::River => format_river(monitors),
-        Compositor::Unknown => return Ok(()),
+    let mut layout = layout.clone();
+    layout.monitors.retain(|m| !unmanaged.contains(&m.name));
+    let Some(content) = render_monitor_config(compositor, &layout, custom_modes)
+    else {
+        return Ok(());
+    };
+    let old = std::fs::read_to_string(path);
+    let content = match &old {
+        Ok(old) => diff::merge_into(compositor, old, &content, unmanaged),
+        Err(_) => content,
     };
-    let comment = "# This file is managed by xwlm. Do not edit manually.\n\n";
-    let final_content = format!("{}{}", comment, content);
-    std::fs::write(path, final_content)
+    if old.is_ok_and(|old| old != content) {
+        rotate_backup(path, backup_count)?;
+    }
+    utils::write_atomic(path, &content)?;
+    if compositor.capabilities().reload_runs_config {
+        utils::set_executable(path)?;
+    }
+    Ok(())
 }
 
-fn current_mode(monitor: &WlMonitor) -> (i32, i32, i32) {
-    monitor
-        .modes
+/// `<path>.bak.N`, the Nth newest backup of `path`.
+pub fn backup_path(path: &Path, n: usize) -> PathBuf {
+    let mut name = path.as_os_str().to_os_string();
+    name.push(format!(".bak.{n}"));
+    PathBuf::from(name)
+}
+
+/// Shifts the backups of `path` up by one, dropping `.bak.{count}`, and
+/// copies the file to `.bak.1`. The file itself stays, so writing it
+/// keeps its permissions and symlink. Does nothing and says false when
+/// `count` is 0 or there is no file yet.
+pub fn rotate_backup(path: &Path, count: usize) -> io::Result<bool> {
+    if count == 0 || !path.exists() {
+        return Ok(false);
+    }
+    for n in (1..count).rev() {
+        let from = backup_path(path, n);
+        if from.exists() {
+            std::fs::rename(&from, backup_path(path, n + 1))?;
+        }
+    }
+    std::fs::copy(path, backup_path(path, 1))?;
+    Ok(true)
+}
+
+pub fn render_monitor_config(
+    compositor: Compositor,
+    layout: &Layout,
+    custom_modes: &[CustomMode],
+) -> Option<String> {
+    let monitors = &layout.monitors;
+    let caps = compositor.capabilities();
+    // A layout saved on another compositor may say more than this one
+    // can take, so only what it supports is written.
+    let workspaces: Vec<WorkspaceRule> = layout
+        .workspaces
         .iter()
-        .find(|m| m.is_current)
-        .map(|m| (m.resolution.width, m.resolution.height, m.refresh_rate))
-        .unwrap_or((0, 0, 60))
+        .filter(|_| caps.supports_workspace_rules)
+        .map(|ws| WorkspaceRule {
+            fallbacks: match caps.supports_workspace_fallbacks {
+                true => ws.fallbacks.clone(),
+                false => Vec::new(),
+            },
+            is_default: ws.is_default && caps.supports_workspace_defaults,
+            is_persistent: ws.is_persistent && caps.supports_workspace_defaults,
+            ..ws.clone()
+        })
+        .collect();
+    let workspaces = &workspaces;
+    let content = match caps.config_style? {
+        ConfigStyle::Hyprland => {
+            format_hyprland(monitors, workspaces, custom_modes)
+        }
+        ConfigStyle::Sway => format_sway(monitors, workspaces, custom_modes),
+        ConfigStyle::WlrRandr => format_river(monitors, custom_modes),
+        ConfigStyle::NiriMsg => format_niri(monitors, custom_modes),
+        ConfigStyle::WayfireIni => format_wayfire(monitors, custom_modes),
+    };
+    let comment = "# This file is managed by xwlm. Do not edit manually.";
+    let written = format!("# Written by {}", BUILD.header());
+    // A script's shebang only works as its first line.
+    let (shebang, content) = match content.split_once('\n') {
+        Some((first, rest)) if first.starts_with("#!") => {
+            (format!("{first}\n"), rest)
+        }
+        _ => (String::new(), content.as_str()),
+    };
+    Some(format!("{shebang}{comment}\n{written}\n\n{content}"))
+}
+
+fn active_custom_mode<'a>(
+    monitor: &LayoutMonitor,
+    custom_modes: &'a [CustomMode],
+) -> Option<&'a CustomMode> {
+    custom_modes
+        .iter()
+        .find(|c| c.active && c.monitor == monitor.name)
+}
+
+/// `1920x1080@60` with `unit` after the rate and whether it is a custom
+/// mode, or `None` for a monitor that reports no modes, which is left to
+/// pick its own.
+fn written_mode(
+    monitor: &LayoutMonitor,
+    custom_modes: &[CustomMode],
+    unit: &str,
+) -> Option<(String, bool)> {
+    if let Some(c) = active_custom_mode(monitor, custom_modes) {
+        let (w, h, hz) = (c.width, c.height, c.refresh_rate);
+        return Some((format!("{w}x{h}@{hz}{unit}"), true));
+    }
+    monitor
+        .has_mode()
+        .then(|| (monitor.mode_name(unit), false))
+}
+
+fn hyprland_mode(
+    monitor: &LayoutMonitor,
+    custom_modes: &[CustomMode],
+) -> String {
+    if let Some(c) = active_custom_mode(monitor, custom_modes)
+        && let Some(modeline) =
+            cvt_reduced_blanking(c.width, c.height, c.refresh_rate)
+    {
+        return format!("modeline {modeline}");
+    }
+    if !monitor.has_mode() {
+        return "preferred".to_string();
+    }
+    monitor.mode_name("")
 }
 
 fn format_scale(scale: f64) -> String {
@@ -63,7 +316,7 @@ fn transform_to_hyprland(t: WlTransform) -> u8 {
     }
 }
 
-fn transform_to_sway(t: WlTransform) -> &'static str {
+pub fn transform_to_sway(t: WlTransform) -> &'static str {
     match t {
         WlTransform::Normal => "normal",
         WlTransform::Rotate90 => "90",
@@ -76,17 +329,43 @@ fn transform_to_sway(t: WlTransform) -> &'static str {
     }
 }
 
+/// The transform a sway or wlr-randr name such as "flipped-90" stands for.
+pub fn transform_from_sway(name: &str) -> Option<WlTransform> {
+    TRANSFORMS
+        .iter()
+        .copied()
+        .find(|&t| transform_to_sway(t) == name)
+}
+
+/// Wayfire puts the rotation before `_flipped`, e.g. "90_flipped".
+pub fn transform_to_wayfire(t: WlTransform) -> &'static str {
+    match t {
+        WlTransform::Flipped90 => "90_flipped",
+        WlTransform::Flipped180 => "180_flipped",
+        WlTransform::Flipped270 => "270_flipped",
+        t => transform_to_sway(t),
+    }
+}
+
+pub fn transform_from_wayfire(name: &str) -> Option<WlTransform> {
+    TRANSFORMS
+        .iter()
+        .copied()
+        .find(|&t| transform_to_wayfire(t) == name)
+}
+
 fn format_hyprland(
-    monitors: &[WlMonitor],
+    monitors: &[LayoutMonitor],
     workspaces: &[WorkspaceRule],
+    custom_modes: &[CustomMode],
 ) -> String {
     let mut lines = Vec::new();
     for m in monitors {
-        let (w, h, refresh) = current_mode(m);
+        let mode = hyprland_mode(m, custom_modes);
         let scale = format_scale(m.scale);
         let base = format!(
-            "monitor = {}, {}x{}@{}, {}x{}, {}",
-            m.name, w, h, refresh, m.position.x, m.position.y, scale,
+            "monitor = {}, {}, {}x{}, {}",
+            m.name, mode, m.x, m.y, scale,
         );
         if m.transform != WlTransform::Normal {
             lines.push(format!(
@@ -102,6 +381,8 @@ fn format_hyprland(
         }
     }
 
+    // Hyprland merges every rule for a workspace into one, the last
+    // monitor winning, so there is no way to write fallbacks.
     let ws_lines: Vec<String> = workspaces
         .iter()
         .map(|ws| {
@@ -124,25 +405,45 @@ fn format_hyprland(
     lines.join("\n")
 }
 
-fn format_sway(monitors: &[WlMonitor], workspaces: &[WorkspaceRule]) -> String {
+/// A workspace without a monitor has nothing to fall back from.
+fn fallbacks(ws: &WorkspaceRule) -> &[String] {
+    if ws.monitor.is_empty() {
+        &[]
+    } else {
+        &ws.fallbacks
+    }
+}
+
+fn format_sway(
+    monitors: &[LayoutMonitor],
+    workspaces: &[WorkspaceRule],
+    custom_modes: &[CustomMode],
+) -> String {
     let mut blocks = Vec::new();
     for m in monitors {
         if !m.enabled {
             blocks.push(format!("output {} disable", m.name));
             continue;
         }
-        let (w, h, refresh) = current_mode(m);
-        let scale = format_scale(m.scale);
-        let transform = transform_to_sway(m.transform);
-        blocks.push(format!(
-            "output {} {{\n    mode {}x{}@{}Hz\n    pos {} {}\n    scale {}\n    transform {}\n}}",
-            m.name, w, h, refresh, m.position.x, m.position.y, scale, transform,
-        ));
+        let mut block = vec![format!("output {} {{", m.name)];
+        if let Some((mode, custom)) = written_mode(m, custom_modes, "Hz") {
+            let flag = if custom { "--custom " } else { "" };
+            block.push(format!("    mode {flag}{mode}"));
+        }
+        block.push(format!("    pos {} {}", m.x, m.y));
+        block.push(format!("    scale {}", format_scale(m.scale)));
+        block.push(format!("    transform {}", transform_to_sway(m.transform)));
+        block.push("}".to_string());
+        blocks.push(block.join("\n"));
     }
 
     let ws_lines: Vec<String> = workspaces
         .iter()
-        .map(|ws| format!("workspace {} output {}", ws.id, ws.monitor))
+        .map(|ws| {
+            let outputs = std::iter::once(&ws.monitor).chain(fallbacks(ws));
+            let outputs: Vec<&str> = outputs.map(String::as_str).collect();
+            format!("workspace {} output {}", ws.id, outputs.join(" "))
+        })
         .collect();
     if !ws_lines.is_empty() {
         blocks.push(ws_lines.join("\n"));
@@ -152,21 +453,434 @@ fn format_sway(monitors: &[WlMonitor], workspaces: &[WorkspaceRule]) -> String {
     blocks.join("\n\n")
 }
 
-fn format_river(monitors: &[WlMonitor]) -> String {
-    let mut lines = vec!["#!/bin/sh".to_string()];
+fn format_river(
+    monitors: &[LayoutMonitor],
+    custom_modes: &[CustomMode],
+) -> String {
+    let (start, end) = diff::SCRIPT_BLOCK;
+    let mut lines = vec!["#!/bin/sh".to_string(), start.to_string()];
     for m in monitors {
         if !m.enabled {
             lines.push(format!("wlr-randr --output {} --off", m.name));
             continue;
         }
-        let (w, h, refresh) = current_mode(m);
+        let mode = match written_mode(m, custom_modes, "Hz") {
+            Some((mode, true)) => format!(" --custom-mode {mode}"),
+            Some((mode, false)) => format!(" --mode {mode}"),
+            None => String::new(),
+        };
         let scale = format_scale(m.scale);
         let transform = transform_to_sway(m.transform);
         lines.push(format!(
-            "wlr-randr --output {} --mode {}x{}@{}Hz --pos {},{} --scale {} --transform {}",
-            m.name, w, h, refresh, m.position.x, m.position.y, scale, transform,
+            "wlr-randr --output {} --on{} --pos {},{} --scale {} \
+             --transform {}",
+            m.name, mode, m.x, m.y, scale, transform,
         ));
     }
+    lines.push(end.to_string());
     lines.push(String::new());
     lines.join("\n")
 }
+
+/// `niri msg output` takes one setting per call, so each monitor gets a
+/// line per setting.
+fn format_niri(
+    monitors: &[LayoutMonitor],
+    custom_modes: &[CustomMode],
+) -> String {
+    let (start, end) = diff::SCRIPT_BLOCK;
+    let mut lines = vec!["#!/bin/sh".to_string(), start.to_string()];
+    for m in monitors {
+        let output = format!("niri msg output {}", m.name);
+        if !m.enabled {
+            lines.push(format!("{output} off"));
+            continue;
+        }
+        lines.push(format!("{output} on"));
+        if let Some((mode, custom)) = written_mode(m, custom_modes, "") {
+            let flag = if custom { "custom-mode" } else { "mode" };
+            lines.push(format!("{output} {flag} {mode}"));
+        }
+        lines.push(format!("{output} position set {} {}", m.x, m.y));
+        lines.push(format!("{output} scale {}", format_scale(m.scale)));
+        let transform = transform_to_sway(m.transform);
+        lines.push(format!("{output} transform {transform}"));
+    }
+    lines.push(end.to_string());
+    lines.push(String::new());
+    lines.join("\n")
+}
+
+/// Wayfire takes the refresh rate in mHz and switches a monitor off with
+/// `mode = off`.
+fn format_wayfire(
+    monitors: &[LayoutMonitor],
+    custom_modes: &[CustomMode],
+) -> String {
+    let mut sections = Vec::new();
+    for m in monitors {
+        let mut section = vec![format!("[output:{}]", m.name)];
+        if !m.enabled {
+            section.push("mode = off".to_string());
+            sections.push(section.join("\n"));
+            continue;
+        }
+        let mode = match active_custom_mode(m, custom_modes) {
+            Some(c) => Some((c.width, c.height, Some(c.refresh_rate * 1000))),
+            None => m.has_mode().then(|| {
+                let mhz = m.refresh_mhz.unwrap_or(m.refresh_rate * 1000);
+                (m.width, m.height, m.has_refresh().then_some(mhz))
+            }),
+        };
+        match mode {
+            Some((w, h, Some(mhz))) => {
+                section.push(format!("mode = {w}x{h}@{mhz}"))
+            }
+            Some((w, h, None)) => section.push(format!("mode = {w}x{h}")),
+            None => {}
+        }
+        section.push(format!("position = {},{}", m.x, m.y));
+        section.push(format!("scale = {}", format_scale(m.scale)));
+        let transform = transform_to_wayfire(m.transform);
+        section.push(format!("transform = {transform}"));
+        sections.push(section.join("\n"));
+    }
+    sections.push(String::new());
+    sections.join("\n\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        compositor::settings,
+        test_support::{disabled, monitor},
+    };
+
+    #[test]
+    fn test_active_custom_mode_is_written_per_compositor() {
+        let monitors: Vec<LayoutMonitor> =
+            [monitor("DP-1", 0, 0), monitor("DP-2", 1920, 0)]
+                .iter()
+                .map(LayoutMonitor::from_monitor)
+                .collect();
+        let custom_modes = vec![CustomMode {
+            monitor: "DP-1".into(),
+            width: 1920,
+            height: 1080,
+            refresh_rate: 60,
+            active: true,
+        }];
+
+        let hypr = format_hyprland(&monitors, &[], &custom_modes);
+        assert!(hypr.contains(
+            "monitor = DP-1, modeline 138.50 1920 1968 2000 2080 \
+             1080 1083 1088 1111 +hsync -vsync, 0x0, 1"
+        ));
+        assert!(hypr.contains("monitor = DP-2, 1920x1080@60, 1920x0, 1"));
+
+        let sway = format_sway(&monitors, &[], &custom_modes);
+        assert!(sway.contains("mode --custom 1920x1080@60Hz"));
+
+        let river = format_river(&monitors, &custom_modes);
+        assert!(
+            river.contains("--output DP-1 --on --custom-mode 1920x1080@60Hz")
+        );
+        assert!(river.contains("--output DP-2 --on --mode 1920x1080@60Hz"));
+
+        let niri = format_niri(&monitors, &custom_modes);
+        assert!(niri.contains("output DP-1 custom-mode 1920x1080@60\n"));
+        assert!(niri.contains("output DP-2 mode 1920x1080@60\n"));
+        assert!(niri.contains("output DP-2 position set 1920 0\n"));
+
+        let wayfire = format_wayfire(&monitors, &custom_modes);
+        assert!(wayfire.contains("[output:DP-1]\nmode = 1920x1080@60000\n"));
+        assert!(wayfire.contains("[output:DP-2]\nmode = 1920x1080@60000\n"));
+        assert!(wayfire.contains("position = 1920,0\n"));
+    }
+
+    #[test]
+    fn test_wayfire_transforms_read_back_as_written() {
+        for &transform in &TRANSFORMS {
+            let mut m = monitor("DP-1", 0, 0);
+            m.transform = transform;
+            let written =
+                format_wayfire(&[LayoutMonitor::from_monitor(&m)], &[]);
+            let read = settings::config_monitors(Compositor::Wayfire, &written);
+            assert_eq!(read[0].transform, Some(transform), "{written}");
+        }
+        let mut m = monitor("DP-1", 0, 0);
+        m.transform = WlTransform::Flipped90;
+        let written = format_wayfire(&[LayoutMonitor::from_monitor(&m)], &[]);
+        assert!(written.contains("transform = 90_flipped\n"));
+    }
+
+    #[test]
+    fn test_workspace_fallbacks_are_written_in_order() {
+        let rule = WorkspaceRule {
+            id: 3,
+            monitor: "DP-1".into(),
+            fallbacks: vec!["DP-2".into(), "eDP-1".into()],
+            is_default: true,
+            is_persistent: false,
+            line: None,
+        };
+        let hypr = format_hyprland(&[], std::slice::from_ref(&rule), &[]);
+        assert!(hypr.ends_with("\nworkspace = 3, monitor:DP-1,default:true\n"));
+        let sway = format_sway(&[], &[rule], &[]);
+        assert!(sway.contains("workspace 3 output DP-1 DP-2 eDP-1\n"));
+    }
+
+    #[test]
+    fn test_formats_twelve_monitors() {
+        let monitors: Vec<LayoutMonitor> = (0..12)
+            .map(|i| {
+                let mut m = monitor(&format!("DP-{i}"), i * 1920, 0);
+                m.enabled = i % 3 != 0;
+                LayoutMonitor::from_monitor(&m)
+            })
+            .collect();
+
+        let hypr = format_hyprland(&monitors, &[], &[]);
+        assert_eq!(hypr.matches("monitor = DP-").count(), 12 + 4);
+        assert!(hypr.contains("monitor = DP-11, 1920x1080@60, 21120x0, 1"));
+
+        let sway = format_sway(&monitors, &[], &[]);
+        assert_eq!(sway.matches("output DP-").count(), 12);
+        assert_eq!(sway.matches(" disable").count(), 4);
+
+        let river = format_river(&monitors, &[]);
+        assert_eq!(river.matches("wlr-randr --output").count(), 12);
+        // A monitor switched off before the script ran comes back on.
+        assert_eq!(river.matches(" --on ").count(), 8);
+        assert_eq!(river.matches(" --off").count(), 4);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_saving_a_river_script_keeps_its_exec_bit_and_commands() {
+        use std::{fs, os::unix::fs::PermissionsExt};
+
+        let path = std::env::temp_dir()
+            .join(format!("xwlm-river-{}.sh", std::process::id()));
+        fs::write(&path, "#!/bin/sh\nset -e\nswaybg -i wall.png &\n").unwrap();
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o755)).unwrap();
+        let mut layout = Layout::capture(&[monitor("DP-1", 0, 0)], &[]);
+        for x in [0, 1920, 1920] {
+            layout.monitors[0].x = x;
+            let comp = Compositor::River;
+            save_monitor_config(comp, &path, &layout, &[], &[], 0).unwrap();
+        }
+        let content = fs::read_to_string(&path).unwrap();
+        let mode = fs::metadata(&path).unwrap().permissions().mode();
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(mode & 0o777, 0o755);
+        assert!(content.starts_with(
+            "#!/bin/sh\nset -e\nswaybg -i wall.png &\n# >>> xwlm\n"
+        ));
+        assert_eq!(content.matches("wlr-randr").count(), 1);
+        assert!(content.contains("--pos 1920,0"));
+        assert!(content.ends_with("# <<< xwlm\n"));
+    }
+
+    #[test]
+    fn test_saving_keeps_where_workspace_rules_stood() {
+        use std::fs;
+
+        let path = std::env::temp_dir()
+            .join(format!("xwlm-workspaces-{}.conf", std::process::id()));
+        let old = "monitor = DP-1, 1920x1080@60, 0x0, 1\n\
+                   workspace = 1, monitor:DP-1\n\
+                   monitor = DP-2, 1920x1080@60, 1920x0, 1\n";
+        fs::write(&path, old).unwrap();
+        let rule = WorkspaceRule {
+            id: 1,
+            monitor: "DP-1".into(),
+            fallbacks: Vec::new(),
+            is_default: false,
+            is_persistent: false,
+            line: None,
+        };
+        let mut layout = Layout::capture(
+            &[monitor("DP-1", 0, 0), monitor("DP-2", 1920, 0)],
+            &[rule],
+        );
+        layout.monitors[1].x = 2000;
+        let comp = Compositor::Hyprland;
+        save_monitor_config(comp, &path, &layout, &[], &[], 0).unwrap();
+        let content = fs::read_to_string(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        let lines: Vec<&str> = content.lines().collect();
+        let at = |prefix: &str| {
+            lines.iter().position(|l| l.starts_with(prefix)).unwrap()
+        };
+        assert_eq!(at("workspace"), at("monitor = DP-1") + 1, "{content}");
+        assert!(content.contains("2000x0"), "{content}");
+    }
+
+    #[test]
+    fn test_saves_rotate_backups_of_a_changed_config() {
+        use std::fs;
+
+        let dir = std::env::temp_dir()
+            .join(format!("xwlm-backup-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("monitors.conf");
+        fs::write(&path, "# by hand\n").unwrap();
+        let mut layout = Layout::capture(&[monitor("DP-1", 0, 0)], &[]);
+        let mut save = |x| {
+            layout.monitors[0].x = x;
+            let comp = Compositor::Hyprland;
+            save_monitor_config(comp, &path, &layout, &[], &[], 2).unwrap();
+        };
+        save(0);
+        // Saving the same layout again leaves the backups alone.
+        save(0);
+        assert_eq!(
+            fs::read_to_string(backup_path(&path, 1)).unwrap(),
+            "# by hand\n"
+        );
+        save(1920);
+        save(3840);
+        let read = |n| fs::read_to_string(backup_path(&path, n)).unwrap();
+        let (newest, oldest) = (read(1), read(2));
+        let third = backup_path(&path, 3).exists();
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert!(newest.contains("DP-1, 1920x1080@60, 1920x0, 1"));
+        assert!(oldest.contains("DP-1, 1920x1080@60, 0x0, 1"));
+        assert!(!third);
+        assert_eq!(
+            backup_path(Path::new("/h/monitors.conf"), 3),
+            Path::new("/h/monitors.conf.bak.3")
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_saving_a_labwc_script_makes_it_executable() {
+        use std::{fs, os::unix::fs::PermissionsExt};
+
+        let path = std::env::temp_dir()
+            .join(format!("xwlm-labwc-{}.sh", std::process::id()));
+        fs::write(&path, "").unwrap();
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o640)).unwrap();
+        let layout = Layout::capture(&[monitor("DP-1", 1920, 0)], &[]);
+        save_monitor_config(Compositor::Labwc, &path, &layout, &[], &[], 0)
+            .unwrap();
+        let content = fs::read_to_string(&path).unwrap();
+        let mode = fs::metadata(&path).unwrap().permissions().mode();
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(mode & 0o777, 0o750);
+        assert!(content.contains(
+            "wlr-randr --output DP-1 --on --mode 1920x1080@60Hz --pos 1920,0"
+        ));
+    }
+
+    #[test]
+    fn test_kwin_applies_with_one_kscreen_doctor_call() {
+        let mut rotated = monitor("DP-2", 1920, 0);
+        rotated.scale = 1.5;
+        rotated.transform = WlTransform::Rotate90;
+        let monitors = [monitor("DP-1", 0, 0), rotated, disabled("DP-3")];
+        let layout = Layout::capture(&monitors, &[]);
+
+        let custom_modes = [CustomMode {
+            monitor: "DP-2".into(),
+            width: 2560,
+            height: 1440,
+            refresh_rate: 75,
+            active: true,
+        }];
+
+        assert_eq!(apply_command(Compositor::Sway, &layout, &[]), None);
+        let command =
+            apply_command(Compositor::Kwin, &layout, &custom_modes).unwrap();
+        assert_eq!(
+            command.join(" "),
+            "kscreen-doctor \
+             output.DP-1.enable output.DP-1.mode.1920x1080@60 \
+             output.DP-1.position.0,0 output.DP-1.scale.1 \
+             output.DP-1.rotation.none \
+             output.DP-2.enable output.DP-2.mode.2560x1440@75 \
+             output.DP-2.position.1920,0 output.DP-2.scale.1.50 \
+             output.DP-2.rotation.left \
+             output.DP-3.disable"
+        );
+    }
+
+    #[test]
+    fn test_saving_wayfire_ini_keeps_everything_but_output_sections() {
+        use std::fs;
+
+        const INI: &str = "\
+# Wayfire config
+[core]
+plugins = animate autostart command decoration move resize vswitch
+vwidth = 3
+xwayland = true
+
+[output:DP-1]
+mode = 2560x1440@144000
+position = 0,0
+# adaptive sync for games
+vrr = true
+
+[output:HDMI-A-2]
+mode = 1920x1080@60000
+position = 2560,0
+
+[input]
+xkb_layout = us,de
+
+[autostart]
+bar = waybar
+";
+        let path = std::env::temp_dir()
+            .join(format!("xwlm-wayfire-{}.ini", std::process::id()));
+        fs::write(&path, INI).unwrap();
+        let mut dp2 = monitor("DP-2", 1920, 0);
+        dp2.enabled = false;
+        let mut layout = Layout::capture(&[monitor("DP-1", 0, 0), dp2], &[]);
+        layout.monitors[0].scale = 1.25;
+        let save = |layout: &Layout| {
+            let comp = Compositor::Wayfire;
+            save_monitor_config(comp, &path, layout, &[], &[], 0).unwrap();
+            fs::read_to_string(&path).unwrap()
+        };
+        let content = save(&layout);
+        assert_eq!(save(&layout), content);
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(
+            content,
+            "\
+# Wayfire config
+[core]
+plugins = animate autostart command decoration move resize vswitch
+vwidth = 3
+xwayland = true
+
+[output:DP-1]
+mode = 1920x1080@60000
+position = 0,0
+scale = 1.25
+transform = normal
+# adaptive sync for games
+vrr = true
+
+[output:DP-2]
+mode = off
+
+[input]
+xkb_layout = us,de
+
+[autostart]
+bar = waybar
+"
+        );
+    }
+}
@@ -1,8 +1,11 @@
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::path::{Path, PathBuf};
 
-use crate::compositor::extraction::{ExtractionPlan, resolve_path};
+use crate::compositor::extraction::{
+    ConfigLine, ExtractionPlan, resolve_includes,
+};
 use crate::compositor::position::ConfigPosition;
+use crate::utils;
 
 pub fn extract(config_path: &Path, output_filename: &str) -> Result<ExtractionPlan, String> {
     let config_path = config_path
@@ -18,6 +21,7 @@ pub fn extract(config_path: &Path, output_filename: &str) -> Result<ExtractionPl
     let mut modified_files = Vec::new();
     let mut visited = HashSet::new();
     let mut source_exists = false;
+    let mut monitor_rules = Vec::new();
 
     visited.insert(config_path.clone());
 
@@ -28,6 +32,7 @@ pub fn extract(config_path: &Path, output_filename: &str) -> Result<ExtractionPl
         &mut modified_files,
         &mut visited,
         &mut source_exists,
+        &mut monitor_rules,
     )?;
 
     let output_content = if extracted.is_empty() {
@@ -53,14 +58,18 @@ pub fn extract(config_path: &Path, output_filename: &str) -> Result<ExtractionPl
         modified_files,
         source_line,
         main_config: config_path,
+        output_path,
         source_exists,
+        monitor_rules,
     })
 }
 
 fn get_source_path(output_path: &Path) -> String {
-    let home = std::env::var("HOME").unwrap_or_default();
+    let home = utils::home_dir();
 
-    if let Ok(stripped) = output_path.strip_prefix(&home) {
+    if let Some(stripped) =
+        home.and_then(|home| output_path.strip_prefix(home).ok())
+    {
         let stripped_str = stripped.display().to_string();
         if stripped_str.starts_with('/') {
             format!("~{}", stripped_str)
@@ -79,6 +88,7 @@ fn parse_file(
     modified_files: &mut Vec<(PathBuf, String)>,
     visited: &mut HashSet<PathBuf>,
     source_exists: &mut bool,
+    monitor_rules: &mut Vec<ConfigLine>,
 ) -> Result<(), String> {
     let content = std::fs::read_to_string(path)
         .map_err(|e| format!("Failed to read {}: {e}", path.display()))?;
@@ -92,7 +102,7 @@ fn parse_file(
         ));
     };
 
-    for line in content.lines() {
+    for (i, line) in content.lines().enumerate() {
         let trimmed = line.trim();
 
         if trimmed.is_empty() || trimmed.starts_with('#') {
@@ -100,6 +110,13 @@ fn parse_file(
             continue;
         }
 
+        if is_monitor_line(trimmed) {
+            monitor_rules.push(ConfigLine {
+                path: path.to_path_buf(),
+                line: i + 1,
+                text: trimmed.to_string(),
+            });
+        }
         if is_monitor_line(trimmed) || is_workspace_line(trimmed) {
             extracted.push(trimmed.to_string());
             file_modified = true;
@@ -107,30 +124,32 @@ fn parse_file(
         }
 
         if let Some(source_path_str) = parse_source_line(trimmed) {
-            let resolved = resolve_path(base_dir, &source_path_str);
-
-            if let Ok(canonical) = resolved.canonicalize() {
-                if canonical == output_path.canonicalize().unwrap_or_default() {
+            for resolved in resolve_includes(base_dir, &source_path_str) {
+                if let Ok(canonical) = resolved.canonicalize() {
+                    let output = output_path.canonicalize().unwrap_or_default();
+                    if canonical == output {
+                        *source_exists = true;
+                    }
+
+                    if !visited.contains(&canonical) {
+                        visited.insert(canonical.clone());
+                        parse_file(
+                            &canonical,
+                            output_path,
+                            extracted,
+                            modified_files,
+                            visited,
+                            source_exists,
+                            monitor_rules,
+                        )?;
+                    }
+                } else if resolved
+                    .file_name()
+                    .and_then(|f| output_path.file_name().map(|o| f == o))
+                    .unwrap_or(false)
+                {
                     *source_exists = true;
                 }
-
-                if !visited.contains(&canonical) {
-                    visited.insert(canonical.clone());
-                    parse_file(
-                        &canonical,
-                        output_path,
-                        extracted,
-                        modified_files,
-                        visited,
-                        source_exists,
-                    )?;
-                }
-            } else if resolved
-                .file_name()
-                .and_then(|f| output_path.file_name().map(|o| f == o))
-                .unwrap_or(false)
-            {
-                *source_exists = true;
             }
         }
 
@@ -180,7 +199,17 @@ fn parse_source_line(line: &str) -> Option<String> {
 }
 
 pub fn config_position(content: &str, monitor_name: &str) -> Option<ConfigPosition> {
-    let mut found_position: Option<ConfigPosition> = None;
+    config_positions(content)
+        .into_iter()
+        .find(|(name, _)| name == monitor_name)
+        .map(|(_, pos)| pos)
+}
+
+/// Every monitor `content` places, in the order they first get a position,
+/// read in one pass. A later line for the same monitor wins.
+pub fn config_positions(content: &str) -> Vec<(String, ConfigPosition)> {
+    let mut found: Vec<(String, ConfigPosition)> = Vec::new();
+    let mut index: HashMap<String, usize> = HashMap::new();
 
     for line in content.lines() {
         let line = line.trim();
@@ -198,10 +227,9 @@ pub fn config_position(content: &str, monitor_name: &str) -> Option<ConfigPositi
         // Split into comma-separated parts
         let parts: Vec<&str> = line.split(',').map(|p| p.trim()).collect();
 
-        // Must start with the target monitor name
-        if parts.first().copied() != Some(monitor_name) {
+        let Some(&name) = parts.first() else {
             continue;
-        }
+        };
 
         // Skip disabled entries, but don't stop — a later entry might re-enable it
         if parts.contains(&"disable") {
@@ -212,11 +240,18 @@ pub fn config_position(content: &str, monitor_name: &str) -> Option<ConfigPositi
         if let Some(pos_str) = parts.get(2)
             && let Some((x, y)) = parse_xy_position(pos_str)
         {
-            found_position = Some(ConfigPosition { x, y });
+            let pos = ConfigPosition { x, y };
+            match index.get(name) {
+                Some(&i) => found[i].1 = pos,
+                None => {
+                    index.insert(name.to_string(), found.len());
+                    found.push((name.to_string(), pos));
+                }
+            }
         }
     }
 
-    found_position
+    found
 }
 
 fn parse_xy_position(s: &str) -> Option<(i32, i32)> {
@@ -0,0 +1,481 @@
+use std::{
+    collections::HashMap,
+    env, fs, io,
+    path::PathBuf,
+    process::Command,
+    sync::mpsc,
+    thread,
+    time::Duration,
+};
+
+use serde_json::Value;
+use thiserror::Error;
+
+use crate::{
+    compositor::Compositor, constants::BAR_PROBE_MS, refresh, utils,
+};
+
+/// Layer-shell namespaces that belong to bars and panels.
+const BAR_NAMESPACES: [&str; 5] = ["bar", "panel", "ags", "eww", "quickshell"];
+
+/// A bar pinned to one output, which may need restarting when that output
+/// moves or goes away.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Bar {
+    /// e.g. "waybar"
+    pub app: String,
+    pub output: String,
+}
+
+#[derive(Error, Debug)]
+pub enum IpcError {
+    #[error("failed to run {command}: {source}")]
+    Spawn {
+        command: &'static str,
+        #[source]
+        source: io::Error,
+    },
+
+    #[error("{0}")]
+    Failed(String),
+
+    #[error("not supported on {0}")]
+    Unsupported(&'static str),
+}
+
+fn run(command: &'static str, args: &[&str]) -> Result<String, IpcError> {
+    let output = Command::new(command)
+        .args(args)
+        .output()
+        .map_err(|source| IpcError::Spawn { command, source })?;
+    let stdout = String::from_utf8_lossy(&output.stdout).into_owned();
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let msg = if stderr.trim().is_empty() { &stdout } else { &*stderr };
+        return Err(IpcError::Failed(msg.trim().to_string()));
+    }
+    Ok(stdout)
+}
+
+/// The outputs as JSON: `hyprctl monitors all -j` or `swaymsg -r -t
+/// get_outputs`. Piped, swaymsg prints JSON either way, so the text form
+/// is never asked for.
+fn outputs_json(compositor: Compositor) -> Option<Vec<Value>> {
+    let output = match compositor {
+        Compositor::Hyprland => run("hyprctl", &["monitors", "all", "-j"]),
+        Compositor::Sway => run("swaymsg", &["-r", "-t", "get_outputs"]),
+        _ => return None,
+    };
+    parse_outputs(&output.ok()?)
+}
+
+fn parse_outputs(json: &str) -> Option<Vec<Value>> {
+    match serde_json::from_str(json).ok()? {
+        Value::Array(outputs) => Some(outputs),
+        _ => None,
+    }
+}
+
+/// Whether each output is powered (DPMS on), keyed by connector name.
+/// Compositors without an IPC answer with an empty map.
+pub fn power_states(compositor: Compositor) -> HashMap<String, bool> {
+    outputs_json(compositor)
+        .map(|outputs| parse_power(&outputs))
+        .unwrap_or_default()
+}
+
+/// The modes each output accepts without a custom modeline, as
+/// `(width, height, mHz)` keyed by connector name. Empty when the compositor
+/// cannot be asked, in which case every mode counts as supported.
+pub fn available_modes(
+    compositor: Compositor,
+) -> HashMap<String, Vec<(i32, i32, i32)>> {
+    outputs_json(compositor)
+        .map(|outputs| parse_modes(&outputs))
+        .unwrap_or_default()
+}
+
+/// The output of the focused window, which is the terminal while xwlm
+/// reads keys from it.
+pub fn focused_window_output(compositor: Compositor) -> Option<String> {
+    match compositor {
+        Compositor::Hyprland => {
+            let window = run("hyprctl", &["activewindow", "-j"]).ok()?;
+            hyprland_window_output(
+                &serde_json::from_str(&window).ok()?,
+                &outputs_json(compositor)?,
+            )
+        }
+        Compositor::Sway => {
+            let tree = run("swaymsg", &["-r", "-t", "get_tree"]).ok()?;
+            sway_focused_output(&serde_json::from_str(&tree).ok()?)
+        }
+        _ => None,
+    }
+}
+
+/// Bars drawn on each output: the layer-shell surfaces Hyprland lists, or
+/// the outputs a running waybar is configured for elsewhere. Anything that
+/// cannot be asked in time counts as no bar.
+pub fn bars(compositor: Compositor) -> Vec<Bar> {
+    match compositor {
+        Compositor::Hyprland => {
+            let (tx, rx) = mpsc::channel();
+            thread::spawn(move || {
+                let _ = tx.send(run("hyprctl", &["layers", "-j"]));
+            });
+            match rx.recv_timeout(Duration::from_millis(BAR_PROBE_MS)) {
+                Ok(Ok(output)) => parse_hyprland_layers(&output),
+                _ => Vec::new(),
+            }
+        }
+        _ if is_running("waybar") => waybar_config()
+            .map(|config| waybar_outputs(&config))
+            .unwrap_or_default()
+            .into_iter()
+            .map(|output| Bar {
+                app: "waybar".to_string(),
+                output,
+            })
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// Whether Hyprland re-reads its config once a file of it is written,
+/// which it does unless `misc:disable_autoreload` is set. Taken as yes
+/// when `hyprctl` cannot tell; no on other compositors.
+pub fn autoreloads(compositor: Compositor) -> bool {
+    if compositor != Compositor::Hyprland {
+        return false;
+    }
+    let args = ["getoption", "misc:disable_autoreload", "-j"];
+    run("hyprctl", &args)
+        .ok()
+        .and_then(|out| serde_json::from_str::<Value>(&out).ok())
+        .and_then(|option| option["int"].as_i64())
+        .is_none_or(|disabled| disabled == 0)
+}
+
+fn is_running(program: &str) -> bool {
+    let Ok(entries) = fs::read_dir("/proc") else {
+        return false;
+    };
+    entries.flatten().any(|entry| {
+        fs::read_to_string(entry.path().join("comm"))
+            .is_ok_and(|comm| comm.trim() == program)
+    })
+}
+
+fn waybar_config() -> Option<String> {
+    let dir = match env::var_os("XDG_CONFIG_HOME") {
+        Some(dir) if !dir.is_empty() => PathBuf::from(dir),
+        _ => utils::home_dir()?.join(".config"),
+    };
+    ["config", "config.jsonc"]
+        .iter()
+        .find_map(|name| fs::read_to_string(dir.join("waybar").join(name)).ok())
+}
+
+/// The outputs named by `"output"` keys in a waybar config, either one
+/// string or a list. Exclusions such as `"!DP-1"` are left out.
+fn waybar_outputs(config: &str) -> Vec<String> {
+    let mut outputs: Vec<String> = Vec::new();
+    for (at, _) in config.match_indices("\"output\"") {
+        let rest = config[at + "\"output\"".len()..].trim_start();
+        let Some(rest) = rest.strip_prefix(':') else {
+            continue;
+        };
+        let rest = rest.trim_start();
+        let value = match rest.strip_prefix('[') {
+            Some(list) => list.split(']').next().unwrap_or(""),
+            None => rest.split([',', '\n', '}']).next().unwrap_or(""),
+        };
+        for name in value.split('"').skip(1).step_by(2) {
+            if !name.starts_with('!') && !outputs.iter().any(|o| o == name) {
+                outputs.push(name.to_string());
+            }
+        }
+    }
+    outputs
+}
+
+/// `hyprctl layers -j`: surfaces on the bottom and top levels of each
+/// monitor whose namespace looks like a bar.
+fn parse_hyprland_layers(json: &str) -> Vec<Bar> {
+    let Ok(Value::Object(monitors)) = serde_json::from_str(json) else {
+        return Vec::new();
+    };
+    let mut bars: Vec<Bar> = Vec::new();
+    for (output, monitor) in &monitors {
+        let surfaces = ["1", "2"]
+            .iter()
+            .filter_map(|level| monitor["levels"][level].as_array())
+            .flatten();
+        for surface in surfaces {
+            let namespace = surface["namespace"].as_str().unwrap_or("");
+            let is_bar = BAR_NAMESPACES.iter().any(|b| namespace.contains(b));
+            let bar = Bar {
+                app: namespace.to_string(),
+                output: output.clone(),
+            };
+            if is_bar && !bars.contains(&bar) {
+                bars.push(bar);
+            }
+        }
+    }
+    bars
+}
+
+pub fn set_power(
+    compositor: Compositor,
+    name: &str,
+    on: bool,
+) -> Result<(), IpcError> {
+    let state = if on { "on" } else { "off" };
+    match compositor {
+        Compositor::Hyprland => {
+            let out = run("hyprctl", &["dispatch", "dpms", state, name])?;
+            if out.trim() != "ok" {
+                return Err(IpcError::Failed(out.trim().to_string()));
+            }
+        }
+        Compositor::Sway => {
+            run("swaymsg", &["output", name, "power", state])?;
+        }
+        other => return Err(IpcError::Unsupported(other.label())),
+    }
+    Ok(())
+}
+
+/// Hyprland says `dpmsStatus`, Sway `power` since 1.8 and `dpms` before.
+fn parse_power(outputs: &[Value]) -> HashMap<String, bool> {
+    outputs
+        .iter()
+        .filter_map(|output| {
+            let name = output["name"].as_str()?;
+            let on = ["dpmsStatus", "power", "dpms"]
+                .iter()
+                .find_map(|key| output[key].as_bool())?;
+            Some((name.to_string(), on))
+        })
+        .collect()
+}
+
+/// The monitor `activewindow -j` names by id, or the focused one when no
+/// window has focus.
+fn hyprland_window_output(
+    window: &Value,
+    monitors: &[Value],
+) -> Option<String> {
+    let id = window["monitor"].as_i64();
+    monitors
+        .iter()
+        .find(|m| match id {
+            Some(id) => m["id"].as_i64() == Some(id),
+            None => m["focused"].as_bool() == Some(true),
+        })
+        .and_then(|m| m["name"].as_str())
+        .map(str::to_string)
+}
+
+/// The output holding the focused node of a `get_tree` answer.
+fn sway_focused_output(tree: &Value) -> Option<String> {
+    fn walk(node: &Value, output: Option<&str>) -> Option<String> {
+        let output = match node["type"].as_str() {
+            Some("output") => node["name"].as_str(),
+            _ => output,
+        };
+        if node["focused"].as_bool() == Some(true) {
+            return output.map(str::to_string);
+        }
+        ["nodes", "floating_nodes"]
+            .iter()
+            .filter_map(|key| node[key].as_array())
+            .flatten()
+            .find_map(|child| walk(child, output))
+    }
+    walk(tree, None)
+}
+
+/// Hyprland lists `"availableModes": ["2560x1440@143.99Hz", …]`, Sway
+/// `"modes": [{"width": 640, "height": 480, "refresh": 59940}, …]`.
+fn parse_modes(outputs: &[Value]) -> HashMap<String, Vec<(i32, i32, i32)>> {
+    let size = |v: &Value| i32::try_from(v.as_i64()?).ok();
+    outputs
+        .iter()
+        .filter_map(|output| {
+            let name = output["name"].as_str()?;
+            let modes = if let Some(list) = output["availableModes"].as_array()
+            {
+                list.iter()
+                    .filter_map(|m| {
+                        let m = m.as_str()?.trim_end_matches("Hz");
+                        let (size, hz) = m.split_once('@')?;
+                        parse_mode(size, hz)
+                    })
+                    .collect()
+            } else {
+                output["modes"]
+                    .as_array()?
+                    .iter()
+                    .filter_map(|m| {
+                        let (w, h) = (size(&m["width"])?, size(&m["height"])?);
+                        Some((w, h, size(&m["refresh"])?))
+                    })
+                    .collect()
+            };
+            Some((name.to_string(), modes))
+        })
+        .collect()
+}
+
+fn parse_mode(size: &str, hz: &str) -> Option<(i32, i32, i32)> {
+    let (w, h) = size.split_once('x')?;
+    Some((w.parse().ok()?, h.parse().ok()?, refresh::parse(hz)?))
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_hyprland_power() {
+        let output = r#"[
+  {"id": 0, "name": "DP-1", "dpmsStatus": true, "vrr": false},
+  {"id": 1, "name": "HDMI-A-1", "dpmsStatus": false}
+]"#;
+        let states = parse_power(&parse_outputs(output).unwrap());
+        assert_eq!(states.get("DP-1"), Some(&true));
+        assert_eq!(states.get("HDMI-A-1"), Some(&false));
+    }
+
+    #[test]
+    fn test_parse_available_modes() {
+        let hyprland = r#"[{
+  "id": 0,
+  "name": "DP-1",
+  "availableModes": ["2560x1440@143.99Hz", "1920x1080@60.00Hz"]
+}]"#;
+        let modes = parse_modes(&parse_outputs(hyprland).unwrap());
+        assert_eq!(
+            modes["DP-1"],
+            [(2560, 1440, 143_990), (1920, 1080, 60_000)]
+        );
+
+        let sway = r#"[{
+  "name": "DP-1",
+  "focused": true,
+  "modes": [
+    {"width": 3840, "height": 2160, "refresh": 59997},
+    {"width": 1920, "height": 1080, "refresh": 60000}
+  ],
+  "rect": {"x": 0, "y": 0, "width": 3840, "height": 2160}
+}]"#;
+        let modes = parse_modes(&parse_outputs(sway).unwrap());
+        assert_eq!(
+            modes["DP-1"],
+            [(3840, 2160, 59_997), (1920, 1080, 60_000)]
+        );
+    }
+
+    #[test]
+    fn test_focused_window_output() {
+        // The terminal sits on DP-1 while the cursor is on HDMI-A-1.
+        let monitors = parse_outputs(
+            r#"[
+  {"id": 0, "name": "DP-1", "focused": false},
+  {"id": 1, "name": "HDMI-A-1", "focused": true}
+]"#,
+        )
+        .unwrap();
+        let window: Value =
+            serde_json::from_str(r#"{"class": "foot", "monitor": 0}"#).unwrap();
+        assert_eq!(
+            hyprland_window_output(&window, &monitors).as_deref(),
+            Some("DP-1")
+        );
+        let none: Value = serde_json::from_str("{}").unwrap();
+        assert_eq!(
+            hyprland_window_output(&none, &monitors).as_deref(),
+            Some("HDMI-A-1")
+        );
+
+        let tree: Value = serde_json::from_str(
+            r#"{"type": "root", "nodes": [
+  {"type": "output", "name": "DP-1", "focused": false, "nodes": [
+    {"type": "workspace", "name": "1", "nodes": []}
+  ]},
+  {"type": "output", "name": "eDP-1", "nodes": [
+    {"type": "workspace", "name": "2", "nodes": [], "floating_nodes": [
+      {"type": "floating_con", "app_id": "foot", "focused": true}
+    ]}
+  ]}
+]}"#,
+        )
+        .unwrap();
+        assert_eq!(sway_focused_output(&tree).as_deref(), Some("eDP-1"));
+        assert_eq!(sway_focused_output(&none), None);
+    }
+
+    #[test]
+    fn test_parse_hyprland_layers_finds_bars() {
+        let output = r#"{
+  "DP-1": {"levels": {
+    "0": [{"address": "0x55d2b7d3c0a0", "x": 0, "y": 0, "w": 2560,
+           "h": 1440, "namespace": "hyprpaper", "pid": 11}],
+    "1": [],
+    "2": [{"address": "0x55d2b7e2f5b0", "x": 0, "y": 0, "w": 2560,
+           "h": 34, "namespace": "waybar", "pid": 12}],
+    "3": [{"address": "0x55d2b7e2f5c0", "x": 0, "y": 0, "w": 300,
+           "h": 80, "namespace": "notifications", "pid": 13}]
+  }},
+  "HDMI-A-1": {"levels": {
+    "2": [{"address": "0x55d2b7e2f5d0", "x": 0, "y": 0, "w": 1920,
+           "h": 30, "namespace": "bar-1", "pid": 14}]
+  }}
+}"#;
+        assert_eq!(
+            parse_hyprland_layers(output),
+            [
+                Bar {
+                    app: "waybar".into(),
+                    output: "DP-1".into(),
+                },
+                Bar {
+                    app: "bar-1".into(),
+                    output: "HDMI-A-1".into(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_waybar_outputs() {
+        let config = r#"[
+  {
+    // the main bar
+    "layer": "top",
+    "output": ["DP-1", "!HDMI-A-1", "eDP-1"],
+    "modules-left": ["hyprland/workspaces"]
+  },
+  { "output": "DP-2", "position": "bottom" },
+  { "position": "top" }
+]"#;
+        assert_eq!(waybar_outputs(config), ["DP-1", "eDP-1", "DP-2"]);
+        assert!(waybar_outputs("{ \"layer\": \"top\" }").is_empty());
+    }
+
+    #[test]
+    fn test_parse_sway_power_old_and_new() {
+        let output = r#"[
+  {"name": "DP-1", "focused": true, "power": true, "dpms": true},
+  {"name": "eDP-1", "dpms": false}
+]"#;
+        let states = parse_power(&parse_outputs(output).unwrap());
+        assert_eq!(states.get("DP-1"), Some(&true));
+        assert_eq!(states.get("eDP-1"), Some(&false));
+        assert!(parse_outputs("Output DP-1 'x'").is_none());
+    }
+}
@@ -1,32 +1,63 @@
+pub mod capabilities;
+pub mod check;
+pub mod diff;
 pub mod extraction;
 pub mod format;
 mod hyprland;
+pub mod ipc;
+pub mod modeline;
 pub mod position;
+mod river;
+pub mod settings;
 mod sway;
 pub mod workspace_config;
 
 use std::env;
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum Compositor {
     Hyprland,
     Sway,
     River,
+    Niri,
+    Wayfire,
+    Labwc,
+    Kwin,
     Unknown,
 }
 
 impl Compositor {
+    pub const ALL: [Compositor; 8] = [
+        Compositor::Hyprland,
+        Compositor::Sway,
+        Compositor::River,
+        Compositor::Niri,
+        Compositor::Wayfire,
+        Compositor::Labwc,
+        Compositor::Kwin,
+        Compositor::Unknown,
+    ];
+
     pub fn label(self) -> &'static str {
         match self {
             Compositor::Hyprland => "Hyprland",
             Compositor::Sway => "Sway",
             Compositor::River => "River",
-            Compositor::Unknown => "Unknown",
+            Compositor::Niri => "Niri",
+            Compositor::Wayfire => "Wayfire",
+            Compositor::Labwc => "labwc",
+            Compositor::Kwin => "KWin",
+            // Anything else speaking wlr-output-management.
+            Compositor::Unknown => "wlr-generic",
         }
     }
 
-    pub fn supports_workspace_defaults(self) -> bool {
-        matches!(self, Compositor::Hyprland)
+    /// The compositor [`Compositor::label`] names, e.g. in a debug dump.
+    pub fn from_label(label: &str) -> Self {
+        Compositor::ALL
+            .into_iter()
+            .find(|comp| comp.label() == label)
+            .unwrap_or(Compositor::Unknown)
     }
 }
 
@@ -39,6 +70,20 @@ pub fn detect() -> Compositor {
         return Compositor::Sway;
     }
 
+    if env::var_os("NIRI_SOCKET").is_some() {
+        return Compositor::Niri;
+    }
+
+    if env::var_os("WAYFIRE_SOCKET").is_some() {
+        return Compositor::Wayfire;
+    }
+
+    // Plasma on X11 sets these too, and has no Wayland monitors to manage.
+    let wayland = env::var_os("WAYLAND_DISPLAY").is_some();
+    if env::var_os("KDE_FULL_SESSION").is_some() && wayland {
+        return Compositor::Kwin;
+    }
+
     if let Ok(desktop) = env::var("XDG_CURRENT_DESKTOP") {
         let lower = desktop.to_ascii_lowercase();
         for entry in lower.split(':') {
@@ -46,6 +91,10 @@ pub fn detect() -> Compositor {
                 "hyprland" => return Compositor::Hyprland,
                 "sway" => return Compositor::Sway,
                 "river" => return Compositor::River,
+                "niri" => return Compositor::Niri,
+                "wayfire" => return Compositor::Wayfire,
+                "labwc" => return Compositor::Labwc,
+                "kde" if wayland => return Compositor::Kwin,
                 _ => {}
             }
         }
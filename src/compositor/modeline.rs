@@ -0,0 +1,148 @@
+use std::fmt;
+
+use crate::refresh;
+
+// VESA CVT 1.2 reduced blanking (v1) constants.
+const RB_H_BLANK: i32 = 160;
+const RB_H_SYNC: i32 = 32;
+const RB_H_FRONT_PORCH: i32 = 48;
+const RB_V_FRONT_PORCH: i32 = 3;
+const RB_MIN_V_BACK_PORCH: i32 = 6;
+const RB_MIN_V_BLANK_US: f64 = 460.0;
+const CLOCK_STEP_MHZ: f64 = 0.25;
+const CELL_GRANULARITY: i32 = 8;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Modeline {
+    pub clock_mhz: f64,
+    pub hdisplay: i32,
+    pub hsync_start: i32,
+    pub hsync_end: i32,
+    pub htotal: i32,
+    pub vdisplay: i32,
+    pub vsync_start: i32,
+    pub vsync_end: i32,
+    pub vtotal: i32,
+}
+
+impl fmt::Display for Modeline {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:.2} {} {} {} {} {} {} {} {} +hsync -vsync",
+            self.clock_mhz,
+            self.hdisplay,
+            self.hsync_start,
+            self.hsync_end,
+            self.htotal,
+            self.vdisplay,
+            self.vsync_start,
+            self.vsync_end,
+            self.vtotal,
+        )
+    }
+}
+
+/// Vertical sync width is how CVT encodes the aspect ratio.
+fn vsync_width(width: i32, height: i32) -> i32 {
+    let is = |w: i32, h: i32| width * h == height * w;
+    if is(4, 3) {
+        4
+    } else if is(16, 9) {
+        5
+    } else if is(16, 10) {
+        6
+    } else if is(5, 4) || is(15, 9) {
+        7
+    } else {
+        10
+    }
+}
+
+/// Same timings as `cvt -r <width> <height> <refresh>`.
+pub fn cvt_reduced_blanking(
+    width: i32,
+    height: i32,
+    refresh_rate: i32,
+) -> Option<Modeline> {
+    if width <= 0 || height <= 0 || refresh_rate <= 0 {
+        return None;
+    }
+    let hdisplay = width - width % CELL_GRANULARITY;
+    let vsync = vsync_width(width, height);
+
+    let frame_us = 1_000_000.0 / f64::from(refresh_rate);
+    let h_period_est = (frame_us - RB_MIN_V_BLANK_US) / f64::from(height);
+    if h_period_est <= 0.0 {
+        return None;
+    }
+    let vbi_lines = (RB_MIN_V_BLANK_US / h_period_est).floor() as i32 + 1;
+    let min_vbi_lines = RB_V_FRONT_PORCH + vsync + RB_MIN_V_BACK_PORCH;
+    let vtotal = height + vbi_lines.max(min_vbi_lines);
+    let htotal = hdisplay + RB_H_BLANK;
+
+    let clock = f64::from(refresh_rate) * f64::from(vtotal) * f64::from(htotal)
+        / 1_000_000.0;
+    let clock_mhz = (clock / CLOCK_STEP_MHZ).floor() * CLOCK_STEP_MHZ;
+
+    let hsync_start = hdisplay + RB_H_FRONT_PORCH;
+    let vsync_start = height + RB_V_FRONT_PORCH;
+    Some(Modeline {
+        clock_mhz,
+        hdisplay,
+        hsync_start,
+        hsync_end: hsync_start + RB_H_SYNC,
+        htotal,
+        vdisplay: height,
+        vsync_start,
+        vsync_end: vsync_start + vsync,
+        vtotal,
+    })
+}
+
+/// Parses `WIDTHxHEIGHT@REFRESH`, e.g. `3840x1080@120` or
+/// `1920x1080@59.94`, the refresh rate in mHz.
+pub fn parse_mode(input: &str) -> Option<(i32, i32, i32)> {
+    let (size, hz) = input.trim().split_once('@')?;
+    let (w, h) = size.split_once('x')?;
+    let (w, h, r) = (w.parse().ok()?, h.parse().ok()?, refresh::parse(hz)?);
+    (w > 0 && h > 0).then_some((w, h, r))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_matches_cvt_reduced_blanking() {
+        let m = cvt_reduced_blanking(1920, 1080, 60).unwrap();
+        assert_eq!(
+            m.to_string(),
+            "138.50 1920 1968 2000 2080 1080 1083 1088 1111 +hsync -vsync"
+        );
+
+        let m = cvt_reduced_blanking(2560, 1440, 60).unwrap();
+        assert_eq!(
+            m.to_string(),
+            "241.50 2560 2608 2640 2720 1440 1443 1448 1481 +hsync -vsync"
+        );
+    }
+
+    #[test]
+    fn test_non_standard_aspect_uses_wide_vsync() {
+        let m = cvt_reduced_blanking(3840, 1080, 120).unwrap();
+        assert_eq!(m.vsync_end - m.vsync_start, 10);
+        assert_eq!(m.htotal, 4000);
+    }
+
+    #[test]
+    fn test_parse_mode() {
+        let parsed = parse_mode("3840x1080@120");
+        assert_eq!(parsed, Some((3840, 1080, 120_000)));
+        let parsed = parse_mode(" 1920x1080@59.94Hz ");
+        assert_eq!(parsed, Some((1920, 1080, 59_940)));
+        assert_eq!(parse_mode("1920x1080"), None);
+        assert_eq!(parse_mode("0x1080@60"), None);
+        assert_eq!(parse_mode("axb@c"), None);
+    }
+}
@@ -1,5 +1,3 @@
-use std::{fs, path::PathBuf};
-
 use crate::compositor::{hyprland, sway, Compositor};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -8,20 +6,27 @@ pub struct ConfigPosition {
     pub y: i32,
 }
 
-pub fn get_position(
+pub fn config_position(
     compositor: Compositor,
-    config_path: &PathBuf,
+    content: &str,
     monitor_name: &str,
 ) -> Option<ConfigPosition> {
-    if !config_path.exists() {
-        return None;
+    match compositor {
+        Compositor::Hyprland => hyprland::config_position(content, monitor_name),
+        Compositor::Sway => sway::config_position(content, monitor_name),
+        _ => None,
     }
+}
 
-    let content = fs::read_to_string(config_path).ok()?;
-
+/// [`config_position`] for every monitor at once, in one pass over
+/// `content`.
+pub fn config_positions(
+    compositor: Compositor,
+    content: &str,
+) -> Vec<(String, ConfigPosition)> {
     match compositor {
-        Compositor::Hyprland => hyprland::config_position(&content, monitor_name),
-        Compositor::Sway => sway::config_position(&content, monitor_name),
-        _ => None,
+        Compositor::Hyprland => hyprland::config_positions(content),
+        Compositor::Sway => sway::config_positions(content),
+        _ => Vec::new(),
     }
 }
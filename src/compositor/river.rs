@@ -0,0 +1,264 @@
+use std::path::Path;
+
+use crate::compositor::extraction::{ConfigLine, ExtractionPlan, resolve_path};
+
+/// Moves the `wlr-randr` and `riverctl output-*` commands of the River
+/// init script or labwc autostart at `config_path` into `output_filename`
+/// next to it. The script runs that file with `sh` where the first of
+/// them was. Commands inside an `if`, `case`, loop or function stay where
+/// they are, since moving them could leave an empty block behind; they
+/// are still listed in `monitor_rules`, so they show up as stray rules.
+pub fn extract(
+    config_path: &Path,
+    output_filename: &str,
+) -> Result<ExtractionPlan, String> {
+    let config_path = config_path
+        .canonicalize()
+        .map_err(|e| format!("Cannot resolve config path: {e}"))?;
+    let base_dir = config_path
+        .parent()
+        .ok_or("Cannot determine config directory")?;
+    let output_path = base_dir.join(output_filename);
+    let content = std::fs::read_to_string(&config_path).map_err(|e| {
+        format!("Failed to read {}: {e}", config_path.display())
+    })?;
+
+    let lines: Vec<&str> = content.lines().collect();
+    let mut extracted = Vec::new();
+    let mut kept_lines = Vec::new();
+    let mut monitor_rules = Vec::new();
+    let mut source_exists = false;
+    let mut first = None;
+    let mut depth = 0;
+    let mut i = 0;
+
+    while i < lines.len() {
+        let trimmed = lines[i].trim();
+        if runs_script(trimmed)
+            .is_some_and(|script| resolve_path(base_dir, script) == output_path)
+        {
+            source_exists = true;
+        }
+        if !is_output_command(trimmed) {
+            depth = (depth + nesting(trimmed)).max(0);
+            kept_lines.push(lines[i].to_string());
+            i += 1;
+            continue;
+        }
+
+        // A command may go on over lines ending in a backslash.
+        let start = i;
+        while i + 1 < lines.len() && lines[i].trim_end().ends_with('\\') {
+            i += 1;
+        }
+        i += 1;
+        let command = lines[start..i].iter().map(|l| l.to_string());
+        if depth == 0 {
+            extracted.extend(command);
+            first.get_or_insert(kept_lines.len());
+        } else {
+            kept_lines.extend(command);
+        }
+        if trimmed.starts_with("wlr-randr") {
+            let text: Vec<&str> =
+                lines[start..i].iter().map(|l| l.trim()).collect();
+            monitor_rules.push(ConfigLine {
+                path: config_path.clone(),
+                line: start + 1,
+                text: text.join("\n"),
+            });
+        }
+    }
+
+    let mut output_content = String::new();
+    let mut modified_files = Vec::new();
+    let mut source_line = None;
+    if let Some(first) = first {
+        output_content
+            .push_str("# Generated by xwlm. Do not edit manually.\n\n");
+        for line in &extracted {
+            output_content.push_str(line);
+            output_content.push('\n');
+        }
+        if !source_exists {
+            let line = format!("sh {}", output_path.display());
+            kept_lines.insert(first, line.clone());
+            source_line = Some(line);
+        }
+        let mut new_content = kept_lines.join("\n");
+        if content.ends_with('\n') {
+            new_content.push('\n');
+        }
+        modified_files.push((config_path.clone(), new_content));
+    }
+
+    Ok(ExtractionPlan {
+        output_content,
+        modified_files,
+        source_line,
+        main_config: config_path,
+        output_path,
+        source_exists,
+        monitor_rules,
+    })
+}
+
+/// `riverctl output-layout` picks the layout generator, not a monitor
+/// setting, so it stays in the init script.
+fn is_output_command(line: &str) -> bool {
+    let mut words = line.split_whitespace();
+    match words.next() {
+        Some("wlr-randr") => true,
+        Some("riverctl") => words
+            .next()
+            .is_some_and(|w| w.starts_with("output-") && w != "output-layout"),
+        _ => false,
+    }
+}
+
+/// How many `if`, `case`, loop or `{` blocks `line` opens, less the ones
+/// it closes, so `if x; then y; fi` on one line counts as none.
+fn nesting(line: &str) -> i32 {
+    if line.starts_with('#') {
+        return 0;
+    }
+    line.split([';', '&', '|'])
+        .filter_map(|command| command.split_whitespace().next())
+        .map(|word| match word {
+            "if" | "case" | "for" | "while" | "until" | "{" => 1,
+            "fi" | "esac" | "done" | "}" => -1,
+            _ => 0,
+        })
+        .sum::<i32>()
+        + i32::from(line.ends_with('{') && !line.starts_with('{'))
+}
+
+/// The script `sh FILE` or `. FILE` runs.
+fn runs_script(line: &str) -> Option<&str> {
+    let rest = line
+        .strip_prefix("sh ")
+        .or_else(|| line.strip_prefix(". "))?;
+    let script = rest.trim().trim_matches(['"', '\'']);
+    (!script.is_empty()).then_some(script)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{env, fs};
+
+    #[test]
+    fn test_output_commands_move_out_of_the_init_script() {
+        let dir = env::temp_dir()
+            .join(format!("xwlm-river-extract-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let init = dir.join("init");
+        fs::write(
+            &init,
+            "#!/bin/sh\n\
+             riverctl map normal Super Return spawn foot\n\
+             wlr-randr --output DP-1 --mode 2560x1440@144Hz \\\n\
+             \x20   --pos 0,0\n\
+             riverctl output-layout rivertile\n\
+             wlr-randr --output HDMI-A-1 --off\n\
+             rivertile &\n",
+        )
+        .unwrap();
+
+        let plan = extract(&init, "monitors.conf").unwrap();
+        assert!(plan.has_monitors());
+        assert_eq!(plan.monitor_rules.len(), 2);
+        assert_eq!(plan.monitor_rules[0].line, 3);
+        assert_eq!(plan.monitor_rules[1].line, 6);
+        assert!(plan.output_content.contains("--pos 0,0\n"));
+        assert!(!plan.output_content.contains("riverctl output-layout"));
+        assert!(!plan.output_content.contains("spawn foot"));
+
+        plan.apply().unwrap();
+        let init_content = fs::read_to_string(&init).unwrap();
+        let managed = dir.canonicalize().unwrap().join("monitors.conf");
+        assert_eq!(
+            init_content,
+            format!(
+                "#!/bin/sh\n\
+                 riverctl map normal Super Return spawn foot\n\
+                 sh {}\n\
+                 riverctl output-layout rivertile\n\
+                 rivertile &\n",
+                managed.display()
+            )
+        );
+        let script = fs::read_to_string(&managed).unwrap();
+        assert!(script.contains("wlr-randr --output HDMI-A-1 --off"));
+
+        // Run again, nothing is left to move and the call stays single.
+        let again = extract(&init, "monitors.conf").unwrap();
+        assert!(!again.has_monitors());
+        assert!(again.source_exists);
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_commands_inside_a_block_stay_in_the_init_script() {
+        let dir = env::temp_dir()
+            .join(format!("xwlm-river-nested-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let init = dir.join("init");
+        let content = "#!/bin/sh\n\
+                       if [ \"$(hostname)\" = desk ]; then\n\
+                       \x20   wlr-randr --output DP-2 --pos 2560,0\n\
+                       fi\n\
+                       case $1 in a) wlr-randr --output eDP-1 --off ;; esac\n\
+                       wlr-randr --output DP-1 --pos 0,0\n";
+        fs::write(&init, content).unwrap();
+
+        let plan = extract(&init, "monitors.sh").unwrap();
+        fs::remove_dir_all(dir).unwrap();
+
+        assert_eq!(
+            plan.output_content,
+            "# Generated by xwlm. Do not edit manually.\n\n\
+             wlr-randr --output DP-1 --pos 0,0\n"
+        );
+        let (_, kept) = &plan.modified_files[0];
+        assert!(kept.contains("then\n    wlr-randr --output DP-2"));
+        // The nested one is still reported, to be moved by hand.
+        let lines: Vec<usize> =
+            plan.monitor_rules.iter().map(|rule| rule.line).collect();
+        assert_eq!(lines, [3, 6]);
+    }
+
+    #[test]
+    fn test_init_that_already_runs_the_script_gets_no_second_call() {
+        let dir = env::temp_dir()
+            .join(format!("xwlm-river-sourced-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let dir = dir.canonicalize().unwrap();
+        let init = dir.join("init");
+        let script = dir.join("monitors.sh");
+        let call = format!("sh \"{}\"", script.display());
+        fs::write(
+            &init,
+            format!(
+                "#!/bin/sh\n\
+                 {call}\n\
+                 wlr-randr --output DP-2 --pos 2560,0\n\
+                 rivertile &\n"
+            ),
+        )
+        .unwrap();
+
+        let plan = extract(&init, "monitors.sh").unwrap();
+        assert!(plan.source_exists);
+        assert_eq!(plan.source_line, None);
+        plan.apply().unwrap();
+        let init_content = fs::read_to_string(&init).unwrap();
+        let moved = fs::read_to_string(&script).unwrap();
+        let stray = dir.join("monitors.conf").exists();
+        fs::remove_dir_all(dir).unwrap();
+
+        assert_eq!(init_content, format!("#!/bin/sh\n{call}\nrivertile &\n"));
+        assert!(moved.contains("wlr-randr --output DP-2 --pos 2560,0\n"));
+        assert!(!stray);
+    }
+}
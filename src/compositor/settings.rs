@@ -0,0 +1,387 @@
+use wlx_monitors::WlTransform;
+
+use crate::{
+    compositor::{
+        Compositor,
+        diff::monitor_name,
+        format::{transform_from_sway, transform_from_wayfire},
+    },
+    constants::TRANSFORMS,
+    layout::LayoutMonitor,
+    refresh,
+};
+
+/// What the monitor config sets for one monitor. Settings it leaves to
+/// the compositor, like `preferred` or `auto`, are `None`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConfigMonitor {
+    pub name: String,
+    pub enabled: bool,
+    /// Width, height and, when given, the refresh rate in mHz.
+    pub mode: Option<(i32, i32, Option<i32>)>,
+    pub position: Option<(i32, i32)>,
+    pub scale: Option<f64>,
+    pub transform: Option<WlTransform>,
+}
+
+impl ConfigMonitor {
+    fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            enabled: true,
+            mode: None,
+            position: None,
+            scale: None,
+            transform: None,
+        }
+    }
+}
+
+impl From<&LayoutMonitor> for ConfigMonitor {
+    /// What a config rendered from `saved` would set.
+    fn from(saved: &LayoutMonitor) -> Self {
+        let mhz = saved
+            .has_refresh()
+            .then(|| saved.refresh_mhz.unwrap_or(saved.refresh_rate * 1000));
+        Self {
+            name: saved.name.clone(),
+            enabled: saved.enabled,
+            mode: saved.has_mode().then_some((saved.width, saved.height, mhz)),
+            position: Some((saved.x, saved.y)),
+            scale: Some(saved.scale),
+            transform: Some(saved.transform),
+        }
+    }
+}
+
+/// Every monitor `content` configures, in order of appearance. A later
+/// line for the same monitor overrides what it sets.
+pub fn config_monitors(
+    compositor: Compositor,
+    content: &str,
+) -> Vec<ConfigMonitor> {
+    let lines: Vec<&str> = content.lines().collect();
+    let mut monitors: Vec<ConfigMonitor> = Vec::new();
+    let mut i = 0;
+    while i < lines.len() {
+        let line = lines[i].trim();
+        i += 1;
+        let Some(name) = monitor_name(compositor, line) else {
+            continue;
+        };
+        if name.is_empty() || name == "*" {
+            continue;
+        }
+        let idx = match monitors.iter().position(|m| m.name == name) {
+            Some(idx) => idx,
+            None => {
+                monitors.push(ConfigMonitor::new(name));
+                monitors.len() - 1
+            }
+        };
+        let monitor = &mut monitors[idx];
+        match compositor {
+            Compositor::Hyprland => read_hyprland(monitor, line),
+            Compositor::Sway => {
+                let mut words: Vec<&str> =
+                    line.split_whitespace().skip(2).collect();
+                if line.contains('{') && !line.contains('}') {
+                    while i < lines.len() {
+                        let inner = lines[i].trim();
+                        i += 1;
+                        if inner.starts_with('}') {
+                            break;
+                        }
+                        words.extend(inner.split_whitespace());
+                    }
+                }
+                read_sway(monitor, &words);
+            }
+            Compositor::River | Compositor::Labwc | Compositor::Unknown => {
+                read_river(monitor, line)
+            }
+            Compositor::Niri => read_niri(monitor, line),
+            Compositor::Wayfire => {
+                while i < lines.len() && !lines[i].trim().starts_with('[') {
+                    read_wayfire(monitor, lines[i].trim());
+                    i += 1;
+                }
+            }
+            Compositor::Kwin => {}
+        }
+    }
+    monitors
+}
+
+/// `monitor = DP-1, 1920x1080@60, 0x0, 1, transform, 1` or
+/// `monitor = DP-1, disable`.
+fn read_hyprland(monitor: &mut ConfigMonitor, line: &str) {
+    let Some((_, rest)) = line.split_once('=') else {
+        return;
+    };
+    let parts: Vec<&str> = rest.split(',').map(str::trim).collect();
+    if parts.get(1) == Some(&"disable") {
+        monitor.enabled = false;
+        return;
+    }
+    monitor.enabled = true;
+    if let Some(mode) = parts.get(1) {
+        monitor.mode = match mode.strip_prefix("modeline") {
+            Some(modeline) => read_modeline(modeline),
+            None => read_mode(mode),
+        };
+    }
+    if let Some(position) = parts.get(2) {
+        monitor.position = position
+            .split_once('x')
+            .and_then(|(x, y)| Some((x.parse().ok()?, y.parse().ok()?)));
+    }
+    monitor.scale = parts.get(3).and_then(|s| s.parse().ok());
+    let mut extras = parts.iter().skip(4);
+    while let Some(&key) = extras.next() {
+        if key == "transform" {
+            monitor.transform = extras
+                .next()
+                .and_then(|n| n.parse::<usize>().ok())
+                .and_then(|n| TRANSFORMS.get(n).copied());
+        }
+    }
+}
+
+/// The words after `output NAME`, from one line or a `{ }` block.
+fn read_sway(monitor: &mut ConfigMonitor, words: &[&str]) {
+    let mut words = words.iter().copied().filter(|w| *w != "{" && *w != "}");
+    while let Some(word) = words.next() {
+        match word {
+            "disable" => monitor.enabled = false,
+            "enable" => monitor.enabled = true,
+            "mode" | "resolution" | "res" => {
+                let mut mode = words.next().unwrap_or_default();
+                if mode == "--custom" {
+                    mode = words.next().unwrap_or_default();
+                }
+                monitor.mode = read_mode(mode);
+            }
+            "pos" | "position" => {
+                let x = words.next().and_then(|x| x.parse().ok());
+                let y = words.next().and_then(|y| y.parse().ok());
+                monitor.position = x.zip(y);
+            }
+            "scale" => {
+                monitor.scale = words.next().and_then(|s| s.parse().ok())
+            }
+            "transform" => {
+                monitor.transform = words.next().and_then(transform_from_sway)
+            }
+            _ => {}
+        }
+    }
+}
+
+/// `wlr-randr --output DP-1 --mode 1920x1080@60Hz --pos 0,0 ...`
+fn read_river(monitor: &mut ConfigMonitor, line: &str) {
+    let mut words = line.split_whitespace();
+    while let Some(word) = words.next() {
+        match word {
+            "--off" => monitor.enabled = false,
+            "--on" => monitor.enabled = true,
+            "--mode" | "--custom-mode" => {
+                monitor.mode = words.next().and_then(read_mode)
+            }
+            "--pos" => {
+                monitor.position = words
+                    .next()
+                    .and_then(|p| p.split_once(','))
+                    .and_then(|(x, y)| Some((x.parse().ok()?, y.parse().ok()?)))
+            }
+            "--scale" => {
+                monitor.scale = words.next().and_then(|s| s.parse().ok())
+            }
+            "--transform" => {
+                monitor.transform = words.next().and_then(transform_from_sway)
+            }
+            _ => {}
+        }
+    }
+}
+
+/// `niri msg output DP-1 mode 1920x1080@60`, one setting per line.
+fn read_niri(monitor: &mut ConfigMonitor, line: &str) {
+    let mut words = line.split_whitespace().skip(4);
+    match words.next() {
+        Some("off") => monitor.enabled = false,
+        Some("on") => monitor.enabled = true,
+        Some("mode" | "custom-mode") => {
+            monitor.mode = words.next().and_then(read_mode)
+        }
+        Some("position") => {
+            let mut words = words.skip_while(|&w| w == "set");
+            monitor.position = (|| {
+                Some((words.next()?.parse().ok()?, words.next()?.parse().ok()?))
+            })();
+        }
+        Some("scale") => {
+            monitor.scale = words.next().and_then(|s| s.parse().ok())
+        }
+        Some("transform") => {
+            monitor.transform = words.next().and_then(transform_from_sway)
+        }
+        _ => {}
+    }
+}
+
+/// One `key = value` line of an `[output:DP-1]` section. Wayfire gives the
+/// refresh rate in mHz, or in Hz when it is below 1000.
+fn read_wayfire(monitor: &mut ConfigMonitor, line: &str) {
+    let Some((key, value)) = line.split_once('=') else {
+        return;
+    };
+    let value = value.trim();
+    match key.trim() {
+        "mode" if value == "off" => monitor.enabled = false,
+        "mode" => {
+            monitor.enabled = true;
+            let in_mhz = |mhz: i32| {
+                if mhz >= 1_000_000 { mhz / 1000 } else { mhz }
+            };
+            monitor.mode = read_mode(value)
+                .map(|(w, h, refresh)| (w, h, refresh.map(in_mhz)));
+        }
+        "position" => {
+            monitor.position = value.split_once(',').and_then(|(x, y)| {
+                Some((x.trim().parse().ok()?, y.trim().parse().ok()?))
+            });
+        }
+        "scale" => monitor.scale = value.parse().ok(),
+        "transform" => monitor.transform = transform_from_wayfire(value),
+        _ => {}
+    }
+}
+
+/// `1920x1080`, `1920x1080@60` or `1920x1080@59.951Hz`. Anything else,
+/// like `preferred`, leaves the mode to the compositor.
+fn read_mode(mode: &str) -> Option<(i32, i32, Option<i32>)> {
+    let (size, refresh) = match mode.split_once('@') {
+        Some((size, refresh)) => (size, Some(refresh)),
+        None => (mode, None),
+    };
+    let (w, h) = size.split_once('x')?;
+    let refresh = match refresh {
+        Some(r) => Some(refresh::parse(r)?),
+        None => None,
+    };
+    Some((w.parse().ok()?, h.parse().ok()?, refresh))
+}
+
+/// `138.50 1920 1968 2000 2080 1080 1083 1088 1111 +hsync -vsync`
+fn read_modeline(modeline: &str) -> Option<(i32, i32, Option<i32>)> {
+    let numbers: Vec<f64> = modeline
+        .split_whitespace()
+        .take(9)
+        .map(|n| n.parse().ok())
+        .collect::<Option<_>>()?;
+    let &[clock, width, _, _, htotal, height, _, _, vtotal] = &numbers[..]
+    else {
+        return None;
+    };
+    let mhz = clock * 1_000_000_000.0 / (htotal * vtotal);
+    Some((width as i32, height as i32, Some(mhz.round() as i32)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn first(compositor: Compositor, content: &str) -> ConfigMonitor {
+        config_monitors(compositor, content).remove(0)
+    }
+
+    #[test]
+    fn test_reads_what_each_compositor_config_sets() {
+        let expected = ConfigMonitor {
+            name: "DP-1".into(),
+            enabled: true,
+            mode: Some((2560, 1440, Some(144_000))),
+            position: Some((1920, 0)),
+            scale: Some(1.25),
+            transform: Some(WlTransform::Rotate90),
+        };
+        let table = [
+            (
+                Compositor::Hyprland,
+                "monitor = DP-1, 2560x1440@144.00, 1920x0, 1.25, \
+                 transform, 1",
+            ),
+            (
+                Compositor::Sway,
+                "output DP-1 {\n    mode 2560x1440@144Hz\n    pos 1920 0\n    \
+                 scale 1.25\n    transform 90\n}",
+            ),
+            (
+                Compositor::Sway,
+                "output DP-1 mode 2560x1440@144Hz pos 1920 0 scale 1.25 \
+                 transform 90",
+            ),
+            (
+                Compositor::River,
+                "wlr-randr --output DP-1 --mode 2560x1440@144Hz --pos 1920,0 \
+                 --scale 1.25 --transform 90",
+            ),
+            (
+                Compositor::Niri,
+                "niri msg output DP-1 on\n\
+                 niri msg output DP-1 mode 2560x1440@144.000\n\
+                 niri msg output DP-1 position set 1920 0\n\
+                 niri msg output DP-1 scale 1.25\n\
+                 niri msg output DP-1 transform 90",
+            ),
+            (
+                Compositor::Wayfire,
+                "[core]\n\
+                 plugins = move\n\
+                 [output:DP-1]\n\
+                 mode = 2560x1440@144000\n\
+                 position = 1920, 0\n\
+                 scale = 1.25\n\
+                 transform = 90\n\
+                 [input]\n\
+                 scale = 2",
+            ),
+        ];
+        for (compositor, content) in table {
+            assert_eq!(first(compositor, content), expected, "{content}");
+        }
+    }
+
+    #[test]
+    fn test_disabled_and_compositor_chosen_settings() {
+        let hypr = "monitor = DP-1, 1920x1080@60, 0x0, 1\n\
+                    monitor = DP-1, disable\n\
+                    monitor = DP-2, preferred, auto, auto\n\
+                    monitor = , preferred, auto, 1\n";
+        let monitors = config_monitors(Compositor::Hyprland, hypr);
+        assert_eq!(monitors.len(), 2);
+        assert!(!monitors[0].enabled);
+        assert_eq!(monitors[0].mode, Some((1920, 1080, Some(60_000))));
+        assert_eq!(monitors[1].mode, None);
+        assert_eq!(monitors[1].position, None);
+        assert_eq!(monitors[1].scale, None);
+
+        let sway = first(Compositor::Sway, "output HDMI-A-1 disable");
+        assert!(!sway.enabled);
+        let river = first(Compositor::River, "wlr-randr --output DP-3 --off");
+        assert!(!river.enabled);
+        let niri = first(Compositor::Niri, "niri msg output DP-3 off");
+        assert!(!niri.enabled);
+        let wayfire = first(Compositor::Wayfire, "[output:DP-3]\nmode = off");
+        assert!(!wayfire.enabled);
+    }
+
+    #[test]
+    fn test_modeline_refresh_comes_from_the_timings() {
+        let line = "monitor = DP-1, modeline 138.50 1920 1968 2000 2080 \
+                    1080 1083 1088 1111 +hsync -vsync, 0x0, 1";
+        let monitor = first(Compositor::Hyprland, line);
+        assert_eq!(monitor.mode, Some((1920, 1080, Some(59_934))));
+    }
+}
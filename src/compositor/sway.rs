@@ -1,7 +1,9 @@
 use std::collections::HashSet;
 use std::path::{Path, PathBuf};
 
-use crate::compositor::extraction::{ExtractionPlan, resolve_path};
+use crate::compositor::extraction::{
+    ConfigLine, ExtractionPlan, resolve_includes,
+};
 use crate::compositor::position::ConfigPosition;
 
 pub fn extract(config_path: &Path, output_filename: &str) -> Result<ExtractionPlan, String> {
@@ -18,6 +20,7 @@ pub fn extract(config_path: &Path, output_filename: &str) -> Result<ExtractionPl
     let mut modified_files = Vec::new();
     let mut visited = HashSet::new();
     let mut source_exists = false;
+    let mut monitor_rules = Vec::new();
 
     visited.insert(config_path.clone());
 
@@ -28,6 +31,7 @@ pub fn extract(config_path: &Path, output_filename: &str) -> Result<ExtractionPl
         &mut modified_files,
         &mut visited,
         &mut source_exists,
+        &mut monitor_rules,
     )?;
 
     let output_content = if extracted.is_empty() {
@@ -52,7 +56,9 @@ pub fn extract(config_path: &Path, output_filename: &str) -> Result<ExtractionPl
         modified_files,
         source_line,
         main_config: config_path,
+        output_path,
         source_exists,
+        monitor_rules,
     })
 }
 
@@ -63,6 +69,7 @@ fn parse_file(
     modified_files: &mut Vec<(PathBuf, String)>,
     visited: &mut HashSet<PathBuf>,
     source_exists: &mut bool,
+    monitor_rules: &mut Vec<ConfigLine>,
 ) -> Result<(), String> {
     let content = std::fs::read_to_string(path)
         .map_err(|e| format!("Failed to read {}: {e}", path.display()))?;
@@ -95,6 +102,7 @@ fn parse_file(
         }
 
         if is_output_line(trimmed) {
+            let start = i;
             if trimmed.contains('{') {
                 let mut block = vec![lines[i].to_string()];
                 let mut depth = count_char(trimmed, '{') as i32 - count_char(trimmed, '}') as i32;
@@ -107,41 +115,48 @@ fn parse_file(
                 }
                 extracted.extend(block);
                 extracted.push(String::new());
-                file_modified = true;
-                continue;
             } else {
                 extracted.push(lines[i].to_string());
-                file_modified = true;
                 i += 1;
-                continue;
             }
+            let text: Vec<&str> =
+                lines[start..i].iter().map(|l| l.trim()).collect();
+            monitor_rules.push(ConfigLine {
+                path: path.to_path_buf(),
+                line: start + 1,
+                text: text.join("\n"),
+            });
+            file_modified = true;
+            continue;
         }
 
         if let Some(include_path_str) = parse_include_line(trimmed) {
-            let resolved = resolve_path(base_dir, &include_path_str);
-
-            if let Ok(canonical) = resolved.canonicalize() {
-                if canonical == output_path.canonicalize().unwrap_or_default() {
+            for resolved in resolve_includes(base_dir, &include_path_str) {
+                if let Ok(canonical) = resolved.canonicalize() {
+                    let output = output_path.canonicalize().unwrap_or_default();
+                    if canonical == output {
+                        *source_exists = true;
+                    }
+
+                    if !visited.contains(&canonical) {
+                        visited.insert(canonical.clone());
+                        parse_file(
+                            &canonical,
+                            output_path,
+                            extracted,
+                            modified_files,
+                            visited,
+                            source_exists,
+                            monitor_rules,
+                        )?;
+                    }
+                } else if resolved
+                    .file_name()
+                    .and_then(|f| output_path.file_name().map(|o| f == o))
+                    .unwrap_or(false)
+                {
                     *source_exists = true;
                 }
-
-                if !visited.contains(&canonical) {
-                    visited.insert(canonical.clone());
-                    parse_file(
-                        &canonical,
-                        output_path,
-                        extracted,
-                        modified_files,
-                        visited,
-                        source_exists,
-                    )?;
-                }
-            } else if resolved
-                .file_name()
-                .and_then(|f| output_path.file_name().map(|o| f == o))
-                .unwrap_or(false)
-            {
-                *source_exists = true;
             }
         }
 
@@ -210,6 +225,17 @@ fn count_char(s: &str, c: char) -> usize {
 }
 
 pub fn config_position(content: &str, monitor_name: &str) -> Option<ConfigPosition> {
+    config_positions(content)
+        .into_iter()
+        .find(|(name, _)| name == monitor_name)
+        .map(|(_, pos)| pos)
+}
+
+/// Every output `content` places, in the order they first get a position,
+/// read in one pass. The first `pos` for an output wins.
+pub fn config_positions(content: &str) -> Vec<(String, ConfigPosition)> {
+    let mut found: Vec<(String, ConfigPosition)> = Vec::new();
+    let mut seen: HashSet<String> = HashSet::new();
     let mut current_output: Option<String> = None;
     let mut in_output_block = false;
 
@@ -229,8 +255,10 @@ pub fn config_position(content: &str, monitor_name: &str) -> Option<ConfigPositi
             current_output = None;
         }
 
-        // Only parse position lines for the target output
-        if current_output.as_deref() != Some(monitor_name) {
+        let Some(output) = current_output.as_deref() else {
+            continue;
+        };
+        if seen.contains(output) {
             continue;
         }
 
@@ -242,11 +270,13 @@ pub fn config_position(content: &str, monitor_name: &str) -> Option<ConfigPositi
                     && let (Ok(x), Ok(y)) =
                         (parts[i + 1].parse::<i32>(), parts[i + 2].parse::<i32>())
                 {
-                    return Some(ConfigPosition { x, y });
+                    seen.insert(output.to_string());
+                    found.push((output.to_string(), ConfigPosition { x, y }));
+                    break;
                 }
             }
         }
     }
 
-    None
+    found
 }
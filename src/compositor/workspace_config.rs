@@ -1,31 +1,67 @@
-use std::path::PathBuf;
+use serde::{Deserialize, Serialize};
 
 use crate::compositor::Compositor;
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct WorkspaceRule {
     pub id: usize,
     pub monitor: String,
+    /// Monitors to use, in order, when `monitor` is not connected.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub fallbacks: Vec<String>,
+    #[serde(default)]
     pub is_default: bool,
+    #[serde(default)]
     pub is_persistent: bool,
+    /// The 1-based line of the monitor config the rule was read from.
+    #[serde(skip)]
+    pub line: Option<usize>,
 }
 
-pub fn parse_workspace_config(compositor: Compositor, path: &PathBuf) -> Vec<WorkspaceRule> {
-    let content = match std::fs::read_to_string(path) {
-        Ok(c) => c,
-        Err(_) => return Vec::new(),
-    };
+/// Rules are equal when they say the same, wherever they were read from.
+impl PartialEq for WorkspaceRule {
+    fn eq(&self, other: &Self) -> bool {
+        self.id == other.id
+            && self.monitor == other.monitor
+            && self.fallbacks == other.fallbacks
+            && self.is_default == other.is_default
+            && self.is_persistent == other.is_persistent
+    }
+}
+
+pub fn parse_workspaces(compositor: Compositor, content: &str) -> Vec<WorkspaceRule> {
     match compositor {
-        Compositor::Hyprland => parse_hyprland_workspaces(&content),
-        Compositor::Sway => parse_sway_workspaces(&content),
+        Compositor::Hyprland => parse_hyprland_workspaces(content),
+        Compositor::Sway => parse_sway_workspaces(content),
         _ => Vec::new(),
     }
 }
 
+/// Hyprland merges the rules for one workspace, so a later one with a
+/// monitor moves it there, as a hand-written override does. The rule
+/// keeps the first one's line.
 fn parse_hyprland_workspaces(content: &str) -> Vec<WorkspaceRule> {
+    let mut merged: Vec<WorkspaceRule> = Vec::new();
+    for rule in hyprland_rules(content) {
+        match merged.iter_mut().find(|r| r.id == rule.id) {
+            Some(first) => {
+                if !rule.monitor.is_empty() {
+                    first.monitor = rule.monitor;
+                }
+                first.is_default |= rule.is_default;
+                first.is_persistent |= rule.is_persistent;
+            }
+            None => merged.push(rule),
+        }
+    }
+    merged
+}
+
+fn hyprland_rules(content: &str) -> impl Iterator<Item = WorkspaceRule> {
     content
         .lines()
-        .filter_map(|line| {
+        .enumerate()
+        .filter_map(|(i, line)| {
             let trimmed = line.trim();
             if trimmed.is_empty() || trimmed.starts_with('#') {
                 return None;
@@ -44,11 +80,12 @@ fn parse_hyprland_workspaces(content: &str) -> Vec<WorkspaceRule> {
             Some(WorkspaceRule {
                 id,
                 monitor,
+                fallbacks: Vec::new(),
                 is_default,
                 is_persistent,
+                line: Some(i + 1),
             })
         })
-        .collect()
 }
 
 fn extract_monitor_name(rules: &str) -> String {
@@ -69,20 +106,25 @@ fn extract_monitor_name(rules: &str) -> String {
 fn parse_sway_workspaces(content: &str) -> Vec<WorkspaceRule> {
     content
         .lines()
-        .filter_map(|line| {
+        .enumerate()
+        .filter_map(|(i, line)| {
             let trimmed = line.trim();
             if trimmed.is_empty() || trimmed.starts_with('#') {
                 return None;
             }
             let rest = trimmed.strip_prefix("workspace")?.trim_start();
             let (id_str, rest) = rest.split_once(char::is_whitespace)?;
-            let id: usize = id_str.trim().parse().ok()?;
-            let monitor = rest.trim().strip_prefix("output")?.trim().to_string();
+            let id: usize = id_str.trim().trim_matches('"').parse().ok()?;
+            let outputs = rest.trim().strip_prefix("output")?;
+            let mut outputs =
+                outputs.split_whitespace().map(|o| o.trim_matches('"').into());
             Some(WorkspaceRule {
                 id,
-                monitor,
+                monitor: outputs.next().unwrap_or_default(),
+                fallbacks: outputs.collect(),
                 is_default: false,
                 is_persistent: false,
+                line: Some(i + 1),
             })
         })
         .collect()
@@ -124,8 +166,52 @@ workspace = 2, monitor:eDP-1
         assert_eq!(result.len(), 2);
         assert_eq!(result[0].id, 1);
         assert_eq!(result[0].monitor, "HDMI-A-1");
-        assert!(result[0].is_default);
-        assert!(result[0].is_persistent);
+        assert_eq!(result[1].line, Some(3));
+        assert!(!result[0].is_default);
+        assert!(!result[0].is_persistent);
+    }
+
+    #[test]
+    fn test_parse_hyprland_workspace_arbitrary_ids() {
+        let content = "workspace = 0, monitor:DP-1\nworkspace = 11, monitor:DP-2\nworkspace = 20, monitor:DP-2\n";
+        let ids: Vec<usize> = parse_hyprland_workspaces(content)
+            .iter()
+            .map(|r| r.id)
+            .collect();
+        assert_eq!(ids, vec![0, 11, 20]);
+    }
+
+    #[test]
+    fn test_parse_sway_workspace_arbitrary_ids() {
+        let content = r#"
+workspace 0 output DP-1
+workspace 11 output DP-2
+workspace "20" output DP-2
+"#;
+        let result = parse_sway_workspaces(content);
+        let ids: Vec<usize> = result.iter().map(|r| r.id).collect();
+        assert_eq!(ids, vec![0, 11, 20]);
+        assert_eq!(result[2].monitor, "DP-2");
+    }
+
+    #[test]
+    fn test_fallback_monitors_are_read_in_order() {
+        let line = "workspace 3 output DP-1 DP-2 eDP-1\n";
+        let sway = parse_sway_workspaces(line);
+        assert_eq!(sway[0].monitor, "DP-1");
+        assert_eq!(sway[0].fallbacks, ["DP-2", "eDP-1"]);
+
+        let content = "workspace = 3, monitor:DP-1, default:true\n\
+                       workspace = 4, monitor:DP-2\n\
+                       workspace = 3, monitor:DP-2\n\
+                       workspace = 3, monitor:eDP-1\n";
+        // Hyprland has no fallbacks; the last rule wins.
+        let hypr = parse_hyprland_workspaces(content);
+        assert_eq!(hypr.len(), 2);
+        assert_eq!(hypr[0].monitor, "eDP-1");
+        assert!(hypr[0].fallbacks.is_empty());
+        assert!(hypr[0].is_default);
+        assert_eq!(hypr[0].line, Some(1));
     }
 
     #[test]
@@ -0,0 +1,321 @@
+use toml::{Table, Value};
+
+use crate::{
+    i18n::{t, tf},
+    xwlm_config::Config,
+};
+
+/// How a setting is changed in the `,` modal.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FieldKind {
+    Toggle,
+    /// Typed in; a whole number stays whole.
+    Number,
+    /// Cycled through; "" stands for unset.
+    Choice(&'static [&'static str]),
+    Text,
+}
+
+/// What the TOML value of a setting cannot tell about it. Settings without
+/// one are still listed, edited the way their value suggests.
+struct Hint {
+    key: &'static str,
+    kind: FieldKind,
+    min: Option<f64>,
+    /// Only read at startup.
+    restart: bool,
+}
+
+const HINTS: &[Hint] = &[
+    Hint {
+        key: "canonical_layout",
+        kind: FieldKind::Toggle,
+        min: None,
+        restart: true,
+    },
+    Hint {
+        key: "event_queue_size",
+        kind: FieldKind::Number,
+        min: Some(1.0),
+        restart: true,
+    },
+    Hint {
+        key: "failure_alert",
+        kind: FieldKind::Choice(&["none", "bell", "flash", "both"]),
+        min: None,
+        restart: false,
+    },
+    Hint {
+        key: "highlight_symbol",
+        kind: FieldKind::Text,
+        min: None,
+        restart: false,
+    },
+    Hint {
+        key: "hold_on_error",
+        kind: FieldKind::Toggle,
+        min: None,
+        restart: true,
+    },
+    Hint {
+        key: "idle_timeout_ms",
+        kind: FieldKind::Number,
+        min: None,
+        restart: true,
+    },
+    Hint {
+        key: "language",
+        kind: FieldKind::Choice(&["", "de", "en"]),
+        min: None,
+        restart: true,
+    },
+    Hint {
+        key: "on_new_monitor",
+        kind: FieldKind::Choice(&[
+            "ignore",
+            "enable_right",
+            "enable_saved",
+            "ask",
+        ]),
+        min: None,
+        restart: false,
+    },
+    Hint {
+        key: "workspace_count",
+        kind: FieldKind::Number,
+        min: Some(1.0),
+        restart: true,
+    },
+    Hint {
+        key: "zoom_max",
+        kind: FieldKind::Number,
+        min: Some(0.01),
+        restart: false,
+    },
+    Hint {
+        key: "zoom_min",
+        kind: FieldKind::Number,
+        min: Some(0.01),
+        restart: false,
+    },
+    Hint {
+        key: "zoom_step",
+        kind: FieldKind::Number,
+        min: Some(1.01),
+        restart: false,
+    },
+];
+
+/// Left to `xwlm init`, which checks the path it writes.
+const HIDDEN: &[&str] = &["monitor_config_path"];
+
+/// One setting as the modal lists it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Field {
+    pub key: String,
+    pub kind: FieldKind,
+    /// As shown and typed; "" when unset.
+    pub value: String,
+    pub restart: bool,
+}
+
+impl Field {
+    /// The choice after (or before) the current one.
+    pub fn cycle(&self, forward: bool) -> Option<String> {
+        match self.kind {
+            FieldKind::Toggle => Some((self.value != "true").to_string()),
+            FieldKind::Choice(choices) => {
+                let len = choices.len();
+                let i = choices.iter().position(|&c| c == self.value);
+                let next = match (i, forward) {
+                    (Some(i), true) => (i + 1) % len,
+                    (Some(i), false) => (i + len - 1) % len,
+                    (None, _) => 0,
+                };
+                Some(choices[next].to_string())
+            }
+            FieldKind::Number | FieldKind::Text => None,
+        }
+    }
+}
+
+fn hint(key: &str) -> Option<&'static Hint> {
+    HINTS.iter().find(|h| h.key == key)
+}
+
+fn to_table(config: &Config) -> Table {
+    Table::try_from(config).unwrap_or_default()
+}
+
+/// Every plain setting in `config`, by key. Lists and tables are edited
+/// through their own keys and panels and left out.
+pub fn fields(config: &Config) -> Vec<Field> {
+    let table = to_table(config);
+    let plain = table
+        .iter()
+        .filter(|(_, v)| !matches!(v, Value::Array(_) | Value::Table(_)))
+        .map(|(k, _)| k.as_str());
+    let mut keys: Vec<&str> = plain
+        .chain(HINTS.iter().map(|h| h.key))
+        .filter(|k| !HIDDEN.contains(k))
+        .collect();
+    keys.sort_unstable();
+    keys.dedup();
+
+    keys.into_iter()
+        .map(|key| {
+            let value = table.get(key);
+            let kind = match (hint(key), value) {
+                (Some(hint), _) => hint.kind,
+                (None, Some(Value::Boolean(_))) => FieldKind::Toggle,
+                (None, Some(Value::Integer(_) | Value::Float(_))) => {
+                    FieldKind::Number
+                }
+                (None, _) => FieldKind::Text,
+            };
+            let value = match value {
+                Some(Value::String(s)) => s.clone(),
+                Some(v) => v.to_string(),
+                None => String::new(),
+            };
+            Field {
+                key: key.to_string(),
+                kind,
+                value,
+                restart: hint(key).is_some_and(|h| h.restart),
+            }
+        })
+        .collect()
+}
+
+/// `config` with `field` set to `input`, or why it cannot be.
+pub fn set(
+    config: &Config,
+    field: &Field,
+    input: &str,
+) -> Result<Config, String> {
+    let mut table = to_table(config);
+    let key = field.key.as_str();
+    let input = input.trim();
+    let value = match field.kind {
+        FieldKind::Toggle => Some(Value::Boolean(input == "true")),
+        FieldKind::Number => {
+            let value = match table.get(key) {
+                Some(Value::Float(_)) => input
+                    .parse::<f64>()
+                    .ok()
+                    .filter(|f| f.is_finite())
+                    .map(Value::Float)
+                    .ok_or_else(|| t("Not a number").to_string())?,
+                _ => input
+                    .parse::<i64>()
+                    .map(Value::Integer)
+                    .map_err(|_| t("Not a whole number").to_string())?,
+            };
+            let number = match value {
+                Value::Float(f) => f,
+                Value::Integer(i) => i as f64,
+                _ => 0.0,
+            };
+            if let Some(min) = hint(key).and_then(|h| h.min)
+                && number < min
+            {
+                return Err(tf("Must be at least {}", &[&min]));
+            }
+            Some(value)
+        }
+        FieldKind::Choice(_) | FieldKind::Text => {
+            (!input.is_empty()).then(|| Value::String(input.to_string()))
+        }
+    };
+    match value {
+        Some(value) => table.insert(key.to_string(), value),
+        None => table.remove(key),
+    };
+    let changed: Config =
+        table.try_into().map_err(|e| e.message().to_string())?;
+    if changed.zoom_min > changed.zoom_max {
+        return Err(t("zoom_min is above zoom_max").to_string());
+    }
+    Ok(changed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::xwlm_config::{FailureAlert, NewMonitorAction};
+    use std::path::PathBuf;
+
+    fn config() -> Config {
+        Config::new(PathBuf::from("/tmp/monitors.conf"))
+    }
+
+    fn field(key: &str) -> Field {
+        fields(&config()).into_iter().find(|f| f.key == key).unwrap()
+    }
+
+    #[test]
+    fn test_every_plain_setting_is_listed_with_a_fitting_editor() {
+        let listed = fields(&config());
+        let keys: Vec<&str> = listed.iter().map(|f| f.key.as_str()).collect();
+        assert!(keys.is_sorted());
+        assert!(!keys.contains(&"monitor_config_path"));
+        assert!(!keys.contains(&"anchored_monitors"));
+        assert!(!keys.contains(&"defaults"));
+
+        assert_eq!(field("snap_layout").kind, FieldKind::Toggle);
+        assert_eq!(field("snap_layout").value, "true");
+        assert_eq!(field("reload_cooldown_ms").kind, FieldKind::Number);
+        assert_eq!(field("zoom_step").value, "1.1");
+        assert!(matches!(field("failure_alert").kind, FieldKind::Choice(_)));
+        assert_eq!(field("language").value, "");
+        assert!(field("language").restart);
+
+        // Every hint names a real setting and every choice is accepted.
+        for hint in HINTS {
+            let field = field(hint.key);
+            if let FieldKind::Choice(choices) = field.kind {
+                for choice in choices {
+                    assert!(set(&config(), &field, choice).is_ok(), "{choice}");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_set_changes_one_setting() {
+        let changed = set(&config(), &field("instant_toggle"), "true");
+        assert!(changed.unwrap().instant_toggle);
+
+        let alert = field("failure_alert");
+        let changed = set(&config(), &alert, &alert.cycle(true).unwrap());
+        assert_eq!(changed.unwrap().failure_alert, FailureAlert::Bell);
+
+        let on_new = field("on_new_monitor");
+        let changed = set(&config(), &on_new, &on_new.cycle(false).unwrap());
+        assert_eq!(changed.unwrap().on_new_monitor, NewMonitorAction::Ask);
+
+        let changed = set(&config(), &field("zoom_step"), " 1.5 ");
+        assert_eq!(changed.unwrap().zoom_step, 1.5);
+
+        let changed = set(&config(), &field("language"), "de");
+        assert_eq!(changed.unwrap().language.as_deref(), Some("de"));
+    }
+
+    #[test]
+    fn test_set_refuses_values_that_do_not_fit() {
+        let count = field("workspace_count");
+        let refused = |input| set(&config(), &count, input).unwrap_err();
+        assert_eq!(refused("ten"), "Not a whole number");
+        assert_eq!(refused("0"), "Must be at least 1");
+        assert!(set(&config(), &field("flap_threshold"), "-1").is_err());
+        assert_eq!(
+            set(&config(), &field("zoom_min"), "9").unwrap_err(),
+            "zoom_min is above zoom_max"
+        );
+        assert_eq!(
+            set(&config(), &field("zoom_step"), "fast").unwrap_err(),
+            "Not a number"
+        );
+    }
+}
@@ -12,3 +12,90 @@ pub const TRANSFORMS: [WlTransform; 8] = [
 ];
 
 pub const REPEAT_WINDOW_MS: u128 = 200;
+
+/// Poll interval while the user is typing or monitor events are arriving.
+pub const ACTIVE_POLL_MS: u64 = 50;
+
+/// How long after the last key or monitor event the loop stays responsive.
+pub const ACTIVE_WINDOW_MS: u64 = 1000;
+
+pub const MIN_SCALE: f64 = 0.5;
+
+pub const MAX_SCALE: f64 = 10.0;
+
+pub const MAX_PUSH_CASCADE: usize = 32;
+
+/// Largest gap or overlap, in logical pixels, that saving closes between
+/// neighbours whose shared edge falls between pixels.
+pub const SNAP_DISTANCE: i32 = 2;
+
+pub const MAX_HISTORY: usize = 20;
+
+/// The size the map draws a monitor in that reports neither modes nor a
+/// resolution, as some virtual outputs do.
+pub const PLACEHOLDER_RESOLUTION: (i32, i32) = (1920, 1080);
+
+/// How far, in Hz, a saved refresh rate may be from an advertised one and
+/// still count as the same mode, e.g. 144 against 143.
+pub const MODE_REFRESH_TOLERANCE: i32 = 1;
+
+/// How long a previewed transform stays before it is reverted.
+pub const TRANSFORM_PREVIEW_SECS: u64 = 5;
+
+/// How long a workspace assigned away from the monitor the Workspaces
+/// panel follows stays listed, highlighted.
+pub const WORKSPACE_LEAVING_MS: u64 = 1500;
+
+/// How long a panel border flashes red after a failure.
+pub const FAILURE_FLASH_MS: u64 = 600;
+
+/// How long a risky change to the terminal's monitor waits for a key
+/// before it is reverted.
+pub const GUARD_REVERT_SECS: u64 = 10;
+
+/// Scale changes at least this big on the terminal's monitor count as
+/// risky.
+pub const RISKY_SCALE_JUMP: f64 = 0.5;
+
+/// How long to wait for `pw-dump` before assuming nothing is shared.
+pub const CAPTURE_PROBE_MS: u64 = 300;
+
+/// How long to wait for the compositor to list layer surfaces before
+/// assuming there are no bars.
+pub const BAR_PROBE_MS: u64 = 300;
+
+/// How often monitor events may ask the compositor again which outputs
+/// are powered, so a burst of them does not start a process each.
+pub const POWER_REFRESH_MS: u64 = 1000;
+
+/// Status and error messages kept for a debug dump.
+pub const MAX_MESSAGES: usize = 50;
+
+/// Monitor events kept in the session timeline.
+pub const MAX_TIMELINE: usize = 500;
+
+/// Reconnects within this window count towards the flap warning.
+pub const FLAP_WINDOW_SECS: i64 = 600;
+
+/// A monitor coming back on another connector within this long after it
+/// was removed is taken to be the same one, as after an MST dock replug.
+pub const RENAME_WINDOW_SECS: i64 = 60;
+
+/// A second output under a taken name that reports no modes within this
+/// long is forgotten; MST hubs show such ghosts briefly while replugging.
+pub const GHOST_GRACE_MS: u64 = 5000;
+
+/// Past this many monitors the Modes and Workspaces panels share a tabbed
+/// column so the map keeps enough room.
+pub const COMPACT_LAYOUT_MONITORS: usize = 6;
+
+/// How long an answer on the control socket waits for the compositor to
+/// report the change it asked for.
+pub const CONTROL_SETTLE_MS: u64 = 500;
+
+/// How long a save waits for the compositor to report back the changes
+/// xwlm sent it, and how long reports after a reload count as its echo.
+pub const FEEDBACK_WINDOW_MS: u64 = 1000;
+
+/// How much of the map's drawable area the layout fills at 100% zoom.
+pub const MAP_FILL: f64 = 0.8;
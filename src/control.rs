@@ -0,0 +1,532 @@
+use std::{
+    env, fs,
+    io::{self, BufRead, BufReader, Write},
+    os::unix::net::{UnixListener, UnixStream},
+    path::{Path, PathBuf},
+    sync::mpsc::{self, Receiver, Sender},
+    thread,
+    time::{Duration, Instant},
+};
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use thiserror::Error;
+use wlx_monitors::{WlMonitor, WlMonitorAction};
+
+use crate::{
+    compositor::format::transform_to_sway,
+    constants::CONTROL_SETTLE_MS,
+    state::{App, Confirm, Panel},
+    utils::monitor_resolution,
+};
+
+const SOCKET_NAME: &str = "xwlm.sock";
+
+/// JSON-RPC error codes.
+const PARSE_ERROR: i64 = -32700;
+const INVALID_REQUEST: i64 = -32600;
+const FAILED: i64 = -32000;
+
+#[derive(Error, Debug)]
+pub enum ControlError {
+    #[error("another xwlm is already listening on {}", .0.display())]
+    Running(PathBuf),
+
+    #[error("io error: {0}")]
+    Io(#[from] io::Error),
+}
+
+/// One request on the control socket, e.g.
+/// `{"jsonrpc": "2.0", "id": 1, "method": "toggle", "params": {"output":
+/// "DP-2"}}`.
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+#[serde(tag = "method", content = "params", rename_all = "snake_case")]
+pub enum Request {
+    List,
+    Set(SetParams),
+    Toggle { output: String },
+    ApplyProfile { name: String },
+    Save,
+    DistributeWorkspaces,
+}
+
+/// What `set` changes on one output; the rest stays as it is.
+#[derive(Serialize, Deserialize, Debug, Default, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct SetParams {
+    pub output: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mode: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub position: Option<(i32, i32)>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scale: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub enabled: Option<bool>,
+}
+
+/// A request waiting for the UI loop to answer it.
+pub struct Call {
+    pub request: Request,
+    id: Value,
+    reply: Sender<String>,
+}
+
+/// The monitor state every answer carries.
+#[derive(Serialize)]
+struct MonitorState<'a> {
+    name: &'a str,
+    description: &'a str,
+    enabled: bool,
+    width: i32,
+    height: i32,
+    refresh_rate: i32,
+    x: i32,
+    y: i32,
+    scale: f64,
+    transform: &'static str,
+}
+
+impl<'a> MonitorState<'a> {
+    fn of(monitor: &'a WlMonitor) -> Self {
+        let (width, height) = monitor_resolution(monitor);
+        let refresh_rate = monitor
+            .modes
+            .iter()
+            .find(|m| m.is_current)
+            .map_or(0, |m| m.refresh_rate);
+        Self {
+            name: &monitor.name,
+            description: &monitor.description,
+            enabled: monitor.enabled,
+            width,
+            height,
+            refresh_rate,
+            x: monitor.position.x,
+            y: monitor.position.y,
+            scale: monitor.scale,
+            transform: transform_to_sway(monitor.transform),
+        }
+    }
+}
+
+/// `$XDG_RUNTIME_DIR/xwlm.sock`, where a running TUI takes requests.
+pub fn socket_path() -> Option<PathBuf> {
+    let dir = env::var_os("XDG_RUNTIME_DIR").filter(|d| !d.is_empty())?;
+    Some(PathBuf::from(dir).join(SOCKET_NAME))
+}
+
+/// The listening control socket. Only one xwlm holds it at a time, so
+/// finding it answered tells the CLI a TUI is running.
+pub struct Server {
+    path: PathBuf,
+    pub calls: Receiver<Call>,
+}
+
+impl Drop for Server {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+/// Listens on `path`, unless another xwlm already does. A socket left
+/// behind by one that crashed is replaced.
+pub fn listen(path: &Path) -> Result<Server, ControlError> {
+    if path.exists() {
+        if UnixStream::connect(path).is_ok() {
+            return Err(ControlError::Running(path.to_path_buf()));
+        }
+        fs::remove_file(path)?;
+    }
+    let listener = UnixListener::bind(path)?;
+    let (tx, calls) = mpsc::channel();
+    thread::spawn(move || {
+        for stream in listener.incoming().flatten() {
+            let tx = tx.clone();
+            thread::spawn(move || serve(stream, tx));
+        }
+    });
+    Ok(Server {
+        path: path.to_path_buf(),
+        calls,
+    })
+}
+
+/// Answers the requests of one connection, a line each, in order.
+fn serve(stream: UnixStream, calls: Sender<Call>) -> io::Result<()> {
+    let mut writer = stream.try_clone()?;
+    for line in BufReader::new(stream).lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let answer = match parse(&line) {
+            Ok((id, request)) => {
+                let (reply, answer) = mpsc::channel();
+                let call = Call { request, id, reply };
+                // Gone once the TUI quits.
+                if calls.send(call).is_err() {
+                    return Ok(());
+                }
+                match answer.recv() {
+                    Ok(answer) => answer,
+                    Err(_) => return Ok(()),
+                }
+            }
+            Err(answer) => answer,
+        };
+        writeln!(writer, "{answer}")?;
+    }
+    Ok(())
+}
+
+/// The id and request of one line, or the error answer for it.
+fn parse(line: &str) -> Result<(Value, Request), String> {
+    let mut value: Value = serde_json::from_str(line)
+        .map_err(|e| error(&Value::Null, PARSE_ERROR, &e.to_string(), None))?;
+    let id = value
+        .as_object_mut()
+        .and_then(|o| {
+            o.remove("jsonrpc");
+            o.remove("id")
+        })
+        .unwrap_or(Value::Null);
+    let request = serde_json::from_value(value)
+        .map_err(|e| error(&id, INVALID_REQUEST, &e.to_string(), None))?;
+    Ok((id, request))
+}
+
+fn error(id: &Value, code: i64, message: &str, data: Option<Value>) -> String {
+    let mut error = json!({ "code": code, "message": message });
+    if let Some(data) = data {
+        error["data"] = data;
+    }
+    json!({ "jsonrpc": "2.0", "id": id, "error": error }).to_string()
+}
+
+fn monitors(app: &App) -> Value {
+    let states: Vec<MonitorState> =
+        app.monitors.iter().map(MonitorState::of).collect();
+    json!({ "monitors": states })
+}
+
+/// Carries out `request` on `app` like the same command in plain mode.
+/// Ok tells whether a change went to the compositor, whose answer is
+/// worth waiting for.
+pub fn handle(app: &mut App, request: &Request) -> Result<bool, String> {
+    // The TUI stays on the panel and monitor it showed.
+    let panel = app.panel;
+    let selected = app.selected_monitor().map(|m| m.name.clone());
+    app.error_message = None;
+    let sent = perform(app, request).map_err(|e| e.to_string());
+    app.panel = panel;
+    if let Some(name) = selected {
+        app.select_monitor_named(&name);
+    }
+    match app.error_message.clone() {
+        Some(error) => Err(error),
+        None => sent?,
+    }
+}
+
+type Sent = Result<Result<bool, String>, mpsc::SendError<WlMonitorAction>>;
+
+fn perform(app: &mut App, request: &Request) -> Sent {
+    let select = |app: &mut App, name: &str| {
+        app.select_monitor_named(name)
+            .then_some(())
+            .ok_or_else(|| format!("no monitor named {name}"))
+    };
+    match request {
+        Request::List => Ok(Ok(false)),
+        Request::Set(params) => {
+            if let Err(e) = select(app, &params.output) {
+                return Ok(Err(e));
+            }
+            let mut sent = false;
+            if let Some(enabled) = params.enabled {
+                match toggle(app, enabled)? {
+                    Ok(toggled) => sent |= toggled,
+                    Err(e) => return Ok(Err(e)),
+                }
+            }
+            if let Some(mode) = &params.mode {
+                app.set_mode_from_input(mode)?;
+                sent = true;
+            }
+            if let Some((x, y)) = params.position
+                && app.stage_position(x, y)
+            {
+                app.panel = Panel::Monitor;
+                app.apply_action()?;
+                sent = true;
+            }
+            if let Some(scale) = params.scale
+                && app.set_pending_scale(scale)
+            {
+                app.panel = Panel::Scale;
+                app.apply_action()?;
+                sent = true;
+            }
+            Ok(Ok(sent))
+        }
+        Request::Toggle { output } => {
+            if let Err(e) = select(app, output) {
+                return Ok(Err(e));
+            }
+            let enabled = app.selected_monitor().is_some_and(|m| m.enabled);
+            toggle(app, !enabled)
+        }
+        Request::ApplyProfile { .. } => {
+            Ok(Err("xwlm has no profiles to apply".to_string()))
+        }
+        Request::Save => {
+            app.mark_dirty();
+            app.save_config();
+            Ok(Ok(false))
+        }
+        Request::DistributeWorkspaces => {
+            app.distribute_workspaces();
+            Ok(Ok(false))
+        }
+    }
+}
+
+/// Enables or disables the selected monitor. Nobody is there to confirm
+/// disabling the last one, so that is refused.
+fn toggle(app: &mut App, enable: bool) -> Sent {
+    let Some(monitor) = app.selected_monitor() else {
+        return Ok(Ok(false));
+    };
+    let name = monitor.name.clone();
+    if monitor.enabled == enable {
+        return Ok(Ok(false));
+    }
+    app.toggle_monitor()?;
+    if app.confirm == Some(Confirm::LastMonitor) {
+        app.dismiss_confirm();
+        return Ok(Err(format!("{name} is the last enabled monitor")));
+    }
+    if app.has_pending_toggles() {
+        app.panel = Panel::Monitor;
+        app.apply_action()?;
+    }
+    Ok(Ok(true))
+}
+
+/// Calls handled by the UI loop whose answers wait for the compositor to
+/// report back, so they carry the monitor state the change led to.
+#[derive(Default)]
+pub struct Replies {
+    waiting: Vec<(Instant, Call, Result<(), String>)>,
+}
+
+impl Replies {
+    /// Handles `call` on `app`, answering it now or once the change it
+    /// sent has settled.
+    pub fn handle(&mut self, app: &mut App, call: Call, now: Instant) {
+        let outcome = handle(app, &call.request);
+        match outcome {
+            Ok(true) => {
+                let until = now + Duration::from_millis(CONTROL_SETTLE_MS);
+                self.waiting.push((until, call, Ok(())));
+            }
+            Ok(false) => answer(app, call, Ok(())),
+            Err(e) => answer(app, call, Err(e)),
+        }
+    }
+
+    /// Answers the calls that have waited long enough, or all of them once
+    /// monitor events came in.
+    pub fn flush(&mut self, app: &App, now: Instant, had_events: bool) {
+        let (due, waiting): (Vec<_>, Vec<_>) =
+            std::mem::take(&mut self.waiting)
+                .into_iter()
+                .partition(|(until, _, _)| had_events || *until <= now);
+        self.waiting = waiting;
+        for (_, call, outcome) in due {
+            answer(app, call, outcome);
+        }
+    }
+
+    /// How long until the next answer is due.
+    pub fn timeout(&self, now: Instant) -> Option<Duration> {
+        self.waiting
+            .iter()
+            .map(|(until, _, _)| until.saturating_duration_since(now))
+            .min()
+    }
+}
+
+fn answer(app: &App, call: Call, outcome: Result<(), String>) {
+    let answer = match outcome {
+        Ok(()) => json!({
+            "jsonrpc": "2.0",
+            "id": call.id,
+            "result": monitors(app),
+        })
+        .to_string(),
+        Err(e) => error(&call.id, FAILED, &e, Some(monitors(app))),
+    };
+    // The client may have hung up already.
+    let _ = call.reply.send(answer);
+}
+
+/// Sends `request` to the xwlm listening on `path` and returns its
+/// result, or `None` when none is.
+pub fn request(
+    path: &Path,
+    request: &Request,
+) -> Option<io::Result<Result<Value, String>>> {
+    let mut stream = UnixStream::connect(path).ok()?;
+    let mut line = serde_json::to_value(request).unwrap_or_default();
+    line["jsonrpc"] = json!("2.0");
+    line["id"] = json!(1);
+    Some((|| {
+        writeln!(stream, "{line}")?;
+        let mut answer = String::new();
+        BufReader::new(stream).read_line(&mut answer)?;
+        let mut answer: Value = serde_json::from_str(&answer)?;
+        Ok(match answer.get_mut("error") {
+            Some(error) => {
+                Err(error["message"].as_str().unwrap_or_default().to_string())
+            }
+            None => Ok(answer["result"].take()),
+        })
+    })())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compositor::Compositor::Hyprland;
+    use crate::test_support::{changed, harness, monitor, play};
+    use wlx_monitors::WlMonitorEvent;
+
+    #[test]
+    fn test_requests_parse_with_or_without_params() {
+        assert_eq!(
+            parse(r#"{"jsonrpc":"2.0","id":7,"method":"list"}"#),
+            Ok((json!(7), Request::List))
+        );
+        assert_eq!(
+            parse(
+                r#"{"id":"a","method":"set","params":{"output":"DP-1",
+                "position":[1920,0],"scale":1.5}}"#
+            ),
+            Ok((
+                json!("a"),
+                Request::Set(SetParams {
+                    output: "DP-1".into(),
+                    position: Some((1920, 0)),
+                    scale: Some(1.5),
+                    ..SetParams::default()
+                })
+            ))
+        );
+        let bad = parse(r#"{"id":2,"method":"frobnicate"}"#).unwrap_err();
+        assert!(bad.contains("-32600"), "{bad}");
+        assert!(bad.contains(r#""id":2"#), "{bad}");
+        let bad = parse("{").unwrap_err();
+        assert!(bad.contains("-32700"), "{bad}");
+    }
+
+    #[test]
+    fn test_harness_set_and_toggle_drive_the_same_actions_as_plain_mode() {
+        let (mut app, recorder) = harness(Hyprland, None);
+        play(
+            &mut app,
+            [WlMonitorEvent::InitialState(vec![
+                monitor("DP-1", 0, 0),
+                monitor("DP-2", 1920, 0),
+            ])],
+        );
+        recorder.take_actions();
+
+        let set = Request::Set(SetParams {
+            output: "DP-2".into(),
+            scale: Some(1.5),
+            ..SetParams::default()
+        });
+        assert_eq!(handle(&mut app, &set), Ok(true));
+        assert_eq!(recorder.take_actions(), ["scale DP-2 to 1.50"]);
+        // The TUI keeps showing what it showed.
+        assert_eq!(app.selected_monitor().unwrap().name, "DP-1");
+        assert_eq!(app.panel, Panel::Monitor);
+
+        let toggle = Request::Toggle {
+            output: "DP-1".into(),
+        };
+        assert_eq!(handle(&mut app, &toggle), Ok(true));
+        assert_eq!(recorder.take_actions(), ["toggle DP-1"]);
+
+        let unknown = Request::Toggle {
+            output: "DP-9".into(),
+        };
+        assert_eq!(
+            handle(&mut app, &unknown),
+            Err("no monitor named DP-9".into())
+        );
+    }
+
+    #[test]
+    fn test_harness_answers_wait_for_the_compositor() {
+        let (mut app, _recorder) = harness(Hyprland, None);
+        play(
+            &mut app,
+            [WlMonitorEvent::InitialState(vec![
+                monitor("DP-1", 0, 0),
+                monitor("DP-2", 1920, 0),
+            ])],
+        );
+        let (reply, answers) = mpsc::channel();
+        let call = Call {
+            request: Request::Set(SetParams {
+                output: "DP-2".into(),
+                position: Some((0, 1080)),
+                ..SetParams::default()
+            }),
+            id: json!(3),
+            reply,
+        };
+
+        let now = Instant::now();
+        let mut replies = Replies::default();
+        replies.handle(&mut app, call, now);
+        replies.flush(&app, now, false);
+        assert!(answers.try_recv().is_err());
+        assert!(replies.timeout(now).is_some());
+
+        play(&mut app, [changed(monitor("DP-2", 0, 1080))]);
+        replies.flush(&app, now, true);
+        let answer: Value =
+            serde_json::from_str(&answers.try_recv().unwrap()).unwrap();
+        assert_eq!(answer["id"], 3);
+        assert_eq!(answer["result"]["monitors"][1]["name"], "DP-2");
+        assert_eq!(answer["result"]["monitors"][1]["y"], 1080);
+        assert_eq!(replies.timeout(now), None);
+    }
+
+    #[test]
+    fn test_socket_round_trip() {
+        let path = env::temp_dir()
+            .join(format!("xwlm-control-{}.sock", std::process::id()));
+        let server = listen(&path).unwrap();
+        assert!(matches!(listen(&path), Err(ControlError::Running(_))));
+
+        let client = {
+            let path = path.clone();
+            thread::spawn(move || request(&path, &Request::Save))
+        };
+        let call = server.calls.recv().unwrap();
+        assert_eq!(call.request, Request::Save);
+        let (app, _recorder) = harness(Hyprland, None);
+        answer(&app, call, Err("nothing to save".into()));
+
+        let result = client.join().unwrap().unwrap().unwrap();
+        assert_eq!(result, Err("nothing to save".to_string()));
+        drop(server);
+        assert!(!path.exists());
+        assert!(request(&path, &Request::List).is_none());
+    }
+}
@@ -0,0 +1,294 @@
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    env, fs,
+    path::{Path, PathBuf},
+};
+#[cfg(any(test, feature = "replay"))]
+use std::{os::unix::net::UnixStream, sync::OnceLock};
+#[cfg(any(test, feature = "replay"))]
+use wayland_client::{
+    Connection, Proxy,
+    backend::{ObjectId, WeakBackend},
+};
+#[cfg(any(test, feature = "replay"))]
+use wayland_protocols_wlr::output_management::v1::client::{
+    zwlr_output_head_v1::ZwlrOutputHeadV1,
+    zwlr_output_mode_v1::ZwlrOutputModeV1,
+};
+use wlx_monitors::WlMonitor;
+#[cfg(any(test, feature = "replay"))]
+use wlx_monitors::{WlMonitorMode, WlPosition, WlResolution};
+
+use crate::{
+    cli::CliError,
+    history,
+    layout::LayoutMonitor,
+    state::WorkspaceAssignment,
+    xwlm_config::{Config, ConfigError},
+};
+
+/// Environment variables compositor detection and the UI depend on.
+const ENV_SIGNALS: [&str; 6] = [
+    "HYPRLAND_INSTANCE_SIGNATURE",
+    "SWAYSOCK",
+    "XDG_CURRENT_DESKTOP",
+    "WAYLAND_DISPLAY",
+    "LC_MESSAGES",
+    "LANG",
+];
+
+/// Serials shorter than this, like the "0" many monitors report, are
+/// left alone by redaction since they would match unrelated text.
+const MIN_REDACTED_SERIAL: usize = 4;
+
+/// Everything xwlm knew at one moment, written for bug reports and read
+/// back by `xwlm replay`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Dump {
+    pub version: String,
+    /// `git describe` and build date, empty in dumps from older builds.
+    #[serde(default)]
+    pub build: String,
+    pub compositor: String,
+    pub env: BTreeMap<String, String>,
+    /// The monitor config as read, empty when it could not be.
+    pub monitor_config: String,
+    /// What was wrong with the monitor config, if anything.
+    pub config_problem: Option<String>,
+    /// Staged changes, one line each.
+    pub pending: Vec<String>,
+    /// The last status and error messages, oldest first.
+    pub messages: Vec<String>,
+    pub config: Config,
+    #[serde(default, rename = "workspace")]
+    pub workspaces: Vec<WorkspaceAssignment>,
+    #[serde(default, rename = "monitor")]
+    pub monitors: Vec<DumpMonitor>,
+    /// What changed this session, as summed up on quit.
+    #[serde(default)]
+    pub session: Vec<String>,
+}
+
+/// A live monitor with every mode it advertises.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DumpMonitor {
+    #[serde(flatten)]
+    pub layout: LayoutMonitor,
+    pub make: String,
+    pub model: String,
+    pub serial_number: String,
+    #[serde(default, rename = "mode")]
+    pub modes: Vec<DumpMode>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DumpMode {
+    pub width: i32,
+    pub height: i32,
+    pub refresh_rate: i32,
+    pub preferred: bool,
+    pub current: bool,
+}
+
+impl DumpMonitor {
+    pub fn from_monitor(monitor: &WlMonitor) -> Self {
+        Self {
+            layout: LayoutMonitor::from_monitor(monitor),
+            make: monitor.make.clone(),
+            model: monitor.model.clone(),
+            serial_number: monitor.serial_number.clone(),
+            modes: monitor
+                .modes
+                .iter()
+                .map(|m| DumpMode {
+                    width: m.resolution.width,
+                    height: m.resolution.height,
+                    refresh_rate: m.refresh_rate,
+                    preferred: m.preferred,
+                    current: m.is_current,
+                })
+                .collect(),
+        }
+    }
+
+    /// A monitor backed by inert proxies, so nothing sent for it can reach
+    /// a compositor.
+    #[cfg(any(test, feature = "replay"))]
+    pub fn to_monitor(&self) -> WlMonitor {
+        let layout = &self.layout;
+        WlMonitor {
+            head_id: ObjectId::null(),
+            name: layout.name.clone(),
+            description: layout.description.clone(),
+            make: self.make.clone(),
+            model: self.model.clone(),
+            serial_number: self.serial_number.clone(),
+            modes: self.modes.iter().map(DumpMode::to_mode).collect(),
+            resolution: WlResolution {
+                width: layout.width,
+                height: layout.height,
+            },
+            position: WlPosition {
+                x: layout.x,
+                y: layout.y,
+            },
+            scale: layout.scale,
+            enabled: layout.enabled,
+            current_mode: None,
+            transform: layout.transform,
+            head: ZwlrOutputHeadV1::inert(inert_backend()),
+            changed: false,
+            last_mode: None,
+        }
+    }
+}
+
+impl DumpMode {
+    #[cfg(any(test, feature = "replay"))]
+    fn to_mode(&self) -> WlMonitorMode {
+        WlMonitorMode {
+            mode_id: ObjectId::null(),
+            head_id: ObjectId::null(),
+            refresh_rate: self.refresh_rate,
+            resolution: WlResolution {
+                width: self.width,
+                height: self.height,
+            },
+            preferred: self.preferred,
+            is_current: self.current,
+            proxy: ZwlrOutputModeV1::inert(inert_backend()),
+        }
+    }
+}
+
+/// Proxies need a backend to be inert against; a connection over one half
+/// of a socket pair is enough since nothing is ever sent on it.
+#[cfg(any(test, feature = "replay"))]
+pub fn inert_backend() -> WeakBackend {
+    static CONNECTION: OnceLock<(Connection, UnixStream)> = OnceLock::new();
+    let (conn, _) = CONNECTION.get_or_init(|| {
+        let (ours, theirs) = UnixStream::pair().expect("socket pair");
+        let conn = Connection::from_socket(ours).expect("inert connection");
+        (conn, theirs)
+    });
+    conn.backend().downgrade()
+}
+
+/// The dump's monitors for `xwlm replay`. Making them takes Wayland
+/// proxies, which only builds with the `replay` feature have.
+#[cfg(feature = "replay")]
+pub fn monitors(dump: &Dump) -> Result<Vec<WlMonitor>, CliError> {
+    Ok(dump.monitors.iter().map(DumpMonitor::to_monitor).collect())
+}
+
+#[cfg(not(feature = "replay"))]
+pub fn monitors(_: &Dump) -> Result<Vec<WlMonitor>, CliError> {
+    Err(CliError::NoReplay)
+}
+
+pub fn env_signals() -> BTreeMap<String, String> {
+    ENV_SIGNALS
+        .iter()
+        .filter_map(|&var| Some((var.to_string(), env::var(var).ok()?)))
+        .collect()
+}
+
+/// Where the TUI writes its dump.
+pub fn default_path() -> Option<PathBuf> {
+    history::state_path(&format!("debug-dump-{}.toml", history::now()))
+}
+
+/// The dump as TOML. With `redact`, every monitor serial is replaced
+/// wherever it shows up, descriptions and the monitor config included.
+pub fn render(dump: &Dump, redact: bool) -> Result<String, ConfigError> {
+    if !redact {
+        return Ok(toml::to_string(dump)?);
+    }
+    let mut dump = dump.clone();
+    dump.redact();
+    Ok(toml::to_string(&dump)?)
+}
+
+impl Dump {
+    /// Replaces every monitor serial in the fields that can hold one.
+    fn redact(&mut self) {
+        let serials: Vec<String> = self
+            .monitors
+            .iter()
+            .map(|m| m.serial_number.trim().to_string())
+            .filter(|s| s.len() >= MIN_REDACTED_SERIAL)
+            .collect();
+        let scrub = |text: &mut String| {
+            for serial in &serials {
+                if text.contains(serial.as_str()) {
+                    *text = text.replace(serial.as_str(), "REDACTED");
+                }
+            }
+        };
+        for monitor in &mut self.monitors {
+            if serials.contains(&monitor.serial_number.trim().to_string()) {
+                monitor.serial_number = "REDACTED".to_string();
+            }
+            scrub(&mut monitor.layout.description);
+        }
+        scrub(&mut self.monitor_config);
+        self.config_problem.iter_mut().for_each(scrub);
+        let config = &mut self.config;
+        let lists = [
+            &mut self.pending,
+            &mut self.messages,
+            &mut self.session,
+            &mut config.anchored_monitors,
+            &mut config.night_light_excluded,
+            &mut config.unmanaged_monitors,
+        ];
+        lists.into_iter().flatten().for_each(scrub);
+        for ws in &mut self.workspaces {
+            ws.monitor.iter_mut().chain(&mut ws.reserved).for_each(scrub);
+            ws.fallbacks.iter_mut().for_each(scrub);
+        }
+    }
+}
+
+pub fn load(path: &Path) -> Result<Dump, ConfigError> {
+    let content = fs::read_to_string(path).map_err(|e| ConfigError::Read {
+        path: path.to_string_lossy().into(),
+        source: e,
+    })?;
+    Ok(toml::from_str(&content)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{app, monitor};
+
+    #[test]
+    fn test_dump_roundtrips_and_redacts_serials() {
+        let (mut app, _rx) = app();
+        let mut dp1 = monitor("DP-1", 0, 0);
+        dp1.serial_number = "SN12345".into();
+        dp1.description = "Test Monitor SN12345".into();
+        app.set_monitors(vec![dp1, monitor("DP-2", 1920, 0)]);
+        app.set_status("hello");
+        let dump = app.debug_dump();
+
+        let content = render(&dump, false).unwrap();
+        let back: Dump = toml::from_str(&content).unwrap();
+        assert_eq!(back.monitors, dump.monitors);
+        assert_eq!(back.messages, ["hello"]);
+        assert_eq!(back.monitors[0].to_monitor().position.x, 0);
+        assert_eq!(back.monitors[1].to_monitor().modes.len(), 1);
+        assert_eq!(back.build, dump.build);
+        assert!(dump.build.starts_with("xwlm "));
+
+        let redacted = render(&dump, true).unwrap();
+        assert!(!redacted.contains("SN12345"));
+        let back: Dump = toml::from_str(&redacted).unwrap();
+        assert_eq!(back.monitors[0].serial_number, "REDACTED");
+        let description = &back.monitors[0].layout.description;
+        assert_eq!(description, "Test Monitor REDACTED");
+        assert_eq!(back.monitors[1], dump.monitors[1]);
+    }
+}
@@ -0,0 +1,237 @@
+use std::{fs, path::Path};
+
+const DRM_SYSFS: &str = "/sys/class/drm";
+const EDID_HEADER: [u8; 8] = [0x00, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x00];
+const CTA_EXTENSION: u8 = 0x02;
+const VENDOR_BLOCK: u8 = 3;
+/// IEEE OUIs of the HDMI 1.x and HDMI Forum vendor blocks, as stored.
+const HDMI_OUI: [u8; 3] = [0x03, 0x0c, 0x00];
+const HDMI_FORUM_OUI: [u8; 3] = [0xd8, 0x5d, 0xc4];
+
+/// Physical size of the panel in millimetres, as reported by its EDID.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PhysicalSize {
+    pub width_mm: u32,
+    pub height_mm: u32,
+}
+
+pub fn read_physical_size(connector: &str) -> Option<PhysicalSize> {
+    let bytes = read_edid(Path::new(DRM_SYSFS), connector)?;
+    parse_physical_size(&bytes)
+}
+
+/// What the EDID says about the fastest link the sink takes.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct LinkHints {
+    /// Highest TMDS clock, from the HDMI Forum or HDMI vendor block.
+    pub max_tmds_mhz: Option<u32>,
+    /// Whether the sink decodes DSC 1.2.
+    pub dsc: bool,
+    /// Whether the sink takes HDMI 2.1 fixed rate links.
+    pub frl: bool,
+}
+
+pub fn read_link_hints(connector: &str) -> Option<LinkHints> {
+    let bytes = read_edid(Path::new(DRM_SYSFS), connector)?;
+    parse_link_hints(&bytes)
+}
+
+fn read_edid(drm_dir: &Path, connector: &str) -> Option<Vec<u8>> {
+    let suffix = format!("-{connector}");
+    fs::read_dir(drm_dir)
+        .ok()?
+        .filter_map(Result::ok)
+        .find(|entry| {
+            let name = entry.file_name();
+            let name = name.to_string_lossy();
+            name.starts_with("card") && name.ends_with(&suffix)
+        })
+        .and_then(|entry| fs::read(entry.path().join("edid")).ok())
+}
+
+/// Prefers the first detailed timing descriptor (millimetre precision) and
+/// falls back to the basic display parameters (centimetres). Zero sizes,
+/// which projectors and some TVs report, yield `None`.
+pub fn parse_physical_size(edid: &[u8]) -> Option<PhysicalSize> {
+    if edid.len() < 128 || edid[..8] != EDID_HEADER {
+        return None;
+    }
+
+    let dtd = &edid[54..72];
+    let is_timing = dtd[0] != 0 || dtd[1] != 0;
+    if is_timing {
+        let width_mm = u32::from(dtd[12]) | (u32::from(dtd[14] & 0xf0) << 4);
+        let height_mm = u32::from(dtd[13]) | (u32::from(dtd[14] & 0x0f) << 8);
+        if width_mm > 0 && height_mm > 0 {
+            return Some(PhysicalSize {
+                width_mm,
+                height_mm,
+            });
+        }
+    }
+
+    let (width_cm, height_cm) = (u32::from(edid[21]), u32::from(edid[22]));
+    if width_cm == 0 || height_cm == 0 {
+        return None;
+    }
+    Some(PhysicalSize {
+        width_mm: width_cm * 10,
+        height_mm: height_cm * 10,
+    })
+}
+
+/// Reads the HDMI vendor blocks of the CTA extensions. The HDMI Forum
+/// block's clock wins over the older one's when both are set.
+pub fn parse_link_hints(edid: &[u8]) -> Option<LinkHints> {
+    if edid.len() < 128 || edid[..8] != EDID_HEADER {
+        return None;
+    }
+    let mut hints = LinkHints::default();
+    let mut hdmi_mhz = None;
+    let mut forum_mhz = None;
+    for ext in edid[128..].chunks_exact(128) {
+        if ext[0] != CTA_EXTENSION {
+            continue;
+        }
+        let end = usize::from(ext[2]).min(127);
+        let mut at = 4;
+        while at < end {
+            let tag = ext[at] >> 5;
+            let len = usize::from(ext[at] & 0x1f);
+            let Some(payload) = ext.get(at + 1..at + 1 + len) else {
+                break;
+            };
+            at += 1 + len;
+            if tag != VENDOR_BLOCK || len < 3 {
+                continue;
+            }
+            if payload[..3] == HDMI_OUI && len >= 7 && payload[6] != 0 {
+                hdmi_mhz = Some(u32::from(payload[6]) * 5);
+            } else if payload[..3] == HDMI_FORUM_OUI && len >= 5 {
+                if payload[4] != 0 {
+                    forum_mhz = Some(u32::from(payload[4]) * 5);
+                }
+                // Max_FRL_Rate is the high nibble of block byte 7 and
+                // DSC_1p2 the top bit of byte 11, counted from the header.
+                hints.frl |= payload.get(6).is_some_and(|b| b >> 4 != 0);
+                hints.dsc |= payload.get(10).is_some_and(|b| b & 0x80 != 0);
+            }
+        }
+    }
+    hints.max_tmds_mhz = forum_mhz.or(hdmi_mhz);
+    Some(hints)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn edid_with(basic_cm: (u8, u8), dtd_mm: Option<(u32, u32)>) -> Vec<u8> {
+        let mut edid = vec![0u8; 128];
+        edid[..8].copy_from_slice(&EDID_HEADER);
+        edid[21] = basic_cm.0;
+        edid[22] = basic_cm.1;
+        if let Some((w, h)) = dtd_mm {
+            edid[54] = 0x02;
+            edid[55] = 0x3a;
+            edid[66] = (w & 0xff) as u8;
+            edid[67] = (h & 0xff) as u8;
+            edid[68] = (((w >> 8) as u8) << 4) | ((h >> 8) as u8 & 0x0f);
+        }
+        edid
+    }
+
+    #[test]
+    fn test_parse_prefers_detailed_timing() {
+        let edid = edid_with((60, 34), Some((597, 336)));
+        assert_eq!(
+            parse_physical_size(&edid),
+            Some(PhysicalSize {
+                width_mm: 597,
+                height_mm: 336
+            })
+        );
+    }
+
+    #[test]
+    fn test_parse_falls_back_to_basic_params() {
+        let edid = edid_with((53, 30), None);
+        assert_eq!(
+            parse_physical_size(&edid),
+            Some(PhysicalSize {
+                width_mm: 530,
+                height_mm: 300
+            })
+        );
+    }
+
+    #[test]
+    fn test_parse_link_hints_from_cta_vendor_blocks() {
+        let mut edid = edid_with((60, 34), None);
+        edid.resize(256, 0);
+        let ext = &mut edid[128..];
+        ext[0] = CTA_EXTENSION;
+        // HDMI 1.4 block capped at 340 MHz, then an HDMI Forum block
+        // raising it to 600 MHz.
+        let blocks = [
+            0x67, 0x03, 0x0c, 0x00, 0x10, 0x00, 0x00, 0x44, //
+            0x67, 0xd8, 0x5d, 0xc4, 0x01, 0x78, 0x00, 0x00,
+        ];
+        ext[4..4 + blocks.len()].copy_from_slice(&blocks);
+        ext[2] = (4 + blocks.len()) as u8;
+
+        let hints = parse_link_hints(&edid).unwrap();
+        assert_eq!(hints.max_tmds_mhz, Some(600));
+        assert!(!hints.dsc && !hints.frl);
+
+        edid[128 + 2] = 4 + 8;
+        let hints = parse_link_hints(&edid).unwrap();
+        assert_eq!(hints.max_tmds_mhz, Some(340));
+    }
+
+    /// An EDID whose CTA extension holds one HDMI Forum block with
+    /// `payload` after the OUI.
+    fn edid_with_forum_block(payload: &[u8]) -> Vec<u8> {
+        let mut edid = edid_with((60, 34), None);
+        edid.resize(256, 0);
+        let ext = &mut edid[128..];
+        ext[0] = CTA_EXTENSION;
+        ext[4] = (VENDOR_BLOCK << 5) | (3 + payload.len()) as u8;
+        ext[5..8].copy_from_slice(&HDMI_FORUM_OUI);
+        ext[8..8 + payload.len()].copy_from_slice(payload);
+        ext[2] = (8 + payload.len()) as u8;
+        edid
+    }
+
+    #[test]
+    fn test_parse_link_hints_reads_frl_and_dsc() {
+        // Version, 600 MHz, SCDC flags, then Max_FRL_Rate 6 (48 Gbit/s).
+        let frl = edid_with_forum_block(&[0x01, 0x78, 0x80, 0x60]);
+        let hints = parse_link_hints(&frl).unwrap();
+        assert!(hints.frl && !hints.dsc);
+        assert_eq!(hints.max_tmds_mhz, Some(600));
+
+        // The same without FRL, then DSC_1p2 in the eighth payload byte.
+        let dsc = edid_with_forum_block(&[
+            0x01, 0x78, 0x80, 0x00, 0x00, 0x00, 0x00, 0x80,
+        ]);
+        let hints = parse_link_hints(&dsc).unwrap();
+        assert!(hints.dsc && !hints.frl);
+
+        // FAPA and VRR bits after the FRL byte are not FRL.
+        let neither =
+            edid_with_forum_block(&[0x01, 0x78, 0x80, 0x00, 0xf0, 0x00]);
+        let hints = parse_link_hints(&neither).unwrap();
+        assert!(!hints.frl && !hints.dsc);
+    }
+
+    #[test]
+    fn test_parse_rejects_zero_size_and_bad_header() {
+        assert_eq!(parse_physical_size(&edid_with((0, 0), None)), None);
+
+        let mut edid = edid_with((53, 30), None);
+        edid[0] = 0x42;
+        assert_eq!(parse_physical_size(&edid), None);
+        assert_eq!(parse_physical_size(&[0u8; 16]), None);
+    }
+}
@@ -0,0 +1,321 @@
+use std::{
+    collections::HashMap,
+    fmt, fs, io,
+    path::{Path, PathBuf},
+    sync::mpsc::{SendError, SyncSender},
+    time::SystemTime,
+};
+
+use wlx_monitors::WlMonitorAction;
+
+use crate::{
+    compositor::{
+        Compositor, format,
+        ipc::{self, Bar},
+    },
+    screencast::{self, Capture},
+    utils::{self, transform_label},
+};
+
+/// Everything the App does to the compositor and to files goes through
+/// here, so a dry run can log it instead of doing it.
+pub trait Effects: fmt::Debug {
+    fn send(
+        &mut self,
+        action: WlMonitorAction,
+    ) -> Result<(), SendError<WlMonitorAction>>;
+
+    fn write(&mut self, path: &Path, content: &str) -> io::Result<()>;
+
+    fn read(&self, path: &Path) -> io::Result<String>;
+
+    /// Keeps a copy of `path` as its newest of `count` backups, as
+    /// [`format::rotate_backup`] does. False when nothing was copied.
+    fn back_up(&mut self, path: &Path, count: usize) -> io::Result<bool>;
+
+    /// When `path` last changed, so a read of it can be reused until it
+    /// changes again. `None` when that cannot be told.
+    fn modified(&self, path: &Path) -> Option<SystemTime>;
+
+    /// Has the compositor re-read its config, or runs the monitor config
+    /// at `config` where that is how it applies. False when nothing was
+    /// reloaded.
+    fn reload(
+        &mut self,
+        compositor: Compositor,
+        config: &Path,
+    ) -> io::Result<bool>;
+
+    /// Runs `command`, such as the kscreen-doctor call that applies the
+    /// layout on KWin.
+    fn run(&mut self, command: &[String]) -> io::Result<()>;
+
+    /// Whether to go ahead with a side effect that has no method of its
+    /// own, such as a compositor reload or saving xwlm's settings. A dry
+    /// run logs `what` and says no.
+    fn allow(&mut self, what: &str) -> bool;
+
+    /// What was skipped so far, oldest first.
+    fn transcript(&self) -> &[String];
+
+    /// What is capturing the screen right now.
+    fn captures(&self) -> Vec<Capture>;
+
+    /// The bars pinned to outputs right now.
+    fn bars(&self, compositor: Compositor) -> Vec<Bar>;
+
+    /// Whether the compositor re-reads its config once it is written.
+    fn autoreloads(&self, compositor: Compositor) -> bool;
+
+    fn is_dry_run(&self) -> bool;
+}
+
+/// Sends actions to the monitor manager thread and writes files.
+#[derive(Debug)]
+pub struct Live {
+    sender: SyncSender<WlMonitorAction>,
+}
+
+impl Live {
+    pub fn new(sender: SyncSender<WlMonitorAction>) -> Self {
+        Self { sender }
+    }
+}
+
+impl Effects for Live {
+    fn send(
+        &mut self,
+        action: WlMonitorAction,
+    ) -> Result<(), SendError<WlMonitorAction>> {
+        self.sender.send(action)
+    }
+
+    fn write(&mut self, path: &Path, content: &str) -> io::Result<()> {
+        utils::write_atomic(path, content)
+    }
+
+    fn read(&self, path: &Path) -> io::Result<String> {
+        fs::read_to_string(path)
+    }
+
+    fn back_up(&mut self, path: &Path, count: usize) -> io::Result<bool> {
+        format::rotate_backup(path, count)
+    }
+
+    fn modified(&self, path: &Path) -> Option<SystemTime> {
+        fs::metadata(path).and_then(|m| m.modified()).ok()
+    }
+
+    fn reload(
+        &mut self,
+        compositor: Compositor,
+        config: &Path,
+    ) -> io::Result<bool> {
+        format::reload(compositor, config)
+    }
+
+    fn run(&mut self, command: &[String]) -> io::Result<()> {
+        format::run_command(command)
+    }
+
+    fn allow(&mut self, _what: &str) -> bool {
+        true
+    }
+
+    fn transcript(&self) -> &[String] {
+        &[]
+    }
+
+    fn captures(&self) -> Vec<Capture> {
+        screencast::active()
+    }
+
+    fn bars(&self, compositor: Compositor) -> Vec<Bar> {
+        ipc::bars(compositor)
+    }
+
+    fn autoreloads(&self, compositor: Compositor) -> bool {
+        ipc::autoreloads(compositor)
+    }
+
+    fn is_dry_run(&self) -> bool {
+        false
+    }
+}
+
+/// Logs every action and write instead of performing it.
+#[derive(Debug, Default)]
+pub struct DryRun {
+    transcript: Vec<String>,
+    /// When replaying a dump, the only files there are to read.
+    files: Option<HashMap<PathBuf, String>>,
+}
+
+impl DryRun {
+    /// A dry run that reads `files` instead of the disk, so a replayed
+    /// dump sees what its author's machine had.
+    pub fn replaying(files: HashMap<PathBuf, String>) -> Self {
+        Self {
+            transcript: Vec::new(),
+            files: Some(files),
+        }
+    }
+}
+
+impl Effects for DryRun {
+    fn send(
+        &mut self,
+        action: WlMonitorAction,
+    ) -> Result<(), SendError<WlMonitorAction>> {
+        self.transcript.push(describe(&action));
+        Ok(())
+    }
+
+    fn write(&mut self, path: &Path, content: &str) -> io::Result<()> {
+        let lines = content.lines().count();
+        self.transcript
+            .push(format!("write {} ({lines} lines)", path.display()));
+        Ok(())
+    }
+
+    /// Reading has no side effects, so a dry run sees the real files.
+    fn read(&self, path: &Path) -> io::Result<String> {
+        match &self.files {
+            Some(files) => files.get(path).cloned().ok_or_else(|| {
+                io::Error::new(io::ErrorKind::NotFound, "not in the dump")
+            }),
+            None => fs::read_to_string(path),
+        }
+    }
+
+    fn back_up(&mut self, path: &Path, count: usize) -> io::Result<bool> {
+        if count > 0 {
+            self.transcript.push(format!("back up {}", path.display()));
+        }
+        Ok(false)
+    }
+
+    /// A replayed dump never changes.
+    fn modified(&self, path: &Path) -> Option<SystemTime> {
+        match &self.files {
+            Some(files) => {
+                files.contains_key(path).then_some(SystemTime::UNIX_EPOCH)
+            }
+            None => fs::metadata(path).and_then(|m| m.modified()).ok(),
+        }
+    }
+
+    fn reload(
+        &mut self,
+        compositor: Compositor,
+        _config: &Path,
+    ) -> io::Result<bool> {
+        self.transcript.push(format!("reload {}", compositor.label()));
+        Ok(false)
+    }
+
+    fn run(&mut self, command: &[String]) -> io::Result<()> {
+        self.transcript.push(command.join(" "));
+        Ok(())
+    }
+
+    fn allow(&mut self, what: &str) -> bool {
+        self.transcript.push(what.to_string());
+        false
+    }
+
+    fn transcript(&self) -> &[String] {
+        &self.transcript
+    }
+
+    /// Looking has no side effects either; a replayed dump has nothing
+    /// live to look at.
+    fn captures(&self) -> Vec<Capture> {
+        match self.files {
+            Some(_) => Vec::new(),
+            None => screencast::active(),
+        }
+    }
+
+    fn bars(&self, compositor: Compositor) -> Vec<Bar> {
+        match self.files {
+            Some(_) => Vec::new(),
+            None => ipc::bars(compositor),
+        }
+    }
+
+    fn autoreloads(&self, compositor: Compositor) -> bool {
+        match self.files {
+            Some(_) => true,
+            None => ipc::autoreloads(compositor),
+        }
+    }
+
+    fn is_dry_run(&self) -> bool {
+        true
+    }
+}
+
+/// e.g. "move DP-1 to 1920,0"
+pub fn describe(action: &WlMonitorAction) -> String {
+    match action {
+        WlMonitorAction::Toggle {
+            name,
+            mode,
+            position,
+        } => {
+            let mut line = format!("toggle {name}");
+            if let Some((w, h, hz)) = mode {
+                line.push_str(&format!(" at {w}x{h}@{hz}"));
+            }
+            if let Some((x, y)) = position {
+                line.push_str(&format!(" to {x},{y}"));
+            }
+            line
+        }
+        WlMonitorAction::SwitchMode {
+            name,
+            width,
+            height,
+            refresh_rate,
+        } => format!("switch {name} to {width}x{height}@{refresh_rate}"),
+        WlMonitorAction::SetScale { name, scale } => {
+            format!("scale {name} to {scale:.2}")
+        }
+        WlMonitorAction::SetTransform { name, transform } => {
+            format!("transform {name} to {}", transform_label(*transform))
+        }
+        WlMonitorAction::SetPosition { name, x, y } => {
+            format!("move {name} to {x},{y}")
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dry_run_logs_instead_of_acting() {
+        let mut effects = DryRun::default();
+        effects
+            .send(WlMonitorAction::Toggle {
+                name: "DP-2".into(),
+                mode: Some((2560, 1440, 144)),
+                position: Some((1920, 0)),
+            })
+            .unwrap();
+        let path = Path::new("/nonexistent/xwlm-dry-run.conf");
+        effects.write(path, "a\nb\n").unwrap();
+        assert!(!effects.allow("reload Hyprland"));
+
+        assert_eq!(
+            effects.transcript(),
+            [
+                "toggle DP-2 at 2560x1440@144 to 1920,0",
+                "write /nonexistent/xwlm-dry-run.conf (2 lines)",
+                "reload Hyprland",
+            ]
+        );
+    }
+}
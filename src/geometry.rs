@@ -0,0 +1,380 @@
+use std::collections::VecDeque;
+
+use wlx_monitors::WlTransform;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rect {
+    pub x: i32,
+    pub y: i32,
+    pub w: i32,
+    pub h: i32,
+}
+
+impl Rect {
+    pub fn new(x: i32, y: i32, w: i32, h: i32) -> Self {
+        Self { x, y, w, h }
+    }
+
+    pub fn right(&self) -> i32 {
+        self.x + self.w
+    }
+
+    pub fn bottom(&self) -> i32 {
+        self.y + self.h
+    }
+
+    pub fn overlaps(&self, other: &Rect) -> bool {
+        self.x < other.right()
+            && self.right() > other.x
+            && self.y < other.bottom()
+            && self.bottom() > other.y
+    }
+}
+
+pub fn bounding_box(rects: &[Rect]) -> Option<Rect> {
+    let min_x = rects.iter().map(|r| r.x).min()?;
+    let min_y = rects.iter().map(|r| r.y).min()?;
+    let max_x = rects.iter().map(Rect::right).max()?;
+    let max_y = rects.iter().map(Rect::bottom).max()?;
+    Some(Rect::new(min_x, min_y, max_x - min_x, max_y - min_y))
+}
+
+/// Rotates `rect` clockwise by `quarter_turns` inside `bounds`, keeping the
+/// top-left corner of the bounding box fixed.
+pub fn rotate_rect(rect: Rect, bounds: Rect, quarter_turns: u8) -> Rect {
+    let rx = rect.x - bounds.x;
+    let ry = rect.y - bounds.y;
+
+    let (nx, ny, nw, nh) = match quarter_turns % 4 {
+        1 => (bounds.h - (ry + rect.h), rx, rect.h, rect.w),
+        2 => (
+            bounds.w - (rx + rect.w),
+            bounds.h - (ry + rect.h),
+            rect.w,
+            rect.h,
+        ),
+        3 => (ry, bounds.w - (rx + rect.w), rect.h, rect.w),
+        _ => (rx, ry, rect.w, rect.h),
+    };
+
+    Rect::new(bounds.x + nx, bounds.y + ny, nw, nh)
+}
+
+/// Adds `quarter_turns` of rotation to a transform, keeping its flip.
+pub fn compose_transform(t: WlTransform, quarter_turns: u8) -> WlTransform {
+    let (flipped, turns) = match t {
+        WlTransform::Normal => (false, 0),
+        WlTransform::Rotate90 => (false, 1),
+        WlTransform::Rotate180 => (false, 2),
+        WlTransform::Rotate270 => (false, 3),
+        WlTransform::Flipped => (true, 0),
+        WlTransform::Flipped90 => (true, 1),
+        WlTransform::Flipped180 => (true, 2),
+        WlTransform::Flipped270 => (true, 3),
+    };
+
+    match (flipped, (turns + quarter_turns) % 4) {
+        (false, 0) => WlTransform::Normal,
+        (false, 1) => WlTransform::Rotate90,
+        (false, 2) => WlTransform::Rotate180,
+        (false, _) => WlTransform::Rotate270,
+        (true, 0) => WlTransform::Flipped,
+        (true, 1) => WlTransform::Flipped90,
+        (true, 2) => WlTransform::Flipped180,
+        (true, _) => WlTransform::Flipped270,
+    }
+}
+
+/// How far `other` has to move to keep its gap to a monitor that changed
+/// size from `before` to `after`. Only monitors entirely to the right of or
+/// below the old rect move.
+pub fn reflow_offset(before: Rect, after: Rect, other: Rect) -> (i32, i32) {
+    let dx = if other.x >= before.right() {
+        after.w - before.w
+    } else {
+        0
+    };
+    let dy = if other.y >= before.bottom() {
+        after.h - before.h
+    } else {
+        0
+    };
+    (dx, dy)
+}
+
+/// Pushes every rect overlapping `rects[moved]` along `dir` (a unit step
+/// such as `(1, 0)`) until it just clears, cascading into whatever the
+/// pushed rects hit. Gives up with `false` after `max_pushes` pushes.
+pub fn push_clear(
+    rects: &mut [Rect],
+    moved: usize,
+    dir: (i32, i32),
+    max_pushes: usize,
+) -> bool {
+    let mut queue = VecDeque::from([moved]);
+    let mut pushes = 0;
+    while let Some(i) = queue.pop_front() {
+        let pusher = rects[i];
+        for (j, r) in rects.iter_mut().enumerate() {
+            if j == i || j == moved || !pusher.overlaps(r) {
+                continue;
+            }
+            if dir.0 > 0 {
+                r.x = pusher.right();
+            } else if dir.0 < 0 {
+                r.x = pusher.x - r.w;
+            } else if dir.1 > 0 {
+                r.y = pusher.bottom();
+            } else {
+                r.y = pusher.y - r.h;
+            }
+            pushes += 1;
+            if pushes > max_pushes {
+                return false;
+            }
+            queue.push_back(j);
+        }
+    }
+    true
+}
+
+/// Moves `rects[moved]` on along `dir` so one of its edges lies flush with
+/// the nearest edge of another rect at most `threshold` pixels ahead. A
+/// rect that overlaps something, or would once snapped, stays put.
+pub fn snap_ahead(
+    rects: &mut [Rect],
+    moved: usize,
+    dir: (i32, i32),
+    threshold: i32,
+) {
+    let r = rects[moved];
+    let others = || rects.iter().enumerate().filter(|&(i, _)| i != moved);
+    if threshold <= 0 || others().any(|(_, o)| o.overlaps(&r)) {
+        return;
+    }
+    let edges = |r: &Rect| {
+        if dir.0 != 0 {
+            [r.x, r.right()]
+        } else {
+            [r.y, r.bottom()]
+        }
+    };
+    let sign = dir.0 + dir.1;
+    let mut deltas: Vec<i32> = others()
+        .flat_map(|(_, o)| edges(o))
+        .flat_map(|edge| edges(&r).map(|own| edge - own))
+        .filter(|delta| (1..=threshold).contains(&(delta * sign)))
+        .collect();
+    deltas.sort_by_key(|delta| delta.abs());
+    let snapped = deltas.into_iter().map(|delta| {
+        let (dx, dy) = (delta * dir.0.abs(), delta * dir.1.abs());
+        Rect::new(r.x + dx, r.y + dy, r.w, r.h)
+    });
+    let mut fits = snapped.filter(|s| others().all(|(_, o)| !o.overlaps(s)));
+    if let Some(snapped) = fits.next() {
+        rects[moved] = snapped;
+    }
+}
+
+/// Shifts all rects so none has a negative coordinate.
+pub fn shift_to_origin(rects: &mut [Rect]) {
+    let dx = rects.iter().map(|r| r.x).min().unwrap_or(0).min(0);
+    let dy = rects.iter().map(|r| r.y).min().unwrap_or(0).min(0);
+    for r in rects {
+        r.x -= dx;
+        r.y -= dy;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn any_overlap(rects: &[Rect]) -> bool {
+        rects.iter().enumerate().any(|(i, a)| {
+            rects[i + 1..].iter().any(|b| a.overlaps(b))
+        })
+    }
+
+    #[test]
+    fn test_compose_transform_table() {
+        let cases = [
+            (WlTransform::Normal, 1, WlTransform::Rotate90),
+            (WlTransform::Normal, 2, WlTransform::Rotate180),
+            (WlTransform::Normal, 3, WlTransform::Rotate270),
+            (WlTransform::Rotate90, 1, WlTransform::Rotate180),
+            (WlTransform::Rotate270, 1, WlTransform::Normal),
+            (WlTransform::Rotate180, 3, WlTransform::Rotate90),
+            (WlTransform::Flipped, 1, WlTransform::Flipped90),
+            (WlTransform::Flipped90, 2, WlTransform::Flipped270),
+            (WlTransform::Flipped270, 1, WlTransform::Flipped),
+            (WlTransform::Flipped180, 3, WlTransform::Flipped90),
+        ];
+        for (input, turns, expected) in cases {
+            assert_eq!(compose_transform(input, turns), expected);
+        }
+    }
+
+    #[test]
+    fn test_compose_transform_full_turn_is_identity() {
+        for t in crate::constants::TRANSFORMS {
+            assert_eq!(compose_transform(t, 4), t);
+            assert_eq!(compose_transform(t, 0), t);
+        }
+    }
+
+    #[test]
+    fn test_rotate_side_by_side_becomes_stacked() {
+        let left = Rect::new(0, 0, 1920, 1080);
+        let right = Rect::new(1920, 0, 1920, 1080);
+        let bounds = bounding_box(&[left, right]).unwrap();
+
+        assert_eq!(rotate_rect(left, bounds, 1), Rect::new(0, 0, 1080, 1920));
+        assert_eq!(
+            rotate_rect(right, bounds, 1),
+            Rect::new(0, 1920, 1080, 1920)
+        );
+    }
+
+    #[test]
+    fn test_rotate_half_turn_mirrors_both_axes() {
+        let left = Rect::new(0, 0, 2560, 1440);
+        let right = Rect::new(2560, 360, 1920, 1080);
+        let bounds = bounding_box(&[left, right]).unwrap();
+
+        assert_eq!(
+            rotate_rect(left, bounds, 2),
+            Rect::new(1920, 0, 2560, 1440)
+        );
+        assert_eq!(rotate_rect(right, bounds, 2), Rect::new(0, 0, 1920, 1080));
+    }
+
+    #[test]
+    fn test_rotate_keeps_bounding_box_origin() {
+        let a = Rect::new(100, 200, 1920, 1080);
+        let b = Rect::new(2020, 200, 1280, 1024);
+        let bounds = bounding_box(&[a, b]).unwrap();
+
+        for turns in 1..4 {
+            let rotated: Vec<Rect> = [a, b]
+                .iter()
+                .map(|r| rotate_rect(*r, bounds, turns))
+                .collect();
+            let new_bounds = bounding_box(&rotated).unwrap();
+            assert_eq!((new_bounds.x, new_bounds.y), (100, 200));
+            assert!(!rotated[0].overlaps(&rotated[1]));
+        }
+    }
+
+    #[test]
+    fn test_rotate_four_quarter_turns_round_trips() {
+        let a = Rect::new(0, 0, 1920, 1080);
+        let b = Rect::new(1920, 0, 1080, 1920);
+        let mut rects = vec![a, b];
+        for _ in 0..4 {
+            let bounds = bounding_box(&rects).unwrap();
+            rects = rects.iter().map(|r| rotate_rect(*r, bounds, 1)).collect();
+        }
+        assert_eq!(rects, vec![a, b]);
+    }
+
+    #[test]
+    fn test_reflow_shifts_right_and_below_neighbours() {
+        let before = Rect::new(1920, 0, 2560, 1440);
+        let after = Rect::new(1920, 0, 3840, 2160);
+
+        let left = Rect::new(0, 0, 1920, 1080);
+        let right = Rect::new(4480, 0, 1920, 1080);
+        let below = Rect::new(1920, 1440, 1920, 1080);
+        let gap_right = Rect::new(4500, 100, 1920, 1080);
+
+        assert_eq!(reflow_offset(before, after, left), (0, 0));
+        assert_eq!(reflow_offset(before, after, right), (1280, 0));
+        assert_eq!(reflow_offset(before, after, below), (0, 720));
+        assert_eq!(reflow_offset(before, after, gap_right), (1280, 0));
+    }
+
+    #[test]
+    fn test_reflow_shrinking_pulls_neighbours_back() {
+        let before = Rect::new(0, 0, 3840, 2160);
+        let after = Rect::new(0, 0, 2560, 1440);
+        let right = Rect::new(3840, 0, 1920, 1080);
+        assert_eq!(reflow_offset(before, after, right), (-1280, 0));
+    }
+
+    #[test]
+    fn test_push_clears_every_overlapping_neighbour() {
+        // A portrait monitor moved right into a stack of two landscapes.
+        let mut rects = vec![
+            Rect::new(100, 0, 1440, 2560),
+            Rect::new(1440, 0, 1920, 1080),
+            Rect::new(1440, 1080, 1920, 1080),
+        ];
+        assert!(push_clear(&mut rects, 0, (1, 0), 8));
+        assert_eq!(rects[1], Rect::new(1540, 0, 1920, 1080));
+        assert_eq!(rects[2], Rect::new(1540, 1080, 1920, 1080));
+        assert!(!any_overlap(&rects));
+    }
+
+    #[test]
+    fn test_push_cascades_through_a_row() {
+        let mut rects = vec![
+            Rect::new(0, 10, 1920, 1080),
+            Rect::new(0, 1080, 1920, 1080),
+            Rect::new(0, 2160, 1920, 1080),
+        ];
+        assert!(push_clear(&mut rects, 0, (0, 1), 8));
+        assert_eq!(rects[1].y, 1090);
+        assert_eq!(rects[2].y, 2170);
+        assert!(!any_overlap(&rects));
+    }
+
+    #[test]
+    fn test_push_gives_up_past_the_cap() {
+        let mut rects: Vec<Rect> =
+            (0..5).map(|i| Rect::new(i * 100 - 50, 0, 100, 100)).collect();
+        rects[0].x = 0;
+        assert!(!push_clear(&mut rects, 0, (1, 0), 2));
+    }
+
+    #[test]
+    fn test_snap_closes_a_small_gap_ahead_only() {
+        let left = Rect::new(0, 0, 1920, 1080);
+        let right = Rect::new(1960, 0, 1920, 1080);
+        let mut rects = vec![left, right];
+        snap_ahead(&mut rects, 0, (1, 0), 50);
+        assert_eq!(rects[0].x, 40);
+
+        // Moving away, or from further off, it stays where it was put.
+        let mut rects = vec![left, right];
+        snap_ahead(&mut rects, 0, (-1, 0), 50);
+        assert_eq!(rects[0], left);
+        let mut rects = vec![left, Rect::new(2000, 0, 1920, 1080)];
+        snap_ahead(&mut rects, 0, (1, 0), 50);
+        assert_eq!(rects[0], left);
+
+        // Next to a neighbour, its top edge lines up with the other's.
+        let mut rects = vec![Rect::new(1920, 30, 1920, 1080), left];
+        snap_ahead(&mut rects, 0, (0, -1), 50);
+        assert_eq!(rects[0].y, 0);
+    }
+
+    #[test]
+    fn test_snap_leaves_overlaps_to_the_push() {
+        let mut rects =
+            vec![Rect::new(10, 0, 1920, 1080), Rect::new(1920, 0, 1920, 1080)];
+        snap_ahead(&mut rects, 0, (1, 0), 50);
+        assert_eq!(rects[0].x, 10);
+    }
+
+    #[test]
+    fn test_shift_to_origin() {
+        let mut rects =
+            vec![Rect::new(-20, 5, 10, 10), Rect::new(0, 0, 10, 10)];
+        shift_to_origin(&mut rects);
+        assert_eq!(
+            rects,
+            vec![Rect::new(0, 5, 10, 10), Rect::new(20, 0, 10, 10)]
+        );
+    }
+}
@@ -0,0 +1,265 @@
+use serde::{Deserialize, Serialize};
+use std::{
+    env, fs,
+    path::PathBuf,
+    process::Command,
+    sync::OnceLock,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use crate::{
+    constants::MAX_HISTORY,
+    layout::Layout,
+    utils,
+    xwlm_config::ConfigError,
+};
+
+const SECS_PER_DAY: i64 = 86_400;
+
+/// A layout as it was written to the compositor config.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Snapshot {
+    pub timestamp: i64,
+    pub layout: Layout,
+}
+
+impl Snapshot {
+    /// e.g. "2 monitors, DP-1 2560x1440@144 @0,0, HDMI-A-1 off, 14:32 today"
+    pub fn summary(&self, now: i64) -> String {
+        let count = self.layout.monitors.len();
+        let mut parts = vec![format!(
+            "{count} monitor{}",
+            if count == 1 { "" } else { "s" }
+        )];
+        for m in &self.layout.monitors {
+            if m.enabled {
+                parts.push(format!(
+                    "{} {}x{}@{} @{},{}",
+                    m.name, m.width, m.height, m.refresh_rate, m.x, m.y
+                ));
+            } else {
+                parts.push(format!("{} off", m.name));
+            }
+        }
+        parts.push(self.when(now));
+        parts.join(", ")
+    }
+
+    /// e.g. "14:32 today", "09:05 yesterday", "2026-03-02 18:40"
+    pub fn when(&self, now: i64) -> String {
+        format_time(self.timestamp, now, local_offset())
+    }
+}
+
+/// Oldest first, as stored on disk.
+#[derive(Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct History {
+    #[serde(default, rename = "snapshot")]
+    pub snapshots: Vec<Snapshot>,
+}
+
+impl History {
+    /// Returns false when `layout` matches the latest snapshot.
+    pub fn push(&mut self, layout: &Layout, timestamp: i64) -> bool {
+        if self.snapshots.last().is_some_and(|s| &s.layout == layout) {
+            return false;
+        }
+        self.snapshots.push(Snapshot {
+            timestamp,
+            layout: layout.clone(),
+        });
+        let excess = self.snapshots.len().saturating_sub(MAX_HISTORY);
+        self.snapshots.drain(..excess);
+        true
+    }
+}
+
+pub fn now() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0)
+}
+
+/// `file` in xwlm's state directory.
+pub fn state_path(file: &str) -> Option<PathBuf> {
+    let base = env::var("XDG_STATE_HOME")
+        .ok()
+        .filter(|p| !p.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| Some(utils::home_dir()?.join(".local/state")))?;
+    Some(base.join("xwlm").join(file))
+}
+
+fn history_path() -> Option<PathBuf> {
+    state_path("history.toml")
+}
+
+pub fn load_history() -> Result<History, ConfigError> {
+    match history_path() {
+        Some(path) => load_from_path(&path),
+        None => Ok(History::default()),
+    }
+}
+
+/// Appends `layout` to the stored history unless it is unchanged.
+pub fn record(layout: &Layout) -> Result<(), ConfigError> {
+    let Some(path) = history_path() else {
+        return Ok(());
+    };
+    let mut history = load_from_path(&path)?;
+    if history.push(layout, now()) {
+        save_to_path(&path, &history)?;
+    }
+    Ok(())
+}
+
+fn load_from_path(path: &PathBuf) -> Result<History, ConfigError> {
+    if !path.exists() {
+        return Ok(History::default());
+    }
+    let content = fs::read_to_string(path).map_err(|e| ConfigError::Read {
+        path: path.to_string_lossy().into(),
+        source: e,
+    })?;
+    Ok(toml::from_str(&content)?)
+}
+
+fn save_to_path(path: &PathBuf, history: &History) -> Result<(), ConfigError> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| ConfigError::Write {
+            path: parent.to_string_lossy().into(),
+            source: e,
+        })?;
+    }
+    fs::write(path, toml::to_string(history)?).map_err(|e| {
+        ConfigError::Write {
+            path: path.to_string_lossy().into(),
+            source: e,
+        }
+    })
+}
+
+/// e.g. "14:32:07", the local time of day.
+pub fn clock(timestamp: i64) -> String {
+    let secs = (timestamp + local_offset()).rem_euclid(SECS_PER_DAY);
+    format!("{:02}:{:02}:{:02}", secs / 3600, secs % 3600 / 60, secs % 60)
+}
+
+/// Seconds east of UTC, from `date +%z` since std has no timezone support.
+fn local_offset() -> i64 {
+    static OFFSET: OnceLock<i64> = OnceLock::new();
+    *OFFSET.get_or_init(|| {
+        Command::new("date")
+            .arg("+%z")
+            .output()
+            .ok()
+            .and_then(|o| {
+                parse_offset(String::from_utf8_lossy(&o.stdout).trim())
+            })
+            .unwrap_or(0)
+    })
+}
+
+fn parse_offset(s: &str) -> Option<i64> {
+    let (sign, digits) = match s.split_at_checked(1)? {
+        ("+", rest) => (1, rest),
+        ("-", rest) => (-1, rest),
+        _ => return None,
+    };
+    if digits.len() != 4 {
+        return None;
+    }
+    let hours: i64 = digits[..2].parse().ok()?;
+    let minutes: i64 = digits[2..].parse().ok()?;
+    Some(sign * (hours * 3600 + minutes * 60))
+}
+
+/// Days since the epoch to a civil date (Howard Hinnant's algorithm).
+fn civil_date(days: i64) -> (i64, i64, i64) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+fn format_time(timestamp: i64, now: i64, offset: i64) -> String {
+    let local = timestamp + offset;
+    let day = local.div_euclid(SECS_PER_DAY);
+    let secs = local.rem_euclid(SECS_PER_DAY);
+    let time = format!("{:02}:{:02}", secs / 3600, secs % 3600 / 60);
+    match (now + offset).div_euclid(SECS_PER_DAY) - day {
+        0 => format!("{time} today"),
+        1 => format!("{time} yesterday"),
+        _ => {
+            let (y, m, d) = civil_date(day);
+            format!("{y}-{m:02}-{d:02} {time}")
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{TempDir, monitor};
+
+    fn layout(x: i32) -> Layout {
+        Layout::capture(&[monitor("DP-1", x, 0)], &[])
+    }
+
+    #[test]
+    fn test_identical_snapshots_are_skipped() {
+        let mut history = History::default();
+        assert!(history.push(&layout(0), 1));
+        assert!(!history.push(&layout(0), 2));
+        assert!(history.push(&layout(1920), 3));
+        assert!(history.push(&layout(0), 4));
+        assert_eq!(history.snapshots.len(), 3);
+    }
+
+    #[test]
+    fn test_history_keeps_the_latest_entries() {
+        let mut history = History::default();
+        for i in 0..(MAX_HISTORY as i32 + 5) {
+            history.push(&layout(i), i64::from(i));
+        }
+        assert_eq!(history.snapshots.len(), MAX_HISTORY);
+        assert_eq!(history.snapshots[0].timestamp, 5);
+    }
+
+    #[test]
+    fn test_history_roundtrips_through_toml() {
+        let mut history = History::default();
+        history.push(&layout(0), 1_700_000_000);
+        let dir = TempDir::new("test-history");
+        let path = dir.path().join("history.toml");
+        save_to_path(&path, &history).unwrap();
+        assert_eq!(load_from_path(&path).unwrap(), history);
+    }
+
+    #[test]
+    fn test_format_time() {
+        // 2023-11-14 22:13:20 UTC
+        let ts = 1_700_000_000;
+        assert_eq!(format_time(ts, ts + 60, 0), "22:13 today");
+        assert_eq!(format_time(ts, ts + SECS_PER_DAY, 0), "22:13 yesterday");
+        assert_eq!(
+            format_time(ts, ts + 3 * SECS_PER_DAY, 0),
+            "2023-11-14 22:13"
+        );
+        assert_eq!(format_time(ts, ts, 2 * 3600), "00:13 today");
+    }
+
+    #[test]
+    fn test_parse_offset() {
+        assert_eq!(parse_offset("+0200"), Some(7200));
+        assert_eq!(parse_offset("-0530"), Some(-19_800));
+        assert_eq!(parse_offset("UTC"), None);
+    }
+}
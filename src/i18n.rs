@@ -0,0 +1,1009 @@
+use std::{env, fmt::Display, sync::OnceLock};
+
+/// English strings double as catalogue keys, so a missing translation falls
+/// back to the English text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Locale {
+    En,
+    De,
+}
+
+impl Locale {
+    /// Accepts a config value or a POSIX locale such as `de_DE.UTF-8`.
+    pub fn parse(tag: &str) -> Option<Self> {
+        let language = tag.split(['_', '-', '.', '@']).next()?;
+        match language.to_ascii_lowercase().as_str() {
+            "en" | "c" | "posix" => Some(Locale::En),
+            "de" => Some(Locale::De),
+            _ => None,
+        }
+    }
+
+    fn from_env() -> Self {
+        ["LC_ALL", "LC_MESSAGES", "LANG"]
+            .iter()
+            .filter_map(|var| env::var(var).ok())
+            .find(|value| !value.is_empty())
+            .and_then(|value| Locale::parse(&value))
+            .unwrap_or(Locale::En)
+    }
+
+    fn catalogue(self) -> &'static [(&'static str, &'static str)] {
+        match self {
+            Locale::En => &[],
+            Locale::De => DE,
+        }
+    }
+
+    fn lookup(self, msg: &'static str) -> &'static str {
+        self.catalogue()
+            .iter()
+            .find(|(key, _)| *key == msg)
+            .map_or(msg, |(_, text)| text)
+    }
+}
+
+static LOCALE: OnceLock<Locale> = OnceLock::new();
+
+/// Picks the locale from the `language` config key, falling back to
+/// LC_ALL, LC_MESSAGES and LANG. Only the first call has an effect.
+pub fn init(language: Option<&str>) {
+    let locale = language
+        .and_then(Locale::parse)
+        .unwrap_or_else(Locale::from_env);
+    let _ = LOCALE.set(locale);
+}
+
+fn locale() -> Locale {
+    if cfg!(test) {
+        return Locale::En;
+    }
+    *LOCALE.get_or_init(Locale::from_env)
+}
+
+pub fn t(msg: &'static str) -> &'static str {
+    locale().lookup(msg)
+}
+
+/// Translates `msg` and substitutes each `{}` with the next argument.
+pub fn tf(msg: &'static str, args: &[&dyn Display]) -> String {
+    fill(t(msg), args)
+}
+
+/// Like `tf`, choosing between a singular and plural form by `n`, which is
+/// also the first argument.
+pub fn tn(
+    one: &'static str,
+    many: &'static str,
+    n: usize,
+    args: &[&dyn Display],
+) -> String {
+    let mut all: Vec<&dyn Display> = vec![&n];
+    all.extend_from_slice(args);
+    tf(if n == 1 { one } else { many }, &all)
+}
+
+fn fill(template: &str, args: &[&dyn Display]) -> String {
+    let mut out = String::with_capacity(template.len());
+    let mut args = args.iter();
+    let mut parts = template.split("{}");
+    if let Some(first) = parts.next() {
+        out.push_str(first);
+    }
+    for part in parts {
+        if let Some(arg) = args.next() {
+            out.push_str(&arg.to_string());
+        }
+        out.push_str(part);
+    }
+    out
+}
+
+const DE: &[(&str, &str)] = &[
+    // Key bar and panel titles
+    ("switch panel", "Panel wechseln"),
+    ("history", "Verlauf"),
+    ("quit", "Beenden"),
+    ("Monitor Layout", "Monitor-Anordnung"),
+    ("Modes", "Modi"),
+    ("Scale", "Skalierung"),
+    ("Transform", "Drehung"),
+    ("Workspaces", "Arbeitsflächen"),
+    ("Wkspc", "AF"),
+    ("move", "verschieben"),
+    ("zoom", "Zoom"),
+    ("switch monitor", "Monitor wechseln"),
+    ("rotate layout", "Anordnung drehen"),
+    ("anchor", "verankern"),
+    ("power", "Strom"),
+    ("select", "auswählen"),
+    ("apply", "anwenden"),
+    ("previous mode", "vorheriger Modus"),
+    ("assign", "zuweisen"),
+    ("default", "Standard"),
+    ("persistent", "dauerhaft"),
+    ("edit id", "ID ändern"),
+    ("pin", "anheften"),
+    ("distribute", "verteilen"),
+    ("adjust", "anpassen"),
+    ("recommended", "empfohlen"),
+    ("clean scale", "glatte Skalierung"),
+    ("rotate", "drehen"),
+    ("confirm", "bestätigen"),
+    ("cancel", "abbrechen"),
+    ("manual", "manuell"),
+    ("restore", "wiederherstellen"),
+    ("close", "schließen"),
+    ("Layout history", "Anordnungsverlauf"),
+    ("Session timeline", "Sitzungsverlauf"),
+    ("timeline", "Verlauf der Sitzung"),
+    ("scroll", "blättern"),
+    ("No monitor events yet", "Noch keine Monitor-Ereignisse"),
+    ("connected", "verbunden"),
+    ("disconnected", "getrennt"),
+    ("enabled", "aktiviert"),
+    ("disabled", "deaktiviert"),
+    ("changed", "geändert"),
+    (
+        "{} reconnect in the last hour",
+        "{} Neuverbindung in der letzten Stunde",
+    ),
+    (
+        "{} reconnects in the last hour",
+        "{} Neuverbindungen in der letzten Stunde",
+    ),
+    (
+        "{} reconnected {} times in ten minutes; check its cable or link",
+        "{} hat sich in zehn Minuten {}-mal neu verbunden; Kabel oder \
+         Verbindung prüfen",
+    ),
+    ("Untouched monitors", "Unveränderte Monitore"),
+    ("Monitor config", "Monitor-Konfiguration"),
+    ("run setup", "Einrichtung starten"),
+    ("create file", "Datei anlegen"),
+    ("continue", "weiter"),
+    (
+        "Saving would create a new file there, which your compositor may \
+         not read.",
+        "Speichern würde dort eine neue Datei anlegen, die dein Compositor \
+         eventuell nicht liest.",
+    ),
+    (
+        "Monitors and workspaces from this file are ignored.",
+        "Monitore und Arbeitsflächen aus dieser Datei werden ignoriert.",
+    ),
+    (
+        "Monitor config not found: {}",
+        "Monitor-Konfiguration nicht gefunden: {}",
+    ),
+    (
+        "Cannot read monitor config {}: {}",
+        "Monitor-Konfiguration {} nicht lesbar: {}",
+    ),
+    (
+        "{} looks like a {} config, but {} is running",
+        "{} sieht nach einer {}-Konfiguration aus, es läuft aber {}",
+    ),
+    (
+        "{} has no monitor lines xwlm understands for {}",
+        "{} enthält keine Monitorzeilen, die xwlm für {} versteht",
+    ),
+    ("Created {}", "{} angelegt"),
+    ("Setup failed: {}", "Einrichtung fehlgeschlagen: {}"),
+    ("include", "übernehmen"),
+    ("skip", "überspringen"),
+    ("always include", "immer übernehmen"),
+    ("always", "immer"),
+    (
+        "Type include, skip or always: ",
+        "Gib übernehmen, überspringen oder immer ein: ",
+    ),
+    (
+        "Saving would also change monitors you did not touch:",
+        "Speichern würde auch Monitore ändern, die du nicht angefasst hast:",
+    ),
+    // Monitor panel
+    ("ON", "AN"),
+    ("OFF", "AUS"),
+    ("ON → OFF", "AN → AUS"),
+    ("OFF → ON", "AUS → AN"),
+    ("power: off", "Strom: aus"),
+    ("anchored", "verankert"),
+    ("⚠ fractional", "⚠ gebrochen"),
+    ("Enter to apply", "Enter zum Anwenden"),
+    ("— t to enable", "— t zum Aktivieren"),
+    ("No monitor selected", "Kein Monitor ausgewählt"),
+    ("No monitors", "Keine Monitore"),
+    ("Panel too small", "Panel zu klein"),
+    ("current", "aktuell"),
+    ("pending", "ausstehend"),
+    (
+        "⚠ logical {}×{} is not whole pixels",
+        "⚠ logisch {}×{} ist keine ganze Pixelzahl",
+    ),
+    ("↑↓ or +/- adjust", "↑↓ oder +/- anpassen"),
+    ("custom", "eigener"),
+    ("+ custom…", "+ eigener…"),
+    // Last monitor warning
+    ("Warning", "Warnung"),
+    ("⚠ Disable your last monitor?", "⚠ Letzten Monitor deaktivieren?"),
+    (
+        "No way to undo from here.",
+        "Das lässt sich hier nicht rückgängig machen.",
+    ),
+    ("To recover, you'll need to:", "Zur Wiederherstellung musst du:"),
+    ("1. Reboot your machine", "1. Den Rechner neu starten"),
+    ("2. Open a TTY session", "2. Eine TTY-Sitzung öffnen"),
+    ("3. Edit", "3. Bearbeite"),
+    ("and remove the disable line", "und entferne die disable-Zeile"),
+    (
+        "4. Reboot and log into your compositor",
+        "4. Neu starten und im Compositor anmelden",
+    ),
+    ("Yes", "Ja"),
+    ("No", "Nein"),
+    // Prompts
+    ("Workspace id", "Arbeitsflächen-ID"),
+    ("Custom mode (WxH@Hz)", "Eigener Modus (BxH@Hz)"),
+    // Status and errors
+    ("Action failed: {}", "Aktion fehlgeschlagen: {}"),
+    ("Failed to restore: {}", "Wiederherstellen fehlgeschlagen: {}"),
+    (
+        "Failed to toggle monitor: {}",
+        "Monitor umschalten fehlgeschlagen: {}",
+    ),
+    ("Failed to apply: {}", "Anwenden fehlgeschlagen: {}"),
+    ("Failed to load layout: {}", "Anordnung laden fehlgeschlagen: {}"),
+    (
+        "No clean scale in that direction",
+        "Keine glatte Skalierung in dieser Richtung",
+    ),
+    (
+        "⚠ Non-integer logical size: {}",
+        "⚠ Nicht ganzzahlige logische Größe: {}",
+    ),
+    (
+        "No physical size reported for this monitor",
+        "Dieser Monitor meldet keine physische Größe",
+    ),
+    ("Too many monitors in the way", "Zu viele Monitore im Weg"),
+    ("Reflowed {} monitor", "{} Monitor verschoben"),
+    ("Reflowed {} monitors", "{} Monitore verschoben"),
+    (", left anchored {}", ", verankert belassen: {}"),
+    ("Failed to reflow {}: {}", "Verschieben von {} fehlgeschlagen: {}"),
+    ("Powered {} on", "{} eingeschaltet"),
+    ("Powered {} off", "{} ausgeschaltet"),
+    ("Failed to set power: {}", "Strom schalten fehlgeschlagen: {}"),
+    (
+        "Failed to save anchored monitors: {}",
+        "Speichern der verankerten Monitore fehlgeschlagen: {}",
+    ),
+    ("Failed to save layout: {}", "Anordnung speichern fehlgeschlagen: {}"),
+    ("Failed to save config: {}", "Konfiguration speichern fehlgeschlagen: {}"),
+    (
+        "Failed to save settings: {}",
+        "Einstellungen speichern fehlgeschlagen: {}",
+    ),
+    (
+        "Failed to record layout history: {}",
+        "Verlauf speichern fehlgeschlagen: {}",
+    ),
+    (
+        "Failed to load layout history: {}",
+        "Verlauf laden fehlgeschlagen: {}",
+    ),
+    ("No saved layouts yet", "Noch keine gespeicherten Anordnungen"),
+    ("Restoring layout from {}", "Stelle Anordnung von {} wieder her"),
+    ("Created layout.toml from {}", "layout.toml aus {} erstellt"),
+    ("Failed to create layout: {}", "Anordnung erstellen fehlgeschlagen: {}"),
+    ("Invalid workspace id: {}", "Ungültige Arbeitsflächen-ID: {}"),
+    ("Workspace {} already exists", "Arbeitsfläche {} existiert bereits"),
+    (
+        "Failed to save pinned workspaces: {}",
+        "Speichern der angehefteten Arbeitsflächen fehlgeschlagen: {}",
+    ),
+    (
+        "No enabled monitors to distribute workspaces across",
+        "Keine aktiven Monitore zum Verteilen der Arbeitsflächen",
+    ),
+    (
+        "Workspaces already distributed ({})",
+        "Arbeitsflächen bereits verteilt ({})",
+    ),
+    (
+        "Distributed {} workspaces across {} monitors ({})",
+        "{} Arbeitsflächen auf {} Monitore verteilt ({})",
+    ),
+    ("No previous mode for {}", "Kein vorheriger Modus für {}"),
+    ("Switching {} back to {}", "Stelle {} zurück auf {}"),
+    (
+        "Invalid mode {}, expected WxH@Hz",
+        "Ungültiger Modus {}, erwartet BxH@Hz",
+    ),
+    (
+        "Failed to save custom modes: {}",
+        "Speichern der eigenen Modi fehlgeschlagen: {}",
+    ),
+    // First-time setup
+    ("first-time setup", "Ersteinrichtung"),
+    ("Extraction failed: {}", "Extraktion fehlgeschlagen: {}"),
+    ("Path cannot be empty", "Der Pfad darf nicht leer sein"),
+    (
+        "Are you sure? This file will be overwritten with monitor settings. \
+         If it's your main Hyprland/Sway config (like hyprland.conf), you \
+         will LOSE all your keybinds, animations, window rules, and other \
+         settings! Use a separate file like monitors.conf or output.conf \
+         instead. Press Enter again to confirm.",
+        "Bist du sicher? Diese Datei wird mit Monitoreinstellungen \
+         überschrieben. Ist sie deine Haupt-Konfiguration von Hyprland/Sway \
+         (etwa hyprland.conf), VERLIERST du alle Tastenkürzel, Animationen, \
+         Fensterregeln und übrigen Einstellungen! Nutze stattdessen eine \
+         eigene Datei wie monitors.conf oder output.conf. Drücke zur \
+         Bestätigung erneut Enter.",
+    ),
+    (
+        "File does not exist. Please enter a valid path.",
+        "Die Datei existiert nicht. Bitte gib einen gültigen Pfad ein.",
+    ),
+    (
+        "Detected existing {} monitor config at:",
+        "Vorhandene {}-Monitorkonfiguration gefunden unter:",
+    ),
+    (
+        "Found {} monitor config line(s) in:",
+        "{} Monitor-Konfigurationszeile(n) gefunden in:",
+    ),
+    ("Consolidate to:", "Zusammenführen nach:"),
+    (
+        "Enter the path to your {} monitor config file:",
+        "Pfad zu deiner {}-Monitorkonfiguration:",
+    ),
+    (
+        "WARNING: Don't use your main config file! Use a separate file like \
+         monitors.conf",
+        "WARNUNG: Nicht die Haupt-Konfiguration verwenden! Nutze eine eigene \
+         Datei wie monitors.conf",
+    ),
+    ("Path", "Pfad"),
+    // Shared App validation
+    (
+        "Scale must be between {} and {}",
+        "Die Skalierung muss zwischen {} und {} liegen",
+    ),
+    ("{} is disabled", "{} ist deaktiviert"),
+    ("Positions cannot be negative", "Positionen dürfen nicht negativ sein"),
+    (
+        "That position overlaps another monitor",
+        "Diese Position überlappt einen anderen Monitor",
+    ),
+    ("No monitor named {}", "Kein Monitor namens {}"),
+    ("No workspace {}", "Keine Arbeitsfläche {}"),
+    // Plain mode
+    ("{} ({}): off", "{} ({}): aus"),
+    (
+        "{} ({}): on, {}x{} at {} Hz, position {},{}, scale {}",
+        "{} ({}): an, {}x{} mit {} Hz, Position {},{}, Skalierung {}",
+    ),
+    ("Type help for a list of commands.", "help zeigt alle Befehle."),
+    ("Error: {}", "Fehler: {}"),
+    ("Selected {}.", "{} ausgewählt."),
+    (
+        "Warning: the logical size {}x{} is not whole pixels.",
+        "Warnung: die logische Größe {}x{} ist keine ganze Pixelzahl.",
+    ),
+    ("Workspace {} moved to {}.", "Arbeitsfläche {} auf {} verschoben."),
+    ("Nothing to apply.", "Nichts anzuwenden."),
+    ("Saved to {}.", "Gespeichert in {}."),
+    ("{} is already enabled.", "{} ist bereits aktiviert."),
+    ("{} is already disabled.", "{} ist bereits deaktiviert."),
+    (
+        "{} is your last enabled monitor. Disabling it leaves you with no \
+         display, and {} has to be edited from a TTY to undo it.",
+        "{} ist dein letzter aktiver Monitor. Ohne ihn bleibt kein Bild, \
+         und {} muss zum Rückgängigmachen in einer TTY bearbeitet werden.",
+    ),
+    (
+        "Type yes to disable it anyway: ",
+        "Tippe ja, um ihn trotzdem zu deaktivieren: ",
+    ),
+    ("yes", "ja"),
+    ("Kept {} enabled.", "{} bleibt aktiviert."),
+    ("No monitors.", "Keine Monitore."),
+    (" (selected)", " (ausgewählt)"),
+    ("Now {}.", "Jetzt {}."),
+    ("{} was disconnected.", "{} wurde getrennt."),
+    ("show all monitors", "alle Monitore anzeigen"),
+    (
+        "select a monitor by name or number",
+        "Monitor nach Name oder Nummer auswählen",
+    ),
+    ("switch the selected monitor's mode", "Modus des Monitors wechseln"),
+    ("move the selected monitor", "Monitor verschieben"),
+    ("set the selected monitor's scale", "Skalierung des Monitors setzen"),
+    ("turn the selected monitor on or off", "Monitor ein- oder ausschalten"),
+    ("put a workspace on a monitor", "Arbeitsfläche einem Monitor zuweisen"),
+    (
+        "apply positions staged by a resize",
+        "durch Größenänderung vorgemerkte Positionen anwenden",
+    ),
+    ("write the monitor config", "Monitorkonfiguration schreiben"),
+    ("leave", "beenden"),
+    ("Options", "Optionen"),
+    ("change", "ändern"),
+    ("Power", "Strom"),
+    ("Anchored", "Verankert"),
+    ("on", "an"),
+    ("off", "aus"),
+    ("no", "nein"),
+    ("New monitor", "Neuer Monitor"),
+    ("enable right", "rechts aktivieren"),
+    ("enable saved", "gespeichert aktivieren"),
+    ("ignore", "ignorieren"),
+    (
+        "{} connected without a place in the layout.",
+        "{} wurde ohne Platz in der Anordnung verbunden.",
+    ),
+    (
+        "Type right, saved or ignore: ",
+        "Tippe rechts, gespeichert oder ignorieren: ",
+    ),
+    ("right", "rechts"),
+    ("saved", "gespeichert"),
+    ("Moved new monitor {} to {},{}", "Neuen Monitor {} nach {},{} verschoben"),
+    ("Enabled new monitor {} at {},{}", "Neuen Monitor {} bei {},{} aktiviert"),
+    ("Failed to place {}: {}", "{} konnte nicht platziert werden: {}"),
+    ("Conflicting monitor rules", "Widersprüchliche Monitorregeln"),
+    ("extract", "auslagern"),
+    (
+        "These rules also configure monitors besides {}:",
+        "Diese Regeln konfigurieren neben {} ebenfalls Monitore:",
+    ),
+    (
+        "Whichever file is sourced last wins.",
+        "Es gilt die zuletzt eingebundene Datei.",
+    ),
+    (
+        "Failed to remove monitor rules: {}",
+        "Monitorregeln konnten nicht entfernt werden: {}",
+    ),
+    ("Removed {} stray monitor rule", "{} verstreute Monitorregel entfernt"),
+    ("Removed {} stray monitor rules", "{} verstreute Monitorregeln entfernt"),
+    (
+        "{} monitor rule outside {}, first at {}:{}",
+        "{} Monitorregel außerhalb von {}, zuerst in {}:{}",
+    ),
+    (
+        "{} monitor rules outside {}, first at {}:{}",
+        "{} Monitorregeln außerhalb von {}, zuerst in {}:{}",
+    ),
+    ("Dry run: {}", "Probelauf: {}"),
+    ("DRY RUN", "PROBELAUF"),
+    ("dry run log", "Probelauf-Protokoll"),
+    ("Dry run log", "Probelauf-Protokoll"),
+    ("Nothing skipped yet.", "Noch nichts übersprungen."),
+    ("Preview", "Vorschau"),
+    ("preview", "Vorschau"),
+    ("keep", "behalten"),
+    ("revert", "zurücksetzen"),
+    ("{}: {}, back to {} in {}s", "{}: {}, zurück zu {} in {} s"),
+    ("Reverted {} to {}", "{} auf {} zurückgesetzt"),
+    ("Failed to revert: {}", "Zurücksetzen fehlgeschlagen: {}"),
+    (
+        "Saved {} monitor snapped to whole logical pixels: {}",
+        "{} Monitor auf ganze logische Pixel ausgerichtet gespeichert: {}",
+    ),
+    (
+        "Saved {} monitors snapped to whole logical pixels: {}",
+        "{} Monitore auf ganze logische Pixel ausgerichtet gespeichert: {}",
+    ),
+    (
+        "saved {} unavailable on {}, using {}",
+        "gespeicherter Modus {} auf {} nicht verfügbar, nutze {}",
+    ),
+    (
+        "Failed to load parked monitors: {}",
+        "Abgeschaltete Monitore konnten nicht geladen werden: {}",
+    ),
+    (
+        "Failed to remember parked monitors: {}",
+        "Abgeschaltete Monitore konnten nicht gemerkt werden: {}",
+    ),
+    ("reloading…", "lädt neu…"),
+    ("may require custom modeline", "braucht evtl. eigene Modeline"),
+    (
+        "{} may reject scales above {}",
+        "{} lehnt Skalierungen über {} evtl. ab",
+    ),
+    (
+        "⚠ {} may reject scales above {}",
+        "⚠ {} lehnt Skalierungen über {} evtl. ab",
+    ),
+    ("Night light", "Nachtlicht"),
+    ("excluded", "ausgenommen"),
+    ("wlsunset did not start again", "wlsunset ist nicht wieder gestartet"),
+    (
+        "Failed to restart wlsunset: {}",
+        "wlsunset konnte nicht neu gestartet werden: {}",
+    ),
+    (
+        "No monitor matches {}, starting as usual",
+        "Kein Monitor passt zu {}, normaler Start",
+    ),
+    ("REPLAY", "WIEDERGABE"),
+    (
+        "No state directory for the dump",
+        "Kein Statusverzeichnis für den Dump",
+    ),
+    ("Wrote debug dump to {}", "Debug-Dump nach {} geschrieben"),
+    (
+        "Failed to write debug dump: {}",
+        "Debug-Dump konnte nicht geschrieben werden: {}",
+    ),
+    (
+        "may exceed link bandwidth",
+        "übersteigt evtl. die Verbindungsbandbreite",
+    ),
+    (
+        "{} may exceed the link bandwidth of {}, Enter again to apply it \
+         anyway",
+        "{} übersteigt evtl. die Bandbreite der Verbindung von {}, zum \
+         Übernehmen erneut Enter drücken",
+    ),
+    ("Apply everything", "Alles anwenden"),
+    ("apply all", "alles anwenden"),
+    ("workspace assignments", "Arbeitsbereich-Zuordnungen"),
+    ("monitor options", "Monitoroptionen"),
+    (
+        "Applying everything would leave no monitor on",
+        "Alles anzuwenden ließe keinen Monitor eingeschaltet",
+    ),
+    (
+        "⚠ focus {} misses {}, {} px off",
+        "⚠ Fokus {} verfehlt {}, {} px versetzt",
+    ),
+    ("aligns", "richtet aus"),
+    ("align", "ausrichten"),
+    ("up", "oben"),
+    ("down", "unten"),
+    ("left", "links"),
+    ("Focus reaches every neighbour", "Fokus erreicht jeden Nachbarn"),
+    (
+        "{} lined up with {}, Enter to apply",
+        "{} an {} ausgerichtet, Enter übernimmt",
+    ),
+    (
+        "{} recognized as former {} — settings restored",
+        "{} als früherer {} erkannt — Einstellungen wiederhergestellt",
+    ),
+    (
+        "Failed to restore {}: {}",
+        "{} konnte nicht wiederhergestellt werden: {}",
+    ),
+    ("unsaved changes", "ungespeicherte Änderungen"),
+    ("saved at {}", "gespeichert um {}"),
+    ("not saved yet", "noch nicht gespeichert"),
+    ("auto-reload on", "Auto-Reload an"),
+    ("auto-reload off", "Auto-Reload aus"),
+    ("Keep this change?", "Diese Änderung behalten?"),
+    (
+        "{} holds this terminal, reverting in {}s",
+        "{} zeigt dieses Terminal, Rückkehr in {}s",
+    ),
+    ("Kept the change to {}", "Änderung an {} behalten"),
+    ("Reverted the change to {}", "Änderung an {} zurückgenommen"),
+    ("{} (not connected)", "{} (nicht angeschlossen)"),
+    ("copy to", "kopieren nach"),
+    ("Copy {}'s settings to", "Einstellungen von {} kopieren nach"),
+    ("next to it", "daneben"),
+    ("stage", "vormerken"),
+    ("and place it right of {}", "und rechts von {} platzieren"),
+    ("keep its position", "Position beibehalten"),
+    (
+        "No other enabled monitor to copy to",
+        "Kein anderer aktiver Monitor zum Kopieren",
+    ),
+    ("{} cannot do {}, using {}", "{} kann {} nicht, nutze {}"),
+    ("{} lists no modes to pick from", "{} bietet keine Modi an"),
+    ("no room right of {}", "rechts von {} ist kein Platz"),
+    (
+        "Copied {}'s settings to {}, Enter to apply",
+        "Einstellungen von {} nach {} kopiert, Enter übernimmt",
+    ),
+    (
+        "{} is being captured by {}, do it again to go ahead anyway",
+        "{} wird von {} aufgenommen, zum Fortfahren erneut auslösen",
+    ),
+    ("Kept the current layout", "Aktuelles Layout beibehalten"),
+    (
+        "No workspace assignments to clear",
+        "Keine Arbeitsflächen-Zuordnungen zum Entfernen",
+    ),
+    (
+        "Cleared {} workspace assignments",
+        "{} Arbeitsflächen-Zuordnungen entfernt",
+    ),
+    ("clear all", "alle lösen"),
+    ("Clear workspaces", "Arbeitsflächen lösen"),
+    (
+        "Pinned workspaces keep their monitor.",
+        "Angeheftete Arbeitsflächen behalten ihren Monitor.",
+    ),
+    (
+        "The compositor config is rewritten right away.",
+        "Die Compositor-Konfiguration wird sofort neu geschrieben.",
+    ),
+    ("Yes, clear", "Ja, lösen"),
+    (
+        "Press t on a monitor to turn it back on.",
+        "Mit t auf einem Monitor schaltest du ihn wieder ein.",
+    ),
+    ("Restore layout", "Layout wiederherstellen"),
+    ("Yes, restore", "Ja, wiederherstellen"),
+    ("⚠ Unassign {} workspaces?", "⚠ {} Arbeitsflächen-Zuordnungen lösen?"),
+    ("⚠ The layout from {} disables:", "⚠ Das Layout von {} deaktiviert:"),
+    ("Showing modes of every aspect", "Modi aller Seitenverhältnisse"),
+    (
+        "Showing {} modes only, {} hidden",
+        "Nur {}-Modi, {} ausgeblendet",
+    ),
+    ("Modes ({} hidden)", "Modi ({} ausgeblendet)"),
+    ("interlaced", "interlaced"),
+    ("same aspect", "gleiches Format"),
+    (
+        "Applying that would leave no monitor on",
+        "Das würde keinen Monitor eingeschaltet lassen",
+    ),
+    ("Failed to read {}: {}", "{} konnte nicht gelesen werden: {}"),
+    ("The live layout matches {}", "Das aktuelle Layout entspricht {}"),
+    (
+        "Writing the live settings of {} to {}",
+        "Schreibe die aktuellen Einstellungen von {} nach {}",
+    ),
+    ("Applying the config to {}", "Wende die Konfiguration auf {} an"),
+    ("vs config", "vs. Konfig"),
+    ("Live vs config", "Aktuell vs. Konfiguration"),
+    (
+        "What {} says  →  what runs now",
+        "Was {} sagt  →  was gerade läuft",
+    ),
+    ("keep live", "aktuell behalten"),
+    ("apply config", "Konfig anwenden"),
+    ("mode", "Modus"),
+    ("position", "Position"),
+    ("scale", "Skalierung"),
+    ("transform", "Drehung"),
+    ("new", "neu"),
+    (
+        "Staged the defaults for {} on {}; Enter applies them",
+        "Standardwerte für {} auf {} vorgemerkt; Enter wendet sie an",
+    ),
+    (
+        "Failed to apply the defaults to {}: {}",
+        "Anwenden der Standardwerte auf {} fehlgeschlagen: {}",
+    ),
+    (
+        "Applied the defaults for {} to {}",
+        "Standardwerte für {} auf {} angewendet",
+    ),
+    (
+        "{} does not report its make and model",
+        "{} meldet weder Hersteller noch Modell",
+    ),
+    (
+        "Failed to save the defaults: {}",
+        "Speichern der Standardwerte fehlgeschlagen: {}",
+    ),
+    (
+        "New {} monitors start at {}, scale {}",
+        "Neue {}-Monitore starten mit {}, Skalierung {}",
+    ),
+    ("make default", "als Standard"),
+    ("this monitor", "dieser Monitor"),
+    ("Wkspc of {}", "AF von {}"),
+    ("Workspaces of {}", "Arbeitsflächen von {}"),
+    ("Showing workspaces of {} only", "Nur Arbeitsflächen von {}"),
+    ("Showing every workspace", "Alle Arbeitsflächen"),
+    (
+        "Failed to remember the filter: {}",
+        "Filter konnte nicht gespeichert werden: {}",
+    ),
+    (
+        "Failed to write the recovery config: {}",
+        "Die Notfallkonfiguration konnte nicht geschrieben werden: {}",
+    ),
+    ("Applied {} to {}", "{} auf {} angewendet"),
+    (
+        "Or load the recovery config from a TTY:",
+        "Oder die Notfallkonfiguration aus einem TTY laden:",
+    ),
+    (
+        "No monitors to assign workspaces to",
+        "Keine Monitore, denen Arbeitsflächen zugewiesen werden können",
+    ),
+    ("then {}", "danach {}"),
+    ("fallbacks", "Ausweichmonitore"),
+    ("Monitors for WS {}", "Monitore für WS {}"),
+    ("add/remove", "hinzufügen/entfernen"),
+    ("Failed to reload {}: {}", "{} konnte nicht neu geladen werden: {}"),
+    ("Managed", "Verwaltet"),
+    (
+        "{} is not managed by xwlm, do it again to go ahead anyway",
+        "{} wird nicht von xwlm verwaltet, zum Fortfahren erneut ausführen",
+    ),
+    ("unmanaged", "nicht verwaltet"),
+    ("Settings", "Einstellungen"),
+    ("save", "speichern"),
+    ("choose", "wählen"),
+    ("on restart", "nach Neustart"),
+    ("Not a number", "Keine Zahl"),
+    ("Not a whole number", "Keine ganze Zahl"),
+    ("Must be at least {}", "Mindestens {}"),
+    ("zoom_min is above zoom_max", "zoom_min liegt über zoom_max"),
+    (
+        "Takes effect when xwlm starts again",
+        "Wirkt beim nächsten Start von xwlm",
+    ),
+    (
+        "{} has a layer surface on {}; you may need to restart it",
+        "{} hat eine Layer-Fläche auf {}; eventuell neu starten",
+    ),
+    (
+        "Another xwlm already listens on {}",
+        "Ein anderes xwlm lauscht bereits auf {}",
+    ),
+    ("No control socket: {}", "Kein Steuer-Socket: {}"),
+    ("Quit", "Beenden"),
+    ("Yes, quit", "Ja, beenden"),
+    ("reload failed ({} why)", "Neuladen fehlgeschlagen ({} warum)"),
+    ("⚠ The last reload failed:", "⚠ Das letzte Neuladen schlug fehl:"),
+    (
+        "The config is written but not in use.",
+        "Die Konfiguration ist geschrieben, aber nicht aktiv.",
+    ),
+    (
+        "⚠ Not everything is saved yet.",
+        "⚠ Noch ist nicht alles gespeichert.",
+    ),
+    ("Staged changes are dropped.", "Vorgemerkte Änderungen gehen verloren."),
+    ("xwlm could not start", "xwlm konnte nicht starten"),
+    ("Press any key to close.", "Zum Schließen eine Taste drücken."),
+    ("OBSERVER", "BEOBACHTER"),
+    (
+        "Only observing: nothing can be changed",
+        "Nur beobachten: nichts lässt sich ändern",
+    ),
+    (
+        "The map shows your monitors; arrow keys move the selected one.",
+        "Die Karte zeigt die Monitore; Pfeiltasten bewegen den gewählten.",
+    ),
+    (
+        "Modes lists the resolutions and refresh rates the monitor offers.",
+        "Modi listet, was der Monitor an Auflösungen und Raten anbietet.",
+    ),
+    (
+        "Workspaces shows which monitor each workspace opens on.",
+        "Arbeitsflächen zeigt, auf welchem Monitor jede Arbeitsfläche öffnet.",
+    ),
+    (
+        "Scale sets how large everything is drawn on the monitor.",
+        "Skalierung legt fest, wie groß alles auf dem Monitor gezeichnet wird.",
+    ),
+    (
+        "Transform rotates or flips the monitor.",
+        "Drehung dreht oder spiegelt den Monitor.",
+    ),
+    (
+        "Options holds power and more; W applies all that is staged.",
+        "Optionen enthält Strom und mehr; W wendet alles Vorgemerkte an.",
+    ),
+    ("any key", "beliebige Taste"),
+    ("next", "weiter"),
+    ("done", "fertig"),
+    ("skip tour", "Rundgang überspringen"),
+    ("Tour {}/{}", "Rundgang {}/{}"),
+    (
+        "Failed to remember the tour: {}",
+        "Rundgang konnte nicht gemerkt werden: {}",
+    ),
+    ("{} reports no modes", "{} meldet keine Modi"),
+    ("no modes reported", "keine Modi gemeldet"),
+    ("⚠ no modes", "⚠ keine Modi"),
+    ("Changed this session:", "In dieser Sitzung geändert:"),
+    ("Keep tweaking", "Weiter anpassen"),
+    ("workspace {}: {} → {}", "Arbeitsbereich {}: {} → {}"),
+    ("wrote {}", "{} geschrieben"),
+    ("none", "keiner"),
+    (
+        "Failed to make {} executable: {}",
+        "{} konnte nicht ausführbar gemacht werden: {}",
+    ),
+    (
+        "Failed to back up {}: {}",
+        "Sicherung von {} fehlgeschlagen: {}",
+    ),
+    ("SELECTED", "AUSGEWÄHLT"),
+    ("1. Open a TTY session", "1. Eine TTY-Sitzung öffnen"),
+    ("2. Run", "2. Ausführen:"),
+    ("3. Switch back to your session", "3. Zurück zur Sitzung wechseln"),
+    (
+        "{} is your last enabled monitor. Disabling it leaves you with no \
+         display, and kscreen-doctor has to turn it back on from a TTY.",
+        "{} ist dein letzter aktiver Monitor. Ohne ihn bleibt kein Bild, \
+         und kscreen-doctor muss ihn aus einer TTY wieder einschalten.",
+    ),
+    ("Nothing to undo", "Nichts rückgängig zu machen"),
+    ("Nothing to redo", "Nichts wiederherzustellen"),
+    ("Failed to undo: {}", "Rückgängig machen fehlgeschlagen: {}"),
+    ("Failed to redo: {}", "Wiederherstellen fehlgeschlagen: {}"),
+    ("undo {}", "rückgängig {}"),
+    ("kept by script", "per Skript gesichert"),
+    (
+        "{} has no fallback monitors for workspaces",
+        "{} kennt keine Ausweichmonitore für Arbeitsbereiche",
+    ),
+    ("backed up to {}", "gesichert nach {}"),
+    ("Failed to stop wlsunset: {}", "wlsunset ließ sich nicht beenden: {}"),
+    (
+        "Applying {} to {} through the monitor config",
+        "Wende {} über die Monitorkonfiguration auf {} an",
+    ),
+    ("only this", "nur dieser"),
+    ("enable all", "alle an"),
+    ("Nothing to toggle", "Nichts umzuschalten"),
+    ("Staged {} toggle", "{} Umschaltung vorgemerkt"),
+    ("Staged {} toggles", "{} Umschaltungen vorgemerkt"),
+    // Transforms
+    ("Normal", "Normal"),
+    ("Rotate 90", "Gedreht 90"),
+    ("Rotate 180", "Gedreht 180"),
+    ("Rotate 270", "Gedreht 270"),
+    ("Flipped", "Gespiegelt"),
+    ("Flipped 90", "Gespiegelt 90"),
+    ("Flipped 180", "Gespiegelt 180"),
+    ("Flipped 270", "Gespiegelt 270"),
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{constants::TRANSFORMS, utils::transform_label};
+
+    const SOURCES: &[&str] = &[
+        include_str!("compositor/check.rs"),
+        include_str!("config_fields.rs"),
+        include_str!("main.rs"),
+        include_str!("plain.rs"),
+        include_str!("session.rs"),
+        include_str!("setup.rs"),
+        include_str!("state.rs"),
+        include_str!("timeline.rs"),
+        include_str!("tui/fatal.rs"),
+        include_str!("tui/key_binds.rs"),
+        include_str!("tui/layout.rs"),
+        include_str!("tui/panels/apply_all.rs"),
+        include_str!("tui/panels/clone_picker.rs"),
+        include_str!("tui/panels/config_check.rs"),
+        include_str!("tui/panels/confirm.rs"),
+        include_str!("tui/panels/fallbacks.rs"),
+        include_str!("tui/panels/history.rs"),
+        include_str!("tui/panels/left.rs"),
+        include_str!("tui/panels/mode.rs"),
+        include_str!("tui/panels/new_monitor.rs"),
+        include_str!("tui/panels/options.rs"),
+        include_str!("tui/panels/reconcile.rs"),
+        include_str!("tui/panels/revert_countdown.rs"),
+        include_str!("tui/panels/scale.rs"),
+        include_str!("tui/panels/settings.rs"),
+        include_str!("tui/panels/stray_rules.rs"),
+        include_str!("tui/panels/timeline.rs"),
+        include_str!("tui/panels/tour.rs"),
+        include_str!("tui/panels/transcript.rs"),
+        include_str!("tui/panels/transform.rs"),
+        include_str!("tui/panels/transform_preview.rs"),
+        include_str!("tui/panels/untouched.rs"),
+        include_str!("tui/panels/workspace.rs"),
+        include_str!("tui/ui.rs"),
+    ];
+
+    /// String literals passed straight to `t`, `tf`, `tn` or `hint`.
+    fn keys_used(source: &str) -> Vec<String> {
+        let mut keys = Vec::new();
+        // (call, leading literals to skip, literals that are keys)
+        for (call, skip, take) in
+            [("t(", 0, 1), ("tf(", 0, 1), ("tn(", 0, 2), ("hint(", 1, 1)]
+        {
+            for (at, _) in source.match_indices(call) {
+                let before = source[..at].chars().next_back();
+                if before.is_some_and(|c| c.is_alphanumeric() || c == '_') {
+                    continue;
+                }
+                let mut rest = &source[at + call.len()..];
+                if call == "hint(" {
+                    // Skip the span vector argument.
+                    rest = rest.split_once(',').map_or("", |(_, r)| r);
+                }
+                for i in 0..skip + take {
+                    let Some(body) = rest.trim_start().strip_prefix('"') else {
+                        break;
+                    };
+                    let end = body.find('"').unwrap();
+                    if i >= skip {
+                        keys.push(unescape(&body[..end]));
+                    }
+                    rest = body[end + 1..].trim_start();
+                    rest = rest.strip_prefix(',').unwrap_or(rest);
+                }
+            }
+        }
+        keys
+    }
+
+    /// Resolves the `\` line continuations used for long messages.
+    fn unescape(literal: &str) -> String {
+        let mut out = String::new();
+        let mut lines = literal.split("\\\n");
+        out.push_str(lines.next().unwrap_or_default());
+        for line in lines {
+            out.push_str(line.trim_start());
+        }
+        out
+    }
+
+    #[test]
+    fn test_every_ui_string_has_a_german_translation() {
+        let missing: Vec<String> = SOURCES
+            .iter()
+            .flat_map(|s| keys_used(s))
+            .filter(|key| !DE.iter().any(|(k, _)| k == key))
+            .collect();
+        assert!(missing.is_empty(), "untranslated: {missing:?}");
+    }
+
+    #[test]
+    fn test_transform_labels_are_translated() {
+        for transform in TRANSFORMS {
+            let label = transform_label(transform);
+            assert!(DE.iter().any(|(k, _)| *k == label), "{label}");
+        }
+    }
+
+    #[test]
+    fn test_translations_keep_their_placeholders() {
+        for (key, text) in DE {
+            assert_eq!(
+                key.matches("{}").count(),
+                text.matches("{}").count(),
+                "{key}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_lookup_falls_back_to_english() {
+        assert_eq!(Locale::De.lookup("quit"), "Beenden");
+        assert_eq!(Locale::De.lookup("not translated"), "not translated");
+        assert_eq!(Locale::En.lookup("quit"), "quit");
+    }
+
+    #[test]
+    fn test_parse_locale() {
+        assert_eq!(Locale::parse("de_DE.UTF-8"), Some(Locale::De));
+        assert_eq!(Locale::parse("de-AT"), Some(Locale::De));
+        assert_eq!(Locale::parse("C.UTF-8"), Some(Locale::En));
+        assert_eq!(Locale::parse("fr_FR"), None);
+    }
+
+    #[test]
+    fn test_fill_and_plurals() {
+        assert_eq!(fill("{} of {}", &[&1, &"two"]), "1 of two");
+        assert_eq!(fill("no args", &[&1]), "no args");
+        assert_eq!(
+            tn("Reflowed {} monitor", "Reflowed {} monitors", 1, &[]),
+            "Reflowed 1 monitor"
+        );
+        assert_eq!(
+            tn("Reflowed {} monitor", "Reflowed {} monitors", 3, &[]),
+            "Reflowed 3 monitors"
+        );
+    }
+}
@@ -0,0 +1,120 @@
+use wlx_monitors::WlMonitor;
+
+use crate::layout::LayoutMonitor;
+
+/// Serials shorter than this, like the "0" many monitors report, say
+/// nothing about which unit it is.
+const MIN_SERIAL: usize = 4;
+
+/// What a display is recognised by, whatever connector it is on.
+#[derive(Debug, Clone, Copy)]
+pub struct Identity<'a> {
+    pub name: &'a str,
+    pub description: &'a str,
+    pub model: &'a str,
+    pub serial: &'a str,
+}
+
+impl<'a> From<&'a WlMonitor> for Identity<'a> {
+    fn from(monitor: &'a WlMonitor) -> Self {
+        Self {
+            name: &monitor.name,
+            description: &monitor.description,
+            model: &monitor.model,
+            serial: &monitor.serial_number,
+        }
+    }
+}
+
+impl<'a> From<&'a LayoutMonitor> for Identity<'a> {
+    fn from(monitor: &'a LayoutMonitor) -> Self {
+        Self {
+            name: &monitor.name,
+            description: &monitor.description,
+            model: "",
+            serial: "",
+        }
+    }
+}
+
+impl Identity<'_> {
+    /// Whether both are the same display: by model and serial when both
+    /// have a real serial, else by description, else by connector.
+    pub fn same_display(&self, other: &Identity) -> bool {
+        let (a, b) = (self.serial.trim(), other.serial.trim());
+        if a.len() >= MIN_SERIAL && b.len() >= MIN_SERIAL {
+            return a == b && self.model == other.model;
+        }
+        let (a, b) = (self.stable_description(), other.stable_description());
+        if a.is_empty() || b.is_empty() {
+            self.name == other.name
+        } else {
+            a == b
+        }
+    }
+
+    /// The description without the " (DP-3)" wlroots ends it with, which
+    /// changes with the connector.
+    fn stable_description(&self) -> &str {
+        self.description
+            .strip_suffix(')')
+            .and_then(|d| d.strip_suffix(self.name))
+            .and_then(|d| d.strip_suffix(" ("))
+            .unwrap_or(self.description)
+            .trim()
+    }
+}
+
+/// "make model", e.g. "LG Electronics LG HDR 4K": the same for every
+/// unit of a model.
+pub fn model_key(monitor: &WlMonitor) -> String {
+    format!("{} {}", monitor.make.trim(), monitor.model.trim())
+        .trim()
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::monitor;
+
+    #[test]
+    fn test_same_display_across_connectors() {
+        let dp3 = monitor("DP-3", 0, 0);
+        let mut dp5 = monitor("DP-5", 0, 0);
+        dp5.description = dp3.description.clone();
+        assert!(Identity::from(&dp3).same_display(&Identity::from(&dp5)));
+
+        // wlroots puts the connector at the end of the description.
+        let mut sway3 = monitor("DP-3", 0, 0);
+        sway3.description = "Dell Inc. DELL U2720Q (DP-3)".into();
+        let mut sway5 = monitor("DP-5", 0, 0);
+        sway5.description = "Dell Inc. DELL U2720Q (DP-5)".into();
+        assert!(Identity::from(&sway3).same_display(&Identity::from(&sway5)));
+
+        // Two units of one model only differ by serial.
+        let mut left = sway3.clone();
+        left.serial_number = "ABC123".into();
+        let mut right = sway5.clone();
+        right.serial_number = "XYZ789".into();
+        assert!(!Identity::from(&left).same_display(&Identity::from(&right)));
+        right.serial_number = "ABC123".into();
+        assert!(Identity::from(&left).same_display(&Identity::from(&right)));
+
+        // A placeholder serial falls back to the description.
+        left.serial_number = "0".into();
+        right.serial_number = "0".into();
+        assert!(Identity::from(&left).same_display(&Identity::from(&right)));
+    }
+
+    #[test]
+    fn test_without_description_only_the_connector_counts() {
+        let mut a = monitor("DP-3", 0, 0);
+        a.description.clear();
+        let mut b = monitor("DP-5", 0, 0);
+        b.description.clear();
+        assert!(!Identity::from(&a).same_display(&Identity::from(&b)));
+        b.name = "DP-3".into();
+        assert!(Identity::from(&a).same_display(&Identity::from(&b)));
+    }
+}
@@ -0,0 +1,484 @@
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::fs;
+use wlx_monitors::{WlMonitor, WlTransform};
+
+use crate::{
+    compositor::{format::transform_to_sway, workspace_config::WorkspaceRule},
+    constants::{SNAP_DISTANCE, TRANSFORMS},
+    geometry::Rect,
+    refresh, scale, utils,
+    xwlm_config::ConfigError,
+};
+
+pub const LAYOUT_PATH: &str = "~/.config/xwlm/layout.toml";
+
+/// One output as xwlm last saved it. `description` survives connector
+/// renames, so it is what live monitors are matched against.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LayoutMonitor {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    pub enabled: bool,
+    pub width: i32,
+    pub height: i32,
+    /// Whole Hz, as the Wayland mode reports it.
+    pub refresh_rate: i32,
+    /// The same rate in mHz, when the compositor listed it that precisely.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub refresh_mhz: Option<i32>,
+    pub x: i32,
+    pub y: i32,
+    pub scale: f64,
+    #[serde(
+        serialize_with = "serialize_transform",
+        deserialize_with = "deserialize_transform"
+    )]
+    pub transform: WlTransform,
+}
+
+impl LayoutMonitor {
+    pub fn from_monitor(monitor: &WlMonitor) -> Self {
+        let (width, height, refresh_rate) = monitor
+            .modes
+            .iter()
+            .find(|m| m.is_current)
+            .map(|m| (m.resolution.width, m.resolution.height, m.refresh_rate))
+            .unwrap_or_default();
+        Self {
+            name: monitor.name.clone(),
+            description: monitor.description.clone(),
+            enabled: monitor.enabled,
+            width,
+            height,
+            refresh_rate,
+            refresh_mhz: None,
+            x: monitor.position.x,
+            y: monitor.position.y,
+            scale: monitor.scale,
+            transform: monitor.transform,
+        }
+    }
+
+    /// False for a monitor that reported no modes when it was captured;
+    /// configs then leave its mode to the compositor.
+    pub fn has_mode(&self) -> bool {
+        self.width > 0 && self.height > 0
+    }
+
+    /// False when the config it came from named no refresh rate, which
+    /// is then left to the compositor.
+    pub fn has_refresh(&self) -> bool {
+        self.refresh_mhz.is_some() || self.refresh_rate > 0
+    }
+
+    /// `1920x1080@60` with `unit` after the rate, or `1920x1080` when
+    /// there is no rate to write.
+    pub fn mode_name(&self, unit: &str) -> String {
+        let size = format!("{}x{}", self.width, self.height);
+        match self.has_refresh() {
+            true => format!("{size}@{}{unit}", self.refresh()),
+            false => size,
+        }
+    }
+
+    /// The refresh rate as configs write it, e.g. `59.94` or `60`.
+    pub fn refresh(&self) -> String {
+        let mhz = self.refresh_mhz.unwrap_or(self.refresh_rate * 1000);
+        refresh::format(mhz)
+    }
+
+    /// Position and size in logical pixels, the size rounded to whole ones.
+    fn logical_rect(&self) -> Rect {
+        let (w, h) = scale::logical_extent(
+            (self.width, self.height),
+            self.transform,
+            self.scale,
+        );
+        Rect::new(self.x, self.y, w, h)
+    }
+}
+
+/// A monitor [`Layout::snap_to_logical_edges`] moved.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Snap {
+    pub name: String,
+    pub from: (i32, i32),
+    pub to: (i32, i32),
+}
+
+/// The canonical layout: compositor files are rendered from this when
+/// `canonical_layout` is on.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Layout {
+    #[serde(default, rename = "monitor")]
+    pub monitors: Vec<LayoutMonitor>,
+    #[serde(default, rename = "workspace")]
+    pub workspaces: Vec<WorkspaceRule>,
+}
+
+impl Layout {
+    pub fn capture(
+        monitors: &[WlMonitor],
+        workspaces: &[WorkspaceRule],
+    ) -> Self {
+        Self {
+            monitors: monitors
+                .iter()
+                .map(LayoutMonitor::from_monitor)
+                .collect(),
+            workspaces: workspaces.to_vec(),
+        }
+    }
+
+    /// Maps a connector name stored in the layout to the live connector
+    /// with the same description, falling back to the stored name.
+    pub fn live_name(&self, name: &str, monitors: &[WlMonitor]) -> String {
+        self.monitors
+            .iter()
+            .find(|m| m.name == name && !m.description.is_empty())
+            .and_then(|saved| {
+                monitors.iter().find(|m| m.description == saved.description)
+            })
+            .map(|m| m.name.clone())
+            .unwrap_or_else(|| name.to_string())
+    }
+
+    /// Moves enabled monitors that sit within [`SNAP_DISTANCE`] of the
+    /// monitor to their left or above onto its logical edge. Fractional
+    /// scales end logical sizes between pixels (2560 at 1.5 is 1706.67
+    /// wide), so a neighbour at 1706 overlaps by a sliver and one at 1709
+    /// leaves a gap; compositors warn about both. Moves that would overlap
+    /// another monitor are left out.
+    pub fn snap_to_logical_edges(&mut self) -> Vec<Snap> {
+        let mut order: Vec<usize> = (0..self.monitors.len())
+            .filter(|&i| self.monitors[i].enabled)
+            .collect();
+        order.sort_by_key(|&i| (self.monitors[i].x, self.monitors[i].y));
+
+        let mut snaps = Vec::new();
+        for &i in &order {
+            let me = self.monitors[i].logical_rect();
+            let others: Vec<Rect> = order
+                .iter()
+                .filter(|&&j| j != i)
+                .map(|&j| self.monitors[j].logical_rect())
+                .collect();
+            let x = nearest_edge(
+                me.x,
+                others
+                    .iter()
+                    .filter(|r| r.x < me.x && r.y < me.bottom())
+                    .filter(|r| r.bottom() > me.y)
+                    .map(Rect::right),
+            );
+            let y = nearest_edge(
+                me.y,
+                others
+                    .iter()
+                    .filter(|r| r.y < me.y && r.x < me.right())
+                    .filter(|r| r.right() > me.x)
+                    .map(Rect::bottom),
+            );
+            let to = (x.unwrap_or(me.x), y.unwrap_or(me.y));
+            let moved = Rect::new(to.0, to.1, me.w, me.h);
+            if to == (me.x, me.y) || others.iter().any(|r| moved.overlaps(r)) {
+                continue;
+            }
+            let monitor = &mut self.monitors[i];
+            (monitor.x, monitor.y) = to;
+            snaps.push(Snap {
+                name: monitor.name.clone(),
+                from: (me.x, me.y),
+                to,
+            });
+        }
+        snaps
+    }
+}
+
+/// The edge closest to `pos` that is off by at most [`SNAP_DISTANCE`].
+fn nearest_edge(pos: i32, edges: impl Iterator<Item = i32>) -> Option<i32> {
+    edges
+        .filter(|&e| e != pos && (e - pos).abs() <= SNAP_DISTANCE)
+        .min_by_key(|&e| (e - pos).abs())
+}
+
+fn serialize_transform<S: Serializer>(
+    transform: &WlTransform,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(transform_to_sway(*transform))
+}
+
+fn deserialize_transform<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<WlTransform, D::Error> {
+    let name = String::deserialize(deserializer)?;
+    TRANSFORMS
+        .iter()
+        .copied()
+        .find(|t| transform_to_sway(*t) == name)
+        .ok_or_else(|| {
+            serde::de::Error::custom(format!("unknown transform `{name}`"))
+        })
+}
+
+/// `Ok(None)` when no layout has been written yet.
+pub fn load_layout() -> Result<Option<Layout>, ConfigError> {
+    load_from_path(LAYOUT_PATH)
+}
+
+pub fn save_layout(layout: &Layout) -> Result<(), ConfigError> {
+    save_to_path(LAYOUT_PATH, layout)
+}
+
+fn load_from_path(path: &str) -> Result<Option<Layout>, ConfigError> {
+    let expanded_path = utils::expand_tilde(path)?;
+    if !expanded_path.exists() {
+        return Ok(None);
+    }
+    let content =
+        fs::read_to_string(&expanded_path).map_err(|e| ConfigError::Read {
+            path: path.to_string(),
+            source: e,
+        })?;
+    Ok(Some(toml::from_str(&content)?))
+}
+
+fn save_to_path(path: &str, layout: &Layout) -> Result<(), ConfigError> {
+    let expanded_path = utils::expand_tilde(path)?;
+
+    if let Some(parent) = expanded_path.parent() {
+        fs::create_dir_all(parent).map_err(|e| ConfigError::Write {
+            path: parent.to_string_lossy().into(),
+            source: e,
+        })?;
+    }
+
+    let comment = "# The canonical xwlm layout. Compositor monitor configs are\n# generated from this file when canonical_layout = true.\n\n";
+    let content = format!("{}{}", comment, toml::to_string_pretty(layout)?);
+
+    utils::write_atomic(&expanded_path, &content).map_err(|e| {
+        ConfigError::Write {
+            path: expanded_path.to_string_lossy().into(),
+            source: e,
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        compositor::{
+            Compositor, format::render_monitor_config,
+            settings::config_monitors,
+        },
+        test_support::monitor,
+    };
+
+    #[test]
+    fn save_then_load_layout_works() {
+        let path = "~/.config/test-xwlm/layout.toml";
+        let mut rotated = monitor("HDMI-A-1", 1920, 0);
+        rotated.transform = WlTransform::Flipped90;
+        rotated.scale = 1.5;
+        let layout = Layout::capture(
+            &[monitor("DP-1", 0, 0), rotated],
+            &[WorkspaceRule {
+                id: 3,
+                monitor: "HDMI-A-1".into(),
+                fallbacks: vec!["DP-1".into()],
+                is_default: true,
+                is_persistent: false,
+                line: None,
+            }],
+        );
+
+        save_to_path(path, &layout).unwrap();
+
+        assert_eq!(load_from_path(path).unwrap(), Some(layout));
+    }
+
+    #[test]
+    fn load_missing_layout_is_none() {
+        let path = "~/.config/test-xwlm/missing-layout.toml";
+        assert_eq!(load_from_path(path).unwrap(), None);
+    }
+
+    #[test]
+    fn monitor_without_modes_leaves_the_mode_unset() {
+        let mut bare = monitor("DP-1", 0, 0);
+        bare.modes.clear();
+        let saved = LayoutMonitor::from_monitor(&bare);
+
+        assert!(!saved.has_mode());
+        assert!(!saved.has_refresh());
+    }
+
+    #[test]
+    fn live_name_follows_description() {
+        let layout = Layout::capture(&[monitor("DP-1", 0, 0)], &[]);
+        let mut replugged = monitor("DP-3", 0, 0);
+        replugged.description = "Test Monitor DP-1".into();
+
+        assert_eq!(layout.live_name("DP-1", &[replugged]), "DP-3");
+        assert_eq!(layout.live_name("DP-1", &[monitor("DP-2", 0, 0)]), "DP-1");
+    }
+
+    fn output(
+        name: &str,
+        (width, height): (i32, i32),
+        scale: f64,
+        (x, y): (i32, i32),
+    ) -> LayoutMonitor {
+        LayoutMonitor {
+            name: name.into(),
+            description: String::new(),
+            enabled: true,
+            width,
+            height,
+            refresh_rate: 60,
+            refresh_mhz: None,
+            x,
+            y,
+            scale,
+            transform: WlTransform::Normal,
+        }
+    }
+
+    fn positions(layout: &Layout) -> Vec<(i32, i32)> {
+        layout.monitors.iter().map(|m| (m.x, m.y)).collect()
+    }
+
+    #[test]
+    fn snap_closes_slivers_left_by_fractional_scales() {
+        // 1706.67, 1536 and 1536.6 logical pixels wide.
+        let mut layout = Layout {
+            monitors: vec![
+                output("DP-1", (2560, 1440), 1.5, (0, 0)),
+                output("DP-2", (1920, 1080), 1.25, (1706, 0)),
+                output("DP-3", (2560, 1600), 1.666, (3245, 0)),
+            ],
+            workspaces: Vec::new(),
+        };
+        let snaps = layout.snap_to_logical_edges();
+
+        assert_eq!(positions(&layout), [(0, 0), (1707, 0), (3243, 0)]);
+        assert_eq!(
+            snaps,
+            [
+                Snap {
+                    name: "DP-2".into(),
+                    from: (1706, 0),
+                    to: (1707, 0),
+                },
+                Snap {
+                    name: "DP-3".into(),
+                    from: (3245, 0),
+                    to: (3243, 0),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn scaled_positions_survive_a_save_and_a_reread() {
+        for compositor in [
+            Compositor::Hyprland,
+            Compositor::Sway,
+            Compositor::River,
+        ] {
+            // 2048, 1706.67 and 2194.29 logical pixels wide.
+            let mut layout = Layout {
+                monitors: vec![
+                    output("DP-1", (2560, 1440), 1.25, (0, 0)),
+                    output("DP-2", (2560, 1440), 1.5, (2049, 0)),
+                    output("DP-3", (3840, 2160), 1.75, (3757, 0)),
+                ],
+                workspaces: Vec::new(),
+            };
+            layout.snap_to_logical_edges();
+            assert_eq!(positions(&layout), [(0, 0), (2048, 0), (3755, 0)]);
+
+            let content =
+                render_monitor_config(compositor, &layout, &[]).unwrap();
+            let mut reread = layout.clone();
+            let parsed = config_monitors(compositor, &content);
+            for (monitor, saved) in reread.monitors.iter_mut().zip(parsed) {
+                (monitor.x, monitor.y) = saved.position.unwrap();
+                monitor.scale = saved.scale.unwrap();
+            }
+            assert_eq!(reread, layout, "{compositor:?}");
+            assert!(reread.snap_to_logical_edges().is_empty());
+        }
+    }
+
+    #[test]
+    fn snap_works_vertically_and_for_rotated_outputs() {
+        let mut top = output("DP-1", (1920, 1080), 1.666, (0, 0));
+        top.transform = WlTransform::Rotate90;
+        let mut layout = Layout {
+            // The rotated output is 648.27 wide and 1152.46 tall.
+            monitors: vec![
+                top,
+                output("DP-2", (1920, 1080), 1.5, (0, 1151)),
+                output("DP-3", (2560, 1440), 1.25, (650, 0)),
+            ],
+            workspaces: Vec::new(),
+        };
+        layout.snap_to_logical_edges();
+        assert_eq!(positions(&layout), [(0, 0), (0, 1152), (648, 0)]);
+    }
+
+    #[test]
+    fn snap_leaves_clean_and_distant_edges_alone() {
+        let mut off = output("DP-3", (2560, 1440), 1.5, (1707, 0));
+        off.enabled = false;
+        let mut layout = Layout {
+            monitors: vec![
+                output("DP-1", (2560, 1440), 1.25, (0, 0)),
+                output("DP-2", (1920, 1080), 1.5, (2048, 0)),
+                output("DP-4", (1920, 1080), 1.25, (3338, 0)),
+                off,
+            ],
+            workspaces: Vec::new(),
+        };
+        let before = layout.clone();
+        assert!(layout.snap_to_logical_edges().is_empty());
+        assert_eq!(layout, before);
+    }
+
+    #[test]
+    fn snapped_neighbours_never_overlap() {
+        let scales: [f64; 4] = [1.0, 1.25, 1.5, 1.666];
+        for left in scales {
+            for right in scales {
+                let edge = (2560.0 / left).round() as i32;
+                for offset in -2..=2 {
+                    let mut layout = Layout {
+                        monitors: vec![
+                            output("DP-1", (2560, 1440), left, (0, 0)),
+                            output(
+                                "DP-2",
+                                (1920, 1080),
+                                right,
+                                (edge + offset, 0),
+                            ),
+                        ],
+                        workspaces: Vec::new(),
+                    };
+                    layout.snap_to_logical_edges();
+                    let rects: Vec<Rect> = layout
+                        .monitors
+                        .iter()
+                        .map(LayoutMonitor::logical_rect)
+                        .collect();
+                    assert_eq!(rects[1].x, rects[0].right());
+                    assert!(!rects[0].overlaps(&rects[1]));
+                }
+            }
+        }
+    }
+}
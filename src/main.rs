@@ -1,27 +1,147 @@
+mod adjacency;
+mod apply_plan;
+mod aspect;
+mod bandwidth;
+mod build_info;
+mod cli;
 mod compositor;
+mod config_fields;
 mod constants;
+mod control;
+mod dump;
+mod edid;
+mod effects;
+mod geometry;
+mod history;
+mod i18n;
+mod identity;
+mod layout;
+mod night_light;
+mod parked;
+mod plain;
+mod reconcile;
+mod recovery;
+mod refresh;
+mod remote;
+mod scale;
+mod screencast;
+mod service;
+mod session;
 mod setup;
 mod state;
+#[cfg(test)]
+mod test_support;
+mod timeline;
 mod tui;
+mod ui_state;
+mod undo;
 mod utils;
 mod xwlm_config;
 
-use std::{error::Error, io, sync::mpsc};
+use std::{
+    collections::HashMap,
+    env,
+    error::Error,
+    io,
+    path::{Path, PathBuf},
+    sync::mpsc,
+    time::Instant,
+};
 
 use wlx_monitors::{WlMonitorManager, WlMonitorManagerError};
 
-use crate::{state::App, xwlm_config::Config};
+use crate::{
+    cli::{Command, TuiOptions},
+    compositor::Compositor,
+    control::ControlError,
+    effects::{DryRun, Effects, Live},
+    i18n::{t, tf},
+    state::App,
+    xwlm_config::{Config, ConfigError},
+};
 
 fn main() {
     if let Err(e) = run() {
-        eprintln!("Error: {}", e);
+        // The terminal may close with xwlm, so the TUI shows it first.
+        if !tui::fatal::hold(&e.to_string()) {
+            eprintln!("Error: {}", e);
+        }
         std::process::exit(1);
     }
 }
 
 fn run() -> Result<(), Box<dyn Error>> {
-    let (wlx_emitter, wlx_events) = mpsc::sync_channel(16);
-    let (wlx_action_handler, wlx_action_rx) = mpsc::sync_channel(16);
+    let (command, dry_run) = cli::parse_with_dry_run(env::args().skip(1))?;
+    if let Command::Tui(opts) = &command {
+        tui::fatal::set_holding(opts.hold_on_error.unwrap_or(true));
+        tui::fatal::install_panic_hook();
+    }
+
+    // These don't need the Wayland connection.
+    match &command {
+        Command::Version { json } => {
+            let build = &build_info::BUILD;
+            let info = if *json { build.json() } else { build.summary() };
+            println!("{info}");
+            return Ok(());
+        }
+        Command::Apply => {
+            return Ok(cli::run_apply(&xwlm_config::load_config()?)?);
+        }
+        Command::Doctor(opts) if !opts.layout => {
+            return Ok(cli::run_doctor()?);
+        }
+        Command::Replay(path) => return replay(path),
+        Command::InstallService(opts) => {
+            return Ok(cli::run_service(true, opts)?);
+        }
+        Command::UninstallService(opts) => {
+            return Ok(cli::run_service(false, opts)?);
+        }
+        Command::Set(opts) if !dry_run => {
+            // A running TUI does it rather than a second connection.
+            if let Some(answer) = cli::proxy_set(opts) {
+                return Ok(answer?);
+            }
+        }
+        Command::Remote(opts) => {
+            let ssh = remote::Ssh {
+                host: opts.host.clone(),
+            };
+            return Ok(remote::run(opts, &ssh)?);
+        }
+        _ => {}
+    }
+
+    let observing = matches!(&command, Command::Tui(opts) if opts.observe);
+    let mut first_launch = false;
+    let config = match command {
+        // Observing needs no config, and must not run setup to get one.
+        Command::Tui(_) if observing => {
+            xwlm_config::load_config().or_else(|_| observer_config())?
+        }
+        // Setup writes its files directly, so a dry run needs a config.
+        Command::Tui(_) if !dry_run => {
+            let Some((config, fresh)) = load()? else {
+                return Ok(());
+            };
+            first_launch = fresh;
+            config
+        }
+        _ => xwlm_config::load_config()?,
+    };
+    i18n::init(config.language.as_deref());
+    if let Command::Tui(TuiOptions {
+        hold_on_error: None,
+        ..
+    }) = &command
+    {
+        tui::fatal::set_holding(config.hold_on_error);
+    }
+
+    let bound = config.event_queue_size.max(1);
+    let (wlx_emitter, wlx_events) = mpsc::sync_channel(bound);
+    let (wlx_action_handler, wlx_action_rx) = mpsc::sync_channel(bound);
     let (wlx_manager, wlx_eq) = WlMonitorManager::new_connection(wlx_emitter, wlx_action_rx)?;
 
     std::thread::spawn(move || -> Result<(), WlMonitorManagerError> {
@@ -29,29 +149,164 @@ fn run() -> Result<(), Box<dyn Error>> {
         Ok(())
     });
 
-    let Some(config) = load()? else { return Ok(()) };
+    let effects: Box<dyn Effects> = if dry_run || observing {
+        Box::new(DryRun::default())
+    } else {
+        Box::new(Live::new(wlx_action_handler))
+    };
+    let mut app = App::new(effects, config);
+    if observing {
+        app.observe();
+    }
+    if matches!(command, Command::Tui(_) | Command::Watch(_)) {
+        app.night_light = night_light::detect();
+    }
+    if matches!(command, Command::Tui(_)) {
+        app.terminal_output =
+            compositor::ipc::focused_window_output(app.compositor);
+    }
+    if !matches!(command, Command::Tui(_)) {
+        let problems = [app.config_problem(), app.stray_rules_problem()];
+        for problem in problems.into_iter().flatten() {
+            eprintln!("Warning: {problem}");
+        }
+    }
+    match command {
+        Command::Tui(opts) => {
+            // Better a clear error than an empty TUI.
+            if app.compositor == Compositor::Kwin {
+                cli::wait_for_monitors(&mut app, &wlx_events)?;
+            }
+            let deadline = Instant::now() + cli::INITIAL_STATE_TIMEOUT;
+            app.focus_on_startup(opts.monitor, opts.panel, deadline);
+            if first_launch {
+                app.first_launch();
+            }
+            // An observer only gets the tour when it asks.
+            if opts.tour || !observing {
+                app.offer_tour(opts.tour);
+            }
+            let mut control = listen(&mut app);
+            tui::run(&mut app, wlx_events, control.as_mut())?;
+            if !dry_run && app.has_session_changes() {
+                print_session(&app);
+            }
+        }
+        Command::Plain => plain::run(&mut app, &wlx_events)?,
+        Command::Set(opts) => cli::run_set(&mut app, &wlx_events, &opts)?,
+        Command::Watch(opts) => cli::run_watch(&mut app, &wlx_events, &opts)?,
+        Command::DebugDump(opts) => {
+            cli::run_debug_dump(&mut app, &wlx_events, &opts)?
+        }
+        Command::Recover => cli::run_recover(&mut app, &wlx_events)?,
+        Command::Doctor(_) => cli::run_layout_doctor(&mut app, &wlx_events)?,
+        Command::Apply
+        | Command::Version { .. }
+        | Command::Replay(_)
+        | Command::InstallService(_)
+        | Command::UninstallService(_)
+        | Command::Remote(_) => unreachable!(),
+    }
+    if dry_run {
+        print_transcript(&app);
+    }
+    Ok(())
+}
+
+/// Runs the TUI on the monitors of a debug dump. Nothing is connected to
+/// a compositor, and it is a dry run throughout.
+fn replay(path: &Path) -> Result<(), Box<dyn Error>> {
+    let dump = dump::load(path)?;
+    let monitors = dump::monitors(&dump)?;
+    i18n::init(dump.config.language.as_deref());
+    let mut files = HashMap::new();
+    if !dump.monitor_config.is_empty() {
+        files.insert(
+            dump.config.monitor_config_path.clone(),
+            dump.monitor_config,
+        );
+    }
+    let effects = Box::new(DryRun::replaying(files));
+    let comp = Compositor::from_label(&dump.compositor);
+    let mut app = App::with_compositor(effects, dump.config, comp);
+    app.replaying = true;
+    app.set_monitors(monitors);
+    app.workspace_assignments = dump.workspaces;
 
-    let mut app = App::new(
-        wlx_action_handler,
-        config.monitor_config_path,
-        config.workspace_count,
-    );
-    tui::run(&mut app, wlx_events)?;
+    // The sender goes away at once; no monitor event ever arrives.
+    let (_, wlx_events) = mpsc::sync_channel(1);
+    tui::run(&mut app, wlx_events, None)?;
+    print_transcript(&app);
     Ok(())
 }
 
-fn load() -> io::Result<Option<Config>> {
-    let comp = compositor::detect();
-    let Ok(cfg) = xwlm_config::load_config() else {
-        return setup::run(comp).map_err(io::Error::other);
-    };
+fn print_transcript(app: &App) {
+    println!("Dry run, nothing was changed. Skipped:");
+    for line in app.transcript() {
+        println!("  {line}");
+    }
+}
+
+/// What a TUI session that applied something changed, printed once the
+/// terminal is back to normal.
+fn print_session(app: &App) {
+    let lines = app.session_summary();
+    if lines.is_empty() {
+        return;
+    }
+    println!("{}", t("Changed this session:"));
+    for line in lines {
+        println!("  {line}");
+    }
+}
 
-    let path_str = cfg.monitor_config_path.to_string_lossy();
-    if !utils::monitor_config_exists(&path_str) {
-        eprintln!("Monitor config file not found: {}", path_str);
-        eprintln!("Re-running setup...");
-        return setup::run(comp).map_err(io::Error::other);
+/// Opens the control socket for other tools, unless this is a dry run or
+/// another xwlm already has it.
+fn listen(app: &mut App) -> Option<control::Server> {
+    if app.is_dry_run() {
+        return None;
     }
+    let path = control::socket_path()?;
+    match control::listen(&path) {
+        Ok(server) => Some(server),
+        Err(ControlError::Running(path)) => {
+            let path = path.display();
+            app.set_status(tf("Another xwlm already listens on {}", &[&path]));
+            None
+        }
+        Err(ControlError::Io(e)) => {
+            app.set_status(tf("No control socket: {}", &[&e]));
+            None
+        }
+    }
+}
+
+/// The config an observer without one works with: the default monitor
+/// config of the compositor, which it only reads.
+fn observer_config() -> Result<Config, ConfigError> {
+    let path = match setup::default_config_path(compositor::detect()) {
+        path if path.is_empty() => PathBuf::new(),
+        path => utils::expand_tilde(&path)?,
+    };
+    Ok(Config::new(path))
+}
+
+/// The config, and whether setup just wrote it.
+fn load() -> io::Result<Option<(Config, bool)>> {
+    let comp = compositor::detect();
+    let cfg = match xwlm_config::load_config() {
+        Ok(cfg) => cfg,
+        // Setup could not write the config without a home either, and
+        // would replace one that is only mistyped.
+        Err(e @ (ConfigError::Path(_) | ConfigError::Parse(_))) => {
+            return Err(io::Error::other(e));
+        }
+        Err(_) => {
+            let cfg = setup::run(comp).map_err(io::Error::other)?;
+            return Ok(cfg.map(|cfg| (cfg, true)));
+        }
+    };
+    i18n::init(cfg.language.as_deref());
 
-    Ok(Some(cfg))
+    Ok(Some((cfg, false)))
 }
@@ -0,0 +1,122 @@
+use std::{
+    fs, io,
+    path::Path,
+    process::Command,
+    thread,
+    time::{Duration, Instant},
+};
+
+const TOOL: &str = "wlsunset";
+
+/// How long [`stop`] waits for wlsunset to exit.
+const EXIT_WAIT: Duration = Duration::from_secs(1);
+
+/// A running wlsunset. gammastep has no way to leave out single outputs
+/// on Wayland, so it is not looked for.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NightLight {
+    /// `None` once stopped because every output was left out.
+    pub pid: Option<u32>,
+    /// Its arguments, without the program name.
+    pub args: Vec<String>,
+}
+
+/// Looks for wlsunset among the running processes. `None` when it is not
+/// running or `/proc` cannot be read.
+pub fn detect() -> Option<NightLight> {
+    fs::read_dir("/proc").ok()?.flatten().find_map(|entry| {
+        let pid: u32 = entry.file_name().to_str()?.parse().ok()?;
+        let comm = fs::read_to_string(entry.path().join("comm")).ok()?;
+        if comm.trim() != TOOL {
+            return None;
+        }
+        let cmdline = fs::read(entry.path().join("cmdline")).ok()?;
+        let args = cmdline
+            .split(|&b| b == 0)
+            .filter(|a| !a.is_empty())
+            .skip(1)
+            .map(|a| String::from_utf8_lossy(a).into_owned())
+            .collect();
+        Some(NightLight {
+            pid: Some(pid),
+            args,
+        })
+    })
+}
+
+/// `args` with its output selection replaced by `outputs`; none means
+/// every output.
+pub fn restart_args(args: &[String], outputs: &[String]) -> Vec<String> {
+    let mut kept = Vec::new();
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        if arg == "-o" {
+            iter.next();
+        } else if !arg.starts_with("-o") {
+            kept.push(arg.clone());
+        }
+    }
+    for output in outputs {
+        kept.push("-o".to_string());
+        kept.push(output.clone());
+    }
+    kept
+}
+
+/// Stops `light` and waits for it to exit, so its gamma is reset before
+/// anything takes over. It is not xwlm's child, so its `/proc` entry is
+/// watched instead of waited on.
+pub fn stop(light: &NightLight) -> io::Result<()> {
+    let Some(pid) = light.pid else {
+        return Ok(());
+    };
+    Command::new("kill").arg(pid.to_string()).status()?;
+    let proc = format!("/proc/{pid}");
+    let deadline = Instant::now() + EXIT_WAIT;
+    while Path::new(&proc).exists() {
+        if Instant::now() >= deadline {
+            return Err(io::Error::new(
+                io::ErrorKind::TimedOut,
+                format!("{TOOL} did not exit"),
+            ));
+        }
+        thread::sleep(Duration::from_millis(20));
+    }
+    Ok(())
+}
+
+/// Stops `light` and starts wlsunset again on `outputs` only, returning
+/// the new process.
+pub fn restart(
+    light: &NightLight,
+    outputs: &[String],
+) -> io::Result<Option<NightLight>> {
+    stop(light)?;
+    // Started through sh so it outlives xwlm without becoming its child.
+    Command::new("sh")
+        .arg("-c")
+        .arg(format!("{TOOL} \"$@\" >/dev/null 2>&1 &"))
+        .arg(TOOL)
+        .args(restart_args(&light.args, outputs))
+        .status()?;
+    Ok(detect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn strings(args: &[&str]) -> Vec<String> {
+        args.iter().map(|a| a.to_string()).collect()
+    }
+
+    #[test]
+    fn test_restart_args_replace_the_outputs() {
+        let args = strings(&["-l", "52.5", "-o", "DP-1", "-oHDMI-A-1"]);
+        assert_eq!(
+            restart_args(&args, &strings(&["DP-2", "eDP-1"])),
+            strings(&["-l", "52.5", "-o", "DP-2", "-o", "eDP-1"])
+        );
+        assert_eq!(restart_args(&args, &[]), strings(&["-l", "52.5"]));
+    }
+}
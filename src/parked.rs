@@ -0,0 +1,98 @@
+use serde::{Deserialize, Serialize};
+use std::{fs, path::PathBuf};
+use wlx_monitors::WlMonitor;
+
+use crate::{
+    history, identity::Identity, layout::LayoutMonitor,
+    xwlm_config::ConfigError,
+};
+
+/// How each monitor was set up the last time it was switched off, so
+/// switching it back on can put it where it was even when the monitor
+/// config no longer has it.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Parked {
+    #[serde(default, rename = "monitor")]
+    pub monitors: Vec<LayoutMonitor>,
+}
+
+impl Parked {
+    /// Keeps `monitor` as it is now, replacing what was kept for it.
+    pub fn remember(&mut self, monitor: &WlMonitor) {
+        let entry = LayoutMonitor::from_monitor(monitor);
+        match self.monitors.iter_mut().find(|m| same(m, monitor)) {
+            Some(kept) => *kept = entry,
+            None => self.monitors.push(entry),
+        }
+    }
+
+    /// What was kept for `monitor`, matched by description so it survives
+    /// connector renames.
+    pub fn get(&self, monitor: &WlMonitor) -> Option<&LayoutMonitor> {
+        self.monitors.iter().find(|m| same(m, monitor))
+    }
+}
+
+fn same(kept: &LayoutMonitor, monitor: &WlMonitor) -> bool {
+    Identity::from(kept).same_display(&Identity::from(monitor))
+}
+
+pub fn parked_path() -> Option<PathBuf> {
+    history::state_path("parked.toml")
+}
+
+pub fn parse(content: &str) -> Result<Parked, ConfigError> {
+    Ok(toml::from_str(content)?)
+}
+
+pub fn save(parked: &Parked) -> Result<(), ConfigError> {
+    let Some(path) = parked_path() else {
+        return Ok(());
+    };
+    save_to_path(&path, parked)
+}
+
+fn save_to_path(path: &PathBuf, parked: &Parked) -> Result<(), ConfigError> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| ConfigError::Write {
+            path: parent.to_string_lossy().into(),
+            source: e,
+        })?;
+    }
+    fs::write(path, toml::to_string(parked)?).map_err(|e| {
+        ConfigError::Write {
+            path: path.to_string_lossy().into(),
+            source: e,
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{TempDir, monitor};
+
+    #[test]
+    fn test_remember_replaces_by_description() {
+        let mut parked = Parked::default();
+        parked.remember(&monitor("DP-1", 0, 0));
+        let mut renamed = monitor("DP-3", 1920, 0);
+        renamed.description = "Test Monitor DP-1".into();
+        parked.remember(&renamed);
+
+        assert_eq!(parked.monitors.len(), 1);
+        let kept = parked.get(&monitor("DP-1", 0, 0)).unwrap();
+        assert_eq!((kept.name.as_str(), kept.x), ("DP-3", 1920));
+    }
+
+    #[test]
+    fn test_parked_roundtrips_through_toml() {
+        let mut parked = Parked::default();
+        parked.remember(&monitor("DP-1", 1920, 0));
+        let dir = TempDir::new("test-parked");
+        let path = dir.path().join("parked.toml");
+        save_to_path(&path, &parked).unwrap();
+        let content = fs::read_to_string(&path).unwrap();
+        assert_eq!(parse(&content).unwrap(), parked);
+    }
+}
@@ -0,0 +1,546 @@
+use std::{
+    io::{self, BufRead, Write},
+    sync::mpsc::{Receiver, SendError},
+    time::Duration,
+};
+
+use thiserror::Error;
+use wlx_monitors::{WlMonitor, WlMonitorAction, WlMonitorEvent};
+
+use crate::{
+    cli::{self, CliError},
+    i18n::{t, tf},
+    state::{App, Confirm, Panel, UntouchedChoice},
+    utils::monitor_resolution,
+    xwlm_config::NewMonitorAction,
+};
+
+/// How long a command waits for the compositor to confirm its change
+/// before the prompt comes back.
+const SETTLE_TIMEOUT: Duration = Duration::from_millis(500);
+
+#[derive(Error, Debug)]
+pub enum PlainError {
+    #[error("io error: {0}")]
+    Io(#[from] io::Error),
+
+    #[error(transparent)]
+    Cli(#[from] CliError),
+
+    #[error("wlx_monitors error: {0}")]
+    WlxMonitorActionError(#[from] SendError<WlMonitorAction>),
+}
+
+#[derive(Debug, PartialEq)]
+pub enum Input {
+    List,
+    Select(String),
+    Mode(String),
+    Position(i32, i32),
+    Scale(f64),
+    Enable,
+    Disable,
+    Workspace(usize, String),
+    Apply,
+    Save,
+    Help,
+    Quit,
+}
+
+#[derive(Error, Debug, PartialEq)]
+pub enum InputError {
+    #[error("unknown command: {0}")]
+    Unknown(String),
+
+    #[error("usage: {0}")]
+    Usage(&'static str),
+}
+
+/// Parses one typed line; blank lines are `None`.
+pub fn parse(line: &str) -> Result<Option<Input>, InputError> {
+    let words: Vec<&str> = line.split_whitespace().collect();
+    let Some((&command, args)) = words.split_first() else {
+        return Ok(None);
+    };
+    let input = match (command, args) {
+        ("list" | "ls", []) => Input::List,
+        ("select", [name]) => Input::Select(name.to_string()),
+        ("select", _) => return Err(InputError::Usage("select NAME")),
+        ("mode", [mode]) => Input::Mode(mode.to_string()),
+        ("mode", _) => return Err(InputError::Usage("mode WxH@Hz")),
+        ("pos" | "position", [x, y]) => match (x.parse(), y.parse()) {
+            (Ok(x), Ok(y)) => Input::Position(x, y),
+            _ => return Err(InputError::Usage("pos X Y")),
+        },
+        ("pos" | "position", _) => return Err(InputError::Usage("pos X Y")),
+        ("scale", [scale]) => match scale.parse() {
+            Ok(scale) => Input::Scale(scale),
+            Err(_) => return Err(InputError::Usage("scale FACTOR")),
+        },
+        ("scale", _) => return Err(InputError::Usage("scale FACTOR")),
+        ("enable", []) => Input::Enable,
+        ("disable", []) => Input::Disable,
+        ("workspace" | "ws", [id, monitor]) => match id.parse() {
+            Ok(id) => Input::Workspace(id, monitor.to_string()),
+            Err(_) => return Err(InputError::Usage("workspace ID MONITOR")),
+        },
+        ("workspace" | "ws", _) => {
+            return Err(InputError::Usage("workspace ID MONITOR"));
+        }
+        ("apply", []) => Input::Apply,
+        ("save", []) => Input::Save,
+        ("help" | "?", []) => Input::Help,
+        ("quit" | "exit" | "q", []) => Input::Quit,
+        _ => return Err(InputError::Unknown(line.trim().to_string())),
+    };
+    Ok(Some(input))
+}
+
+/// One monitor as a sentence fragment, e.g.
+/// "DP-1 (Dell U2720Q): on, 2560x1440 at 60 Hz, position 0,0, scale 1.25".
+pub fn describe(monitor: &WlMonitor) -> String {
+    if !monitor.enabled {
+        return tf("{} ({}): off", &[&monitor.name, &monitor.description]);
+    }
+    let (w, h) = monitor_resolution(monitor);
+    let refresh = monitor
+        .modes
+        .iter()
+        .find(|m| m.is_current)
+        .map_or(0, |m| m.refresh_rate);
+    tf(
+        "{} ({}): on, {}x{} at {} Hz, position {},{}, scale {}",
+        &[
+            &monitor.name,
+            &monitor.description,
+            &w,
+            &h,
+            &refresh,
+            &monitor.position.x,
+            &monitor.position.y,
+            &monitor.scale,
+        ],
+    )
+}
+
+/// Runs the line-oriented mode on stdin/stdout. It never touches raw mode
+/// or the alternate screen, so it works on a dumb terminal.
+pub fn run(
+    app: &mut App,
+    wlx_events: &Receiver<WlMonitorEvent>,
+) -> Result<(), PlainError> {
+    cli::wait_for_monitors(app, wlx_events)?;
+    session(app, wlx_events, io::stdin().lock(), io::stdout().lock())
+}
+
+pub fn session(
+    app: &mut App,
+    wlx_events: &Receiver<WlMonitorEvent>,
+    input: impl BufRead,
+    mut out: impl Write,
+) -> Result<(), PlainError> {
+    list(app, &mut out)?;
+    writeln!(out, "{}", t("Type help for a list of commands."))?;
+    announce(app, &mut out)?;
+
+    let mut lines = input.lines();
+    loop {
+        settle(app, wlx_events, &mut out, None)?;
+        confirm_new_monitor(app, &mut lines, &mut out)?;
+        write!(out, "> ")?;
+        out.flush()?;
+        let Some(line) = lines.next() else {
+            writeln!(out)?;
+            return Ok(());
+        };
+        let command = match parse(&line?) {
+            Ok(Some(command)) => command,
+            Ok(None) => continue,
+            Err(e) => {
+                writeln!(out, "{}", tf("Error: {}", &[&e]))?;
+                continue;
+            }
+        };
+        let sent = match command {
+            Input::Quit => return Ok(()),
+            Input::Help => {
+                help(&mut out)?;
+                false
+            }
+            Input::List => {
+                list(app, &mut out)?;
+                false
+            }
+            Input::Select(name) => {
+                if app.select_monitor_named(&name) {
+                    writeln!(out, "{}", tf("Selected {}.", &[&selected(app)]))?;
+                } else {
+                    app.set_error(tf("No monitor named {}", &[&name]));
+                }
+                false
+            }
+            Input::Mode(mode) => {
+                app.set_mode_from_input(&mode)?;
+                true
+            }
+            Input::Position(x, y) => {
+                if app.stage_position(x, y) {
+                    app.panel = Panel::Monitor;
+                    app.apply_action()?;
+                }
+                true
+            }
+            Input::Scale(scale) => {
+                if app.set_pending_scale(scale) {
+                    app.panel = Panel::Scale;
+                    app.apply_action()?;
+                    if let Some(lint) = app.scale_lint() {
+                        let (w, h) = lint.logical;
+                        writeln!(
+                            out,
+                            "{}",
+                            tf(
+                                "Warning: the logical size {}x{} is not whole \
+                                 pixels.",
+                                &[&w, &h]
+                            )
+                        )?;
+                    }
+                }
+                true
+            }
+            Input::Enable | Input::Disable => {
+                let enable = command == Input::Enable;
+                toggle(app, enable, &mut lines, &mut out)?;
+                true
+            }
+            Input::Workspace(id, monitor) => {
+                if app.stage_workspace(id, &monitor) {
+                    app.panel = Panel::Workspace;
+                    app.apply_action()?;
+                    writeln!(
+                        out,
+                        "{}",
+                        tf("Workspace {} moved to {}.", &[&id, &monitor])
+                    )?;
+                }
+                false
+            }
+            Input::Apply => {
+                if app.has_pending_positions() || app.has_pending_transforms()
+                {
+                    app.panel = Panel::Monitor;
+                    app.apply_action()?;
+                    true
+                } else {
+                    writeln!(out, "{}", t("Nothing to apply."))?;
+                    false
+                }
+            }
+            Input::Save => {
+                app.mark_dirty();
+                app.save_config();
+                confirm_untouched(app, &mut lines, &mut out)?;
+                if !app.needs_save() && app.error_message.is_none() {
+                    let path = app.comp_monitor_config_path.display();
+                    writeln!(out, "{}", tf("Saved to {}.", &[&path]))?;
+                }
+                false
+            }
+        };
+        confirm_untouched(app, &mut lines, &mut out)?;
+        announce(app, &mut out)?;
+        if sent {
+            settle(app, wlx_events, &mut out, Some(SETTLE_TIMEOUT))?;
+            confirm_new_monitor(app, &mut lines, &mut out)?;
+            confirm_untouched(app, &mut lines, &mut out)?;
+            announce(app, &mut out)?;
+        }
+    }
+}
+
+fn selected(app: &App) -> String {
+    app.selected_monitor()
+        .map(|m| m.name.clone())
+        .unwrap_or_default()
+}
+
+fn toggle<B: BufRead>(
+    app: &mut App,
+    enable: bool,
+    lines: &mut io::Lines<B>,
+    out: &mut impl Write,
+) -> Result<(), PlainError> {
+    let Some(monitor) = app.selected_monitor() else {
+        return Ok(());
+    };
+    let name = monitor.name.clone();
+    if monitor.enabled == enable {
+        let msg = if enable {
+            tf("{} is already enabled.", &[&name])
+        } else {
+            tf("{} is already disabled.", &[&name])
+        };
+        writeln!(out, "{msg}")?;
+        return Ok(());
+    }
+    app.toggle_monitor()?;
+    if app.has_pending_toggles() {
+        app.panel = Panel::Monitor;
+        app.apply_action()?;
+    }
+    if app.confirm != Some(Confirm::LastMonitor) {
+        return Ok(());
+    }
+
+    let warning = if app.compositor.capabilities().applies_by_command {
+        tf(
+            "{} is your last enabled monitor. Disabling it leaves you with \
+             no display, and kscreen-doctor has to turn it back on from a \
+             TTY.",
+            &[&name],
+        )
+    } else {
+        tf(
+            "{} is your last enabled monitor. Disabling it leaves you with \
+             no display, and {} has to be edited from a TTY to undo it.",
+            &[&name, &app.comp_monitor_config_path.display()],
+        )
+    };
+    writeln!(out, "{warning}")?;
+    write!(out, "{}", t("Type yes to disable it anyway: "))?;
+    out.flush()?;
+    let answer = lines.next().transpose()?.unwrap_or_default();
+    if answer.trim() == t("yes") {
+        app.accept_confirm()?;
+    } else {
+        app.dismiss_confirm();
+        writeln!(out, "{}", tf("Kept {} enabled.", &[&name]))?;
+    }
+    Ok(())
+}
+
+/// Asks where a monitor that connected without a place should go.
+/// Anything but right or saved leaves it alone.
+fn confirm_new_monitor<B: BufRead>(
+    app: &mut App,
+    lines: &mut io::Lines<B>,
+    out: &mut impl Write,
+) -> Result<(), PlainError> {
+    let Some(name) = &app.new_monitor else {
+        return Ok(());
+    };
+    writeln!(
+        out,
+        "{}",
+        tf("{} connected without a place in the layout.", &[name])
+    )?;
+    write!(out, "{}", t("Type right, saved or ignore: "))?;
+    out.flush()?;
+    let answer = lines.next().transpose()?.unwrap_or_default();
+    let action = match answer.trim() {
+        a if a == t("right") => NewMonitorAction::EnableRight,
+        a if a == t("saved") => NewMonitorAction::EnableSaved,
+        _ => NewMonitorAction::Ignore,
+    };
+    app.resolve_new_monitor(action);
+    announce(app, out)?;
+    Ok(())
+}
+
+/// Asks what to do when a save would rewrite monitors the user never
+/// changed. Anything but include or always keeps their old lines.
+fn confirm_untouched<B: BufRead>(
+    app: &mut App,
+    lines: &mut io::Lines<B>,
+    out: &mut impl Write,
+) -> Result<(), PlainError> {
+    let Some(changes) = &app.untouched_changes else {
+        return Ok(());
+    };
+    writeln!(
+        out,
+        "{}",
+        t("Saving would also change monitors you did not touch:")
+    )?;
+    for change in changes {
+        writeln!(out, "{}", change.name)?;
+        for line in &change.old {
+            writeln!(out, "  - {line}")?;
+        }
+        for line in &change.new {
+            writeln!(out, "  + {line}")?;
+        }
+    }
+    write!(out, "{}", t("Type include, skip or always: "))?;
+    out.flush()?;
+    let answer = lines.next().transpose()?.unwrap_or_default();
+    let choice = match answer.trim() {
+        a if a == t("include") => UntouchedChoice::Include,
+        a if a == t("always") => UntouchedChoice::Always,
+        _ => UntouchedChoice::Skip,
+    };
+    app.resolve_untouched(choice);
+    Ok(())
+}
+
+fn list(app: &App, out: &mut impl Write) -> io::Result<()> {
+    if app.monitors.is_empty() {
+        return writeln!(out, "{}", t("No monitors."));
+    }
+    for (i, monitor) in app.monitors.iter().enumerate() {
+        let marker = if i == app.selected_monitor {
+            t(" (selected)")
+        } else {
+            ""
+        };
+        writeln!(out, "{}. {}{marker}", i + 1, describe(monitor))?;
+    }
+    Ok(())
+}
+
+fn help(out: &mut impl Write) -> io::Result<()> {
+    let commands = [
+        ("list", "show all monitors"),
+        ("select NAME", "select a monitor by name or number"),
+        ("mode WxH@Hz", "switch the selected monitor's mode"),
+        ("pos X Y", "move the selected monitor"),
+        ("scale FACTOR", "set the selected monitor's scale"),
+        ("enable, disable", "turn the selected monitor on or off"),
+        ("workspace ID MONITOR", "put a workspace on a monitor"),
+        ("apply", "apply positions staged by a resize"),
+        ("save", "write the monitor config"),
+        ("quit", "leave"),
+    ];
+    for (usage, what) in commands {
+        writeln!(out, "{usage}: {}", t(what))?;
+    }
+    Ok(())
+}
+
+/// Prints and clears the App's status and error lines.
+fn announce(app: &mut App, out: &mut impl Write) -> io::Result<()> {
+    if let Some(status) = app.status_message.take() {
+        writeln!(out, "{status}")?;
+    }
+    if let Some(err) = app.error_message.take() {
+        writeln!(out, "{}", tf("Error: {}", &[&err]))?;
+    }
+    Ok(())
+}
+
+/// Applies pending compositor events, describing each change. With a
+/// timeout, waits that long for the first one.
+fn settle(
+    app: &mut App,
+    wlx_events: &Receiver<WlMonitorEvent>,
+    out: &mut impl Write,
+    wait: Option<Duration>,
+) -> io::Result<()> {
+    let first = match wait {
+        Some(timeout) => wlx_events.recv_timeout(timeout).ok(),
+        None => wlx_events.try_recv().ok(),
+    };
+    let Some(first) = first else {
+        return Ok(());
+    };
+    for event in std::iter::once(first).chain(wlx_events.try_iter()) {
+        match &event {
+            WlMonitorEvent::Changed(monitor) => {
+                let name = monitor.name.clone();
+                app.handle_event(event);
+                if let Some(m) = app.monitors.iter().find(|m| m.name == name) {
+                    writeln!(out, "{}", tf("Now {}.", &[&describe(m)]))?;
+                }
+            }
+            WlMonitorEvent::Removed { name, .. } => {
+                writeln!(out, "{}", tf("{} was disconnected.", &[name]))?;
+                app.handle_event(event);
+            }
+            _ => app.handle_event(event),
+        }
+    }
+    app.save_config();
+    announce(app, out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{app, monitor};
+    use std::sync::mpsc::sync_channel;
+
+    #[test]
+    fn test_parse_commands() {
+        assert_eq!(parse("  "), Ok(None));
+        assert_eq!(
+            parse("select DP-1"),
+            Ok(Some(Input::Select("DP-1".into())))
+        );
+        assert_eq!(parse("pos 1920 0"), Ok(Some(Input::Position(1920, 0))));
+        assert_eq!(parse("scale 1.25"), Ok(Some(Input::Scale(1.25))));
+        assert_eq!(
+            parse("ws 3 HDMI-A-1"),
+            Ok(Some(Input::Workspace(3, "HDMI-A-1".into())))
+        );
+        assert_eq!(parse("pos 10"), Err(InputError::Usage("pos X Y")));
+        assert_eq!(
+            parse("frobnicate"),
+            Err(InputError::Unknown("frobnicate".into()))
+        );
+    }
+
+    fn run_script(app: &mut App, script: &str) -> String {
+        // No compositor: commands see a closed channel and don't wait.
+        let (_, events) = sync_channel(1);
+        let mut out = Vec::new();
+        session(app, &events, script.as_bytes(), &mut out).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn test_session_drives_the_same_actions_as_the_tui() {
+        let (mut app, rx) = app();
+        app.set_monitors(vec![monitor("DP-1", 0, 0), monitor("DP-2", 1920, 0)]);
+
+        let out = run_script(
+            &mut app,
+            "select DP-2\nscale 1.5\npos 0 0\npos 1920 1080\nbogus\nquit\n",
+        );
+
+        assert!(out.contains("2. DP-2 (Test Monitor DP-2): on, 1920x1080 at"));
+        assert!(out.contains("Selected DP-2."));
+        assert!(out.contains("Error: That position overlaps another monitor"));
+        assert!(out.contains("Error: unknown command: bogus"));
+        assert!(!out.contains(['│', '─', '┌', '\x1b']));
+
+        let Ok(WlMonitorAction::SetScale { name, scale }) = rx.try_recv()
+        else {
+            panic!("expected a scale change");
+        };
+        assert_eq!((name.as_str(), scale), ("DP-2", 1.5));
+        let Ok(WlMonitorAction::SetPosition { name, x, y }) = rx.try_recv()
+        else {
+            panic!("expected a position change");
+        };
+        assert_eq!((name.as_str(), x, y), ("DP-2", 1920, 1080));
+        assert!(rx.try_recv().is_err());
+    }
+
+    #[test]
+    fn test_disabling_the_last_monitor_asks_first() {
+        let (mut app, rx) = app();
+        app.set_monitors(vec![monitor("DP-1", 0, 0)]);
+
+        let out = run_script(&mut app, "disable\nno\n");
+        assert!(out.contains("DP-1 is your last enabled monitor"));
+        assert!(out.contains("Kept DP-1 enabled."));
+        assert!(rx.try_recv().is_err());
+
+        run_script(&mut app, "disable\nyes\n");
+        let Ok(WlMonitorAction::Toggle { name, .. }) = rx.try_recv() else {
+            panic!("expected a toggle");
+        };
+        assert_eq!(name, "DP-1");
+    }
+}
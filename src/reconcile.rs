@@ -0,0 +1,224 @@
+use wlx_monitors::WlMonitor;
+
+use crate::{
+    compositor::settings::ConfigMonitor, i18n::t, layout::LayoutMonitor,
+    refresh, utils::transform_label,
+};
+
+/// Scales this close count as the same; configs round to two decimals.
+const SCALE_TOLERANCE: f64 = 0.005;
+
+/// A setting that can differ between the config and the live monitor.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Setting {
+    Enabled,
+    Mode,
+    Position,
+    Scale,
+    Transform,
+}
+
+impl Setting {
+    pub fn label(self) -> &'static str {
+        match self {
+            Setting::Enabled => "enabled",
+            Setting::Mode => "mode",
+            Setting::Position => "position",
+            Setting::Scale => "scale",
+            Setting::Transform => "transform",
+        }
+    }
+}
+
+/// One setting the config and the live monitor disagree on, as shown.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Mismatch {
+    pub setting: Setting,
+    pub config: String,
+    pub live: String,
+}
+
+/// A connected monitor whose live settings are not what the config says.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Divergence {
+    pub name: String,
+    pub mismatches: Vec<Mismatch>,
+    /// The config's settings, with the ones it leaves open taken from
+    /// the live monitor, ready to be applied.
+    pub config: LayoutMonitor,
+}
+
+/// Every connected monitor the config configures differently from how
+/// it runs. Monitors the config does not mention, and settings it leaves
+/// to the compositor, are not compared. A disabled monitor only compares
+/// whether it is enabled.
+pub fn diverging(
+    config: &[ConfigMonitor],
+    live: &[WlMonitor],
+) -> Vec<Divergence> {
+    live.iter()
+        .filter_map(|monitor| {
+            let saved = config.iter().find(|c| c.name == monitor.name)?;
+            let wanted = wanted(saved, monitor);
+            let current = LayoutMonitor::from_monitor(monitor);
+            let mismatches = mismatches(saved, &wanted, &current);
+            (!mismatches.is_empty()).then(|| Divergence {
+                name: monitor.name.clone(),
+                mismatches,
+                config: wanted,
+            })
+        })
+        .collect()
+}
+
+fn wanted(saved: &ConfigMonitor, live: &WlMonitor) -> LayoutMonitor {
+    let mut wanted = LayoutMonitor::from_monitor(live);
+    wanted.enabled = saved.enabled;
+    if let Some((width, height, mhz)) = saved.mode {
+        wanted.width = width;
+        wanted.height = height;
+        if let Some(mhz) = mhz {
+            wanted.refresh_rate = refresh::whole_hz(mhz);
+            wanted.refresh_mhz = Some(mhz);
+        }
+    }
+    if let Some((x, y)) = saved.position {
+        (wanted.x, wanted.y) = (x, y);
+    }
+    wanted.scale = saved.scale.unwrap_or(wanted.scale);
+    wanted.transform = saved.transform.unwrap_or(wanted.transform);
+    wanted
+}
+
+fn mismatches(
+    saved: &ConfigMonitor,
+    wanted: &LayoutMonitor,
+    current: &LayoutMonitor,
+) -> Vec<Mismatch> {
+    if wanted.enabled != current.enabled {
+        return vec![Mismatch {
+            setting: Setting::Enabled,
+            config: show(Setting::Enabled, wanted),
+            live: show(Setting::Enabled, current),
+        }];
+    }
+    if !current.enabled {
+        return Vec::new();
+    }
+    let compared = [
+        (Setting::Mode, saved.mode.is_some()),
+        (Setting::Position, saved.position.is_some()),
+        (Setting::Scale, saved.scale.is_some()),
+        (Setting::Transform, saved.transform.is_some()),
+    ];
+    compared
+        .into_iter()
+        .filter(|&(_, set)| set)
+        .map(|(setting, _)| setting)
+        .filter(|&setting| match setting {
+            Setting::Scale => {
+                (wanted.scale - current.scale).abs() > SCALE_TOLERANCE
+            }
+            // The live mode only knows whole Hz.
+            Setting::Mode => {
+                (wanted.width, wanted.height, wanted.refresh_rate)
+                    != (current.width, current.height, current.refresh_rate)
+            }
+            _ => show(setting, wanted) != show(setting, current),
+        })
+        .map(|setting| Mismatch {
+            setting,
+            config: show(setting, wanted),
+            live: show(setting, current),
+        })
+        .collect()
+}
+
+/// How a setting reads in the view and in the session summary.
+pub fn show(setting: Setting, m: &LayoutMonitor) -> String {
+    match setting {
+        Setting::Enabled => t(if m.enabled { "on" } else { "off" }).into(),
+        Setting::Mode => format!("{}x{}@{}", m.width, m.height, m.refresh()),
+        Setting::Position => format!("{},{}", m.x, m.y),
+        Setting::Scale => format!("{}", (m.scale * 100.0).round() / 100.0),
+        Setting::Transform => t(transform_label(m.transform)).into(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        compositor::{Compositor, settings::config_monitors},
+        test_support::{disabled, mode, monitor},
+    };
+    use wlx_monitors::WlTransform;
+
+    fn summary(divergences: &[Divergence]) -> Vec<(&str, Setting, &str, &str)> {
+        divergences
+            .iter()
+            .flat_map(|d| {
+                d.mismatches.iter().map(|m| {
+                    (
+                        d.name.as_str(),
+                        m.setting,
+                        m.config.as_str(),
+                        m.live.as_str(),
+                    )
+                })
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_matching_layout_has_no_divergence() {
+        let config = config_monitors(
+            Compositor::Hyprland,
+            "monitor = DP-1, 1920x1080@60, 0x0, 1\n\
+             monitor = DP-2, preferred, auto, auto\n\
+             monitor = DP-3, disable\n\
+             monitor = HDMI-A-1, 1920x1080@60, 0x0, 1\n",
+        );
+        let live = [
+            monitor("DP-1", 0, 0),
+            monitor("DP-2", 5000, 0),
+            disabled("DP-3"),
+            monitor("eDP-1", 0, 0),
+        ];
+        assert_eq!(diverging(&config, &live), []);
+    }
+
+    #[test]
+    fn test_each_differing_setting_is_listed() {
+        let config = config_monitors(
+            Compositor::Sway,
+            "output DP-1 {\n    mode 2560x1440@144Hz\n    pos 1920 0\n    \
+             scale 1.25\n    transform 90\n}\n\
+             output DP-2 disable\n\
+             output DP-3 pos 0 0\n",
+        );
+        let mut dp1 = monitor("DP-1", 0, 0);
+        dp1.modes.push(mode(2560, 1440, 144));
+        let mut dp3 = disabled("DP-3");
+        dp3.scale = 2.0;
+        let live = [dp1, monitor("DP-2", 1920, 0), dp3];
+
+        let found = diverging(&config, &live);
+        assert_eq!(
+            summary(&found),
+            [
+                ("DP-1", Setting::Mode, "2560x1440@144", "1920x1080@60"),
+                ("DP-1", Setting::Position, "1920,0", "0,0"),
+                ("DP-1", Setting::Scale, "1.25", "1"),
+                ("DP-1", Setting::Transform, "Rotate 90", "Normal"),
+                ("DP-2", Setting::Enabled, "off", "on"),
+                ("DP-3", Setting::Enabled, "on", "off"),
+            ]
+        );
+        let dp1 = &found[0].config;
+        assert_eq!((dp1.width, dp1.x, dp1.scale), (2560, 1920, 1.25));
+        assert_eq!(dp1.transform, WlTransform::Rotate90);
+        // What DP-3's config leaves open keeps the live value.
+        assert_eq!(found[2].config.scale, 2.0);
+    }
+}
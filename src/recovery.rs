@@ -0,0 +1,192 @@
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use wlx_monitors::{WlMonitor, WlTransform};
+
+use crate::{
+    compositor::{Compositor, diff, format::render_monitor_config},
+    layout::{Layout, LayoutMonitor},
+};
+
+const RECOVERY_FILE: &str = "monitors-recovery.conf";
+
+/// Wayfire's monitor config is `~/.config/wayfire.ini` itself, so its
+/// recovery config is a whole `wayfire.ini` too.
+const WAYFIRE_RECOVERY_FILE: &str = "wayfire-recovery.ini";
+
+/// Where the recovery config for the monitor config at `config_path`
+/// lives: next to it.
+pub fn recovery_path(compositor: Compositor, config_path: &Path) -> PathBuf {
+    let name = match compositor {
+        Compositor::Wayfire => WAYFIRE_RECOVERY_FILE,
+        _ => RECOVERY_FILE,
+    };
+    config_path.with_file_name(name)
+}
+
+/// A layout that should always come up: every monitor on at its preferred
+/// mode, scale 1 and no rotation, side by side from the left in the order
+/// given. Workspace rules are left out.
+pub fn safe_layout(monitors: &[WlMonitor]) -> Layout {
+    let mut x = 0;
+    let monitors = monitors
+        .iter()
+        .filter_map(|monitor| {
+            let mode = monitor
+                .modes
+                .iter()
+                .find(|m| m.preferred)
+                .or_else(|| monitor.modes.iter().find(|m| m.is_current))
+                .or_else(|| monitor.modes.first())?;
+            let safe = LayoutMonitor {
+                name: monitor.name.clone(),
+                description: monitor.description.clone(),
+                enabled: true,
+                width: mode.resolution.width,
+                height: mode.resolution.height,
+                refresh_rate: mode.refresh_rate,
+                refresh_mhz: None,
+                x,
+                y: 0,
+                scale: 1.0,
+                transform: WlTransform::Normal,
+            };
+            x += mode.resolution.width;
+            Some(safe)
+        })
+        .collect();
+    Layout {
+        monitors,
+        workspaces: Vec::new(),
+    }
+}
+
+/// The command that loads the recovery config at `path` from a TTY.
+pub fn tty_command(compositor: Compositor, path: &Path) -> Option<String> {
+    let path = path.display();
+    match compositor {
+        Compositor::Hyprland => Some(format!("hyprctl keyword source {path}")),
+        Compositor::Sway => Some(format!("sway -c {path}")),
+        Compositor::River
+        | Compositor::Niri
+        | Compositor::Labwc
+        | Compositor::Unknown => Some(format!("sh {path}")),
+        Compositor::Wayfire => Some(format!("wayfire -c {path}")),
+        Compositor::Kwin => None,
+    }
+}
+
+/// The recovery config for `monitors`, headed by how to apply it. Written
+/// with the same formatter as the monitor config. A config shared with
+/// everything else, like `wayfire.ini`, keeps the rest of `config`, so
+/// the compositor started on it still loads its plugins.
+pub fn render(
+    compositor: Compositor,
+    monitors: &[WlMonitor],
+    config: &str,
+    path: &Path,
+) -> Option<String> {
+    let command = tty_command(compositor, path)?;
+    let content =
+        render_monitor_config(compositor, &safe_layout(monitors), &[])?;
+    let content = diff::keep_ini_around(compositor, config, &content);
+    Some(format!(
+        "# Boot-safe monitor layout, rewritten by xwlm on every save.\n\
+         # To apply it, run `xwlm recover` or, from a TTY:\n\
+         #   {command}\n{content}"
+    ))
+}
+
+pub fn save(
+    compositor: Compositor,
+    monitors: &[WlMonitor],
+    config_path: &Path,
+) -> io::Result<()> {
+    let path = recovery_path(compositor, config_path);
+    let config = fs::read_to_string(config_path).unwrap_or_default();
+    match render(compositor, monitors, &config, &path) {
+        Some(content) => fs::write(path, content),
+        None => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        compositor::settings::config_monitors,
+        test_support::{disabled, mode, monitor},
+    };
+
+    #[test]
+    fn test_safe_layout_turns_everything_on_side_by_side() {
+        let mut big = monitor("DP-1", 1920, 500);
+        big.modes[0].preferred = false;
+        let mut preferred = mode(3840, 2160, 60);
+        preferred.preferred = true;
+        big.modes.push(preferred);
+        big.scale = 2.0;
+        big.transform = WlTransform::Rotate90;
+        let monitors = [big, disabled("HDMI-A-1")];
+
+        let layout = safe_layout(&monitors);
+        let placed: Vec<_> = layout
+            .monitors
+            .iter()
+            .map(|m| (m.name.as_str(), m.enabled, m.width, m.x, m.y, m.scale))
+            .collect();
+        assert_eq!(
+            placed,
+            [
+                ("DP-1", true, 3840, 0, 0, 1.0),
+                ("HDMI-A-1", true, 1920, 3840, 0, 1.0),
+            ]
+        );
+        assert_eq!(layout.monitors[0].transform, WlTransform::Normal);
+    }
+
+    #[test]
+    fn test_recovery_config_reads_back_as_the_safe_layout() {
+        let monitors = [monitor("DP-1", 1920, 0), disabled("DP-2")];
+        let path = recovery_path(
+            Compositor::Hyprland,
+            Path::new("/home/me/.config/hypr/xwlm.conf"),
+        );
+        assert_eq!(
+            path,
+            Path::new("/home/me/.config/hypr/monitors-recovery.conf")
+        );
+        for comp in [
+            Compositor::Hyprland,
+            Compositor::Sway,
+            Compositor::River,
+            Compositor::Unknown,
+        ] {
+            let content = render(comp, &monitors, "", &path).unwrap();
+            let command = tty_command(comp, &path).unwrap();
+            assert!(content.contains(&command), "{content}");
+            let read = config_monitors(comp, &content);
+            assert_eq!(read.len(), 2, "{content}");
+            assert!(read.iter().all(|m| m.enabled && m.scale == Some(1.0)));
+            assert_eq!(read[1].position, Some((1920, 0)));
+        }
+        assert_eq!(render(Compositor::Kwin, &monitors, "", &path), None);
+    }
+
+    #[test]
+    fn test_wayfire_recovery_config_keeps_the_rest_of_wayfire_ini() {
+        let config = Path::new("/home/me/.config/wayfire.ini");
+        let path = recovery_path(Compositor::Wayfire, config);
+        assert_eq!(path, Path::new("/home/me/.config/wayfire-recovery.ini"));
+        let ini = "[core]\nplugins = expo grid\n\n[output:DP-1]\nscale = 2\n";
+        let monitors = [monitor("DP-1", 0, 0)];
+        let content =
+            render(Compositor::Wayfire, &monitors, ini, &path).unwrap();
+        assert!(content.contains(&format!("wayfire -c {}", path.display())));
+        assert!(content.contains("[core]\nplugins = expo grid\n"));
+        assert!(content.contains("[output:DP-1]\nmode = 1920x1080@60000\n"));
+        assert!(!content.contains("scale = 2"));
+    }
+}
@@ -0,0 +1,42 @@
+//! Refresh rates in millihertz, the way compositors list them. A Wayland
+//! mode only carries whole Hz, cut off rather than rounded, so a TV's
+//! 59.94 Hz mode is 59 there and its 60 Hz mode is 60.
+
+/// `59.94`, `59.940Hz` or `60`, in millihertz.
+pub fn parse(hz: &str) -> Option<i32> {
+    let hz: f64 = hz.trim().trim_end_matches("Hz").parse().ok()?;
+    (hz.is_finite() && hz > 0.0).then(|| (hz * 1000.0).round() as i32)
+}
+
+/// The whole Hz a Wayland mode refreshing at `mhz` is reported with.
+pub fn whole_hz(mhz: i32) -> i32 {
+    mhz / 1000
+}
+
+/// Hz with the trailing zeros trimmed, e.g. `59.94` or `60`.
+pub fn format(mhz: i32) -> String {
+    let hz = format!("{}.{:03}", mhz / 1000, mhz % 1000);
+    hz.trim_end_matches('0').trim_end_matches('.').to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_59_94_and_60_stay_apart() {
+        assert_eq!(parse("59.94"), Some(59_940));
+        assert_eq!(parse("59.940Hz"), Some(59_940));
+        assert_eq!(parse("60"), Some(60_000));
+        assert_eq!(parse("0"), None);
+        assert_eq!(parse("fast"), None);
+
+        assert_eq!(whole_hz(59_940), 59);
+        assert_eq!(whole_hz(60_000), 60);
+
+        assert_eq!(format(59_940), "59.94");
+        assert_eq!(format(60_000), "60");
+        assert_eq!(format(143_998), "143.998");
+        assert_eq!(format(parse("59.94").unwrap()), "59.94");
+    }
+}
@@ -0,0 +1,611 @@
+use std::{
+    fs, io,
+    io::Write,
+    path::PathBuf,
+    process::{Command, Stdio},
+};
+
+use thiserror::Error;
+use wlx_monitors::WlTransform;
+
+use crate::{
+    compositor::{
+        Compositor,
+        diff::{self, configured_monitors},
+        format::{render_monitor_config, transform_to_sway},
+        settings::{ConfigMonitor, config_monitors},
+        workspace_config::{WorkspaceRule, parse_workspaces},
+    },
+    layout::{Layout, LayoutMonitor},
+    refresh,
+    setup::default_config_path,
+};
+
+#[derive(Error, Debug)]
+pub enum RemoteError {
+    #[error("failed to read {path} on {host}: {source}")]
+    Read {
+        host: String,
+        path: String,
+        #[source]
+        source: io::Error,
+    },
+
+    #[error("failed to write {path} on {host}: {source}")]
+    Write {
+        host: String,
+        path: String,
+        #[source]
+        source: io::Error,
+    },
+
+    #[error("failed to reload on {host}: {source}")]
+    Reload {
+        host: String,
+        #[source]
+        source: io::Error,
+    },
+
+    #[error("cannot tell the compositor from {0}; pass --compositor")]
+    UnknownCompositor(String),
+
+    #[error("{0} writes no monitor config")]
+    NoConfigStyle(&'static str),
+
+    #[error("{name} has no {what} in the config; give one with --{what}")]
+    Missing { name: String, what: &'static str },
+
+    #[error("failed to read layout file {path}: {source}")]
+    LayoutFile {
+        path: String,
+        #[source]
+        source: io::Error,
+    },
+
+    #[error("invalid layout file: {0}")]
+    Layout(#[from] toml::de::Error),
+}
+
+/// How the monitor config of another machine is reached.
+pub trait Transport {
+    /// Where the files are, e.g. "me@signage".
+    fn host(&self) -> &str;
+
+    fn read(&self, path: &str) -> io::Result<String>;
+
+    /// Replaces `path` in one step, so the compositor never reads half a
+    /// file.
+    fn write(&self, path: &str, content: &str) -> io::Result<()>;
+
+    /// Runs `command` over there.
+    fn run(&self, command: &[&str]) -> io::Result<()>;
+}
+
+/// Reaches the other machine with the `ssh` on PATH, which brings its own
+/// keys, agent and `~/.ssh/config` hosts.
+pub struct Ssh {
+    pub host: String,
+}
+
+/// What an ssh login lacks to reach the running session: the Wayland
+/// display and the compositors' IPC sockets, found in the runtime dir
+/// when not already set.
+const SESSION_ENV: &str = r#"pick() { ls "$@" 2>/dev/null | head -n 1; }
+dir=${XDG_RUNTIME_DIR:-/run/user/$(id -u)}
+: "${WAYLAND_DISPLAY:=$(cd "$dir" 2>/dev/null && pick -d wayland-?)}"
+: "${SWAYSOCK:=$(pick -d "$dir"/sway-ipc.*.sock)}"
+: "${HYPRLAND_INSTANCE_SIGNATURE:=$(pick -t "$dir/hypr")}"
+export XDG_RUNTIME_DIR="$dir" WAYLAND_DISPLAY SWAYSOCK \
+    HYPRLAND_INSTANCE_SIGNATURE
+"#;
+
+impl Ssh {
+    fn ssh(&self, remote: &str) -> Command {
+        let mut command = Command::new("ssh");
+        command.arg("--").arg(&self.host).arg(remote);
+        command
+    }
+}
+
+impl Transport for Ssh {
+    fn host(&self) -> &str {
+        &self.host
+    }
+
+    fn read(&self, path: &str) -> io::Result<String> {
+        let output = self.ssh(&format!("cat {}", quote_path(path))).output()?;
+        check(output.status, &output.stderr)?;
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    }
+
+    fn write(&self, path: &str, content: &str) -> io::Result<()> {
+        // A symlinked config is written where it points, and a monitor
+        // script stays executable.
+        let remote = format!(
+            "path={path}; [ ! -L \"$path\" ] || path=$(readlink -f \"$path\") \
+             && tmp=\"$path.xwlm-tmp\" && cat > \"$tmp\" \
+             && {{ [ ! -x \"$path\" ] || chmod +x \"$tmp\"; }} \
+             && mv -f \"$tmp\" \"$path\"",
+            path = quote_path(path),
+        );
+        let mut child = self
+            .ssh(&remote)
+            .stdin(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()?;
+        if let Some(mut stdin) = child.stdin.take() {
+            stdin.write_all(content.as_bytes())?;
+        }
+        let output = child.wait_with_output()?;
+        check(output.status, &output.stderr)
+    }
+
+    fn run(&self, command: &[&str]) -> io::Result<()> {
+        let output = self.ssh(&session_command(command)).output()?;
+        check(output.status, &output.stderr)
+    }
+}
+
+fn check(status: std::process::ExitStatus, stderr: &[u8]) -> io::Result<()> {
+    if status.success() {
+        return Ok(());
+    }
+    let stderr = String::from_utf8_lossy(stderr);
+    let reason = stderr.trim();
+    Err(io::Error::other(if reason.is_empty() {
+        status.to_string()
+    } else {
+        reason.to_string()
+    }))
+}
+
+/// `command` quoted for the remote shell, run in the graphical session.
+fn session_command(command: &[&str]) -> String {
+    let words: Vec<String> = command.iter().map(|w| quote(w)).collect();
+    format!("{SESSION_ENV}{}", words.join(" "))
+}
+
+/// `word` for a POSIX shell, in single quotes.
+fn quote(word: &str) -> String {
+    format!("'{}'", word.replace('\'', r"'\''"))
+}
+
+/// Like [`quote`], leaving a leading `~/` for the remote shell to expand.
+fn quote_path(path: &str) -> String {
+    match path.strip_prefix("~/") {
+        Some(rest) => format!("\"$HOME\"/{}", quote(rest)),
+        None => quote(path),
+    }
+}
+
+/// What `--remote` does with the monitor config.
+#[derive(Debug, PartialEq)]
+pub enum RemoteAction {
+    /// Prints what the config sets.
+    Show,
+    Set(MonitorEdit),
+    /// Writes the monitors and workspaces of a layout.toml.
+    ApplyFile(PathBuf),
+}
+
+/// `set --output NAME ...`: the settings to change for one monitor; the
+/// others stay as the config has them.
+#[derive(Debug, Default, PartialEq)]
+pub struct MonitorEdit {
+    pub output: String,
+    pub mode: Option<(i32, i32, i32)>,
+    pub position: Option<(i32, i32)>,
+    pub scale: Option<f64>,
+    pub transform: Option<WlTransform>,
+    pub enabled: Option<bool>,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct RemoteOptions {
+    pub host: String,
+    pub compositor: Option<Compositor>,
+    /// The monitor config over there; the compositor's usual one when
+    /// unset.
+    pub config: Option<String>,
+    pub reload: bool,
+    pub dry_run: bool,
+    pub action: RemoteAction,
+}
+
+pub fn run(
+    opts: &RemoteOptions,
+    transport: &dyn Transport,
+) -> Result<(), RemoteError> {
+    let host = transport.host().to_string();
+    let path = match (&opts.config, opts.compositor) {
+        (Some(path), _) => path.clone(),
+        (None, Some(comp)) if comp.capabilities().config_style.is_none() => {
+            return Err(RemoteError::NoConfigStyle(comp.label()));
+        }
+        (None, Some(comp)) => default_config_path(comp),
+        (None, None) => {
+            return Err(RemoteError::UnknownCompositor(format!("{host}:")));
+        }
+    };
+    let old = transport.read(&path).map_err(|source| RemoteError::Read {
+        host: host.clone(),
+        path: path.clone(),
+        source,
+    })?;
+    let comp = match opts.compositor {
+        Some(comp) => comp,
+        None => Compositor::ALL
+            .into_iter()
+            .find(|&c| !configured_monitors(c, &old).is_empty())
+            .ok_or_else(|| {
+                RemoteError::UnknownCompositor(format!("{host}:{path}"))
+            })?,
+    };
+
+    let (touched, workspaces) = match &opts.action {
+        RemoteAction::Show => {
+            show(comp, &old);
+            return Ok(());
+        }
+        RemoteAction::Set(edit) => {
+            if edit.mode.is_some() {
+                eprintln!(
+                    "Warning: {} is not connected here, so {}'s mode is \
+                     not checked against what it supports",
+                    host, edit.output
+                );
+            }
+            let monitor = edited(comp, &old, edit)?;
+            (vec![monitor], parse_workspaces(comp, &old))
+        }
+        RemoteAction::ApplyFile(file) => {
+            let text = fs::read_to_string(file).map_err(|source| {
+                RemoteError::LayoutFile {
+                    path: file.display().to_string(),
+                    source,
+                }
+            })?;
+            let layout: Layout = toml::from_str(&text)?;
+            eprintln!(
+                "Warning: {host} is not connected here, so modes are not \
+                 checked against what the monitors support"
+            );
+            let workspaces = if layout.workspaces.is_empty() {
+                parse_workspaces(comp, &old)
+            } else {
+                layout.workspaces
+            };
+            (layout.monitors, workspaces)
+        }
+    };
+
+    let content = rewrite(comp, &old, touched, workspaces)?;
+    if opts.dry_run {
+        println!("Would write {host}:{path}:");
+        print!("{content}");
+        return Ok(());
+    }
+    transport
+        .write(&path, &content)
+        .map_err(|source| RemoteError::Write {
+            host: host.clone(),
+            path: path.clone(),
+            source,
+        })?;
+    println!("Wrote {host}:{path}");
+
+    if opts.reload {
+        let caps = comp.capabilities();
+        let reloaded = match (caps.reload_command, caps.config_style) {
+            (Some(command), _) => Some(transport.run(command)),
+            (None, Some(style)) if style.is_script() => {
+                Some(transport.run(&["sh", &path]))
+            }
+            _ => None,
+        };
+        if let Some(result) = reloaded {
+            result.map_err(|source| RemoteError::Reload { host, source })?;
+            println!("Reloaded {}", comp.label());
+        }
+    }
+    Ok(())
+}
+
+/// The config `old` with `touched` written out in full and every other
+/// monitor's lines kept as they are.
+fn rewrite(
+    comp: Compositor,
+    old: &str,
+    touched: Vec<LayoutMonitor>,
+    workspaces: Vec<WorkspaceRule>,
+) -> Result<String, RemoteError> {
+    let others: Vec<String> = configured_monitors(comp, old)
+        .into_iter()
+        .filter(|name| !touched.iter().any(|m| &m.name == name))
+        .collect();
+    let layout = Layout {
+        monitors: touched,
+        workspaces,
+    };
+    let content = render_monitor_config(comp, &layout, &[])
+        .ok_or(RemoteError::NoConfigStyle(comp.label()))?;
+    Ok(diff::merge_into(comp, old, &content, &others))
+}
+
+/// The monitor `edit` names, as the config has it with the edit applied.
+fn edited(
+    comp: Compositor,
+    old: &str,
+    edit: &MonitorEdit,
+) -> Result<LayoutMonitor, RemoteError> {
+    let name = edit.output.clone();
+    let configured = config_monitors(comp, old)
+        .into_iter()
+        .find(|m| m.name == name)
+        .unwrap_or_else(|| ConfigMonitor {
+            name: name.clone(),
+            enabled: true,
+            mode: None,
+            position: None,
+            scale: None,
+            transform: None,
+        });
+    let enabled = edit.enabled.unwrap_or(configured.enabled);
+    let missing = |what| RemoteError::Missing {
+        name: name.clone(),
+        what,
+    };
+    // A mode written without a rate keeps the compositor's preferred one.
+    let mode = edit
+        .mode
+        .map(|(w, h, mhz)| (w, h, Some(mhz)))
+        .or(configured.mode);
+    let (width, height, mhz) = match mode {
+        Some(mode) => mode,
+        None if !enabled => (0, 0, None),
+        None => return Err(missing("mode")),
+    };
+    let (x, y) = match edit.position.or(configured.position) {
+        Some(position) => position,
+        None if !enabled => (0, 0),
+        None => return Err(missing("position")),
+    };
+    Ok(LayoutMonitor {
+        name,
+        description: String::new(),
+        enabled,
+        width,
+        height,
+        refresh_rate: mhz.map_or(0, refresh::whole_hz),
+        refresh_mhz: mhz,
+        x,
+        y,
+        scale: edit.scale.or(configured.scale).unwrap_or(1.0),
+        transform: edit
+            .transform
+            .or(configured.transform)
+            .unwrap_or(WlTransform::Normal),
+    })
+}
+
+fn show(comp: Compositor, content: &str) {
+    println!("Compositor: {}", comp.label());
+    for m in config_monitors(comp, content) {
+        if !m.enabled {
+            println!("{}: disabled", m.name);
+            continue;
+        }
+        let mut line = format!("{}:", m.name);
+        match m.mode {
+            Some((w, h, Some(mhz))) => {
+                let hz = refresh::format(mhz);
+                line.push_str(&format!(" {w}x{h}@{hz}"));
+            }
+            Some((w, h, None)) => line.push_str(&format!(" {w}x{h}")),
+            None => line.push_str(" preferred"),
+        }
+        if let Some((x, y)) = m.position {
+            line.push_str(&format!(" at {x},{y}"));
+        }
+        if let Some(scale) = m.scale {
+            line.push_str(&format!(" scale {scale}"));
+        }
+        if let Some(transform) = m.transform {
+            let name = transform_to_sway(transform);
+            line.push_str(&format!(" transform {name}"));
+        }
+        println!("{line}");
+    }
+    for ws in parse_workspaces(comp, content) {
+        println!("Workspace {} on {}", ws.id, ws.monitor);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::TempDir;
+    use std::cell::RefCell;
+
+    /// A directory standing in for the other machine's home, removed when
+    /// the test is done with it.
+    struct LocalDir {
+        root: TempDir,
+        ran: RefCell<Vec<String>>,
+    }
+
+    impl LocalDir {
+        fn new(name: &str) -> Self {
+            Self {
+                root: TempDir::new(&format!("remote-{name}")),
+                ran: RefCell::new(Vec::new()),
+            }
+        }
+
+        fn file(&self, path: &str) -> PathBuf {
+            self.root.path().join(path.trim_start_matches("~/"))
+        }
+    }
+
+    impl Transport for LocalDir {
+        fn host(&self) -> &str {
+            "me@signage"
+        }
+
+        fn read(&self, path: &str) -> io::Result<String> {
+            fs::read_to_string(self.file(path))
+        }
+
+        fn write(&self, path: &str, content: &str) -> io::Result<()> {
+            let tmp = self.file(&format!("{path}.xwlm-tmp"));
+            fs::write(&tmp, content)?;
+            fs::rename(tmp, self.file(path))
+        }
+
+        fn run(&self, command: &[&str]) -> io::Result<()> {
+            self.ran.borrow_mut().push(command.join(" "));
+            Ok(())
+        }
+    }
+
+    const SWAY: &str = "\
+# hand-written
+output DP-1 mode 1920x1080@60Hz pos 0 0 scale 1
+output HDMI-A-1 {
+    mode 3840x2160@30Hz
+    pos 1920 0
+    # the signage panel
+}
+workspace 1 output DP-1
+";
+
+    fn options(action: RemoteAction) -> RemoteOptions {
+        RemoteOptions {
+            host: "me@signage".into(),
+            compositor: None,
+            config: Some("~/.config/sway/output.conf".into()),
+            reload: true,
+            dry_run: false,
+            action,
+        }
+    }
+
+    #[test]
+    fn test_set_rewrites_one_monitor_and_reloads() {
+        let remote = LocalDir::new("set");
+        fs::create_dir_all(remote.file("~/.config/sway")).unwrap();
+        fs::write(remote.file("~/.config/sway/output.conf"), SWAY).unwrap();
+
+        let edit = MonitorEdit {
+            output: "DP-1".into(),
+            scale: Some(1.5),
+            transform: Some(WlTransform::Rotate90),
+            ..MonitorEdit::default()
+        };
+        run(&options(RemoteAction::Set(edit)), &remote).unwrap();
+
+        let written =
+            fs::read_to_string(remote.file("~/.config/sway/output.conf"))
+                .unwrap();
+        assert!(
+            written.contains(
+                "output DP-1 {\n    mode 1920x1080@60Hz\n    pos 0 0\n    \
+                 scale 1.50\n    transform 90\n}"
+            ),
+            "{written}"
+        );
+        // The other monitor is left exactly as it was written.
+        assert!(written.contains("    # the signage panel\n"), "{written}");
+        assert!(written.contains("workspace 1 output DP-1"), "{written}");
+        assert!(!remote.file("~/.config/sway/output.conf.xwlm-tmp").exists());
+        assert_eq!(*remote.ran.borrow(), ["swaymsg reload"]);
+    }
+
+    #[test]
+    fn test_apply_file_writes_the_layout() {
+        let remote = LocalDir::new("apply");
+        fs::create_dir_all(remote.file("~/.config/sway")).unwrap();
+        fs::write(remote.file("~/.config/sway/output.conf"), SWAY).unwrap();
+        let layout = remote.root.path().join("layout.toml");
+        fs::write(
+            &layout,
+            "[[monitor]]\nname = \"HDMI-A-1\"\nenabled = false\n\
+             width = 3840\nheight = 2160\nrefresh_rate = 30\nx = 0\ny = 0\n\
+             scale = 1.0\ntransform = \"normal\"\n",
+        )
+        .unwrap();
+
+        let mut opts = options(RemoteAction::ApplyFile(layout));
+        opts.reload = false;
+        run(&opts, &remote).unwrap();
+
+        let written =
+            fs::read_to_string(remote.file("~/.config/sway/output.conf"))
+                .unwrap();
+        assert!(written.contains("output HDMI-A-1 disable"), "{written}");
+        assert!(
+            written
+                .contains("output DP-1 mode 1920x1080@60Hz pos 0 0 scale 1\n"),
+            "{written}"
+        );
+        assert!(remote.ran.borrow().is_empty());
+    }
+
+    #[test]
+    fn test_set_needs_what_the_config_does_not_say() {
+        let remote = LocalDir::new("missing");
+        fs::create_dir_all(remote.file("~/.config/sway")).unwrap();
+        fs::write(remote.file("~/.config/sway/output.conf"), SWAY).unwrap();
+
+        let edit = MonitorEdit {
+            output: "DP-9".into(),
+            position: Some((0, 1080)),
+            ..MonitorEdit::default()
+        };
+        let err = run(&options(RemoteAction::Set(edit)), &remote).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "DP-9 has no mode in the config; give one with --mode"
+        );
+        let kept =
+            fs::read_to_string(remote.file("~/.config/sway/output.conf"))
+                .unwrap();
+        assert_eq!(kept, SWAY);
+    }
+
+    #[test]
+    fn test_set_keeps_a_mode_written_without_a_rate() {
+        let remote = LocalDir::new("rate");
+        fs::create_dir_all(remote.file("~/.config/sway")).unwrap();
+        fs::write(
+            remote.file("~/.config/sway/output.conf"),
+            "output DP-1 mode 1920x1080 pos 0 0\n",
+        )
+        .unwrap();
+
+        let edit = MonitorEdit {
+            output: "DP-1".into(),
+            scale: Some(2.0),
+            ..MonitorEdit::default()
+        };
+        run(&options(RemoteAction::Set(edit)), &remote).unwrap();
+        let written =
+            fs::read_to_string(remote.file("~/.config/sway/output.conf"))
+                .unwrap();
+        assert!(written.contains("    mode 1920x1080\n"), "{written}");
+    }
+
+    #[test]
+    fn test_reload_runs_in_the_graphical_session() {
+        let command = session_command(&["swaymsg", "reload"]);
+        assert!(command.contains(": \"${SWAYSOCK:="));
+        assert!(command.contains(": \"${WAYLAND_DISPLAY:="));
+        assert!(command.ends_with("\n'swaymsg' 'reload'"));
+    }
+
+    #[test]
+    fn test_quote_path_leaves_home_to_the_remote_shell() {
+        assert_eq!(quote_path("~/a b.conf"), "\"$HOME\"/'a b.conf'");
+        assert_eq!(quote_path("/etc/it's.conf"), "'/etc/it'\\''s.conf'");
+    }
+}
@@ -0,0 +1,208 @@
+use wlx_monitors::WlTransform;
+
+use crate::edid::PhysicalSize;
+
+const TARGET_LOGICAL_DPI: (f64, f64) = (96.0, 110.0);
+const PLAUSIBLE_DPI: (f64, f64) = (50.0, 600.0);
+const SUGGESTED_SCALES: [f64; 9] =
+    [1.0, 1.25, 1.5, 1.75, 2.0, 2.25, 2.5, 2.75, 3.0];
+const SCALE_RANGE: (f64, f64) = (0.5, 10.0);
+
+/// A scale that leaves the logical size fractional, with the closest
+/// scales on either side that don't.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ScaleLint {
+    pub logical: (f64, f64),
+    pub lower: Option<f64>,
+    pub higher: Option<f64>,
+}
+
+/// Horizontal DPI of a mode on a panel of the given physical size. Returns
+/// `None` for sizes that can't be real (some TVs put the aspect ratio in
+/// the size fields).
+pub fn dpi(resolution: (i32, i32), size: PhysicalSize) -> Option<f64> {
+    if resolution.0 <= 0 || size.width_mm == 0 {
+        return None;
+    }
+    let dpi = f64::from(resolution.0) / (f64::from(size.width_mm) / 25.4);
+    (PLAUSIBLE_DPI.0..=PLAUSIBLE_DPI.1)
+        .contains(&dpi)
+        .then_some(dpi)
+}
+
+pub fn is_clean_scale(resolution: (i32, i32), scale: f64) -> bool {
+    let w = f64::from(resolution.0) / scale;
+    let h = f64::from(resolution.1) / scale;
+    (w - w.round()).abs() < 1e-6 && (h - h.round()).abs() < 1e-6
+}
+
+/// Size in logical pixels. Rotated outputs swap width and height.
+pub fn logical_size(
+    resolution: (i32, i32),
+    transform: WlTransform,
+    scale: f64,
+) -> (f64, f64) {
+    let (w, h) = match transform {
+        WlTransform::Rotate90
+        | WlTransform::Rotate270
+        | WlTransform::Flipped90
+        | WlTransform::Flipped270 => (resolution.1, resolution.0),
+        _ => resolution,
+    };
+    (f64::from(w) / scale, f64::from(h) / scale)
+}
+
+/// [`logical_size`] in whole logical pixels, rounded to the nearest. The
+/// map, staged moves, snapping and the written config all place monitors
+/// with this, so they agree on where a fractionally scaled monitor ends.
+pub fn logical_extent(
+    resolution: (i32, i32),
+    transform: WlTransform,
+    scale: f64,
+) -> (i32, i32) {
+    let scale = if scale > 0.0 { scale } else { 1.0 };
+    let (w, h) = logical_size(resolution, transform, scale);
+    (w.round() as i32, h.round() as i32)
+}
+
+fn gcd(a: i32, b: i32) -> i32 {
+    if b == 0 { a } else { gcd(b, a % b) }
+}
+
+/// Both sides divide evenly exactly when the scale is `gcd(w, h) / k`.
+/// Only scales that survive being written with two decimals count, since
+/// that is how the compositor configs store them.
+fn clean_scales(resolution: (i32, i32)) -> impl Iterator<Item = f64> {
+    let g = gcd(resolution.0.max(0), resolution.1.max(0));
+    let max_k = (f64::from(g) / SCALE_RANGE.0) as i32;
+    (1..=max_k)
+        .map(move |k| f64::from(g) / f64::from(k))
+        .filter(|s| (SCALE_RANGE.0..=SCALE_RANGE.1).contains(s))
+        .filter(|s| ((s * 100.0).round() / 100.0 - s).abs() < 1e-9)
+}
+
+pub fn lint(
+    resolution: (i32, i32),
+    transform: WlTransform,
+    scale: f64,
+) -> Option<ScaleLint> {
+    if resolution.0 <= 0 || resolution.1 <= 0 || scale <= 0.0 {
+        return None;
+    }
+    if is_clean_scale(resolution, scale) {
+        return None;
+    }
+    let mut lower = None;
+    let mut higher = None;
+    for s in clean_scales(resolution) {
+        if s < scale {
+            lower = Some(lower.map_or(s, |l: f64| l.max(s)));
+        } else {
+            higher = Some(higher.map_or(s, |h: f64| h.min(s)));
+        }
+    }
+    Some(ScaleLint {
+        logical: logical_size(resolution, transform, scale),
+        lower,
+        higher,
+    })
+}
+
+/// Picks the scale whose logical DPI lands in the 96–110 band, preferring
+/// scales that divide the resolution evenly.
+pub fn recommended_scale(dpi: f64, resolution: (i32, i32)) -> f64 {
+    let (low, high) = TARGET_LOGICAL_DPI;
+    let mid = (low + high) / 2.0;
+    let miss = |s: f64| {
+        let logical = dpi / s;
+        (low - logical).max(logical - high).max(0.0)
+    };
+
+    let in_range: Vec<f64> = SUGGESTED_SCALES
+        .into_iter()
+        .filter(|&s| miss(s) == 0.0)
+        .collect();
+    let clean: Vec<f64> = in_range
+        .iter()
+        .copied()
+        .filter(|&s| is_clean_scale(resolution, s))
+        .collect();
+    let pool = if !clean.is_empty() { clean } else { in_range };
+
+    if pool.is_empty() {
+        return SUGGESTED_SCALES
+            .into_iter()
+            .min_by(|a, b| miss(*a).total_cmp(&miss(*b)))
+            .unwrap_or(1.0);
+    }
+    let off_mid = |s: f64| (dpi / s - mid).abs();
+    pool.into_iter()
+        .min_by(|a, b| off_mid(*a).total_cmp(&off_mid(*b)))
+        .unwrap_or(1.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn size(width_mm: u32, height_mm: u32) -> PhysicalSize {
+        PhysicalSize {
+            width_mm,
+            height_mm,
+        }
+    }
+
+    #[test]
+    fn test_dpi_of_common_panels() {
+        let qhd_27 = dpi((2560, 1440), size(597, 336)).unwrap();
+        assert!((qhd_27 - 108.9).abs() < 0.1);
+
+        let uhd_27 = dpi((3840, 2160), size(597, 336)).unwrap();
+        assert!((uhd_27 - 163.4).abs() < 0.1);
+    }
+
+    #[test]
+    fn test_dpi_rejects_bogus_sizes() {
+        assert_eq!(dpi((1920, 1080), size(0, 0)), None);
+        assert_eq!(dpi((1920, 1080), size(16, 9)), None);
+        assert_eq!(dpi((0, 0), size(597, 336)), None);
+    }
+
+    #[test]
+    fn test_recommended_scale() {
+        assert_eq!(recommended_scale(108.9, (2560, 1440)), 1.0);
+        assert_eq!(recommended_scale(163.4, (3840, 2160)), 1.5);
+        assert_eq!(recommended_scale(137.8, (3840, 2160)), 1.25);
+        assert_eq!(recommended_scale(220.0, (3840, 2160)), 2.0);
+    }
+
+    #[test]
+    fn test_recommended_scale_prefers_clean_division() {
+        // 2.25 and 2.5 both land in the band for this DPI, only 2.5
+        // divides 3200x2000 evenly.
+        assert_eq!(recommended_scale(240.0, (3200, 2000)), 2.5);
+    }
+
+    #[test]
+    fn test_lint_offers_nearest_clean_scales() {
+        let found = lint((2560, 1440), WlTransform::Normal, 1.1).unwrap();
+        assert_eq!(found.lower, Some(1.0));
+        assert_eq!(found.higher, Some(1.25));
+        assert!((found.logical.0 - 2327.27).abs() < 0.01);
+
+        assert_eq!(lint((2560, 1440), WlTransform::Normal, 1.25), None);
+    }
+
+    #[test]
+    fn test_lint_accounts_for_rotation() {
+        let found = lint((2560, 1440), WlTransform::Rotate90, 1.1).unwrap();
+        assert!((found.logical.0 - 1309.09).abs() < 0.01);
+        assert!((found.logical.1 - 2327.27).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_is_clean_scale() {
+        assert!(is_clean_scale((2560, 1440), 1.25));
+        assert!(!is_clean_scale((2560, 1440), 1.1));
+    }
+}
@@ -0,0 +1,249 @@
+use std::{
+    fs,
+    io::Read,
+    process::{Command, Stdio},
+    thread,
+    time::{Duration, Instant},
+};
+
+use crate::constants::CAPTURE_PROBE_MS;
+
+/// Recorders that capture one output, chosen with `-o` or `--output`.
+const RECORDERS: [&str; 2] = ["wf-recorder", "wl-screenrec"];
+
+/// Something capturing the screen.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Capture {
+    /// The program the picture goes to, e.g. "Firefox" or "wf-recorder".
+    pub app: String,
+    /// The captured output, `None` when it cannot be told which.
+    pub output: Option<String>,
+}
+
+impl Capture {
+    /// Whether this may be capturing `output`.
+    pub fn covers(&self, output: &str) -> bool {
+        self.output.as_deref().is_none_or(|o| o == output)
+    }
+}
+
+/// Screen recorders found among the running processes and screencasts
+/// the portal streams through PipeWire. Anything that cannot be asked
+/// counts as no capture.
+pub fn active() -> Vec<Capture> {
+    let mut captures = recorders();
+    captures.extend(portal_streams());
+    captures
+}
+
+fn recorders() -> Vec<Capture> {
+    let Ok(entries) = fs::read_dir("/proc") else {
+        return Vec::new();
+    };
+    entries
+        .flatten()
+        .filter_map(|entry| {
+            entry.file_name().to_str()?.parse::<u32>().ok()?;
+            let comm = fs::read_to_string(entry.path().join("comm")).ok()?;
+            if !RECORDERS.contains(&comm.trim()) {
+                return None;
+            }
+            let cmdline = fs::read(entry.path().join("cmdline")).ok()?;
+            let args: Vec<String> = cmdline
+                .split(|&b| b == 0)
+                .filter(|a| !a.is_empty())
+                .map(|a| String::from_utf8_lossy(a).into_owned())
+                .collect();
+            Some(Capture {
+                app: comm.trim().to_string(),
+                output: recorded_output(&args),
+            })
+        })
+        .collect()
+}
+
+/// The output a recorder was started on with `-o`/`--output`.
+fn recorded_output(args: &[String]) -> Option<String> {
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        if arg == "-o" || arg == "--output" {
+            return iter.next().cloned();
+        }
+        if let Some(output) = arg
+            .strip_prefix("--output=")
+            .or_else(|| arg.strip_prefix("-o").filter(|o| !o.is_empty()))
+        {
+            return Some(output.to_string());
+        }
+    }
+    None
+}
+
+/// Asks `pw-dump` for screencasts, giving up after [`CAPTURE_PROBE_MS`].
+fn portal_streams() -> Vec<Capture> {
+    let timeout = Duration::from_millis(CAPTURE_PROBE_MS);
+    match stdout_within(&mut Command::new("pw-dump"), timeout) {
+        Some(dump) => parse_pw_dump(&dump),
+        None => Vec::new(),
+    }
+}
+
+/// What `command` prints when it succeeds within `timeout`. One that
+/// takes longer is killed and waited for, so it leaves no zombie.
+fn stdout_within(command: &mut Command, timeout: Duration) -> Option<String> {
+    let mut child = command
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .ok()?;
+    // Read alongside, so a full pipe cannot keep the command from exiting.
+    let mut stdout = child.stdout.take()?;
+    let reader = thread::spawn(move || {
+        let mut out = String::new();
+        stdout.read_to_string(&mut out).map(|_| out)
+    });
+    let deadline = Instant::now() + timeout;
+    loop {
+        match child.try_wait() {
+            Ok(Some(status)) if status.success() => {
+                return reader.join().ok()?.ok();
+            }
+            Ok(Some(_)) => return None,
+            Ok(None) if Instant::now() < deadline => {
+                thread::sleep(Duration::from_millis(10));
+            }
+            _ => {
+                let _ = child.kill();
+                let _ = child.wait();
+                return None;
+            }
+        }
+    }
+}
+
+/// Screencasts in `pw-dump` output: a video source the desktop portal
+/// feeds, and the apps reading video streams. The portal does not say
+/// which output it shares, so these cover every output.
+fn parse_pw_dump(dump: &str) -> Vec<Capture> {
+    let objects: Vec<&str> = dump.split("\n  {").collect();
+    let shared = objects.iter().any(|object| {
+        prop(object, "media.class") == Some("Video/Source")
+            && prop(object, "node.name").is_some_and(|name| {
+                name.starts_with("xdpw")
+                    || name.starts_with("xdph")
+                    || name.contains("xdg-desktop-portal")
+            })
+    });
+    if !shared {
+        return Vec::new();
+    }
+    let mut captures: Vec<Capture> = objects
+        .iter()
+        .filter(|object| {
+            prop(object, "media.class") == Some("Stream/Input/Video")
+        })
+        .filter_map(|object| {
+            prop(object, "application.name")
+                .or_else(|| prop(object, "node.name"))
+        })
+        .map(|app| Capture {
+            app: app.to_string(),
+            output: None,
+        })
+        .collect();
+    captures.dedup();
+    if captures.is_empty() {
+        captures.push(Capture {
+            app: "xdg-desktop-portal".to_string(),
+            output: None,
+        });
+    }
+    captures
+}
+
+/// The string value of `"key": "value"` in a `pw-dump` object.
+fn prop<'a>(object: &'a str, key: &str) -> Option<&'a str> {
+    let quoted = format!("\"{key}\":");
+    object.lines().find_map(|line| {
+        let value = line.trim().strip_prefix(&quoted)?.trim();
+        let value = value.trim_end_matches(',').strip_prefix('"')?;
+        value.strip_suffix('"')
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_a_slow_probe_is_killed_and_reaped() {
+        let quick = Duration::from_secs(5);
+        let out = stdout_within(Command::new("echo").arg("{}"), quick);
+        assert_eq!(out.as_deref(), Some("{}\n"));
+        assert_eq!(stdout_within(&mut Command::new("false"), quick), None);
+
+        let started = Instant::now();
+        let slow = Duration::from_millis(50);
+        assert_eq!(stdout_within(Command::new("sleep").arg("5"), slow), None);
+        assert!(started.elapsed() < Duration::from_secs(2));
+    }
+
+    fn strings(args: &[&str]) -> Vec<String> {
+        args.iter().map(|a| a.to_string()).collect()
+    }
+
+    #[test]
+    fn test_recorded_output() {
+        let table = [
+            (vec!["wf-recorder", "-o", "DP-1", "-f", "a.mp4"], Some("DP-1")),
+            (vec!["wf-recorder", "-oHDMI-A-1"], Some("HDMI-A-1")),
+            (vec!["wl-screenrec", "--output=eDP-1"], Some("eDP-1")),
+            (vec!["wl-screenrec", "--output", "DP-2"], Some("DP-2")),
+            (vec!["wf-recorder", "-f", "a.mp4"], None),
+        ];
+        for (args, want) in table {
+            let got = recorded_output(&strings(&args));
+            assert_eq!(got.as_deref(), want, "{args:?}");
+        }
+    }
+
+    #[test]
+    fn test_parse_pw_dump_finds_portal_screencasts() {
+        let dump = r#"[
+  {
+    "id": 40,
+    "type": "PipeWire:Interface:Node",
+    "info": {
+      "props": {
+        "media.class": "Video/Source",
+        "node.name": "xdph-streaming-0"
+      }
+    }
+  },
+  {
+    "id": 41,
+    "type": "PipeWire:Interface:Node",
+    "info": {
+      "props": {
+        "application.name": "Firefox",
+        "media.class": "Stream/Input/Video",
+        "node.name": "webrtc-consume-stream"
+      }
+    }
+  }
+]"#;
+        let captures = parse_pw_dump(dump);
+        assert_eq!(
+            captures,
+            [Capture {
+                app: "Firefox".into(),
+                output: None,
+            }]
+        );
+        assert!(captures[0].covers("DP-1"));
+
+        // A webcam read without a portal stream is no screencast.
+        let camera = dump.replace("xdph-streaming-0", "v4l2_input.usb");
+        assert_eq!(parse_pw_dump(&camera), []);
+    }
+}
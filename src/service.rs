@@ -0,0 +1,337 @@
+use std::{
+    env, fs, io,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+use thiserror::Error;
+
+use crate::{
+    compositor::{Compositor, extraction::main_config_path},
+    utils,
+};
+
+const UNIT_NAME: &str = "xwlm.service";
+const AUTOSTART_MARKER: &str = "# added by xwlm install-service";
+
+#[derive(Error, Debug)]
+pub enum ServiceError {
+    #[error(
+        "HOME is not set and /etc/passwd has no home directory for this \
+         user, so the user unit has nowhere to go"
+    )]
+    NoHome,
+
+    #[error("failed to locate the xwlm binary: {0}")]
+    Exe(#[source] io::Error),
+
+    #[error("failed to write {path}: {source}")]
+    Write {
+        path: String,
+        #[source]
+        source: io::Error,
+    },
+
+    #[error("failed to run systemctl: {0}")]
+    Systemctl(String),
+}
+
+/// One change `install` or `uninstall` made, or would make on a dry run.
+#[derive(Debug, PartialEq)]
+pub enum Step {
+    WriteFile { path: PathBuf, contents: String },
+    RemoveFile(PathBuf),
+    Systemctl(Vec<&'static str>),
+    Unchanged(String),
+}
+
+impl Step {
+    pub fn describe(&self) -> String {
+        match self {
+            Step::WriteFile { path, contents } => {
+                format!("write {}:\n{}", path.display(), contents)
+            }
+            Step::RemoveFile(path) => format!("remove {}", path.display()),
+            Step::Systemctl(args) => {
+                format!("run systemctl --user {}", args.join(" "))
+            }
+            Step::Unchanged(what) => format!("{what} (unchanged)"),
+        }
+    }
+}
+
+pub fn unit_contents(exe: &Path) -> String {
+    format!(
+        "[Unit]\n\
+         Description=Apply the xwlm monitor layout\n\
+         PartOf=graphical-session.target\n\
+         After=graphical-session.target\n\
+         \n\
+         [Service]\n\
+         Type=oneshot\n\
+         ExecStart={} apply --once\n\
+         RemainAfterExit=yes\n\
+         \n\
+         [Install]\n\
+         WantedBy=graphical-session.target\n",
+        systemd_quote(exe)
+    )
+}
+
+/// `path` as one word of a unit's command line: quoted, with what systemd
+/// would expand as a specifier or variable escaped.
+fn systemd_quote(path: &Path) -> String {
+    let mut out = String::from("\"");
+    for c in path.display().to_string().chars() {
+        match c {
+            '"' | '\\' => {
+                out.push('\\');
+                out.push(c);
+            }
+            '%' => out.push_str("%%"),
+            '$' => out.push_str("$$"),
+            _ => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+/// What `xwlm apply` needs to find `compositor`, which a user unit does not
+/// inherit from the session on its own.
+fn session_vars(compositor: Compositor) -> &'static str {
+    match compositor {
+        Compositor::Hyprland => "WAYLAND_DISPLAY HYPRLAND_INSTANCE_SIGNATURE",
+        Compositor::Sway => "WAYLAND_DISPLAY SWAYSOCK",
+        _ => "WAYLAND_DISPLAY",
+    }
+}
+
+/// The line that, run at every session start, hands the session's
+/// variables to the user manager and starts the unit with them.
+fn autostart_line(compositor: Compositor) -> Option<String> {
+    let cmd = format!(
+        "systemctl --user import-environment {} && \
+         systemctl --user start {UNIT_NAME}",
+        session_vars(compositor)
+    );
+    match compositor {
+        Compositor::Hyprland => Some(format!("exec-once = {cmd}")),
+        Compositor::Sway => Some(format!("exec {cmd}")),
+        Compositor::River | Compositor::Labwc => Some(format!("{cmd} &")),
+        // config.kdl would not parse the `#` marker; the user unit starts
+        // with Niri's session instead. Wayfire applies wayfire.ini itself
+        // and KWin restores its own layout.
+        Compositor::Niri
+        | Compositor::Wayfire
+        | Compositor::Kwin
+        | Compositor::Unknown => None,
+    }
+}
+
+fn autostart_path(compositor: Compositor, home: &str) -> Option<PathBuf> {
+    match compositor {
+        Compositor::River => {
+            let p = PathBuf::from(format!("{home}/.config/river/init"));
+            p.exists().then_some(p)
+        }
+        _ => main_config_path(compositor),
+    }
+}
+
+/// Appends the marked autostart line unless it is already there.
+fn add_autostart(content: &str, line: &str) -> Option<String> {
+    if content.lines().any(|l| l.trim() == line) {
+        return None;
+    }
+    let mut out = content.to_string();
+    if !out.is_empty() && !out.ends_with('\n') {
+        out.push('\n');
+    }
+    out.push_str(&format!("\n{AUTOSTART_MARKER}\n{line}\n"));
+    Some(out)
+}
+
+/// Drops the autostart line and the marker above it.
+fn remove_autostart(content: &str, line: &str) -> Option<String> {
+    if !content.lines().any(|l| l.trim() == line) {
+        return None;
+    }
+    let mut out = String::new();
+    let mut lines = content.lines().peekable();
+    while let Some(l) = lines.next() {
+        if l.trim() == AUTOSTART_MARKER
+            && lines.peek().is_some_and(|next| next.trim() == line)
+        {
+            lines.next();
+            continue;
+        }
+        if l.trim() == line {
+            continue;
+        }
+        out.push_str(l);
+        out.push('\n');
+    }
+    Some(out)
+}
+
+fn home() -> Result<String, ServiceError> {
+    utils::home_dir()
+        .map(|home| home.display().to_string())
+        .ok_or(ServiceError::NoHome)
+}
+
+fn unit_path(home: &str) -> PathBuf {
+    let base = env::var("XDG_CONFIG_HOME")
+        .unwrap_or_else(|_| format!("{home}/.config"));
+    PathBuf::from(base).join("systemd/user").join(UNIT_NAME)
+}
+
+pub fn plan_install(
+    compositor: Compositor,
+    autostart: bool,
+) -> Result<Vec<Step>, ServiceError> {
+    let home = home()?;
+    let exe = env::current_exe().map_err(ServiceError::Exe)?;
+    let mut steps = Vec::new();
+
+    let path = unit_path(&home);
+    let contents = unit_contents(&exe);
+    if fs::read_to_string(&path).is_ok_and(|c| c == contents) {
+        steps.push(Step::Unchanged(path.display().to_string()));
+    } else {
+        steps.push(Step::WriteFile { path, contents });
+    }
+    steps.push(Step::Systemctl(vec!["daemon-reload"]));
+    steps.push(Step::Systemctl(vec!["enable", UNIT_NAME]));
+
+    if autostart
+        && let Some(line) = autostart_line(compositor)
+        && let Some(path) = autostart_path(compositor, &home)
+    {
+        let content = fs::read_to_string(&path).unwrap_or_default();
+        match add_autostart(&content, &line) {
+            Some(contents) => steps.push(Step::WriteFile { path, contents }),
+            None => steps.push(Step::Unchanged(path.display().to_string())),
+        }
+    }
+    Ok(steps)
+}
+
+pub fn plan_uninstall(compositor: Compositor) -> Result<Vec<Step>, ServiceError> {
+    let home = home()?;
+    let mut steps = Vec::new();
+
+    let path = unit_path(&home);
+    if path.exists() {
+        steps.push(Step::Systemctl(vec!["disable", UNIT_NAME]));
+        steps.push(Step::RemoveFile(path));
+        steps.push(Step::Systemctl(vec!["daemon-reload"]));
+    }
+
+    if let Some(line) = autostart_line(compositor)
+        && let Some(path) = autostart_path(compositor, &home)
+        && let Ok(content) = fs::read_to_string(&path)
+        && let Some(contents) = remove_autostart(&content, &line)
+    {
+        steps.push(Step::WriteFile { path, contents });
+    }
+    Ok(steps)
+}
+
+pub fn execute(steps: &[Step]) -> Result<(), ServiceError> {
+    for step in steps {
+        match step {
+            Step::WriteFile { path, contents } => {
+                let write = |p: &Path| -> io::Result<()> {
+                    if let Some(parent) = p.parent() {
+                        fs::create_dir_all(parent)?;
+                    }
+                    fs::write(p, contents)
+                };
+                write(path).map_err(|source| ServiceError::Write {
+                    path: path.display().to_string(),
+                    source,
+                })?;
+            }
+            Step::RemoveFile(path) => {
+                fs::remove_file(path).map_err(|source| ServiceError::Write {
+                    path: path.display().to_string(),
+                    source,
+                })?;
+            }
+            Step::Systemctl(args) => {
+                let status = Command::new("systemctl")
+                    .arg("--user")
+                    .args(args)
+                    .status()
+                    .map_err(|e| ServiceError::Systemctl(e.to_string()))?;
+                if !status.success() {
+                    return Err(ServiceError::Systemctl(format!(
+                        "`systemctl --user {}` exited with {status}",
+                        args.join(" ")
+                    )));
+                }
+            }
+            Step::Unchanged(_) => {}
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unit_binds_to_graphical_session() {
+        let unit = unit_contents(Path::new("/usr/bin/xwlm"));
+        assert!(unit.contains("PartOf=graphical-session.target"));
+        assert!(unit.contains("WantedBy=graphical-session.target"));
+        assert!(unit.contains("ExecStart=\"/usr/bin/xwlm\" apply --once"));
+    }
+
+    #[test]
+    fn test_unit_quotes_the_binary_path() {
+        let unit = unit_contents(Path::new("/opt/my apps/100%/$x\"/xwlm"));
+        assert!(
+            unit.contains(
+                "ExecStart=\"/opt/my apps/100%%/$$x\\\"/xwlm\" apply --once\n"
+            ),
+            "{unit}"
+        );
+    }
+
+    #[test]
+    fn test_autostart_imports_the_session_before_starting_the_unit() {
+        assert_eq!(
+            autostart_line(Compositor::Hyprland).unwrap(),
+            "exec-once = systemctl --user import-environment WAYLAND_DISPLAY \
+             HYPRLAND_INSTANCE_SIGNATURE && systemctl --user start xwlm.service"
+        );
+        assert_eq!(
+            autostart_line(Compositor::River).unwrap(),
+            "systemctl --user import-environment WAYLAND_DISPLAY && \
+             systemctl --user start xwlm.service &"
+        );
+        assert_eq!(autostart_line(Compositor::Niri), None);
+    }
+
+    #[test]
+    fn test_autostart_is_not_duplicated() {
+        let line = "exec-once = /usr/bin/xwlm apply --once";
+        let once = add_autostart("source = monitors.conf", line).unwrap();
+        assert_eq!(add_autostart(&once, line), None);
+        assert_eq!(once.matches(line).count(), 1);
+    }
+
+    #[test]
+    fn test_remove_autostart_restores_config() {
+        let line = "exec swaybar";
+        let original = "output * bg #000000 solid_color\n";
+        let added = add_autostart(original, line).unwrap();
+        let removed = remove_autostart(&added, line).unwrap();
+        assert_eq!(removed.trim_end(), original.trim_end());
+        assert_eq!(remove_autostart(&removed, line), None);
+    }
+}
@@ -0,0 +1,173 @@
+use std::{collections::BTreeMap, path::PathBuf};
+
+use wlx_monitors::WlMonitor;
+
+use crate::{
+    i18n::{t, tf},
+    layout::LayoutMonitor,
+    reconcile::{self, Setting},
+    state::WorkspaceAssignment,
+};
+
+const SETTINGS: [Setting; 5] = [
+    Setting::Enabled,
+    Setting::Mode,
+    Setting::Position,
+    Setting::Scale,
+    Setting::Transform,
+];
+
+/// What this run of xwlm changed, summed up on quit and in debug dumps.
+#[derive(Debug, Default)]
+pub struct SessionLog {
+    /// Each monitor as it was when first seen.
+    monitors: BTreeMap<String, LayoutMonitor>,
+    /// The monitor of each workspace at launch.
+    workspaces: Option<BTreeMap<usize, Option<String>>>,
+    applied: usize,
+    /// Files written, in the order they were first written.
+    written: Vec<PathBuf>,
+    /// Backups made, newest last, each once.
+    backups: Vec<PathBuf>,
+}
+
+impl SessionLog {
+    /// Remembers the monitors not seen before as they are now.
+    pub fn see(&mut self, monitors: &[WlMonitor]) {
+        for m in monitors {
+            self.monitors
+                .entry(m.name.clone())
+                .or_insert_with(|| LayoutMonitor::from_monitor(m));
+        }
+    }
+
+    /// Remembers the workspace assignments, the first time only.
+    pub fn see_workspaces(&mut self, assignments: &[WorkspaceAssignment]) {
+        self.workspaces
+            .get_or_insert_with(|| workspace_monitors(assignments));
+    }
+
+    pub fn applied(&mut self) {
+        self.applied += 1;
+    }
+
+    pub fn wrote(&mut self, path: PathBuf) {
+        if !self.written.contains(&path) {
+            self.written.push(path);
+        }
+    }
+
+    pub fn backed_up(&mut self, backup: PathBuf) {
+        if !self.backups.contains(&backup) {
+            self.backups.push(backup);
+        }
+    }
+
+    /// Whether anything was sent to the compositor this session.
+    pub fn has_applied(&self) -> bool {
+        self.applied > 0
+    }
+
+    /// One line per changed monitor and workspace, then one per file
+    /// written and per backup made, e.g. "DP-1: position 0,0 → 1920,0,
+    /// scale 1 → 1.25".
+    pub fn summary(
+        &self,
+        monitors: &[WlMonitor],
+        assignments: &[WorkspaceAssignment],
+    ) -> Vec<String> {
+        let mut lines = Vec::new();
+        for (name, before) in &self.monitors {
+            let Some(now) = monitors.iter().find(|m| &m.name == name) else {
+                continue;
+            };
+            let now = LayoutMonitor::from_monitor(now);
+            // The settings of a monitor that is off say nothing.
+            let compared = if before.enabled != now.enabled || !now.enabled {
+                &SETTINGS[..1]
+            } else {
+                &SETTINGS[1..]
+            };
+            let changes: Vec<String> = compared
+                .iter()
+                .copied()
+                .filter_map(|setting| {
+                    let old = reconcile::show(setting, before);
+                    let new = reconcile::show(setting, &now);
+                    (old != new).then(|| match setting {
+                        Setting::Enabled => format!("{old} → {new}"),
+                        _ => format!("{} {old} → {new}", t(setting.label())),
+                    })
+                })
+                .collect();
+            if !changes.is_empty() {
+                lines.push(format!("{name}: {}", changes.join(", ")));
+            }
+        }
+        if let Some(before) = &self.workspaces {
+            let none = t("none");
+            for (id, now) in workspace_monitors(assignments) {
+                let old = before.get(&id).cloned().flatten();
+                if old != now {
+                    lines.push(tf(
+                        "workspace {}: {} → {}",
+                        &[
+                            &id,
+                            &old.as_deref().unwrap_or(none),
+                            &now.as_deref().unwrap_or(none),
+                        ],
+                    ));
+                }
+            }
+        }
+        for path in &self.written {
+            lines.push(tf("wrote {}", &[&path.display()]));
+        }
+        for path in &self.backups {
+            lines.push(tf("backed up to {}", &[&path.display()]));
+        }
+        lines
+    }
+}
+
+fn workspace_monitors(
+    assignments: &[WorkspaceAssignment],
+) -> BTreeMap<usize, Option<String>> {
+    assignments
+        .iter()
+        .map(|w| (w.id, w.monitor.clone()))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{disabled, monitor};
+
+    #[test]
+    fn test_summary_lists_what_changed_since_first_seen() {
+        let mut log = SessionLog::default();
+        log.see(&[monitor("DP-1", 0, 0), monitor("DP-2", 1920, 0)]);
+        let mut moved = monitor("DP-1", 1920, 0);
+        moved.scale = 1.25;
+        // Seen again later, the first state still counts.
+        log.see(&[moved.clone()]);
+        log.wrote(PathBuf::from("/tmp/monitors.conf"));
+        log.wrote(PathBuf::from("/tmp/monitors.conf"));
+        log.backed_up(PathBuf::from("/tmp/monitors.conf.bak.1"));
+        assert!(!log.has_applied());
+        log.applied();
+
+        let lines = log.summary(&[moved, disabled("DP-2")], &[]);
+        assert_eq!(
+            lines,
+            [
+                "DP-1: position 0,0 → 1920,0, scale 1 → 1.25",
+                "DP-2: on → off",
+                "wrote /tmp/monitors.conf",
+                "backed up to /tmp/monitors.conf.bak.1",
+            ]
+        );
+        assert!(log.has_applied());
+    }
+}
@@ -16,6 +16,8 @@ use ratatui::{DefaultTerminal, Frame, Terminal};
 
 use crate::compositor::Compositor;
 use crate::compositor::extraction::{ExtractionPlan, extract_monitors, main_config_path};
+use crate::i18n::{t, tf};
+use crate::tui::key_binds::{fit, hint};
 use crate::utils::expand_tilde;
 use crate::xwlm_config::{self, Config, save_config};
 
@@ -60,12 +62,16 @@ impl SetupState {
     }
 }
 
-fn default_config_path(compositor: Compositor) -> String {
+pub fn default_config_path(compositor: Compositor) -> String {
     match compositor {
         Compositor::Hyprland => "~/.config/hypr/monitors.conf".to_string(),
         Compositor::Sway => "~/.config/sway/output.conf".to_string(),
-        Compositor::River => "~/.config/river/monitors.conf".to_string(),
-        Compositor::Unknown => String::new(),
+        Compositor::River => "~/.config/river/monitors.sh".to_string(),
+        Compositor::Niri => "~/.config/niri/monitors.sh".to_string(),
+        Compositor::Wayfire => "~/.config/wayfire.ini".to_string(),
+        Compositor::Labwc => "~/.config/labwc/monitors.sh".to_string(),
+        Compositor::Unknown => "~/.config/xwlm/monitors.sh".to_string(),
+        Compositor::Kwin => String::new(),
     }
 }
 
@@ -73,8 +79,12 @@ fn get_monitors_config_name(compositor: Compositor) -> &'static str {
     match compositor {
         Compositor::Hyprland => "monitors.conf",
         Compositor::Sway => "output.conf",
-        Compositor::River => "monitors.conf",
-        Compositor::Unknown => "monitors.conf",
+        Compositor::River => "monitors.sh",
+        Compositor::Niri => "monitors.sh",
+        Compositor::Wayfire => "wayfire.ini",
+        Compositor::Labwc => "monitors.sh",
+        Compositor::Unknown => "monitors.sh",
+        Compositor::Kwin => "monitors.conf",
     }
 }
 
@@ -127,6 +137,12 @@ fn attempt_extraction(compositor: Compositor) -> Option<ExtractionResult> {
 }
 
 pub fn run(compositor: Compositor) -> Result<Option<Config>, xwlm_config::ConfigError> {
+    // KWin keeps its own output settings, so there is no file to pick.
+    if compositor.capabilities().applies_by_command {
+        let cfg = Config::new(PathBuf::new());
+        save_config(&cfg)?;
+        return Ok(Some(cfg));
+    }
     let result = run_setup(compositor).map_err(io::Error::other)?;
     match result {
         Some(cfg) => {
@@ -186,14 +202,11 @@ pub fn init(terminal: &mut DefaultTerminal, compositor: Compositor) -> io::Resul
                     if !result.already_consolidated
                         && let Err(e) = result.plan.apply()
                     {
-                        state.error = Some(format!("Extraction failed: {e}"));
+                        state.error = Some(tf("Extraction failed: {}", &[&e]));
                         state.phase = SetupPhase::Manual;
                         continue;
                     }
-                    return Ok(Some(Config {
-                        monitor_config_path: PathBuf::from(config_path),
-                        workspace_count: 10,
-                    }));
+                    return Ok(Some(Config::new(PathBuf::from(config_path))));
                 }
                 (SetupPhase::Extraction, KeyCode::Char('m')) => {
                     state.phase = SetupPhase::Manual;
@@ -228,42 +241,45 @@ pub fn init(terminal: &mut DefaultTerminal, compositor: Compositor) -> io::Resul
                     state.error = None;
                     state.warned = false;
                 }
-                (SetupPhase::Manual, KeyCode::Left) => {
-                    if state.cursor > 0 {
-                        state.cursor = state.prev_cursor();
-                    }
+                (SetupPhase::Manual, KeyCode::Left) if state.cursor > 0 => {
+                    state.cursor = state.prev_cursor();
                 }
-                (SetupPhase::Manual, KeyCode::Right) => {
-                    if state.cursor < state.input.len() {
-                        state.cursor = state.next_cursor();
-                    }
+                (SetupPhase::Manual, KeyCode::Right)
+                    if state.cursor < state.input.len() =>
+                {
+                    state.cursor = state.next_cursor();
                 }
                 (SetupPhase::Manual, KeyCode::Home) => state.cursor = 0,
                 (SetupPhase::Manual, KeyCode::End) => state.cursor = state.input.len(),
                 (SetupPhase::Manual, KeyCode::Enter) => {
                     let path = state.input.trim();
                     if path.is_empty() {
-                        state.error = Some("Path cannot be empty".to_string());
+                        state.error = Some(t("Path cannot be empty").to_string());
                         continue;
                     }
 
                     if !state.warned {
                         state.warned = true;
-                        state.error = Some("Are you sure? This file will be overwritten with monitor settings. If it's your main Hyprland/Sway config (like hyprland.conf), you will LOSE all your keybinds, animations, window rules, and other settings! Use a separate file like monitors.conf or output.conf instead. Press Enter again to confirm.".to_string());
+                        state.error = Some(
+                            t("Are you sure? This file will be overwritten with monitor settings. \
+                               If it's your main Hyprland/Sway config (like hyprland.conf), you \
+                               will LOSE all your keybinds, animations, window rules, and other \
+                               settings! Use a separate file like monitors.conf or output.conf \
+                               instead. Press Enter again to confirm.")
+                            .to_string(),
+                        );
                         continue;
                     }
 
                     let expanded = expand_tilde(path).map_err(io::Error::other)?;
                     if !expanded.exists() {
-                        state.error = Some("File does not exist. Please enter a valid path.".to_string());
+                        state.error =
+                            Some(t("File does not exist. Please enter a valid path.").to_string());
                         state.warned = false;
                         continue;
                     }
 
-                    return Ok(Some(Config {
-                        monitor_config_path: expanded,
-                        workspace_count: 10,
-                    }));
+                    return Ok(Some(Config::new(expanded)));
                 }
                 _ => {}
             }
@@ -305,7 +321,7 @@ fn render_title(frame: &mut Frame, area: Rect) {
                 .fg(Color::Cyan)
                 .add_modifier(Modifier::BOLD),
         ),
-        Span::styled("first-time setup", Style::default().fg(Color::DarkGray)),
+        Span::styled(t("first-time setup"), Style::default().fg(Color::DarkGray)),
     ]));
     frame.render_widget(title, area);
 }
@@ -354,9 +370,9 @@ fn render_extraction(frame: &mut Frame, state: &SetupState) {
 
     if extraction.already_consolidated {
         let desc = Paragraph::new(Line::from(Span::styled(
-            format!(
+            tf(
                 "Detected existing {} monitor config at:",
-                state.compositor.label()
+                &[&state.compositor.label()],
             ),
             Style::default().fg(Color::White),
         )));
@@ -371,9 +387,9 @@ fn render_extraction(frame: &mut Frame, state: &SetupState) {
         frame.render_widget(Paragraph::new(""), output_area);
     } else {
         let desc = Paragraph::new(Line::from(Span::styled(
-            format!(
+            tf(
                 "Found {} monitor config line(s) in:",
-                extraction.monitor_count
+                &[&extraction.monitor_count],
             ),
             Style::default().fg(Color::White),
         )));
@@ -392,7 +408,10 @@ fn render_extraction(frame: &mut Frame, state: &SetupState) {
         frame.render_widget(Paragraph::new(file_lines), files_area);
 
         let output = Paragraph::new(Line::from(vec![
-            Span::styled("Consolidate to: ", Style::default().fg(Color::DarkGray)),
+            Span::styled(
+                format!("{} ", t("Consolidate to:")),
+                Style::default().fg(Color::DarkGray),
+            ),
             Span::styled(&extraction.output_path, Style::default().fg(Color::Cyan)),
         ]));
         frame.render_widget(output, output_area);
@@ -407,17 +426,11 @@ fn render_extraction(frame: &mut Frame, state: &SetupState) {
             info_area,
         );
     } else {
-        let mut hints = vec![
-            Span::styled("Enter ", Style::default().fg(Color::Cyan)),
-            Span::styled("confirm  ", Style::default().fg(Color::DarkGray)),
-        ];
-        hints.push(Span::styled("m ", Style::default().fg(Color::Cyan)));
-        hints.push(Span::styled(
-            "manual  ",
-            Style::default().fg(Color::DarkGray),
-        ));
-        hints.push(Span::styled("Esc ", Style::default().fg(Color::Cyan)));
-        hints.push(Span::styled("quit", Style::default().fg(Color::DarkGray)));
+        let mut hints = Vec::new();
+        hint(&mut hints, "Enter", "confirm");
+        hint(&mut hints, "m", "manual");
+        hint(&mut hints, "Esc", "quit");
+        let hints = fit(hints, info_area.width as usize);
         frame.render_widget(Paragraph::new(Line::from(hints)), info_area);
     }
 }
@@ -451,17 +464,21 @@ fn render_manual(frame: &mut Frame, state: &SetupState) {
     render_title(frame, title_area);
 
     let desc = Paragraph::new(Line::from(Span::styled(
-        format!(
+        tf(
             "Enter the path to your {} monitor config file:",
-            state.compositor.label()
+            &[&state.compositor.label()],
         ),
         Style::default().fg(Color::White),
     )));
     frame.render_widget(desc, desc_area);
 
-    let warning = Paragraph::new(Line::from(Span::styled(
-        "WARNING: Don't use your main config file! Use a separate file like monitors.conf",
-        Style::default().fg(Color::Yellow),
+    let warning = Paragraph::new(Line::from(fit(
+        vec![Span::styled(
+            t("WARNING: Don't use your main config file! Use a separate file like \
+               monitors.conf"),
+            Style::default().fg(Color::Yellow),
+        )],
+        warning_area.width as usize,
     )));
     frame.render_widget(warning, warning_area);
 
@@ -482,7 +499,7 @@ fn render_manual(frame: &mut Frame, state: &SetupState) {
         .borders(Borders::ALL)
         .border_type(BorderType::Rounded)
         .border_style(Style::default().fg(Color::Blue))
-        .title(" Path ");
+        .title(format!(" {} ", t("Path")));
 
     frame.render_widget(Paragraph::new(input_line).block(input_block), input_area);
 
@@ -495,14 +512,10 @@ fn render_manual(frame: &mut Frame, state: &SetupState) {
             info_area,
         );
     } else {
-        frame.render_widget(
-            Paragraph::new(Line::from(vec![
-                Span::styled("Enter ", Style::default().fg(Color::Cyan)),
-                Span::styled("confirm  ", Style::default().fg(Color::DarkGray)),
-                Span::styled("Esc ", Style::default().fg(Color::Cyan)),
-                Span::styled("quit", Style::default().fg(Color::DarkGray)),
-            ])),
-            info_area,
-        );
+        let mut hints = Vec::new();
+        hint(&mut hints, "Enter", "confirm");
+        hint(&mut hints, "Esc", "quit");
+        let hints = fit(hints, info_area.width as usize);
+        frame.render_widget(Paragraph::new(Line::from(hints)), info_area);
     }
 }
@@ -1,31 +1,159 @@
 use std::{
-    collections::HashMap,
-    path::PathBuf,
-    sync::mpsc::{SendError, SyncSender},
-    time::Instant,
+    cell::RefCell,
+    collections::{HashMap, HashSet, VecDeque},
+    fmt, fs, io,
+    path::{Path, PathBuf},
+    sync::mpsc::SendError,
+    time::{Duration, Instant, SystemTime},
 };
 
 use ratatui::widgets::ListState;
-use wlx_monitors::{WlMonitor, WlMonitorAction};
+use serde::{Deserialize, Serialize};
+use wlx_monitors::{
+    ActionKind, WlMonitor, WlMonitorAction, WlMonitorEvent, WlMonitorMode,
+    WlTransform,
+};
 
 use crate::{
+    adjacency::{self, Gap},
+    apply_plan::{self, Staged, Step},
+    aspect,
+    bandwidth::Link,
+    build_info::BUILD,
     compositor::{
         self,
-        format::{reload, save_monitor_config},
-        position::get_position,
-        workspace_config::{WorkspaceRule, parse_workspace_config},
+        check::{ConfigStatus, check_config_content},
+        diff::{self, MonitorChange},
+        extraction::{self, ConfigLine},
+        format::{
+            apply_command, backup_path, live_commands,
+            render_monitor_config, transform_from_sway, transform_to_sway,
+        },
+        ipc,
+        modeline::parse_mode,
+        position::config_position,
+        settings,
+        workspace_config::{WorkspaceRule, parse_workspaces},
+    },
+    config_fields::{self, Field},
+    constants::{
+        FAILURE_FLASH_MS, FEEDBACK_WINDOW_MS, FLAP_WINDOW_SECS, GHOST_GRACE_MS,
+        GUARD_REVERT_SECS, MAP_FILL, MAX_MESSAGES, MAX_PUSH_CASCADE, MAX_SCALE,
+        MIN_SCALE, MODE_REFRESH_TOLERANCE, POWER_REFRESH_MS,
+        RENAME_WINDOW_SECS, REPEAT_WINDOW_MS, RISKY_SCALE_JUMP,
+        TRANSFORM_PREVIEW_SECS, TRANSFORMS, WORKSPACE_LEAVING_MS,
+    },
+    dump::{self, Dump, DumpMonitor},
+    edid::{PhysicalSize, read_link_hints, read_physical_size},
+    effects::{DryRun, Effects, describe},
+    history::{self, Snapshot},
+    i18n::{t, tf, tn},
+    identity::{self, Identity},
+    geometry::{
+        Rect, bounding_box, compose_transform, push_clear, reflow_offset,
+        rotate_rect, shift_to_origin, snap_ahead,
+    },
+    layout::{self, Layout, LayoutMonitor, Snap},
+    night_light::{self, NightLight},
+    parked::{self, Parked},
+    reconcile::{self, Divergence},
+    recovery, refresh,
+    scale::{self, ScaleLint},
+    session::SessionLog,
+    timeline::{Timeline, Transition},
+    ui_state::{self, UiState},
+    undo::{self, UndoStack},
+    utils::{
+        self, connector_order, effective_dimensions, monitor_resolution,
+        transform_label,
+    },
+    xwlm_config::{
+        self, Config, ConfigError, CustomMode, MonitorDefaults,
+        NewMonitorAction,
     },
-    constants::{REPEAT_WINDOW_MS, TRANSFORMS},
-    utils::effective_dimensions,
 };
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Panel {
     Monitor,
     Mode,
     Workspace,
     Scale,
     Transform,
+    Options,
+}
+
+impl Panel {
+    /// The panel whose setting a failed action was changing.
+    fn of_action(action: &ActionKind) -> Self {
+        match action {
+            ActionKind::SwitchMode => Panel::Mode,
+            ActionKind::SetScale => Panel::Scale,
+            ActionKind::SetTransform => Panel::Transform,
+            ActionKind::Toggle
+            | ActionKind::SetPosition
+            | ActionKind::ConfigApply => Panel::Monitor,
+        }
+    }
+
+    /// The panel called `name`, ignoring case, as `--panel` takes it.
+    pub fn from_name(name: &str) -> Option<Self> {
+        [
+            Self::Monitor,
+            Self::Mode,
+            Self::Workspace,
+            Self::Scale,
+            Self::Transform,
+            Self::Options,
+        ]
+        .into_iter()
+        .find(|panel| format!("{panel:?}").eq_ignore_ascii_case(name))
+    }
+}
+
+/// The highlighted row of a panel's list. The panel widget keeps where
+/// the list is scrolled to.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Selection(Option<usize>);
+
+impl Selection {
+    pub fn selected(&self) -> Option<usize> {
+        self.0
+    }
+
+    pub fn select(&mut self, row: Option<usize>) {
+        self.0 = row;
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PromptKind {
+    WorkspaceId,
+    CustomMode,
+}
+
+#[derive(Debug)]
+pub struct Prompt {
+    pub kind: PromptKind,
+    pub input: String,
+}
+
+impl Prompt {
+    pub fn label(&self) -> &'static str {
+        match self.kind {
+            PromptKind::WorkspaceId => t("Workspace id"),
+            PromptKind::CustomMode => t("Custom mode (WxH@Hz)"),
+        }
+    }
+
+    fn accepts(&self, c: char) -> bool {
+        match self.kind {
+            PromptKind::WorkspaceId => c.is_ascii_digit(),
+            PromptKind::CustomMode => {
+                c.is_ascii_digit() || c == 'x' || c == '@'
+            }
+        }
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -36,12 +164,366 @@ pub enum PositionDirection {
     Down,
 }
 
-#[derive(Clone, Debug)]
+/// The panels the first-launch tour shows, in the order Tab visits them.
+pub const TOUR: [Panel; 6] = [
+    Panel::Monitor,
+    Panel::Mode,
+    Panel::Workspace,
+    Panel::Scale,
+    Panel::Transform,
+    Panel::Options,
+];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ModeSpec {
+    pub width: i32,
+    pub height: i32,
+    pub refresh_rate: i32,
+}
+
+impl ModeSpec {
+    pub fn of(mode: &WlMonitorMode) -> Self {
+        Self {
+            width: mode.resolution.width,
+            height: mode.resolution.height,
+            refresh_rate: mode.refresh_rate,
+        }
+    }
+
+    fn current(monitor: &WlMonitor) -> Option<Self> {
+        monitor.modes.iter().find(|m| m.is_current).map(Self::of)
+    }
+}
+
+impl fmt::Display for ModeSpec {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}x{}@{}Hz", self.width, self.height, self.refresh_rate)
+    }
+}
+
+/// Where saving is. Writes and reloads run one after the other, and saves
+/// asked for before the compositor has settled wait and are merged.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum SavePhase {
+    #[default]
+    Idle,
+    Writing,
+    /// Until the instant, counted from when the reload finished.
+    Cooldown(Instant),
+}
+
+/// Whether what the UI shows is in the monitor config and loaded by the
+/// compositor, as the footer reports it.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub enum SaveState {
+    #[default]
+    Clean,
+    /// Changed since the last save.
+    Dirty,
+    /// Written, but the compositor refused to read it.
+    ReloadFailed { reason: String },
+}
+
+/// How [`resolve_mode`] got to the mode it picked.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ModeMatch {
+    Exact,
+    /// Same resolution, refresh within [`MODE_REFRESH_TOLERANCE`].
+    NearRefresh,
+    /// The preferred mode at the same resolution, or its fastest.
+    SameResolution,
+    /// The monitor's preferred mode.
+    Preferred,
+}
+
+/// The advertised mode to use for a saved `wanted` one, which may be
+/// missing after a firmware update or be reported a hertz off. `None` when
+/// the monitor advertises no modes.
+pub fn resolve_mode(
+    monitor: &WlMonitor,
+    wanted: ModeSpec,
+) -> Option<(ModeSpec, ModeMatch)> {
+    let modes: Vec<(ModeSpec, bool)> =
+        monitor.modes.iter().map(|m| (ModeSpec::of(m), m.preferred)).collect();
+    if modes.iter().any(|(m, _)| *m == wanted) {
+        return Some((wanted, ModeMatch::Exact));
+    }
+    let same_size: Vec<(ModeSpec, bool)> = modes
+        .iter()
+        .copied()
+        .filter(|(m, _)| (m.width, m.height) == (wanted.width, wanted.height))
+        .collect();
+    let near = same_size
+        .iter()
+        .map(|(m, _)| *m)
+        .filter(|m| {
+            (m.refresh_rate - wanted.refresh_rate).abs()
+                <= MODE_REFRESH_TOLERANCE
+        })
+        .min_by_key(|m| (m.refresh_rate - wanted.refresh_rate).abs());
+    if let Some(mode) = near {
+        return Some((mode, ModeMatch::NearRefresh));
+    }
+    let same = same_size
+        .iter()
+        .find(|(_, preferred)| *preferred)
+        .or_else(|| same_size.iter().max_by_key(|(m, _)| m.refresh_rate));
+    if let Some(&(mode, _)) = same {
+        return Some((mode, ModeMatch::SameResolution));
+    }
+    modes
+        .iter()
+        .find(|(_, preferred)| *preferred)
+        .or(modes.first())
+        .map(|&(mode, _)| (mode, ModeMatch::Preferred))
+}
+
+/// A row in the Modes panel.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ModeEntry {
+    Advertised(usize),
+    Custom { mode: ModeSpec, active: bool },
+    AddCustom,
+}
+
+/// A transform sent with `p` that is reverted at `deadline` unless kept.
+#[derive(Debug)]
+pub struct TransformPreview {
+    pub name: String,
+    pub transform: WlTransform,
+    pub previous: WlTransform,
+    pub deadline: Instant,
+}
+
+/// How to undo a risky change to the monitor the terminal is on.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Revert {
+    Transform(WlTransform),
+    Scale(f64),
+    /// Switch it back on where it was.
+    Enable(i32, i32),
+}
+
+/// A change that may have left the terminal unreadable. It is reverted at
+/// `deadline` unless a key is pressed, so it can be kept or undone blind.
+#[derive(Debug)]
+pub struct GuardedChange {
+    pub name: String,
+    pub revert: Revert,
+    pub deadline: Instant,
+}
+
+/// Where `--monitor` and `--panel` asked the TUI to start, waiting for
+/// the monitor to show up until `deadline`.
+#[derive(Debug)]
+struct StartupFocus {
+    monitor: Option<String>,
+    panel: Option<Panel>,
+    deadline: Instant,
+}
+
+/// A second output reported under the name of a known one, as MST hubs do
+/// for a moment while replugging. It is kept apart so it cannot replace
+/// the real monitor, and dropped after [`GHOST_GRACE_MS`] without modes.
+#[derive(Debug)]
+struct Ghost {
+    monitor: WlMonitor,
+    seen: Instant,
+}
+
+/// A monitor that was removed, with what xwlm knew about it, kept for
+/// [`RENAME_WINDOW_SECS`] in case it comes back on another connector.
+#[derive(Debug)]
+struct Departed {
+    monitor: WlMonitor,
+    at: i64,
+    anchored: bool,
+    touched: bool,
+    /// Ids of the workspaces assigned to it, applied and staged.
+    workspaces: Vec<usize>,
+    pending_workspaces: Vec<usize>,
+    position: Option<(i32, i32)>,
+    transform: Option<WlTransform>,
+    toggle: Option<Option<(i32, i32)>>,
+    options: Vec<(MonitorOption, bool)>,
+    previous_mode: Option<ModeSpec>,
+}
+
+/// The monitor config as last read, good until its file changes.
+#[derive(Debug)]
+struct CachedConfig {
+    path: PathBuf,
+    modified: SystemTime,
+    content: String,
+}
+
+/// The `W` modal: everything staged in the order it goes out, then how
+/// each step went.
+#[derive(Debug)]
+pub struct ApplyAll {
+    pub steps: Vec<Step>,
+    /// Staged changes applied after the steps, e.g. workspace assignments.
+    pub extras: Vec<&'static str>,
+    /// Bars on outputs the steps move or switch off, which may need a
+    /// restart afterwards.
+    pub notes: Vec<String>,
+    /// Each step's line and its error, once applied.
+    pub results: Option<Vec<(String, Option<String>)>>,
+}
+
+/// The `H` modal: saved snapshots, newest first.
+#[derive(Debug)]
+pub struct HistoryBrowser {
+    pub entries: Vec<Snapshot>,
+    pub state: ListState,
+}
+
+impl HistoryBrowser {
+    pub fn selected(&self) -> Option<&Snapshot> {
+        self.state.selected().and_then(|i| self.entries.get(i))
+    }
+}
+
+/// The `c` picker: which monitor gets the selected one's settings.
+#[derive(Debug)]
+pub struct ClonePicker {
+    pub source: String,
+    pub targets: Vec<String>,
+    pub state: ListState,
+    /// Also stage the target right of the source.
+    pub adjacent: bool,
+}
+
+/// The `Enter` editor on a workspace row: the monitors the workspace
+/// goes to, first available first, followed by the ones it could.
+#[derive(Debug)]
+pub struct FallbackEditor {
+    pub row: usize,
+    pub id: usize,
+    pub chosen: Vec<String>,
+    pub others: Vec<String>,
+    pub state: ListState,
+}
+
+impl FallbackEditor {
+    /// The chosen monitors, then the others, as listed.
+    pub fn entries(&self) -> impl Iterator<Item = &String> {
+        self.chosen.iter().chain(&self.others)
+    }
+
+    fn len(&self) -> usize {
+        self.chosen.len() + self.others.len()
+    }
+
+    fn cursor(&self) -> usize {
+        self.state.selected().unwrap_or(0)
+    }
+}
+
+/// The `,` modal: xwlm's own settings, each saved as soon as it changes.
+#[derive(Debug)]
+pub struct SettingsEditor {
+    pub fields: Vec<Field>,
+    pub state: ListState,
+    /// What is typed for the highlighted number or text, while editing it.
+    pub input: Option<String>,
+    /// The field a change was refused for, and why.
+    pub error: Option<(usize, String)>,
+}
+
+impl SettingsEditor {
+    fn cursor(&self) -> usize {
+        self.state.selected().unwrap_or(0)
+    }
+}
+
+/// The `=` view: connected monitors that do not run the way the monitor
+/// config says.
+#[derive(Debug)]
+pub struct Reconciliation {
+    pub rows: Vec<Divergence>,
+    pub state: ListState,
+}
+
+impl Reconciliation {
+    pub fn selected(&self) -> Option<&Divergence> {
+        self.state.selected().and_then(|i| self.rows.get(i))
+    }
+}
+
+/// A destructive step waiting for a yes. Only one is asked at a time.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Confirm {
+    /// Disabling the only enabled monitor.
+    LastMonitor,
+    /// Dropping every unpinned workspace assignment.
+    ClearWorkspaces,
+    /// Restoring a snapshot that switches off the listed monitors.
+    RestoreSnapshot {
+        snapshot: Snapshot,
+        disables: Vec<String>,
+    },
+    /// Quitting before everything is saved and loaded.
+    Quit,
+}
+
+/// A row in the Options panel.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MonitorOption {
+    Power,
+    Anchored,
+    /// Left out of the night light.
+    NightLight,
+    /// Written to the monitor config and sent changes without asking.
+    Managed,
+}
+
+impl MonitorOption {
+    pub fn label(self) -> &'static str {
+        match self {
+            MonitorOption::Power => t("Power"),
+            MonitorOption::Anchored => t("Anchored"),
+            MonitorOption::NightLight => t("Night light"),
+            MonitorOption::Managed => t("Managed"),
+        }
+    }
+}
+
+/// Scale, Modes and Transform panel values the user changed since they
+/// were last applied or synced, which a Changed event must not overwrite.
+#[derive(Debug, Default)]
+struct PanelEdits {
+    mode: bool,
+    transform: bool,
+}
+
+/// Answer to the prompt about monitors the user did not touch.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum UntouchedChoice {
+    Include,
+    Skip,
+    Always,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct WorkspaceAssignment {
     pub id: usize,
-    pub monitor_idx: Option<usize>,
+    pub monitor: Option<String>,
+    /// A monitor the config assigns this workspace to that is not
+    /// connected. It is written back as is and claimed when the monitor
+    /// shows up; only assigning the row to something else drops it.
+    #[serde(default)]
+    pub reserved: Option<String>,
+    /// Monitors to use, in order, when the assigned one is not connected.
+    #[serde(default)]
+    pub fallbacks: Vec<String>,
     pub is_default: bool,
     pub is_persistent: bool,
+    pub is_pinned: bool,
+    /// The monitor config line the assignment was read from, or last
+    /// written to; `None` for one made this session.
+    #[serde(default)]
+    pub line: Option<usize>,
 }
 
 #[derive(Debug)]
@@ -50,845 +532,9007 @@ pub struct App {
     pub selected_monitor: usize,
     pub panel: Panel,
     pub compositor: compositor::Compositor,
-    pub wlx_action_handler: SyncSender<WlMonitorAction>,
     pub workspace_assignments: Vec<WorkspaceAssignment>,
     pub comp_monitor_config_path: PathBuf,
-    pub needs_save: bool,
+    pub config: Config,
+    pub save_state: SaveState,
+    /// What `save_state` was before the unsaved changes, for a failed
+    /// action to go back to.
+    before_dirty: SaveState,
 
-    pub pending_positions: HashMap<usize, (i32, i32)>,
+    pub pending_positions: HashMap<String, (i32, i32)>,
+    pub pending_transforms: HashMap<String, WlTransform>,
+    /// Modes and scales staged for monitors other than the one the Modes
+    /// and Scale panels edit, such as by `c`.
+    pub pending_modes: HashMap<String, ModeSpec>,
+    pub pending_scales: HashMap<String, f64>,
     pub pending_workspaces: HashMap<usize, WorkspaceAssignment>,
-    pub pending_scale: f64,
+    /// Staged enable/disable toggles, with the position an enabled
+    /// monitor will take.
+    pub pending_toggles: HashMap<String, Option<(i32, i32)>>,
+    /// Options panel values staged per monitor until Enter.
+    pub pending_options: HashMap<(String, MonitorOption), bool>,
+    /// Scale panel values per monitor until Enter, so switching monitors
+    /// keeps them.
+    pub scale_edits: HashMap<String, f64>,
     pub map_zoom: f64,
-    pub transform_state: ListState,
-    pub mode_state: ListState,
-    pub workspace_state: ListState,
-    pub pending_last_toggle_monitor: bool,
+    /// `f` in the Modes panel: only list modes of the native aspect.
+    pub hide_other_aspects: bool,
+    /// A workspace just assigned away from the followed monitor, still
+    /// listed, highlighted, until the instant.
+    pub workspace_leaving: Option<(usize, Instant)>,
+    /// The panel whose border flashes red after a failure, and until when.
+    pub failure_flash: Option<(Panel, Instant)>,
+    bell: bool,
+    pub transform_selection: Selection,
+    pub mode_selection: Selection,
+    pub options_selection: Selection,
+    pub workspace_selection: Selection,
+    pub confirm: Option<Confirm>,
     pub error_message: Option<String>,
+    pub status_message: Option<String>,
+    pub prompt: Option<Prompt>,
+    pub power: HashMap<String, bool>,
+    /// When [`App::power`] was last read from the compositor.
+    power_read: Option<Instant>,
+    /// The running wlsunset, looked for at startup by the TUI and watch.
+    pub night_light: Option<NightLight>,
+    pub history: Option<HistoryBrowser>,
+    pub clone_picker: Option<ClonePicker>,
+    pub fallback_editor: Option<FallbackEditor>,
+    pub settings: Option<SettingsEditor>,
+    pub reconcile: Option<Reconciliation>,
+    pub timeline: Timeline,
+    /// The `T` modal's selection, newest entry first.
+    pub timeline_view: Option<ListState>,
+    /// Changes the next save would write for untouched monitors, waiting
+    /// for an [`UntouchedChoice`].
+    pub untouched_changes: Option<Vec<MonitorChange>>,
+    /// Problem with the monitor config found at startup, until dismissed.
+    pub config_check: Option<ConfigStatus>,
+    /// Monitor rules outside the managed file, found at startup and after
+    /// each save.
+    pub stray_rules: Vec<ConfigLine>,
+    /// Whether the stray rules warning is up; it comes back when the rules
+    /// found change.
+    pub show_stray_rules: bool,
+    /// A monitor that just connected, waiting for a placement because
+    /// `on_new_monitor` is ask.
+    pub new_monitor: Option<String>,
+    /// Whether the `L` modal listing what a dry run skipped is up.
+    pub transcript_open: bool,
+    pub transform_preview: Option<TransformPreview>,
+    /// The output the terminal running the TUI is on, looked up at start.
+    pub terminal_output: Option<String>,
+    pub guarded: Option<GuardedChange>,
+    pub apply_all: Option<ApplyAll>,
+    /// Set by `xwlm replay`: the monitors came from a dump and nothing
+    /// can reach a compositor.
+    pub replaying: bool,
+    /// Set by `xwlm --observe`: nothing is staged, sent or written, so the
+    /// screen always shows the compositor as it is.
+    pub observing: bool,
+    /// The step of the first-launch tour on screen, an index into
+    /// [`TOUR`].
+    pub tour: Option<usize>,
+    /// The panel the tour hands back to: the one it started on, or the
+    /// one `--panel` asked for.
+    tour_return: Panel,
 
     last_move_time: Instant,
     move_repeat_count: u32,
     last_move_direction: Option<PositionDirection>,
     initial_workspaces: Option<Vec<WorkspaceRule>>,
+    monitor_config: RefCell<Option<CachedConfig>>,
+    selected_name: Option<String>,
+    previous_modes: HashMap<String, ModeSpec>,
+    layout: Option<Layout>,
+    layout_needs_migration: bool,
+    physical_sizes: HashMap<String, Option<PhysicalSize>>,
+    links: HashMap<String, Link>,
+    /// A mode over its link's bandwidth that Enter was pressed on once;
+    /// pressing it again applies it.
+    confirmed_over_link: Option<(String, ModeSpec)>,
+    /// Outputs something was capturing when switching them off or to
+    /// another mode was asked for once; asking again goes ahead.
+    confirmed_capture: Option<Vec<String>>,
+    /// Unmanaged outputs a change was asked for once; asking again goes
+    /// ahead.
+    confirmed_unmanaged: Option<Vec<String>>,
+    available_modes: HashMap<String, Vec<(i32, i32, i32)>>,
+    touched: HashSet<String>,
+    untouched_choice: Option<UntouchedChoice>,
+    edited: PanelEdits,
+    main_config_path: Option<PathBuf>,
+    effects: Box<dyn Effects>,
+    reported_snaps: Vec<Snap>,
+    parked: Parked,
+    ui_state: UiState,
+    save_phase: SavePhase,
+    save_queued: bool,
+    /// Monitors xwlm changed itself: how many reports each still has to
+    /// send, and until when to wait for them. Saves hold off meanwhile so
+    /// one change is written once, with all of it.
+    expected: HashMap<String, (usize, Instant)>,
+    startup_focus: Option<StartupFocus>,
+    departed: Vec<Departed>,
+    ghosts: Vec<Ghost>,
+    /// Models seen this session; their `defaults` are only applied to the
+    /// first one.
+    seen_models: HashSet<String>,
+    /// When the monitor config was last written this session.
+    last_saved: Option<i64>,
+    /// Recent status and error messages for [`App::debug_dump`].
+    messages: VecDeque<String>,
+    session: SessionLog,
+    /// Monitor states before each applied change, for `Ctrl+Z`.
+    undo: UndoStack,
+    /// The monitors before the change being applied, pushed onto `undo`
+    /// once the change actually sends something.
+    undo_from: Option<Vec<WlMonitor>>,
+    /// An undo (false) or redo (true) waiting on the last-monitor
+    /// confirmation.
+    replay_pending: Option<bool>,
 }
 
 impl App {
-    pub fn new(
-        wlx_action_handler: SyncSender<WlMonitorAction>,
-        comp_monitor_config_path: PathBuf,
-        comp_workspace_count: usize,
+    pub fn new(effects: Box<dyn Effects>, config: Config) -> Self {
+        Self::with_compositor(effects, config, compositor::detect())
+    }
+
+    /// [`App::new`] for `comp` instead of the running compositor.
+    pub fn with_compositor(
+        effects: Box<dyn Effects>,
+        config: Config,
+        comp: compositor::Compositor,
     ) -> Self {
-        let comp = compositor::detect();
-        let initial_workspaces = Some(parse_workspace_config(comp, &comp_monitor_config_path));
+        let comp_monitor_config_path = config.monitor_config_path.clone();
+
+        let mut error_message = None;
+        let mut layout = None;
+        let mut layout_needs_migration = false;
+        if config.canonical_layout {
+            match layout::load_layout() {
+                Ok(Some(loaded)) => layout = Some(loaded),
+                Ok(None) => layout_needs_migration = true,
+                Err(e) => {
+                    error_message =
+                        Some(tf("Failed to load layout: {}", &[&e]));
+                }
+            }
+        }
+        let parked = match parked::parked_path().map(|p| effects.read(&p)) {
+            Some(Ok(content)) => parked::parse(&content).unwrap_or_else(|e| {
+                error_message =
+                    Some(tf("Failed to load parked monitors: {}", &[&e]));
+                Parked::default()
+            }),
+            _ => Parked::default(),
+        };
+        let ui_state = match ui_state::ui_state_path().map(|p| effects.read(&p))
+        {
+            Some(Ok(content)) => ui_state::parse(&content).unwrap_or_default(),
+            _ => UiState::default(),
+        };
+        let modified = effects.modified(&comp_monitor_config_path);
+        let existing = effects.read(&comp_monitor_config_path);
+        let cached_config = match (modified, &existing) {
+            (Some(modified), Ok(content)) => Some(CachedConfig {
+                path: comp_monitor_config_path.clone(),
+                modified,
+                content: content.clone(),
+            }),
+            _ => None,
+        };
+        let config_check = check_config_content(comp, existing.as_deref());
+        let parsed_workspaces = match (&layout, &existing) {
+            (Some(layout), _) => layout.workspaces.clone(),
+            (None, Ok(content)) if config_check.is_ok() => {
+                parse_workspaces(comp, content)
+            }
+            (None, _) => Vec::new(),
+        };
+        let config_check = (!config_check.is_ok()).then_some(config_check);
+        let parsed_ids: Vec<usize> = parsed_workspaces.iter().map(|r| r.id).collect();
+        let initial_workspaces = Some(parsed_workspaces);
 
-        let workspace_assignments = (1..=comp_workspace_count)
+        let workspace_assignments = seed_workspace_ids(&parsed_ids, config.workspace_count)
+            .into_iter()
             .map(|id| WorkspaceAssignment {
                 id,
-                monitor_idx: None,
+                monitor: None,
+                reserved: None,
+                fallbacks: Vec::new(),
                 is_default: false,
                 is_persistent: false,
+                is_pinned: config.pinned_workspaces.contains(&id),
+                line: None,
             })
             .collect();
 
-        Self {
+        let mut app = Self {
             monitors: Vec::new(),
             selected_monitor: 0,
             panel: Panel::Monitor,
             compositor: comp,
-            wlx_action_handler,
-            needs_save: false,
+            save_state: SaveState::Clean,
+            before_dirty: SaveState::Clean,
             pending_positions: HashMap::new(),
+            pending_transforms: HashMap::new(),
+            pending_modes: HashMap::new(),
+            pending_scales: HashMap::new(),
             pending_workspaces: HashMap::new(),
+            pending_toggles: HashMap::new(),
+            pending_options: HashMap::new(),
             workspace_assignments,
-            workspace_state: ListState::default().with_selected(Some(0)),
+            workspace_selection: Selection(Some(0)),
             map_zoom: 1.0,
-            pending_scale: 1.0,
-            transform_state: ListState::default().with_selected(Some(0)),
-            mode_state: ListState::default().with_selected(Some(0)),
-            pending_last_toggle_monitor: false,
-            error_message: None,
+            hide_other_aspects: false,
+            workspace_leaving: None,
+            failure_flash: None,
+            bell: false,
+            scale_edits: HashMap::new(),
+            transform_selection: Selection(Some(0)),
+            mode_selection: Selection(Some(0)),
+            options_selection: Selection(Some(0)),
+            confirm: None,
+            error_message,
+            status_message: None,
+            prompt: None,
+            power: HashMap::new(),
+            power_read: None,
+            night_light: None,
+            available_modes: HashMap::new(),
+            history: None,
+            clone_picker: None,
+            fallback_editor: None,
+            settings: None,
+            reconcile: None,
+            timeline: Timeline::default(),
+            timeline_view: None,
+            untouched_changes: None,
+            config_check,
+            stray_rules: Vec::new(),
+            show_stray_rules: false,
+            new_monitor: None,
+            transcript_open: false,
+            transform_preview: None,
+            apply_all: None,
+            replaying: false,
+            observing: false,
+            tour: None,
+            tour_return: Panel::Monitor,
             comp_monitor_config_path,
+            config,
             last_move_time: Instant::now(),
             last_move_direction: None,
             move_repeat_count: 0,
             initial_workspaces,
-        }
+            monitor_config: RefCell::new(cached_config),
+            selected_name: None,
+            previous_modes: HashMap::new(),
+            layout,
+            layout_needs_migration,
+            physical_sizes: HashMap::new(),
+            links: HashMap::new(),
+            confirmed_over_link: None,
+            confirmed_capture: None,
+            confirmed_unmanaged: None,
+            touched: HashSet::new(),
+            untouched_choice: None,
+            edited: PanelEdits::default(),
+            main_config_path: extraction::main_config_path(comp),
+            effects,
+            reported_snaps: Vec::new(),
+            parked,
+            ui_state,
+            save_phase: SavePhase::Idle,
+            save_queued: false,
+            expected: HashMap::new(),
+            startup_focus: None,
+            departed: Vec::new(),
+            ghosts: Vec::new(),
+            seen_models: HashSet::new(),
+            terminal_output: None,
+            guarded: None,
+            last_saved: None,
+            messages: VecDeque::new(),
+            session: SessionLog::default(),
+            undo: UndoStack::default(),
+            undo_from: None,
+            replay_pending: None,
+        };
+        app.scan_stray_rules();
+        app
     }
 
     pub fn set_monitors(&mut self, monitors: Vec<WlMonitor>) {
-        self.monitors = monitors;
+        // One monitor per name, the one with the most modes.
+        let mut kept: Vec<WlMonitor> = Vec::new();
+        for monitor in monitors {
+            match kept.iter_mut().find(|m| m.name == monitor.name) {
+                Some(m) if monitor.modes.len() > m.modes.len() => {
+                    let ghost = std::mem::replace(m, monitor);
+                    self.haunt(ghost);
+                }
+                Some(_) => self.haunt(monitor),
+                None => kept.push(monitor),
+            }
+        }
+        kept.sort_by(|a, b| connector_order(&a.name, &b.name));
+        self.monitors = kept;
+        for m in &self.monitors {
+            self.seen_models.insert(identity::model_key(m));
+            self.physical_sizes
+                .entry(m.name.clone())
+                .or_insert_with(|| read_physical_size(&m.name));
+            self.links.entry(m.name.clone()).or_insert_with(|| {
+                Link::infer(&m.name, read_link_hints(&m.name))
+            });
+        }
         if !self.monitors.is_empty() {
-            self.selected_monitor = 0;
-            self.mode_state.select(Some(0));
+            self.refresh_selection();
+            self.mode_selection.select(Some(0));
             self.sync_panel_state();
         }
+        self.power_read = None;
+        self.refresh_power();
+        self.available_modes.clear();
+        self.refresh_available_modes();
         self.resolve_initial_workspaces();
         self.validate_workspace_assignments();
+        self.claim_reserved_workspaces();
+        self.lint_loaded_scales();
+        self.migrate_layout();
+        self.session.see(&self.monitors);
+        self.session.see_workspaces(&self.workspace_assignments);
     }
 
-    pub fn update_monitor(&mut self, monitor: WlMonitor) {
-        if let Some(existing_monitor) = self.monitors.iter_mut().find(|m| m.name == monitor.name) {
-            *existing_monitor = monitor;
+    pub fn update_monitor(&mut self, mut monitor: WlMonitor) {
+        let existing = self.monitors.iter().position(|m| m.name == monitor.name);
+        if let Some(idx) = existing {
+            let existing_monitor = &self.monitors[idx];
+            // An update without modes never takes away the ones known.
+            if monitor.modes.is_empty() {
+                monitor.modes = existing_monitor.modes.clone();
+            }
+            let before = ModeSpec::current(existing_monitor);
+            if let (Some(before), Some(after)) =
+                (before, ModeSpec::current(&monitor))
+                && before != after
+            {
+                self.previous_modes.insert(monitor.name.clone(), before);
+            }
+
+            let resized = (existing_monitor.enabled && monitor.enabled)
+                .then(|| {
+                    let (x, y) = self.display_position(idx);
+                    let (bw, bh) = effective_dimensions(existing_monitor);
+                    let (aw, ah) = effective_dimensions(&monitor);
+                    (Rect::new(x, y, bw, bh), Rect::new(x, y, aw, ah))
+                })
+                .filter(|(before, after)| before != after);
+
+            let previous = std::mem::replace(&mut self.monitors[idx], monitor);
+            if idx == self.selected_monitor {
+                self.merge_panel_state(&previous);
+            }
+            if let Some((before, after)) = resized {
+                self.reflow_neighbours(idx, before, after);
+            }
         } else {
-            self.monitors.push(monitor);
-            self.sanitize_selection();
+            self.physical_sizes
+                .entry(monitor.name.clone())
+                .or_insert_with(|| read_physical_size(&monitor.name));
+            self.links.entry(monitor.name.clone()).or_insert_with(|| {
+                Link::infer(&monitor.name, read_link_hints(&monitor.name))
+            });
+            self.session.see(std::slice::from_ref(&monitor));
+            let idx = self.monitors.partition_point(|m| {
+                connector_order(&m.name, &monitor.name).is_lt()
+            });
+            self.monitors.insert(idx, monitor);
+            self.refresh_selection();
         };
+        self.refresh_power();
+        self.refresh_available_modes();
     }
 
-    pub fn remove_monitor(&mut self, name: &str) {
-        let removed_idx = self.monitors.iter().position(|m| m.name == name);
-        self.monitors.retain(|m| m.name != name);
-
-        if let Some(idx) = removed_idx {
-            self.pending_positions.remove(&idx);
-            for key in self.pending_positions.keys().copied().collect::<Vec<_>>() {
-                if key > idx
-                    && let Some(pos) = self.pending_positions.remove(&key)
+    /// Applies an event from the compositor. Shared by the TUI and the
+    /// plain mode so both track monitors the same way.
+    pub fn handle_event(&mut self, event: WlMonitorEvent) {
+        let now = Instant::now();
+        self.drop_stale_ghosts(now);
+        match event {
+            WlMonitorEvent::InitialState(monitors) => {
+                self.set_monitors(monitors);
+                self.fit_map();
+            }
+            WlMonitorEvent::Changed(monitor) => {
+                if self.is_ghost(&monitor) {
+                    self.haunt(*monitor);
+                    return;
+                }
+                self.ghosts.retain(|g| !same_output(&g.monitor, &monitor));
+                self.record_transition(&monitor);
+                let name = monitor.name.clone();
+                let echo = self.is_echo(&name, now);
+                let clean = !self.needs_save();
+                let awaited = self.awaited();
+                let known = self.monitors.iter().any(|m| m.name == name);
+                let was_enabled =
+                    self.monitors.iter().any(|m| m.name == name && m.enabled);
+                let enabled = monitor.enabled;
+                let former = if known {
+                    None
+                } else {
+                    self.take_departed(&monitor)
+                };
+                self.update_monitor(*monitor);
+                if !known {
+                    self.claim_reserved_workspaces();
+                }
+                if self.observing {
+                    // Nothing is restored, placed or defaulted.
+                } else if let Some(former) = former {
+                    self.restore_renamed(&name, former);
+                } else if !known && self.is_managed(&name) {
+                    self.new_monitor_connected(&name);
+                    self.apply_monitor_defaults(&name);
+                }
+                if !known {
+                    self.fit_map();
+                }
+                // A monitor that comes up needs adding to wlsunset's list.
+                if enabled
+                    && !was_enabled
+                    && !self.observing
+                    && !self.config.night_light_excluded.is_empty()
                 {
-                    self.pending_positions.insert(key - 1, pos);
+                    self.restart_night_light();
+                }
+                // The compositor only told back what xwlm did, which is
+                // saved already or about to be.
+                if echo
+                    && clean
+                    && self.needs_save()
+                    && self.awaited() <= awaited
+                {
+                    self.save_state = std::mem::take(&mut self.before_dirty);
                 }
             }
-
-            if self.selected_monitor >= self.monitors.len() {
-                self.selected_monitor = self.monitors.len().saturating_sub(1);
+            WlMonitorEvent::Removed { id, name } => {
+                // A ghost leaving takes the real monitor with it otherwise.
+                let ghosts = self.ghosts.len();
+                self.ghosts.retain(|g| {
+                    id.is_null() || g.monitor.head_id != id
+                });
+                if self.ghosts.len() < ghosts {
+                    return;
+                }
+                let now = history::now();
+                self.timeline.record(now, &name, Transition::Disconnected);
+                self.depart(&name, now);
+                self.remove_monitor(&name);
+                self.fit_map();
+            }
+            WlMonitorEvent::ActionFailed { action, reason } => {
+                // The change never happened, so neither did what it left
+                // to save.
+                if self.save_state == SaveState::Dirty {
+                    self.save_state = std::mem::take(&mut self.before_dirty);
+                }
+                self.expected.clear();
+                self.set_error(tf("Action failed: {}", &[&reason]));
+                self.alert_failure(Panel::of_action(&action));
             }
-            self.sync_panel_state();
-        }
-    }
-
-    fn sanitize_selection(&mut self) {
-        if self.monitors.is_empty() {
-            self.selected_monitor = 0;
-        } else if self.selected_monitor >= self.monitors.len() {
-            self.selected_monitor = self.monitors.len() - 1;
         }
     }
 
-    pub fn selected_monitor(&self) -> Option<&WlMonitor> {
-        self.monitors.get(self.selected_monitor)
+    /// Keeps what xwlm knows about `name` before it is removed.
+    fn depart(&mut self, name: &str, now: i64) {
+        let Some(monitor) = self.monitors.iter().find(|m| m.name == name)
+        else {
+            return;
+        };
+        let monitor = monitor.clone();
+        let assigned = |ws: &WorkspaceAssignment| {
+            ws.monitor.as_deref() == Some(name)
+        };
+        let departed = Departed {
+            at: now,
+            anchored: self.is_anchored(name),
+            touched: self.touched.contains(name),
+            workspaces: self
+                .workspace_assignments
+                .iter()
+                .filter(|ws| assigned(ws))
+                .map(|ws| ws.id)
+                .collect(),
+            pending_workspaces: self
+                .pending_workspaces
+                .iter()
+                .filter(|(_, ws)| assigned(ws))
+                .map(|(row, _)| *row)
+                .collect(),
+            position: self.pending_positions.get(name).copied(),
+            transform: self.pending_transforms.get(name).copied(),
+            toggle: self.pending_toggles.get(name).copied(),
+            options: self
+                .pending_options
+                .iter()
+                .filter(|((n, _), _)| n == name)
+                .map(|((_, option), value)| (*option, *value))
+                .collect(),
+            previous_mode: self.previous_modes.get(name).copied(),
+            monitor,
+        };
+        let identity = Identity::from(&departed.monitor);
+        self.departed.retain(|d| {
+            now - d.at <= RENAME_WINDOW_SECS
+                && !Identity::from(&d.monitor).same_display(&identity)
+        });
+        self.departed.push(departed);
     }
 
-    pub fn display_position(&self, idx: usize) -> (i32, i32) {
-        if let Some(&pos) = self.pending_positions.get(&idx) {
-            return pos;
-        }
-        self.monitors
-            .get(idx)
-            .map(|m| (m.position.x, m.position.y))
-            .unwrap_or((0, 0))
+    /// The recently removed monitor `monitor` is, when it came back on
+    /// another connector.
+    fn take_departed(&mut self, monitor: &WlMonitor) -> Option<Departed> {
+        let now = history::now();
+        self.departed.retain(|d| now - d.at <= RENAME_WINDOW_SECS);
+        let identity = Identity::from(monitor);
+        let idx = self
+            .departed
+            .iter()
+            .position(|d| Identity::from(&d.monitor).same_display(&identity))?;
+        let former = self.departed.remove(idx);
+        (former.monitor.name != monitor.name).then_some(former)
     }
 
-    pub fn has_pending_positions(&self) -> bool {
-        !self.pending_positions.is_empty()
-    }
+    /// Carries everything known about `former` over to its new connector
+    /// and sets the monitor up the way it was.
+    fn restore_renamed(&mut self, name: &str, former: Departed) {
+        let old = former.monitor.name.clone();
+        let old_is_live = self.monitors.iter().any(|m| m.name == old);
 
-    pub fn set_error(&mut self, msg: impl Into<String>) {
-        self.error_message = Some(msg.into());
-    }
+        for ws in &mut self.workspace_assignments {
+            if former.workspaces.contains(&ws.id)
+                && ws.monitor.as_deref() == Some(old.as_str())
+            {
+                ws.monitor = Some(name.to_string());
+            }
+        }
+        for row in &former.pending_workspaces {
+            if let Some(ws) = self.pending_workspaces.get_mut(row) {
+                ws.monitor = Some(name.to_string());
+            }
+        }
+        if former.anchored != self.is_anchored(name) {
+            self.set_anchored(name, former.anchored);
+            if former.anchored && !old_is_live {
+                self.set_anchored(&old, false);
+            }
+            self.save_anchors();
+        }
+        if former.touched {
+            self.touched.insert(name.to_string());
+        }
+        if let Some(position) = former.position {
+            self.pending_positions.insert(name.to_string(), position);
+        }
+        if let Some(transform) = former.transform {
+            self.pending_transforms.insert(name.to_string(), transform);
+        }
+        if let Some(toggle) = former.toggle {
+            self.pending_toggles.insert(name.to_string(), toggle);
+        }
+        for (option, value) in former.options {
+            self.pending_options.insert((name.to_string(), option), value);
+        }
+        if let Some(mode) = former.previous_mode {
+            self.previous_modes.insert(name.to_string(), mode);
+        }
 
-    pub fn clear_error(&mut self) {
-        self.error_message = None;
+        let Some(live) = self.monitors.iter().find(|m| m.name == name) else {
+            return;
+        };
+        let kept = &former.monitor;
+        let rect = |m: &WlMonitor| {
+            let (w, h) = effective_dimensions(m);
+            Rect::new(m.position.x, m.position.y, w, h)
+        };
+        let mode = ModeSpec::current(kept)
+            .and_then(|wanted| resolve_mode(live, wanted))
+            .map(|(mode, _)| mode)
+            .filter(|&mode| Some(mode) != ModeSpec::current(live));
+        let staged = Staged {
+            name: name.to_string(),
+            now: live.enabled.then(|| rect(live)),
+            after: kept.enabled.then(|| rect(kept)),
+            mode: mode.filter(|_| kept.enabled),
+            scale: Some(kept.scale).filter(|scale| {
+                kept.enabled && (scale - live.scale).abs() > 0.001
+            }),
+            transform: Some(kept.transform)
+                .filter(|&t| kept.enabled && t != live.transform),
+        };
+        for step in apply_plan::plan(&[staged]) {
+            if let Err(e) = self.run_step(&step) {
+                self.set_error(tf("Failed to restore {}: {}", &[&name, &e]));
+                return;
+            }
+        }
+        self.mark_dirty();
+        self.set_status(tf(
+            "{} recognized as former {} — settings restored",
+            &[&name, &old],
+        ));
     }
 
-    pub fn zoom_in(&mut self) {
-        self.map_zoom = (self.map_zoom + 0.1).min(5.0);
+    /// Follows `on_new_monitor` for a monitor that came up disabled or on
+    /// top of another one.
+    fn new_monitor_connected(&mut self, name: &str) {
+        let Some(monitor) = self.monitors.iter().find(|m| m.name == name)
+        else {
+            return;
+        };
+        let pos = (monitor.position.x, monitor.position.y);
+        let size = effective_dimensions(monitor);
+        if monitor.enabled && !self.position_overlaps(name, pos, size) {
+            return;
+        }
+        match self.config.on_new_monitor {
+            NewMonitorAction::Ignore => {}
+            NewMonitorAction::Ask => self.new_monitor = Some(name.to_string()),
+            action => self.place_new_monitor(name, action),
+        }
     }
 
-    pub fn zoom_out(&mut self) {
-        self.map_zoom = (self.map_zoom - 0.1).max(0.2);
-    }
+    /// Sets up the first monitor of a model this session with the
+    /// `defaults` for that model, except for what the monitor config
+    /// already says about it. They are applied right away when
+    /// `on_new_monitor` enables new monitors, else staged for Enter.
+    fn apply_monitor_defaults(&mut self, name: &str) {
+        let Some(monitor) = self.monitors.iter().find(|m| m.name == name)
+        else {
+            return;
+        };
+        let model = identity::model_key(monitor);
+        if !self.seen_models.insert(model.clone()) {
+            return;
+        }
+        let Some(defaults) = self.config.defaults.get(&model) else {
+            return;
+        };
+        let configured = self
+            .saved_monitors()
+            .ok()
+            .and_then(|saved| saved.into_iter().find(|c| c.name == name));
+        let (set_transform, set_scale) = configured
+            .map(|c| (c.transform.is_some(), c.scale.is_some()))
+            .unwrap_or_default();
+        let transform = defaults
+            .transform
+            .as_deref()
+            .and_then(transform_from_sway)
+            .filter(|&t| !set_transform && t != monitor.transform);
+        let scale = defaults
+            .scale
+            .filter(|s| !set_scale && (s - monitor.scale).abs() > 0.001);
+        if transform.is_none() && scale.is_none() {
+            return;
+        }
 
-    pub fn scale_up(&mut self) {
-        self.pending_scale = (self.pending_scale + 0.01).min(10.0);
+        let apply = matches!(
+            self.config.on_new_monitor,
+            NewMonitorAction::EnableRight | NewMonitorAction::EnableSaved
+        );
+        if !apply {
+            if let Some(scale) = scale {
+                self.pending_scales.insert(name.to_string(), scale);
+            }
+            if let Some(transform) = transform {
+                self.pending_transforms.insert(name.to_string(), transform);
+            }
+            self.set_status(tf(
+                "Staged the defaults for {} on {}; Enter applies them",
+                &[&model, &name],
+            ));
+            return;
+        }
+        let mut actions = Vec::new();
+        if let Some(scale) = scale {
+            actions.push(WlMonitorAction::SetScale {
+                name: name.to_string(),
+                scale,
+            });
+        }
+        if let Some(transform) = transform {
+            actions.push(WlMonitorAction::SetTransform {
+                name: name.to_string(),
+                transform,
+            });
+        }
+        for action in actions {
+            if let Err(e) = self.send_action(action) {
+                self.set_error(tf(
+                    "Failed to apply the defaults to {}: {}",
+                    &[&name, &e],
+                ));
+                return;
+            }
+        }
+        self.touched.insert(name.to_string());
+        self.mark_dirty();
+        self.set_status(tf(
+            "Applied the defaults for {} to {}",
+            &[&model, &name],
+        ));
     }
 
-    pub fn scale_down(&mut self) {
-        self.pending_scale = (self.pending_scale - 0.01).max(0.5);
+    /// Keeps the selected monitor's transform and scale as the `defaults`
+    /// for its model.
+    pub fn remember_defaults(&mut self) {
+        let Some(monitor) = self.monitors.get(self.selected_monitor) else {
+            return;
+        };
+        let model = identity::model_key(monitor);
+        if model.is_empty() {
+            self.set_error(tf(
+                "{} does not report its make and model",
+                &[&monitor.name],
+            ));
+            return;
+        }
+        let (transform, scale) = (monitor.transform, monitor.scale);
+        let scale = (scale * 100.0).round() / 100.0;
+        self.config.defaults.insert(
+            model.clone(),
+            MonitorDefaults {
+                transform: Some(transform_to_sway(transform).to_string()),
+                scale: Some(scale),
+            },
+        );
+        if let Err(e) = self.save_settings() {
+            self.set_error(tf("Failed to save the defaults: {}", &[&e]));
+            return;
+        }
+        self.set_status(tf(
+            "New {} monitors start at {}, scale {}",
+            &[&model, &t(transform_label(transform)), &scale],
+        ));
     }
 
-    fn enabled_count(&self) -> usize {
-        self.monitors.iter().filter(|m| m.enabled).count()
+    /// Answers the prompt for [`App::new_monitor`].
+    pub fn resolve_new_monitor(&mut self, action: NewMonitorAction) {
+        let Some(name) = self.new_monitor.take() else {
+            return;
+        };
+        self.place_new_monitor(&name, action);
     }
 
-    pub fn dismiss_warning(&mut self) {
-        self.pending_last_toggle_monitor = false;
+    fn place_new_monitor(&mut self, name: &str, action: NewMonitorAction) {
+        let Some(monitor) = self.monitors.iter().find(|m| m.name == name)
+        else {
+            return;
+        };
+        let (x, y) = match action {
+            NewMonitorAction::EnableRight => {
+                self.calculate_non_overlapping_position(name)
+            }
+            NewMonitorAction::EnableSaved => {
+                self.toggle_position(name, false).unwrap_or_default()
+            }
+            NewMonitorAction::Ignore | NewMonitorAction::Ask => return,
+        };
+        let action = if monitor.enabled {
+            WlMonitorAction::SetPosition {
+                name: name.to_string(),
+                x,
+                y,
+            }
+        } else {
+            let preferred = monitor
+                .modes
+                .iter()
+                .find(|m| m.preferred)
+                .or_else(|| monitor.modes.first())
+                .map(|m| {
+                    (m.resolution.width, m.resolution.height, m.refresh_rate)
+                });
+            WlMonitorAction::Toggle {
+                name: name.to_string(),
+                mode: preferred,
+                position: Some((x, y)),
+            }
+        };
+        let status = if monitor.enabled {
+            tf("Moved new monitor {} to {},{}", &[&name, &x, &y])
+        } else {
+            tf("Enabled new monitor {} at {},{}", &[&name, &x, &y])
+        };
+        if let Err(e) = self.send_action(action) {
+            self.set_error(tf("Failed to place {}: {}", &[&name, &e]));
+            return;
+        }
+        self.touched.insert(name.to_string());
+        self.mark_dirty();
+        self.set_status(status);
     }
 
-    pub fn toggle_monitor(&mut self) -> Result<(), SendError<WlMonitorAction>> {
-        if self.pending_last_toggle_monitor {
-            self.pending_last_toggle_monitor = false;
-            let Some(monitor) = self.monitors.get(self.selected_monitor) else {
-                return Ok(());
-            };
-            self.perform_toggle(&monitor.name.clone(), monitor.enabled)?;
-            return Ok(());
+    /// Whether `monitor` is another output under the name of a known one:
+    /// one that lacks the modes the known one has, or an earlier ghost.
+    /// Outputs are told apart by Wayland object when both have one, by
+    /// description otherwise.
+    fn is_ghost(&self, monitor: &WlMonitor) -> bool {
+        let Some(known) = self.monitors.iter().find(|m| m.name == monitor.name)
+        else {
+            return false;
+        };
+        !same_output(known, monitor)
+            && (monitor.modes.is_empty() && !known.modes.is_empty()
+                || self.ghosts.iter().any(|g| same_output(&g.monitor, monitor)))
+    }
+
+    /// Keeps `monitor` as a ghost; its grace period runs from when it
+    /// first showed up.
+    fn haunt(&mut self, monitor: WlMonitor) {
+        match self.ghosts.iter_mut().find(|g| same_output(&g.monitor, &monitor))
+        {
+            Some(ghost) => ghost.monitor = monitor,
+            None => self.ghosts.push(Ghost {
+                monitor,
+                seen: Instant::now(),
+            }),
         }
+    }
 
-        let Some(monitor) = self.monitors.get(self.selected_monitor) else {
-            return Ok(());
+    /// Forgets ghosts that have not shown any modes within the grace
+    /// period.
+    fn drop_stale_ghosts(&mut self, now: Instant) {
+        let grace = Duration::from_millis(GHOST_GRACE_MS);
+        self.ghosts.retain(|g| {
+            !g.monitor.modes.is_empty() || now.duration_since(g.seen) < grace
+        });
+    }
+
+    /// Adds a Changed event to the timeline and warns when the monitor
+    /// keeps reconnecting.
+    fn record_transition(&mut self, monitor: &WlMonitor) {
+        let known = self.monitors.iter().find(|m| m.name == monitor.name);
+        let transition = match known {
+            None => Transition::Connected,
+            Some(m) if m.enabled == monitor.enabled => Transition::Changed,
+            Some(_) if monitor.enabled => Transition::Enabled,
+            Some(_) => Transition::Disabled,
         };
+        if transition == Transition::Disabled
+            && let Some(was) = known.cloned()
+        {
+            self.park(&was);
+        }
+        let now = history::now();
+        self.timeline.record(now, &monitor.name, transition);
 
-        if monitor.enabled && self.enabled_count() == 1 {
-            self.pending_last_toggle_monitor = true;
-            return Ok(());
+        let threshold = self.config.flap_threshold;
+        let recent =
+            self.timeline.reconnects(&monitor.name, now - FLAP_WINDOW_SECS);
+        if transition == Transition::Connected
+            && threshold > 0
+            && recent >= threshold
+        {
+            self.set_error(tf(
+                "{} reconnected {} times in ten minutes; check its cable or \
+                 link",
+                &[&monitor.name, &recent],
+            ));
         }
-        self.perform_toggle(&monitor.name.clone(), monitor.enabled)?;
+    }
 
-        Ok(())
+    /// Remembers how `monitor` was set up before it was switched off.
+    fn park(&mut self, monitor: &WlMonitor) {
+        self.parked.remember(monitor);
+        if self.allow("remember parked monitors")
+            && let Err(e) = parked::save(&self.parked)
+        {
+            self.set_error(tf("Failed to remember parked monitors: {}", &[&e]));
+        }
     }
 
-    fn perform_toggle(
-        &mut self,
-        monitor_name: &str,
-        currently_enabled: bool,
-    ) -> Result<(), SendError<WlMonitorAction>> {
-        let will_enable = !currently_enabled;
-        let position = if will_enable {
-            let saved_pos = get_position(
-                self.compositor,
-                &self.comp_monitor_config_path,
-                monitor_name,
-            );
+    /// How `name` was set up when it was last switched off.
+    pub fn parked(&self, name: &str) -> Option<&LayoutMonitor> {
+        let monitor = self.monitors.iter().find(|m| m.name == name)?;
+        self.parked.get(monitor)
+    }
 
-            let (w, h) = self
-                .monitors
-                .iter()
-                .find(|m| m.name == monitor_name)
-                .map(effective_dimensions)
-                .unwrap_or((1920, 1080));
+    pub fn remove_monitor(&mut self, name: &str) {
+        let Some(idx) = self.monitors.iter().position(|m| m.name == name)
+        else {
+            return;
+        };
+        self.monitors.remove(idx);
+        self.available_modes.remove(name);
+        self.pending_positions.remove(name);
+        self.pending_transforms.remove(name);
+        self.pending_modes.remove(name);
+        self.pending_scales.remove(name);
+        self.scale_edits.remove(name);
 
-            if let Some(saved) = saved_pos {
-                let pos = (saved.x, saved.y);
-                if self.position_overlaps(monitor_name, pos, (w, h)) {
-                    Some(self.calculate_closest_non_overlapping_position(monitor_name, pos, (w, h)))
-                } else {
-                    Some(pos)
-                }
-            } else {
-                Some(self.calculate_non_overlapping_position(monitor_name))
-            }
+        if self.selected_name.as_deref() == Some(name) {
+            self.selected_name = None;
+            self.refresh_selection();
+            self.sync_panel_state();
         } else {
-            None
+            self.refresh_selection();
+        }
+    }
+
+    /// The selection is tracked by connector name; `selected_monitor` is
+    /// just its current index. When the named monitor is gone, the
+    /// neighbour at the old index takes over.
+    fn refresh_selection(&mut self) {
+        let found = self
+            .selected_name
+            .as_deref()
+            .and_then(|name| self.monitors.iter().position(|m| m.name == name));
+        self.selected_monitor = match found {
+            Some(idx) => idx,
+            None => self
+                .selected_monitor
+                .min(self.monitors.len().saturating_sub(1)),
         };
+        self.selected_name = self
+            .monitors
+            .get(self.selected_monitor)
+            .map(|m| m.name.clone());
+    }
 
-        self.wlx_action_handler.send(WlMonitorAction::Toggle {
-            name: monitor_name.to_string(),
-            mode: None,
-            position,
-        })?;
+    fn select_index(&mut self, idx: usize) {
+        self.selected_monitor = idx;
+        self.selected_name = self.monitors.get(idx).map(|m| m.name.clone());
+    }
 
-        self.needs_save = true;
+    pub fn selected_monitor(&self) -> Option<&WlMonitor> {
+        self.monitors.get(self.selected_monitor)
+    }
 
-        Ok(())
+    pub fn display_position(&self, idx: usize) -> (i32, i32) {
+        let Some(monitor) = self.monitors.get(idx) else {
+            return (0, 0);
+        };
+        self.pending_positions
+            .get(&monitor.name)
+            .copied()
+            .unwrap_or((monitor.position.x, monitor.position.y))
     }
 
-    fn position_overlaps(&self, exclude_name: &str, pos: (i32, i32), size: (i32, i32)) -> bool {
-        let (x1, y1) = pos;
-        let (w1, h1) = size;
+    pub fn display_transform(&self, idx: usize) -> WlTransform {
+        let Some(monitor) = self.monitors.get(idx) else {
+            return WlTransform::Normal;
+        };
+        self.pending_transforms
+            .get(&monitor.name)
+            .copied()
+            .unwrap_or(monitor.transform)
+    }
 
-        self.monitors.iter().any(|m| {
-            if m.name == exclude_name || !m.enabled {
-                return false;
-            }
-            let (x2, y2) = (m.position.x, m.position.y);
-            let (w2, h2) = effective_dimensions(m);
+    /// The logical size the monitor at `idx` ends up with, staged mode,
+    /// scale and transform included.
+    pub fn display_dimensions(&self, idx: usize) -> (i32, i32) {
+        if idx >= self.monitors.len() {
+            return (0, 0);
+        }
+        let rect = self.logical_rect(idx);
+        (rect.w, rect.h)
+    }
+
+    /// Where the monitor at `idx` ends up in logical pixels, with staged
+    /// positions, modes, scales and transforms.
+    fn logical_rect(&self, idx: usize) -> Rect {
+        let monitor = &self.monitors[idx];
+        let (x, y) = self.display_position(idx);
+        let resolution = self
+            .pending_modes
+            .get(&monitor.name)
+            .map(|mode| (mode.width, mode.height))
+            .unwrap_or_else(|| monitor_resolution(monitor));
+        let scale = self
+            .pending_scales
+            .get(&monitor.name)
+            .copied()
+            .unwrap_or(monitor.scale);
+        let transform = self.display_transform(idx);
+        let (w, h) = scale::logical_extent(resolution, transform, scale);
+        Rect::new(x, y, w, h)
+    }
+
+    pub fn has_pending_positions(&self) -> bool {
+        !self.pending_positions.is_empty()
+    }
+
+    pub fn has_pending_transforms(&self) -> bool {
+        !self.pending_transforms.is_empty()
+    }
+
+    /// Whether modes or scales are staged outside the Modes and Scale
+    /// panels.
+    pub fn has_pending_settings(&self) -> bool {
+        !self.pending_modes.is_empty() || !self.pending_scales.is_empty()
+    }
+
+    pub fn has_pending_toggles(&self) -> bool {
+        !self.pending_toggles.is_empty()
+    }
 
-            x1 < x2 + w2 && x1 + w1 > x2 && y1 < y2 + h2 && y1 + h1 > y2
+    /// Whether the monitor at `idx` is enabled once staged toggles apply.
+    pub fn will_be_enabled(&self, idx: usize) -> bool {
+        self.monitors.get(idx).is_some_and(|m| {
+            m.enabled != self.pending_toggles.contains_key(&m.name)
         })
     }
 
-    fn calculate_closest_non_overlapping_position(
-        &self,
-        exclude_name: &str,
-        preferred_pos: (i32, i32),
-        size: (i32, i32),
-    ) -> (i32, i32) {
-        let (w, h) = size;
-        let enabled_monitors: Vec<&WlMonitor> = self
-            .monitors
-            .iter()
-            .filter(|m| m.enabled && m.name != exclude_name)
-            .collect();
+    pub fn set_error(&mut self, msg: impl Into<String>) {
+        let msg = msg.into();
+        self.log_message(&msg);
+        self.error_message = Some(msg);
+    }
 
-        if enabled_monitors.is_empty() {
-            return preferred_pos;
+    pub fn clear_error(&mut self) {
+        self.error_message = None;
+    }
+
+    /// In a dry run every status says so, since nothing it reports
+    /// actually happened.
+    pub fn set_status(&mut self, msg: impl Into<String>) {
+        let msg = msg.into();
+        self.log_message(&msg);
+        self.status_message = Some(if self.is_dry_run() {
+            tf("Dry run: {}", &[&msg])
+        } else {
+            msg
+        });
+    }
+
+    fn log_message(&mut self, msg: &str) {
+        if self.messages.len() == MAX_MESSAGES {
+            self.messages.pop_front();
         }
+        self.messages.push_back(msg.to_string());
+    }
 
-        let mut candidates: Vec<(i32, i32)> = Vec::new();
+    pub fn clear_status(&mut self) {
+        self.status_message = None;
+    }
 
-        let min_left = enabled_monitors
-            .iter()
-            .map(|m| m.position.x)
-            .min()
-            .unwrap_or(0);
-        candidates.push((min_left - w, 0));
+    pub fn is_dry_run(&self) -> bool {
+        self.effects.is_dry_run()
+    }
 
-        let max_right = enabled_monitors
-            .iter()
-            .map(|m| {
-                let (mw, _) = effective_dimensions(m);
-                m.position.x + mw
-            })
-            .max()
-            .unwrap_or(0);
-        candidates.push((max_right, 0));
+    /// Actions and writes a dry run skipped, oldest first.
+    pub fn transcript(&self) -> &[String] {
+        self.effects.transcript()
+    }
 
-        let min_top = enabled_monitors
-            .iter()
-            .map(|m| m.position.y)
-            .min()
-            .unwrap_or(0);
-        candidates.push((0, min_top - h));
+    /// Everything a bug report needs to reproduce what xwlm sees.
+    pub fn debug_dump(&self) -> Dump {
+        Dump {
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            build: BUILD.summary(),
+            compositor: self.compositor.label().to_string(),
+            env: dump::env_signals(),
+            monitor_config: self.read_monitor_config().unwrap_or_default(),
+            config_problem: self.config_problem(),
+            pending: self.pending_lines(),
+            messages: self.messages.iter().cloned().collect(),
+            config: self.config.clone(),
+            workspaces: self.workspace_assignments.clone(),
+            monitors: self
+                .monitors
+                .iter()
+                .map(DumpMonitor::from_monitor)
+                .collect(),
+            session: self.session_summary(),
+        }
+    }
 
-        let max_bottom = enabled_monitors
-            .iter()
-            .map(|m| {
-                let (_, mh) = effective_dimensions(m);
-                m.position.y + mh
-            })
-            .max()
-            .unwrap_or(0);
-        candidates.push((0, max_bottom));
+    /// What changed since launch, one line each, as printed on quit.
+    pub fn session_summary(&self) -> Vec<String> {
+        self.session
+            .summary(&self.monitors, &self.workspace_assignments)
+    }
 
-        candidates
-            .into_iter()
-            .filter(|pos| !self.position_overlaps(exclude_name, *pos, size))
-            .map(|pos| {
-                let dist = (pos.0 - preferred_pos.0).abs() + (pos.1 - preferred_pos.1).abs();
-                (dist, pos)
-            })
-            .min_by_key(|(d, _)| *d)
-            .map(|(_, pos)| pos)
-            .unwrap_or((max_right, 0))
+    /// Whether anything was applied this session, so quitting sums it up.
+    pub fn has_session_changes(&self) -> bool {
+        self.session.has_applied()
     }
 
-    fn calculate_non_overlapping_position(&self, exclude_name: &str) -> (i32, i32) {
-        let enabled_monitors: Vec<&WlMonitor> = self
-            .monitors
+    /// Staged changes as one line each, e.g. "move DP-1 to 1920,0".
+    fn pending_lines(&self) -> Vec<String> {
+        let mut lines: Vec<String> = self
+            .pending_positions
             .iter()
-            .filter(|m| m.enabled && m.name != exclude_name)
+            .map(|(name, &(x, y))| {
+                describe(&WlMonitorAction::SetPosition {
+                    name: name.clone(),
+                    x,
+                    y,
+                })
+            })
+            .chain(self.pending_toggles.iter().map(|(name, &position)| {
+                describe(&WlMonitorAction::Toggle {
+                    name: name.clone(),
+                    mode: None,
+                    position,
+                })
+            }))
+            .chain(self.pending_transforms.iter().map(|(name, &transform)| {
+                describe(&WlMonitorAction::SetTransform {
+                    name: name.clone(),
+                    transform,
+                })
+            }))
+            .chain(self.pending_modes.iter().map(|(name, mode)| {
+                describe(&WlMonitorAction::SwitchMode {
+                    name: name.clone(),
+                    width: mode.width,
+                    height: mode.height,
+                    refresh_rate: mode.refresh_rate,
+                })
+            }))
+            .chain(self.pending_scales.iter().map(|(name, &scale)| {
+                describe(&WlMonitorAction::SetScale {
+                    name: name.clone(),
+                    scale,
+                })
+            }))
+            .chain(self.pending_workspaces.iter().map(|(id, ws)| {
+                let monitor = ws.monitor.as_deref().unwrap_or("-");
+                format!("workspace {id} to {monitor}")
+            }))
+            .chain(self.pending_options.iter().map(|((name, option), on)| {
+                format!("{option:?} {name} to {on}")
+            }))
             .collect();
+        for monitor in &self.monitors {
+            if let Some(&scale) = self.scale_edits.get(&monitor.name)
+                && scale != monitor.scale
+            {
+                lines.push(describe(&WlMonitorAction::SetScale {
+                    name: monitor.name.clone(),
+                    scale,
+                }));
+            }
+        }
+        lines.sort();
+        lines
+    }
 
-        if enabled_monitors.is_empty() {
-            return (0, 0);
+    /// Writes [`App::debug_dump`] to `path`, with serials replaced when
+    /// `redact` is set.
+    pub fn write_debug_dump(&mut self, path: &Path, redact: bool) {
+        let written = dump::render(&self.debug_dump(), redact)
+            .map_err(|e| e.to_string())
+            .and_then(|content| {
+                if let Some(parent) =
+                    path.parent().filter(|p| !p.as_os_str().is_empty())
+                    && self.allow(&format!("create {}", parent.display()))
+                {
+                    fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+                }
+                self.effects
+                    .write(path, &content)
+                    .map_err(|e| e.to_string())
+            });
+        match written {
+            Ok(()) => {
+                let path = path.display();
+                self.set_status(tf("Wrote debug dump to {}", &[&path]));
+            }
+            Err(e) => {
+                self.set_error(tf("Failed to write debug dump: {}", &[&e]))
+            }
         }
+    }
 
-        let max_right = enabled_monitors
-            .iter()
-            .map(|m| {
-                let (w, _) = effective_dimensions(m);
-                m.position.x + w
-            })
-            .max()
-            .unwrap_or(0);
+    /// `B`: a redacted dump in the state directory, ready to attach to a
+    /// bug report.
+    pub fn write_bug_report_dump(&mut self) {
+        match dump::default_path() {
+            Some(path) => self.write_debug_dump(&path, true),
+            None => self.set_error(t("No state directory for the dump")),
+        }
+    }
 
-        (max_right, 0)
+    fn send_action(
+        &mut self,
+        action: WlMonitorAction,
+    ) -> Result<(), SendError<WlMonitorAction>> {
+        self.session.see(&self.monitors);
+        let name = undo::target(&action).to_string();
+        if let Some(before) = self.undo_from.take() {
+            self.undo.push(&before);
+        }
+        self.effects.send(action)?;
+        self.expect(&name, 1);
+        self.session.applied();
+        self.note_skipped();
+        Ok(())
     }
 
-    pub fn move_monitor(&mut self, direction: PositionDirection) {
-        let Some(selected) = self.monitors.get(self.selected_monitor) else {
-            return;
-        };
-        if !selected.enabled {
+    /// Waits for `count` more reports from `name`, which xwlm changed.
+    /// A dry run changes nothing, so nothing comes back.
+    fn expect(&mut self, name: &str, count: usize) {
+        if self.effects.is_dry_run() {
             return;
         }
+        let until = Instant::now() + Duration::from_millis(FEEDBACK_WINDOW_MS);
+        let entry = self.expected.entry(name.to_string()).or_insert((0, until));
+        *entry = (entry.0 + count, until);
+    }
 
-        let now = Instant::now();
-        let elapsed = now.duration_since(self.last_move_time).as_millis();
-        let same_direction = self
-            .last_move_direction
-            .as_ref()
-            .map(|d| std::mem::discriminant(d) == std::mem::discriminant(&direction))
-            .unwrap_or(false);
+    /// Whether a report of `name` is the echo of a change xwlm made, and
+    /// counts it off if so.
+    fn is_echo(&mut self, name: &str, now: Instant) -> bool {
+        self.expected.retain(|_, (_, until)| *until > now);
+        let Some((count, _)) = self.expected.get_mut(name) else {
+            return false;
+        };
+        *count = count.saturating_sub(1);
+        true
+    }
 
-        if elapsed < REPEAT_WINDOW_MS && same_direction {
-            self.move_repeat_count += 1;
-        } else {
-            self.move_repeat_count = 0;
-        }
-        self.last_move_time = now;
-        self.last_move_direction = Some(direction.clone());
+    /// How many reports of changes xwlm sent are still to come.
+    fn awaited(&self) -> usize {
+        self.expected.values().map(|&(count, _)| count).sum()
+    }
 
-        let step = 1 + (self.move_repeat_count * 2) as i32;
+    /// When the last report still awaited is due, if any is.
+    fn echoes_due(&self, now: Instant) -> Option<Instant> {
+        self.expected
+            .values()
+            .filter(|&&(count, until)| count > 0 && until > now)
+            .map(|&(_, until)| until)
+            .max()
+    }
 
-        let (cur_x, cur_y) = self.display_position(self.selected_monitor);
-        let (sel_w, sel_h) = effective_dimensions(selected);
+    fn allow(&mut self, what: &str) -> bool {
+        let allowed = self.effects.allow(what);
+        self.note_skipped();
+        allowed
+    }
 
-        let (new_x, new_y) = match direction {
-            PositionDirection::Left => (cur_x - step, cur_y),
-            PositionDirection::Right => (cur_x + step, cur_y),
-            PositionDirection::Up => (cur_x, cur_y - step),
-            PositionDirection::Down => (cur_x, cur_y + step),
-        };
+    /// Shows the latest skipped effect of a dry run in the status line.
+    fn note_skipped(&mut self) {
+        if let Some(last) = self.effects.transcript().last().cloned() {
+            self.set_status(last);
+        }
+    }
 
-        let new_x = new_x.max(0);
-        let new_y = new_y.max(0);
+    fn save_settings(&mut self) -> Result<(), ConfigError> {
+        if !self.allow("write xwlm settings") {
+            return Ok(());
+        }
+        xwlm_config::save_config(&self.config)
+    }
 
-        let collided = self.monitors.iter().enumerate().find(|(i, m)| {
-            if *i == self.selected_monitor || !m.enabled {
-                return false;
-            }
-            let (mx, my) = self.display_position(*i);
-            let (mw, mh) = effective_dimensions(m);
-            new_x < mx + mw && new_x + sel_w > mx && new_y < my + mh && new_y + sel_h > my
-        });
+    pub fn zoom_in(&mut self) {
+        self.map_zoom *= self.zoom_step();
+        self.clamp_zoom();
+    }
 
-        if let Some((other_idx, other_mon)) = collided {
-            let (other_x, other_y) = self.display_position(other_idx);
-            let (other_w, other_h) = effective_dimensions(other_mon);
+    pub fn zoom_out(&mut self) {
+        self.map_zoom /= self.zoom_step();
+        self.clamp_zoom();
+    }
 
-            let (new_pos_selected, new_pos_other) = match direction {
-                PositionDirection::Left => ((other_x, other_y), (other_x + sel_w, other_y)),
-                PositionDirection::Right => ((cur_x + other_w, cur_y), (cur_x, cur_y)),
-                PositionDirection::Up => ((other_x, other_y), (other_x, other_y + sel_h)),
-                PositionDirection::Down => ((cur_x, cur_y + other_h), (cur_x, cur_y)),
-            };
+    /// The zoom as a rounded percentage, e.g. 140.
+    pub fn zoom_percent(&self) -> u32 {
+        (self.map_zoom * 100.0).round() as u32
+    }
 
-            let new_pos_selected = (new_pos_selected.0.max(0), new_pos_selected.1.max(0));
-            let new_pos_other = (new_pos_other.0.max(0), new_pos_other.1.max(0));
+    fn zoom_step(&self) -> f64 {
+        self.config.zoom_step.max(1.01)
+    }
 
-            self.pending_positions
-                .insert(self.selected_monitor, new_pos_selected);
-            self.pending_positions.insert(other_idx, new_pos_other);
-        } else {
-            self.pending_positions
-                .insert(self.selected_monitor, (new_x, new_y));
+    /// Keeps the zoom within the configured limits.
+    fn clamp_zoom(&mut self) {
+        let min = self.config.zoom_min.max(0.01);
+        let max = self.config.zoom_max.max(min);
+        self.map_zoom = self.map_zoom.clamp(min, max);
+    }
+
+    /// Also zooms out to where the whole layout fits the map's drawable
+    /// area, so a layout that grew or shrank, or a smaller terminal, never
+    /// leaves monitors drawn off the map. `+` still zooms in past it.
+    pub fn fit_map(&mut self) {
+        self.clamp_zoom();
+        let fits = (1.0 / MAP_FILL).max(self.config.zoom_min);
+        self.map_zoom = self.map_zoom.min(fits);
+    }
+
+    /// The Scale panel's value for the selected monitor: what the user
+    /// set it to, or else its current scale.
+    pub fn pending_scale(&self) -> f64 {
+        let Some(monitor) = self.selected_monitor() else {
+            return 1.0;
+        };
+        self.scale_edits
+            .get(&monitor.name)
+            .copied()
+            .unwrap_or(monitor.scale)
+    }
+
+    /// Whether `name` has a Scale panel value that is not applied yet.
+    pub fn has_scale_edit(&self, name: &str) -> bool {
+        let Some(&scale) = self.scale_edits.get(name) else {
+            return false;
+        };
+        let monitor = self.monitors.iter().find(|m| m.name == name);
+        monitor.is_some_and(|m| (scale - m.scale).abs() > 0.001)
+    }
+
+    pub fn scale_up(&mut self) {
+        self.set_scale_edit((self.pending_scale() + 0.01).min(MAX_SCALE));
+    }
+
+    pub fn scale_down(&mut self) {
+        self.set_scale_edit((self.pending_scale() - 0.01).max(MIN_SCALE));
+    }
+
+    pub fn set_pending_scale(&mut self, scale: f64) -> bool {
+        if !(MIN_SCALE..=MAX_SCALE).contains(&scale) {
+            self.set_error(tf(
+                "Scale must be between {} and {}",
+                &[&MIN_SCALE, &MAX_SCALE],
+            ));
+            return false;
         }
+        self.set_scale_edit(scale);
+        true
     }
 
-    pub fn previous(&mut self) {
-        match self.panel {
-            Panel::Mode => {
-                let len = self.selected_monitor().map(|m| m.modes.len()).unwrap_or(0);
-                if len == 0 {
-                    return;
+    pub fn monitor_dpi(&self, idx: usize) -> Option<f64> {
+        let monitor = self.monitors.get(idx)?;
+        let size = (*self.physical_sizes.get(&monitor.name)?)?;
+        scale::dpi(monitor_resolution(monitor), size)
+    }
+
+    pub fn recommended_scale(&self, idx: usize) -> Option<f64> {
+        let dpi = self.monitor_dpi(idx)?;
+        let monitor = self.monitors.get(idx)?;
+        Some(scale::recommended_scale(dpi, monitor_resolution(monitor)))
+    }
+
+    /// The resolution the Scale panel judges: the mode highlighted in the
+    /// Modes panel, which is the current one unless the user is browsing.
+    fn pending_resolution(&self) -> Option<(i32, i32)> {
+        let monitor = self.selected_monitor()?;
+        match self.selected_mode_entry() {
+            Some(ModeEntry::Advertised(i)) => monitor
+                .modes
+                .get(i)
+                .map(|m| (m.resolution.width, m.resolution.height)),
+            Some(ModeEntry::Custom { mode, .. }) => {
+                Some((mode.width, mode.height))
+            }
+            _ => Some(monitor_resolution(monitor)),
+        }
+    }
+
+    pub fn scale_lint(&self) -> Option<ScaleLint> {
+        scale::lint(
+            self.pending_resolution()?,
+            self.display_transform(self.selected_monitor),
+            self.pending_scale(),
+        )
+    }
+
+    fn set_scale_edit(&mut self, scale: f64) {
+        if let Some(name) = self.selected_monitor().map(|m| m.name.clone()) {
+            self.scale_edits.insert(name, scale);
+        }
+    }
+
+    /// Moves the pending scale to the nearest clean one below or above.
+    pub fn snap_scale(&mut self, higher: bool) {
+        let Some(lint) = self.scale_lint() else {
+            return;
+        };
+        match if higher { lint.higher } else { lint.lower } {
+            Some(s) => self.set_scale_edit(s),
+            None => self.set_status(t("No clean scale in that direction")),
+        }
+    }
+
+    /// Flags configs that were already fractional before xwlm touched them.
+    fn lint_loaded_scales(&mut self) {
+        let flagged: Vec<String> = self
+            .monitors
+            .iter()
+            .filter(|m| m.enabled && self.is_managed(&m.name))
+            .filter(|m| {
+                scale::lint(monitor_resolution(m), m.transform, m.scale)
+                    .is_some()
+            })
+            .map(|m| {
+                let (w, h) = monitor_resolution(m);
+                format!("{} ({w}x{h} @ {})", m.name, m.scale)
+            })
+            .collect();
+        if !flagged.is_empty() {
+            self.set_status(tf(
+                "⚠ Non-integer logical size: {}",
+                &[&flagged.join(", ")],
+            ));
+        }
+    }
+
+    pub fn use_recommended_scale(&mut self) {
+        match self.recommended_scale(self.selected_monitor) {
+            Some(s) => self.set_scale_edit(s),
+            None => self
+                .set_status(t("No physical size reported for this monitor")),
+        }
+    }
+
+    fn enabled_count(&self) -> usize {
+        self.monitors.iter().filter(|m| m.enabled).count()
+    }
+
+    /// Stages switching off every monitor but the selected one, e.g. to
+    /// present on a projector.
+    pub fn stage_only_selected(&mut self) {
+        let Some(selected) = self.selected_monitor() else {
+            return;
+        };
+        let keep = selected.name.clone();
+        if !selected.enabled {
+            self.set_error(tf("{} is disabled", &[&keep]));
+            return;
+        }
+        let others: Vec<String> = self
+            .monitors
+            .iter()
+            .filter(|m| m.enabled && m.name != keep)
+            .map(|m| m.name.clone())
+            .collect();
+        self.pending_toggles.clear();
+        for name in &others {
+            self.pending_positions.remove(name);
+            self.pending_toggles.insert(name.clone(), None);
+        }
+        self.panel = Panel::Monitor;
+        self.stage_status();
+    }
+
+    /// Stages switching on every disabled monitor with the mode and at the
+    /// position it was saved with, pushing whatever it would overlap out
+    /// of its way.
+    pub fn stage_enable_all(&mut self) {
+        let disabled: Vec<usize> = (0..self.monitors.len())
+            .filter(|&i| !self.monitors[i].enabled)
+            .collect();
+        let monitors = &self.monitors;
+        self.pending_toggles.retain(|name, _| {
+            disabled.iter().any(|&i| monitors[i].name == *name)
+        });
+        let saved = self.saved_monitors().unwrap_or_default();
+        let mut fallbacks = Vec::new();
+
+        // Enabled monitors and already staged enables keep their spot
+        // unless one being switched on lands on them.
+        let mut placed: Vec<(usize, Rect)> = (0..self.monitors.len())
+            .filter(|&i| {
+                self.monitors[i].enabled
+                    || self.pending_toggles.contains_key(&self.monitors[i].name)
+            })
+            .map(|i| {
+                let mut rect = self.logical_rect(i);
+                let name = &self.monitors[i].name;
+                if let Some(Some((x, y))) = self.pending_toggles.get(name) {
+                    (rect.x, rect.y) = (*x, *y);
                 }
-                let i = self
-                    .mode_state
-                    .selected()
-                    .map(|i| if i == 0 { len - 1 } else { i - 1 })
-                    .unwrap_or(0);
-                self.mode_state.select(Some(i));
+                (i, rect)
+            })
+            .collect();
+        let mut enabling = Vec::new();
+        for &i in &disabled {
+            let live = self.monitors[i].clone();
+            if self.pending_toggles.contains_key(&live.name) {
+                continue;
             }
-            Panel::Monitor => {
-                self.move_monitor(PositionDirection::Up);
+            let config = saved.iter().find(|c| c.name == live.name);
+            let (position, mode) = match self.parked(&live.name) {
+                Some(kept) => (
+                    Some((kept.x, kept.y)),
+                    kept.has_mode().then_some(ModeSpec {
+                        width: kept.width,
+                        height: kept.height,
+                        refresh_rate: kept.refresh_rate,
+                    }),
+                ),
+                None => (
+                    config.and_then(|c| c.position),
+                    config.and_then(|c| c.mode).and_then(|(w, h, mhz)| {
+                        let saved = ModeSpec {
+                            width: w,
+                            height: h,
+                            refresh_rate: mhz.map_or(0, refresh::whole_hz),
+                        };
+                        // Without a saved rate any at that size will do.
+                        match mhz {
+                            Some(_) => Some(self.saved_mode(
+                                &live,
+                                saved,
+                                &mut fallbacks,
+                            )),
+                            None => resolve_mode(&live, saved).map(|m| m.0),
+                        }
+                    }),
+                ),
+            };
+            // A parked monitor gets its mode with the toggle.
+            if self.parked(&live.name).is_none()
+                && let Some(mode) = mode
+                && ModeSpec::current(&live) != Some(mode)
+            {
+                self.pending_modes.insert(live.name.clone(), mode);
             }
-            Panel::Scale => {
-                self.scale_down();
+            let resolution = mode
+                .map(|m| (m.width, m.height))
+                .unwrap_or_else(|| monitor_resolution(&live));
+            let (w, h) =
+                scale::logical_extent(resolution, live.transform, live.scale);
+            let (x, y) = match position {
+                Some(position) => position,
+                None => self.calculate_non_overlapping_position(&live.name),
+            };
+            enabling.push(placed.len());
+            placed.push((i, Rect::new(x, y, w, h)));
+        }
+
+        let mut rects: Vec<Rect> = placed.iter().map(|&(_, r)| r).collect();
+        for &moved in &enabling {
+            if !push_clear(&mut rects, moved, (1, 0), MAX_PUSH_CASCADE) {
+                let right = rects.iter().map(Rect::right).max().unwrap_or(0);
+                rects[moved].x = right;
+                rects[moved].y = 0;
             }
-            Panel::Transform => {
-                let len = TRANSFORMS.len();
-                let i = self
-                    .transform_state
-                    .selected()
-                    .map(|i| if i == 0 { len - 1 } else { i - 1 })
-                    .unwrap_or(0);
-                self.transform_state.select(Some(i));
+        }
+        shift_to_origin(&mut rects);
+
+        for (&(i, _), rect) in placed.iter().zip(&rects) {
+            let name = self.monitors[i].name.clone();
+            if !self.monitors[i].enabled {
+                self.pending_toggles.insert(name, Some((rect.x, rect.y)));
+            } else if (rect.x, rect.y) != self.display_position(i) {
+                self.pending_positions.insert(name, (rect.x, rect.y));
             }
-            Panel::Workspace => {
-                let len = self.workspace_assignments.len();
-                if len == 0 {
-                    return;
-                }
-                let i = self
-                    .workspace_state
-                    .selected()
-                    .map(|i| if i == 0 { len - 1 } else { i - 1 })
-                    .unwrap_or(0);
-                self.workspace_state.select(Some(i));
+        }
+        self.panel = Panel::Monitor;
+        self.stage_status();
+        if !fallbacks.is_empty() {
+            self.set_error(fallbacks.join("; "));
+        }
+    }
+
+    fn stage_status(&mut self) {
+        if self.pending_toggles.is_empty() {
+            self.set_status(t("Nothing to toggle"));
+            return;
+        }
+        let mut status = tn(
+            "Staged {} toggle",
+            "Staged {} toggles",
+            self.pending_toggles.len(),
+            &[],
+        );
+        status.push_str(" — ");
+        status.push_str(t("Enter to apply"));
+        self.set_status(status);
+    }
+
+    /// Asks `confirm`, turning down whatever was asked before.
+    fn ask(&mut self, confirm: Confirm) {
+        self.dismiss_confirm();
+        self.confirm = Some(confirm);
+    }
+
+    /// The no side of the pending confirmation.
+    pub fn dismiss_confirm(&mut self) {
+        match self.confirm.take() {
+            Some(Confirm::LastMonitor) => {
+                self.pending_toggles.clear();
+                self.replay_pending = None;
+            }
+            Some(Confirm::ClearWorkspaces | Confirm::Quit) => {}
+            Some(Confirm::RestoreSnapshot { .. }) => {
+                self.set_status(t("Kept the current layout"))
             }
+            None => {}
         }
     }
 
-    pub fn next(&mut self) {
-        match self.panel {
-            Panel::Mode => {
-                let len = self.selected_monitor().map(|m| m.modes.len()).unwrap_or(0);
-                if len == 0 {
-                    return;
-                }
-                let i = self
-                    .mode_state
-                    .selected()
-                    .map(|i| (i + 1) % len)
-                    .unwrap_or(0);
-                self.mode_state.select(Some(i));
+    /// The yes side of the pending confirmation.
+    pub fn accept_confirm(&mut self) -> Result<(), SendError<WlMonitorAction>> {
+        match self.confirm.take() {
+            Some(Confirm::LastMonitor) if self.replay_pending.is_some() => {
+                let redo = self.replay_pending.take() == Some(true);
+                self.step_history(redo, true)
             }
-            Panel::Monitor => {
-                self.move_monitor(PositionDirection::Down);
+            Some(Confirm::LastMonitor) if self.config.instant_toggle => {
+                let Some(monitor) = self.monitors.get(self.selected_monitor)
+                else {
+                    return Ok(());
+                };
+                let (name, enabled) = (monitor.name.clone(), monitor.enabled);
+                self.undoable(|app| app.perform_toggle(&name, enabled))
             }
-            Panel::Scale => {
-                self.scale_up();
+            Some(Confirm::LastMonitor) => {
+                self.undoable(|app| app.apply_monitor_changes(true))
             }
-            Panel::Transform => {
-                let len = TRANSFORMS.len();
-                let i = self
-                    .transform_state
-                    .selected()
-                    .map(|i| (i + 1) % len)
-                    .unwrap_or(0);
-                self.transform_state.select(Some(i));
+            Some(Confirm::ClearWorkspaces) => {
+                self.clear_workspaces();
+                Ok(())
             }
-            Panel::Workspace => {
-                let len = self.workspace_assignments.len();
-                if len == 0 {
-                    return;
+            Some(Confirm::RestoreSnapshot { snapshot, .. }) => {
+                self.restore_snapshot(&snapshot)
+            }
+            // The UI loop ends.
+            Some(Confirm::Quit) | None => Ok(()),
+        }
+    }
+
+    /// Stages enabling or disabling the selected monitor, or unstages it
+    /// when it already has a pending toggle. With `instant_toggle` the
+    /// change is sent right away instead.
+    pub fn toggle_monitor(&mut self) -> Result<(), SendError<WlMonitorAction>> {
+        if !self.config.instant_toggle {
+            let Some(monitor) = self.monitors.get(self.selected_monitor) else {
+                return Ok(());
+            };
+            let name = monitor.name.clone();
+            if self.pending_toggles.remove(&name).is_none() {
+                let position = self.toggle_position(&name, monitor.enabled);
+                self.pending_positions.remove(&name);
+                self.pending_toggles.insert(name, position);
+                self.panel = Panel::Monitor;
+            }
+            return Ok(());
+        }
+
+        let Some(monitor) = self.monitors.get(self.selected_monitor) else {
+            return Ok(());
+        };
+
+        if monitor.enabled && self.enabled_count() == 1 {
+            self.ask(Confirm::LastMonitor);
+            return Ok(());
+        }
+        let (name, enabled) = (monitor.name.clone(), monitor.enabled);
+        if !self.clear_of_unmanaged([name.clone()])
+            || enabled && !self.clear_of_captures([name.clone()])
+        {
+            return Ok(());
+        }
+        self.undoable(|app| app.perform_toggle(&name, enabled))
+    }
+
+    /// Runs `apply` with the monitors as they are now kept for undo. They
+    /// are pushed only once `apply` sends something, so a change turned
+    /// down or merely staged leaves nothing to undo.
+    fn undoable<T>(&mut self, apply: impl FnOnce(&mut Self) -> T) -> T {
+        self.undo_from = Some(self.monitors.clone());
+        let result = apply(self);
+        self.undo_from = None;
+        result
+    }
+
+    fn perform_toggle(
+        &mut self,
+        monitor_name: &str,
+        currently_enabled: bool,
+    ) -> Result<(), SendError<WlMonitorAction>> {
+        let position = self.toggle_position(monitor_name, currently_enabled);
+        let risky = self
+            .monitors
+            .iter()
+            .find(|m| m.name == monitor_name && m.enabled)
+            .filter(|_| self.terminal_output.as_deref() == Some(monitor_name))
+            .map(|m| {
+                let revert = Revert::Enable(m.position.x, m.position.y);
+                (m.name.clone(), revert)
+            });
+        self.send_toggle(monitor_name, position)?;
+        if currently_enabled {
+            self.note_bars(&[monitor_name.to_string()]);
+        }
+        self.guard(risky);
+        Ok(())
+    }
+
+    /// Toggles `monitor_name`. Switching on brings back the mode, scale and
+    /// transform it had when it was switched off.
+    fn send_toggle(
+        &mut self,
+        monitor_name: &str,
+        position: Option<(i32, i32)>,
+    ) -> Result<(), SendError<WlMonitorAction>> {
+        let live = self.monitors.iter().find(|m| m.name == monitor_name);
+        let parked = position
+            .and(self.parked(monitor_name))
+            .cloned()
+            .zip(live.cloned());
+        let mode = parked.as_ref().and_then(|(kept, live)| {
+            let saved = ModeSpec {
+                width: kept.width,
+                height: kept.height,
+                refresh_rate: kept.refresh_rate,
+            };
+            resolve_mode(live, saved)
+                .map(|(m, _)| (m.width, m.height, m.refresh_rate))
+        });
+        self.send_action(WlMonitorAction::Toggle {
+            name: monitor_name.to_string(),
+            mode,
+            position,
+        })?;
+        if let Some((kept, live)) = parked {
+            if (live.scale - kept.scale).abs() > 0.001 {
+                self.send_action(WlMonitorAction::SetScale {
+                    name: monitor_name.to_string(),
+                    scale: kept.scale,
+                })?;
+            }
+            if live.transform != kept.transform {
+                self.send_action(WlMonitorAction::SetTransform {
+                    name: monitor_name.to_string(),
+                    transform: kept.transform,
+                })?;
+            }
+        }
+        self.touched.insert(monitor_name.to_string());
+
+        self.mark_dirty();
+
+        Ok(())
+    }
+
+    /// Where a monitor goes when it is switched on: where it was before it
+    /// was switched off, its saved position, or the nearest free spot.
+    /// `None` when it is being switched off.
+    fn toggle_position(
+        &self,
+        monitor_name: &str,
+        currently_enabled: bool,
+    ) -> Option<(i32, i32)> {
+        let will_enable = !currently_enabled;
+        if will_enable {
+            let saved_pos = match self.parked(monitor_name) {
+                Some(kept) => Some((kept.x, kept.y)),
+                None => self
+                    .read_monitor_config()
+                    .ok()
+                    .and_then(|content| {
+                        config_position(self.compositor, &content, monitor_name)
+                    })
+                    .map(|saved| (saved.x, saved.y)),
+            };
+
+            let (w, h) = self
+                .monitors
+                .iter()
+                .find(|m| m.name == monitor_name)
+                .map(effective_dimensions)
+                .unwrap_or((1920, 1080));
+
+            if let Some(pos) = saved_pos {
+                if self.position_overlaps(monitor_name, pos, (w, h)) {
+                    Some(self.calculate_closest_non_overlapping_position(monitor_name, pos, (w, h)))
+                } else {
+                    Some(pos)
                 }
-                let i = self
-                    .workspace_state
-                    .selected()
-                    .map(|i| (i + 1) % len)
-                    .unwrap_or(0);
-                self.workspace_state.select(Some(i));
+            } else {
+                Some(self.calculate_non_overlapping_position(monitor_name))
+            }
+        } else {
+            None
+        }
+    }
+
+    fn position_overlaps(&self, exclude_name: &str, pos: (i32, i32), size: (i32, i32)) -> bool {
+        let candidate = Rect::new(pos.0, pos.1, size.0, size.1);
+
+        self.monitors.iter().any(|m| {
+            if m.name == exclude_name || !m.enabled {
+                return false;
             }
+            let (w, h) = effective_dimensions(m);
+            candidate.overlaps(&Rect::new(m.position.x, m.position.y, w, h))
+        })
+    }
+
+    fn calculate_closest_non_overlapping_position(
+        &self,
+        exclude_name: &str,
+        preferred_pos: (i32, i32),
+        size: (i32, i32),
+    ) -> (i32, i32) {
+        let (w, h) = size;
+        let enabled_monitors: Vec<&WlMonitor> = self
+            .monitors
+            .iter()
+            .filter(|m| m.enabled && m.name != exclude_name)
+            .collect();
+
+        if enabled_monitors.is_empty() {
+            return preferred_pos;
         }
+
+        let mut candidates: Vec<(i32, i32)> = Vec::new();
+
+        let min_left = enabled_monitors
+            .iter()
+            .map(|m| m.position.x)
+            .min()
+            .unwrap_or(0);
+        candidates.push((min_left - w, 0));
+
+        let max_right = enabled_monitors
+            .iter()
+            .map(|m| {
+                let (mw, _) = effective_dimensions(m);
+                m.position.x + mw
+            })
+            .max()
+            .unwrap_or(0);
+        candidates.push((max_right, 0));
+
+        let min_top = enabled_monitors
+            .iter()
+            .map(|m| m.position.y)
+            .min()
+            .unwrap_or(0);
+        candidates.push((0, min_top - h));
+
+        let max_bottom = enabled_monitors
+            .iter()
+            .map(|m| {
+                let (_, mh) = effective_dimensions(m);
+                m.position.y + mh
+            })
+            .max()
+            .unwrap_or(0);
+        candidates.push((0, max_bottom));
+
+        candidates
+            .into_iter()
+            .filter(|pos| !self.position_overlaps(exclude_name, *pos, size))
+            .map(|pos| {
+                let dist = (pos.0 - preferred_pos.0).abs() + (pos.1 - preferred_pos.1).abs();
+                (dist, pos)
+            })
+            .min_by_key(|(d, _)| *d)
+            .map(|(_, pos)| pos)
+            .unwrap_or((max_right, 0))
+    }
+
+    fn calculate_non_overlapping_position(&self, exclude_name: &str) -> (i32, i32) {
+        let enabled_monitors: Vec<&WlMonitor> = self
+            .monitors
+            .iter()
+            .filter(|m| m.enabled && m.name != exclude_name)
+            .collect();
+
+        if enabled_monitors.is_empty() {
+            return (0, 0);
+        }
+
+        let max_right = enabled_monitors
+            .iter()
+            .map(|m| {
+                let (w, _) = effective_dimensions(m);
+                m.position.x + w
+            })
+            .max()
+            .unwrap_or(0);
+
+        (max_right, 0)
+    }
+
+    pub fn move_monitor(&mut self, direction: PositionDirection) {
+        let Some(selected) = self.monitors.get(self.selected_monitor) else {
+            return;
+        };
+        if !selected.enabled {
+            return;
+        }
+
+        let now = Instant::now();
+        let elapsed = now.duration_since(self.last_move_time).as_millis();
+        let same_direction = self
+            .last_move_direction
+            .as_ref()
+            .map(|d| std::mem::discriminant(d) == std::mem::discriminant(&direction))
+            .unwrap_or(false);
+
+        if elapsed < REPEAT_WINDOW_MS && same_direction {
+            self.move_repeat_count += 1;
+        } else {
+            self.move_repeat_count = 0;
+        }
+        self.last_move_time = now;
+        self.last_move_direction = Some(direction.clone());
+
+        let step = 1 + (self.move_repeat_count * 2) as i32;
+
+        let (cur_x, cur_y) = self.display_position(self.selected_monitor);
+        let (sel_w, sel_h) = self.display_dimensions(self.selected_monitor);
+
+        let (dir, (new_x, new_y)) = match direction {
+            PositionDirection::Left => ((-1, 0), (cur_x - step, cur_y)),
+            PositionDirection::Right => ((1, 0), (cur_x + step, cur_y)),
+            PositionDirection::Up => ((0, -1), (cur_x, cur_y - step)),
+            PositionDirection::Down => ((0, 1), (cur_x, cur_y + step)),
+        };
+
+        let new_x = new_x.max(0);
+        let new_y = new_y.max(0);
+
+        let enabled: Vec<usize> = self
+            .monitors
+            .iter()
+            .enumerate()
+            .filter(|(_, m)| m.enabled)
+            .map(|(i, _)| i)
+            .collect();
+        let selected = self.selected_monitor;
+        let Some(moved) = enabled.iter().position(|&i| i == selected) else {
+            return;
+        };
+        let mut rects: Vec<Rect> = enabled
+            .iter()
+            .map(|&i| {
+                if i == self.selected_monitor {
+                    return Rect::new(new_x, new_y, sel_w, sel_h);
+                }
+                let (x, y) = self.display_position(i);
+                let (w, h) = self.display_dimensions(i);
+                Rect::new(x, y, w, h)
+            })
+            .collect();
+
+        snap_ahead(&mut rects, moved, dir, self.config.snap_threshold);
+        if !push_clear(&mut rects, moved, dir, MAX_PUSH_CASCADE) {
+            self.set_status(t("Too many monitors in the way"));
+            return;
+        }
+        shift_to_origin(&mut rects);
+
+        for (&i, rect) in enabled.iter().zip(&rects) {
+            if (rect.x, rect.y) != self.display_position(i) {
+                let name = self.monitors[i].name.clone();
+                self.pending_positions.insert(name, (rect.x, rect.y));
+            }
+        }
+    }
+
+    /// Stages an absolute position for the selected monitor, refusing ones
+    /// that would overlap another enabled monitor.
+    pub fn stage_position(&mut self, x: i32, y: i32) -> bool {
+        let Some(monitor) = self.selected_monitor() else {
+            return false;
+        };
+        let name = monitor.name.clone();
+        if !monitor.enabled {
+            self.set_error(tf("{} is disabled", &[&name]));
+            return false;
+        }
+        if x < 0 || y < 0 {
+            self.set_error(t("Positions cannot be negative"));
+            return false;
+        }
+        let (w, h) = self.display_dimensions(self.selected_monitor);
+        let candidate = Rect::new(x, y, w, h);
+        let overlaps = self
+            .monitors
+            .iter()
+            .enumerate()
+            .filter(|(i, m)| m.enabled && *i != self.selected_monitor)
+            .any(|(i, _)| {
+                let (ox, oy) = self.display_position(i);
+                let (ow, oh) = self.display_dimensions(i);
+                candidate.overlaps(&Rect::new(ox, oy, ow, oh))
+            });
+        if overlaps {
+            self.set_error(t("That position overlaps another monitor"));
+            return false;
+        }
+        self.pending_positions.insert(name, (x, y));
+        true
+    }
+
+    /// Directions focus gets stuck in between the enabled monitors, judged
+    /// by their logical rects with staged positions and transforms.
+    pub fn focus_gaps(&self) -> Vec<Gap> {
+        let monitors: Vec<(String, Rect)> = self
+            .monitors
+            .iter()
+            .enumerate()
+            .filter(|(_, m)| m.enabled)
+            .map(|(i, m)| (m.name.clone(), self.logical_rect(i)))
+            .collect();
+        adjacency::gaps(&monitors)
+    }
+
+    /// The first focus gap leaving the selected monitor.
+    pub fn selected_focus_gap(&self) -> Option<Gap> {
+        let name = &self.selected_monitor()?.name;
+        self.focus_gaps().into_iter().find(|g| &g.from == name)
+    }
+
+    /// Stages the selected monitor where it lines up with the monitor its
+    /// first focus gap points at.
+    pub fn align_for_focus(&mut self) {
+        let Some(gap) = self.selected_focus_gap() else {
+            self.set_status(t("Focus reaches every neighbour"));
+            return;
+        };
+        let (x, y) = gap.fix;
+        if self.stage_position(x, y) {
+            self.set_status(tf(
+                "{} lined up with {}, Enter to apply",
+                &[&gap.from, &gap.toward],
+            ));
+        }
+    }
+
+    /// `c`: asks which monitor gets the selected one's mode, scale and
+    /// transform.
+    pub fn open_clone_picker(&mut self) {
+        let Some(source) = self.selected_monitor() else {
+            return;
+        };
+        if !source.enabled {
+            self.set_error(tf("{} is disabled", &[&source.name]));
+            return;
+        }
+        let source = source.name.clone();
+        let targets: Vec<String> = self
+            .monitors
+            .iter()
+            .filter(|m| m.enabled && m.name != source)
+            .map(|m| m.name.clone())
+            .collect();
+        if targets.is_empty() {
+            self.set_status(t("No other enabled monitor to copy to"));
+            return;
+        }
+        self.clone_picker = Some(ClonePicker {
+            source,
+            targets,
+            state: ListState::default().with_selected(Some(0)),
+            adjacent: false,
+        });
+    }
+
+    pub fn close_clone_picker(&mut self) {
+        self.clone_picker = None;
+    }
+
+    pub fn clone_picker_step(&mut self, forward: bool) {
+        let Some(picker) = &mut self.clone_picker else {
+            return;
+        };
+        let len = picker.targets.len();
+        let current = picker.state.selected().unwrap_or(0);
+        let next = if forward {
+            (current + 1) % len
+        } else {
+            (current + len - 1) % len
+        };
+        picker.state.select(Some(next));
+    }
+
+    pub fn toggle_clone_adjacent(&mut self) {
+        if let Some(picker) = &mut self.clone_picker {
+            picker.adjacent = !picker.adjacent;
+        }
+    }
+
+    pub fn confirm_clone(&mut self) {
+        let Some(picker) = &self.clone_picker else {
+            return;
+        };
+        let selected = picker.state.selected();
+        let target = selected.and_then(|i| picker.targets.get(i)).cloned();
+        // The picker stays open for the second Enter an unmanaged target
+        // needs.
+        if let Some(target) = &target
+            && !self.clear_of_unmanaged([target.clone()])
+        {
+            return;
+        }
+        let Some(picker) = self.clone_picker.take() else {
+            return;
+        };
+        if let Some(target) = target {
+            self.clone_settings(&picker.source, &target, picker.adjacent);
+        }
+    }
+
+    /// Stages `source`'s mode, scale and transform on `target`, with the
+    /// closest mode `target` advertises when it lacks the exact one. With
+    /// `adjacent`, `target` is also staged right of `source`.
+    pub fn clone_settings(
+        &mut self,
+        source: &str,
+        target: &str,
+        adjacent: bool,
+    ) {
+        let find = |name| self.monitors.iter().position(|m| m.name == name);
+        let (Some(from), Some(to)) = (find(source), find(target)) else {
+            return;
+        };
+        let staged =
+            self.staged_changes().into_iter().find(|s| s.name == source);
+        let original = &self.monitors[from];
+        let wanted = staged
+            .as_ref()
+            .and_then(|s| s.mode)
+            .or_else(|| self.pending_modes.get(source).copied())
+            .or_else(|| ModeSpec::current(original));
+        let scale = staged
+            .as_ref()
+            .and_then(|s| s.scale)
+            .unwrap_or(original.scale);
+        let transform = staged
+            .as_ref()
+            .and_then(|s| s.transform)
+            .unwrap_or(original.transform);
+        let resolution = wanted
+            .map(|m| (m.width, m.height))
+            .unwrap_or_else(|| monitor_resolution(original));
+
+        let copy = self.monitors[to].clone();
+        let custom = |mode: ModeSpec| {
+            self.config.custom_modes.iter().any(|c| {
+                c.monitor == target
+                    && (c.width, c.height, c.refresh_rate)
+                        == (mode.width, mode.height, mode.refresh_rate)
+            })
+        };
+        let mut notes = Vec::new();
+        let mode = match wanted {
+            Some(wanted) if custom(wanted) => Some(wanted),
+            Some(wanted) => match resolve_mode(&copy, wanted) {
+                Some((mode, ModeMatch::Exact)) => Some(mode),
+                Some((mode, _)) => {
+                    notes.push(tf(
+                        "{} cannot do {}, using {}",
+                        &[&target, &wanted, &mode],
+                    ));
+                    Some(mode)
+                }
+                None => {
+                    notes.push(tf(
+                        "{} lists no modes to pick from",
+                        &[&target],
+                    ));
+                    None
+                }
+            },
+            None => None,
+        };
+        match mode.filter(|&mode| Some(mode) != ModeSpec::current(&copy)) {
+            Some(mode) => {
+                self.pending_modes.insert(target.to_string(), mode);
+            }
+            None => {
+                self.pending_modes.remove(target);
+            }
+        }
+        if (scale - copy.scale).abs() > 0.001 {
+            self.pending_scales.insert(target.to_string(), scale);
+        } else {
+            self.pending_scales.remove(target);
+        }
+        if transform != copy.transform {
+            self.pending_transforms.insert(target.to_string(), transform);
+        } else {
+            self.pending_transforms.remove(target);
+        }
+
+        if adjacent {
+            let (x, y) = self.display_position(from);
+            let (w, _) = scale::logical_extent(resolution, transform, scale);
+            let size = self.logical_rect(to);
+            let spot = Rect::new(x + w, y, size.w, size.h);
+            let blocked = (0..self.monitors.len())
+                .filter(|&i| i != from && i != to && self.monitors[i].enabled)
+                .any(|i| self.logical_rect(i).overlaps(&spot));
+            if blocked {
+                notes.push(tf("no room right of {}", &[&source]));
+            } else {
+                self.pending_positions
+                    .insert(target.to_string(), (spot.x, spot.y));
+            }
+        }
+
+        let mut status = tf(
+            "Copied {}'s settings to {}, Enter to apply",
+            &[&source, &target],
+        );
+        for note in notes {
+            status.push_str("; ");
+            status.push_str(&note);
+        }
+        self.set_status(status);
+    }
+
+    /// Rotates the whole enabled layout a quarter turn clockwise, composing
+    /// every monitor's transform with the same rotation. Results are staged
+    /// as pending positions and transforms for preview.
+    pub fn rotate_layout(&mut self) {
+        let enabled: Vec<usize> = self
+            .monitors
+            .iter()
+            .enumerate()
+            .filter(|(_, m)| m.enabled)
+            .map(|(i, _)| i)
+            .collect();
+
+        let rects: Vec<Rect> = enabled
+            .iter()
+            .map(|&i| {
+                let (x, y) = self.display_position(i);
+                let (w, h) = self.display_dimensions(i);
+                Rect::new(x, y, w, h)
+            })
+            .collect();
+
+        let Some(bounds) = bounding_box(&rects) else {
+            return;
+        };
+
+        for (&idx, rect) in enabled.iter().zip(&rects) {
+            let rotated = rotate_rect(*rect, bounds, 1);
+            let transform = compose_transform(self.display_transform(idx), 1);
+            let monitor = &self.monitors[idx];
+            self.pending_positions
+                .insert(monitor.name.clone(), (rotated.x, rotated.y));
+            if monitor.transform == transform {
+                self.pending_transforms.remove(&monitor.name);
+            } else {
+                self.pending_transforms
+                    .insert(monitor.name.clone(), transform);
+            }
+        }
+    }
+
+    /// Keeps gaps intact after the monitor at `idx` changed size by moving
+    /// everything to its right or below by the size delta. Moves are staged
+    /// unless `auto_reflow` is set; anchored monitors never move.
+    fn reflow_neighbours(&mut self, idx: usize, before: Rect, after: Rect) {
+        if self.observing {
+            return;
+        }
+        let mut moved: Vec<(String, (i32, i32))> = Vec::new();
+        let mut anchored: Vec<&str> = Vec::new();
+        for (i, m) in self.monitors.iter().enumerate() {
+            if i == idx || !m.enabled {
+                continue;
+            }
+            let (x, y) = self.display_position(i);
+            let (w, h) = self.display_dimensions(i);
+            let (dx, dy) = reflow_offset(before, after, Rect::new(x, y, w, h));
+            if (dx, dy) == (0, 0) {
+                continue;
+            }
+            if self.is_anchored(&m.name) {
+                anchored.push(&m.name);
+            } else {
+                moved.push((m.name.clone(), (x + dx, y + dy)));
+            }
+        }
+        if moved.is_empty() && anchored.is_empty() {
+            return;
+        }
+
+        let mut status =
+            tn("Reflowed {} monitor", "Reflowed {} monitors", moved.len(), &[]);
+        if !anchored.is_empty() {
+            status.push_str(&tf(", left anchored {}", &[&anchored.join(", ")]));
+        }
+
+        if self.config.auto_reflow {
+            for (name, (x, y)) in moved {
+                let sent = self.send_action(WlMonitorAction::SetPosition {
+                    name: name.clone(),
+                    x,
+                    y,
+                });
+                if let Err(e) = sent {
+                    self.set_error(tf("Failed to reflow {}: {}", &[&name, &e]));
+                    return;
+                }
+                self.touched.insert(name.clone());
+                self.pending_positions.remove(&name);
+                if let Some(m) =
+                    self.monitors.iter_mut().find(|m| m.name == name)
+                {
+                    m.position.x = x;
+                    m.position.y = y;
+                }
+            }
+            self.mark_dirty();
+        } else {
+            let staged = !moved.is_empty();
+            self.pending_positions.extend(moved);
+            if staged {
+                status.push_str(" — ");
+                status.push_str(t("Enter to apply"));
+            }
+        }
+        self.set_status(status);
+    }
+
+    /// Power (DPMS) is runtime-only state; it is read from the compositor
+    /// and never written to the monitor config. Read at most once per
+    /// [`POWER_REFRESH_MS`], since every monitor event asks.
+    fn refresh_power(&mut self) {
+        let now = Instant::now();
+        let every = Duration::from_millis(POWER_REFRESH_MS);
+        if self.power_read.is_some_and(|read| now < read + every) {
+            return;
+        }
+        self.power_read = Some(now);
+        self.power = ipc::power_states(self.compositor);
+    }
+
+    /// Asks the compositor for the modes of outputs not asked about yet.
+    /// An output keeps its modes until it goes away, so this only runs
+    /// when one shows up.
+    fn refresh_available_modes(&mut self) {
+        let known = |m: &WlMonitor| self.available_modes.contains_key(&m.name);
+        if self.monitors.iter().all(known) {
+            return;
+        }
+        let mut listed = ipc::available_modes(self.compositor);
+        for m in &self.monitors {
+            let modes = listed.remove(&m.name).unwrap_or_default();
+            self.available_modes.entry(m.name.clone()).or_insert(modes);
+        }
+    }
+
+    /// Whether the compositor lists `mode` for `name`, so it should work
+    /// without a custom modeline. True when the compositor cannot be asked.
+    pub fn mode_supported(&self, name: &str, mode: &WlMonitorMode) -> bool {
+        let Some(available) = self.available_modes.get(name) else {
+            return true;
+        };
+        let (w, h) = (mode.resolution.width, mode.resolution.height);
+        available.is_empty()
+            || available.iter().any(|&(aw, ah, mhz)| {
+                let hz = refresh::whole_hz(mhz);
+                (aw, ah) == (w, h)
+                    && (hz - mode.refresh_rate).abs() <= MODE_REFRESH_TOLERANCE
+            })
+    }
+
+    /// `mode`'s refresh rate in mHz, as precise as the compositor lists
+    /// it for `name`. Whole Hz otherwise.
+    pub fn refresh_mhz(&self, name: &str, mode: &WlMonitorMode) -> i32 {
+        let size = (mode.resolution.width, mode.resolution.height);
+        self.available_modes
+            .get(name)
+            .into_iter()
+            .flatten()
+            .find(|&&(w, h, mhz)| {
+                (w, h) == size && refresh::whole_hz(mhz) == mode.refresh_rate
+            })
+            .map_or(mode.refresh_rate * 1000, |&(_, _, mhz)| mhz)
+    }
+
+    /// The layout as it is, with refresh rates as precise as the
+    /// compositor lists them.
+    fn capture_layout(&self) -> Layout {
+        let mut layout =
+            Layout::capture(&self.monitors, &self.workspace_rules());
+        for saved in &mut layout.monitors {
+            let current = self
+                .monitors
+                .iter()
+                .find(|m| m.name == saved.name)
+                .and_then(|m| m.modes.iter().find(|mode| mode.is_current));
+            saved.refresh_mhz =
+                current.map(|mode| self.refresh_mhz(&saved.name, mode));
+        }
+        layout
+    }
+
+    /// Whether `mode` likely needs more bandwidth than `name`'s link has.
+    pub fn exceeds_link(&self, name: &str, mode: ModeSpec) -> bool {
+        self.links.get(name).is_some_and(|link| {
+            link.exceeded_by(mode.width, mode.height, mode.refresh_rate)
+        })
+    }
+
+    /// The compositor's scale limit when the pending scale is above it.
+    pub fn scale_over_limit(&self) -> Option<f64> {
+        self.compositor
+            .capabilities()
+            .max_scale
+            .filter(|&max| self.pending_scale() > max + 0.001)
+    }
+
+    pub fn is_powered(&self, name: &str) -> bool {
+        self.power.get(name).copied().unwrap_or(true)
+    }
+
+    pub fn toggle_power(&mut self) {
+        let Some(name) = self.selected_monitor().map(|m| m.name.clone()) else {
+            return;
+        };
+        self.set_power(&name, !self.is_powered(&name));
+    }
+
+    fn set_power(&mut self, name: &str, on: bool) {
+        let what = format!("power {name} {}", if on { "on" } else { "off" });
+        let result = if self.allow(&what) {
+            ipc::set_power(self.compositor, name, on)
+        } else {
+            Ok(())
+        };
+        match result {
+            Ok(()) => {
+                self.power.insert(name.to_string(), on);
+                let status = if on {
+                    tf("Powered {} on", &[&name])
+                } else {
+                    tf("Powered {} off", &[&name])
+                };
+                self.set_status(status);
+            }
+            Err(e) => self.set_error(tf("Failed to set power: {}", &[&e])),
+        }
+    }
+
+    pub fn is_anchored(&self, name: &str) -> bool {
+        self.config.anchored_monitors.iter().any(|n| n == name)
+    }
+
+    pub fn toggle_anchor(&mut self) {
+        let Some(name) = self.selected_monitor().map(|m| m.name.clone()) else {
+            return;
+        };
+        self.set_anchored(&name, !self.is_anchored(&name));
+        self.save_anchors();
+    }
+
+    fn set_anchored(&mut self, name: &str, anchored: bool) {
+        self.config.anchored_monitors.retain(|n| n != name);
+        if anchored {
+            self.config.anchored_monitors.push(name.to_string());
+        }
+    }
+
+    fn save_anchors(&mut self) {
+        if let Err(e) = self.save_settings() {
+            self.set_error(tf("Failed to save anchored monitors: {}", &[&e]));
+        }
+    }
+
+    /// Options panel rows the running compositor can act on.
+    pub fn monitor_options(&self) -> Vec<MonitorOption> {
+        let mut options = Vec::new();
+        if self.compositor.capabilities().supports_power {
+            options.push(MonitorOption::Power);
+        }
+        options.push(MonitorOption::Anchored);
+        if self.night_light.is_some() {
+            options.push(MonitorOption::NightLight);
+        }
+        options.push(MonitorOption::Managed);
+        options
+    }
+
+    pub fn option_value(&self, name: &str, option: MonitorOption) -> bool {
+        match option {
+            MonitorOption::Power => self.is_powered(name),
+            MonitorOption::Anchored => self.is_anchored(name),
+            MonitorOption::NightLight => self.is_night_light_excluded(name),
+            MonitorOption::Managed => self.is_managed(name),
+        }
+    }
+
+    /// The value `option` will have once the Options panel is applied.
+    pub fn pending_option(&self, name: &str, option: MonitorOption) -> bool {
+        self.pending_options
+            .get(&(name.to_string(), option))
+            .copied()
+            .unwrap_or_else(|| self.option_value(name, option))
+    }
+
+    /// Flips the highlighted option of the selected monitor, unstaging it
+    /// when that brings it back to the current value.
+    pub fn cycle_option(&mut self) {
+        let Some(name) = self.selected_monitor().map(|m| m.name.clone()) else {
+            return;
+        };
+        let options = self.monitor_options();
+        let Some(&option) =
+            self.options_selection.selected().and_then(|i| options.get(i))
+        else {
+            return;
+        };
+        let value = !self.pending_option(&name, option);
+        if value == self.option_value(&name, option) {
+            self.pending_options.remove(&(name, option));
+        } else {
+            self.pending_options.insert((name, option), value);
+        }
+    }
+
+    fn apply_options(&mut self) {
+        if self.pending_options.is_empty() {
+            self.set_status(t("Nothing to apply."));
+            return;
+        }
+        let mut anchors_changed = false;
+        let mut night_light_changed = false;
+        let mut managed_changed = false;
+        for ((name, option), value) in std::mem::take(&mut self.pending_options)
+        {
+            match option {
+                MonitorOption::Power => self.set_power(&name, value),
+                MonitorOption::Anchored => {
+                    self.set_anchored(&name, value);
+                    anchors_changed = true;
+                }
+                MonitorOption::NightLight => {
+                    let excluded = &mut self.config.night_light_excluded;
+                    excluded.retain(|n| *n != name);
+                    if value {
+                        excluded.push(name);
+                    }
+                    night_light_changed = true;
+                }
+                MonitorOption::Managed => {
+                    let unmanaged = &mut self.config.unmanaged_monitors;
+                    unmanaged.retain(|n| *n != name);
+                    if !value {
+                        unmanaged.push(name);
+                    }
+                    managed_changed = true;
+                }
+            }
+        }
+        if anchors_changed {
+            self.save_anchors();
+        }
+        if (night_light_changed || managed_changed)
+            && let Err(e) = self.save_settings()
+        {
+            self.set_error(tf("Failed to save settings: {}", &[&e]));
+        }
+        if night_light_changed {
+            self.restart_night_light();
+        }
+    }
+
+    /// Whether xwlm writes `name`'s lines and changes it without asking
+    /// twice.
+    pub fn is_managed(&self, name: &str) -> bool {
+        !self.config.unmanaged_monitors.iter().any(|n| n == name)
+    }
+
+    pub fn is_night_light_excluded(&self, name: &str) -> bool {
+        self.config.night_light_excluded.iter().any(|n| n == name)
+    }
+
+    /// Restarts wlsunset on the enabled outputs that are not left out.
+    /// Best effort: failures only show in the status line.
+    fn restart_night_light(&mut self) {
+        let Some(light) = self.night_light.clone() else {
+            return;
+        };
+        // No exclusions means wlsunset's default of every output.
+        let every = self.config.night_light_excluded.is_empty();
+        let outputs: Vec<String> = if every {
+            Vec::new()
+        } else {
+            self.monitors
+                .iter()
+                .filter(|m| m.enabled && !self.is_night_light_excluded(&m.name))
+                .map(|m| m.name.clone())
+                .collect()
+        };
+        if !self.allow("restart wlsunset") {
+            return;
+        }
+        // Without outputs wlsunset would take every one, so it stays off
+        // until one is let back in.
+        if !every && outputs.is_empty() {
+            match night_light::stop(&light) {
+                Ok(()) => {
+                    self.night_light = Some(NightLight { pid: None, ..light })
+                }
+                Err(e) => {
+                    self.set_error(tf("Failed to stop wlsunset: {}", &[&e]))
+                }
+            }
+            return;
+        }
+        match night_light::restart(&light, &outputs) {
+            Ok(Some(restarted)) => self.night_light = Some(restarted),
+            Ok(None) => self.set_error(t("wlsunset did not start again")),
+            Err(e) => {
+                self.set_error(tf("Failed to restart wlsunset: {}", &[&e]))
+            }
+        }
+    }
+
+    pub fn previous(&mut self) {
+        match self.panel {
+            Panel::Mode => {
+                let len = self.mode_entries().len();
+                if len == 0 {
+                    return;
+                }
+                let i = self
+                    .mode_selection
+                    .selected()
+                    .map(|i| if i == 0 { len - 1 } else { i - 1 })
+                    .unwrap_or(0);
+                self.mode_selection.select(Some(i));
+                self.edited.mode = true;
+            }
+            Panel::Monitor => {
+                self.move_monitor(PositionDirection::Up);
+            }
+            Panel::Scale => {
+                self.scale_down();
+            }
+            Panel::Transform => {
+                let len = TRANSFORMS.len();
+                let i = self
+                    .transform_selection
+                    .selected()
+                    .map(|i| if i == 0 { len - 1 } else { i - 1 })
+                    .unwrap_or(0);
+                self.transform_selection.select(Some(i));
+                self.edited.transform = true;
+            }
+            Panel::Options => {
+                let len = self.monitor_options().len();
+                let i = self
+                    .options_selection
+                    .selected()
+                    .map(|i| if i == 0 { len - 1 } else { i - 1 })
+                    .unwrap_or(0);
+                self.options_selection.select(Some(i));
+            }
+            Panel::Workspace => self.step_workspace(false),
+        }
+    }
+
+    pub fn next(&mut self) {
+        match self.panel {
+            Panel::Mode => {
+                let len = self.mode_entries().len();
+                if len == 0 {
+                    return;
+                }
+                let i = self
+                    .mode_selection
+                    .selected()
+                    .map(|i| (i + 1) % len)
+                    .unwrap_or(0);
+                self.mode_selection.select(Some(i));
+                self.edited.mode = true;
+            }
+            Panel::Monitor => {
+                self.move_monitor(PositionDirection::Down);
+            }
+            Panel::Scale => {
+                self.scale_up();
+            }
+            Panel::Transform => {
+                let len = TRANSFORMS.len();
+                let i = self
+                    .transform_selection
+                    .selected()
+                    .map(|i| (i + 1) % len)
+                    .unwrap_or(0);
+                self.transform_selection.select(Some(i));
+                self.edited.transform = true;
+            }
+            Panel::Options => {
+                let len = self.monitor_options().len();
+                let i = self
+                    .options_selection
+                    .selected()
+                    .map(|i| (i + 1) % len)
+                    .unwrap_or(0);
+                self.options_selection.select(Some(i));
+            }
+            Panel::Workspace => self.step_workspace(true),
+        }
+    }
+
+    fn step_workspace(&mut self, forward: bool) {
+        let visible = self.visible_workspaces();
+        let len = visible.len();
+        if len == 0 {
+            return;
+        }
+        let i = self
+            .workspace_row()
+            .map(|i| match forward {
+                true => (i + 1) % len,
+                false if i == 0 => len - 1,
+                false => i - 1,
+            })
+            .unwrap_or(0);
+        self.workspace_selection.select(Some(visible[i]));
+    }
+
+    /// The rows the Workspaces panel lists, as indexes into
+    /// `workspace_assignments`: all of them, or while following the
+    /// selected monitor its workspaces, the unassigned ones and one that
+    /// is leaving.
+    pub fn visible_workspaces(&self) -> Vec<usize> {
+        let followed = self.selected_monitor().map(|m| m.name.as_str());
+        let leaving = self.workspace_leaving.map(|(idx, _)| idx);
+        (0..self.workspace_assignments.len())
+            .filter(|&idx| {
+                if !self.ui_state.workspaces_follow_monitor
+                    || leaving == Some(idx)
+                {
+                    return true;
+                }
+                let Some(ws) = self.get_effective_workspace(idx) else {
+                    return false;
+                };
+                match (&ws.monitor, &ws.reserved) {
+                    (None, None) => true,
+                    (monitor, _) => monitor.as_deref() == followed,
+                }
+            })
+            .collect()
+    }
+
+    /// Where the selected workspace is among the listed rows.
+    pub fn workspace_row(&self) -> Option<usize> {
+        let selected = self.workspace_selection.selected()?;
+        self.visible_workspaces().iter().position(|&i| i == selected)
+    }
+
+    pub fn workspaces_follow_monitor(&self) -> bool {
+        self.ui_state.workspaces_follow_monitor
+    }
+
+    /// `f` in the Workspaces panel: list only the selected monitor's
+    /// workspaces and the unassigned ones, or all again. Remembered for
+    /// the next run.
+    pub fn toggle_workspace_filter(&mut self) {
+        let follow = !self.ui_state.workspaces_follow_monitor;
+        self.ui_state.workspaces_follow_monitor = follow;
+        self.workspace_leaving = None;
+        self.keep_workspace_listed();
+        if self.allow("remember the workspace filter")
+            && let Err(e) = ui_state::save(&self.ui_state)
+        {
+            self.set_error(tf("Failed to remember the filter: {}", &[&e]));
+            return;
+        }
+        match self.selected_monitor() {
+            Some(monitor) if follow => {
+                let name = monitor.name.clone();
+                self.set_status(tf("Showing workspaces of {} only", &[&name]));
+            }
+            _ => self.set_status(t("Showing every workspace")),
+        }
+    }
+
+    /// Rings the bell and flashes `panel`'s border as `failure_alert`
+    /// says. A dry run stays quiet.
+    fn alert_failure(&mut self, panel: Panel) {
+        let alert = self.config.failure_alert;
+        if alert.bell() && !self.effects.is_dry_run() {
+            self.bell = true;
+        }
+        if alert.flash() {
+            let until =
+                Instant::now() + Duration::from_millis(FAILURE_FLASH_MS);
+            self.failure_flash = Some((panel, until));
+        }
+    }
+
+    /// Whether the bell is to ring, once.
+    pub fn take_bell(&mut self) -> bool {
+        std::mem::take(&mut self.bell)
+    }
+
+    /// Whether `panel`'s border is flashing for a failure.
+    pub fn is_flashing(&self, panel: Panel) -> bool {
+        self.failure_flash.is_some_and(|(p, _)| p == panel)
+    }
+
+    /// Ends the failure flash once it is over.
+    pub fn check_failure_flash(&mut self, now: Instant) {
+        if self.failure_flash.is_some_and(|(_, until)| now >= until) {
+            self.failure_flash = None;
+        }
+    }
+
+    /// Ends the highlight of a workspace assigned away from the followed
+    /// monitor once it is due, and moves the selection off rows the
+    /// filter hides.
+    pub fn check_workspace_leaving(&mut self, now: Instant) {
+        if self.workspace_leaving.is_some_and(|(_, until)| now >= until) {
+            self.workspace_leaving = None;
+        }
+        self.keep_workspace_listed();
+    }
+
+    /// Selects the next listed workspace, or the last one, when the
+    /// selected one is not listed.
+    fn keep_workspace_listed(&mut self) {
+        if self.workspace_row().is_some() {
+            return;
+        }
+        let visible = self.visible_workspaces();
+        let selected = self.workspace_selection.selected().unwrap_or(0);
+        let row = visible
+            .iter()
+            .find(|&&idx| idx > selected)
+            .or(visible.last())
+            .copied();
+        self.workspace_selection.select(row);
+    }
+
+    pub fn cycle_workspace_monitor(&mut self, forward: bool) {
+        let Some(ws_idx) = self.workspace_selection.selected() else {
+            return;
+        };
+
+        let Some(effective) = self.get_effective_workspace(ws_idx) else {
+            return;
+        };
+
+        let monitors: Vec<&str> =
+            self.monitors.iter().map(|m| m.name.as_str()).collect();
+
+        if monitors.is_empty() {
+            return;
+        }
+
+        let new_monitor = match effective.monitor.as_deref() {
+            None => {
+                if forward {
+                    Some(monitors[0])
+                } else {
+                    Some(monitors[monitors.len() - 1])
+                }
+            }
+            Some(name) => {
+                let pos = monitors.iter().position(|&m| m == name);
+                match pos {
+                    Some(p) => {
+                        if forward {
+                            if p + 1 >= monitors.len() {
+                                None
+                            } else {
+                                Some(monitors[p + 1])
+                            }
+                        } else if p == 0 {
+                            None
+                        } else {
+                            Some(monitors[p - 1])
+                        }
+                    }
+                    None => {
+                        if forward {
+                            Some(monitors[0])
+                        } else {
+                            Some(monitors[monitors.len() - 1])
+                        }
+                    }
+                }
+            }
+        };
+
+        let new_monitor = new_monitor.map(str::to_string);
+        let followed = self.selected_monitor().map(|m| m.name.clone());
+        let leaves = self.ui_state.workspaces_follow_monitor
+            && new_monitor.is_some()
+            && new_monitor != followed;
+        let until =
+            Instant::now() + Duration::from_millis(WORKSPACE_LEAVING_MS);
+        self.workspace_leaving = leaves.then_some((ws_idx, until));
+        let mut new_ws = effective;
+        new_ws.fallbacks.retain(|name| Some(name) != new_monitor.as_ref());
+        new_ws.monitor = new_monitor;
+        new_ws.reserved = None;
+        self.pending_workspaces.insert(ws_idx, new_ws);
+    }
+
+    /// Stages moving workspace `id` to `monitor`.
+    pub fn stage_workspace(&mut self, id: usize, monitor: &str) -> bool {
+        if !self.monitors.iter().any(|m| m.name == monitor) {
+            self.set_error(tf("No monitor named {}", &[&monitor]));
+            return false;
+        }
+        let row = (0..self.workspace_assignments.len()).find(|&i| {
+            self.get_effective_workspace(i).is_some_and(|ws| ws.id == id)
+        });
+        let Some(row) = row else {
+            self.set_error(tf("No workspace {}", &[&id]));
+            return false;
+        };
+        let Some(mut ws) = self.get_effective_workspace(row) else {
+            return false;
+        };
+        ws.monitor = Some(monitor.to_string());
+        ws.reserved = None;
+        ws.fallbacks.retain(|name| name != monitor);
+        self.pending_workspaces.insert(row, ws);
+        true
+    }
+
+    pub fn get_effective_workspace(&self, idx: usize) -> Option<WorkspaceAssignment> {
+        if let Some(ws) = self.pending_workspaces.get(&idx) {
+            return Some(ws.clone());
+        }
+        self.workspace_assignments.get(idx).cloned()
+    }
+
+    pub fn has_pending_workspaces(&self) -> bool {
+        !self.pending_workspaces.is_empty()
+    }
+
+    pub fn nav_left(&mut self) {
+        match self.panel {
+            Panel::Monitor => self.move_monitor(PositionDirection::Left),
+            Panel::Scale => self.scale_down(),
+            Panel::Workspace => self.cycle_workspace_monitor(false),
+            Panel::Options => self.cycle_option(),
+            _ => {}
+        }
+    }
+
+    pub fn nav_right(&mut self) {
+        match self.panel {
+            Panel::Monitor => self.move_monitor(PositionDirection::Right),
+            Panel::Scale => self.scale_up(),
+            Panel::Workspace => self.cycle_workspace_monitor(true),
+            Panel::Options => self.cycle_option(),
+            _ => {}
+        }
+    }
+
+    /// Starts on `monitor`, matched by connector name or a piece of its
+    /// description, and `panel` once the monitors arrive. Until `deadline`
+    /// passes a missing monitor is waited for.
+    pub fn focus_on_startup(
+        &mut self,
+        monitor: Option<String>,
+        panel: Option<Panel>,
+        deadline: Instant,
+    ) {
+        self.startup_focus = (monitor.is_some() || panel.is_some())
+            .then_some(StartupFocus {
+                monitor,
+                panel,
+                deadline,
+            });
+    }
+
+    /// How long [`App::apply_startup_focus`] may still wait for its
+    /// monitor.
+    pub fn startup_wait(&self, now: Instant) -> Option<Duration> {
+        let focus = self.startup_focus.as_ref()?;
+        Some(focus.deadline.saturating_duration_since(now))
+    }
+
+    /// Selects the monitor and panel asked for at startup once the monitor
+    /// is known. Past the deadline the defaults stay and the status line
+    /// says why.
+    pub fn apply_startup_focus(&mut self, now: Instant) {
+        let Some(focus) = self.startup_focus.take() else {
+            return;
+        };
+        let waiting = now < focus.deadline;
+        if self.monitors.is_empty() && waiting {
+            self.startup_focus = Some(focus);
+            return;
+        }
+        if let Some(target) = &focus.monitor {
+            match self.find_monitor_matching(target) {
+                Some(idx) => self.select_index(idx),
+                None if waiting => {
+                    self.startup_focus = Some(focus);
+                    return;
+                }
+                None => {
+                    self.set_status(tf(
+                        "No monitor matches {}, starting as usual",
+                        &[target],
+                    ));
+                    return;
+                }
+            }
+        }
+        if let Some(panel) = focus.panel.filter(|&p| self.has_panel(p)) {
+            match self.tour {
+                Some(_) => self.tour_return = panel,
+                None => self.panel = panel,
+            }
+        }
+        self.sync_panel_state();
+    }
+
+    fn find_monitor_matching(&self, target: &str) -> Option<usize> {
+        let target_lower = target.to_lowercase();
+        self.monitors
+            .iter()
+            .position(|m| m.name == target)
+            .or_else(|| {
+                self.monitors.iter().position(|m| {
+                    m.description.to_lowercase().contains(&target_lower)
+                })
+            })
+    }
+
+    pub fn save_config(&mut self) {
+        if self.observing
+            || !self.needs_save()
+            || self.untouched_changes.is_some()
+        {
+            return;
+        }
+        self.settle(Instant::now());
+        // A save before the compositor told back every change xwlm sent
+        // writes half of it, and the next event writes the rest.
+        if self.save_phase != SavePhase::Idle
+            || self.echoes_due(Instant::now()).is_some()
+        {
+            self.save_queued = true;
+            return;
+        }
+
+        let mut layout = self.capture_layout();
+        let snaps = if self.config.snap_layout {
+            layout.snap_to_logical_edges()
+        } else {
+            Vec::new()
+        };
+        let mut managed = layout.clone();
+        managed.monitors.retain(|m| self.is_managed(&m.name));
+        let content = render_monitor_config(
+            self.compositor,
+            &managed,
+            &self.config.custom_modes,
+        );
+        let mut skipped = Vec::new();
+        if let Some(content) = &content {
+            let changes = self.untouched_monitor_changes(content);
+            match self.untouched_choice {
+                None if !changes.is_empty() => {
+                    self.untouched_changes = Some(changes);
+                    return;
+                }
+                Some(UntouchedChoice::Skip) => skipped = changes,
+                _ => {}
+            }
+        }
+        if self.config.canonical_layout {
+            if self.allow("write layout.toml")
+                && let Err(e) = layout::save_layout(&layout)
+            {
+                self.keep_unsaved();
+                self.set_error(tf("Failed to save layout: {}", &[&e]));
+                return;
+            }
+            self.layout = Some(layout.clone());
+        }
+
+        // The keyword calls that apply the change instead of a reload.
+        let mut live = None;
+        if let Some(content) = content {
+            let content =
+                diff::keep_old_lines(self.compositor, &content, &skipped);
+            let path = self.comp_monitor_config_path.clone();
+            let old = self.read_monitor_config();
+            let content = match &old {
+                Ok(old) => diff::merge_into(
+                    self.compositor,
+                    old,
+                    &content,
+                    &self.config.unmanaged_monitors,
+                ),
+                Err(_) => content,
+            };
+            let changed = old.as_ref().is_ok_and(|old| *old != content);
+            // A compositor that re-reads the file on its own would apply
+            // the change a second time.
+            live = match &old {
+                Ok(old) if self.config.live_apply => {
+                    live_commands(self.compositor, old, &content)
+                        .filter(|_| !self.effects.autoreloads(self.compositor))
+                }
+                _ => None,
+            };
+            if changed {
+                match self.effects.back_up(&path, self.config.backup_count) {
+                    Ok(true) => self.session.backed_up(backup_path(&path, 1)),
+                    Ok(false) => {}
+                    Err(e) => {
+                        self.keep_unsaved();
+                        self.set_error(tf(
+                            "Failed to back up {}: {}",
+                            &[&path.display(), &e],
+                        ));
+                        return;
+                    }
+                }
+            }
+            self.save_phase = SavePhase::Writing;
+            let written = self.effects.write(&path, &content);
+            self.save_phase = SavePhase::Idle;
+            if let Err(e) = written {
+                self.keep_unsaved();
+                self.set_error(tf("Failed to save config: {}", &[&e]));
+                return;
+            }
+            // So that labwc's autostart can run the script by its path.
+            if self.compositor.capabilities().reload_runs_config
+                && self.allow("make the monitor config executable")
+                && let Err(e) = utils::set_executable(&path)
+            {
+                self.set_error(tf(
+                    "Failed to make {} executable: {}",
+                    &[&path.display(), &e],
+                ));
+            }
+            self.session.wrote(path);
+            self.remember_monitor_config(content.clone());
+            self.last_saved = Some(history::now());
+            self.note_workspace_lines(&content);
+            self.save_recovery();
+        }
+        // KWin takes the layout as a command instead of a file to reload.
+        let command =
+            apply_command(self.compositor, &managed, &self.config.custom_modes);
+        let applied = match (command, live) {
+            (Some(command), _) => self.effects.run(&command).map(|()| false),
+            (None, Some(commands)) => commands
+                .iter()
+                .try_for_each(|command| self.effects.run(command))
+                .map(|()| !commands.is_empty()),
+            (None, None) => {
+                let config = &self.comp_monitor_config_path;
+                self.effects.reload(self.compositor, config)
+            }
+        };
+        self.save_phase = match applied {
+            Ok(true) => {
+                self.save_state = SaveState::Clean;
+                // What the reload changes comes back as events too.
+                let names: Vec<String> =
+                    managed.monitors.iter().map(|m| m.name.clone()).collect();
+                for name in names {
+                    self.expect(&name, 0);
+                }
+                let ms = self.config.reload_cooldown_ms;
+                SavePhase::Cooldown(Instant::now() + Duration::from_millis(ms))
+            }
+            Ok(false) => {
+                self.save_state = SaveState::Clean;
+                SavePhase::Idle
+            }
+            Err(e) => {
+                self.save_state = SaveState::ReloadFailed {
+                    reason: e.to_string(),
+                };
+                self.set_error(tf(
+                    "Failed to reload {}: {}",
+                    &[&self.compositor.label(), &e],
+                ));
+                self.alert_failure(self.panel);
+                SavePhase::Idle
+            }
+        };
+        self.note_skipped();
+        if self.allow("record layout history")
+            && let Err(e) = history::record(&layout)
+        {
+            self.set_error(tf("Failed to record layout history: {}", &[&e]));
+        }
+        self.report_snaps(snaps);
+        self.scan_stray_rules();
+    }
+
+    /// The monitor config, read again only once its file changed.
+    /// The monitors as last saved: from layout.toml with
+    /// `canonical_layout`, matched to live connectors by description,
+    /// otherwise as the monitor config sets them up.
+    fn saved_monitors(&self) -> io::Result<Vec<settings::ConfigMonitor>> {
+        let Some(layout) = &self.layout else {
+            let content = self.read_monitor_config()?;
+            return Ok(settings::config_monitors(self.compositor, &content));
+        };
+        Ok(layout
+            .monitors
+            .iter()
+            .map(|saved| settings::ConfigMonitor {
+                name: layout.live_name(&saved.name, &self.monitors),
+                ..saved.into()
+            })
+            .collect())
+    }
+
+    fn read_monitor_config(&self) -> io::Result<String> {
+        let path = &self.comp_monitor_config_path;
+        let modified = self.effects.modified(path);
+        if let Some(cached) = &*self.monitor_config.borrow()
+            && cached.path == *path
+            && Some(cached.modified) == modified
+        {
+            return Ok(cached.content.clone());
+        }
+        let content = self.effects.read(path)?;
+        *self.monitor_config.borrow_mut() = modified.map(|modified| {
+            CachedConfig {
+                path: path.clone(),
+                modified,
+                content: content.clone(),
+            }
+        });
+        Ok(content)
+    }
+
+    /// Keeps what was just written as the monitor config, so it is not
+    /// read back.
+    fn remember_monitor_config(&mut self, content: String) {
+        // A dry run wrote nothing, so what was read still stands.
+        if self.effects.is_dry_run() {
+            return;
+        }
+        let path = &self.comp_monitor_config_path;
+        *self.monitor_config.get_mut() =
+            self.effects.modified(path).map(|modified| CachedConfig {
+                path: path.clone(),
+                modified,
+                content,
+            });
+    }
+
+    /// Rewrites the boot-safe config next to the monitor config.
+    fn save_recovery(&mut self) {
+        if !self.allow("write the recovery config") {
+            return;
+        }
+        let path = &self.comp_monitor_config_path;
+        match recovery::save(self.compositor, &self.monitors, path) {
+            Ok(()) => self
+                .session
+                .wrote(recovery::recovery_path(self.compositor, path)),
+            Err(e) => self.set_error(tf(
+                "Failed to write the recovery config: {}",
+                &[&e],
+            )),
+        }
+    }
+
+    /// `xwlm recover`: sends what the recovery config says for every
+    /// connected monitor that runs differently.
+    pub fn recover(&mut self) -> Result<(), SendError<WlMonitorAction>> {
+        let path = recovery::recovery_path(
+            self.compositor,
+            &self.comp_monitor_config_path,
+        );
+        let content = match self.effects.read(&path) {
+            Ok(content) => content,
+            Err(e) => {
+                self.set_error(tf(
+                    "Failed to read {}: {}",
+                    &[&path.display(), &e],
+                ));
+                return Ok(());
+            }
+        };
+        let config = settings::config_monitors(self.compositor, &content);
+        let rows = reconcile::diverging(&config, &self.monitors);
+        if rows.is_empty() {
+            self.set_status(tf(
+                "The live layout matches {}",
+                &[&path.display()],
+            ));
+            return Ok(());
+        }
+        let names: Vec<String> = rows.iter().map(|r| r.name.clone()).collect();
+        let layout = Layout {
+            monitors: rows.into_iter().map(|r| r.config).collect(),
+            workspaces: Vec::new(),
+        };
+        let Some(notes) = self.restore_layout(&layout)? else {
+            return Ok(());
+        };
+        let mut status = tf(
+            "Applied {} to {}",
+            &[&path.display(), &names.join(", ")],
+        );
+        for note in notes {
+            status.push_str("; ");
+            status.push_str(&note);
+        }
+        self.set_status(status);
+        Ok(())
+    }
+
+    /// Points each workspace row at the line `content` has it on.
+    fn note_workspace_lines(&mut self, content: &str) {
+        let rules = parse_workspaces(self.compositor, content);
+        for ws in &mut self.workspace_assignments {
+            let rule = rules.iter().find(|r| r.id == ws.id);
+            ws.line = rule.and_then(|r| r.line);
+        }
+    }
+
+    /// Ends the reload cooldown once it is over.
+    fn settle(&mut self, now: Instant) {
+        if let SavePhase::Cooldown(until) = self.save_phase
+            && now >= until
+        {
+            self.save_phase = SavePhase::Idle;
+        }
+    }
+
+    /// Runs the save that waited for the compositor, once it has settled.
+    pub fn flush_saves(&mut self, now: Instant) {
+        self.settle(now);
+        if self.save_phase == SavePhase::Idle && self.save_queued {
+            self.save_queued = false;
+            self.save_config();
+        }
+    }
+
+    /// Waits out the reload cooldown for a save that is still waiting, so
+    /// quitting does not drop it.
+    pub fn finish_saves(&mut self) {
+        if self.save_queued
+            && let Some(wait) = self.settle_time(Instant::now())
+        {
+            std::thread::sleep(wait);
+        }
+        self.flush_saves(Instant::now());
+    }
+
+    /// How long until the reload cooldown is over, if one is running, or
+    /// until a waiting save stops waiting for the compositor to report
+    /// back.
+    pub fn settle_time(&self, now: Instant) -> Option<Duration> {
+        let echoes = self.echoes_due(now).filter(|_| self.save_queued);
+        let until = match self.save_phase {
+            SavePhase::Cooldown(until) => {
+                Some(echoes.map_or(until, |echoes| until.max(echoes)))
+            }
+            _ => echoes,
+        };
+        until.map(|until| until.saturating_duration_since(now))
+    }
+
+    /// Marks the monitors as changed since the last save.
+    pub fn mark_dirty(&mut self) {
+        if self.save_state != SaveState::Dirty {
+            self.before_dirty = std::mem::take(&mut self.save_state);
+            self.save_state = SaveState::Dirty;
+        }
+    }
+
+    /// Leaves the changes unsaved after a save that failed. A change the
+    /// compositor rejects afterwards cannot make them count as saved.
+    fn keep_unsaved(&mut self) {
+        self.save_state = SaveState::Dirty;
+        self.before_dirty = SaveState::Dirty;
+    }
+
+    pub fn needs_save(&self) -> bool {
+        self.save_state == SaveState::Dirty
+    }
+
+    /// Shows why the last reload failed, for the footer's `!`.
+    pub fn show_reload_failure(&mut self) {
+        if let SaveState::ReloadFailed { reason } = &self.save_state {
+            let msg = tf(
+                "Failed to reload {}: {}",
+                &[&self.compositor.label(), reason],
+            );
+            self.set_error(msg);
+        }
+    }
+
+    /// Whether xwlm can quit without losing anything, after finishing the
+    /// saves that wait. Otherwise asks first, as it does with
+    /// `confirm_quit` once something was applied.
+    pub fn request_quit(&mut self) -> bool {
+        self.finish_saves();
+        let recap = self.config.confirm_quit && self.has_session_changes();
+        if self.save_state == SaveState::Clean
+            && !self.has_unsaved_changes()
+            && !recap
+        {
+            return true;
+        }
+        self.confirm = Some(Confirm::Quit);
+        false
+    }
+
+    pub fn is_reloading(&self) -> bool {
+        self.save_phase != SavePhase::Idle
+    }
+
+    pub fn last_saved(&self) -> Option<i64> {
+        self.last_saved
+    }
+
+    /// Whether anything is staged or applied but not yet written.
+    pub fn has_unsaved_changes(&self) -> bool {
+        self.needs_save()
+            || self.has_pending_positions()
+            || self.has_pending_transforms()
+            || self.has_pending_settings()
+            || self.has_pending_toggles()
+            || self.has_pending_workspaces()
+            || !self.pending_options.is_empty()
+    }
+
+    /// Says where saving moved monitors the UI shows elsewhere, once for
+    /// each set of moves.
+    fn report_snaps(&mut self, snaps: Vec<Snap>) {
+        if snaps.is_empty() || snaps == self.reported_snaps {
+            self.reported_snaps = snaps;
+            return;
+        }
+        let moves: Vec<String> = snaps
+            .iter()
+            .map(|s| {
+                let (fx, fy) = s.from;
+                let (tx, ty) = s.to;
+                format!("{} {fx},{fy} → {tx},{ty}", s.name)
+            })
+            .collect();
+        self.set_status(tn(
+            "Saved {} monitor snapped to whole logical pixels: {}",
+            "Saved {} monitors snapped to whole logical pixels: {}",
+            snaps.len(),
+            &[&moves.join(", ")],
+        ));
+        self.reported_snaps = snaps;
+    }
+
+    fn scan_stray_rules(&mut self) {
+        let rules = match &self.main_config_path {
+            Some(main) => extraction::stray_monitor_rules(
+                self.compositor,
+                main,
+                &self.comp_monitor_config_path,
+            ),
+            None => Vec::new(),
+        };
+        if rules != self.stray_rules {
+            self.show_stray_rules = !rules.is_empty();
+            self.stray_rules = rules;
+        }
+    }
+
+    /// e.g. "2 monitor rules outside monitors.conf, first at
+    /// hyprland.conf:12"
+    pub fn stray_rules_problem(&self) -> Option<String> {
+        let first = self.stray_rules.first()?;
+        Some(tn(
+            "{} monitor rule outside {}, first at {}:{}",
+            "{} monitor rules outside {}, first at {}:{}",
+            self.stray_rules.len(),
+            &[
+                &self.comp_monitor_config_path.display(),
+                &first.path.display(),
+                &first.line,
+            ],
+        ))
+    }
+
+    pub fn dismiss_stray_rules(&mut self) {
+        self.show_stray_rules = false;
+    }
+
+    /// Takes the stray rules out of the main config and writes the live
+    /// layout, so only the managed file configures monitors.
+    pub fn extract_stray_rules(&mut self) {
+        let what =
+            format!("remove {} stray monitor rules", self.stray_rules.len());
+        if self.allow(&what)
+            && let Err(e) = extraction::remove_rules(&self.stray_rules)
+        {
+            self.set_error(tf("Failed to remove monitor rules: {}", &[&e]));
+            return;
+        }
+        let n = self.stray_rules.len();
+        self.stray_rules.clear();
+        self.show_stray_rules = false;
+        self.set_status(tn(
+            "Removed {} stray monitor rule",
+            "Removed {} stray monitor rules",
+            n,
+            &[],
+        ));
+        self.mark_dirty();
+        self.save_config();
+    }
+
+    /// Monitors the user never changed whose lines in the existing config
+    /// would differ from `content`.
+    fn untouched_monitor_changes(&self, content: &str) -> Vec<MonitorChange> {
+        if self.config.include_untouched_monitors {
+            return Vec::new();
+        }
+        let Ok(existing) = self.read_monitor_config() else {
+            return Vec::new();
+        };
+        let names = self
+            .monitors
+            .iter()
+            .map(|m| m.name.as_str())
+            .filter(|name| {
+                !self.touched.contains(*name) && self.is_managed(name)
+            });
+        // Saving keeps what xwlm does not write; that is no change.
+        let content =
+            diff::keep_output_directives(self.compositor, &existing, content);
+        diff::changed_monitors(self.compositor, &existing, &content, names)
+    }
+
+    pub fn config_problem(&self) -> Option<String> {
+        self.config_check.as_ref()?.problem(
+            self.compositor,
+            &self.comp_monitor_config_path,
+        )
+    }
+
+    pub fn dismiss_config_check(&mut self) {
+        self.config_check = None;
+    }
+
+    /// Writes the current layout to the missing monitor config.
+    pub fn create_monitor_config(&mut self) {
+        self.config_check = None;
+        let path = self.comp_monitor_config_path.clone();
+        if let Some(parent) = path.parent()
+            && self.allow(&format!("create {}", parent.display()))
+            && let Err(e) = fs::create_dir_all(parent)
+        {
+            self.set_error(tf("Failed to save config: {}", &[&e]));
+            return;
+        }
+        self.mark_dirty();
+        self.save_config();
+        if self.error_message.is_none() {
+            let path = self.comp_monitor_config_path.display();
+            self.set_status(tf("Created {}", &[&path]));
+        }
+    }
+
+    /// Saves the config setup produced and starts over with it, keeping
+    /// the monitors already reported.
+    pub fn rerun_setup(&mut self, config: Config) {
+        let saved = if self.allow("write xwlm settings") {
+            xwlm_config::save_config(&config)
+        } else {
+            Ok(())
+        };
+        if let Err(e) = saved {
+            self.set_error(tf("Failed to save settings: {}", &[&e]));
+            return;
+        }
+        let monitors = std::mem::take(&mut self.monitors);
+        let effects =
+            std::mem::replace(&mut self.effects, Box::new(DryRun::default()));
+        *self = App::new(effects, config);
+        self.set_monitors(monitors);
+    }
+
+    /// Settles the pending untouched-monitor prompt for the rest of the
+    /// session and retries the save it interrupted.
+    pub fn resolve_untouched(&mut self, choice: UntouchedChoice) {
+        if self.untouched_changes.take().is_none() {
+            return;
+        }
+        if choice == UntouchedChoice::Always {
+            self.config.include_untouched_monitors = true;
+            if let Err(e) = self.save_settings() {
+                self.set_error(tf("Failed to save settings: {}", &[&e]));
+            }
+        }
+        self.untouched_choice = Some(choice);
+        self.save_config();
+    }
+
+    pub fn open_history(&mut self) {
+        let mut entries = match history::load_history() {
+            Ok(history) => history.snapshots,
+            Err(e) => {
+                self.set_error(tf("Failed to load layout history: {}", &[&e]));
+                return;
+            }
+        };
+        if entries.is_empty() {
+            self.set_status(t("No saved layouts yet"));
+            return;
+        }
+        entries.reverse();
+        self.history = Some(HistoryBrowser {
+            entries,
+            state: ListState::default().with_selected(Some(0)),
+        });
+    }
+
+    pub fn close_history(&mut self) {
+        self.history = None;
+    }
+
+    /// `=`: compares the live monitors with the monitor config. At startup
+    /// (`quiet`) nothing is said when they agree or the config cannot be
+    /// read, as the config check covers that.
+    pub fn open_reconcile(&mut self, quiet: bool) {
+        let path = match self.layout {
+            Some(_) => PathBuf::from(layout::LAYOUT_PATH),
+            None => self.comp_monitor_config_path.clone(),
+        };
+        let config = match self.saved_monitors() {
+            Ok(config) => config,
+            Err(e) if !quiet => {
+                self.set_error(tf(
+                    "Failed to read {}: {}",
+                    &[&path.display(), &e],
+                ));
+                return;
+            }
+            Err(_) => return,
+        };
+        let mut rows = reconcile::diverging(&config, &self.monitors);
+        rows.retain(|row| self.is_managed(&row.name));
+        if rows.is_empty() {
+            if !quiet {
+                self.set_status(tf(
+                    "The live layout matches {}",
+                    &[&path.display()],
+                ));
+            }
+            return;
+        }
+        self.reconcile = Some(Reconciliation {
+            rows,
+            state: ListState::default().with_selected(Some(0)),
+        });
+    }
+
+    pub fn close_reconcile(&mut self) {
+        self.reconcile = None;
+    }
+
+    pub fn reconcile_step(&mut self, forward: bool) {
+        let Some(view) = &mut self.reconcile else {
+            return;
+        };
+        let len = view.rows.len();
+        let current = view.state.selected().unwrap_or(0);
+        let next = if forward {
+            (current + 1) % len
+        } else {
+            (current + len - 1) % len
+        };
+        view.state.select(Some(next));
+    }
+
+    /// Takes the selected row, or every row, out of the `=` view, closing
+    /// it once none are left.
+    fn take_reconciled(&mut self, all: bool) -> Vec<Divergence> {
+        let Some(view) = &mut self.reconcile else {
+            return Vec::new();
+        };
+        let taken = if all {
+            std::mem::take(&mut view.rows)
+        } else {
+            let Some(idx) = view.state.selected() else {
+                return Vec::new();
+            };
+            vec![view.rows.remove(idx)]
+        };
+        if view.rows.is_empty() {
+            self.reconcile = None;
+        } else {
+            let last = view.rows.len() - 1;
+            let selected = view.state.selected().unwrap_or(0).min(last);
+            view.state.select(Some(selected));
+        }
+        taken
+    }
+
+    /// `l`/`L` in the `=` view: writes how the selected monitor, or every
+    /// listed one, runs now into the monitor config.
+    pub fn adopt_live(&mut self, all: bool) {
+        let rows = self.take_reconciled(all);
+        if rows.is_empty() {
+            return;
+        }
+        let names: Vec<&str> = rows.iter().map(|r| r.name.as_str()).collect();
+        for name in &names {
+            self.touched.insert(name.to_string());
+        }
+        // A save may be waiting to ask about exactly these monitors.
+        if let Some(changes) = &mut self.untouched_changes {
+            changes.retain(|c| !names.contains(&c.name.as_str()));
+            if changes.is_empty() {
+                self.untouched_changes = None;
+            }
+        }
+        self.set_status(tf(
+            "Writing the live settings of {} to {}",
+            &[&names.join(", "), &self.comp_monitor_config_path.display()],
+        ));
+        self.mark_dirty();
+        self.save_config();
+    }
+
+    /// `c`/`C` in the `=` view: sends what the config says for the selected
+    /// monitor, or every listed one.
+    pub fn apply_config(
+        &mut self,
+        all: bool,
+    ) -> Result<(), SendError<WlMonitorAction>> {
+        let Some(view) = &self.reconcile else {
+            return Ok(());
+        };
+        let chosen: Vec<&Divergence> = if all {
+            view.rows.iter().collect()
+        } else {
+            view.selected().into_iter().collect()
+        };
+        let none_left = !self.monitors.iter().any(|m| {
+            chosen
+                .iter()
+                .find(|d| d.name == m.name)
+                .map_or(m.enabled, |d| d.config.enabled)
+        });
+        if none_left {
+            self.set_error(t("Applying that would leave no monitor on"));
+            return Ok(());
+        }
+        let rows = self.take_reconciled(all);
+        let names: Vec<String> = rows.iter().map(|r| r.name.clone()).collect();
+        let layout = Layout {
+            monitors: rows.into_iter().map(|r| r.config).collect(),
+            workspaces: Vec::new(),
+        };
+        let Some(fallbacks) = self.restore_layout(&layout)? else {
+            return Ok(());
+        };
+        let mut status =
+            tf("Applying the config to {}", &[&names.join(", ")]);
+        for fallback in fallbacks {
+            status.push_str("; ");
+            status.push_str(&fallback);
+        }
+        self.set_status(status);
+        Ok(())
+    }
+
+    pub fn open_timeline(&mut self) {
+        if self.timeline.is_empty() {
+            self.set_status(t("No monitor events yet"));
+            return;
+        }
+        self.timeline_view = Some(ListState::default().with_selected(Some(0)));
+    }
+
+    /// Turns this into an observer of the compositor: the config need not
+    /// exist, so nothing about it is asked, and the timeline is open from
+    /// the start.
+    pub fn observe(&mut self) {
+        self.observing = true;
+        self.config_check = None;
+        self.show_stray_rules = false;
+        self.timeline_view = Some(ListState::default().with_selected(Some(0)));
+    }
+
+    /// Whether `panel` is shown at all: the Workspaces panel only where
+    /// workspace rules can be written.
+    pub fn has_panel(&self, panel: Panel) -> bool {
+        panel != Panel::Workspace
+            || self.compositor.capabilities().supports_workspace_rules
+    }
+
+    /// Marks this run as the first one, right after setup wrote the
+    /// config, so [`App::offer_tour`] shows the tour.
+    pub fn first_launch(&mut self) {
+        self.ui_state.toured = false;
+    }
+
+    /// Starts the tour on the first launch, or when `--tour` asks for it.
+    /// A dry run cannot remember having shown it, so it only tours when
+    /// asked.
+    pub fn offer_tour(&mut self, asked: bool) {
+        let unseen = !self.ui_state.toured && !self.is_dry_run();
+        if asked || unseen {
+            self.tour = Some(0);
+            self.tour_return = self.panel;
+            self.panel = TOUR[0];
+        }
+    }
+
+    /// The panel the tour is showing.
+    pub fn touring(&self) -> Option<Panel> {
+        self.tour.map(|step| TOUR[step])
+    }
+
+    /// Any key: on to the next panel, or done after the last.
+    pub fn tour_step(&mut self) {
+        let next = self.tour.and_then(|step| {
+            (step + 1..TOUR.len()).find(|&i| self.has_panel(TOUR[i]))
+        });
+        match next {
+            Some(step) => {
+                self.tour = Some(step);
+                self.panel = TOUR[step];
+            }
+            None => self.end_tour(),
+        }
+    }
+
+    /// Esc, or past the last panel. Remembered, so the tour only comes
+    /// back with `--tour`.
+    pub fn end_tour(&mut self) {
+        self.tour = None;
+        self.panel = self.tour_return;
+        if self.ui_state.toured {
+            return;
+        }
+        self.ui_state.toured = true;
+        if self.allow("remember the tour")
+            && let Err(e) = ui_state::save(&self.ui_state)
+        {
+            self.set_error(tf("Failed to remember the tour: {}", &[&e]));
+        }
+    }
+
+    pub fn close_timeline(&mut self) {
+        self.timeline_view = None;
+    }
+
+    pub fn timeline_step(&mut self, forward: bool) {
+        let len = self.timeline.len();
+        let Some(state) = &mut self.timeline_view else {
+            return;
+        };
+        let current = state.selected().unwrap_or(0);
+        let next = if forward {
+            (current + 1).min(len.saturating_sub(1))
+        } else {
+            current.saturating_sub(1)
+        };
+        state.select(Some(next));
+    }
+
+    pub fn history_step(&mut self, forward: bool) {
+        let Some(browser) = &mut self.history else {
+            return;
+        };
+        let len = browser.entries.len();
+        let current = browser.state.selected().unwrap_or(0);
+        let next = if forward {
+            (current + 1) % len
+        } else {
+            (current + len - 1) % len
+        };
+        browser.state.select(Some(next));
+    }
+
+    pub fn restore_selected_snapshot(
+        &mut self,
+    ) -> Result<(), SendError<WlMonitorAction>> {
+        let Some(snapshot) =
+            self.history.take().and_then(|b| b.selected().cloned())
+        else {
+            return Ok(());
+        };
+        let disables: Vec<String> = snapshot
+            .layout
+            .monitors
+            .iter()
+            .filter(|saved| !saved.enabled)
+            .map(|saved| {
+                snapshot.layout.live_name(&saved.name, &self.monitors)
+            })
+            .filter(|name| {
+                self.monitors.iter().any(|m| m.name == *name && m.enabled)
+            })
+            .collect();
+        if !disables.is_empty() {
+            self.ask(Confirm::RestoreSnapshot { snapshot, disables });
+            return Ok(());
+        }
+        self.restore_snapshot(&snapshot)
+    }
+
+    fn restore_snapshot(
+        &mut self,
+        snapshot: &Snapshot,
+    ) -> Result<(), SendError<WlMonitorAction>> {
+        let Some(fallbacks) = self.restore_layout(&snapshot.layout)? else {
+            return Ok(());
+        };
+        let mut status = tf(
+            "Restoring layout from {}",
+            &[&snapshot.when(history::now())],
+        );
+        for fallback in fallbacks {
+            status.push_str("; ");
+            status.push_str(&fallback);
+        }
+        self.set_status(status);
+        Ok(())
+    }
+
+    /// Sends whatever actions bring the live monitors to `layout`, matched
+    /// by description like the canonical layout. Saved monitors that are not
+    /// connected are skipped. Returns a note for each saved mode that had to
+    /// be swapped for another and each bar on an output it moves or
+    /// switches off, or `None` when an unmanaged monitor it changes held
+    /// it back.
+    fn restore_layout(
+        &mut self,
+        layout: &Layout,
+    ) -> Result<Option<Vec<String>>, SendError<WlMonitorAction>> {
+        if !self.clear_of_unmanaged(self.restored_outputs(layout)) {
+            return Ok(None);
+        }
+        let mut fallbacks = Vec::new();
+        let mut moved = Vec::new();
+        self.reset_positions();
+        let mut sent = false;
+        for saved in &layout.monitors {
+            let name = layout.live_name(&saved.name, &self.monitors);
+            let Some(live) =
+                self.monitors.iter().find(|m| m.name == name).cloned()
+            else {
+                continue;
+            };
+            self.touched.insert(name.clone());
+            let saved_mode = ModeSpec {
+                width: saved.width,
+                height: saved.height,
+                refresh_rate: saved.refresh_rate,
+            };
+            let mode = self.saved_mode(&live, saved_mode, &mut fallbacks);
+            if live.enabled != saved.enabled {
+                self.send_action(WlMonitorAction::Toggle {
+                    name: name.clone(),
+                    mode: saved.enabled.then_some((
+                        mode.width,
+                        mode.height,
+                        mode.refresh_rate,
+                    )),
+                    position: saved.enabled.then_some((saved.x, saved.y)),
+                })?;
+                sent = true;
+            }
+            if !saved.enabled {
+                if live.enabled {
+                    moved.push(name);
+                }
+                continue;
+            }
+            if ModeSpec::current(&live) != Some(mode) {
+                self.switch_mode(&name, mode)?;
+                sent = true;
+            }
+            if (live.scale - saved.scale).abs() > 0.001 {
+                self.send_action(WlMonitorAction::SetScale {
+                    name: name.clone(),
+                    scale: saved.scale,
+                })?;
+                sent = true;
+            }
+            if live.transform != saved.transform {
+                self.send_action(WlMonitorAction::SetTransform {
+                    name: name.clone(),
+                    transform: saved.transform,
+                })?;
+                sent = true;
+            }
+            if (live.position.x, live.position.y) != (saved.x, saved.y) {
+                self.send_action(WlMonitorAction::SetPosition {
+                    name: name.clone(),
+                    x: saved.x,
+                    y: saved.y,
+                })?;
+                sent = true;
+                moved.push(name);
+            }
+        }
+        fallbacks.extend(self.bar_notes(&moved));
+
+        for rule in &layout.workspaces {
+            let name = layout.live_name(&rule.monitor, &self.monitors);
+            let known = self.monitors.iter().any(|m| m.name == name);
+            if let Some(ws) = self
+                .workspace_assignments
+                .iter_mut()
+                .find(|ws| ws.id == rule.id)
+            {
+                ws.reserved = (!known && !rule.monitor.is_empty())
+                    .then(|| rule.monitor.clone());
+                ws.monitor = known.then_some(name);
+                ws.fallbacks = rule.fallbacks.clone();
+                ws.is_default = rule.is_default;
+                ws.is_persistent = rule.is_persistent;
+            }
+        }
+
+        // Monitor changes save once the compositor reports them back.
+        self.mark_dirty();
+        if !sent {
+            self.save_config();
+        }
+        Ok(Some(fallbacks))
+    }
+
+    /// The connected monitors `layout` runs differently from now.
+    fn restored_outputs(&self, layout: &Layout) -> Vec<String> {
+        layout
+            .monitors
+            .iter()
+            .filter_map(|saved| {
+                let name = layout.live_name(&saved.name, &self.monitors);
+                let live = self.monitors.iter().find(|m| m.name == name)?;
+                let mode = ModeSpec {
+                    width: saved.width,
+                    height: saved.height,
+                    refresh_rate: saved.refresh_rate,
+                };
+                let differs = live.enabled != saved.enabled
+                    || saved.enabled
+                        && (ModeSpec::current(live) != Some(mode)
+                            || (live.scale - saved.scale).abs() > 0.001
+                            || live.transform != saved.transform
+                            || (live.position.x, live.position.y)
+                                != (saved.x, saved.y));
+                differs.then_some(name)
+            })
+            .collect()
+    }
+
+    /// Puts the monitors back as they were before the last applied change.
+    pub fn undo(&mut self) -> Result<(), SendError<WlMonitorAction>> {
+        self.step_history(false, false)
+    }
+
+    /// Makes the last undone change again.
+    pub fn redo(&mut self) -> Result<(), SendError<WlMonitorAction>> {
+        self.step_history(true, false)
+    }
+
+    /// Undoes, or with `redo` redoes, once the change has passed the same
+    /// checks as any other: the last monitor going off, unmanaged and
+    /// captured monitors, and the guard on the terminal's monitor.
+    fn step_history(
+        &mut self,
+        redo: bool,
+        last_confirmed: bool,
+    ) -> Result<(), SendError<WlMonitorAction>> {
+        let Some(actions) = self.undo.peek(redo) else {
+            self.set_status(if redo {
+                t("Nothing to redo")
+            } else {
+                t("Nothing to undo")
+            });
+            return Ok(());
+        };
+        let changed: Vec<&WlMonitorAction> = actions
+            .iter()
+            .filter(|action| {
+                self.monitors.iter().any(|m| {
+                    m.name == undo::target(action) && undo::changes(action, m)
+                })
+            })
+            .collect();
+        let switched_off = |name: &str| {
+            changed.iter().any(|action| {
+                matches!(action, WlMonitorAction::Toggle { position: None, .. })
+                    && undo::target(action) == name
+            })
+        };
+        let names: Vec<String> = changed
+            .iter()
+            .map(|action| undo::target(action).to_string())
+            .collect();
+        let captured: Vec<String> = changed
+            .iter()
+            .filter(|action| {
+                matches!(action, WlMonitorAction::SwitchMode { .. })
+                    || switched_off(undo::target(action))
+            })
+            .map(|action| undo::target(action).to_string())
+            .collect();
+        let none_left = self.enabled_count() > 0
+            && !self
+                .monitors
+                .iter()
+                .any(|m| m.enabled && !switched_off(&m.name));
+        let risky = self.terminal_output.as_ref().and_then(|name| {
+            let live =
+                self.monitors.iter().find(|m| &m.name == name && m.enabled)?;
+            let revert = if switched_off(name) {
+                Revert::Enable(live.position.x, live.position.y)
+            } else {
+                changed
+                    .iter()
+                    .filter(|action| undo::target(action) == name)
+                    .find_map(|action| match action {
+                        WlMonitorAction::SetTransform { .. } => {
+                            Some(Revert::Transform(live.transform))
+                        }
+                        WlMonitorAction::SetScale { scale, .. }
+                            if (scale - live.scale).abs()
+                                >= RISKY_SCALE_JUMP =>
+                        {
+                            Some(Revert::Scale(live.scale))
+                        }
+                        _ => None,
+                    })?
+            };
+            Some((name.clone(), revert))
+        });
+        if none_left && !last_confirmed {
+            self.replay_pending = Some(redo);
+            self.ask(Confirm::LastMonitor);
+            return Ok(());
+        }
+        if !self.clear_of_unmanaged(names)
+            || !self.clear_of_captures(captured)
+        {
+            return Ok(());
+        }
+        let actions = if redo {
+            self.undo.redo(&self.monitors)
+        } else {
+            self.undo.undo(&self.monitors)
+        };
+        if let Some(actions) = actions {
+            self.replay(actions)?;
+            self.guard(risky);
+        }
+        Ok(())
+    }
+
+    /// How many changes [`App::undo`] can take back.
+    pub fn undo_depth(&self) -> usize {
+        self.undo.depth()
+    }
+
+    /// Sends the actions of an undo snapshot that change something.
+    /// Monitors that are gone are skipped.
+    fn replay(
+        &mut self,
+        actions: Vec<WlMonitorAction>,
+    ) -> Result<(), SendError<WlMonitorAction>> {
+        self.reset_positions();
+        let mut sent = false;
+        let mut toggled = HashSet::new();
+        for action in actions {
+            let name = undo::target(&action).to_string();
+            let Some(live) = self.monitors.iter().find(|m| m.name == name)
+            else {
+                continue;
+            };
+            // A monitor turned on gets its mode and position with the
+            // toggle.
+            let on_toggle = matches!(
+                action,
+                WlMonitorAction::SwitchMode { .. }
+                    | WlMonitorAction::SetPosition { .. }
+            );
+            if on_toggle && toggled.contains(&name)
+                || !undo::changes(&action, live)
+            {
+                continue;
+            }
+            if matches!(action, WlMonitorAction::Toggle { .. }) {
+                toggled.insert(name.clone());
+            }
+            self.send_action(action)?;
+            self.touched.insert(name);
+            sent = true;
+        }
+        // Monitor changes save once the compositor reports them back.
+        self.mark_dirty();
+        if !sent {
+            self.save_config();
+        }
+        Ok(())
+    }
+
+    /// The mode to send for a saved one: as saved when it is advertised or
+    /// one of the monitor's custom modes, otherwise the closest advertised
+    /// mode, noted in `fallbacks`.
+    fn saved_mode(
+        &self,
+        live: &WlMonitor,
+        saved: ModeSpec,
+        fallbacks: &mut Vec<String>,
+    ) -> ModeSpec {
+        let custom = self.config.custom_modes.iter().any(|c| {
+            c.monitor == live.name
+                && (c.width, c.height, c.refresh_rate)
+                    == (saved.width, saved.height, saved.refresh_rate)
+        });
+        if custom {
+            return saved;
+        }
+        match resolve_mode(live, saved) {
+            Some((_, ModeMatch::Exact)) | None => saved,
+            Some((mode, _)) => {
+                fallbacks.push(tf(
+                    "saved {} unavailable on {}, using {}",
+                    &[&saved, &live.name, &mode],
+                ));
+                mode
+            }
+        }
+    }
+
+    fn workspace_rules(&self) -> Vec<WorkspaceRule> {
+        self.workspace_assignments
+            .iter()
+            .map(|ws| WorkspaceRule {
+                id: ws.id,
+                monitor: ws
+                    .monitor
+                    .clone()
+                    .or_else(|| ws.reserved.clone())
+                    .unwrap_or_default(),
+                fallbacks: ws.fallbacks.clone(),
+                is_default: ws.is_default,
+                is_persistent: ws.is_persistent,
+                line: ws.line,
+            })
+            .collect()
+    }
+
+    /// The first start with `canonical_layout` on has no layout.toml yet,
+    /// so it is captured from what the existing compositor config set up.
+    fn migrate_layout(&mut self) {
+        if !self.layout_needs_migration || self.monitors.is_empty() {
+            return;
+        }
+        self.layout_needs_migration = false;
+        let layout = self.capture_layout();
+        let saved = if self.allow("write layout.toml") {
+            layout::save_layout(&layout)
+        } else {
+            Ok(())
+        };
+        match saved {
+            Ok(()) => self.set_status(tf(
+                "Created layout.toml from {}",
+                &[&self.comp_monitor_config_path.display()],
+            )),
+            Err(e) => self.set_error(tf("Failed to create layout: {}", &[&e])),
+        }
+        self.layout = Some(layout);
+    }
+
+    pub fn reset_positions(&mut self) {
+        self.pending_positions.clear();
+        self.pending_transforms.clear();
+        self.pending_modes.clear();
+        self.pending_scales.clear();
+        self.scale_edits.clear();
+        self.pending_workspaces.clear();
+        self.pending_toggles.clear();
+        self.pending_options.clear();
+    }
+
+    pub fn select_next_monitor(&mut self) {
+        if self.monitors.is_empty() {
+            return;
+        }
+        self.select_index((self.selected_monitor + 1) % self.monitors.len());
+        self.mode_selection.select(Some(0));
+        self.sync_panel_state();
+    }
+
+    /// Selects a monitor by connector name, or by its 1-based position in
+    /// the list.
+    pub fn select_monitor_named(&mut self, name: &str) -> bool {
+        let idx = self.monitors.iter().position(|m| m.name == name).or_else(
+            || {
+                name.parse::<usize>()
+                    .ok()
+                    .filter(|&n| (1..=self.monitors.len()).contains(&n))
+                    .map(|n| n - 1)
+            },
+        );
+        let Some(idx) = idx else {
+            return false;
+        };
+        self.select_index(idx);
+        self.sync_panel_state();
+        true
+    }
+
+    pub fn select_prev_monitor(&mut self) {
+        if self.monitors.is_empty() {
+            return;
+        }
+        self.select_index(if self.selected_monitor == 0 {
+            self.monitors.len() - 1
+        } else {
+            self.selected_monitor - 1
+        });
+        self.mode_selection.select(Some(0));
+        self.sync_panel_state();
+    }
+
+    fn sync_panel_state(&mut self) {
+        self.workspace_leaving = None;
+        self.keep_workspace_listed();
+        let Some(monitor) = self.monitors.get(self.selected_monitor) else {
+            return;
+        };
+        self.edited = PanelEdits::default();
+        if let Some(tidx) = TRANSFORMS.iter().position(|&x| x == monitor.transform) {
+            self.transform_selection.select(Some(tidx));
+        }
+        let current = monitor.modes.iter().position(|m| m.is_current);
+        let row = current.and_then(|i| self.mode_row(i)).unwrap_or(0);
+        self.mode_selection.select(Some(row));
+    }
+
+    /// Follows a Changed event for the selected monitor, keeping any
+    /// panel value the user is still editing.
+    fn merge_panel_state(&mut self, previous: &WlMonitor) {
+        let Some(monitor) = self.monitors.get(self.selected_monitor) else {
+            return;
+        };
+        if monitor.transform != previous.transform && !self.edited.transform
+        {
+            let tidx = TRANSFORMS.iter().position(|&x| x == monitor.transform);
+            if let Some(tidx) = tidx {
+                self.transform_selection.select(Some(tidx));
+            }
+        }
+        if ModeSpec::current(monitor) != ModeSpec::current(previous)
+            && !self.edited.mode
+            && let Some(idx) = monitor.modes.iter().position(|m| m.is_current)
+            && let Some(row) = self.mode_row(idx)
+        {
+            self.mode_selection.select(Some(row));
+        }
+    }
+
+    /// `Enter` on a workspace row with nothing staged: opens the list of
+    /// monitors the workspace goes to.
+    pub fn open_fallback_editor(&mut self) {
+        if !self.compositor.capabilities().supports_workspace_fallbacks {
+            self.set_status(tf(
+                "{} has no fallback monitors for workspaces",
+                &[&self.compositor.label()],
+            ));
+            return;
+        }
+        let Some(row) = self.workspace_selection.selected() else {
+            return;
+        };
+        let Some(ws) = self.get_effective_workspace(row) else {
+            return;
+        };
+        let primary = ws.monitor.clone().or(ws.reserved.clone());
+        let chosen: Vec<String> =
+            primary.into_iter().chain(ws.fallbacks.clone()).collect();
+        let connected = self.monitors.iter().map(|m| m.name.clone());
+        let reserved = self.workspace_assignments.iter().flat_map(|w| {
+            w.reserved.iter().chain(&w.fallbacks).cloned()
+        });
+        let mut others: Vec<String> = Vec::new();
+        for name in connected.chain(reserved) {
+            if !chosen.contains(&name) && !others.contains(&name) {
+                others.push(name);
+            }
+        }
+        if chosen.is_empty() && others.is_empty() {
+            self.set_status(t("No monitors to assign workspaces to"));
+            return;
+        }
+        self.fallback_editor = Some(FallbackEditor {
+            row,
+            id: ws.id,
+            chosen,
+            others,
+            state: ListState::default().with_selected(Some(0)),
+        });
+    }
+
+    pub fn close_fallback_editor(&mut self) {
+        self.fallback_editor = None;
+    }
+
+    pub fn fallback_step(&mut self, forward: bool) {
+        let Some(editor) = &mut self.fallback_editor else {
+            return;
+        };
+        let len = editor.len();
+        let current = editor.cursor();
+        let next = if forward {
+            (current + 1) % len
+        } else {
+            (current + len - 1) % len
+        };
+        editor.state.select(Some(next));
+    }
+
+    /// Space: takes the highlighted monitor off the list, or adds it last.
+    pub fn toggle_fallback(&mut self) {
+        let Some(editor) = &mut self.fallback_editor else {
+            return;
+        };
+        let cursor = editor.cursor();
+        if cursor < editor.chosen.len() {
+            let name = editor.chosen.remove(cursor);
+            editor.others.insert(0, name);
+            editor.state.select(Some(editor.chosen.len()));
+        } else {
+            let name = editor.others.remove(cursor - editor.chosen.len());
+            editor.chosen.push(name);
+            editor.state.select(Some(editor.chosen.len() - 1));
+        }
+    }
+
+    /// `K`/`J`: moves the highlighted monitor up or down the list.
+    pub fn move_fallback(&mut self, up: bool) {
+        let Some(editor) = &mut self.fallback_editor else {
+            return;
+        };
+        let cursor = editor.cursor();
+        let len = editor.chosen.len();
+        let target = if up {
+            cursor.checked_sub(1)
+        } else {
+            Some(cursor + 1).filter(|&i| i < len)
+        };
+        if let Some(target) = target.filter(|_| cursor < len) {
+            editor.chosen.swap(cursor, target);
+            editor.state.select(Some(target));
+        }
+    }
+
+    /// `Enter` in the editor: stages the list for the workspace, the first
+    /// monitor as its own and the rest as fallbacks.
+    pub fn confirm_fallbacks(&mut self) {
+        let Some(editor) = self.fallback_editor.take() else {
+            return;
+        };
+        let Some(mut ws) = self.get_effective_workspace(editor.row) else {
+            return;
+        };
+        let mut chosen = editor.chosen.into_iter();
+        let primary = chosen.next();
+        let fallbacks: Vec<String> = chosen.collect();
+        let known = primary
+            .as_ref()
+            .is_some_and(|name| self.monitors.iter().any(|m| &m.name == name));
+        let (monitor, reserved) = if known {
+            (primary, None)
+        } else {
+            (None, primary)
+        };
+        if (&ws.monitor, &ws.reserved, &ws.fallbacks)
+            == (&monitor, &reserved, &fallbacks)
+        {
+            return;
+        }
+        ws.monitor = monitor;
+        ws.reserved = reserved;
+        ws.fallbacks = fallbacks;
+        if ws.monitor.is_none() && ws.reserved.is_none() {
+            ws.is_default = false;
+        }
+        self.pending_workspaces.insert(editor.row, ws);
+    }
+
+    pub fn toggle_persistent(&mut self) {
+        let Some(ws_idx) = self.workspace_selection.selected() else {
+            return;
+        };
+
+        let Some(mut effective) = self.get_effective_workspace(ws_idx) else {
+            return;
+        };
+        effective.is_persistent = !effective.is_persistent;
+        self.pending_workspaces.insert(ws_idx, effective);
+    }
+
+    pub fn toggle_default(&mut self) {
+        let Some(ws_idx) = self.workspace_selection.selected() else {
+            return;
+        };
+
+        let Some(effective) = self.get_effective_workspace(ws_idx) else {
+            return;
+        };
+
+        let new_default_monitor = if effective.is_default { None } else { effective.monitor.clone() };
+
+        let Some(mut effective) = self.get_effective_workspace(ws_idx) else {
+            return;
+        };
+        effective.is_default = new_default_monitor.is_some();
+
+        if let Some(target_monitor) = new_default_monitor {
+            for (_, w) in self.pending_workspaces.iter_mut() {
+                if w.is_default && w.monitor.as_ref() == Some(&target_monitor) {
+                    w.is_default = false;
+                }
+            }
+            for w in self.workspace_assignments.iter_mut() {
+                if w.is_default && w.monitor.as_ref() == Some(&target_monitor) {
+                    w.is_default = false;
+                }
+            }
+        }
+
+        self.pending_workspaces.insert(ws_idx, effective);
+    }
+
+    pub fn open_settings(&mut self) {
+        self.settings = Some(SettingsEditor {
+            fields: config_fields::fields(&self.config),
+            state: ListState::default().with_selected(Some(0)),
+            input: None,
+            error: None,
+        });
+    }
+
+    /// `Esc` in the settings: stops editing a value, else closes them.
+    pub fn close_settings(&mut self) {
+        match &mut self.settings {
+            Some(editor) if editor.input.is_some() => editor.input = None,
+            _ => self.settings = None,
+        }
+    }
+
+    pub fn settings_step(&mut self, forward: bool) {
+        let Some(editor) = &mut self.settings else {
+            return;
+        };
+        if editor.input.is_some() {
+            return;
+        }
+        let len = editor.fields.len();
+        let current = editor.cursor();
+        let next = if forward {
+            (current + 1) % len
+        } else {
+            (current + len - 1) % len
+        };
+        editor.state.select(Some(next));
+    }
+
+    /// `Enter` in the settings: flips a toggle, moves a choice on, or
+    /// starts and finishes typing a number or text.
+    pub fn settings_enter(&mut self) {
+        let Some(editor) = &mut self.settings else {
+            return;
+        };
+        let cursor = editor.cursor();
+        if let Some(input) = editor.input.take() {
+            self.change_setting(cursor, &input);
+            return;
+        }
+        let field = &editor.fields[cursor];
+        match field.cycle(true) {
+            Some(value) => self.change_setting(cursor, &value),
+            None => editor.input = Some(field.value.clone()),
+        }
+    }
+
+    /// `←`/`→` in the settings: the previous or next choice.
+    pub fn settings_cycle(&mut self, forward: bool) {
+        let Some(editor) = &self.settings else {
+            return;
+        };
+        let cursor = editor.cursor();
+        if editor.input.is_none()
+            && let Some(value) = editor.fields[cursor].cycle(forward)
+        {
+            self.change_setting(cursor, &value);
+        }
+    }
+
+    pub fn settings_input(&mut self, c: char) {
+        let editor = self.settings.as_mut();
+        if let Some(input) = editor.and_then(|e| e.input.as_mut()) {
+            input.push(c);
+        }
+    }
+
+    pub fn settings_backspace(&mut self) {
+        let editor = self.settings.as_mut();
+        if let Some(input) = editor.and_then(|e| e.input.as_mut()) {
+            input.pop();
+        }
+    }
+
+    /// Saves the setting at `index` as `value` and uses it right away, or
+    /// says next to it why it cannot be.
+    fn change_setting(&mut self, index: usize, value: &str) {
+        let Some(editor) = &mut self.settings else {
+            return;
+        };
+        let field = &editor.fields[index];
+        let config = match config_fields::set(&self.config, field, value) {
+            Ok(config) => config,
+            Err(e) => {
+                editor.error = Some((index, e));
+                return;
+            }
+        };
+        let restart = field.restart;
+        editor.error = None;
+        self.config = config;
+        self.clamp_zoom();
+        if let Err(e) = self.save_settings() {
+            self.set_error(tf("Failed to save settings: {}", &[&e]));
+        } else if restart {
+            self.set_status(t("Takes effect when xwlm starts again"));
+        }
+        if let Some(editor) = &mut self.settings {
+            editor.fields = config_fields::fields(&self.config);
+        }
+    }
+
+    pub fn open_prompt(&mut self, kind: PromptKind) {
+        let input = match kind {
+            PromptKind::WorkspaceId => self
+                .workspace_selection
+                .selected()
+                .and_then(|idx| self.get_effective_workspace(idx))
+                .map(|ws| ws.id.to_string())
+                .unwrap_or_default(),
+            PromptKind::CustomMode => String::new(),
+        };
+        self.prompt = Some(Prompt { kind, input });
+    }
+
+    pub fn prompt_input(&mut self, c: char) {
+        if let Some(prompt) = self.prompt.as_mut()
+            && prompt.accepts(c)
+        {
+            prompt.input.push(c);
+        }
+    }
+
+    pub fn prompt_backspace(&mut self) {
+        if let Some(prompt) = self.prompt.as_mut() {
+            prompt.input.pop();
+        }
+    }
+
+    pub fn cancel_prompt(&mut self) {
+        self.prompt = None;
+    }
+
+    pub fn submit_prompt(&mut self) {
+        let Some(prompt) = self.prompt.take() else {
+            return;
+        };
+        match prompt.kind {
+            PromptKind::WorkspaceId => self.set_workspace_id(&prompt.input),
+            PromptKind::CustomMode => {
+                if let Err(e) = self.add_custom_mode(&prompt.input) {
+                    self.set_error(tf("Failed to apply: {}", &[&e]));
+                }
+            }
+        }
+    }
+
+    fn set_workspace_id(&mut self, input: &str) {
+        let Ok(id) = input.trim().parse::<usize>() else {
+            self.set_error(tf(
+                "Invalid workspace id: {}",
+                &[&format!("{input:?}")],
+            ));
+            return;
+        };
+        let Some(ws_idx) = self.workspace_selection.selected() else {
+            return;
+        };
+        let taken = (0..self.workspace_assignments.len())
+            .filter(|&i| i != ws_idx)
+            .filter_map(|i| self.get_effective_workspace(i))
+            .any(|ws| ws.id == id);
+        if taken {
+            self.set_error(tf("Workspace {} already exists", &[&id]));
+            return;
+        }
+        let Some(mut effective) = self.get_effective_workspace(ws_idx) else {
+            return;
+        };
+        if effective.id == id {
+            return;
+        }
+        effective.id = id;
+        self.pending_workspaces.insert(ws_idx, effective);
+    }
+
+    pub fn toggle_pinned(&mut self) {
+        let Some(ws_idx) = self.workspace_selection.selected() else {
+            return;
+        };
+        let Some(ws) = self.workspace_assignments.get_mut(ws_idx) else {
+            return;
+        };
+        ws.is_pinned = !ws.is_pinned;
+        if let Some(pending) = self.pending_workspaces.get_mut(&ws_idx) {
+            pending.is_pinned = ws.is_pinned;
+        }
+        self.save_pinned_workspaces();
+    }
+
+    fn save_pinned_workspaces(&mut self) {
+        let pinned: Vec<usize> = self
+            .workspace_assignments
+            .iter()
+            .filter(|w| w.is_pinned)
+            .map(|w| w.id)
+            .collect();
+        if pinned == self.config.pinned_workspaces {
+            return;
+        }
+        self.config.pinned_workspaces = pinned;
+        if let Err(e) = self.save_settings() {
+            self.set_error(tf("Failed to save pinned workspaces: {}", &[&e]));
+        }
+    }
+
+    /// Splits the unpinned workspaces evenly across enabled monitors ordered
+    /// left-to-right, giving any remainder to the leftmost monitors.
+    pub fn distribute_workspaces(&mut self) {
+        let mut monitor_order: Vec<usize> = self
+            .monitors
+            .iter()
+            .enumerate()
+            .filter(|(_, m)| m.enabled)
+            .map(|(i, _)| i)
+            .collect();
+        if monitor_order.is_empty() {
+            self.set_error(t(
+                "No enabled monitors to distribute workspaces across",
+            ));
+            return;
+        }
+        monitor_order.sort_by_key(|&i| {
+            let (x, y) = self.display_position(i);
+            (x, y)
+        });
+
+        let rows: Vec<usize> = self
+            .workspace_assignments
+            .iter()
+            .enumerate()
+            .filter(|(_, ws)| !ws.is_pinned)
+            .map(|(i, _)| i)
+            .collect();
+
+        let sizes = distribution_sizes(rows.len(), monitor_order.len());
+        let targets: Vec<String> = monitor_order
+            .iter()
+            .zip(&sizes)
+            .flat_map(|(&mon, &n)| {
+                std::iter::repeat_n(self.monitors[mon].name.clone(), n)
+            })
+            .collect();
+
+        let mut changed = 0;
+        for (&row, target) in rows.iter().zip(targets) {
+            self.pending_workspaces.remove(&row);
+            let ws = &mut self.workspace_assignments[row];
+            if ws.monitor.as_ref() != Some(&target) {
+                ws.fallbacks.retain(|name| *name != target);
+                ws.monitor = Some(target);
+                ws.reserved = None;
+                changed += 1;
+            }
+        }
+
+        let split = sizes
+            .iter()
+            .map(|n| n.to_string())
+            .collect::<Vec<_>>()
+            .join("/");
+        if changed == 0 {
+            self.set_status(tf(
+                "Workspaces already distributed ({})",
+                &[&split],
+            ));
+            return;
+        }
+
+        self.set_status(tf(
+            "Distributed {} workspaces across {} monitors ({})",
+            &[&rows.len(), &monitor_order.len(), &split],
+        ));
+        self.mark_dirty();
+        self.save_config();
+    }
+
+    /// `X`: asks before dropping every unpinned assignment.
+    pub fn ask_clear_workspaces(&mut self) {
+        let assigned = self.workspace_assignments.iter().any(|ws| {
+            !ws.is_pinned && (ws.monitor.is_some() || ws.reserved.is_some())
+        });
+        if !assigned {
+            self.set_status(t("No workspace assignments to clear"));
+            return;
+        }
+        self.ask(Confirm::ClearWorkspaces);
+    }
+
+    fn clear_workspaces(&mut self) {
+        let mut cleared = 0;
+        for (row, ws) in self.workspace_assignments.iter_mut().enumerate() {
+            if ws.is_pinned {
+                continue;
+            }
+            self.pending_workspaces.remove(&row);
+            ws.fallbacks.clear();
+            if ws.monitor.take().is_some() | ws.reserved.take().is_some() {
+                cleared += 1;
+            }
+        }
+        self.set_status(tf("Cleared {} workspace assignments", &[&cleared]));
+        self.mark_dirty();
+        self.save_config();
+    }
+
+    pub fn apply_action(&mut self) -> Result<(), SendError<WlMonitorAction>> {
+        self.undoable(Self::apply_panel)
+    }
+
+    fn apply_panel(&mut self) -> Result<(), SendError<WlMonitorAction>> {
+        let targets: Vec<String> = match self.panel {
+            Panel::Mode | Panel::Scale | Panel::Transform => self
+                .selected_monitor()
+                .map(|m| m.name.clone())
+                .into_iter()
+                .collect(),
+            Panel::Monitor => {
+                self.staged_changes().into_iter().map(|s| s.name).collect()
+            }
+            Panel::Workspace | Panel::Options => Vec::new(),
+        };
+        if !self.clear_of_unmanaged(targets) {
+            return Ok(());
+        }
+        let risky = self.risky_revert();
+        match self.panel {
+            Panel::Mode => {
+                if self.selected_mode_entry() == Some(ModeEntry::AddCustom) {
+                    self.open_prompt(PromptKind::CustomMode);
+                    return Ok(());
+                }
+                if let Some(target) = self.selected_mode_target()
+                    && self.exceeds_link(&target.0, target.1)
+                    && self.confirmed_over_link.as_ref() != Some(&target)
+                {
+                    self.set_error(tf(
+                        "{} may exceed the link bandwidth of {}, Enter again \
+                         to apply it anyway",
+                        &[&target.1, &target.0],
+                    ));
+                    self.confirmed_over_link = Some(target);
+                    return Ok(());
+                }
+                self.confirmed_over_link = None;
+                let switching = self.selected_mode_target().filter(|target| {
+                    self.selected_monitor().and_then(ModeSpec::current)
+                        != Some(target.1)
+                });
+                if !self.clear_of_captures(switching.into_iter().map(|t| t.0))
+                {
+                    return Ok(());
+                }
+                self.apply_mode()?;
+                self.edited.mode = false;
+            }
+            Panel::Scale => {
+                // Applying drops the edit, and with it the value to check.
+                let over_limit = self.scale_over_limit();
+                self.apply_scale()?;
+                if let Some(max) = over_limit {
+                    self.set_error(tf(
+                        "{} may reject scales above {}",
+                        &[&self.compositor.label(), &format!("{max:.2}x")],
+                    ));
+                }
+            }
+            Panel::Transform => {
+                // Enter keeps a preview: the highlighted transform is sent
+                // again, which is a no-op when it was the one previewed.
+                self.transform_preview = None;
+                self.apply_transform()?;
+                self.edited.transform = false;
+            }
+            Panel::Options => {
+                self.apply_options();
+                return Ok(());
+            }
+            Panel::Monitor => {
+                let dark = self.monitors.iter().filter(|m| {
+                    m.enabled && self.pending_toggles.contains_key(&m.name)
+                });
+                let outputs: Vec<String> = dark
+                    .map(|m| m.name.clone())
+                    .chain(self.pending_modes.keys().cloned())
+                    .collect();
+                if !self.clear_of_captures(outputs) {
+                    return Ok(());
+                }
+                self.apply_monitor_changes(false)?
+            }
+            Panel::Workspace => {
+                if self.pending_workspaces.is_empty() {
+                    return Ok(());
+                }
+                self.commit_pending_workspaces();
+            }
+        }
+        self.guard(risky);
+        self.mark_dirty();
+        self.save_config();
+
+        Ok(())
+    }
+
+    /// Starts the countdown that reverts `risky` unless a key is pressed,
+    /// once it has been sent rather than left waiting on a confirmation.
+    fn guard(&mut self, risky: Option<(String, Revert)>) {
+        if let Some((name, revert)) = risky
+            && self.confirm.is_none()
+        {
+            self.guarded = Some(GuardedChange {
+                name,
+                revert,
+                deadline: Instant::now()
+                    + Duration::from_secs(GUARD_REVERT_SECS),
+            });
+        }
+    }
+
+    /// Whether switching `outputs` off or to another mode can go ahead.
+    /// When a screencast or recorder captures one of them, the first try
+    /// only names it and the same change asked for again goes ahead.
+    fn clear_of_captures(
+        &mut self,
+        outputs: impl IntoIterator<Item = String>,
+    ) -> bool {
+        let mut outputs: Vec<String> = outputs.into_iter().collect();
+        outputs.sort();
+        outputs.dedup();
+        if outputs.is_empty()
+            || self.confirmed_capture.take().as_ref() == Some(&outputs)
+        {
+            return true;
+        }
+        let captures = self.effects.captures();
+        let hit = outputs.iter().find_map(|output| {
+            let capture = captures.iter().find(|c| c.covers(output))?;
+            Some((output.clone(), capture.app.clone()))
+        });
+        let Some((output, app)) = hit else {
+            return true;
+        };
+        self.set_error(tf(
+            "{} is being captured by {}, do it again to go ahead anyway",
+            &[&output, &app],
+        ));
+        self.confirmed_capture = Some(outputs);
+        false
+    }
+
+    /// Whether changing `outputs` can go ahead. For a monitor xwlm does
+    /// not manage, the first try only names it and the same change asked
+    /// for again goes ahead.
+    fn clear_of_unmanaged(
+        &mut self,
+        outputs: impl IntoIterator<Item = String>,
+    ) -> bool {
+        let mut outputs: Vec<String> = outputs.into_iter().collect();
+        outputs.sort();
+        outputs.dedup();
+        if self.confirmed_unmanaged.as_ref() == Some(&outputs) {
+            self.confirmed_unmanaged = None;
+            return true;
+        }
+        let Some(output) = outputs.iter().find(|o| !self.is_managed(o))
+        else {
+            return true;
+        };
+        self.set_error(tf(
+            "{} is not managed by xwlm, do it again to go ahead anyway",
+            &[output],
+        ));
+        self.confirmed_unmanaged = Some(outputs);
+        false
+    }
+
+    /// How to undo what applying the focused panel does to the monitor the
+    /// terminal is on, when that could leave the TUI unreadable: a new
+    /// transform, a big scale jump, or switching it off.
+    fn risky_revert(&self) -> Option<(String, Revert)> {
+        let name = self.terminal_output.as_ref()?;
+        let monitor =
+            self.monitors.iter().find(|m| &m.name == name && m.enabled)?;
+        let selected = self.selected_monitor().is_some_and(|m| &m.name == name);
+        let revert = match self.panel {
+            Panel::Transform if selected => {
+                let previewed = self
+                    .transform_preview
+                    .as_ref()
+                    .is_some_and(|p| &p.name == name);
+                let target = self
+                    .transform_selection
+                    .selected()
+                    .and_then(|i| TRANSFORMS.get(i))?;
+                if previewed || *target == monitor.transform {
+                    return None;
+                }
+                Revert::Transform(monitor.transform)
+            }
+            Panel::Scale if selected => {
+                let jump = (self.pending_scale() - monitor.scale).abs();
+                if jump < RISKY_SCALE_JUMP {
+                    return None;
+                }
+                Revert::Scale(monitor.scale)
+            }
+            Panel::Monitor => {
+                if self.pending_toggles.get(name) == Some(&None) {
+                    Revert::Enable(monitor.position.x, monitor.position.y)
+                } else {
+                    self.pending_transforms
+                        .get(name)
+                        .filter(|&&t| t != monitor.transform)?;
+                    Revert::Transform(monitor.transform)
+                }
+            }
+            _ => return None,
+        };
+        Some((name.clone(), revert))
+    }
+
+    /// `risky_revert` for the steps of the `W` review.
+    fn risky_steps(&self, steps: &[Step]) -> Option<(String, Revert)> {
+        let name = self.terminal_output.as_ref()?;
+        let monitor =
+            self.monitors.iter().find(|m| &m.name == name && m.enabled)?;
+        let mut steps = steps.iter().filter(|step| step.name() == name);
+        let revert = if steps.clone().any(|s| matches!(s, Step::Disable { .. }))
+        {
+            Revert::Enable(monitor.position.x, monitor.position.y)
+        } else {
+            steps.find_map(|step| match *step {
+                Step::Transform { transform, .. }
+                    if transform != monitor.transform =>
+                {
+                    Some(Revert::Transform(monitor.transform))
+                }
+                Step::Scale { scale, .. }
+                    if (scale - monitor.scale).abs() >= RISKY_SCALE_JUMP =>
+                {
+                    Some(Revert::Scale(monitor.scale))
+                }
+                _ => None,
+            })?
+        };
+        Some((name.clone(), revert))
+    }
+
+    /// Keeps a guarded change; any key but Esc does.
+    pub fn keep_guarded(&mut self) {
+        if let Some(guarded) = self.guarded.take() {
+            self.set_status(tf("Kept the change to {}", &[&guarded.name]));
+        }
+    }
+
+    /// Undoes a guarded change and saves the monitor as it was.
+    pub fn revert_guarded(&mut self) {
+        let Some(guarded) = self.guarded.take() else {
+            return;
+        };
+        let name = guarded.name;
+        let sent = match guarded.revert {
+            Revert::Transform(transform) => {
+                self.send_action(WlMonitorAction::SetTransform {
+                    name: name.clone(),
+                    transform,
+                })
+            }
+            Revert::Scale(scale) => {
+                self.send_action(WlMonitorAction::SetScale {
+                    name: name.clone(),
+                    scale,
+                })
+            }
+            Revert::Enable(x, y) => self.send_toggle(&name, Some((x, y))),
+        };
+        match sent {
+            Ok(()) => {
+                self.mark_dirty();
+                self.set_status(tf("Reverted the change to {}", &[&name]));
+            }
+            Err(e) => self.set_error(tf("Failed to revert: {}", &[&e])),
+        }
+    }
+
+    /// Reverts a guarded change nobody answered in time.
+    pub fn check_guarded(&mut self, now: Instant) {
+        if self.guarded.as_ref().is_some_and(|g| now >= g.deadline) {
+            self.revert_guarded();
+        }
+    }
+
+    fn commit_pending_workspaces(&mut self) {
+        for (&idx, ws) in &self.pending_workspaces {
+            if let Some(existing) = self.workspace_assignments.get_mut(idx) {
+                existing.id = ws.id;
+                existing.monitor = ws.monitor.clone();
+                existing.reserved = ws.reserved.clone();
+                existing.fallbacks = ws.fallbacks.clone();
+                existing.is_pinned = ws.is_pinned;
+                existing.is_default = ws.is_default;
+                existing.is_persistent = ws.is_persistent;
+            }
+        }
+        self.pending_workspaces.clear();
+        self.save_pinned_workspaces();
+    }
+
+    /// `W`: lists everything staged on every monitor and panel, in the
+    /// order [`apply_plan::plan`] sends it.
+    pub fn open_apply_all(&mut self) {
+        let steps = apply_plan::plan(&self.staged_changes());
+        let mut extras = Vec::new();
+        let rules = self.has_panel(Panel::Workspace);
+        if rules && !self.pending_workspaces.is_empty() {
+            extras.push(t("workspace assignments"));
+        }
+        if !self.pending_options.is_empty() {
+            extras.push(t("monitor options"));
+        }
+        if steps.is_empty() && extras.is_empty() {
+            self.set_status(t("Nothing to apply."));
+            return;
+        }
+        let none_left =
+            (0..self.monitors.len()).all(|idx| !self.will_be_enabled(idx));
+        if none_left {
+            self.set_error(t("Applying everything would leave no monitor on"));
+            return;
+        }
+        let touched: Vec<String> = steps
+            .iter()
+            .filter(|step| {
+                matches!(step, Step::Position { .. } | Step::Disable { .. })
+            })
+            .map(|step| step.name().to_string())
+            .collect();
+        let notes = self.bar_notes(&touched);
+        self.apply_all = Some(ApplyAll {
+            steps,
+            extras,
+            notes,
+            results: None,
+        });
+    }
+
+    /// e.g. "waybar has a layer surface on DP-1; you may need to restart
+    /// it", for each bar on the `outputs` that move or switch off.
+    fn bar_notes(&self, outputs: &[String]) -> Vec<String> {
+        if outputs.is_empty() {
+            return Vec::new();
+        }
+        self.effects
+            .bars(self.compositor)
+            .into_iter()
+            .filter(|bar| outputs.contains(&bar.output))
+            .map(|bar| {
+                tf(
+                    "{} has a layer surface on {}; you may need to restart it",
+                    &[&bar.app, &bar.output],
+                )
+            })
+            .collect()
+    }
+
+    /// Shows the [`App::bar_notes`] for `outputs` that were just moved or
+    /// switched off.
+    fn note_bars(&mut self, outputs: &[String]) {
+        let notes = self.bar_notes(outputs);
+        if !notes.is_empty() {
+            self.set_status(notes.join("; "));
+        }
+    }
+
+    /// Sends every step of the open `W` modal and keeps how each went.
+    pub fn confirm_apply_all(&mut self) {
+        self.undoable(Self::apply_all_steps)
+    }
+
+    fn apply_all_steps(&mut self) {
+        let Some(view) = &self.apply_all else {
+            return;
+        };
+        if view.results.is_some() {
+            return;
+        }
+        let steps = view.steps.clone();
+        let names = steps.iter().map(|step| step.name().to_string());
+        if !self.clear_of_unmanaged(names) {
+            return;
+        }
+        let outputs = steps.iter().filter_map(|step| match step {
+            Step::Mode { name, .. } | Step::Disable { name } => {
+                Some(name.clone())
+            }
+            _ => None,
+        });
+        if !self.clear_of_captures(outputs) {
+            return;
+        }
+        let risky = self.risky_steps(&steps);
+        let mut results = Vec::new();
+        for step in steps {
+            let error = self.run_step(&step).err().map(|e| e.to_string());
+            results.push((step.to_string(), error));
+        }
+        self.guard(risky);
+        if self.has_panel(Panel::Workspace)
+            && !self.pending_workspaces.is_empty()
+        {
+            self.commit_pending_workspaces();
+            results.push((t("workspace assignments").to_string(), None));
+        }
+        if !self.pending_options.is_empty() {
+            self.clear_error();
+            self.apply_options();
+            let error = self.error_message.clone();
+            results.push((t("monitor options").to_string(), error));
+        }
+        self.pending_toggles.clear();
+        self.pending_positions.clear();
+        self.pending_transforms.clear();
+        self.pending_modes.clear();
+        self.pending_scales.clear();
+        self.scale_edits.clear();
+        self.edited = PanelEdits::default();
+        self.mark_dirty();
+        self.save_config();
+        if let Some(view) = &mut self.apply_all {
+            view.results = Some(results);
+        }
+    }
+
+    pub fn close_apply_all(&mut self) {
+        self.apply_all = None;
+    }
+
+    /// What each monitor has staged: toggles, moves, and the mode, scale
+    /// and transform edited for the selected one.
+    fn staged_changes(&self) -> Vec<Staged> {
+        let selected = self.selected_monitor().map(|m| m.name.clone());
+        let edited_transform = self
+            .transform_selection
+            .selected()
+            .and_then(|i| TRANSFORMS.get(i))
+            .copied();
+        self.monitors
+            .iter()
+            .enumerate()
+            .filter_map(|(idx, m)| {
+                let (w, h) = effective_dimensions(m);
+                let now = m
+                    .enabled
+                    .then(|| Rect::new(m.position.x, m.position.y, w, h));
+                let after = self.will_be_enabled(idx).then(|| {
+                    let (x, y) = match self.pending_toggles.get(&m.name) {
+                        Some(Some(position)) => *position,
+                        _ => self.display_position(idx),
+                    };
+                    Rect::new(x, y, w, h)
+                });
+                let edited = selected.as_deref() == Some(m.name.as_str());
+                let mode = self
+                    .selected_mode_target()
+                    .filter(|_| edited && self.edited.mode)
+                    .map(|(_, mode)| mode)
+                    .or_else(|| self.pending_modes.get(&m.name).copied())
+                    .filter(|&mode| Some(mode) != ModeSpec::current(m));
+                let scale = self
+                    .scale_edits
+                    .get(&m.name)
+                    .or_else(|| self.pending_scales.get(&m.name))
+                    .copied()
+                    .filter(|scale| (scale - m.scale).abs() > 0.001);
+                let transform = if edited && self.edited.transform {
+                    edited_transform
+                } else {
+                    self.pending_transforms.get(&m.name).copied()
+                }
+                .filter(|&t| t != m.transform);
+                let changed = now != after
+                    || mode.is_some()
+                    || scale.is_some()
+                    || transform.is_some();
+                changed.then(|| Staged {
+                    name: m.name.clone(),
+                    now,
+                    after,
+                    mode,
+                    scale,
+                    transform,
+                })
+            })
+            .collect()
+    }
+
+    fn run_step(
+        &mut self,
+        step: &Step,
+    ) -> Result<(), SendError<WlMonitorAction>> {
+        let name = step.name().to_string();
+        let idx = self.monitors.iter().position(|m| m.name == name);
+        match *step {
+            Step::Enable { x, y, .. } => {
+                return self.send_toggle(&name, Some((x, y)));
+            }
+            Step::Disable { .. } => return self.send_toggle(&name, None),
+            Step::Mode { mode, .. } => return self.send_mode(&name, mode),
+            Step::Scale { scale, .. } => {
+                self.send_action(WlMonitorAction::SetScale {
+                    name: name.clone(),
+                    scale,
+                })?;
+            }
+            Step::Transform { transform, .. } => {
+                self.send_action(WlMonitorAction::SetTransform {
+                    name: name.clone(),
+                    transform,
+                })?;
+                if let Some(idx) = idx {
+                    self.monitors[idx].transform = transform;
+                }
+            }
+            Step::Position { x, y, .. } => {
+                self.send_action(WlMonitorAction::SetPosition {
+                    name: name.clone(),
+                    x,
+                    y,
+                })?;
+                if let Some(idx) = idx {
+                    self.monitors[idx].position.x = x;
+                    self.monitors[idx].position.y = y;
+                }
+            }
+        }
+        self.touched.insert(name);
+        Ok(())
+    }
+
+    /// Advertised modes first, then saved custom modes the monitor doesn't
+    /// advertise, then the row that adds a new one. With
+    /// `hide_other_aspects`, advertised modes of another aspect than the
+    /// native one are left out unless current. None for a monitor that
+    /// reports no modes, since it has nothing to switch between.
+    pub fn mode_entries(&self) -> Vec<ModeEntry> {
+        let Some(monitor) = self.selected_monitor() else {
+            return Vec::new();
+        };
+        if monitor.modes.is_empty() {
+            return Vec::new();
+        }
+        let tags = aspect::tags(&monitor.modes);
+        let mut entries: Vec<ModeEntry> = (0..monitor.modes.len())
+            .filter(|&i| {
+                !self.hide_other_aspects
+                    || tags[i].native
+                    || monitor.modes[i].is_current
+            })
+            .map(ModeEntry::Advertised)
+            .collect();
+        for c in &self.config.custom_modes {
+            let mode = ModeSpec {
+                width: c.width,
+                height: c.height,
+                refresh_rate: c.refresh_rate,
+            };
+            let advertised =
+                monitor.modes.iter().any(|m| ModeSpec::of(m) == mode);
+            if c.monitor == monitor.name && !advertised {
+                entries.push(ModeEntry::Custom {
+                    mode,
+                    active: c.active,
+                });
+            }
+        }
+        entries.push(ModeEntry::AddCustom);
+        entries
+    }
+
+    /// How many advertised modes `hide_other_aspects` leaves out.
+    pub fn hidden_modes(&self) -> usize {
+        let Some(monitor) = self.selected_monitor() else {
+            return 0;
+        };
+        let listed = self
+            .mode_entries()
+            .iter()
+            .filter(|e| matches!(e, ModeEntry::Advertised(_)))
+            .count();
+        monitor.modes.len() - listed
+    }
+
+    /// The Modes panel row of advertised mode `idx`.
+    fn mode_row(&self, idx: usize) -> Option<usize> {
+        self.mode_entries()
+            .iter()
+            .position(|e| *e == ModeEntry::Advertised(idx))
+    }
+
+    /// `f`: lists only the modes of the native aspect, or all again,
+    /// keeping the highlighted mode when it stays listed.
+    pub fn toggle_aspect_filter(&mut self) {
+        let selected = self.selected_mode_entry();
+        self.hide_other_aspects = !self.hide_other_aspects;
+        let entries = self.mode_entries();
+        let current = self.selected_monitor().and_then(|m| {
+            m.modes.iter().position(|mode| mode.is_current)
+        });
+        let row = selected
+            .and_then(|s| entries.iter().position(|e| *e == s))
+            .or_else(|| current.and_then(|i| self.mode_row(i)))
+            .unwrap_or(0);
+        self.mode_selection.select(Some(row));
+        if !self.hide_other_aspects {
+            self.set_status(t("Showing modes of every aspect"));
+            return;
+        }
+        let native = self
+            .selected_monitor()
+            .and_then(|m| {
+                let tags = aspect::tags(&m.modes);
+                tags.into_iter().find(|t| t.native)
+            })
+            .map(|t| t.aspect.to_string())
+            .unwrap_or_default();
+        self.set_status(tf(
+            "Showing {} modes only, {} hidden",
+            &[&native, &self.hidden_modes()],
+        ));
+    }
+
+    fn selected_mode_entry(&self) -> Option<ModeEntry> {
+        let idx = self.mode_selection.selected()?;
+        self.mode_entries().get(idx).copied()
+    }
+
+    /// The selected monitor and the mode highlighted for it.
+    fn selected_mode_target(&self) -> Option<(String, ModeSpec)> {
+        let monitor = self.selected_monitor()?;
+        let target = match self.selected_mode_entry()? {
+            ModeEntry::Advertised(i) => ModeSpec::of(&monitor.modes[i]),
+            ModeEntry::Custom { mode, .. } => mode,
+            ModeEntry::AddCustom => return None,
+        };
+        Some((monitor.name.clone(), target))
+    }
+
+    /// Says so when the selected monitor reports no modes, which leaves no
+    /// mode to apply to it.
+    fn reports_no_modes(&mut self) -> bool {
+        let Some(monitor) = self.selected_monitor() else {
+            return false;
+        };
+        if !monitor.modes.is_empty() {
+            return false;
+        }
+        let name = monitor.name.clone();
+        self.set_status(tf("{} reports no modes", &[&name]));
+        true
+    }
+
+    fn apply_mode(&mut self) -> Result<(), SendError<WlMonitorAction>> {
+        if self.reports_no_modes() {
+            return Ok(());
+        }
+        let Some((name, target)) = self.selected_mode_target() else {
+            return Ok(());
+        };
+        self.send_mode(&name, target)
+    }
+
+    fn send_mode(
+        &mut self,
+        name: &str,
+        target: ModeSpec,
+    ) -> Result<(), SendError<WlMonitorAction>> {
+        let current = self
+            .monitors
+            .iter()
+            .find(|m| m.name == name)
+            .and_then(ModeSpec::current);
+
+        self.set_active_custom_mode(name, target);
+        self.switch_mode(name, target)?;
+        if let Some(current) = current
+            && current != target
+        {
+            self.previous_modes.insert(name.to_string(), current);
+        }
+
+        Ok(())
+    }
+
+    /// Switches the selected monitor back to the mode it had before its
+    /// last mode change. The history itself is only swapped once the
+    /// compositor confirms the change.
+    pub fn restore_previous_mode(
+        &mut self,
+    ) -> Result<(), SendError<WlMonitorAction>> {
+        let Some(monitor) = self.selected_monitor() else {
+            return Ok(());
+        };
+        let name = monitor.name.clone();
+        let Some(&previous) = self.previous_modes.get(&name) else {
+            self.set_status(tf("No previous mode for {}", &[&name]));
+            return Ok(());
+        };
+        if let Some(row) = monitor
+            .modes
+            .iter()
+            .position(|m| ModeSpec::of(m) == previous)
+            .and_then(|idx| self.mode_row(idx))
+        {
+            self.mode_selection.select(Some(row));
+        }
+
+        self.set_active_custom_mode(&name, previous);
+        self.switch_mode(&name, previous)?;
+        let mode = format!(
+            "{}x{}@{}Hz",
+            previous.width, previous.height, previous.refresh_rate
+        );
+        self.set_status(tf("Switching {} back to {}", &[&name, &mode]));
+        self.mark_dirty();
+        self.save_config();
+
+        Ok(())
+    }
+
+    /// Switches the selected monitor to a `WxH@Hz` mode, going through the
+    /// Modes panel entry when there is one and adding a custom mode when
+    /// there isn't.
+    pub fn set_mode_from_input(
+        &mut self,
+        input: &str,
+    ) -> Result<(), SendError<WlMonitorAction>> {
+        if self.reports_no_modes() {
+            return Ok(());
+        }
+        let Some(monitor) = self.selected_monitor() else {
+            return Ok(());
+        };
+        let Some((width, height, mhz)) = parse_mode(input) else {
+            return self.add_custom_mode(input);
+        };
+        let wanted = ModeSpec {
+            width,
+            height,
+            refresh_rate: refresh::whole_hz(mhz),
+        };
+        // The filter only thins out the list, a typed mode still counts.
+        let hidden =
+            monitor.modes.iter().position(|m| ModeSpec::of(m) == wanted);
+        if hidden.is_some_and(|idx| self.mode_row(idx).is_none()) {
+            self.hide_other_aspects = false;
+        }
+        let Some(monitor) = self.selected_monitor() else {
+            return Ok(());
+        };
+        let entry = self.mode_entries().iter().position(|e| match e {
+            ModeEntry::Advertised(i) => {
+                ModeSpec::of(&monitor.modes[*i]) == wanted
+            }
+            ModeEntry::Custom { mode, .. } => *mode == wanted,
+            ModeEntry::AddCustom => false,
+        });
+        let Some(idx) = entry else {
+            return self.add_custom_mode(input);
+        };
+        self.mode_selection.select(Some(idx));
+        self.panel = Panel::Mode;
+        self.apply_action()
+    }
+
+    fn add_custom_mode(
+        &mut self,
+        input: &str,
+    ) -> Result<(), SendError<WlMonitorAction>> {
+        // A modeline is computed for whole Hz only.
+        let whole = parse_mode(input).filter(|&(_, _, mhz)| mhz % 1000 == 0);
+        let Some((width, height, mhz)) = whole else {
+            self.set_error(tf(
+                "Invalid mode {}, expected WxH@Hz",
+                &[&format!("{input:?}")],
+            ));
+            return Ok(());
+        };
+        let Some(name) = self.selected_monitor().map(|m| m.name.clone()) else {
+            return Ok(());
+        };
+        let refresh_rate = refresh::whole_hz(mhz);
+        let mode = ModeSpec {
+            width,
+            height,
+            refresh_rate,
+        };
+        let exists = self.config.custom_modes.iter().any(|c| {
+            c.monitor == name
+                && c.width == width
+                && c.height == height
+                && c.refresh_rate == refresh_rate
+        });
+        if !exists {
+            self.config.custom_modes.push(CustomMode {
+                monitor: name,
+                width,
+                height,
+                refresh_rate,
+                active: false,
+            });
+        }
+        if let Some(idx) = self.mode_entries().iter().position(|e| {
+            matches!(e, ModeEntry::Custom { mode: m, .. } if *m == mode)
+        }) {
+            self.mode_selection.select(Some(idx));
+        }
+        self.apply_mode()?;
+        self.mark_dirty();
+        self.save_config();
+        Ok(())
+    }
+
+    /// Marks `mode` as the custom mode written for `name`, or clears the
+    /// marker when it is an advertised mode. Saved to the xwlm config so
+    /// custom modes show up again next launch.
+    fn set_active_custom_mode(&mut self, name: &str, mode: ModeSpec) {
+        let mut changed = false;
+        for c in &mut self.config.custom_modes {
+            if c.monitor != name {
+                continue;
+            }
+            let active = (c.width, c.height, c.refresh_rate)
+                == (mode.width, mode.height, mode.refresh_rate);
+            changed |= c.active != active;
+            c.active = active;
+        }
+        if !changed {
+            return;
+        }
+        if let Err(e) = self.save_settings() {
+            self.set_error(tf("Failed to save custom modes: {}", &[&e]));
+        }
+    }
+
+    /// Sends staged toggles, modes, scales, transforms and positions.
+    /// Staged toggles that would switch off every monitor raise the
+    /// last-monitor warning unless `confirmed`.
+    fn apply_monitor_changes(
+        &mut self,
+        confirmed: bool,
+    ) -> Result<(), SendError<WlMonitorAction>> {
+        if self.pending_positions.is_empty()
+            && self.pending_transforms.is_empty()
+            && self.pending_toggles.is_empty()
+            && !self.has_pending_settings()
+        {
+            return Ok(());
+        }
+        let enabled_after = (0..self.monitors.len())
+            .filter(|&idx| self.will_be_enabled(idx))
+            .count();
+        if self.has_pending_toggles() && enabled_after == 0 && !confirmed {
+            self.ask(Confirm::LastMonitor);
+            return Ok(());
+        }
+
+        let moved: Vec<String> = self
+            .pending_positions
+            .keys()
+            .cloned()
+            .chain(
+                self.pending_toggles
+                    .iter()
+                    .filter(|(_, position)| position.is_none())
+                    .map(|(name, _)| name.clone()),
+            )
+            .collect();
+        let toggles: Vec<_> = self.pending_toggles.drain().collect();
+        for (name, position) in toggles {
+            self.send_toggle(&name, position)?;
+        }
+        let modes: Vec<_> = self.pending_modes.drain().collect();
+        for (name, mode) in modes {
+            self.send_mode(&name, mode)?;
+        }
+        let scales: Vec<_> = self.pending_scales.drain().collect();
+        for (name, scale) in scales {
+            self.send_action(WlMonitorAction::SetScale {
+                name: name.clone(),
+                scale,
+            })?;
+            self.touched.insert(name);
+        }
+        self.apply_pending_transforms()?;
+        for monitor in &mut self.monitors {
+            let name = &monitor.name;
+            if let Some(&t) = self.pending_transforms.get(name) {
+                monitor.transform = t;
+            }
+            if let Some(&(x, y)) = self.pending_positions.get(name) {
+                monitor.position.x = x;
+                monitor.position.y = y;
+            }
+        }
+        self.pending_transforms.clear();
+        self.apply_positions()?;
+        self.pending_positions.clear();
+        self.note_bars(&moved);
+        Ok(())
+    }
+
+    /// Switches `name` to `mode`. The protocol only picks modes a monitor
+    /// advertises, so a custom one goes through the monitor config, whose
+    /// modeline the compositor takes with the reload.
+    fn switch_mode(
+        &mut self,
+        name: &str,
+        mode: ModeSpec,
+    ) -> Result<(), SendError<WlMonitorAction>> {
+        let advertised = self
+            .monitors
+            .iter()
+            .find(|m| m.name == name)
+            .is_none_or(|m| m.modes.iter().any(|m| ModeSpec::of(m) == mode));
+        if !advertised {
+            self.set_active_custom_mode(name, mode);
+            self.touched.insert(name.to_string());
+            self.set_status(tf(
+                "Applying {} to {} through the monitor config",
+                &[&mode, &name],
+            ));
+            self.mark_dirty();
+            self.save_queued = true;
+            return Ok(());
+        }
+        self.send_action(WlMonitorAction::SwitchMode {
+            name: name.to_string(),
+            width: mode.width,
+            height: mode.height,
+            refresh_rate: mode.refresh_rate,
+        })?;
+        self.touched.insert(name.to_string());
+        Ok(())
+    }
+
+    fn apply_scale(&mut self) -> Result<(), SendError<WlMonitorAction>> {
+        let Some(name) = self.selected_monitor().map(|m| m.name.clone())
+        else {
+            return Ok(());
+        };
+        self.send_action(WlMonitorAction::SetScale {
+            name: name.clone(),
+            scale: self.pending_scale(),
+        })?;
+        self.scale_edits.remove(&name);
+        self.touched.insert(name);
+        Ok(())
+    }
+
+    fn apply_transform(&mut self) -> Result<(), SendError<WlMonitorAction>> {
+        let Some(name) = self.selected_monitor().map(|m| m.name.clone())
+        else {
+            return Ok(());
+        };
+        let Some(idx) = self.transform_selection.selected() else {
+            return Ok(());
+        };
+        let Some(&transform) = TRANSFORMS.get(idx) else {
+            return Ok(());
+        };
+
+        self.send_action(WlMonitorAction::SetTransform {
+            name: name.clone(),
+            transform,
+        })?;
+        self.touched.insert(name);
+
+        Ok(())
+    }
+
+    /// Sends the highlighted transform without saving it; it is reverted
+    /// after [`TRANSFORM_PREVIEW_SECS`] unless Enter keeps it. Monitors
+    /// xwlm does not manage are not previewed.
+    pub fn preview_transform(
+        &mut self,
+        now: Instant,
+    ) -> Result<(), SendError<WlMonitorAction>> {
+        let Some(monitor) = self.selected_monitor() else {
+            return Ok(());
+        };
+        if !self.is_managed(&monitor.name) {
+            return Ok(());
+        }
+        let name = monitor.name.clone();
+        let current = monitor.transform;
+        let Some(&transform) =
+            self.transform_selection.selected().and_then(|i| TRANSFORMS.get(i))
+        else {
+            return Ok(());
+        };
+        // Previewing another monitor first puts the last one back.
+        if self.transform_preview.as_ref().is_some_and(|p| p.name != name) {
+            self.revert_transform_preview();
+        }
+        let previous = match self.transform_preview.take() {
+            Some(preview) => preview.previous,
+            None => current,
+        };
+        self.send_action(WlMonitorAction::SetTransform {
+            name: name.clone(),
+            transform,
+        })?;
+        self.transform_preview = Some(TransformPreview {
+            name,
+            transform,
+            previous,
+            deadline: now + Duration::from_secs(TRANSFORM_PREVIEW_SECS),
+        });
+        Ok(())
+    }
+
+    /// Puts the transform from before the preview back.
+    pub fn revert_transform_preview(&mut self) {
+        let Some(preview) = self.transform_preview.take() else {
+            return;
+        };
+        let sent = self.send_action(WlMonitorAction::SetTransform {
+            name: preview.name.clone(),
+            transform: preview.previous,
+        });
+        match sent {
+            Ok(()) => self.set_status(tf(
+                "Reverted {} to {}",
+                &[&preview.name, &t(transform_label(preview.previous))],
+            )),
+            Err(e) => self.set_error(tf("Failed to revert: {}", &[&e])),
+        }
+    }
+
+    /// Reverts a preview whose time is up or whose panel lost focus.
+    pub fn check_transform_preview(&mut self, now: Instant) {
+        let Some(preview) = &self.transform_preview else {
+            return;
+        };
+        if now >= preview.deadline || self.panel != Panel::Transform {
+            self.revert_transform_preview();
+        }
+    }
+
+    fn apply_pending_transforms(&mut self) -> Result<(), SendError<WlMonitorAction>> {
+        let transforms: Vec<_> = self
+            .pending_transforms
+            .iter()
+            .map(|(name, &transform)| (name.clone(), transform))
+            .collect();
+        for (name, transform) in transforms {
+            self.send_action(WlMonitorAction::SetTransform {
+                name: name.clone(),
+                transform,
+            })?;
+            self.touched.insert(name.clone());
+        }
+
+        Ok(())
+    }
+
+    fn apply_positions(&mut self) -> Result<(), SendError<WlMonitorAction>> {
+        let positions: Vec<_> = self
+            .pending_positions
+            .iter()
+            .map(|(name, &position)| (name.clone(), position))
+            .collect();
+        for (name, (x, y)) in positions {
+            self.send_action(WlMonitorAction::SetPosition {
+                name: name.clone(),
+                x,
+                y,
+            })?;
+            self.touched.insert(name.clone());
+        }
+
+        Ok(())
+    }
+
+    fn resolve_initial_workspaces(&mut self) {
+        let Some(workspace_rules) = self.initial_workspaces.take() else {
+            return;
+        };
+        for rule in &workspace_rules {
+            let name = match &self.layout {
+                Some(layout) => layout.live_name(&rule.monitor, &self.monitors),
+                None => rule.monitor.clone(),
+            };
+            let known = self.monitors.iter().any(|m| m.name == name);
+            if let Some(ws) = self
+                .workspace_assignments
+                .iter_mut()
+                .find(|ws| ws.id == rule.id)
+            {
+                ws.reserved = (!known && !rule.monitor.is_empty())
+                    .then(|| rule.monitor.clone());
+                ws.monitor = known.then_some(name);
+                ws.fallbacks = rule.fallbacks.clone();
+                ws.is_default = rule.is_default;
+                ws.is_persistent = rule.is_persistent;
+                ws.line = rule.line;
+            }
+        }
+    }
+
+    fn validate_workspace_assignments(&mut self) {
+        for ws in &mut self.workspace_assignments {
+            if let Some(name) = &ws.monitor
+                && !self.monitors.iter().any(|m| &m.name == name)
+            {
+                ws.reserved = ws.monitor.take();
+            }
+        }
+    }
+
+    /// Hands reserved workspaces to their monitor once it is connected.
+    fn claim_reserved_workspaces(&mut self) {
+        let rows = self
+            .workspace_assignments
+            .iter_mut()
+            .chain(self.pending_workspaces.values_mut());
+        for ws in rows {
+            let Some(reserved) = &ws.reserved else {
+                continue;
+            };
+            let name = match &self.layout {
+                Some(layout) => layout.live_name(reserved, &self.monitors),
+                None => reserved.clone(),
+            };
+            if self.monitors.iter().any(|m| m.name == name) {
+                ws.monitor = Some(name);
+                ws.reserved = None;
+            }
+        }
+    }
+}
+
+/// Workspace rows start from the ids found in the config, topped up with
+/// the lowest unused ids until there are at least `count` rows.
+fn seed_workspace_ids(parsed: &[usize], count: usize) -> Vec<usize> {
+    let mut ids: Vec<usize> = parsed.to_vec();
+    ids.sort_unstable();
+    ids.dedup();
+
+    let mut next = 1;
+    while ids.len() < count {
+        if !ids.contains(&next) {
+            ids.push(next);
+        }
+        next += 1;
+    }
+    ids.sort_unstable();
+    ids
+}
+
+/// Whether `a` and `b` are the same output: the same name and, where
+/// both have one, the same head, else the same description.
+fn same_output(a: &WlMonitor, b: &WlMonitor) -> bool {
+    if a.name != b.name {
+        return false;
+    }
+    if !a.head_id.is_null() && !b.head_id.is_null() {
+        return a.head_id == b.head_id;
+    }
+    a.description == b.description
+}
+
+/// Sizes of `buckets` near-equal chunks of `items`, largest first.
+fn distribution_sizes(items: usize, buckets: usize) -> Vec<usize> {
+    if buckets == 0 {
+        return Vec::new();
+    }
+    (0..buckets)
+        .map(|i| items / buckets + usize::from(i < items % buckets))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{
+        CONFIG_PATH, app, changed, disabled, failed, harness, mode, monitor,
+        play, removed, restart,
+    };
+    use compositor::Compositor::{
+        Hyprland, Kwin, Labwc, Niri, River, Sway, Unknown, Wayfire,
+    };
+    use std::collections::BTreeMap;
+    use wlx_monitors::ActionKind;
+    use wlx_monitors::WlResolution;
+
+    const SORTED: [&str; 4] = ["DP-2", "DP-10", "HDMI-A-1", "eDP-1"];
+
+    fn names(app: &App) -> Vec<&str> {
+        app.monitors.iter().map(|m| m.name.as_str()).collect()
+    }
+
+    fn permutations(items: &[&'static str]) -> Vec<Vec<&'static str>> {
+        if items.len() <= 1 {
+            return vec![items.to_vec()];
+        }
+        let mut out = Vec::new();
+        for i in 0..items.len() {
+            let mut rest = items.to_vec();
+            let first = rest.remove(i);
+            for mut perm in permutations(&rest) {
+                perm.insert(0, first);
+                out.push(perm);
+            }
+        }
+        out
+    }
+
+    #[test]
+    fn test_monitor_order_ignores_event_arrival_order() {
+        for perm in permutations(&SORTED) {
+            let (mut app, _rx) = app();
+            app.set_monitors(vec![
+                monitor(perm[0], 0, 0),
+                monitor(perm[1], 0, 0),
+            ]);
+            for name in &perm[2..] {
+                app.update_monitor(monitor(name, 0, 0));
+            }
+            assert_eq!(names(&app), SORTED, "arrival order {perm:?}");
+        }
+    }
+
+    #[test]
+    fn test_selection_survives_replug_in_any_order() {
+        for perm in permutations(&["DP-10", "HDMI-A-1", "eDP-1"]) {
+            let (mut app, _rx) = app();
+            app.set_monitors(
+                SORTED.iter().map(|n| monitor(n, 0, 0)).collect(),
+            );
+            app.select_next_monitor();
+            assert_eq!(app.selected_monitor().unwrap().name, "DP-10");
+
+            for name in &perm {
+                if *name != "DP-10" {
+                    app.remove_monitor(name);
+                }
+            }
+            app.update_monitor(monitor("DP-2", 0, 0));
+            for name in perm.iter().rev() {
+                app.update_monitor(monitor(name, 0, 0));
+            }
+
+            assert_eq!(names(&app), SORTED);
+            assert_eq!(app.selected_monitor, 1);
+            assert_eq!(app.selected_monitor().unwrap().name, "DP-10");
+        }
+    }
+
+    #[test]
+    fn test_removing_selected_monitor_selects_neighbour() {
+        let (mut app, _rx) = app();
+        app.set_monitors(SORTED.iter().map(|n| monitor(n, 0, 0)).collect());
+        app.select_prev_monitor();
+        assert_eq!(app.selected_monitor().unwrap().name, "eDP-1");
+
+        app.remove_monitor("eDP-1");
+        assert_eq!(app.selected_monitor().unwrap().name, "HDMI-A-1");
+        app.update_monitor(monitor("eDP-1", 0, 0));
+        assert_eq!(app.selected_monitor().unwrap().name, "HDMI-A-1");
+    }
+
+    #[test]
+    fn test_pending_state_follows_monitor_name() {
+        let (mut app, _rx) = app();
+        app.set_monitors(vec![
+            monitor("HDMI-A-1", 1920, 0),
+            monitor("DP-10", 0, 0),
+        ]);
+        app.pending_positions.insert("HDMI-A-1".into(), (3840, 0));
+        app.pending_transforms
+            .insert("DP-10".into(), WlTransform::Rotate90);
+
+        app.update_monitor(monitor("DP-2", 5760, 0));
+        assert_eq!(app.display_position(2), (3840, 0));
+        assert_eq!(app.display_transform(1), WlTransform::Rotate90);
+
+        app.remove_monitor("DP-10");
+        assert_eq!(app.display_position(1), (3840, 0));
+        assert!(app.pending_transforms.is_empty());
+    }
+
+    #[test]
+    fn test_distribution_sizes_gives_extra_to_leftmost() {
+        assert_eq!(distribution_sizes(10, 3), vec![4, 3, 3]);
+        assert_eq!(distribution_sizes(11, 3), vec![4, 4, 3]);
+        assert_eq!(distribution_sizes(9, 3), vec![3, 3, 3]);
+    }
+
+    #[test]
+    fn test_distribution_sizes_more_buckets_than_items() {
+        assert_eq!(distribution_sizes(2, 4), vec![1, 1, 0, 0]);
+        assert_eq!(distribution_sizes(5, 0), Vec::<usize>::new());
+    }
+
+    #[test]
+    fn test_seed_workspace_ids_defaults_to_sequential() {
+        assert_eq!(seed_workspace_ids(&[], 4), vec![1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn test_seed_workspace_ids_keeps_parsed_ids() {
+        let parsed: Vec<usize> = (1..=20).collect();
+        assert_eq!(seed_workspace_ids(&parsed, 10), parsed);
+        assert_eq!(seed_workspace_ids(&[0, 11, 12], 3), vec![0, 11, 12]);
+    }
+
+    #[test]
+    fn test_seed_workspace_ids_fills_and_dedups() {
+        assert_eq!(seed_workspace_ids(&[11, 2, 11], 4), vec![1, 2, 3, 11]);
+    }
+
+    fn monitor_in_mode(name: &str, current: usize) -> WlMonitor {
+        let mut m = monitor(name, 0, 0);
+        m.modes = vec![mode(2560, 1440, 144), mode(2560, 1440, 120)];
+        m.modes[current].is_current = true;
+        m
+    }
+
+    #[test]
+    fn test_previous_mode_follows_confirmed_changes() {
+        let (mut app, rx) = app();
+        app.set_monitors(vec![monitor_in_mode("DP-1", 0)]);
+        app.restore_previous_mode().unwrap();
+        assert!(rx.try_recv().is_err());
+
+        app.update_monitor(monitor_in_mode("DP-1", 1));
+        app.restore_previous_mode().unwrap();
+        let Ok(WlMonitorAction::SwitchMode { refresh_rate, .. }) = rx.try_recv()
+        else {
+            panic!("expected a mode switch");
+        };
+        assert_eq!(refresh_rate, 144);
+        assert_eq!(app.mode_selection.selected(), Some(0));
+
+        app.update_monitor(monitor_in_mode("DP-1", 0));
+        app.restore_previous_mode().unwrap();
+        let Ok(WlMonitorAction::SwitchMode { refresh_rate, .. }) = rx.try_recv()
+        else {
+            panic!("expected a mode switch");
+        };
+        assert_eq!(refresh_rate, 120);
+    }
+
+    fn resized(name: &str, x: i32, width: i32, height: i32) -> WlMonitor {
+        let mut m = monitor(name, x, 0);
+        m.modes[0].resolution = WlResolution { width, height };
+        m
+    }
+
+    #[test]
+    fn test_resize_stages_reflow_of_right_neighbours() {
+        let (mut app, rx) = app();
+        app.set_monitors(vec![
+            monitor("DP-1", 0, 0),
+            monitor("DP-2", 1920, 0),
+            monitor("DP-3", 3840, 0),
+        ]);
+
+        app.update_monitor(resized("DP-2", 1920, 3840, 2160));
+
+        assert_eq!(app.display_position(0), (0, 0));
+        assert_eq!(app.display_position(2), (5760, 0));
+        assert!(app.status_message.unwrap().starts_with("Reflowed 1 monitor"));
+        assert!(rx.try_recv().is_err());
+    }
+
+    #[test]
+    fn test_reflow_skips_anchored_and_can_apply_directly() {
+        let (mut app, rx) = app();
+        app.config.auto_reflow = true;
+        app.config.anchored_monitors = vec!["DP-3".into()];
+        app.set_monitors(vec![
+            monitor("DP-1", 0, 0),
+            monitor("DP-2", 1920, 0),
+            monitor("DP-3", 3840, 0),
+        ]);
+
+        app.update_monitor(resized("DP-1", 0, 2560, 1440));
+
+        let Ok(WlMonitorAction::SetPosition { name, x, .. }) = rx.try_recv()
+        else {
+            panic!("expected a position update");
+        };
+        assert_eq!((name.as_str(), x), ("DP-2", 2560));
+        assert!(rx.try_recv().is_err());
+        assert!(app.pending_positions.is_empty());
+        assert_eq!(app.monitors[1].position.x, 2560);
+        assert_eq!(app.monitors[2].position.x, 3840);
+        assert!(app.status_message.unwrap().contains("anchored DP-3"));
+    }
+
+    fn assert_no_overlaps(app: &App) {
+        let rects: Vec<Rect> = (0..app.monitors.len())
+            .filter(|&i| app.monitors[i].enabled)
+            .map(|i| {
+                let (x, y) = app.display_position(i);
+                let (w, h) = app.display_dimensions(i);
+                Rect::new(x, y, w, h)
+            })
+            .collect();
+        for (i, a) in rects.iter().enumerate() {
+            for b in &rects[i + 1..] {
+                assert!(!a.overlaps(b), "{a:?} overlaps {b:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_moves_push_scaled_neighbours_to_their_logical_edge() {
+        let (mut app, _rx) = app();
+        let mut scaled = monitor("DP-1", 0, 0);
+        scaled.scale = 1.75;
+        app.set_monitors(vec![scaled, monitor("DP-2", 1097, 0)]);
+        // 1920 / 1.75 is 1097.14, the same edge snapping and saves use.
+        assert_eq!(app.display_dimensions(0), (1097, 617));
+        assert_eq!(app.logical_rect(0).right(), 1097);
+
+        app.select_index(0);
+        app.move_monitor(PositionDirection::Right);
+        assert_eq!(app.pending_positions["DP-1"], (1, 0));
+        assert_eq!(app.pending_positions["DP-2"], (1098, 0));
+        assert_no_overlaps(&app);
+    }
+
+    #[test]
+    fn test_moves_snap_onto_a_nearby_edge() {
+        let (mut app, _rx) = app();
+        app.set_monitors(vec![monitor("DP-1", 0, 0), monitor("DP-2", 1950, 0)]);
+        app.select_index(0);
+        app.move_monitor(PositionDirection::Right);
+        assert_eq!(app.pending_positions["DP-1"], (30, 0));
+        assert!(!app.pending_positions.contains_key("DP-2"));
+
+        app.pending_positions.clear();
+        app.config.snap_threshold = 0;
+        app.move_monitor(PositionDirection::Left);
+        app.move_monitor(PositionDirection::Right);
+        assert_eq!(app.pending_positions["DP-1"], (1, 0));
+    }
+
+    #[test]
+    fn test_random_moves_never_leave_overlaps() {
+        let mut seed: u64 = 0x2545_f491_4f6c_dd1d;
+        let mut next = move || {
+            seed ^= seed << 13;
+            seed ^= seed >> 7;
+            seed ^= seed << 17;
+            seed
+        };
+
+        for _ in 0..20 {
+            let (mut app, _rx) = app();
+            let mut portrait = monitor("DP-1", 0, 0);
+            portrait.transform = WlTransform::Rotate90;
+            app.set_monitors(vec![
+                portrait,
+                monitor("DP-2", 1080, 0),
+                monitor("DP-3", 1080, 1080),
+                monitor("HDMI-A-1", 3000, 0),
+            ]);
+
+            for _ in 0..200 {
+                match next() % 6 {
+                    0 => app.select_next_monitor(),
+                    1 => app.move_monitor(PositionDirection::Left),
+                    2 => app.move_monitor(PositionDirection::Right),
+                    3 => app.move_monitor(PositionDirection::Up),
+                    4 => app.move_monitor(PositionDirection::Down),
+                    _ => {
+                        // Fast repeats grow the step, so big jumps get
+                        // exercised too.
+                        for _ in 0..30 {
+                            app.move_monitor(PositionDirection::Left);
+                        }
+                    }
+                }
+                assert_no_overlaps(&app);
+            }
+        }
+    }
+
+    #[test]
+    fn test_mode_entries_list_unadvertised_custom_modes() {
+        let (mut app, _rx) = app();
+        let custom = |monitor: &str, width, refresh_rate| CustomMode {
+            monitor: monitor.into(),
+            width,
+            height: 1080,
+            refresh_rate,
+            active: false,
+        };
+        app.config.custom_modes = vec![
+            custom("DP-1", 3840, 120),
+            custom("DP-1", 1920, 60),
+            custom("DP-2", 2560, 75),
+        ];
+        app.set_monitors(vec![monitor("DP-1", 0, 0)]);
+
+        let mode = ModeSpec {
+            width: 3840,
+            height: 1080,
+            refresh_rate: 120,
+        };
+        assert_eq!(
+            app.mode_entries(),
+            vec![
+                ModeEntry::Advertised(0),
+                ModeEntry::Custom {
+                    mode,
+                    active: false
+                },
+                ModeEntry::AddCustom,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_harness_custom_mode_goes_through_the_config() {
+        let (mut app, recorder) = harness(Hyprland, None);
+        app.set_monitors(vec![monitor("DP-1", 0, 0)]);
+        app.panel = Panel::Mode;
+        app.set_mode_from_input("3840x1080@120").unwrap();
+        assert!(recorder.take_actions().is_empty());
+
+        app.flush_saves(Instant::now());
+        let written = recorder.file(CONFIG_PATH).unwrap();
+        assert!(written.contains("modeline"), "{written}");
+        assert!(app.config.custom_modes[0].active);
+        assert_eq!(app.save_state, SaveState::Clean);
+    }
+
+    #[test]
+    fn test_layout_workspaces_follow_monitor_description() {
+        let (mut app, _rx) = app();
+        let saved = Layout::capture(
+            &[monitor("DP-1", 0, 0)],
+            &[WorkspaceRule {
+                id: 2,
+                monitor: "DP-1".into(),
+                fallbacks: Vec::new(),
+                is_default: true,
+                is_persistent: false,
+                line: None,
+            }],
+        );
+        app.initial_workspaces = Some(saved.workspaces.clone());
+        app.layout = Some(saved);
+
+        let mut replugged = monitor("DP-3", 0, 0);
+        replugged.description = "Test Monitor DP-1".into();
+        app.set_monitors(vec![replugged]);
+
+        let ws = app.workspace_assignments.iter().find(|w| w.id == 2).unwrap();
+        assert_eq!(ws.monitor.as_deref(), Some("DP-3"));
+        assert!(ws.is_default);
+    }
+
+    #[test]
+    fn test_layout_monitors_are_compared_by_description() {
+        let (mut app, _rx) = app();
+        app.layout = Some(Layout::capture(&[monitor("DP-1", 0, 500)], &[]));
+
+        let mut replugged = monitor("DP-3", 0, 0);
+        replugged.description = "Test Monitor DP-1".into();
+        app.set_monitors(vec![replugged]);
+        app.open_reconcile(false);
+
+        let view = app.reconcile.as_ref().unwrap();
+        assert_eq!(view.rows.len(), 1);
+        assert_eq!(view.rows[0].name, "DP-3");
+        assert_eq!((view.rows[0].config.x, view.rows[0].config.y), (0, 500));
+    }
+
+    #[test]
+    fn test_restore_layout_only_sends_differences() {
+        let (mut app, rx) = app();
+        app.set_monitors(vec![monitor("DP-1", 0, 0), monitor("DP-2", 1920, 0)]);
+        let mut saved = Layout::capture(&app.monitors, &[]);
+        saved.monitors[1].x = 0;
+        saved.monitors[1].y = 1080;
+
+        app.restore_layout(&saved).unwrap().unwrap();
+
+        let Ok(WlMonitorAction::SetPosition { name, x, y }) = rx.try_recv()
+        else {
+            panic!("expected a position change");
+        };
+        assert_eq!((name.as_str(), x, y), ("DP-2", 0, 1080));
+        assert!(rx.try_recv().is_err());
+        assert!(app.needs_save());
+    }
+
+    #[test]
+    fn test_resolve_mode_falls_back_tier_by_tier() {
+        let mut m = monitor("DP-1", 0, 0);
+        m.modes.push(mode(2560, 1440, 143));
+        m.modes.push(mode(2560, 1440, 60));
+        m.modes.push(mode(3840, 2160, 30));
+        m.modes.push(mode(3840, 2160, 60));
+        let spec = |width, height, refresh_rate| ModeSpec {
+            width,
+            height,
+            refresh_rate,
+        };
+
+        assert_eq!(
+            resolve_mode(&m, spec(2560, 1440, 60)),
+            Some((spec(2560, 1440, 60), ModeMatch::Exact))
+        );
+        assert_eq!(
+            resolve_mode(&m, spec(2560, 1440, 144)),
+            Some((spec(2560, 1440, 143), ModeMatch::NearRefresh))
+        );
+        assert_eq!(
+            resolve_mode(&m, spec(3840, 2160, 120)),
+            Some((spec(3840, 2160, 60), ModeMatch::SameResolution))
+        );
+        assert_eq!(
+            resolve_mode(&m, spec(1280, 720, 60)),
+            Some((spec(1920, 1080, 60), ModeMatch::Preferred))
+        );
+        m.modes[0].preferred = false;
+        m.modes[3].preferred = true;
+        assert_eq!(
+            resolve_mode(&m, spec(3840, 2160, 120)),
+            Some((spec(3840, 2160, 30), ModeMatch::SameResolution))
+        );
+        m.modes.clear();
+        assert_eq!(resolve_mode(&m, spec(1280, 720, 60)), None);
+    }
+
+    #[test]
+    fn test_restore_layout_swaps_a_missing_saved_mode() {
+        let (mut app, rx) = app();
+        let mut live = monitor("DP-1", 0, 0);
+        live.modes.push(mode(2560, 1440, 120));
+        app.set_monitors(vec![live]);
+        let mut saved = Layout::capture(&app.monitors, &[]);
+        saved.monitors[0].width = 2560;
+        saved.monitors[0].height = 1440;
+        saved.monitors[0].refresh_rate = 144;
+
+        let fallbacks = app.restore_layout(&saved).unwrap().unwrap();
+
+        let Ok(WlMonitorAction::SwitchMode { refresh_rate, .. }) = rx.try_recv()
+        else {
+            panic!("expected a mode switch");
+        };
+        assert_eq!(refresh_rate, 120);
+        assert_eq!(
+            fallbacks,
+            ["saved 2560x1440@144Hz unavailable on DP-1, using 2560x1440@120Hz"]
+        );
+    }
+
+    #[test]
+    fn test_scale_edits_stay_with_their_monitor() {
+        let (mut app, rx) = app();
+        app.set_monitors(vec![monitor("DP-1", 0, 0), monitor("DP-2", 1920, 0)]);
+        app.panel = Panel::Scale;
+        app.set_pending_scale(1.25);
+        assert!(app.has_scale_edit("DP-1"));
+
+        // No Changed event comes in between.
+        app.select_next_monitor();
+        assert_eq!(app.pending_scale(), 1.0);
+        app.scale_up();
+        app.apply_action().unwrap();
+        app.select_prev_monitor();
+        assert_eq!(app.pending_scale(), 1.25);
+        app.apply_action().unwrap();
+
+        let sent: Vec<String> = rx.try_iter().map(|a| describe(&a)).collect();
+        assert_eq!(sent, ["scale DP-2 to 1.01", "scale DP-1 to 1.25"]);
+        assert!(app.scale_edits.is_empty());
+    }
+
+    #[test]
+    fn test_fractional_scale_is_flagged_and_snapped() {
+        let (mut app, _rx) = app();
+        let mut fractional = monitor("DP-1", 0, 0);
+        fractional.scale = 1.1;
+        app.set_monitors(vec![fractional]);
+        assert!(app.status_message.as_deref().unwrap().contains("DP-1"));
+
+        app.set_pending_scale(1.1);
+        app.snap_scale(true);
+        assert_eq!(app.pending_scale(), 1.2);
+        assert_eq!(app.scale_lint(), None);
+
+        app.set_pending_scale(1.1);
+        app.snap_scale(false);
+        assert_eq!(app.pending_scale(), 1.0);
+    }
+
+    #[test]
+    fn test_toggles_are_staged_until_applied() {
+        let (mut app, rx) = app();
+        let mut docked = monitor("DP-2", 1920, 0);
+        docked.enabled = false;
+        app.set_monitors(vec![monitor("DP-1", 0, 0), docked]);
+
+        app.select_monitor_named("DP-2");
+        app.toggle_monitor().unwrap();
+        assert!(app.will_be_enabled(1));
+        assert!(rx.try_recv().is_err());
+
+        app.reset_positions();
+        assert!(!app.will_be_enabled(1));
+
+        app.toggle_monitor().unwrap();
+        app.apply_action().unwrap();
+        let Ok(WlMonitorAction::Toggle { name, position, .. }) = rx.try_recv()
+        else {
+            panic!("expected a toggle");
+        };
+        assert_eq!(name, "DP-2");
+        assert!(position.is_some());
+        assert!(!app.has_pending_toggles());
+    }
+
+    #[test]
+    fn test_last_monitor_warning_waits_for_apply() {
+        let (mut app, rx) = app();
+        app.set_monitors(vec![monitor("DP-1", 0, 0)]);
+
+        app.toggle_monitor().unwrap();
+        assert_eq!(app.confirm, None);
+        app.apply_action().unwrap();
+        assert_eq!(app.confirm, Some(Confirm::LastMonitor));
+        assert!(rx.try_recv().is_err());
+
+        app.accept_confirm().unwrap();
+        let Ok(WlMonitorAction::Toggle { name, position, .. }) = rx.try_recv()
+        else {
+            panic!("expected a toggle");
+        };
+        assert_eq!((name.as_str(), position), ("DP-1", None));
+
+        app.config.instant_toggle = true;
+        app.toggle_monitor().unwrap();
+        assert_eq!(app.confirm, Some(Confirm::LastMonitor));
+        app.dismiss_confirm();
+        app.set_monitors(vec![monitor("DP-1", 0, 0), monitor("DP-2", 1920, 0)]);
+        app.toggle_monitor().unwrap();
+        assert!(rx.try_recv().is_ok());
+    }
+
+    #[test]
+    fn test_bouncing_monitor_is_counted_and_flagged() {
+        let (mut app, _rx) = app();
+        app.config.flap_threshold = 3;
+        app.set_monitors(vec![monitor("DP-1", 0, 0), monitor("DP-2", 1920, 0)]);
+
+        for i in 0..3 {
+            let dock = monitor("DP-2", 1920, 0);
+            app.handle_event(WlMonitorEvent::Removed {
+                id: dock.head_id.clone(),
+                name: dock.name.clone(),
+            });
+            assert!(app.error_message.is_none(), "bounce {i}");
+            app.handle_event(WlMonitorEvent::Changed(Box::new(dock)));
+        }
+
+        let warning = app.error_message.as_deref().unwrap();
+        assert!(warning.contains("DP-2"));
+        let now = history::now();
+        assert_eq!(
+            app.timeline.summary("DP-2", now).as_deref(),
+            Some("3 reconnects in the last hour")
+        );
+        assert_eq!(app.timeline.summary("DP-1", now), None);
+        assert_eq!(app.timeline.len(), 6);
+    }
+
+    #[test]
+    fn test_external_change_keeps_pending_edits() {
+        let (mut app, _rx) = app();
+        app.set_monitors(vec![monitor("DP-1", 0, 0), monitor("DP-2", 1920, 0)]);
+        app.select_monitor_named("DP-2");
+        app.move_monitor(PositionDirection::Down);
+        let staged = app.pending_positions.get("DP-2").copied();
+        assert!(staged.is_some());
+
+        let mut scaled = monitor("DP-2", 1920, 0);
+        scaled.scale = 2.0;
+        app.handle_event(WlMonitorEvent::Changed(Box::new(scaled)));
+        assert_eq!(app.pending_positions.get("DP-2").copied(), staged);
+        assert_eq!(app.pending_scale(), 2.0);
+
+        app.panel = Panel::Scale;
+        app.next();
+        let mut scaled = monitor("DP-2", 1920, 0);
+        scaled.scale = 1.5;
+        scaled.transform = WlTransform::Rotate90;
+        app.handle_event(WlMonitorEvent::Changed(Box::new(scaled)));
+        assert!((app.pending_scale() - 2.01).abs() < 1e-9);
+        assert_eq!(app.pending_positions.get("DP-2").copied(), staged);
+        let rotated =
+            TRANSFORMS.iter().position(|&t| t == WlTransform::Rotate90);
+        assert_eq!(app.transform_selection.selected(), rotated);
+    }
+
+    #[test]
+    fn test_options_are_staged_per_monitor() {
+        let (mut app, _rx) = app();
+        app.set_monitors(vec![monitor("DP-1", 0, 0), monitor("DP-2", 1920, 0)]);
+        assert_eq!(
+            app.monitor_options(),
+            [MonitorOption::Anchored, MonitorOption::Managed]
+        );
+        app.compositor = compositor::Compositor::Sway;
+        assert_eq!(
+            app.monitor_options(),
+            [
+                MonitorOption::Power,
+                MonitorOption::Anchored,
+                MonitorOption::Managed
+            ]
+        );
+
+        app.panel = Panel::Options;
+        app.next();
+        app.nav_right();
+        assert!(app.pending_option("DP-1", MonitorOption::Anchored));
+        assert!(!app.is_anchored("DP-1"));
+        assert!(!app.pending_option("DP-2", MonitorOption::Anchored));
+
+        app.nav_left();
+        assert!(app.pending_options.is_empty());
+        app.nav_right();
+        app.reset_positions();
+        assert!(app.pending_options.is_empty());
+    }
+
+    #[test]
+    fn test_harness_night_light_exclusion_restarts_wlsunset() {
+        let (mut app, recorder) = harness(Hyprland, None);
+        app.set_monitors(vec![monitor("DP-1", 0, 0), monitor("DP-2", 1920, 0)]);
+        assert!(!app.monitor_options().contains(&MonitorOption::NightLight));
+        app.night_light = Some(NightLight {
+            pid: None,
+            args: Vec::new(),
+        });
+        let options = app.monitor_options();
+        let row = options.iter().position(|&o| o == MonitorOption::NightLight);
+
+        assert!(app.select_monitor_named("DP-2"));
+        app.panel = Panel::Options;
+        app.options_selection.select(row);
+        app.cycle_option();
+        app.apply_action().unwrap();
+        assert!(app.is_night_light_excluded("DP-2"));
+        let restarts = || {
+            let record = recorder.record();
+            record.declined.iter().filter(|d| *d == "restart wlsunset").count()
+        };
+        assert_eq!(restarts(), 1);
+
+        play(&mut app, [changed(monitor("HDMI-A-1", 3840, 0))]);
+        assert_eq!(restarts(), 2);
+    }
+
+    #[test]
+    fn test_apply_all_enables_before_disabling_what_it_replaces() {
+        let (mut app, rx) = app();
+        let mut dp3 = monitor("DP-3", 0, 0);
+        dp3.enabled = false;
+        app.set_monitors(vec![
+            monitor("DP-1", 0, 0),
+            monitor("DP-2", 1920, 0),
+            dp3,
+        ]);
+        app.pending_toggles.insert("DP-2".into(), None);
+        app.pending_toggles.insert("DP-3".into(), Some((1920, 0)));
+        app.panel = Panel::Scale;
+        app.scale_up();
+        app.set_pending_scale(1.5);
+
+        app.open_apply_all();
+        let view = app.apply_all.as_ref().unwrap();
+        let steps: Vec<String> =
+            view.steps.iter().map(|step| step.to_string()).collect();
+        assert_eq!(
+            steps,
+            [
+                "enable DP-3 at 3840,0",
+                "scale DP-1 to 1.50",
+                "disable DP-2",
+                "move DP-3 to 1920,0",
+            ]
+        );
+        app.confirm_apply_all();
+        let sent: Vec<String> = rx.try_iter().map(|a| describe(&a)).collect();
+        assert_eq!(
+            sent,
+            [
+                "toggle DP-3 to 3840,0",
+                "scale DP-1 to 1.50",
+                "toggle DP-2",
+                "move DP-3 to 1920,0",
+            ]
+        );
+        let results = app.apply_all.as_ref().unwrap().results.as_ref();
+        assert!(results.unwrap().iter().all(|(_, error)| error.is_none()));
+        assert!(app.pending_toggles.is_empty());
+    }
+
+    #[test]
+    fn test_apply_all_refuses_to_switch_everything_off() {
+        let (mut app, _rx) = app();
+        app.set_monitors(vec![monitor("DP-1", 0, 0)]);
+        app.pending_toggles.insert("DP-1".into(), None);
+        app.open_apply_all();
+        assert!(app.apply_all.is_none());
+        assert!(app.error_message.is_some());
+    }
+
+    #[test]
+    fn test_harness_apply_all_notes_bars_on_moved_outputs() {
+        let (mut app, recorder) = harness(Hyprland, Some(SIDE_BY_SIDE));
+        app.set_monitors(vec![monitor("DP-1", 0, 0), monitor("DP-2", 1920, 0)]);
+        recorder.bar("waybar", "DP-1");
+        recorder.bar("waybar", "DP-2");
+
+        app.pending_toggles.insert("DP-2".into(), None);
+        app.open_apply_all();
+        let view = app.apply_all.as_ref().unwrap();
+        assert_eq!(
+            view.notes,
+            ["waybar has a layer surface on DP-2; you may need to restart it"]
+        );
+
+        // The note is only a note: everything is still applied.
+        app.confirm_apply_all();
+        assert_eq!(recorder.take_actions(), ["toggle DP-2"]);
+    }
+
+    #[test]
+    fn test_harness_applying_a_panel_notes_bars_on_moved_outputs() {
+        let (mut app, recorder) = harness(Hyprland, Some(SIDE_BY_SIDE));
+        app.set_monitors(vec![monitor("DP-1", 0, 0), monitor("DP-2", 1920, 0)]);
+        recorder.bar("waybar", "DP-1");
+        recorder.bar("waybar", "DP-2");
+
+        app.pending_positions.insert("DP-2".into(), (1920, 200));
+        app.apply_action().unwrap();
+        assert_eq!(recorder.take_actions(), ["move DP-2 to 1920,200"]);
+        let status = app.status_message.clone().unwrap();
+        assert!(status.contains("layer surface on DP-2"), "{status}");
+        assert!(!status.contains("DP-1"), "{status}");
+
+        let mut saved = Layout::capture(&app.monitors, &[]);
+        saved.monitors[0].enabled = false;
+        let notes = app.restore_layout(&saved).unwrap().unwrap();
+        assert_eq!(
+            notes,
+            ["waybar has a layer surface on DP-1; you may need to restart it"]
+        );
+    }
+
+    #[test]
+    fn test_mode_over_link_bandwidth_needs_a_second_enter() {
+        let (mut app, rx) = app();
+        let mut hdmi = monitor("HDMI-A-1", 0, 0);
+        hdmi.modes.push(mode(3840, 2160, 144));
+        hdmi.modes.push(mode(3840, 2160, 60));
+        app.set_monitors(vec![hdmi]);
+        app.panel = Panel::Mode;
+
+        app.mode_selection.select(Some(1));
+        app.apply_action().unwrap();
+        assert!(app.error_message.take().unwrap().contains("3840x2160@144"));
+        assert!(rx.try_recv().is_err());
+        app.apply_action().unwrap();
+        assert!(matches!(
+            rx.try_recv(),
+            Ok(WlMonitorAction::SwitchMode { refresh_rate: 144, .. })
+        ));
+
+        app.mode_selection.select(Some(2));
+        app.apply_action().unwrap();
+        assert!(matches!(
+            rx.try_recv(),
+            Ok(WlMonitorAction::SwitchMode { refresh_rate: 60, .. })
+        ));
+    }
+
+    #[test]
+    fn test_startup_focus_waits_for_its_monitor() {
+        let (mut app, _rx) = app();
+        let start = Instant::now();
+        let deadline = start + Duration::from_secs(3);
+        app.focus_on_startup(
+            Some("monitor dp-2".into()),
+            Some(Panel::Scale),
+            deadline,
+        );
+        app.set_monitors(vec![monitor("DP-1", 0, 0)]);
+        app.apply_startup_focus(start);
+        assert_eq!((app.selected_monitor, &app.panel), (0, &Panel::Monitor));
+
+        let mut dp2 = monitor("DP-2", 1920, 0);
+        dp2.scale = 1.5;
+        app.handle_event(WlMonitorEvent::Changed(Box::new(dp2)));
+        app.apply_startup_focus(start);
+        assert_eq!(app.selected_monitor().unwrap().name, "DP-2");
+        assert_eq!((&app.panel, app.pending_scale()), (&Panel::Scale, 1.5));
+        assert_eq!(app.startup_wait(start), None);
+
+        let (mut app, _rx) = crate::test_support::app();
+        app.set_monitors(vec![monitor("DP-1", 0, 0)]);
+        app.focus_on_startup(Some("HDMI-A-1".into()), None, deadline);
+        app.apply_startup_focus(deadline);
+        assert_eq!(app.selected_monitor, 0);
+        assert!(app.status_message.unwrap().contains("HDMI-A-1"));
+    }
+
+    #[test]
+    fn test_new_monitor_is_placed_right_of_the_layout() {
+        let (mut app, rx) = app();
+        app.set_monitors(vec![monitor("DP-1", 0, 0)]);
+        app.config.on_new_monitor = NewMonitorAction::EnableRight;
+
+        let mut dock = monitor("DP-2", 0, 0);
+        dock.enabled = false;
+        app.handle_event(WlMonitorEvent::Changed(Box::new(dock)));
+        let Ok(WlMonitorAction::Toggle {
+            name,
+            mode,
+            position,
+        }) = rx.try_recv()
+        else {
+            panic!("expected a toggle");
+        };
+        assert_eq!(name, "DP-2");
+        assert_eq!(mode, Some((1920, 1080, 60)));
+        assert_eq!(position, Some((1920, 0)));
+        assert!(app.needs_save());
+
+        app.handle_event(WlMonitorEvent::Changed(Box::new(monitor(
+            "DP-3", 3840, 0,
+        ))));
+        assert!(rx.try_recv().is_err());
+    }
+
+    #[test]
+    fn test_mst_ghosts_never_replace_the_real_monitor() {
+        let (mut app, _rx) = app();
+        let mut ghost = monitor("DP-1", 0, 0);
+        ghost.description = "Unknown".into();
+        ghost.modes.clear();
+        ghost.enabled = false;
+        // Even the initial state can list both.
+        app.set_monitors(vec![
+            ghost.clone(),
+            monitor("DP-1", 0, 0),
+            monitor("DP-2", 1920, 0),
+        ]);
+        assert_eq!(app.monitors.len(), 2);
+        assert_eq!(app.monitors[0].modes.len(), 1);
+
+        // The hub replugs: the ghost comes and goes, the real one is
+        // updated without modes in between.
+        let events = app.timeline.len();
+        app.handle_event(WlMonitorEvent::Changed(Box::new(ghost.clone())));
+        let mut bare = monitor("DP-1", 0, 0);
+        bare.modes.clear();
+        app.handle_event(WlMonitorEvent::Changed(Box::new(bare)));
+        ghost.enabled = true;
+        app.handle_event(WlMonitorEvent::Changed(Box::new(ghost)));
+
+        let dp1 = &app.monitors[0];
+        assert_eq!(dp1.description, "Test Monitor DP-1");
+        assert!(dp1.enabled);
+        assert_eq!(dp1.modes.len(), 1);
+        assert!(app.parked("DP-1").is_none());
+        assert_eq!(app.timeline.len(), events + 1);
+        app.select_monitor_named("DP-1");
+        app.panel = Panel::Mode;
+        assert!(!app.mode_entries().is_empty());
+
+        let later = Instant::now() + Duration::from_millis(GHOST_GRACE_MS);
+        app.drop_stale_ghosts(later);
+        assert!(app.ghosts.is_empty());
+    }
+
+    #[test]
+    fn test_an_observer_only_watches() {
+        let (mut app, rx) = app();
+        app.set_monitors(vec![monitor("DP-1", 0, 0), monitor("DP-2", 1920, 0)]);
+        app.config.on_new_monitor = NewMonitorAction::EnableRight;
+        app.observe();
+        assert!(app.timeline_view.is_some());
+
+        let mut dock = monitor("DP-3", 0, 0);
+        dock.enabled = false;
+        app.handle_event(WlMonitorEvent::Changed(Box::new(dock)));
+        // A bigger DP-1 would push DP-2 aside otherwise.
+        let mut scaled = monitor("DP-1", 0, 0);
+        scaled.scale = 0.5;
+        app.handle_event(WlMonitorEvent::Changed(Box::new(scaled)));
+
+        assert!(rx.try_recv().is_err());
+        assert!(app.pending_positions.is_empty());
+        assert!(!app.needs_save());
+        assert_eq!(app.timeline.len(), 2);
+    }
+
+    #[test]
+    fn test_new_monitor_waits_for_an_answer_when_asking() {
+        let (mut app, rx) = app();
+        app.set_monitors(vec![monitor("DP-1", 0, 0)]);
+        app.config.on_new_monitor = NewMonitorAction::Ask;
+
+        app.handle_event(WlMonitorEvent::Changed(Box::new(monitor(
+            "DP-2", 0, 0,
+        ))));
+        assert_eq!(app.new_monitor.as_deref(), Some("DP-2"));
+        assert!(rx.try_recv().is_err());
+
+        app.resolve_new_monitor(NewMonitorAction::EnableSaved);
+        assert!(app.new_monitor.is_none());
+        let Ok(WlMonitorAction::SetPosition { name, x, y }) = rx.try_recv()
+        else {
+            panic!("expected a position");
+        };
+        assert_eq!((name.as_str(), x, y), ("DP-2", 1920, 0));
+    }
+
+    #[test]
+    fn test_save_asks_before_rewriting_untouched_monitors() {
+        let path = std::env::temp_dir()
+            .join(format!("xwlm-untouched-{}.conf", std::process::id()));
+        fs::write(&path, "monitor = DP-1, preferred, auto, 1\n").unwrap();
+
+        let (mut app, _rx) = app();
+        app.compositor = compositor::Compositor::Hyprland;
+        app.comp_monitor_config_path = path.clone();
+        let mut docked = monitor("DP-2", 1920, 0);
+        docked.enabled = false;
+        app.set_monitors(vec![monitor("DP-1", 0, 0), docked]);
+
+        assert!(app.stage_position(0, 1080));
+        app.panel = Panel::Monitor;
+        app.apply_action().unwrap();
+        app.handle_event(changed(monitor("DP-1", 0, 1080)));
+        app.mark_dirty();
+        app.save_config();
+
+        let changes = app.untouched_changes.clone().unwrap();
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].name, "DP-2");
+        assert!(changes[0].old.is_empty());
+        assert!(changes[0].new.contains(&"monitor = DP-2, disable".into()));
+        assert!(app.needs_save());
+
+        app.untouched_changes = None;
+        app.config.include_untouched_monitors = true;
+        let content = render_monitor_config(
+            app.compositor,
+            &Layout::capture(&app.monitors, &[]),
+            &[],
+        )
+        .unwrap();
+        assert!(app.untouched_monitor_changes(&content).is_empty());
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_dry_run_logs_actions_and_writes() {
+        let path = std::env::temp_dir()
+            .join(format!("xwlm-dry-run-{}.conf", std::process::id()));
+        let mut config = Config::new(path.clone());
+        config.include_untouched_monitors = true;
+        let mut app = App::new(Box::new(DryRun::default()), config);
+        app.dismiss_config_check();
+        app.compositor = compositor::Compositor::Hyprland;
+        app.set_monitors(vec![monitor("DP-1", 0, 0), monitor("DP-2", 1920, 0)]);
+
+        assert!(app.stage_position(0, 1080));
+        app.panel = Panel::Monitor;
+        app.apply_action().unwrap();
+        assert_eq!(app.monitors[0].position.y, 1080);
+        assert!(!app.needs_save());
+        assert!(!path.exists());
+
+        let transcript = app.transcript();
+        assert_eq!(transcript[0], "move DP-1 to 0,1080");
+        let write = format!("write {}", path.display());
+        assert!(transcript.iter().any(|l| l.starts_with(&write)));
+        assert!(transcript.iter().any(|l| l == "reload Hyprland"));
+        assert_eq!(
+            app.status_message.as_deref(),
+            transcript.last().map(|l| format!("Dry run: {l}")).as_deref()
+        );
+        assert!(!app.needs_save());
+    }
+
+    const SIDE_BY_SIDE: &str = "monitor = DP-1, 1920x1080@60, 0x0, 1\n\
+                                monitor = DP-2, 1920x1080@60, 1920x0, 1\n";
+
+    fn sorted(mut lines: Vec<String>) -> Vec<String> {
+        lines.sort();
+        lines
+    }
+
+    #[test]
+    fn test_harness_moving_into_a_neighbour_pushes_it() {
+        let (mut app, recorder) = harness(Hyprland, None);
+        app.set_monitors(vec![
+            monitor("DP-1", 0, 0),
+            monitor("DP-2", 0, 1080),
+            monitor("DP-3", 1920, 0),
+        ]);
+        assert!(app.select_monitor_named("DP-1"));
+        app.move_monitor(PositionDirection::Down);
+        assert_eq!(app.pending_positions.get("DP-1"), Some(&(0, 1)));
+        assert_eq!(app.pending_positions.get("DP-2"), Some(&(0, 1081)));
+
+        app.panel = Panel::Monitor;
+        app.apply_action().unwrap();
+        assert_eq!(
+            sorted(recorder.take_actions()),
+            ["move DP-1 to 0,1", "move DP-2 to 0,1081"]
+        );
+    }
+
+    #[test]
+    fn test_harness_overlapping_position_is_refused() {
+        let (mut app, recorder) = harness(Hyprland, None);
+        app.set_monitors(vec![monitor("DP-1", 0, 0), monitor("DP-2", 1920, 0)]);
+        assert!(app.select_monitor_named("DP-2"));
+        assert!(!app.stage_position(1000, 0));
+        assert!(app.error_message.is_some());
+        assert!(app.pending_positions.is_empty());
+
+        app.panel = Panel::Monitor;
+        app.apply_action().unwrap();
+        assert!(recorder.take_actions().is_empty());
+    }
+
+    #[test]
+    fn test_harness_ignored_new_monitor_is_left_overlapping() {
+        let (mut app, recorder) = harness(Hyprland, None);
+        play(&mut app, [changed(monitor("DP-1", 0, 0))]);
+        play(&mut app, [changed(monitor("DP-2", 0, 0))]);
+        assert_eq!(app.monitors.len(), 2);
+        assert!(app.new_monitor.is_none());
+        assert!(recorder.take_actions().is_empty());
+        assert!(recorder.record().writes.is_empty());
+    }
+
+    #[test]
+    fn test_harness_toggle_on_restores_saved_position() {
+        let existing = format!(
+            "{SIDE_BY_SIDE}monitor = DP-3, 1920x1080@60, 3840x0, 1\n"
+        );
+        let (mut app, recorder) = harness(Hyprland, Some(&existing));
+        app.set_monitors(vec![
+            monitor("DP-1", 0, 0),
+            monitor("DP-2", 1920, 0),
+            disabled("DP-3"),
+        ]);
+        assert!(app.select_monitor_named("DP-3"));
+        app.toggle_monitor().unwrap();
+        assert!(recorder.take_actions().is_empty());
+
+        app.apply_action().unwrap();
+        assert_eq!(recorder.take_actions(), ["toggle DP-3 to 3840,0"]);
+    }
+
+    #[test]
+    fn test_harness_large_config_is_parsed_quickly_and_read_once() {
+        let mut existing = String::new();
+        for i in 0..10_000 {
+            existing.push_str(&match i % 4 {
+                0 => format!("# section {i}\n"),
+                1 => format!(
+                    "monitor = HDMI-A-{}, 1920x1080@60, {i}x0, 1\n",
+                    i % 500
+                ),
+                2 => format!(
+                    "workspace = {}, monitor:HDMI-A-{}\n",
+                    i % 300 + 1,
+                    i % 500
+                ),
+                _ => "\n".to_string(),
+            });
+        }
+        existing.push_str("monitor = DP-3, 1920x1080@60, 3840x0, 1\n");
+
+        let started = Instant::now();
+        let (mut app, recorder) = harness(Hyprland, Some(&existing));
+        assert!(started.elapsed() < Duration::from_secs(1));
+
+        app.set_monitors(vec![
+            monitor("DP-1", 0, 0),
+            monitor("DP-2", 1920, 0),
+            disabled("DP-3"),
+        ]);
+        let reads = recorder.record().reads;
+        assert!(app.select_monitor_named("DP-3"));
+        app.toggle_monitor().unwrap();
+        app.apply_action().unwrap();
+        assert_eq!(recorder.take_actions(), ["toggle DP-3 to 3840,0"]);
+        assert_eq!(recorder.record().reads, reads);
+    }
+
+    #[test]
+    fn test_harness_toggle_on_sees_a_config_changed_since_startup() {
+        let (mut app, recorder) = harness(Hyprland, Some(SIDE_BY_SIDE));
+        recorder.put(
+            CONFIG_PATH,
+            &format!("{SIDE_BY_SIDE}monitor = DP-3, 1920x1080@60, 3840x0, 1\n"),
+        );
+        app.set_monitors(vec![
+            monitor("DP-1", 0, 0),
+            monitor("DP-2", 1920, 0),
+            disabled("DP-3"),
+        ]);
+        assert!(app.select_monitor_named("DP-3"));
+        app.toggle_monitor().unwrap();
+        app.apply_action().unwrap();
+        assert_eq!(recorder.take_actions(), ["toggle DP-3 to 3840,0"]);
+    }
+
+    #[test]
+    fn test_harness_toggle_on_moves_off_a_taken_saved_position() {
+        let existing = format!(
+            "{SIDE_BY_SIDE}monitor = DP-3, 1920x1080@60, 1920x0, 1\n"
+        );
+        let (mut app, recorder) = harness(Hyprland, Some(&existing));
+        app.set_monitors(vec![
+            monitor("DP-1", 0, 0),
+            monitor("DP-2", 1920, 0),
+            disabled("DP-3"),
+        ]);
+        assert!(app.select_monitor_named("DP-3"));
+        app.toggle_monitor().unwrap();
+        app.apply_action().unwrap();
+        let actions = recorder.take_actions();
+        assert_eq!(actions.len(), 1);
+        assert!(actions[0].starts_with("toggle DP-3 to "));
+        assert_ne!(actions[0], "toggle DP-3 to 1920,0");
+    }
+
+    #[test]
+    fn test_harness_toggle_on_without_saved_position_goes_right() {
+        let (mut app, recorder) = harness(Hyprland, None);
+        app.set_monitors(vec![monitor("DP-1", 0, 0), disabled("DP-2")]);
+        assert!(app.select_monitor_named("DP-2"));
+        app.toggle_monitor().unwrap();
+        app.apply_action().unwrap();
+        assert_eq!(recorder.take_actions(), ["toggle DP-2 to 1920,0"]);
+    }
+
+    #[test]
+    fn test_harness_toggle_off_sends_no_position() {
+        let (mut app, recorder) = harness(Hyprland, Some(SIDE_BY_SIDE));
+        app.set_monitors(vec![monitor("DP-1", 0, 0), monitor("DP-2", 1920, 0)]);
+        assert!(app.select_monitor_named("DP-2"));
+        app.toggle_monitor().unwrap();
+        app.apply_action().unwrap();
+        assert_eq!(recorder.take_actions(), ["toggle DP-2"]);
+    }
+
+    #[test]
+    fn test_harness_only_selected_and_enable_all() {
+        let (mut app, recorder) = harness(Hyprland, Some(SIDE_BY_SIDE));
+        app.config.include_untouched_monitors = true;
+        app.set_monitors(vec![
+            monitor("DP-1", 0, 0),
+            monitor("DP-2", 1920, 0),
+            monitor("DP-3", 3840, 0),
+        ]);
+        assert!(app.select_monitor_named("DP-2"));
+        app.stage_only_selected();
+        assert!(recorder.take_actions().is_empty());
+        app.apply_action().unwrap();
+        play(&mut app, [changed(disabled("DP-1")), changed(disabled("DP-3"))]);
+        let mut actions = recorder.take_actions();
+        actions.sort();
+        assert_eq!(actions, ["toggle DP-1", "toggle DP-3"]);
+        assert_eq!(recorder.record().writes.len(), 1);
+
+        app.set_monitors(vec![
+            disabled("DP-1"),
+            monitor("DP-2", 1920, 0),
+            disabled("DP-3"),
+        ]);
+        app.stage_enable_all();
+        app.apply_action().unwrap();
+        play(
+            &mut app,
+            [
+                changed(monitor("DP-1", 0, 0)),
+                changed(monitor("DP-3", 3840, 0)),
+            ],
+        );
+        let mut actions = recorder.take_actions();
+        actions.sort();
+        // Seen turning off, the monitors come back in the mode they had.
+        assert_eq!(
+            actions,
+            [
+                "toggle DP-1 at 1920x1080@60 to 0,0",
+                "toggle DP-3 at 1920x1080@60 to 3840,0",
+            ]
+        );
+        assert_eq!(recorder.record().writes.len(), 2);
+    }
+
+    #[test]
+    fn test_harness_enable_all_keeps_new_monitors_apart() {
+        let (mut app, recorder) = harness(Hyprland, None);
+        app.set_monitors(vec![
+            monitor("DP-1", 0, 0),
+            disabled("DP-2"),
+            disabled("DP-3"),
+        ]);
+        app.stage_enable_all();
+        app.apply_action().unwrap();
+        let mut actions = recorder.take_actions();
+        actions.sort();
+        assert_eq!(actions, ["toggle DP-2 to 1920,0", "toggle DP-3 to 3840,0"]);
+    }
+
+    #[test]
+    fn test_harness_enable_all_restores_saved_modes_and_makes_room() {
+        let existing = "monitor = DP-1, 1920x1080@60, 0x0, 1\n\
+                        monitor = DP-2, 2560x1440@144, 0x0, 1\n";
+        let (mut app, recorder) = harness(Hyprland, Some(existing));
+        let mut off = disabled("DP-2");
+        off.modes.push(mode(2560, 1440, 144));
+        app.set_monitors(vec![monitor("DP-1", 0, 0), off]);
+
+        app.stage_enable_all();
+        app.apply_action().unwrap();
+        let mut actions = recorder.take_actions();
+        actions.sort();
+        assert_eq!(
+            actions,
+            [
+                "move DP-1 to 2560,0",
+                "switch DP-2 to 2560x1440@144",
+                "toggle DP-2 to 0,0",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_harness_toggle_on_restores_where_it_was_parked() {
+        let (mut app, recorder) = harness(Hyprland, None);
+        let mut fast = monitor("DP-2", 0, 1080);
+        fast.modes[0].is_current = false;
+        let mut current = mode(2560, 1440, 144);
+        current.is_current = true;
+        fast.modes.push(current);
+        fast.scale = 1.5;
+        app.set_monitors(vec![monitor("DP-1", 0, 0), fast.clone()]);
+
+        let mut off = fast.clone();
+        off.enabled = false;
+        off.scale = 1.0;
+        play(&mut app, [changed(off.clone())]);
+        assert!(recorder.record().declined.iter().any(|d| d.contains("park")));
+
+        assert!(app.select_monitor_named("DP-2"));
+        app.toggle_monitor().unwrap();
+        app.apply_action().unwrap();
+        assert_eq!(
+            recorder.take_actions(),
+            ["toggle DP-2 at 2560x1440@144 to 0,1080", "scale DP-2 to 1.50"]
+        );
+    }
+
+    #[test]
+    fn test_harness_parked_monitors_survive_a_restart() {
+        let (_, recorder) = harness(Hyprland, None);
+        let mut parked = Parked::default();
+        parked.remember(&monitor("DP-2", 0, 1080));
+        let path = parked::parked_path().unwrap();
+        let content = toml::to_string(&parked).unwrap();
+        recorder.put(path.to_str().unwrap(), &content);
+
+        let mut app = restart(Hyprland, &recorder);
+        app.set_monitors(vec![monitor("DP-1", 0, 0), disabled("DP-2")]);
+        assert!(app.select_monitor_named("DP-2"));
+        app.toggle_monitor().unwrap();
+        app.apply_action().unwrap();
+        assert_eq!(
+            recorder.take_actions(),
+            ["toggle DP-2 at 1920x1080@60 to 0,1080"]
+        );
+    }
+
+    #[test]
+    fn test_harness_saves_during_a_reload_are_merged() {
+        let (mut app, recorder) = harness(Hyprland, None);
+        app.config.live_apply = false;
+        app.config.reload_cooldown_ms = 60_000;
+        app.config.include_untouched_monitors = true;
+        app.set_monitors(vec![monitor("DP-1", 0, 0), monitor("DP-2", 1920, 0)]);
+        for _ in 0..50 {
+            app.monitors[1].position.x += 1;
+            app.mark_dirty();
+            app.save_config();
+        }
+        assert!(app.is_reloading());
+        let write = format!("write {CONFIG_PATH}");
+        let first = [write.as_str(), "reload Hyprland"];
+        assert_eq!(recorder.record().saves, first);
+
+        app.flush_saves(Instant::now());
+        assert_eq!(recorder.record().saves.len(), 2);
+
+        app.flush_saves(Instant::now() + Duration::from_secs(61));
+        assert_eq!(
+            recorder.record().saves,
+            [&write, "reload Hyprland", &write, "reload Hyprland"]
+        );
+        let (_, content) = recorder.record().writes.last().cloned().unwrap();
+        assert!(content.contains("1970x0"));
+    }
+
+    #[test]
+    fn test_harness_hyprland_applies_changed_monitors_with_keywords() {
+        let (mut app, recorder) = harness(Hyprland, None);
+        app.config.include_untouched_monitors = true;
+        app.set_monitors(vec![monitor("DP-1", 0, 0), monitor("DP-2", 1920, 0)]);
+        app.mark_dirty();
+        app.save_config();
+        let write = format!("write {CONFIG_PATH}");
+        assert_eq!(recorder.record().saves, [&write, "reload Hyprland"]);
+
+        app.monitors[1].scale = 2.0;
+        app.mark_dirty();
+        app.save_config();
+        assert_eq!(
+            recorder.record().saves[2..],
+            [
+                &write,
+                "hyprctl keyword monitor DP-2, 1920x1080@60, 1920x0, 2",
+            ]
+        );
+
+        // A monitor the config does not have yet needs the reload.
+        app.handle_event(changed(monitor("DP-3", 3840, 0)));
+        app.mark_dirty();
+        app.save_config();
+        let saves = recorder.record().saves.clone();
+        assert_eq!(saves[4..], [&write, "reload Hyprland"]);
+
+        // With autoreload on, Hyprland applies the written file itself.
+        recorder.autoreload();
+        app.monitors[1].scale = 1.0;
+        app.mark_dirty();
+        app.save_config();
+        let saves = recorder.record().saves.clone();
+        assert_eq!(saves[6..], [&write, "reload Hyprland"]);
+    }
+
+    #[test]
+    fn test_harness_own_changes_are_saved_once_they_are_reported() {
+        let (mut app, recorder) = harness(Hyprland, Some(SIDE_BY_SIDE));
+        app.config.live_apply = false;
+        app.set_monitors(vec![monitor("DP-1", 0, 0), monitor("DP-2", 1920, 0)]);
+        assert!(app.select_monitor_named("DP-1"));
+        assert!(app.stage_position(0, 1080));
+        assert!(app.select_monitor_named("DP-2"));
+        assert!(app.stage_position(0, 0));
+        app.panel = Panel::Monitor;
+        app.apply_action().unwrap();
+        let mut actions = recorder.take_actions();
+        actions.sort();
+        assert_eq!(actions, ["move DP-1 to 0,1080", "move DP-2 to 0,0"]);
+        assert!(recorder.record().writes.is_empty());
+
+        // Half of the change reported is not worth writing.
+        play(&mut app, [changed(monitor("DP-1", 0, 1080))]);
+        assert!(recorder.record().writes.is_empty());
+        play(&mut app, [changed(monitor("DP-2", 0, 0))]);
+        let write = format!("write {CONFIG_PATH}");
+        assert_eq!(recorder.record().saves, [&write, "reload Hyprland"]);
+        let content = recorder.file(CONFIG_PATH).unwrap();
+        assert!(content.contains("DP-1, 1920x1080@60, 0x1080"), "{content}");
+        assert!(content.contains("DP-2, 1920x1080@60, 0x0"), "{content}");
+
+        // Nor is what the reload reports back.
+        play(
+            &mut app,
+            [
+                changed(monitor("DP-1", 0, 1080)),
+                changed(monitor("DP-2", 0, 0)),
+            ],
+        );
+        assert_eq!(recorder.record().writes.len(), 1);
+        assert!(!app.needs_save());
+    }
+
+    #[test]
+    fn test_harness_undo_and_redo_replay_the_monitor_state() {
+        let (mut app, recorder) = harness(Hyprland, None);
+        app.config.instant_toggle = true;
+        app.set_monitors(vec![monitor("DP-1", 0, 0), monitor("DP-2", 1920, 0)]);
+        assert!(app.select_monitor_named("DP-2"));
+        app.toggle_monitor().unwrap();
+        assert_eq!(recorder.take_actions(), ["toggle DP-2"]);
+        app.handle_event(changed(disabled("DP-2")));
+        assert_eq!(app.undo_depth(), 1);
+
+        app.undo().unwrap();
+        assert_eq!(
+            recorder.take_actions(),
+            ["toggle DP-2 at 1920x1080@60 to 1920,0"]
+        );
+        assert_eq!(app.undo_depth(), 0);
+        app.handle_event(changed(monitor("DP-2", 1920, 0)));
+        app.undo().unwrap();
+        assert!(recorder.take_actions().is_empty());
+
+        app.redo().unwrap();
+        assert_eq!(recorder.take_actions(), ["toggle DP-2"]);
+        assert_eq!(app.undo_depth(), 1);
+    }
+
+    #[test]
+    fn test_harness_undo_keeps_only_changes_that_were_sent() {
+        let (mut app, recorder) = harness(Hyprland, None);
+        app.set_monitors(vec![monitor("DP-1", 0, 0), monitor("DP-2", 1920, 0)]);
+        assert!(app.select_monitor_named("DP-2"));
+        app.toggle_monitor().unwrap();
+        assert!(recorder.take_actions().is_empty());
+        assert_eq!(app.undo_depth(), 0);
+
+        app.panel = Panel::Workspace;
+        app.workspace_selection.select(Some(0));
+        app.cycle_workspace_monitor(true);
+        assert!(app.has_pending_workspaces());
+        assert_eq!(app.undo_depth(), 0);
+
+        app.config.instant_toggle = true;
+        assert!(app.select_monitor_named("DP-1"));
+        app.toggle_monitor().unwrap();
+        app.handle_event(changed(disabled("DP-1")));
+        assert!(app.select_monitor_named("DP-2"));
+        app.toggle_monitor().unwrap();
+        assert_eq!(app.confirm, Some(Confirm::LastMonitor));
+        app.dismiss_confirm();
+        assert_eq!(recorder.take_actions(), ["toggle DP-1"]);
+        assert_eq!(app.undo_depth(), 1);
+    }
+
+    #[test]
+    fn test_harness_undo_checks_the_replay_like_any_change() {
+        let (mut app, recorder) = harness(Hyprland, None);
+        app.config.instant_toggle = true;
+        app.set_monitors(vec![disabled("DP-1"), disabled("DP-2")]);
+        assert!(app.select_monitor_named("DP-1"));
+        app.toggle_monitor().unwrap();
+        app.handle_event(changed(monitor("DP-1", 0, 0)));
+        recorder.take_actions();
+        assert_eq!(app.undo_depth(), 1);
+
+        // Going back switches off the only monitor on.
+        app.undo().unwrap();
+        assert_eq!(app.confirm, Some(Confirm::LastMonitor));
+        assert!(recorder.take_actions().is_empty());
+        assert_eq!(app.undo_depth(), 1);
+        app.dismiss_confirm();
+        app.undo().unwrap();
+        app.accept_confirm().unwrap();
+        assert_eq!(recorder.take_actions(), ["toggle DP-1"]);
+        assert_eq!(app.undo_depth(), 0);
+    }
+
+    #[test]
+    fn test_harness_undo_on_the_terminal_monitor_is_guarded() {
+        let (mut app, recorder) = harness(Hyprland, None);
+        app.set_monitors(vec![monitor("DP-1", 0, 0), monitor("DP-2", 1920, 0)]);
+        app.panel = Panel::Transform;
+        app.transform_selection.select(Some(1));
+        app.apply_action().unwrap();
+        let mut turned = monitor("DP-1", 0, 0);
+        turned.transform = WlTransform::Rotate90;
+        app.handle_event(changed(turned));
+        recorder.take_actions();
+        assert!(app.guarded.is_none());
+
+        app.terminal_output = Some("DP-1".into());
+        app.undo().unwrap();
+        assert_eq!(recorder.take_actions(), ["transform DP-1 to Normal"]);
+        let guarded = app.guarded.as_ref().unwrap();
+        assert!(matches!(
+            guarded.revert,
+            Revert::Transform(WlTransform::Rotate90)
+        ));
+    }
+
+    #[test]
+    fn test_harness_saving_a_changed_config_backs_it_up() {
+        let (mut app, recorder) = harness(Hyprland, Some(SIDE_BY_SIDE));
+        app.config.include_untouched_monitors = true;
+        app.set_monitors(vec![monitor("DP-1", 0, 0), monitor("DP-2", 1920, 0)]);
+        app.mark_dirty();
+        app.save_config();
+        let backup = format!("{CONFIG_PATH}.bak.1");
+        assert_eq!(recorder.file(&backup).as_deref(), Some(SIDE_BY_SIDE));
+        let written = recorder.file(CONFIG_PATH);
+
+        app.monitors[1].scale = 2.0;
+        app.mark_dirty();
+        app.save_config();
+        assert_eq!(recorder.file(&backup), written);
+        let older = recorder.file(&format!("{CONFIG_PATH}.bak.2"));
+        assert_eq!(older.as_deref(), Some(SIDE_BY_SIDE));
+        let summary = app.session_summary();
+        assert!(summary.contains(&format!("backed up to {backup}")));
+    }
+
+    #[test]
+    fn test_harness_kwin_saves_through_kscreen_doctor() {
+        let (mut app, recorder) = harness(Kwin, None);
+        assert_eq!(app.config_check, None);
+        app.set_monitors(vec![monitor("DP-1", 0, 0), monitor("DP-2", 1920, 0)]);
+        app.mark_dirty();
+        app.save_config();
+        let saves = recorder.record().saves.clone();
+        assert_eq!(saves.len(), 1);
+        assert!(saves[0].starts_with("kscreen-doctor output.DP-1.enable"));
+        assert!(saves[0].contains("output.DP-2.position.1920,0"));
+        assert!(recorder.record().writes.is_empty());
+        assert!(!app.needs_save());
+
+        recorder.fail_reloads("no such output");
+        app.mark_dirty();
+        app.save_config();
+        assert!(matches!(app.save_state, SaveState::ReloadFailed { .. }));
+    }
+
+    #[test]
+    fn test_compositor_capabilities_flag_modes_and_scales() {
+        let (mut app, _recorder) = harness(Hyprland, None);
+        let mut m = monitor("DP-1", 0, 0);
+        m.modes.push(mode(2560, 1440, 144));
+        app.set_monitors(vec![m.clone()]);
+        assert!(app.mode_supported("DP-1", &m.modes[1]));
+
+        app.available_modes.insert(
+            "DP-1".into(),
+            vec![(1920, 1080, 60_000), (2560, 1440, 143_990)],
+        );
+        assert!(app.mode_supported("DP-1", &m.modes[1]));
+        app.available_modes
+            .insert("DP-1".into(), vec![(1920, 1080, 60_000)]);
+        assert!(app.mode_supported("DP-1", &m.modes[0]));
+        assert!(!app.mode_supported("DP-1", &m.modes[1]));
+        // Events for a known output keep what it listed.
+        app.handle_event(changed(m.clone()));
+        assert!(!app.mode_supported("DP-1", &m.modes[1]));
+
+        app.set_pending_scale(3.0);
+        assert_eq!(app.scale_over_limit(), None);
+        app.set_pending_scale(3.5);
+        assert_eq!(app.scale_over_limit(), Some(3.0));
+        app.panel = Panel::Scale;
+        app.apply_action().unwrap();
+        assert!(app.error_message.as_deref().unwrap().contains("3.00x"));
+    }
+
+    #[test]
+    fn test_59_94_hz_stays_apart_from_60_through_a_save() {
+        let (mut app, recorder) = harness(Hyprland, None);
+        let mut tv = monitor("HDMI-A-1", 0, 0);
+        tv.modes[0].is_current = false;
+        let mut ntsc = mode(1920, 1080, 59);
+        ntsc.is_current = true;
+        tv.modes.push(ntsc);
+        app.set_monitors(vec![tv.clone()]);
+        app.available_modes.insert(
+            "HDMI-A-1".into(),
+            vec![(1920, 1080, 60_000), (1920, 1080, 59_940)],
+        );
+        assert_eq!(app.refresh_mhz("HDMI-A-1", &tv.modes[0]), 60_000);
+        assert_eq!(app.refresh_mhz("HDMI-A-1", &tv.modes[1]), 59_940);
+
+        app.mark_dirty();
+        app.save_config();
+        let (_, content) = recorder.record().writes.last().cloned().unwrap();
+        assert!(content.contains("1920x1080@59.94,"), "{content}");
+
+        // Back on 60 Hz, the saved config still asks for the 59.94 mode.
+        tv.modes[0].is_current = true;
+        tv.modes[1].is_current = false;
+        let config = settings::config_monitors(Hyprland, &content);
+        assert_eq!(config[0].mode, Some((1920, 1080, Some(59_940))));
+        let divergences = reconcile::diverging(&config, &[tv.clone()]);
+        let wanted = &divergences[0].config;
+        assert_eq!(wanted.refresh(), "59.94");
+        let spec = ModeSpec {
+            width: wanted.width,
+            height: wanted.height,
+            refresh_rate: wanted.refresh_rate,
+        };
+        let (resolved, how) = resolve_mode(&tv, spec).unwrap();
+        assert_eq!((resolved.refresh_rate, how), (59, ModeMatch::Exact));
+    }
+
+    #[test]
+    fn test_only_selected_refuses_a_disabled_monitor() {
+        let (mut app, _rx) = app();
+        app.set_monitors(vec![monitor("DP-1", 0, 0), disabled("DP-2")]);
+        assert!(app.select_monitor_named("DP-2"));
+        app.stage_only_selected();
+        assert!(!app.has_pending_toggles());
+    }
+
+    #[test]
+    fn test_harness_distributed_workspaces_are_saved() {
+        let (mut app, recorder) = harness(Hyprland, None);
+        app.set_monitors(vec![monitor("DP-2", 1920, 0), monitor("DP-1", 0, 0)]);
+        app.distribute_workspaces();
+
+        let written = recorder.file(CONFIG_PATH).unwrap();
+        assert!(written.contains("workspace = 1, monitor:DP-1\n"));
+        assert!(written.contains("workspace = 5, monitor:DP-1\n"));
+        assert!(written.contains("workspace = 6, monitor:DP-2\n"));
+        assert!(written.contains("workspace = 10, monitor:DP-2\n"));
+        assert_eq!(recorder.record().writes.len(), 1);
+    }
+
+    #[test]
+    fn test_harness_workspaces_are_read_from_the_existing_config() {
+        let existing = format!("{SIDE_BY_SIDE}\nworkspace = 3, monitor:DP-2\n");
+        let (mut app, _recorder) = harness(Hyprland, Some(&existing));
+        play(
+            &mut app,
+            [WlMonitorEvent::InitialState(vec![
+                monitor("DP-1", 0, 0),
+                monitor("DP-2", 1920, 0),
+            ])],
+        );
+        let ws = app.workspace_assignments.iter().find(|w| w.id == 3);
+        assert_eq!(ws.unwrap().monitor.as_deref(), Some("DP-2"));
+    }
+
+    #[test]
+    fn test_harness_distribution_leaves_pinned_workspaces() {
+        let (mut app, _recorder) = harness(Hyprland, None);
+        app.set_monitors(vec![monitor("DP-1", 0, 0), monitor("DP-2", 1920, 0)]);
+        let first = &mut app.workspace_assignments[0];
+        first.is_pinned = true;
+        first.monitor = Some("DP-2".into());
+        app.distribute_workspaces();
+
+        let on = |name: &str| {
+            app.workspace_assignments
+                .iter()
+                .filter(|w| w.monitor.as_deref() == Some(name))
+                .count()
+        };
+        let pinned = app.workspace_assignments[0].monitor.as_deref();
+        assert_eq!(pinned, Some("DP-2"));
+        assert_eq!((on("DP-1"), on("DP-2")), (5, 5));
+    }
+
+    #[test]
+    fn test_harness_distribution_fills_monitors_left_to_right() {
+        let (mut app, _recorder) = harness(Hyprland, None);
+        app.set_monitors(vec![
+            monitor("DP-1", 3840, 0),
+            monitor("DP-3", 0, 0),
+            monitor("DP-2", 1920, 0),
+        ]);
+        app.distribute_workspaces();
+
+        let placed: Vec<_> = app
+            .workspace_assignments
+            .iter()
+            .map(|w| (w.id, w.monitor.as_deref().unwrap()))
+            .collect();
+        assert_eq!(
+            placed,
+            [
+                (1, "DP-3"),
+                (2, "DP-3"),
+                (3, "DP-3"),
+                (4, "DP-3"),
+                (5, "DP-2"),
+                (6, "DP-2"),
+                (7, "DP-2"),
+                (8, "DP-1"),
+                (9, "DP-1"),
+                (10, "DP-1"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_harness_distributing_again_changes_nothing() {
+        let (mut app, recorder) = harness(Hyprland, None);
+        app.set_monitors(vec![monitor("DP-1", 0, 0), monitor("DP-2", 1920, 0)]);
+        app.distribute_workspaces();
+        let placed = |app: &App| {
+            app.workspace_assignments
+                .iter()
+                .map(|w| w.monitor.clone())
+                .collect::<Vec<_>>()
+        };
+        let first = placed(&app);
+        let written = recorder.file(CONFIG_PATH).unwrap();
+
+        app.distribute_workspaces();
+        assert_eq!(placed(&app), first);
+        assert!(!app.needs_save());
+        assert_eq!(recorder.record().writes.len(), 1);
+        assert_eq!(recorder.file(CONFIG_PATH).unwrap(), written);
+        assert_eq!(
+            app.status_message.as_deref(),
+            Some("Workspaces already distributed (5/5)")
+        );
+    }
+
+    #[test]
+    fn test_harness_instant_toggle_saves_after_the_confirming_event() {
+        let (mut app, recorder) = harness(Hyprland, None);
+        app.config.instant_toggle = true;
+        play(
+            &mut app,
+            [WlMonitorEvent::InitialState(vec![
+                monitor("DP-1", 0, 0),
+                monitor("DP-2", 1920, 0),
+            ])],
+        );
+        assert!(recorder.record().writes.is_empty());
+
+        assert!(app.select_monitor_named("DP-2"));
+        app.toggle_monitor().unwrap();
+        assert_eq!(recorder.take_actions(), ["toggle DP-2"]);
+        assert!(app.needs_save());
+        assert!(recorder.record().writes.is_empty());
+
+        play(&mut app, [changed(disabled("DP-2"))]);
+        assert!(!app.needs_save());
+        assert_eq!(recorder.record().writes.len(), 1);
+        let written = recorder.file(CONFIG_PATH).unwrap();
+        assert!(written.contains("monitor = DP-2, disable\n"));
+
+        play(&mut app, [changed(disabled("DP-2"))]);
+        assert_eq!(recorder.record().writes.len(), 1);
+    }
+
+    #[test]
+    fn test_harness_failed_action_cancels_the_pending_save() {
+        let (mut app, recorder) = harness(Hyprland, None);
+        app.config.instant_toggle = true;
+        app.set_monitors(vec![monitor("DP-1", 0, 0), monitor("DP-2", 1920, 0)]);
+        assert!(app.select_monitor_named("DP-2"));
+        app.toggle_monitor().unwrap();
+
+        play(&mut app, [failed(ActionKind::Toggle)]);
+        assert!(!app.needs_save());
+        assert!(app.error_message.is_some());
+        assert!(recorder.record().writes.is_empty());
+    }
+
+    #[test]
+    fn test_harness_save_state_follows_the_write_and_the_reload() {
+        let (mut app, recorder) = harness(Hyprland, None);
+        app.config.live_apply = false;
+        app.set_monitors(vec![monitor("DP-1", 0, 0)]);
+        assert_eq!(app.save_state, SaveState::Clean);
+
+        app.mark_dirty();
+        assert_eq!(app.save_state, SaveState::Dirty);
+        app.save_config();
+        assert_eq!(app.save_state, SaveState::Clean);
+        assert!(app.request_quit());
+
+        recorder.fail_reloads("parse error on line 3");
+        app.mark_dirty();
+        app.save_config();
+        let reload_failed = SaveState::ReloadFailed {
+            reason: "parse error on line 3".into(),
+        };
+        assert_eq!(app.save_state, reload_failed);
+        assert!(!app.has_unsaved_changes());
+
+        // A change the compositor refuses leaves the failure standing
+        // rather than claiming all is saved.
+        app.mark_dirty();
+        play(&mut app, [failed(ActionKind::SetScale)]);
+        assert_eq!(app.save_state, reload_failed);
+
+        app.error_message = None;
+        app.show_reload_failure();
+        let error = app.error_message.clone().unwrap();
+        assert!(error.contains("parse error on line 3"), "{error}");
+
+        assert!(!app.request_quit());
+        assert_eq!(app.confirm, Some(Confirm::Quit));
+        app.dismiss_confirm();
+        assert_eq!(app.save_state, reload_failed);
+
+        // A failed save keeps the changes unsaved, whatever is rejected
+        // after it.
+        recorder.fail_writes("disk full");
+        app.mark_dirty();
+        app.save_config();
+        assert_eq!(app.save_state, SaveState::Dirty);
+        play(&mut app, [failed(ActionKind::SetScale)]);
+        assert!(app.needs_save());
+    }
+
+    #[test]
+    fn test_harness_quit_sums_up_the_session_with_confirm_quit() {
+        let (mut app, _recorder) = harness(Hyprland, None);
+        app.set_monitors(vec![monitor("DP-1", 0, 0)]);
+        assert!(app.request_quit());
+        app.panel = Panel::Scale;
+        app.set_pending_scale(1.25);
+        app.apply_action().unwrap();
+        let mut scaled = monitor("DP-1", 0, 0);
+        scaled.scale = 1.25;
+        play(&mut app, [changed(scaled)]);
+        app.mark_dirty();
+        app.save_config();
+        assert!(app.has_session_changes());
+        assert!(app.request_quit());
+
+        app.config.confirm_quit = true;
+        assert!(!app.request_quit());
+        assert_eq!(app.confirm, Some(Confirm::Quit));
+        let summary = app.session_summary();
+        assert_eq!(summary[0], "DP-1: scale 1 → 1.25");
+        let wrote = format!("wrote {}", Path::new(CONFIG_PATH).display());
+        assert!(summary.contains(&wrote), "{summary:?}");
+        assert_eq!(app.debug_dump().session, summary);
+    }
+
+    #[test]
+    fn test_harness_failed_action_flashes_its_panel_and_rings() {
+        let (mut app, _recorder) = harness(Hyprland, None);
+        app.config.failure_alert = xwlm_config::FailureAlert::Both;
+        app.set_monitors(vec![monitor("DP-1", 0, 0)]);
+
+        play(&mut app, [failed(ActionKind::SetScale)]);
+        assert!(app.is_flashing(Panel::Scale));
+        assert!(!app.is_flashing(Panel::Monitor));
+        assert!(app.take_bell());
+        assert!(!app.take_bell());
+
+        app.check_failure_flash(
+            Instant::now() + Duration::from_millis(FAILURE_FLASH_MS),
+        );
+        assert!(!app.is_flashing(Panel::Scale));
+    }
+
+    #[test]
+    fn test_harness_failed_reload_is_reported_and_alerted() {
+        let (mut app, recorder) = harness(Hyprland, None);
+        app.config.failure_alert = xwlm_config::FailureAlert::Flash;
+        app.set_monitors(vec![monitor("DP-1", 0, 0)]);
+        recorder.fail_reloads("parse error on line 3");
+
+        app.mark_dirty();
+        app.save_config();
+        assert_eq!(recorder.record().writes.len(), 1);
+        let error = app.error_message.clone().unwrap();
+        assert!(error.contains("parse error on line 3"), "{error}");
+        assert!(app.is_flashing(app.panel));
+        assert!(!app.take_bell());
+    }
+
+    #[test]
+    fn test_dry_run_never_rings_the_bell() {
+        let mut config = Config::new("/nonexistent/xwlm-dry-bell.conf".into());
+        config.failure_alert = xwlm_config::FailureAlert::Both;
+        let mut app = App::new(Box::new(DryRun::default()), config);
+        app.set_monitors(vec![monitor("DP-1", 0, 0)]);
+
+        play(&mut app, [failed(ActionKind::SwitchMode)]);
+        assert!(app.is_flashing(Panel::Mode));
+        assert!(!app.take_bell());
+    }
+
+    #[test]
+    fn test_harness_unmanaged_lines_survive_saves() {
+        let hand_written = "monitor=DP-2 ,  modeline 241.5 2560 2608 2640 \
+                            2720 1440 1443 1448 1481 +hsync, 1920x0,1";
+        let existing = format!(
+            "monitor = DP-1, 1920x1080@60, 0x0, 1\n{hand_written}\n\
+             workspace = 2, monitor:DP-2\n"
+        );
+        let (mut app, recorder) = harness(Hyprland, Some(&existing));
+        app.config.unmanaged_monitors = vec!["DP-2".into()];
+        app.config.include_untouched_monitors = true;
+        app.set_monitors(vec![monitor("DP-1", 0, 0), monitor("DP-2", 1920, 0)]);
+
+        play(&mut app, [changed(monitor("DP-1", 0, 1080))]);
+        app.mark_dirty();
+        app.save_config();
+        play(&mut app, [changed(monitor("DP-2", 3840, 0))]);
+        app.mark_dirty();
+        app.save_config();
+
+        assert_eq!(recorder.record().writes.len(), 2);
+        let written = recorder.file(CONFIG_PATH).unwrap();
+        assert!(written.contains("monitor = DP-1, 1920x1080@60, 0x1080, 1"));
+        assert!(written.contains(&format!("{hand_written}\n")), "{written}");
+        assert!(!written.contains("3840x0"), "{written}");
+    }
+
+    #[test]
+    fn test_harness_wayfire_saves_only_touch_output_sections() {
+        let existing = "\
+[core]
+plugins = autostart command move resize
+
+[output:DP-1]
+mode = 1920x1080@60000
+position = 0,0
+
+[output:DP-2]
+mode = 2560x1440@59951
+position = 1920,0
+vrr = true
+
+[autostart]
+bar = waybar
+";
+        let (mut app, recorder) = harness(Wayfire, Some(existing));
+        app.config.unmanaged_monitors = vec!["DP-2".into()];
+        app.config.include_untouched_monitors = true;
+        app.set_monitors(vec![monitor("DP-1", 0, 0), monitor("DP-2", 1920, 0)]);
+
+        play(&mut app, [changed(monitor("DP-1", 0, 1080))]);
+        app.mark_dirty();
+        app.save_config();
+
+        let written = recorder.file(CONFIG_PATH).unwrap();
+        assert!(written.starts_with("[core]\nplugins = "), "{written}");
+        assert!(written.contains("position = 0,1080\n"), "{written}");
+        assert!(
+            written.contains(
+                "[output:DP-2]\nmode = 2560x1440@59951\n\
+                 position = 1920,0\nvrr = true\n\n[autostart]"
+            ),
+            "{written}"
+        );
+        assert!(written.ends_with("[autostart]\nbar = waybar\n"));
+    }
+
+    #[test]
+    fn test_unmanaged_monitor_changes_need_a_second_go() {
+        let (mut app, recorder) = harness(Hyprland, None);
+        app.config.unmanaged_monitors = vec!["DP-2".into()];
+        app.set_monitors(vec![monitor("DP-1", 0, 0), monitor("DP-2", 1920, 0)]);
+        assert!(!app.is_managed("DP-2"));
+        assert!(app.select_monitor_named("DP-2"));
+        app.panel = Panel::Scale;
+        app.set_pending_scale(2.0);
+
+        app.apply_action().unwrap();
+        assert!(recorder.take_actions().is_empty());
+        let error = app.error_message.clone().unwrap();
+        assert!(error.contains("DP-2"), "{error}");
+
+        app.apply_action().unwrap();
+        assert_eq!(recorder.take_actions().len(), 1);
+
+        // Restoring a layout asks the same way, and asking about other
+        // outputs in between does not use up the confirmation.
+        let mut saved = Layout::capture(&app.monitors, &[]);
+        saved.monitors[1].y = 1080;
+        assert_eq!(app.restore_layout(&saved).unwrap(), None);
+        assert!(app.clear_of_unmanaged(["DP-1".to_string()]));
+        let fallbacks = app.restore_layout(&saved).unwrap();
+        assert_eq!(fallbacks, Some(Vec::new()));
+        assert_eq!(recorder.take_actions(), ["move DP-2 to 1920,1080"]);
+
+        // Cloning onto it keeps the picker open for the second Enter.
+        assert!(app.select_monitor_named("DP-1"));
+        app.open_clone_picker();
+        app.confirm_clone();
+        assert!(app.clone_picker.is_some());
+        app.confirm_clone();
+        assert!(app.clone_picker.is_none());
     }
 
-    pub fn cycle_workspace_monitor(&mut self, forward: bool) {
-        let Some(ws_idx) = self.workspace_state.selected() else {
-            return;
-        };
+    #[test]
+    fn test_managed_option_is_kept_in_the_settings() {
+        let (mut app, _recorder) = harness(Hyprland, None);
+        app.set_monitors(vec![monitor("DP-1", 0, 0)]);
+        let options = app.monitor_options();
+        let row = options.iter().position(|&o| o == MonitorOption::Managed);
+        app.panel = Panel::Options;
+        app.options_selection.select(row);
 
-        let Some(effective) = self.get_effective_workspace(ws_idx) else {
-            return;
-        };
+        app.cycle_option();
+        assert!(app.is_managed("DP-1"));
+        app.apply_action().unwrap();
+        assert_eq!(app.config.unmanaged_monitors, ["DP-1"]);
+        app.cycle_option();
+        app.apply_action().unwrap();
+        assert!(app.config.unmanaged_monitors.is_empty());
+    }
 
-        let monitors: Vec<usize> = self.monitors.iter().enumerate().map(|(i, _)| i).collect();
+    #[test]
+    fn test_harness_unplugging_does_not_save() {
+        let (mut app, recorder) = harness(Hyprland, Some(SIDE_BY_SIDE));
+        app.set_monitors(vec![monitor("DP-1", 0, 0), monitor("DP-2", 1920, 0)]);
+        play(&mut app, [removed("DP-2")]);
+        assert_eq!(app.monitors.len(), 1);
+        assert!(recorder.record().writes.is_empty());
+        assert_eq!(recorder.file(CONFIG_PATH).as_deref(), Some(SIDE_BY_SIDE));
+    }
 
-        if monitors.is_empty() {
-            return;
-        }
+    #[test]
+    fn test_harness_snapped_positions_are_reported_once() {
+        let (mut app, recorder) = harness(Hyprland, None);
+        let mut left = monitor("DP-1", 0, 0);
+        left.scale = 1.666;
+        app.set_monitors(vec![left, monitor("DP-2", 1153, 0)]);
 
-        let new_monitor_idx = match effective.monitor_idx {
-            None => {
-                if forward {
-                    Some(monitors[0])
-                } else {
-                    Some(monitors[monitors.len() - 1])
-                }
-            }
-            Some(idx) => {
-                let pos = monitors.iter().position(|&i| i == idx);
-                match pos {
-                    Some(p) => {
-                        if forward {
-                            if p + 1 >= monitors.len() {
-                                None
-                            } else {
-                                Some(monitors[p + 1])
-                            }
-                        } else if p == 0 {
-                            None
-                        } else {
-                            Some(monitors[p - 1])
-                        }
-                    }
-                    None => {
-                        if forward {
-                            Some(monitors[0])
-                        } else {
-                            Some(monitors[monitors.len() - 1])
-                        }
-                    }
-                }
-            }
-        };
+        app.mark_dirty();
+        app.save_config();
+        let written = recorder.file(CONFIG_PATH).unwrap();
+        assert!(written.contains("1152x0"));
+        let status = app.status_message.take().unwrap();
+        assert!(status.contains("DP-2 1153,0 → 1152,0"));
+        assert_eq!(app.monitors[1].position.x, 1153);
 
-        let mut new_ws = effective;
-        new_ws.monitor_idx = new_monitor_idx;
-        self.pending_workspaces.insert(ws_idx, new_ws);
+        app.mark_dirty();
+        app.save_config();
+        assert!(app.status_message.is_none());
+
+        app.config.snap_layout = false;
+        app.config.include_untouched_monitors = true;
+        app.mark_dirty();
+        app.save_config();
+        assert!(recorder.file(CONFIG_PATH).unwrap().contains("1153x0"));
     }
 
-    pub fn get_effective_workspace(&self, idx: usize) -> Option<WorkspaceAssignment> {
-        if let Some(ws) = self.pending_workspaces.get(&idx) {
-            return Some(ws.clone());
-        }
-        self.workspace_assignments.get(idx).cloned()
+    #[test]
+    fn test_transform_preview_reverts_unless_kept() {
+        let (mut app, recorder) = harness(Hyprland, None);
+        app.set_monitors(vec![monitor("DP-1", 0, 0)]);
+        app.panel = Panel::Transform;
+        app.next();
+        let now = Instant::now();
+        app.preview_transform(now).unwrap();
+        assert_eq!(recorder.take_actions(), ["transform DP-1 to Rotate 90"]);
+
+        app.check_transform_preview(now + Duration::from_secs(4));
+        assert!(recorder.take_actions().is_empty());
+        app.check_transform_preview(now + Duration::from_secs(5));
+        assert_eq!(recorder.take_actions(), ["transform DP-1 to Normal"]);
+        assert!(app.transform_preview.is_none());
+
+        app.preview_transform(now).unwrap();
+        app.panel = Panel::Options;
+        app.check_transform_preview(now);
+        let actions = recorder.take_actions();
+        assert_eq!(actions.last().unwrap(), "transform DP-1 to Normal");
+
+        app.panel = Panel::Transform;
+        app.preview_transform(now).unwrap();
+        app.apply_action().unwrap();
+        // The compositor reports back each of the six transforms sent.
+        let mut rotated = monitor("DP-1", 0, 0);
+        rotated.transform = WlTransform::Rotate90;
+        play(&mut app, (0..6).map(|_| changed(rotated.clone())));
+        app.check_transform_preview(now + Duration::from_secs(10));
+        let actions = recorder.take_actions();
+        assert_eq!(actions.last().unwrap(), "transform DP-1 to Rotate 90");
+        assert_eq!(recorder.record().writes.len(), 1);
     }
 
-    pub fn has_pending_workspaces(&self) -> bool {
-        !self.pending_workspaces.is_empty()
+    #[test]
+    fn test_align_for_focus_stages_the_suggested_position() {
+        let (mut app, _rx) = app();
+        let mut laptop = monitor("eDP-1", 2200, 1080);
+        laptop.scale = 2.0;
+        app.set_monitors(vec![monitor("DP-1", 0, 0), laptop]);
+        let gaps = app.focus_gaps();
+        assert_eq!(gaps.len(), 4);
+        assert_eq!(gaps[0].to_string(), "from DP-1, focus-right reaches \
+             nothing although eDP-1 is right of it but offset by 1080px");
+
+        app.selected_monitor = 1;
+        app.align_for_focus();
+        assert_eq!(app.pending_positions["eDP-1"], (2200, 540));
+        assert_eq!(app.focus_gaps(), []);
+
+        app.align_for_focus();
+        assert_eq!(
+            app.status_message.as_deref(),
+            Some("Focus reaches every neighbour")
+        );
     }
 
-    pub fn nav_left(&mut self) {
-        match self.panel {
-            Panel::Monitor => self.move_monitor(PositionDirection::Left),
-            Panel::Scale => self.scale_down(),
-            Panel::Workspace => self.cycle_workspace_monitor(false),
-            _ => {}
-        }
+    #[test]
+    fn test_harness_renamed_monitor_gets_its_settings_back() {
+        let (mut app, recorder) = harness(Hyprland, None);
+        let mut dock = monitor("DP-3", 1920, 0);
+        dock.modes[0].is_current = false;
+        let mut current = mode(2560, 1440, 144);
+        current.is_current = true;
+        dock.modes.push(current);
+        dock.scale = 1.5;
+        app.set_monitors(vec![monitor("DP-1", 0, 0), dock.clone()]);
+        app.config.anchored_monitors = vec!["DP-3".into()];
+        app.workspace_assignments[2].monitor = Some("DP-3".into());
+        app.pending_transforms
+            .insert("DP-3".into(), WlTransform::Rotate90);
+
+        // The dock comes back with its connector renumbered, at defaults.
+        let mut renamed = dock.clone();
+        renamed.name = "DP-5".into();
+        renamed.position.x = 0;
+        renamed.scale = 1.0;
+        renamed.modes[0].is_current = true;
+        renamed.modes[1].is_current = false;
+        play(&mut app, [removed("DP-3"), changed(renamed)]);
+
+        assert_eq!(
+            recorder.take_actions(),
+            [
+                "switch DP-5 to 2560x1440@144",
+                "scale DP-5 to 1.50",
+                "move DP-5 to 1920,0",
+            ]
+        );
+        assert_eq!(
+            app.status_message.as_deref(),
+            Some("DP-5 recognized as former DP-3 — settings restored")
+        );
+        assert!(app.is_anchored("DP-5"));
+        assert!(!app.is_anchored("DP-3"));
+        assert_eq!(
+            app.workspace_assignments[2].monitor.as_deref(),
+            Some("DP-5")
+        );
+        assert_eq!(app.pending_transforms["DP-5"], WlTransform::Rotate90);
+        assert!(app.new_monitor.is_none());
     }
 
-    pub fn nav_right(&mut self) {
-        match self.panel {
-            Panel::Monitor => self.move_monitor(PositionDirection::Right),
-            Panel::Scale => self.scale_up(),
-            Panel::Workspace => self.cycle_workspace_monitor(true),
-            _ => {}
-        }
+    #[test]
+    fn test_only_a_recent_matching_monitor_counts_as_renamed() {
+        let (mut app, _rx) = app();
+        app.config.on_new_monitor = NewMonitorAction::Ask;
+        app.set_monitors(vec![monitor("DP-1", 0, 0), monitor("DP-3", 0, 0)]);
+
+        // Another display on the new connector is just a new monitor.
+        app.handle_event(removed("DP-3"));
+        app.handle_event(changed(monitor("DP-5", 0, 0)));
+        assert_eq!(app.new_monitor.as_deref(), Some("DP-5"));
+        assert!(app.status_message.is_none());
+
+        // The same display coming back too late is one as well.
+        app.new_monitor = None;
+        app.departed[0].at -= RENAME_WINDOW_SECS + 1;
+        let mut late = monitor("DP-6", 0, 0);
+        late.description = "Test Monitor DP-3".into();
+        app.handle_event(changed(late));
+        assert_eq!(app.new_monitor.as_deref(), Some("DP-6"));
+        assert!(app.departed.is_empty());
     }
 
-    pub fn toggle_panel(&mut self) {
-        self.panel = match self.panel {
-            Panel::Monitor => Panel::Mode,
-            Panel::Mode => Panel::Workspace,
-            Panel::Workspace => Panel::Scale,
-            Panel::Scale => Panel::Transform,
-            Panel::Transform => Panel::Monitor,
-        };
+    #[test]
+    fn test_harness_risky_change_to_the_terminal_monitor_reverts_unless_kept() {
+        let (mut app, recorder) = harness(Hyprland, None);
+        app.set_monitors(vec![monitor("DP-1", 0, 0), monitor("DP-2", 1920, 0)]);
+        app.terminal_output = Some("DP-1".into());
+        app.panel = Panel::Transform;
+        app.transform_selection.select(Some(1));
+        app.apply_action().unwrap();
+        let deadline = app.guarded.as_ref().unwrap().deadline;
+        app.check_guarded(deadline - Duration::from_secs(1));
+        assert!(app.guarded.is_some());
+        app.check_guarded(deadline);
+        assert!(app.guarded.is_none());
+        assert_eq!(
+            recorder.take_actions(),
+            ["transform DP-1 to Rotate 90", "transform DP-1 to Normal"]
+        );
+
+        // Any key keeps it.
+        app.apply_action().unwrap();
+        app.keep_guarded();
+        app.check_guarded(deadline + Duration::from_secs(60));
+        assert_eq!(recorder.take_actions(), ["transform DP-1 to Rotate 90"]);
+
+        // A small scale step is fine, a big jump is guarded.
+        app.panel = Panel::Scale;
+        app.set_pending_scale(1.25);
+        app.apply_action().unwrap();
+        assert!(app.guarded.is_none());
+        app.set_pending_scale(2.0);
+        app.apply_action().unwrap();
+        assert_eq!(app.guarded.as_ref().unwrap().revert, Revert::Scale(1.0));
+        app.keep_guarded();
+
+        // Switching it off comes back on Esc.
+        app.panel = Panel::Monitor;
+        app.toggle_monitor().unwrap();
+        app.apply_action().unwrap();
+        assert_eq!(app.guarded.as_ref().unwrap().revert, Revert::Enable(0, 0));
+        recorder.take_actions();
+        app.revert_guarded();
+        assert_eq!(recorder.take_actions(), ["toggle DP-1 to 0,0"]);
+
+        // Other monitors are not guarded.
+        assert!(app.select_monitor_named("DP-2"));
+        app.panel = Panel::Transform;
+        app.apply_action().unwrap();
+        assert!(app.guarded.is_none());
+
+        // Nor is the `W` review or an instant toggle a way around it.
+        assert!(app.select_monitor_named("DP-1"));
+        app.panel = Panel::Monitor;
+        app.pending_transforms.insert("DP-1".into(), WlTransform::Rotate180);
+        app.open_apply_all();
+        app.confirm_apply_all();
+        let revert = app.guarded.take().unwrap().revert;
+        assert_eq!(revert, Revert::Transform(WlTransform::Normal));
+        app.close_apply_all();
+        app.config.instant_toggle = true;
+        app.toggle_monitor().unwrap();
+        assert_eq!(app.guarded.as_ref().unwrap().revert, Revert::Enable(0, 0));
     }
 
-    pub fn save_config(&mut self) {
-        if !self.needs_save {
-            return;
+    #[test]
+    fn test_zoom_steps_proportionally_within_the_configured_limits() {
+        let (mut app, _rx) = app();
+        app.set_monitors(vec![monitor("DP-1", 0, 0)]);
+        app.zoom_in();
+        app.zoom_in();
+        assert_eq!(app.zoom_percent(), 121);
+        app.zoom_out();
+        app.zoom_out();
+        assert_eq!(app.zoom_percent(), 100);
+
+        app.config.zoom_max = 1.5;
+        for _ in 0..10 {
+            app.zoom_in();
         }
-        self.needs_save = false;
+        assert_eq!(app.zoom_percent(), 150);
 
-        let workspace_rules: Vec<WorkspaceRule> = self
-            .workspace_assignments
-            .iter()
-            .map(|ws| {
-                let monitor_name = ws
-                    .monitor_idx
-                    .and_then(|idx| self.monitors.get(idx))
-                    .map(|m| m.name.clone())
-                    .unwrap_or_default();
-                WorkspaceRule {
-                    id: ws.id,
-                    monitor: monitor_name,
-                    is_default: ws.is_default,
-                    is_persistent: ws.is_persistent,
-                }
-            })
-            .collect();
+        // Tighter limits take hold on the next hotplug.
+        app.config.zoom_max = 1.2;
+        play(&mut app, vec![changed(monitor("DP-2", 1920, 0))]);
+        assert_eq!(app.zoom_percent(), 120);
+        app.config.zoom_min = 1.3;
+        play(&mut app, vec![removed("DP-2")]);
+        assert_eq!(app.zoom_percent(), 130);
 
-        if let Err(e) = save_monitor_config(
-            self.compositor,
-            &self.comp_monitor_config_path,
-            &self.monitors,
-            &workspace_rules,
-        ) {
-            self.set_error(format!("Failed to save config: {e}"));
-        } else {
-            reload(self.compositor);
+        // `+` zooms past the whole layout, a hotplug or resize back out.
+        app.config.zoom_min = 0.2;
+        app.config.zoom_max = 5.0;
+        for _ in 0..10 {
+            app.zoom_in();
         }
+        assert_eq!(app.zoom_percent(), 337);
+        play(&mut app, vec![changed(monitor("DP-2", 1920, 0))]);
+        assert_eq!(app.zoom_percent(), 125);
+        app.fit_map();
+        assert_eq!(app.zoom_percent(), 125);
     }
 
-    pub fn reset_positions(&mut self) {
-        self.pending_positions.clear();
-        self.pending_workspaces.clear();
+    #[test]
+    fn test_harness_workspaces_can_follow_the_selected_monitor() {
+        let existing = "monitor = DP-1, 1920x1080@60, 0x0, 1\n\
+                        monitor = DP-2, 1920x1080@60, 1920x0, 1\n\
+                        workspace = 1, monitor:DP-1\n\
+                        workspace = 2, monitor:DP-2\n\
+                        workspace = 3, monitor:DP-1\n";
+        let (mut app, recorder) = harness(Hyprland, Some(existing));
+        app.set_monitors(vec![monitor("DP-1", 0, 0), monitor("DP-2", 1920, 0)]);
+        app.panel = Panel::Workspace;
+        let listed = |app: &App| -> Vec<usize> {
+            let rows = app.visible_workspaces().into_iter();
+            rows.map(|i| app.workspace_assignments[i].id).take(4).collect()
+        };
+        assert_eq!(listed(&app), [1, 2, 3, 4]);
+
+        app.toggle_workspace_filter();
+        assert_eq!(listed(&app), [1, 3, 4, 5]);
+        let filter = "remember the workspace filter".to_string();
+        assert!(recorder.record().declined.contains(&filter));
+
+        // The selection is the workspace, not the row it is listed on.
+        app.next();
+        assert_eq!(app.workspace_row(), Some(1));
+        let ws3 = app.workspace_selection.selected().unwrap();
+        assert_eq!(app.workspace_assignments[ws3].id, 3);
+
+        // Assigned away, it stays listed for a moment, then goes.
+        app.nav_right();
+        assert_eq!(app.workspace_leaving.map(|(idx, _)| idx), Some(ws3));
+        app.check_workspace_leaving(Instant::now());
+        assert_eq!(listed(&app), [1, 3, 4, 5]);
+        let later = Instant::now() + Duration::from_secs(2);
+        app.check_workspace_leaving(later);
+        assert_eq!(listed(&app), [1, 4, 5, 6]);
+        let selected = app.workspace_selection.selected().unwrap();
+        assert_eq!(app.workspace_assignments[selected].id, 4);
+
+        app.select_next_monitor();
+        assert_eq!(listed(&app), [2, 3, 4, 5]);
+        assert_eq!(app.workspace_row(), Some(2));
+
+        // Remembered for the next run.
+        let path = ui_state::ui_state_path().unwrap();
+        let state = UiState {
+            workspaces_follow_monitor: true,
+            toured: false,
+        };
+        let content = toml::to_string(&state).unwrap();
+        recorder.put(path.to_str().unwrap(), &content);
+        let app = restart(Hyprland, &recorder);
+        assert!(app.workspaces_follow_monitor());
     }
 
-    pub fn select_next_monitor(&mut self) {
-        if self.monitors.is_empty() {
-            return;
+    #[test]
+    fn test_the_tour_shows_every_panel_once() {
+        let (mut app, recorder) = harness(Hyprland, None);
+        app.set_monitors(vec![monitor("DP-1", 0, 0)]);
+        // An existing config counts as toured; the run after setup is not.
+        app.offer_tour(false);
+        assert_eq!(app.touring(), None);
+        app.first_launch();
+        app.offer_tour(false);
+        let mut shown = Vec::new();
+        while let Some(panel) = app.touring() {
+            assert_eq!(app.panel, panel);
+            shown.push(panel);
+            app.tour_step();
         }
-        self.selected_monitor = (self.selected_monitor + 1) % self.monitors.len();
-        self.mode_state.select(Some(0));
-        self.sync_panel_state();
+        assert_eq!(shown, TOUR);
+        assert_eq!(app.panel, Panel::Monitor);
+        let declined = recorder.record().declined.clone();
+        assert!(declined.iter().any(|d| d == "remember the tour"));
+
+        // Once seen, only --tour brings it back.
+        let path = ui_state::ui_state_path().unwrap();
+        let content = toml::to_string(&app.ui_state).unwrap();
+        recorder.put(path.to_str().unwrap(), &content);
+        let mut app = restart(Hyprland, &recorder);
+        app.offer_tour(false);
+        assert_eq!(app.touring(), None);
+        app.offer_tour(true);
+        assert_eq!(app.touring(), Some(Panel::Monitor));
+        app.end_tour();
+        assert_eq!(app.touring(), None);
+
+        // It hands back the panel it started on, or the one --panel asked
+        // for once that applies.
+        app.panel = Panel::Scale;
+        app.offer_tour(true);
+        app.end_tour();
+        assert_eq!(app.panel, Panel::Scale);
+        let now = Instant::now();
+        app.focus_on_startup(None, Some(Panel::Transform), now);
+        app.offer_tour(true);
+        app.apply_startup_focus(now);
+        assert_eq!(app.panel, Panel::Monitor);
+        app.end_tour();
+        assert_eq!(app.panel, Panel::Transform);
     }
 
-    pub fn select_prev_monitor(&mut self) {
-        if self.monitors.is_empty() {
-            return;
-        }
-        self.selected_monitor = if self.selected_monitor == 0 {
-            self.monitors.len() - 1
-        } else {
-            self.selected_monitor - 1
-        };
-        self.mode_state.select(Some(0));
-        self.sync_panel_state();
+    #[test]
+    fn test_dry_run_tours_only_when_asked() {
+        let config = Config::new(PathBuf::new());
+        let mut app = App::new(Box::new(DryRun::default()), config);
+        app.set_monitors(vec![monitor("DP-1", 0, 0)]);
+        app.first_launch();
+        app.offer_tour(false);
+        assert_eq!(app.touring(), None);
+        app.offer_tour(true);
+        assert_eq!(app.touring(), Some(Panel::Monitor));
     }
 
-    fn sync_panel_state(&mut self) {
-        let Some(monitor) = self.monitors.get(self.selected_monitor) else {
-            return;
+    #[test]
+    fn test_harness_workspaces_of_an_absent_monitor_are_kept() {
+        let existing = "monitor = DP-1, 1920x1080@60, 0x0, 1\n\
+                        workspace = 1, monitor:DP-1\n\
+                        workspace = 2, monitor:DP-3\n\
+                        workspace = 3, monitor:DP-3\n";
+        let (mut app, recorder) = harness(Hyprland, Some(existing));
+        app.set_monitors(vec![monitor("DP-1", 0, 0)]);
+        let ws = app.workspace_assignments[1].clone();
+        assert_eq!(ws.monitor, None);
+        assert_eq!(ws.reserved.as_deref(), Some("DP-3"));
+
+        app.mark_dirty();
+        app.save_config();
+        let saved = recorder.file(CONFIG_PATH).unwrap();
+        assert!(saved.contains("workspace = 2, monitor:DP-3"), "{saved}");
+
+        // Assigning one in the panel drops its reservation.
+        app.panel = Panel::Workspace;
+        app.workspace_selection.select(Some(2));
+        app.cycle_workspace_monitor(true);
+        app.apply_action().unwrap();
+        let ws = &app.workspace_assignments[2];
+        assert_eq!(ws.reserved, None);
+        assert_eq!(ws.monitor.as_deref(), Some("DP-1"));
+
+        play(&mut app, vec![changed(monitor("DP-3", 1920, 0))]);
+        let ws = &app.workspace_assignments[1];
+        assert_eq!(ws.monitor.as_deref(), Some("DP-3"));
+        assert_eq!(ws.reserved, None);
+    }
+
+    #[test]
+    fn test_harness_generic_wlr_saves_a_wlr_randr_script() {
+        let (mut app, recorder) = harness(Unknown, None);
+        app.set_monitors(vec![monitor("DP-1", 0, 0), disabled("DP-2")]);
+        app.mark_dirty();
+        app.save_config();
+        let script = recorder.file(CONFIG_PATH).unwrap();
+        assert!(
+            script.contains(
+                "wlr-randr --output DP-1 --on --mode 1920x1080@60Hz \
+                 --pos 0,0 --scale 1 --transform normal"
+            ),
+            "{script}"
+        );
+        assert!(script.contains("wlr-randr --output DP-2 --off"), "{script}");
+        let read = settings::config_monitors(Unknown, &script);
+        assert_eq!(read.len(), 2, "{script}");
+        assert!(!app.needs_save());
+    }
+
+    #[test]
+    fn test_harness_a_monitor_without_modes_never_saves_0x0() {
+        let mut headless = monitor("HEADLESS-1", 1920, 0);
+        headless.modes.clear();
+        headless.resolution = WlResolution {
+            width: 0,
+            height: 0,
         };
-        self.pending_scale = monitor.scale;
-        if let Some(tidx) = TRANSFORMS.iter().position(|&x| x == monitor.transform) {
-            self.transform_state.select(Some(tidx));
-        }
-        if let Some(mode_idx) = monitor.modes.iter().position(|m| m.is_current) {
-            self.mode_state.select(Some(mode_idx));
-        } else {
-            self.mode_state.select(Some(0));
+        for comp in [Hyprland, Sway, River, Niri, Wayfire, Labwc, Unknown] {
+            let (mut app, recorder) = harness(comp, None);
+            app.set_monitors(vec![monitor("DP-1", 0, 0), headless.clone()]);
+            app.mark_dirty();
+            app.save_config();
+            let saved = recorder.file(CONFIG_PATH).unwrap();
+            let lines: Vec<&str> = saved
+                .lines()
+                .filter(|l| l.contains("HEADLESS-1"))
+                .collect();
+            assert!(!lines.is_empty(), "{comp:?}: {saved}");
+            let zero = |w: &str| w.starts_with("0x0");
+            for line in &lines {
+                let mut words = line.split([' ', ',']);
+                assert!(!words.any(zero), "{comp:?}: {saved}");
+            }
+            assert!(!saved.contains("0x0@"), "{comp:?}: {saved}");
+            assert!(saved.contains("1920x1080@60"), "{comp:?}: {saved}");
         }
+
+        let (mut app, recorder) = harness(Hyprland, None);
+        app.set_monitors(vec![headless]);
+        app.mark_dirty();
+        app.save_config();
+        let saved = recorder.file(CONFIG_PATH).unwrap();
+        assert!(saved.contains("monitor = HEADLESS-1, preferred, 1920x0, 1"));
     }
 
-    pub fn toggle_persistent(&mut self) {
-        let Some(ws_idx) = self.workspace_state.selected() else {
-            return;
+    #[test]
+    fn test_harness_workspace_lines_are_rewritten_where_they_are() {
+        let existing = "monitor = DP-1, 1920x1080@60, 0x0, 1\n\
+                        workspace = 1, monitor:DP-1\n\
+                        monitor = DP-2, 1920x1080@60, 1920x0, 1\n\
+                        workspace=2,monitor:\"DP-2\"\n";
+        let (mut app, recorder) = harness(Hyprland, Some(existing));
+        app.set_monitors(vec![monitor("DP-1", 0, 0), monitor("DP-2", 1920, 0)]);
+        let lines = |app: &App| -> Vec<Option<usize>> {
+            app.workspace_assignments.iter().take(3).map(|w| w.line).collect()
         };
+        assert_eq!(lines(&app), [Some(2), Some(4), None]);
 
-        let Some(mut effective) = self.get_effective_workspace(ws_idx) else {
-            return;
-        };
-        effective.is_persistent = !effective.is_persistent;
-        self.pending_workspaces.insert(ws_idx, effective);
+        app.panel = Panel::Workspace;
+        app.workspace_selection.select(Some(0));
+        app.cycle_workspace_monitor(true);
+        app.apply_action().unwrap();
+        let saved = recorder.file(CONFIG_PATH).unwrap();
+        let expected = "monitor = DP-1, 1920x1080@60, 0x0, 1\n\
+                        workspace = 1, monitor:DP-2\n\
+                        monitor = DP-2, 1920x1080@60, 1920x0, 1\n\
+                        workspace=2,monitor:\"DP-2\"\n\
+                        workspace = 3, monitor:\n";
+        assert!(saved.contains(expected), "{saved}");
+        assert_eq!(lines(&app), [Some(5), Some(7), Some(8)]);
     }
 
-    pub fn toggle_default(&mut self) {
-        let Some(ws_idx) = self.workspace_state.selected() else {
-            return;
+    #[test]
+    fn test_harness_model_defaults_apply_to_the_first_new_one() {
+        let existing = "monitor = DP-1, 1920x1080@60, 0x0, 1\n\
+                        monitor = HDMI-A-1, 1920x1080@60, 3840x0, 1.5\n";
+        let lg = |name: &str, x| {
+            let mut m = monitor(name, x, 0);
+            m.make = "LG Electronics".into();
+            m.model = "LG HDR 4K".into();
+            m
         };
+        let defaults = BTreeMap::from([(
+            "LG Electronics LG HDR 4K".to_string(),
+            MonitorDefaults {
+                transform: Some("90".into()),
+                scale: Some(1.2),
+            },
+        )]);
+        let (mut app, recorder) = harness(Hyprland, Some(existing));
+        app.config.defaults = defaults.clone();
+        app.set_monitors(vec![monitor("DP-1", 0, 0)]);
 
-        let Some(effective) = self.get_effective_workspace(ws_idx) else {
-            return;
-        };
+        // New monitors are left alone, so the defaults wait for Enter.
+        app.handle_event(changed(lg("DP-2", 1920)));
+        let rotated = Some(&WlTransform::Rotate90);
+        assert_eq!(app.pending_transforms.get("DP-2"), rotated);
+        assert_eq!(app.pending_scales.get("DP-2"), Some(&1.2));
+        assert!(recorder.take_actions().is_empty());
 
-        let new_default_monitor_idx = if effective.is_default { None } else { effective.monitor_idx };
+        // The second one of the model this session keeps its settings.
+        app.handle_event(changed(lg("DP-3", 5760)));
+        assert!(!app.pending_transforms.contains_key("DP-3"));
 
-        let Some(mut effective) = self.get_effective_workspace(ws_idx) else {
-            return;
+        // Enabled ones get them right away, except what the config sets.
+        let (mut app, recorder) = harness(Hyprland, Some(existing));
+        app.config.defaults = defaults;
+        app.config.on_new_monitor = NewMonitorAction::EnableRight;
+        app.set_monitors(vec![monitor("DP-1", 0, 0)]);
+        app.handle_event(changed(lg("HDMI-A-1", 3840)));
+        let actions = recorder.take_actions();
+        assert_eq!(actions, ["transform HDMI-A-1 to Rotate 90"]);
+        assert!(app.needs_save());
+    }
+
+    #[test]
+    fn test_harness_remembered_defaults_go_by_model() {
+        let (mut app, recorder) = harness(Hyprland, None);
+        let mut rotated = monitor("DP-1", 0, 0);
+        rotated.transform = WlTransform::Rotate270;
+        rotated.scale = 1.25;
+        app.set_monitors(vec![rotated]);
+
+        app.remember_defaults();
+        let expected = MonitorDefaults {
+            transform: Some("270".into()),
+            scale: Some(1.25),
         };
-        effective.is_default = new_default_monitor_idx.is_some();
+        assert_eq!(app.config.defaults.get("Test Monitor"), Some(&expected));
+        let record = recorder.record();
+        assert!(record.declined.iter().any(|d| d == "write xwlm settings"));
+    }
 
-        if let Some(target_monitor) = new_default_monitor_idx {
-            for (_, w) in self.pending_workspaces.iter_mut() {
-                if w.is_default && w.monitor_idx == Some(target_monitor) {
-                    w.is_default = false;
-                }
-            }
-            for w in self.workspace_assignments.iter_mut() {
-                if w.is_default && w.monitor_idx == Some(target_monitor) {
-                    w.is_default = false;
-                }
-            }
-        }
+    #[test]
+    fn test_harness_aspect_filter_only_hides_modes_in_the_list() {
+        let (mut app, recorder) = harness(Hyprland, None);
+        let mut tv = monitor("HDMI-A-1", 0, 0);
+        tv.modes[0].preferred = true;
+        tv.modes.push(mode(1024, 768, 60));
+        tv.modes.push(mode(1280, 720, 60));
+        app.set_monitors(vec![tv]);
+        app.panel = Panel::Mode;
+        app.mode_selection.select(Some(2));
 
-        self.pending_workspaces.insert(ws_idx, effective);
+        app.toggle_aspect_filter();
+        assert_eq!(
+            app.mode_entries(),
+            [
+                ModeEntry::Advertised(0),
+                ModeEntry::Advertised(2),
+                ModeEntry::AddCustom,
+            ]
+        );
+        assert_eq!(app.hidden_modes(), 1);
+        assert_eq!(app.mode_selection.selected(), Some(1));
+
+        // Typing a hidden mode still switches to it.
+        app.set_mode_from_input("1024x768@60").unwrap();
+        assert!(!app.hide_other_aspects);
+        assert_eq!(
+            recorder.take_actions(),
+            ["switch HDMI-A-1 to 1024x768@60"]
+        );
     }
 
-    pub fn apply_action(&mut self) -> Result<(), SendError<WlMonitorAction>> {
-        match self.panel {
-            Panel::Mode => self.apply_mode()?,
-            Panel::Scale => self.apply_scale()?,
-            Panel::Transform => self.apply_transform()?,
-            Panel::Monitor => {
-                if self.pending_positions.is_empty() {
-                    return Ok(());
-                }
-                for (&idx, &(x, y)) in &self.pending_positions {
-                    if let Some(monitor) = self.monitors.get_mut(idx) {
-                        monitor.position.x = x;
-                        monitor.position.y = y;
-                    }
-                }
-                self.apply_positions()?;
-                self.pending_positions.clear();
-            }
-            Panel::Workspace => {
-                if self.pending_workspaces.is_empty() {
-                    return Ok(());
-                }
-                for (&idx, ws) in &self.pending_workspaces {
-                    if let Some(existing) = self.workspace_assignments.get_mut(idx) {
-                        existing.monitor_idx = ws.monitor_idx;
-                        existing.is_default = ws.is_default;
-                        existing.is_persistent = ws.is_persistent;
-                    }
-                }
-                self.pending_workspaces.clear();
-            }
-        }
-        self.needs_save = true;
-        self.save_config();
+    #[test]
+    fn test_harness_reconcile_live_and_config_per_monitor() {
+        let existing = "monitor = DP-1, 1920x1080@60, 0x500, 1\n\
+                        monitor = DP-2, 1920x1080@60, 1920x0, 1.5\n";
+        let (mut app, recorder) = harness(Hyprland, Some(existing));
+        app.set_monitors(vec![monitor("DP-1", 0, 0), monitor("DP-2", 1920, 0)]);
 
-        Ok(())
+        app.open_reconcile(false);
+        let view = app.reconcile.as_ref().unwrap();
+        let names: Vec<&str> =
+            view.rows.iter().map(|r| r.name.as_str()).collect();
+        assert_eq!(names, ["DP-1", "DP-2"]);
+
+        app.apply_config(false).unwrap();
+        assert_eq!(recorder.take_actions(), ["move DP-1 to 0,500"]);
+        assert_eq!(app.reconcile.as_ref().unwrap().rows.len(), 1);
+        play(&mut app, vec![changed(monitor("DP-1", 0, 500))]);
+
+        app.adopt_live(false);
+        assert!(app.reconcile.is_none());
+        app.flush_saves(Instant::now() + Duration::from_secs(61));
+        let saved = recorder.file(CONFIG_PATH).unwrap();
+        assert!(saved.contains("monitor = DP-1, 1920x1080@60, 0x500, 1\n"));
+        assert!(saved.contains("monitor = DP-2, 1920x1080@60, 1920x0, 1\n"));
+
+        // Nothing left to reconcile is only said when asked for.
+        app.status_message = None;
+        app.open_reconcile(true);
+        assert!(app.reconcile.is_none());
+        assert_eq!(app.status_message, None);
+        app.open_reconcile(false);
+        assert!(app.reconcile.is_none());
+        assert!(app.status_message.is_some());
     }
 
-    fn apply_mode(&self) -> Result<(), SendError<WlMonitorAction>> {
-        let Some(monitor) = self.selected_monitor() else {
-            return Ok(());
+    #[test]
+    fn test_reconcile_refuses_to_switch_everything_off() {
+        let (mut app, recorder) = harness(
+            Hyprland,
+            Some("monitor = DP-1, disable\n"),
+        );
+        app.set_monitors(vec![monitor("DP-1", 0, 0)]);
+        app.open_reconcile(true);
+        app.apply_config(true).unwrap();
+        assert!(app.error_message.is_some());
+        assert!(app.reconcile.is_some());
+        assert_eq!(recorder.take_actions(), Vec::<String>::new());
+    }
+
+    #[test]
+    fn test_harness_clearing_workspaces_asks_first() {
+        let existing = "monitor = DP-1, 1920x1080@60, 0x0, 1\n\
+                        workspace = 1, monitor:DP-1\n\
+                        workspace = 2, monitor:DP-1\n";
+        let (mut app, recorder) = harness(Hyprland, Some(existing));
+        app.set_monitors(vec![monitor("DP-1", 0, 0)]);
+        app.workspace_assignments[1].is_pinned = true;
+
+        app.ask_clear_workspaces();
+        assert_eq!(app.confirm, Some(Confirm::ClearWorkspaces));
+        app.dismiss_confirm();
+        assert_eq!(app.confirm, None);
+        assert!(app.workspace_assignments[0].monitor.is_some());
+
+        app.ask_clear_workspaces();
+        app.accept_confirm().unwrap();
+        assert_eq!(app.confirm, None);
+        assert_eq!(app.workspace_assignments[0].monitor, None);
+        assert_eq!(
+            app.workspace_assignments[1].monitor.as_deref(),
+            Some("DP-1")
+        );
+        let saved = recorder.file(CONFIG_PATH).unwrap();
+        assert!(!saved.contains("workspace = 1, monitor:DP-1"), "{saved}");
+        assert!(saved.contains("workspace = 2, monitor:DP-1"), "{saved}");
+
+        // With only pinned rows left there is nothing to ask about.
+        app.ask_clear_workspaces();
+        assert_eq!(app.confirm, None);
+    }
+
+    #[test]
+    fn test_harness_restoring_a_layout_that_disables_asks_first() {
+        let (mut app, recorder) = harness(Hyprland, None);
+        let monitors = vec![monitor("DP-1", 0, 0), disabled("DP-2")];
+        let snapshot = history::Snapshot {
+            timestamp: 0,
+            layout: Layout::capture(&monitors, &[]),
         };
-        let Some(mode_idx) = self.mode_state.selected() else {
-            return Ok(());
+        app.set_monitors(vec![monitor("DP-1", 0, 0), monitor("DP-2", 1920, 0)]);
+        let browse = |app: &mut App| {
+            app.history = Some(HistoryBrowser {
+                entries: vec![snapshot.clone()],
+                state: ListState::default().with_selected(Some(0)),
+            });
         };
-        let Some(mode) = monitor.modes.get(mode_idx) else {
-            return Ok(());
+
+        browse(&mut app);
+        app.restore_selected_snapshot().unwrap();
+        assert!(app.history.is_none());
+        let Some(Confirm::RestoreSnapshot { disables, .. }) = &app.confirm
+        else {
+            panic!("expected a confirmation");
         };
+        assert_eq!(disables, &["DP-2"]);
+        app.dismiss_confirm();
+        assert_eq!(recorder.take_actions(), Vec::<String>::new());
 
-        self.wlx_action_handler.send(WlMonitorAction::SwitchMode {
-            name: monitor.name.clone(),
-            width: mode.resolution.width,
-            height: mode.resolution.height,
-            refresh_rate: mode.refresh_rate,
-        })?;
+        browse(&mut app);
+        app.restore_selected_snapshot().unwrap();
+        app.accept_confirm().unwrap();
+        assert_eq!(app.confirm, None);
+        assert!(recorder.take_actions().contains(&"toggle DP-2".to_string()));
+    }
 
-        Ok(())
+    #[test]
+    fn test_only_one_confirmation_is_pending() {
+        let (mut app, rx) = app();
+        app.set_monitors(vec![monitor("DP-1", 0, 0)]);
+        app.toggle_monitor().unwrap();
+        app.apply_action().unwrap();
+        assert_eq!(app.confirm, Some(Confirm::LastMonitor));
+
+        // A newer question turns down the one before it.
+        app.ask(Confirm::ClearWorkspaces);
+        assert_eq!(app.confirm, Some(Confirm::ClearWorkspaces));
+        assert!(!app.has_pending_toggles());
+
+        app.dismiss_confirm();
+        assert_eq!(app.confirm, None);
+        app.accept_confirm().unwrap();
+        app.dismiss_confirm();
+        assert_eq!(app.confirm, None);
+        assert!(rx.try_recv().is_err());
     }
 
-    fn apply_scale(&self) -> Result<(), SendError<WlMonitorAction>> {
-        let Some(monitor) = self.selected_monitor() else {
-            return Ok(());
-        };
-        self.wlx_action_handler.send(WlMonitorAction::SetScale {
-            name: monitor.name.clone(),
-            scale: self.pending_scale,
-        })?;
-        Ok(())
+    #[test]
+    fn test_harness_clone_settings_substitutes_a_missing_mode() {
+        let (mut app, recorder) = harness(Hyprland, None);
+        let mut source = monitor("DP-1", 0, 0);
+        source.modes[0].is_current = false;
+        let mut fast = mode(2560, 1440, 144);
+        fast.is_current = true;
+        source.modes.push(fast);
+        source.scale = 1.25;
+        source.transform = WlTransform::Flipped;
+        app.set_monitors(vec![
+            source,
+            monitor("DP-2", 3000, 0),
+            monitor("DP-3", 6000, 0),
+        ]);
+        assert!(app.select_monitor_named("DP-1"));
+        app.open_clone_picker();
+        app.toggle_clone_adjacent();
+        app.confirm_clone();
+        assert!(app.clone_picker.is_none());
+        assert!(app.pending_modes.is_empty());
+        let status = app.status_message.clone().unwrap();
+        assert!(
+            status.contains("DP-2 cannot do 2560x1440@144Hz, using \
+                             1920x1080@60Hz"),
+            "{status}"
+        );
+
+        // Nothing goes out before Enter.
+        assert_eq!(recorder.take_actions(), Vec::<String>::new());
+        app.panel = Panel::Monitor;
+        app.apply_action().unwrap();
+        assert_eq!(
+            recorder.take_actions(),
+            [
+                "scale DP-2 to 1.25",
+                "transform DP-2 to Flipped",
+                "move DP-2 to 2048,0",
+            ]
+        );
+
+        // A mode the target has is copied as is.
+        let mut wide = monitor("DP-3", 6000, 0);
+        wide.modes.push(mode(2560, 1440, 144));
+        play(&mut app, vec![changed(wide)]);
+        app.clone_settings("DP-1", "DP-3", false);
+        assert_eq!(app.pending_modes["DP-3"].to_string(), "2560x1440@144Hz");
+        assert!(!app.pending_positions.contains_key("DP-3"));
     }
 
-    fn apply_transform(&self) -> Result<(), SendError<WlMonitorAction>> {
-        let Some(monitor) = self.selected_monitor() else {
-            return Ok(());
-        };
-        let Some(idx) = self.transform_state.selected() else {
-            return Ok(());
-        };
-        let Some(&transform) = TRANSFORMS.get(idx) else {
-            return Ok(());
-        };
+    #[test]
+    fn test_harness_captured_output_needs_a_second_confirmation() {
+        let (mut app, recorder) = harness(Hyprland, None);
+        let mut dp1 = monitor("DP-1", 0, 0);
+        dp1.modes.push(mode(1280, 720, 60));
+        app.set_monitors(vec![dp1, monitor("DP-2", 1920, 0)]);
+        recorder.capture("wf-recorder", Some("DP-2"));
 
-        self.wlx_action_handler
-            .send(WlMonitorAction::SetTransform {
-                name: monitor.name.clone(),
-                transform,
-            })?;
+        assert!(app.select_monitor_named("DP-2"));
+        app.toggle_monitor().unwrap();
+        app.apply_action().unwrap();
+        let error = app.error_message.take().unwrap();
+        assert!(error.contains("DP-2 is being captured by wf-recorder"));
+        assert_eq!(recorder.take_actions(), Vec::<String>::new());
+        app.apply_action().unwrap();
+        assert_eq!(recorder.take_actions(), ["toggle DP-2"]);
 
-        Ok(())
+        // Other outputs are not held up.
+        assert!(app.select_monitor_named("DP-1"));
+        app.panel = Panel::Mode;
+        app.mode_selection.select(Some(1));
+        app.apply_action().unwrap();
+        assert_eq!(recorder.take_actions(), ["switch DP-1 to 1280x720@60"]);
+
+        // A portal screencast may be showing any output.
+        recorder.capture("Firefox", None);
+        app.apply_action().unwrap();
+        assert!(app.error_message.take().unwrap().contains("Firefox"));
+        assert_eq!(recorder.take_actions(), Vec::<String>::new());
     }
 
-    fn apply_positions(&self) -> Result<(), SendError<WlMonitorAction>> {
-        for (&idx, &(x, y)) in &self.pending_positions {
-            if let Some(monitor) = self.monitors.get(idx) {
-                self.wlx_action_handler.send(WlMonitorAction::SetPosition {
-                    name: monitor.name.clone(),
-                    x,
-                    y,
-                })?
-            }
-        }
+    #[test]
+    fn test_harness_recover_applies_the_boot_safe_layout() {
+        let (mut app, recorder) = harness(Hyprland, None);
+        let mut scaled = monitor("DP-2", 1920, 0);
+        scaled.scale = 2.0;
+        app.set_monitors(vec![monitor("DP-1", 0, 0), scaled, disabled("DP-3")]);
+        app.mark_dirty();
+        app.save_config();
+        let write = "write the recovery config".to_string();
+        assert!(recorder.record().declined.contains(&write));
 
-        Ok(())
+        app.recover().unwrap();
+        assert!(app.error_message.take().unwrap().contains("recovery.conf"));
+
+        let path = recovery::recovery_path(Hyprland, Path::new(CONFIG_PATH));
+        let content = recovery::render(Hyprland, &app.monitors, "", &path);
+        recorder.put(path.to_str().unwrap(), &content.unwrap());
+        app.recover().unwrap();
+        assert_eq!(
+            recorder.take_actions(),
+            [
+                "scale DP-2 to 1.00",
+                "toggle DP-3 at 1920x1080@60 to 3840,0",
+                "move DP-3 to 3840,0",
+            ]
+        );
     }
 
-    fn resolve_initial_workspaces(&mut self) {
-        let Some(workspace_rules) = self.initial_workspaces.take() else {
-            return;
+    #[test]
+    fn test_harness_settings_are_saved_and_used_right_away() {
+        let (mut app, recorder) = harness(Hyprland, None);
+        app.map_zoom = 4.0;
+        app.open_settings();
+        let select = |app: &mut App, key: &str| {
+            let editor = app.settings.as_mut().unwrap();
+            let at = editor.fields.iter().position(|f| f.key == key);
+            editor.state.select(at);
         };
-        for rule in &workspace_rules {
-            let monitor_idx = self.monitors.iter().position(|m| m.name == rule.monitor);
-            if let Some(ws) = self
-                .workspace_assignments
-                .iter_mut()
-                .find(|ws| ws.id == rule.id)
-            {
-                ws.monitor_idx = monitor_idx;
-                ws.is_default = rule.is_default;
-                ws.is_persistent = rule.is_persistent;
-            }
+
+        select(&mut app, "failure_alert");
+        app.settings_cycle(true);
+        assert_eq!(app.config.failure_alert, xwlm_config::FailureAlert::Bell);
+        assert_eq!(recorder.record().declined, ["write xwlm settings"]);
+
+        select(&mut app, "zoom_max");
+        app.settings_enter();
+        app.settings_input('x');
+        app.settings_enter();
+        assert!(app.settings.as_ref().unwrap().error.is_some());
+        assert_eq!(app.map_zoom, 4.0);
+
+        app.settings_enter();
+        for _ in 0..3 {
+            app.settings_backspace();
         }
+        app.settings_input('2');
+        app.settings_enter();
+        assert_eq!(app.config.zoom_max, 2.0);
+        assert_eq!(app.map_zoom, 2.0);
+        assert!(app.settings.as_ref().unwrap().error.is_none());
+
+        app.close_settings();
+        assert!(app.settings.is_none());
     }
 
-    fn validate_workspace_assignments(&mut self) {
-        let mon_count = self.monitors.len();
-        for ws in &mut self.workspace_assignments {
-            if let Some(idx) = ws.monitor_idx
-                && idx >= mon_count
-            {
-                ws.monitor_idx = None;
-            }
-        }
+    #[test]
+    fn test_harness_workspace_fallbacks_are_edited_and_written() {
+        let existing = "output DP-1 mode 1920x1080@60Hz pos 0 0\n\
+                        workspace 1 output DP-1 HDMI-A-1\n";
+        let (mut app, recorder) = harness(Sway, Some(existing));
+        app.set_monitors(vec![monitor("DP-1", 0, 0), monitor("DP-2", 1920, 0)]);
+        assert_eq!(app.workspace_assignments[0].fallbacks, ["HDMI-A-1"]);
+        app.panel = Panel::Workspace;
+
+        app.open_fallback_editor();
+        let listed = |app: &App| -> Vec<String> {
+            let editor = app.fallback_editor.as_ref().unwrap();
+            editor.entries().cloned().collect()
+        };
+        assert_eq!(listed(&app), ["DP-1", "HDMI-A-1", "DP-2"]);
+        app.fallback_step(false);
+        app.toggle_fallback();
+        app.move_fallback(true);
+        app.move_fallback(true);
+        assert_eq!(app.fallback_editor.as_ref().unwrap().chosen.len(), 3);
+        assert_eq!(listed(&app), ["DP-2", "DP-1", "HDMI-A-1"]);
+        app.confirm_fallbacks();
+        let staged = app.get_effective_workspace(0).unwrap();
+        assert_eq!(staged.monitor.as_deref(), Some("DP-2"));
+        assert_eq!(staged.fallbacks, ["DP-1", "HDMI-A-1"]);
+
+        // Assigning with the arrows keeps the rest of the list.
+        app.cycle_workspace_monitor(false);
+        let staged = app.get_effective_workspace(0).unwrap();
+        assert_eq!(staged.monitor.as_deref(), Some("DP-1"));
+        assert_eq!(staged.fallbacks, ["HDMI-A-1"]);
+        app.apply_action().unwrap();
+        let saved = recorder.file(CONFIG_PATH).unwrap();
+        assert!(saved.contains("workspace 1 output DP-1 HDMI-A-1\n"));
+
+        // Hyprland keeps only the last rule for a workspace.
+        let (mut app, _) = harness(Hyprland, None);
+        app.set_monitors(vec![monitor("DP-1", 0, 0)]);
+        app.panel = Panel::Workspace;
+        app.open_fallback_editor();
+        assert!(app.fallback_editor.is_none());
+        assert_eq!(
+            app.status_message.as_deref(),
+            Some("Hyprland has no fallback monitors for workspaces")
+        );
     }
 }
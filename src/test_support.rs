@@ -0,0 +1,369 @@
+use std::{
+    cell::RefCell,
+    collections::HashMap,
+    io,
+    path::{Path, PathBuf},
+    rc::Rc,
+    sync::mpsc::{Receiver, SendError, sync_channel},
+    time::{Duration, SystemTime},
+};
+
+use ratatui::{Frame, Terminal, backend::TestBackend};
+use wayland_client::{Proxy, backend::ObjectId};
+use wayland_protocols_wlr::output_management::v1::client::{
+    zwlr_output_head_v1::ZwlrOutputHeadV1,
+    zwlr_output_mode_v1::ZwlrOutputModeV1,
+};
+use wlx_monitors::{
+    ActionKind, WlMonitor, WlMonitorAction, WlMonitorEvent, WlMonitorMode,
+    WlPosition, WlResolution, WlTransform,
+};
+
+use crate::{
+    compositor::{Compositor, format::backup_path, ipc::Bar},
+    dump::inert_backend,
+    effects::{Effects, Live, describe},
+    screencast::Capture,
+    state::App,
+    xwlm_config::Config,
+};
+
+/// Where [`app`] and [`harness`] look for the monitor config.
+pub const CONFIG_PATH: &str = "/nonexistent/xwlm-test.conf";
+
+pub fn mode(width: i32, height: i32, refresh_rate: i32) -> WlMonitorMode {
+    WlMonitorMode {
+        mode_id: ObjectId::null(),
+        head_id: ObjectId::null(),
+        refresh_rate,
+        resolution: WlResolution { width, height },
+        preferred: false,
+        is_current: false,
+        proxy: ZwlrOutputModeV1::inert(inert_backend()),
+    }
+}
+
+/// An enabled 1920x1080@60 monitor at the given position.
+pub fn monitor(name: &str, x: i32, y: i32) -> WlMonitor {
+    let mut current = mode(1920, 1080, 60);
+    current.is_current = true;
+    current.preferred = true;
+
+    WlMonitor {
+        head_id: ObjectId::null(),
+        name: name.to_string(),
+        description: format!("Test Monitor {name}"),
+        make: "Test".to_string(),
+        model: "Monitor".to_string(),
+        serial_number: String::new(),
+        modes: vec![current],
+        resolution: WlResolution {
+            width: 1920,
+            height: 1080,
+        },
+        position: WlPosition { x, y },
+        scale: 1.0,
+        enabled: true,
+        current_mode: None,
+        transform: WlTransform::Normal,
+        head: ZwlrOutputHeadV1::inert(inert_backend()),
+        changed: false,
+        last_mode: None,
+    }
+}
+
+/// An app with no monitors whose config paths point nowhere, with the
+/// resulting missing-config warning dismissed. The receiver collects the
+/// actions it sends.
+pub fn app() -> (App, Receiver<WlMonitorAction>) {
+    let (tx, rx) = sync_channel(64);
+    let mut config = Config::new(PathBuf::from(CONFIG_PATH));
+    config.reload_cooldown_ms = 0;
+    let mut app = App::new(Box::new(Live::new(tx)), config);
+    app.dismiss_config_check();
+    (app, rx)
+}
+
+/// What `draw` paints on a `width` by `height` screen, row by row.
+pub fn screen(
+    width: u16,
+    height: u16,
+    draw: impl FnOnce(&mut Frame),
+) -> Vec<String> {
+    let mut terminal = Terminal::new(TestBackend::new(width, height)).unwrap();
+    terminal.draw(draw).unwrap();
+    let buffer = terminal.backend().buffer();
+    (0..height)
+        .map(|y| (0..width).map(|x| buffer[(x, y)].symbol()).collect())
+        .collect()
+}
+
+/// A disabled monitor, as one shows up when plugged in while off.
+pub fn disabled(name: &str) -> WlMonitor {
+    let mut monitor = monitor(name, 0, 0);
+    monitor.enabled = false;
+    monitor
+}
+
+pub fn changed(monitor: WlMonitor) -> WlMonitorEvent {
+    WlMonitorEvent::Changed(Box::new(monitor))
+}
+
+pub fn removed(name: &str) -> WlMonitorEvent {
+    WlMonitorEvent::Removed {
+        id: ObjectId::null(),
+        name: name.to_string(),
+    }
+}
+
+pub fn failed(action: ActionKind) -> WlMonitorEvent {
+    WlMonitorEvent::ActionFailed {
+        action,
+        reason: "rejected".to_string(),
+    }
+}
+
+/// What a [`Recorder`] saw.
+#[derive(Debug, Default)]
+pub struct Recorded {
+    /// Sent actions as [`describe`] lines, oldest first.
+    pub actions: Vec<String>,
+    /// Every write, oldest first.
+    pub writes: Vec<(PathBuf, String)>,
+    /// The in-memory files reads are served from; writes land here too.
+    pub files: HashMap<PathBuf, String>,
+    /// Side effects that were asked for and declined.
+    pub declined: Vec<String>,
+    /// Writes and reloads in the order they happened, e.g. "reload
+    /// Hyprland".
+    pub saves: Vec<String>,
+    /// What `captures` reports.
+    pub captures: Vec<Capture>,
+    /// What `bars` reports.
+    pub bars: Vec<Bar>,
+    /// What `autoreloads` reports.
+    pub autoreload: bool,
+    /// Why reloads fail, when they should.
+    pub reload_error: Option<String>,
+    /// Why writes fail, when they should.
+    pub write_error: Option<String>,
+    /// How many files were read.
+    pub reads: usize,
+    /// Bumped by every write or `put`, as a stand-in for mtimes.
+    pub changes: u64,
+}
+
+/// Effects that keep files in memory and record every action and reload.
+/// Other side effects are declined, so tests never touch the real layout,
+/// history or settings. Clones share one record.
+#[derive(Debug, Clone, Default)]
+pub struct Recorder(Rc<RefCell<Recorded>>);
+
+impl Recorder {
+    pub fn record(&self) -> std::cell::Ref<'_, Recorded> {
+        self.0.borrow()
+    }
+
+    /// The actions sent since the last call.
+    pub fn take_actions(&self) -> Vec<String> {
+        std::mem::take(&mut self.0.borrow_mut().actions)
+    }
+
+    pub fn put(&self, path: &str, content: &str) {
+        let mut record = self.0.borrow_mut();
+        record.files.insert(PathBuf::from(path), content.to_string());
+        record.changes += 1;
+    }
+
+    pub fn file(&self, path: &str) -> Option<String> {
+        self.record().files.get(Path::new(path)).cloned()
+    }
+
+    pub fn fail_reloads(&self, reason: &str) {
+        self.0.borrow_mut().reload_error = Some(reason.to_string());
+    }
+
+    pub fn fail_writes(&self, reason: &str) {
+        self.0.borrow_mut().write_error = Some(reason.to_string());
+    }
+
+    pub fn autoreload(&self) {
+        self.0.borrow_mut().autoreload = true;
+    }
+
+    pub fn bar(&self, app: &str, output: &str) {
+        self.0.borrow_mut().bars.push(Bar {
+            app: app.to_string(),
+            output: output.to_string(),
+        });
+    }
+
+    pub fn capture(&self, app: &str, output: Option<&str>) {
+        self.0.borrow_mut().captures.push(Capture {
+            app: app.to_string(),
+            output: output.map(str::to_string),
+        });
+    }
+}
+
+impl Effects for Recorder {
+    fn send(
+        &mut self,
+        action: WlMonitorAction,
+    ) -> Result<(), SendError<WlMonitorAction>> {
+        self.0.borrow_mut().actions.push(describe(&action));
+        Ok(())
+    }
+
+    fn write(&mut self, path: &Path, content: &str) -> io::Result<()> {
+        let mut record = self.0.borrow_mut();
+        if let Some(reason) = &record.write_error {
+            return Err(io::Error::other(reason.clone()));
+        }
+        record.writes.push((path.to_path_buf(), content.to_string()));
+        record.files.insert(path.to_path_buf(), content.to_string());
+        record.saves.push(format!("write {}", path.display()));
+        record.changes += 1;
+        Ok(())
+    }
+
+    fn read(&self, path: &Path) -> io::Result<String> {
+        let mut record = self.0.borrow_mut();
+        record.reads += 1;
+        record
+            .files
+            .get(path)
+            .cloned()
+            .ok_or_else(|| io::Error::from(io::ErrorKind::NotFound))
+    }
+
+    fn back_up(&mut self, path: &Path, count: usize) -> io::Result<bool> {
+        let mut record = self.0.borrow_mut();
+        let Some(content) = record.files.get(path).cloned() else {
+            return Ok(false);
+        };
+        if count == 0 {
+            return Ok(false);
+        }
+        for n in (1..count).rev() {
+            if let Some(older) = record.files.remove(&backup_path(path, n)) {
+                record.files.insert(backup_path(path, n + 1), older);
+            }
+        }
+        record.files.insert(backup_path(path, 1), content);
+        Ok(true)
+    }
+
+    fn modified(&self, path: &Path) -> Option<SystemTime> {
+        let record = self.record();
+        let stamp = Duration::from_secs(record.changes);
+        record
+            .files
+            .contains_key(path)
+            .then(|| SystemTime::UNIX_EPOCH + stamp)
+    }
+
+    fn reload(
+        &mut self,
+        compositor: Compositor,
+        _config: &Path,
+    ) -> io::Result<bool> {
+        let line = format!("reload {}", compositor.label());
+        let mut record = self.0.borrow_mut();
+        record.saves.push(line);
+        match &record.reload_error {
+            Some(reason) => Err(io::Error::other(reason.clone())),
+            None => Ok(true),
+        }
+    }
+
+    fn run(&mut self, command: &[String]) -> io::Result<()> {
+        let mut record = self.0.borrow_mut();
+        record.saves.push(command.join(" "));
+        match &record.reload_error {
+            Some(reason) => Err(io::Error::other(reason.clone())),
+            None => Ok(()),
+        }
+    }
+
+    fn allow(&mut self, what: &str) -> bool {
+        self.0.borrow_mut().declined.push(what.to_string());
+        false
+    }
+
+    fn transcript(&self) -> &[String] {
+        &[]
+    }
+
+    fn captures(&self) -> Vec<Capture> {
+        self.record().captures.clone()
+    }
+
+    fn bars(&self, _compositor: Compositor) -> Vec<Bar> {
+        self.record().bars.clone()
+    }
+
+    fn autoreloads(&self, _compositor: Compositor) -> bool {
+        self.record().autoreload
+    }
+
+    fn is_dry_run(&self) -> bool {
+        false
+    }
+}
+
+/// An app for `comp` driven by a [`Recorder`], with `existing` as the
+/// monitor config at [`CONFIG_PATH`] (none when `None`) and any startup
+/// warnings dismissed.
+pub fn harness(comp: Compositor, existing: Option<&str>) -> (App, Recorder) {
+    let recorder = Recorder::default();
+    if let Some(content) = existing {
+        recorder.put(CONFIG_PATH, content);
+    }
+    (restart(comp, &recorder), recorder)
+}
+
+/// A fresh app on the files `recorder` already holds, as if xwlm had been
+/// started again.
+pub fn restart(comp: Compositor, recorder: &Recorder) -> App {
+    let mut config = Config::new(PathBuf::from(CONFIG_PATH));
+    config.reload_cooldown_ms = 0;
+    let effects = Box::new(recorder.clone());
+    let mut app = App::with_compositor(effects, config, comp);
+    app.dismiss_config_check();
+    app.dismiss_stray_rules();
+    app
+}
+
+/// Feeds `events` in as one batch the way the TUI loop does: each is
+/// handled, then pending changes are saved once.
+pub fn play(app: &mut App, events: impl IntoIterator<Item = WlMonitorEvent>) {
+    for event in events {
+        app.handle_event(event);
+    }
+    app.save_config();
+}
+
+/// A fresh directory under the system temp dir, unique to this process and
+/// `label`, removed with everything in it when dropped.
+pub struct TempDir(PathBuf);
+
+impl TempDir {
+    pub fn new(label: &str) -> Self {
+        let dir = std::env::temp_dir()
+            .join(format!("xwlm-{label}-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        Self(dir)
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.0);
+    }
+}
@@ -0,0 +1,173 @@
+use std::collections::VecDeque;
+
+use crate::{
+    constants::MAX_TIMELINE,
+    history,
+    i18n::{t, tn},
+};
+
+const SECS_PER_HOUR: i64 = 3600;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Transition {
+    Connected,
+    Disconnected,
+    Enabled,
+    Disabled,
+    Changed,
+}
+
+impl Transition {
+    pub fn label(self) -> &'static str {
+        match self {
+            Transition::Connected => t("connected"),
+            Transition::Disconnected => t("disconnected"),
+            Transition::Enabled => t("enabled"),
+            Transition::Disabled => t("disabled"),
+            Transition::Changed => t("changed"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Entry {
+    pub timestamp: i64,
+    pub name: String,
+    pub transition: Transition,
+}
+
+impl Entry {
+    /// e.g. "14:32:07  DP-2  disconnected"
+    pub fn line(&self) -> String {
+        format!(
+            "{}  {}  {}",
+            history::clock(self.timestamp),
+            self.name,
+            self.transition.label()
+        )
+    }
+}
+
+/// Monitor events seen this session, oldest first. Only the last
+/// `MAX_TIMELINE` are kept, so a flapping link cannot grow it forever.
+#[derive(Debug, Default)]
+pub struct Timeline {
+    entries: VecDeque<Entry>,
+    total: usize,
+}
+
+impl Timeline {
+    pub fn record(
+        &mut self,
+        timestamp: i64,
+        name: &str,
+        transition: Transition,
+    ) {
+        if self.entries.len() == MAX_TIMELINE {
+            self.entries.pop_front();
+        }
+        self.entries.push_back(Entry {
+            timestamp,
+            name: name.to_string(),
+            transition,
+        });
+        self.total += 1;
+    }
+
+    pub fn entries(&self) -> impl DoubleEndedIterator<Item = &Entry> {
+        self.entries.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Entries recorded since the session started, including dropped ones.
+    pub fn total(&self) -> usize {
+        self.total
+    }
+
+    /// Monitors with entries, in order of their first one.
+    pub fn monitors(&self) -> Vec<&str> {
+        let mut names: Vec<&str> = Vec::new();
+        for entry in &self.entries {
+            if !names.contains(&entry.name.as_str()) {
+                names.push(&entry.name);
+            }
+        }
+        names
+    }
+
+    /// How often `name` came back after a disconnect since `since`. The
+    /// first connect, such as at hotplug, is no reconnect.
+    pub fn reconnects(&self, name: &str, since: i64) -> usize {
+        let mut gone = false;
+        let mut count = 0;
+        for entry in self.entries.iter().filter(|e| e.name == name) {
+            match entry.transition {
+                Transition::Disconnected => gone = true,
+                Transition::Connected if gone => {
+                    gone = false;
+                    count += usize::from(entry.timestamp >= since);
+                }
+                _ => {}
+            }
+        }
+        count
+    }
+
+    /// e.g. "7 reconnects in the last hour", or `None` when it was stable.
+    pub fn summary(&self, name: &str, now: i64) -> Option<String> {
+        let n = self.reconnects(name, now - SECS_PER_HOUR);
+        (n > 0).then(|| {
+            tn(
+                "{} reconnect in the last hour",
+                "{} reconnects in the last hour",
+                n,
+                &[],
+            )
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_counts_reconnects_within_the_window() {
+        let mut timeline = Timeline::default();
+        timeline.record(0, "DP-2", Transition::Connected);
+        for i in 0..4 {
+            let at = i * 1000;
+            timeline.record(at, "DP-2", Transition::Disconnected);
+            timeline.record(at + 5, "DP-2", Transition::Connected);
+        }
+        timeline.record(3500, "DP-1", Transition::Disabled);
+
+        assert_eq!(timeline.reconnects("DP-2", 0), 4);
+        assert_eq!(
+            timeline.summary("DP-2", 3600 + 1500).as_deref(),
+            Some("2 reconnects in the last hour")
+        );
+        assert_eq!(timeline.summary("DP-1", 3600), None);
+
+        timeline.record(4000, "HDMI-A-1", Transition::Connected);
+        assert_eq!(timeline.summary("HDMI-A-1", 4000), None);
+    }
+
+    #[test]
+    fn test_keeps_only_the_latest_entries() {
+        let mut timeline = Timeline::default();
+        for i in 0..MAX_TIMELINE + 10 {
+            timeline.record(i as i64, "DP-2", Transition::Changed);
+        }
+        assert_eq!(timeline.len(), MAX_TIMELINE);
+        assert_eq!(timeline.total(), MAX_TIMELINE + 10);
+        assert_eq!(timeline.entries().next().unwrap().timestamp, 10);
+    }
+}
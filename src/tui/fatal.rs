@@ -0,0 +1,108 @@
+use std::{
+    io::{self, IsTerminal},
+    panic,
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+use crossterm::{
+    event::{self, Event, KeyEventKind},
+    execute,
+    terminal::{
+        EnterAlternateScreen, LeaveAlternateScreen, disable_raw_mode,
+        enable_raw_mode,
+    },
+};
+use ratatui::{
+    Terminal,
+    layout::{Constraint, Flex, Layout},
+    prelude::CrosstermBackend,
+    style::{Color, Modifier, Style},
+    text::Line,
+    widgets::{Block, BorderType, Borders, Clear, Paragraph, Wrap},
+};
+
+use crate::i18n::t;
+
+/// Whether an error or a panic waits for a key before xwlm exits. Only
+/// set for the TUI, until it is up; a terminal opened for xwlm by a
+/// compositor keybinding closes with it, message and all.
+static HOLDING: AtomicBool = AtomicBool::new(false);
+
+pub fn set_holding(hold: bool) {
+    HOLDING.store(hold, Ordering::Relaxed);
+}
+
+/// Shows `message` on a screen of its own until a key is pressed, if
+/// errors are held and there is a terminal to show it on. False when the
+/// caller should print it instead.
+pub fn hold(message: &str) -> bool {
+    if !HOLDING.swap(false, Ordering::Relaxed)
+        || !io::stdin().is_terminal()
+        || !io::stdout().is_terminal()
+    {
+        return false;
+    }
+    let held = show(message);
+    let _ = restore();
+    held.is_ok()
+}
+
+/// Leaves the terminal as it was before xwlm, then shows the panic the
+/// way [`hold`] does before it is printed.
+pub fn install_panic_hook() {
+    let print = panic::take_hook();
+    panic::set_hook(Box::new(move |info| {
+        let _ = restore();
+        hold(&info.to_string());
+        print(info);
+    }));
+}
+
+fn restore() -> io::Result<()> {
+    disable_raw_mode()?;
+    execute!(io::stdout(), LeaveAlternateScreen)
+}
+
+fn show(message: &str) -> io::Result<()> {
+    enable_raw_mode()?;
+    execute!(io::stdout(), EnterAlternateScreen)?;
+    let mut terminal = Terminal::new(CrosstermBackend::new(io::stdout()))?;
+
+    let mut lines: Vec<Line> = message.lines().map(Line::from).collect();
+    lines.push(Line::from(""));
+    lines.push(Line::styled(
+        t("Press any key to close."),
+        Style::default().fg(Color::DarkGray),
+    ));
+    let height = lines.len() as u16 + 4;
+    loop {
+        terminal.draw(|f| {
+            let [area] = Layout::vertical([Constraint::Length(height)])
+                .flex(Flex::Center)
+                .areas(f.area());
+            let [area] = Layout::horizontal([Constraint::Percentage(80)])
+                .flex(Flex::Center)
+                .areas(area);
+            let block = Block::default()
+                .title(format!(" {} ", t("xwlm could not start")))
+                .title_style(
+                    Style::default()
+                        .fg(Color::Red)
+                        .add_modifier(Modifier::BOLD),
+                )
+                .borders(Borders::ALL)
+                .border_type(BorderType::Rounded)
+                .border_style(Style::default().fg(Color::Red));
+            let text = Paragraph::new(lines.clone())
+                .block(block)
+                .wrap(Wrap { trim: false });
+            f.render_widget(Clear, area);
+            f.render_widget(text, area);
+        })?;
+        if let Event::Key(key) = event::read()?
+            && key.kind == KeyEventKind::Press
+        {
+            return Ok(());
+        }
+    }
+}
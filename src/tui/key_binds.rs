@@ -1,6 +1,8 @@
 use crate::{
-    compositor::Compositor,
-    state::{App, Panel},
+    compositor::{Compositor, capabilities::ConfigStyle},
+    history,
+    i18n::{t, tf},
+    state::{App, Panel, SaveState},
 };
 
 use ratatui::{
@@ -11,139 +13,351 @@ use ratatui::{
     widgets::Paragraph,
 };
 
+/// The keys that still work while observing; [`strike_unobservable`]
+/// crosses out the others.
+const OBSERVER_KEYS: [&str; 9] =
+    ["Tab", "H", "T", "=", "q", "+/-", "[]", "↑↓", "f"];
+
+/// The keybinding hints. Without the status line they start with the
+/// badges it would show.
 pub fn config(frame: &mut Frame, area: Rect, app: &App) {
     let panel = &app.panel;
-    let mut keys = vec![
-        Span::styled(
-            format!("[xwlm]-[{}]", app.compositor.label()),
+    let mut keys = if app.config.status_line {
+        Vec::new()
+    } else {
+        badges(app)
+    };
+    if !keys.is_empty() {
+        keys.push(Span::styled(" | ", Style::default().fg(Color::Cyan)));
+    }
+    let start = keys.len();
+    hint(&mut keys, "Tab", "switch panel");
+    hint(&mut keys, "H", "history");
+    hint(&mut keys, "T", "timeline");
+    hint(&mut keys, "=", "vs config");
+    hint(&mut keys, "W", "apply all");
+    if app.is_dry_run() && !app.observing {
+        hint(&mut keys, "L", "dry run log");
+    }
+    hint(&mut keys, "q", "quit");
+    strike_unobservable(&mut keys[start..], app);
+    keys.push(Span::styled("| ", Style::default().fg(Color::DarkGray)));
+
+    let name = match panel {
+        Panel::Monitor => t("Monitor Layout"),
+        Panel::Mode => t("Modes"),
+        Panel::Scale => t("Scale"),
+        Panel::Transform => t("Transform"),
+        Panel::Workspace => t("Workspaces"),
+        Panel::Options => t("Options"),
+    };
+    keys.push(Span::styled(
+        format!("[ {name} | "),
+        Style::default().fg(Color::Cyan),
+    ));
+    let start = keys.len();
+    match panel {
+        Panel::Monitor => get_monitor_keybinds(&mut keys),
+        Panel::Mode => get_modes_keybinds(&mut keys),
+        Panel::Scale => get_scale_keybinds(&mut keys),
+        Panel::Transform => get_transform_keybinds(&mut keys),
+        Panel::Workspace => get_workspaces_keybinds(&mut keys, app.compositor),
+        Panel::Options => get_options_keybinds(&mut keys),
+    }
+    strike_unobservable(&mut keys[start..], app);
+    keys.push(Span::styled("]", Style::default().fg(Color::Cyan)));
+
+    let line = Line::from(fit(keys, area.width as usize));
+    frame.render_widget(Paragraph::new(line), area);
+}
+
+/// The compositor, dry run and reload state, e.g. "[xwlm]-[Sway]".
+fn badges(app: &App) -> Vec<Span<'static>> {
+    let mut spans = vec![Span::styled(
+        format!("[xwlm]-[{}]", app.compositor.label()),
+        Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD),
+    )];
+    if app.observing {
+        spans.push(Span::styled(
+            format!("-[{}]", t("OBSERVER")),
             Style::default()
-                .fg(Color::Cyan)
+                .fg(Color::Black)
+                .bg(Color::Yellow)
                 .add_modifier(Modifier::BOLD),
-        ),
-        Span::styled(" | ", Style::default().fg(Color::Cyan)),
-        Span::styled("Tab ", Style::default().fg(Color::Cyan)),
-        Span::styled("switch panel  ", Style::default().fg(Color::DarkGray)),
-        Span::styled("q ", Style::default().fg(Color::Cyan)),
-        Span::styled("quit", Style::default().fg(Color::DarkGray)),
-        Span::styled(" | ", Style::default().fg(Color::DarkGray)),
-    ];
+        ));
+    } else if app.replaying {
+        spans.push(Span::styled(
+            format!("-[{}]", t("REPLAY")),
+            Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+        ));
+    } else if app.is_dry_run() {
+        spans.push(Span::styled(
+            format!("-[{}]", t("DRY RUN")),
+            Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+        ));
+    }
+    if app.is_reloading() {
+        spans.push(Span::styled(
+            format!("-[{}]", t("reloading…")),
+            Style::default().fg(Color::Yellow),
+        ));
+    }
+    spans
+}
 
-    match panel {
-        Panel::Monitor => {
-            keys.push(Span::styled(
-                "[ Monitor Layout | ",
-                Style::default().fg(Color::Cyan),
-            ));
-            get_monitor_keybinds(&mut keys);
-            keys.push(Span::styled("]", Style::default().fg(Color::Cyan)));
-        }
-        Panel::Mode => {
-            keys.push(Span::styled(
-                "[ Modes | ",
-                Style::default().fg(Color::Cyan),
-            ));
-            get_modes_keybinds(&mut keys);
-            keys.push(Span::styled("]", Style::default().fg(Color::Cyan)));
+/// The second footer line: the monitor config path, cut from the left to
+/// fit, then whether everything is saved and the badges.
+pub fn status(frame: &mut Frame, area: Rect, app: &App) {
+    let (color, state) = match &app.save_state {
+        SaveState::ReloadFailed { .. } => {
+            (Color::Red, Some(tf("reload failed ({} why)", &[&"!"])))
         }
-        Panel::Scale => {
-            keys.push(Span::styled(
-                "[ Scale | ",
-                Style::default().fg(Color::Cyan),
-            ));
-            get_scale_keybinds(&mut keys);
-            keys.push(Span::styled("]", Style::default().fg(Color::Cyan)));
-        }
-        Panel::Transform => {
-            keys.push(Span::styled(
-                "[ Transform | ",
-                Style::default().fg(Color::Cyan),
-            ));
-            get_transform_keybinds(&mut keys);
-            keys.push(Span::styled("]", Style::default().fg(Color::Cyan)));
-        }
-        Panel::Workspace => {
-            keys.push(Span::styled(
-                "[ Workspaces | ",
-                Style::default().fg(Color::Cyan),
-            ));
-            get_workspaces_keybinds(&mut keys, app.compositor);
-            keys.push(Span::styled("]", Style::default().fg(Color::Cyan)));
+        _ if app.has_unsaved_changes() => {
+            (Color::Yellow, Some(t("unsaved changes").to_string()))
         }
+        _ => (Color::Green, None),
+    };
+    let dot = match state {
+        Some(state) => format!("  ● {state}"),
+        None => "  ●".to_string(),
     };
-    let line = Line::from(keys);
+    let mut tail = vec![Span::styled(dot, Style::default().fg(color))];
+    let saved = match app.last_saved() {
+        Some(at) => tf("saved at {}", &[&history::clock(at)]),
+        None => t("not saved yet").to_string(),
+    };
+    tail.push(Span::styled(
+        format!("  {saved}"),
+        Style::default().fg(Color::DarkGray),
+    ));
+    if app.undo_depth() > 0 {
+        tail.push(Span::styled(
+            format!("  {}", tf("undo {}", &[&app.undo_depth()])),
+            Style::default().fg(Color::DarkGray),
+        ));
+    }
+    let caps = app.compositor.capabilities();
+    let reload = if caps.reload_command.is_some()
+        || caps.reload_runs_config
+        || caps.applies_by_command
+    {
+        t("auto-reload on")
+    } else if caps.config_style.is_some_and(ConfigStyle::is_script) {
+        // Nothing runs the script but the user's own autostart.
+        t("kept by script")
+    } else {
+        t("auto-reload off")
+    };
+    tail.push(Span::styled(
+        format!("  {reload}  "),
+        Style::default().fg(Color::DarkGray),
+    ));
+    tail.extend(badges(app));
+
+    let used: usize = tail.iter().map(|s| s.content.chars().count()).sum();
+    let room = (area.width as usize).saturating_sub(used);
+    let path = app.comp_monitor_config_path.to_string_lossy();
+    let path = trim_left(&path, room);
+    let mut spans =
+        vec![Span::styled(path, Style::default().fg(Color::White))];
+    spans.extend(tail);
+
+    let line = Line::from(fit(spans, area.width as usize));
     frame.render_widget(Paragraph::new(line), area);
 }
 
-pub fn get_monitor_keybinds(keys: &mut Vec<Span<'static>>) {
-    keys.push(Span::styled("↑↓ ←→ ", Style::default().fg(Color::Cyan)));
-    keys.push(Span::styled("move  ", Style::default().fg(Color::DarkGray)));
-    keys.push(Span::styled("+/- ", Style::default().fg(Color::Cyan)));
-    keys.push(Span::styled("zoom  ", Style::default().fg(Color::DarkGray)));
-    keys.push(Span::styled("[] ", Style::default().fg(Color::Cyan)));
-    keys.push(Span::styled(
-        "switch monitor ",
-        Style::default().fg(Color::DarkGray),
-    ));
+/// Keeps the end of `text` within `width` columns, starting with "…" when
+/// the front was cut.
+fn trim_left(text: &str, width: usize) -> String {
+    let len = text.chars().count();
+    if len <= width {
+        return text.to_string();
+    }
+    if width == 0 {
+        return String::new();
+    }
+    let kept: String = text.chars().skip(len - (width - 1)).collect();
+    format!("…{kept}")
 }
 
-pub fn get_modes_keybinds(keys: &mut Vec<Span<'static>>) {
-    keys.push(Span::styled("↑↓ ", Style::default().fg(Color::Cyan)));
+/// Pushes a key in cyan followed by its translated description.
+pub fn hint(keys: &mut Vec<Span<'_>>, key: &str, label: &'static str) {
     keys.push(Span::styled(
-        "select  ",
-        Style::default().fg(Color::DarkGray),
+        format!("{key} "),
+        Style::default().fg(Color::Cyan),
     ));
-    keys.push(Span::styled("Enter ", Style::default().fg(Color::Cyan)));
     keys.push(Span::styled(
-        "apply  ",
+        format!("{}  ", t(label)),
         Style::default().fg(Color::DarkGray),
     ));
 }
 
+/// Crosses out the hints in `keys`, as [`hint`] pushed them, for keys
+/// that do nothing while xwlm only observes.
+pub fn strike_unobservable(keys: &mut [Span<'_>], app: &App) {
+    if !app.observing {
+        return;
+    }
+    for pair in keys.chunks_exact_mut(2) {
+        if OBSERVER_KEYS.contains(&pair[0].content.trim_end()) {
+            continue;
+        }
+        for span in pair {
+            span.style = span.style.add_modifier(Modifier::CROSSED_OUT);
+        }
+    }
+}
+
+/// Cuts `spans` to `width` columns, ending in "…" when anything was lost.
+/// Columns are counted per char, which holds for the text xwlm draws.
+pub fn fit(spans: Vec<Span<'_>>, width: usize) -> Vec<Span<'_>> {
+    let total: usize = spans.iter().map(|s| s.content.chars().count()).sum();
+    if total <= width {
+        return spans;
+    }
+    if width == 0 {
+        return Vec::new();
+    }
+
+    let mut left = width - 1;
+    let mut out = Vec::new();
+    let mut style = Style::default();
+    for span in spans {
+        let len = span.content.chars().count();
+        style = span.style;
+        if len <= left {
+            left -= len;
+            out.push(span);
+            continue;
+        }
+        let cut: String = span.content.chars().take(left).collect();
+        if !cut.is_empty() {
+            out.push(Span::styled(cut, span.style));
+        }
+        break;
+    }
+    if let Some(last) = out.last_mut() {
+        let trimmed = last.content.trim_end().to_string();
+        last.content = trimmed.into();
+    }
+    out.push(Span::styled("…", style));
+    out
+}
+
+pub fn get_monitor_keybinds(keys: &mut Vec<Span<'static>>) {
+    hint(keys, "↑↓ ←→", "move");
+    hint(keys, "+/-", "zoom");
+    hint(keys, "[]", "switch monitor");
+    hint(keys, "R", "rotate layout");
+    hint(keys, "a", "anchor");
+    hint(keys, "o", "power");
+    hint(keys, "O", "only this");
+    hint(keys, "A", "enable all");
+    hint(keys, "g", "align");
+    hint(keys, "c", "copy to");
+    hint(keys, "m", "make default");
+}
+
+pub fn get_modes_keybinds(keys: &mut Vec<Span<'static>>) {
+    hint(keys, "↑↓", "select");
+    hint(keys, "Enter", "apply");
+    hint(keys, "⌫", "previous mode");
+    hint(keys, "f", "same aspect");
+}
+
 pub fn get_workspaces_keybinds(
     keys: &mut Vec<Span<'static>>,
     compositor: Compositor,
 ) {
-    keys.push(Span::styled("←→ ", Style::default().fg(Color::Cyan)));
-    keys.push(Span::styled(
-        "assign  ",
-        Style::default().fg(Color::DarkGray),
-    ));
-    if compositor.supports_workspace_defaults() {
-        keys.push(Span::styled("d ", Style::default().fg(Color::Cyan)));
-        keys.push(Span::styled(
-            "default  ",
-            Style::default().fg(Color::DarkGray),
-        ));
-        keys.push(Span::styled("p ", Style::default().fg(Color::Cyan)));
-        keys.push(Span::styled(
-            "persistent  ",
-            Style::default().fg(Color::DarkGray),
-        ));
+    hint(keys, "←→", "assign");
+    hint(keys, "Enter", "fallbacks");
+    if compositor.capabilities().supports_workspace_defaults {
+        hint(keys, "d", "default");
+        hint(keys, "p", "persistent");
     }
+    hint(keys, "e", "edit id");
+    hint(keys, "f", "this monitor");
+    hint(keys, "P", "pin");
+    hint(keys, "D", "distribute");
+    hint(keys, "X", "clear all");
 }
 
 pub fn get_scale_keybinds(keys: &mut Vec<Span<'static>>) {
-    keys.push(Span::styled("←→ ", Style::default().fg(Color::Cyan)));
-    keys.push(Span::styled(
-        "adjust ",
-        Style::default().fg(Color::DarkGray),
-    ));
-    keys.push(Span::styled("Enter ", Style::default().fg(Color::Cyan)));
-    keys.push(Span::styled(
-        "apply  ",
-        Style::default().fg(Color::DarkGray),
-    ));
+    hint(keys, "←→", "adjust");
+    hint(keys, "*", "recommended");
+    hint(keys, ", .", "clean scale");
+    hint(keys, "Enter", "apply");
 }
 
 pub fn get_transform_keybinds(keys: &mut Vec<Span<'static>>) {
-    keys.push(Span::styled("↑↓ ", Style::default().fg(Color::Cyan)));
-    keys.push(Span::styled(
-        "rotate  ",
-        Style::default().fg(Color::DarkGray),
-    ));
-    keys.push(Span::styled("Enter ", Style::default().fg(Color::Cyan)));
-    keys.push(Span::styled(
-        "apply  ",
-        Style::default().fg(Color::DarkGray),
-    ));
+    hint(keys, "↑↓", "rotate");
+    hint(keys, "p", "preview");
+    hint(keys, "Enter", "apply");
+}
+
+pub fn get_options_keybinds(keys: &mut Vec<Span<'static>>) {
+    hint(keys, "↑↓", "select");
+    hint(keys, "←→", "change");
+    hint(keys, "Enter", "apply");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text(spans: &[Span]) -> String {
+        spans.iter().map(|s| s.content.as_ref()).collect()
+    }
+
+    #[test]
+    fn test_fit_truncates_with_an_ellipsis() {
+        let spans = vec![Span::raw("Tab "), Span::raw("Panel wechseln  ")];
+        assert_eq!(text(&fit(spans.clone(), 40)), "Tab Panel wechseln  ");
+        assert_eq!(text(&fit(spans.clone(), 10)), "Tab Panel…");
+        assert_eq!(text(&fit(spans.clone(), 5)), "Tab…");
+        assert_eq!(text(&fit(spans.clone(), 1)), "…");
+        assert!(fit(spans, 0).is_empty());
+    }
+
+    #[test]
+    fn test_an_observer_sees_changing_keys_crossed_out() {
+        let (mut app, _rx) = crate::test_support::app();
+        let mut keys = Vec::new();
+        hint(&mut keys, "T", "timeline");
+        hint(&mut keys, "W", "apply all");
+        let crossed = |keys: &[Span]| -> Vec<bool> {
+            keys.iter()
+                .map(|s| s.style.add_modifier.contains(Modifier::CROSSED_OUT))
+                .collect()
+        };
+
+        strike_unobservable(&mut keys, &app);
+        assert_eq!(crossed(&keys), [false; 4]);
+
+        app.observe();
+        strike_unobservable(&mut keys, &app);
+        assert_eq!(crossed(&keys), [false, false, true, true]);
+        assert!(text(&badges(&app)).contains("[OBSERVER]"));
+    }
+
+    #[test]
+    fn test_an_unknown_compositor_is_driven_as_generic_wlr() {
+        let (mut app, _rx) = crate::test_support::app();
+        app.compositor = Compositor::Unknown;
+        app.comp_monitor_config_path = "/home/me/monitors.sh".into();
+        let lines = crate::test_support::screen(100, 1, |f| {
+            status(f, f.area(), &app)
+        });
+        assert!(lines[0].contains("kept by script"), "{}", lines[0]);
+        assert!(lines[0].contains("[xwlm]-[wlr-generic]"), "{}", lines[0]);
+    }
+
+    #[test]
+    fn test_trim_left_keeps_the_end_of_a_path() {
+        let path = "/home/me/.config/hypr/monitors.conf";
+        assert_eq!(trim_left(path, 60), path);
+        assert_eq!(trim_left(path, 14), "…monitors.conf");
+        assert_eq!(trim_left(path, 1), "…");
+        assert_eq!(trim_left(path, 0), "");
+    }
 }
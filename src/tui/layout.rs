@@ -1,68 +1,436 @@
 use crate::{
-    state::App,
+    constants::COMPACT_LAYOUT_MONITORS,
+    i18n::t,
+    state::{App, Panel},
     tui::{
-        key_binds,
+        key_binds::{self, fit, hint},
         panels::{
-            left::{self},
-            mode, workspace,
+            self, Panels, apply_all, clone_picker, config_check, confirm,
+            fallbacks, history, left, new_monitor, reconcile,
+            revert_countdown, settings, stray_rules, timeline, tour,
+            transcript, transform_preview, untouched,
         },
     },
 };
 
 use ratatui::{
-    layout::{Constraint, Direction, Layout},
-    style::{Color, Style},
-    widgets::Paragraph,
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Paragraph, Tabs},
     Frame,
 };
 
-pub fn draw(frame: &mut Frame, app: &mut App) {
+pub fn draw(frame: &mut Frame, app: &mut App, panels: &mut Panels) {
     let area = frame.area();
 
-    let error_exists =
-        app.error_message.is_some() || app.pending_last_toggle_monitor;
-
-    let constraints: [Constraint; 3] = if error_exists {
-        [
-            Constraint::Min(1),
-            Constraint::Length(1),
-            Constraint::Length(1),
-        ]
-    } else {
-        [
-            Constraint::Min(1),
-            Constraint::Length(1),
-            Constraint::Length(0),
-        ]
-    };
+    let error_exists = app.error_message.is_some()
+        || app.status_message.is_some()
+        || app.prompt.is_some()
+        || app.confirm.is_some();
 
+    // The footer keeps its height, so the panels only move when a message
+    // comes or goes.
+    let footer = if app.config.status_line { 2 } else { 1 };
+    let message = u16::from(error_exists);
     let main_layout = Layout::default()
         .direction(Direction::Vertical)
-        .constraints(constraints)
-        .split(area);
-
-    let content = Layout::default()
-        .direction(Direction::Horizontal)
         .constraints([
-            Constraint::Percentage(50),
-            Constraint::Percentage(20),
-            Constraint::Percentage(30),
+            Constraint::Min(1),
+            Constraint::Length(footer),
+            Constraint::Length(message),
         ])
-        .split(main_layout[0]);
+        .split(area);
+    let footer = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints(vec![Constraint::Length(1); footer as usize])
+        .split(main_layout[1]);
 
-    left::panel(frame, app, content[0]);
-    mode::panel(frame, app, content[1]);
-    workspace::panel(frame, app, content[2]);
-    key_binds::config(frame, main_layout[1], app);
+    if app.monitors.len() > COMPACT_LAYOUT_MONITORS {
+        let content = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([
+                Constraint::Percentage(65),
+                Constraint::Percentage(35),
+            ])
+            .split(main_layout[0]);
+        let side = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Length(1), Constraint::Min(1)])
+            .split(content[1]);
 
-    if let Some(ref err) = app.error_message {
+        draw_left(frame, app, panels, content[0]);
+        if app.has_panel(Panel::Workspace) {
+            let shown = app.touring().unwrap_or(app.panel);
+            let workspaces = shown == Panel::Workspace;
+            let tabs = Tabs::new([t("Modes"), t("Workspaces")])
+                .select(usize::from(workspaces))
+                .style(Style::default().fg(Color::DarkGray))
+                .highlight_style(
+                    Style::default()
+                        .fg(Color::Cyan)
+                        .add_modifier(Modifier::BOLD),
+                );
+            frame.render_widget(tabs, side[0]);
+            let side_panel = if workspaces {
+                Panel::Workspace
+            } else {
+                Panel::Mode
+            };
+            panels.render(side_panel, frame, side[1], app);
+        } else {
+            panels.render(Panel::Mode, frame, content[1], app);
+        }
+    } else {
+        // Without workspace rules the Modes panel takes their room too.
+        let constraints = if app.has_panel(Panel::Workspace) {
+            [20, 30]
+        } else {
+            [50, 0]
+        };
+        let content = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([
+                Constraint::Percentage(50),
+                Constraint::Percentage(constraints[0]),
+                Constraint::Percentage(constraints[1]),
+            ])
+            .split(main_layout[0]);
+
+        draw_left(frame, app, panels, content[0]);
+        panels.render(Panel::Mode, frame, content[1], app);
+        if app.has_panel(Panel::Workspace) {
+            panels.render(Panel::Workspace, frame, content[2], app);
+        }
+    }
+    key_binds::config(frame, footer[0], app);
+    if let Some(&area) = footer.get(1) {
+        key_binds::status(frame, area, app);
+    }
+
+    if let Some(ref prompt) = app.prompt {
+        let mut spans = vec![
+            Span::styled(
+                format!("{}: ", prompt.label()),
+                Style::default().fg(Color::Cyan),
+            ),
+            Span::styled(&prompt.input, Style::default().fg(Color::White)),
+            Span::styled("▏  ", Style::default().fg(Color::White)),
+        ];
+        hint(&mut spans, "Enter", "confirm");
+        hint(&mut spans, "Esc", "cancel");
+        let prompt_bar = Paragraph::new(Line::from(fit(
+            spans,
+            main_layout[2].width as usize,
+        )));
+        frame.render_widget(prompt_bar, main_layout[2]);
+    } else if let Some(ref err) = app.error_message {
         let error_bar =
             Paragraph::new(err.as_str()).style(Style::default().fg(Color::Red));
         frame.render_widget(error_bar, main_layout[2]);
+    } else if let Some(ref status) = app.status_message {
+        let status_bar = Paragraph::new(status.as_str())
+            .style(Style::default().fg(Color::Green));
+        frame.render_widget(status_bar, main_layout[2]);
+    }
+
+    if app.history.is_some() {
+        history::render_modal(frame, app, area);
+    }
+
+    if app.timeline_view.is_some() {
+        timeline::render_modal(frame, app, area);
+    }
+
+    let symbol = panels::highlight_symbol(app).to_string();
+    if let Some(ref mut picker) = app.clone_picker {
+        clone_picker::render_modal(frame, picker, &symbol, area);
+    }
+
+    if let Some(ref mut editor) = app.fallback_editor {
+        fallbacks::render_modal(frame, editor, &symbol, area);
+    }
+
+    if let Some(ref mut editor) = app.settings {
+        settings::render_modal(frame, editor, &symbol, area);
+    }
+
+    if let Some(ref preview) = app.transform_preview {
+        transform_preview::render_overlay(frame, preview, area);
+    }
+
+    if let Some(ref guarded) = app.guarded {
+        revert_countdown::render_overlay(frame, guarded, area);
+    }
+
+    if app.transcript_open {
+        transcript::render_modal(frame, app, area);
+    }
+
+    if let Some(ref view) = app.apply_all {
+        apply_all::render_modal(frame, view, area);
+    }
+
+    if let Some(ref confirm) = app.confirm {
+        confirm::render_modal(frame, confirm::dialog(app, confirm), area);
+    }
+
+    if app.reconcile.is_some() {
+        reconcile::render_modal(frame, app, area);
+    }
+
+    if let Some(ref name) = app.new_monitor {
+        new_monitor::render_modal(frame, name, area);
+    }
+
+    if app.show_stray_rules {
+        stray_rules::render_modal(frame, app, area);
+    }
+
+    if let Some(ref changes) = app.untouched_changes {
+        untouched::render_modal(frame, changes, area);
+    }
+
+    if app.config_check.is_some() {
+        config_check::render_modal(frame, app, area);
+    }
+
+    if app.tour.is_some() {
+        tour::render_overlay(frame, app, panels.toured());
+    }
+}
+
+fn draw_left(frame: &mut Frame, app: &App, panels: &mut Panels, area: Rect) {
+    let [map, scale, transform, options] = left::areas(area);
+    panels.render(Panel::Monitor, frame, map, app);
+    panels.render(Panel::Scale, frame, scale, app);
+    panels.render(Panel::Transform, frame, transform, app);
+    panels.render(Panel::Options, frame, options, app);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compositor::{
+        Compositor, check::ConfigStatus, diff::MonitorChange,
+        settings::config_monitors,
+    };
+    use crate::history::Snapshot;
+    use crate::layout::Layout as MonitorLayout;
+    use crate::reconcile::diverging;
+    use crate::state::{
+        ApplyAll, Confirm, GuardedChange, HistoryBrowser, PromptKind,
+        Reconciliation, Revert, SaveState, TransformPreview,
+    };
+    use crate::test_support::{app, monitor};
+    use ratatui::{Terminal, backend::TestBackend, widgets::ListState};
+    use std::time::Instant;
+    use wlx_monitors::WlTransform;
+
+    fn twelve_monitors() -> App {
+        let (mut app, _rx) = app();
+        let monitors = (0..12)
+            .map(|i| {
+                let (x, y) = ((i % 4) * 1920, (i / 4) * 1080);
+                let mut m = monitor(&format!("DP-{i}"), x, y);
+                m.enabled = i < 8;
+                m
+            })
+            .collect();
+        app.set_monitors(monitors);
+        app
+    }
+
+    fn render(app: &mut App, width: u16, height: u16) -> String {
+        let backend = TestBackend::new(width, height);
+        let mut terminal = Terminal::new(backend).unwrap();
+        let mut panels = Panels::default();
+        terminal.draw(|f| draw(f, app, &mut panels)).unwrap();
+        terminal
+            .backend()
+            .buffer()
+            .content()
+            .iter()
+            .map(|c| c.symbol())
+            .collect()
+    }
+
+    #[test]
+    fn test_many_monitors_collapse_side_panels_into_tabs() {
+        let mut app = twelve_monitors();
+        app.compositor = Compositor::Hyprland;
+        for (width, height) in [(240, 70), (120, 40), (80, 24)] {
+            let screen = render(&mut app, width, height);
+            assert!(screen.contains("Workspaces"), "{width}x{height}");
+        }
+        // River takes no workspace rules, so there is no tab for them.
+        app.compositor = Compositor::River;
+        assert!(!render(&mut app, 120, 40).contains("Workspaces"));
+        app.compositor = Compositor::Hyprland;
+
+        let screen = render(&mut app, 240, 70);
+        assert!(screen.contains("Modes"));
+        assert!(screen.contains("DP-0"));
+        assert!(screen.contains("DP-11"));
+    }
+
+    #[test]
+    fn test_narrow_warning_modal_truncates_instead_of_overflowing() {
+        let (mut app, _rx) = app();
+        app.set_monitors(vec![monitor("DP-1", 0, 0)]);
+        app.confirm = Some(Confirm::LastMonitor);
+
+        let screen = render(&mut app, 36, 20);
+        assert!(screen.contains("…"));
+        assert!(screen.contains("[Y]es"));
+        assert!(screen.contains("[N]o"));
+    }
+
+    #[test]
+    fn test_missing_monitor_config_shows_banner() {
+        let (mut app, _rx) = app();
+        app.config_check = Some(ConfigStatus::Missing);
+
+        let screen = render(&mut app, 120, 30);
+        assert!(screen.contains("Monitor Layout — 100%"));
+        assert!(screen.contains("/nonexistent/xwlm-test.conf"));
+        assert!(screen.contains("run setup"));
+        assert!(screen.contains("create file"));
+    }
+
+    #[test]
+    fn test_status_line_shows_save_state_and_can_be_hidden() {
+        let (mut app, _rx) = app();
+        app.set_monitors(vec![monitor("DP-1", 0, 0)]);
+        let screen = render(&mut app, 120, 30);
+        assert!(screen.contains("/nonexistent/xwlm-test.conf"));
+        assert!(screen.contains("not saved yet"));
+        assert!(!screen.contains("unsaved changes"));
+
+        app.pending_positions.insert("DP-1".into(), (100, 0));
+        let screen = render(&mut app, 120, 30);
+        assert!(screen.contains("unsaved changes"));
+
+        app.save_state = SaveState::ReloadFailed {
+            reason: "parse error".into(),
+        };
+        let screen = render(&mut app, 120, 30);
+        assert!(screen.contains("● reload failed (! why)"));
+        assert!(!screen.contains("unsaved changes"));
+        app.save_state = SaveState::Clean;
+
+        // The path gives way first on a narrow screen.
+        let screen = render(&mut app, 90, 30);
+        assert!(screen.contains("xwlm-test.conf"));
+        assert!(!screen.contains("/nonexistent/"));
+        assert!(screen.contains("[xwlm]"));
+
+        app.config.status_line = false;
+        let screen = render(&mut app, 120, 30);
+        assert!(!screen.contains("not saved yet"));
+        assert!(screen.contains("[xwlm]"));
+    }
+
+    #[test]
+    fn test_the_tour_dims_all_but_the_panel_it_explains() {
+        let (mut app, _rx) = app();
+        app.set_monitors(vec![monitor("DP-1", 0, 0)]);
+        app.offer_tour(true);
+        app.tour_step();
+        let backend = TestBackend::new(120, 30);
+        let mut terminal = Terminal::new(backend).unwrap();
+        let mut panels = Panels::default();
+        terminal.draw(|f| draw(f, &mut app, &mut panels)).unwrap();
+        let buffer = terminal.backend().buffer();
+        let screen: String =
+            buffer.content().iter().map(|c| c.symbol()).collect();
+        assert!(screen.contains("Tour 2/6"));
+        assert!(screen.contains("skip tour"));
+
+        let modes = panels.toured();
+        let faded = |x, y| {
+            let cell: &ratatui::buffer::Cell = &buffer[(x, y)];
+            cell.modifier.contains(Modifier::DIM)
+        };
+        assert!(!faded(modes.x, modes.y));
+        assert!(faded(0, 0), "the map is dimmed");
+    }
+
+    /// Every modal and overlay open at once, over the panels.
+    fn everything_open() -> App {
+        let (mut app, _rx) = app();
+        let mut dp2 = monitor("DP-2", 1920, 0);
+        dp2.scale = 2.0;
+        app.set_monitors(vec![monitor("DP-1", 0, 0), dp2]);
+        let list = || ListState::default().with_selected(Some(0));
+        let layout = MonitorLayout::capture(&app.monitors, &[]);
+        app.history = Some(HistoryBrowser {
+            entries: vec![Snapshot {
+                timestamp: 0,
+                layout,
+            }],
+            state: list(),
+        });
+        app.timeline_view = Some(list());
+        app.open_clone_picker();
+        app.open_fallback_editor();
+        let config = config_monitors(
+            Compositor::Hyprland,
+            "monitor = DP-2, 1920x1080@60, 1920x0, 1\n",
+        );
+        app.reconcile = Some(Reconciliation {
+            rows: diverging(&config, &app.monitors),
+            state: list(),
+        });
+        let now = Instant::now();
+        app.transform_preview = Some(TransformPreview {
+            name: "DP-1".into(),
+            transform: WlTransform::Rotate90,
+            previous: WlTransform::Normal,
+            deadline: now,
+        });
+        app.guarded = Some(GuardedChange {
+            name: "DP-1".into(),
+            revert: Revert::Scale(1.0),
+            deadline: now,
+        });
+        app.transcript_open = true;
+        app.apply_all = Some(ApplyAll {
+            steps: Vec::new(),
+            extras: vec!["workspaces"],
+            notes: vec!["waybar has a layer surface on DP-1".into()],
+            results: None,
+        });
+        app.confirm = Some(Confirm::LastMonitor);
+        app.new_monitor = Some("DP-3".into());
+        app.show_stray_rules = true;
+        app.untouched_changes = Some(vec![MonitorChange {
+            name: "DP-2".into(),
+            old: vec!["monitor = DP-2, preferred, auto, 1".into()],
+            new: vec!["monitor = DP-2, 1920x1080@60, 1920x0, 2".into()],
+        }]);
+        app.config_check = Some(ConfigStatus::Missing);
+        app.open_prompt(PromptKind::WorkspaceId);
+        app.offer_tour(true);
+        app
     }
 
-    if app.pending_last_toggle_monitor {
-        let config_path = app.comp_monitor_config_path.to_string_lossy();
-        left::render_warning_modal(frame, area, &config_path);
+    #[test]
+    fn test_shrinking_terminal_never_panics() {
+        let (mut plain, _rx) = app();
+        plain.set_monitors(vec![monitor("DP-1", 0, 0)]);
+        plain.zoom_in();
+        let mut open = everything_open();
+        let mut many = twelve_monitors();
+        let mut panels = Panels::default();
+        let mut terminal = Terminal::new(TestBackend::new(200, 60)).unwrap();
+        let sizes = (1..=200).rev().step_by(7).zip((1..=60).rev().step_by(2));
+        for (width, height) in sizes.chain([(3, 1), (1, 3), (1, 1)]) {
+            terminal.backend_mut().resize(width, height);
+            for app in [&mut plain, &mut open, &mut many] {
+                app.fit_map();
+                terminal.draw(|f| draw(f, app, &mut panels)).unwrap();
+            }
+        }
     }
 }
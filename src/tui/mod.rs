@@ -1,4 +1,5 @@
-mod key_binds;
+pub mod fatal;
+pub mod key_binds;
 mod layout;
 mod panels;
 mod ui;
@@ -11,17 +12,22 @@ use ratatui::{Terminal, prelude::CrosstermBackend};
 use std::{io, sync::mpsc::Receiver};
 use wlx_monitors::WlMonitorEvent;
 
-use crate::state::App;
+use crate::{control::Server, state::App};
 
-pub fn run(app: &mut App, wlx_events: Receiver<WlMonitorEvent>) -> Result<(), ui::TuiLoopError> {
+pub fn run(
+    app: &mut App,
+    wlx_events: Receiver<WlMonitorEvent>,
+    control: Option<&mut Server>,
+) -> Result<(), ui::TuiLoopError> {
     enable_raw_mode()?;
     let mut stdout = io::stdout();
     execute!(stdout, EnterAlternateScreen)?;
 
     let backend = CrosstermBackend::new(stdout);
     let mut terminal = Terminal::new(backend)?;
+    fatal::set_holding(false);
 
-    ui::tui_loop(app, wlx_events, &mut terminal)?;
+    ui::tui_loop(app, wlx_events, control, &mut terminal)?;
 
     disable_raw_mode()?;
     execute!(terminal.backend_mut(), LeaveAlternateScreen)?;
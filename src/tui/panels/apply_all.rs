@@ -0,0 +1,84 @@
+use crate::{
+    i18n::t,
+    state::ApplyAll,
+    tui::key_binds::{fit, hint},
+};
+
+use ratatui::{
+    Frame,
+    layout::Rect,
+    style::{Color, Style},
+    text::{Line, Span},
+    widgets::{Block, BorderType, Borders, Clear, Paragraph},
+};
+
+pub fn render_modal(frame: &mut Frame, view: &ApplyAll, area: Rect) {
+    let rows = view.steps.len() + view.extras.len() + view.notes.len();
+    let modal_w = area.width.saturating_sub(8).clamp(20, 72).min(area.width);
+    let modal_h = (rows as u16 + 2).max(5).min(area.height.saturating_sub(2));
+    let x = (area.width.saturating_sub(modal_w)) / 2;
+    let y = (area.height.saturating_sub(modal_h)) / 2;
+    let modal_area = Rect::new(x, y, modal_w, modal_h);
+
+    frame.render_widget(Clear, modal_area);
+
+    let mut title = vec![Span::styled(
+        format!(" {} | ", t("Apply everything")),
+        Style::default().fg(Color::Yellow),
+    )];
+    if view.results.is_none() {
+        hint(&mut title, "Enter", "apply");
+        hint(&mut title, "Esc", "cancel");
+    } else {
+        hint(&mut title, "Esc", "close");
+    }
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .border_style(Style::default().fg(Color::Yellow))
+        .title(Line::from(fit(title, modal_w.saturating_sub(2) as usize)));
+
+    let inner = block.inner(modal_area);
+    frame.render_widget(block, modal_area);
+
+    let width = inner.width as usize;
+    let mut lines: Vec<Line> = match &view.results {
+        None => view
+            .steps
+            .iter()
+            .map(|step| step.to_string())
+            .chain(view.extras.iter().map(|extra| extra.to_string()))
+            .map(|line| {
+                let span = Span::styled(
+                    format!(" {line}"),
+                    Style::default().fg(Color::White),
+                );
+                Line::from(fit(vec![span], width))
+            })
+            .collect(),
+        Some(results) => results
+            .iter()
+            .map(|(line, error)| {
+                let span = match error {
+                    None => Span::styled(
+                        format!(" ✓ {line}"),
+                        Style::default().fg(Color::Green),
+                    ),
+                    Some(e) => Span::styled(
+                        format!(" ✗ {line}: {e}"),
+                        Style::default().fg(Color::Red),
+                    ),
+                };
+                Line::from(fit(vec![span], width))
+            })
+            .collect(),
+    };
+    lines.extend(view.notes.iter().map(|note| {
+        let span = Span::styled(
+            format!(" ℹ {note}"),
+            Style::default().fg(Color::Yellow),
+        );
+        Line::from(fit(vec![span], width))
+    }));
+    frame.render_widget(Paragraph::new(lines), inner);
+}
@@ -0,0 +1,75 @@
+use crate::{
+    i18n::{t, tf},
+    state::ClonePicker,
+    tui::key_binds::{fit, hint},
+};
+
+use ratatui::{
+    Frame,
+    layout::Rect,
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, BorderType, Borders, Clear, List, ListItem},
+};
+
+pub fn render_modal(
+    frame: &mut Frame,
+    picker: &mut ClonePicker,
+    symbol: &str,
+    area: Rect,
+) {
+    let rows = picker.targets.len() as u16 + 1;
+    let modal_w = area.width.saturating_sub(8).clamp(20, 56).min(area.width);
+    let modal_h = (rows + 2).max(5).min(area.height.saturating_sub(2));
+    let x = (area.width.saturating_sub(modal_w)) / 2;
+    let y = (area.height.saturating_sub(modal_h)) / 2;
+    let modal_area = Rect::new(x, y, modal_w, modal_h);
+
+    frame.render_widget(Clear, modal_area);
+
+    let mut title = vec![Span::styled(
+        format!(" {} | ", tf("Copy {}'s settings to", &[&picker.source])),
+        Style::default().fg(Color::Blue),
+    )];
+    hint(&mut title, "↑↓", "select");
+    hint(&mut title, "←→", "next to it");
+    hint(&mut title, "Enter", "stage");
+    hint(&mut title, "Esc", "cancel");
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .border_style(Style::default().fg(Color::Blue))
+        .title(Line::from(fit(title, modal_w.saturating_sub(2) as usize)));
+
+    let inner = block.inner(modal_area);
+    frame.render_widget(block, modal_area);
+
+    let placement = if picker.adjacent {
+        tf("and place it right of {}", &[&picker.source])
+    } else {
+        t("keep its position").to_string()
+    };
+    let mut items: Vec<ListItem> = picker
+        .targets
+        .iter()
+        .map(|name| {
+            ListItem::new(Line::from(Span::styled(
+                name.clone(),
+                Style::default().fg(Color::White),
+            )))
+        })
+        .collect();
+    items.push(ListItem::new(Line::from(Span::styled(
+        format!("   {placement}"),
+        Style::default().fg(Color::DarkGray),
+    ))));
+
+    let list = List::new(items)
+        .highlight_symbol(symbol)
+        .highlight_style(
+            Style::default()
+                .fg(Color::Cyan)
+                .add_modifier(Modifier::BOLD),
+        );
+    frame.render_stateful_widget(list, inner, &mut picker.state);
+}
@@ -0,0 +1,71 @@
+use crate::{
+    compositor::check::ConfigStatus,
+    i18n::t,
+    state::App,
+    tui::key_binds::{fit, hint},
+};
+
+use ratatui::{
+    Frame,
+    layout::Rect,
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, BorderType, Borders, Clear, Paragraph, Wrap},
+};
+
+pub fn render_modal(frame: &mut Frame, app: &App, area: Rect) {
+    let (Some(status), Some(problem)) =
+        (&app.config_check, app.config_problem())
+    else {
+        return;
+    };
+    let missing = *status == ConfigStatus::Missing;
+
+    let modal_w = 64.min(area.width.saturating_sub(4));
+    let modal_h = 9.min(area.height.saturating_sub(2));
+    let x = (area.width.saturating_sub(modal_w)) / 2;
+    let y = (area.height.saturating_sub(modal_h)) / 2;
+    let modal_area = Rect::new(x, y, modal_w, modal_h);
+
+    frame.render_widget(Clear, modal_area);
+
+    let mut title = vec![Span::styled(
+        format!(" {} | ", t("Monitor config")),
+        Style::default().fg(Color::Red),
+    )];
+    // Setup writes its files directly.
+    if !app.is_dry_run() {
+        hint(&mut title, "s", "run setup");
+    }
+    if missing {
+        hint(&mut title, "c", "create file");
+    }
+    hint(&mut title, "Esc", "continue");
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .border_style(Style::default().fg(Color::Red))
+        .title(Line::from(fit(title, modal_w.saturating_sub(2) as usize)));
+
+    let consequence = if missing {
+        t("Saving would create a new file there, which your compositor \
+           may not read.")
+    } else {
+        t("Monitors and workspaces from this file are ignored.")
+    };
+    let text = vec![
+        Line::from(Span::styled(
+            problem,
+            Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+        )),
+        Line::from(""),
+        Line::from(Span::styled(
+            consequence,
+            Style::default().fg(Color::White),
+        )),
+    ];
+    frame.render_widget(
+        Paragraph::new(text).block(block).wrap(Wrap { trim: true }),
+        modal_area,
+    );
+}
@@ -0,0 +1,315 @@
+use std::env;
+
+use crate::{
+    history, recovery,
+    i18n::{t, tf},
+    state::{App, Confirm, SaveState},
+    tui::key_binds::fit,
+};
+
+use ratatui::{
+    Frame,
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, BorderType, Borders, Clear, Paragraph},
+};
+
+/// A yes/no question: `y` answers yes, any other key no.
+pub struct Dialog {
+    pub title: String,
+    pub lines: Vec<Line<'static>>,
+    pub yes: String,
+    pub no: String,
+    /// The border, and the yes button since yes is the risky answer.
+    pub color: Color,
+}
+
+/// The dialog for what `app` is waiting on.
+pub fn dialog(app: &App, confirm: &Confirm) -> Dialog {
+    let dim = Style::default().fg(Color::DarkGray);
+    let line = |text: String, style: Style| {
+        Line::from(Span::styled(format!(" {text}"), style))
+    };
+    match confirm {
+        Confirm::LastMonitor => {
+            let config_path = app.comp_monitor_config_path.to_string_lossy();
+            let mut lines = vec![
+                line(
+                    t("⚠ Disable your last monitor?").to_string(),
+                    Style::default()
+                        .fg(Color::Red)
+                        .add_modifier(Modifier::BOLD),
+                ),
+                line(
+                    t("No way to undo from here.").to_string(),
+                    Style::default().fg(Color::Yellow),
+                ),
+                Line::from(""),
+                line(
+                    t("To recover, you'll need to:").to_string(),
+                    Style::default().fg(Color::White),
+                ),
+            ];
+            // KWin has no file to edit; it takes the monitor back from a
+            // TTY of the same user.
+            if app.compositor.capabilities().applies_by_command {
+                let name = app.selected_monitor().map_or("", |m| &m.name);
+                let display = env::var("WAYLAND_DISPLAY")
+                    .unwrap_or_else(|_| "wayland-0".to_string());
+                lines.extend([
+                    line(t("1. Open a TTY session").to_string(), dim),
+                    Line::from(vec![
+                        Span::styled(format!(" {} ", t("2. Run")), dim),
+                        Span::styled(
+                            format!(
+                                "WAYLAND_DISPLAY={display} kscreen-doctor \
+                                 output.{name}.enable"
+                            ),
+                            Style::default().fg(Color::Cyan),
+                        ),
+                    ]),
+                    line(t("3. Switch back to your session").into(), dim),
+                ]);
+            } else {
+                lines.extend([
+                    line(t("1. Reboot your machine").to_string(), dim),
+                    line(t("2. Open a TTY session").to_string(), dim),
+                    Line::from(vec![
+                        Span::styled(format!(" {} ", t("3. Edit")), dim),
+                        Span::styled(
+                            config_path.into_owned(),
+                            Style::default().fg(Color::Cyan),
+                        ),
+                    ]),
+                    line(
+                        format!("   {}", t("and remove the disable line")),
+                        dim,
+                    ),
+                    line(
+                        t("4. Reboot and log into your compositor").into(),
+                        dim,
+                    ),
+                ]);
+            }
+            let recovery = recovery::recovery_path(
+                app.compositor,
+                &app.comp_monitor_config_path,
+            );
+            if let Some(command) =
+                recovery::tty_command(app.compositor, &recovery)
+            {
+                lines.push(Line::from(""));
+                lines.push(line(
+                    t("Or load the recovery config from a TTY:").into(),
+                    Style::default().fg(Color::White),
+                ));
+                lines.push(line(
+                    format!("  {command}"),
+                    Style::default().fg(Color::Cyan),
+                ));
+            }
+            Dialog {
+                title: t("Warning").to_string(),
+                lines,
+                yes: t("Yes").to_string(),
+                no: t("No").to_string(),
+                color: Color::Red,
+            }
+        }
+        Confirm::ClearWorkspaces => {
+            let count = app
+                .workspace_assignments
+                .iter()
+                .filter(|ws| {
+                    !ws.is_pinned
+                        && (ws.monitor.is_some() || ws.reserved.is_some())
+                })
+                .count();
+            Dialog {
+                title: t("Clear workspaces").to_string(),
+                lines: vec![
+                    line(
+                        tf("⚠ Unassign {} workspaces?", &[&count]),
+                        Style::default()
+                            .fg(Color::Yellow)
+                            .add_modifier(Modifier::BOLD),
+                    ),
+                    Line::from(""),
+                    line(
+                        t("Pinned workspaces keep their monitor.").into(),
+                        dim,
+                    ),
+                    line(
+                        t("The compositor config is rewritten right away.")
+                            .into(),
+                        dim,
+                    ),
+                ],
+                yes: t("Yes, clear").to_string(),
+                no: t("No").to_string(),
+                color: Color::Yellow,
+            }
+        }
+        Confirm::RestoreSnapshot { snapshot, disables } => {
+            let mut lines = vec![
+                line(
+                    tf(
+                        "⚠ The layout from {} disables:",
+                        &[&snapshot.when(history::now())],
+                    ),
+                    Style::default()
+                        .fg(Color::Yellow)
+                        .add_modifier(Modifier::BOLD),
+                ),
+                Line::from(""),
+            ];
+            lines.extend(disables.iter().map(|name| {
+                line(format!("  {name}"), Style::default().fg(Color::Cyan))
+            }));
+            lines.push(Line::from(""));
+            lines.push(line(
+                t("Press t on a monitor to turn it back on.").into(),
+                dim,
+            ));
+            Dialog {
+                title: t("Restore layout").to_string(),
+                lines,
+                yes: t("Yes, restore").to_string(),
+                no: t("No").to_string(),
+                color: Color::Yellow,
+            }
+        }
+        Confirm::Quit => {
+            let warn = Style::default()
+                .fg(Color::Yellow)
+                .add_modifier(Modifier::BOLD);
+            let mut lines = match &app.save_state {
+                SaveState::ReloadFailed { reason } => vec![
+                    line(t("⚠ The last reload failed:").into(), warn),
+                    line(
+                        format!("  {reason}"),
+                        Style::default().fg(Color::Red),
+                    ),
+                    Line::from(""),
+                    line(
+                        t("The config is written but not in use.").into(),
+                        dim,
+                    ),
+                ],
+                state
+                    if *state != SaveState::Clean
+                        || app.has_unsaved_changes() =>
+                {
+                    vec![
+                        line(t("⚠ Not everything is saved yet.").into(), warn),
+                        Line::from(""),
+                        line(t("Staged changes are dropped.").into(), dim),
+                    ]
+                }
+                _ => Vec::new(),
+            };
+            // With `confirm_quit` the dialog also sums up the session.
+            if app.config.confirm_quit && app.has_session_changes() {
+                if !lines.is_empty() {
+                    lines.push(Line::from(""));
+                }
+                lines.push(line(
+                    t("Changed this session:").into(),
+                    Style::default().add_modifier(Modifier::BOLD),
+                ));
+                lines.extend(app.session_summary().into_iter().map(|l| {
+                    line(format!("  {l}"), Style::default().fg(Color::Cyan))
+                }));
+            }
+            Dialog {
+                title: t("Quit").to_string(),
+                lines,
+                yes: t("Yes, quit").to_string(),
+                no: t("Keep tweaking").to_string(),
+                color: Color::Yellow,
+            }
+        }
+    }
+}
+
+pub fn render_modal(frame: &mut Frame, dialog: Dialog, area: Rect) {
+    let text_w = dialog.lines.iter().map(Line::width).max().unwrap_or(0);
+    let modal_w = (text_w as u16 + 3)
+        .max(48)
+        .min(area.width.saturating_sub(4));
+    let modal_h = (dialog.lines.len() as u16 + 6)
+        .min(area.height.saturating_sub(2));
+    let x = (area.width.saturating_sub(modal_w)) / 2;
+    let y = (area.height.saturating_sub(modal_h)) / 2;
+    let modal_area = Rect::new(x, y, modal_w, modal_h);
+
+    frame.render_widget(Clear, modal_area);
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .border_style(Style::default().fg(dialog.color))
+        .title(format!(" {} ", dialog.title));
+
+    let inner = block.inner(modal_area);
+    frame.render_widget(block, modal_area);
+
+    let layout = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(1), Constraint::Length(3)])
+        .split(inner);
+
+    let width = inner.width as usize;
+    let text: Vec<Line> = dialog
+        .lines
+        .into_iter()
+        .map(|l| Line::from(fit(l.spans, width)))
+        .collect();
+
+    let yes = button('Y', &dialog.yes, dialog.color);
+    let no = button('N', &dialog.no, Color::Green);
+    let buttons: Vec<Line> = (0..3)
+        .map(|row| {
+            let mut spans = vec![Span::raw(" ")];
+            spans.extend(yes[row].clone());
+            spans.push(Span::raw(" "));
+            spans.extend(no[row].clone());
+            Line::from(fit(spans, width))
+        })
+        .collect();
+
+    let text_widget =
+        Paragraph::new(text).style(Style::default().fg(Color::White));
+    frame.render_widget(text_widget, layout[0]);
+
+    let buttons_widget =
+        Paragraph::new(buttons).style(Style::default().fg(Color::White));
+    frame.render_widget(buttons_widget, layout[1]);
+}
+
+/// A boxed button as three rows of spans. The key is bracketed inside the
+/// label when the label starts with it ("[Y]es"), otherwise in front of it
+/// ("[Y] Ja").
+fn button(key: char, label: &str, color: Color) -> [Vec<Span<'static>>; 3] {
+    let style = Style::default().fg(color);
+    let rest = match label.strip_prefix(|c: char| c.eq_ignore_ascii_case(&key))
+    {
+        Some(rest) => rest.to_string(),
+        None => format!(" {label}"),
+    };
+    let width = rest.chars().count() + 5;
+    let edge = "─".repeat(width);
+    [
+        vec![Span::styled(format!("┌{edge}┐"), style)],
+        vec![
+            Span::styled("│ ", style),
+            Span::styled(
+                format!("[{key}]"),
+                style.add_modifier(Modifier::BOLD),
+            ),
+            Span::styled(format!("{rest} │"), style),
+        ],
+        vec![Span::styled(format!("└{edge}┘"), style)],
+    ]
+}
@@ -0,0 +1,117 @@
+use crate::{
+    i18n::tf,
+    state::FallbackEditor,
+    tui::key_binds::{fit, hint},
+};
+
+use ratatui::{
+    Frame,
+    layout::Rect,
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, BorderType, Borders, Clear, List, ListItem},
+};
+
+pub fn render_modal(
+    frame: &mut Frame,
+    editor: &mut FallbackEditor,
+    symbol: &str,
+    area: Rect,
+) {
+    let rows = (editor.chosen.len() + editor.others.len()) as u16;
+    let modal_w = area.width.saturating_sub(8).clamp(20, 64).min(area.width);
+    let modal_h = (rows + 2).max(5).min(area.height.saturating_sub(2));
+    let x = (area.width.saturating_sub(modal_w)) / 2;
+    let y = (area.height.saturating_sub(modal_h)) / 2;
+    let modal_area = Rect::new(x, y, modal_w, modal_h);
+
+    frame.render_widget(Clear, modal_area);
+
+    let mut title = vec![Span::styled(
+        format!(" {} | ", tf("Monitors for WS {}", &[&editor.id])),
+        Style::default().fg(Color::Blue),
+    )];
+    hint(&mut title, "Space", "add/remove");
+    hint(&mut title, "K J", "move");
+    hint(&mut title, "Enter", "stage");
+    hint(&mut title, "Esc", "cancel");
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .border_style(Style::default().fg(Color::Blue))
+        .title(Line::from(fit(title, modal_w.saturating_sub(2) as usize)));
+
+    let inner = block.inner(modal_area);
+    frame.render_widget(block, modal_area);
+
+    let chosen = editor.chosen.len();
+    let items: Vec<ListItem> = editor
+        .entries()
+        .enumerate()
+        .map(|(i, name)| {
+            let line = if i < chosen {
+                Line::from(vec![
+                    Span::styled(
+                        format!("{}. ", i + 1),
+                        Style::default().fg(Color::Yellow),
+                    ),
+                    Span::styled(
+                        name.clone(),
+                        Style::default().fg(Color::White),
+                    ),
+                ])
+            } else {
+                Line::from(Span::styled(
+                    format!("   {name}"),
+                    Style::default().fg(Color::DarkGray),
+                ))
+            };
+            ListItem::new(line)
+        })
+        .collect();
+
+    let list = List::new(items).highlight_symbol(symbol).highlight_style(
+        Style::default()
+            .fg(Color::Cyan)
+            .add_modifier(Modifier::BOLD),
+    );
+    frame.render_stateful_widget(list, inner, &mut editor.state);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        compositor::Compositor,
+        test_support::{app, monitor, screen},
+    };
+
+    #[test]
+    fn test_chosen_monitors_are_numbered_before_the_others() {
+        let (mut app, _rx) = app();
+        app.compositor = Compositor::Sway;
+        app.set_monitors(vec![
+            monitor("DP-1", 0, 0),
+            monitor("DP-2", 1920, 0),
+            monitor("eDP-1", 3840, 0),
+        ]);
+        app.workspace_assignments[0].monitor = Some("DP-2".into());
+        app.workspace_assignments[0].fallbacks = vec!["eDP-1".into()];
+        app.open_fallback_editor();
+        let editor = app.fallback_editor.as_mut().unwrap();
+
+        let shown = screen(44, 7, |f| render_modal(f, editor, " › ", f.area()));
+        assert_eq!(
+            shown,
+            [
+                "                                            ",
+                "    ╭ Monitors for WS 1 | Space add/re…╮    ",
+                "    │ › 1. DP-2                        │    ",
+                "    │   2. eDP-1                       │    ",
+                "    │      DP-1                        │    ",
+                "    ╰──────────────────────────────────╯    ",
+                "                                            ",
+            ]
+        );
+    }
+}
@@ -0,0 +1,136 @@
+use crate::{
+    history,
+    i18n::t,
+    layout::Layout,
+    state::App,
+    tui::{
+        key_binds::{fit, hint},
+        panels::{
+            highlight_symbol,
+            left::{MapCache, MapRect},
+        },
+    },
+};
+
+use ratatui::{
+    Frame,
+    layout::{Constraint, Direction, Layout as Split, Rect},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{
+        Block, BorderType, Borders, Clear, List, ListItem, Paragraph,
+    },
+};
+use wlx_monitors::WlTransform;
+
+pub fn render_modal(frame: &mut Frame, app: &mut App, area: Rect) {
+    let symbol = highlight_symbol(app).to_string();
+    let Some(browser) = &mut app.history else {
+        return;
+    };
+
+    let modal_w = area.width.saturating_sub(8).max(20).min(area.width);
+    let modal_h = area.height.saturating_sub(4).max(10).min(area.height);
+    let x = (area.width.saturating_sub(modal_w)) / 2;
+    let y = (area.height.saturating_sub(modal_h)) / 2;
+    let modal_area = Rect::new(x, y, modal_w, modal_h);
+
+    frame.render_widget(Clear, modal_area);
+
+    let mut title = vec![Span::styled(
+        format!(" {} | ", t("Layout history")),
+        Style::default().fg(Color::Blue),
+    )];
+    hint(&mut title, "↑↓", "select");
+    hint(&mut title, "Enter", "restore");
+    hint(&mut title, "Esc", "close");
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .border_style(Style::default().fg(Color::Blue))
+        .title(Line::from(fit(title, modal_w.saturating_sub(2) as usize)));
+
+    let inner = block.inner(modal_area);
+    frame.render_widget(block, modal_area);
+
+    let split = Split::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(45), Constraint::Percentage(55)])
+        .split(inner);
+
+    let now = history::now();
+    let items: Vec<ListItem> = browser
+        .entries
+        .iter()
+        .map(|s| {
+            ListItem::new(Line::from(Span::styled(
+                s.summary(now),
+                Style::default().fg(Color::White),
+            )))
+        })
+        .collect();
+
+    let list = List::new(items)
+        .highlight_symbol(symbol)
+        .highlight_style(
+            Style::default()
+                .fg(Color::Cyan)
+                .add_modifier(Modifier::BOLD),
+        );
+    frame.render_stateful_widget(list, split[0], &mut browser.state);
+
+    let preview = Block::default()
+        .borders(Borders::LEFT)
+        .border_style(Style::default().fg(Color::DarkGray));
+    let preview_area = preview.inner(split[1]);
+    frame.render_widget(preview, split[1]);
+
+    let Some(snapshot) = browser.selected() else {
+        return;
+    };
+    let mut map = MapCache::default();
+    map.update(
+        map_rects(&snapshot.layout),
+        preview_area.width as usize,
+        preview_area.height as usize,
+        1.0,
+    );
+    frame.render_widget(Paragraph::new(map.lines()), preview_area);
+}
+
+fn map_rects(layout: &Layout) -> Vec<MapRect> {
+    let mut rects: Vec<MapRect> = layout
+        .monitors
+        .iter()
+        .map(|m| {
+            let (w, h) = match m.transform {
+                WlTransform::Rotate90
+                | WlTransform::Rotate270
+                | WlTransform::Flipped90
+                | WlTransform::Flipped270 => (m.height, m.width),
+                _ => (m.width, m.height),
+            };
+            MapRect {
+                name: m.name.clone(),
+                px: m.x,
+                py: m.y,
+                pw: w.max(1),
+                ph: h.max(1),
+                is_selected: false,
+                is_enabled: m.enabled,
+                is_powered: true,
+                is_pending: false,
+                is_managed: true,
+                res_label: format!("{}×{}", m.width, m.height),
+                pos_label: if m.enabled {
+                    format!("({},{})", m.x, m.y)
+                } else {
+                    t("OFF").to_string()
+                },
+                tag: None,
+            }
+        })
+        .collect();
+    rects.sort_by_key(|r| !r.is_enabled);
+    rects
+}
@@ -1,39 +1,90 @@
 use crate::{
-    constants::TRANSFORMS,
+    constants::MAP_FILL,
+    history,
+    i18n::{t, tf},
+    scale,
     state::{App, Panel},
-    tui::key_binds::{
-        get_monitor_keybinds, get_scale_keybinds, get_transform_keybinds,
+    tui::{
+        key_binds::{fit, get_monitor_keybinds, strike_unobservable},
+        panels::{Handled, PanelWidget, focus_marker, pending_marker},
     },
-    utils::{self, effective_dimensions, monitor_resolution, transform_label},
+    utils::{effective_dimensions, monitor_resolution},
 };
 
+use std::{
+    hash::{DefaultHasher, Hash, Hasher},
+    ops::Range,
+};
+
+use crossterm::event::{KeyCode, KeyEvent};
 use ratatui::{
     Frame,
     layout::{Constraint, Direction, Layout, Rect},
     style::{Color, Modifier, Style},
     text::{Line, Span},
-    widgets::{Block, BorderType, Borders, Clear, List, ListItem, Paragraph},
+    widgets::{Block, BorderType, Borders, Paragraph},
 };
-use wlx_monitors::WlTransform;
+use wlx_monitors::WlMonitor;
 
-pub fn panel(frame: &mut Frame, app: &mut App, area: Rect) {
+/// The left column: the map on top, then Scale, Transform and Options
+/// side by side.
+pub fn areas(area: Rect) -> [Rect; 4] {
     let left = Layout::default()
         .direction(Direction::Vertical)
         .constraints([Constraint::Min(8), Constraint::Length(10)])
         .split(area);
 
-    render_map(frame, app, left[0]);
-
     let bottom = Layout::default()
         .direction(Direction::Horizontal)
-        .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+        .constraints([
+            Constraint::Percentage(34),
+            Constraint::Percentage(33),
+            Constraint::Percentage(33),
+        ])
         .split(left[1]);
 
-    render_scale(frame, app, bottom[0]);
-    render_transform(frame, app, bottom[1]);
+    [left[0], bottom[0], bottom[1], bottom[2]]
+}
+
+/// The monitor layout map, which arranges and toggles monitors.
+#[derive(Default)]
+pub struct MapPanel {
+    cache: MapCache,
+}
+
+impl PanelWidget for MapPanel {
+    fn panel(&self) -> Panel {
+        Panel::Monitor
+    }
+
+    fn render(&mut self, frame: &mut Frame, area: Rect, app: &App) {
+        render_map(frame, app, &mut self.cache, area);
+    }
+
+    fn handle_key(&mut self, key: KeyEvent, app: &mut App) -> Handled {
+        match key.code {
+            KeyCode::Char('+') => app.zoom_in(),
+            KeyCode::Char('-') => app.zoom_out(),
+            KeyCode::Char('R') => app.rotate_layout(),
+            KeyCode::Char('a') => app.toggle_anchor(),
+            KeyCode::Char('o') => app.toggle_power(),
+            KeyCode::Char('O') => app.stage_only_selected(),
+            KeyCode::Char('A') => app.stage_enable_all(),
+            KeyCode::Char('g') => app.align_for_focus(),
+            KeyCode::Char('m') => app.remember_defaults(),
+            KeyCode::Char('c') => app.open_clone_picker(),
+            _ => return Handled::No,
+        }
+        Handled::Yes
+    }
 }
 
-fn render_map(frame: &mut Frame, app: &App, area: Rect) {
+fn render_map(
+    frame: &mut Frame,
+    app: &App,
+    cache: &mut MapCache,
+    area: Rect,
+) {
     let focused = app.panel == Panel::Monitor;
     let border_color = if focused {
         Color::Blue
@@ -41,17 +92,19 @@ fn render_map(frame: &mut Frame, app: &App, area: Rect) {
         Color::DarkGray
     };
 
+    let name = format!("{} — {}%", t("Monitor Layout"), app.zoom_percent());
     let title = if focused {
         let mut keys = Vec::new();
         keys.push(Span::styled(
-            " Monitor Layout | ",
+            format!(" {}{name} | ", focus_marker(app, true)),
             Style::default().fg(Color::Blue),
         ));
         get_monitor_keybinds(&mut keys);
-        Line::from(keys)
+        strike_unobservable(&mut keys[1..], app);
+        Line::from(fit(keys, area.width.saturating_sub(2) as usize))
     } else {
         Line::from(Span::styled(
-            " Monitor Layout ",
+            format!(" {name} "),
             Style::default().fg(Color::DarkGray),
         ))
     };
@@ -69,20 +122,26 @@ fn render_map(frame: &mut Frame, app: &App, area: Rect) {
         return;
     }
 
-    let grid_height = inner.height.saturating_sub(1) as usize;
+    let focus_gap = app.selected_focus_gap();
+    let detail_rows = 1 + focus_gap.is_some() as u16;
+    let grid_height = inner.height.saturating_sub(detail_rows) as usize;
     let grid_width = inner.width as usize;
 
-    let mut lines = build_layout_map(app, grid_width, grid_height);
+    let mut lines = build_layout_map(app, cache, grid_width, grid_height);
 
     while lines.len() < grid_height {
         lines.push(Line::from(""));
     }
 
     if let Some(monitor) = app.selected_monitor() {
-        let (ew, eh) = utils::effective_dimensions(monitor);
+        let (ew, eh) = app.display_dimensions(app.selected_monitor);
+        let staged = app.pending_toggles.contains_key(&monitor.name);
         if monitor.enabled {
             let (dx, dy) = app.display_position(app.selected_monitor);
-            let has_pending = app.has_pending_positions();
+            let has_pending = app.has_pending_positions()
+                || app.has_pending_transforms()
+                || app.has_pending_settings()
+                || app.has_pending_toggles();
             let pos_color = if has_pending {
                 Color::Yellow
             } else {
@@ -101,27 +160,106 @@ fn render_map(frame: &mut Frame, app: &App, area: Rect) {
                     Style::default().fg(Color::White),
                 ),
                 Span::styled(
-                    format!("({},{})  ", dx, dy),
+                    format!(
+                        "({},{}){}  ",
+                        dx,
+                        dy,
+                        if has_pending { pending_marker(app) } else { "" }
+                    ),
                     Style::default().fg(pos_color),
                 ),
                 Span::styled(
                     format!("{}×  ", monitor.scale),
                     Style::default().fg(Color::White),
                 ),
-                Span::styled(
-                    "ON",
-                    Style::default()
-                        .fg(Color::Green)
-                        .add_modifier(Modifier::BOLD),
-                ),
             ];
+            if let Some(dpi) = app.monitor_dpi(app.selected_monitor) {
+                spans.push(Span::styled(
+                    format!("{dpi:.0} dpi  "),
+                    Style::default().fg(Color::DarkGray),
+                ));
+            }
+            let (state, state_fg) = if staged {
+                (t("ON → OFF"), Color::Yellow)
+            } else {
+                (t("ON"), Color::Green)
+            };
+            spans.push(Span::styled(
+                state,
+                Style::default().fg(state_fg).add_modifier(Modifier::BOLD),
+            ));
+            if !app.is_powered(&monitor.name) {
+                spans.push(Span::styled(
+                    format!("  {}", t("power: off")),
+                    Style::default().fg(Color::DarkGray),
+                ));
+            }
+            if let Some(summary) =
+                app.timeline.summary(&monitor.name, history::now())
+            {
+                spans.push(Span::styled(
+                    format!("  {summary}"),
+                    Style::default().fg(Color::Yellow),
+                ));
+            }
+            if app.is_anchored(&monitor.name) {
+                spans.push(Span::styled(
+                    format!("  {}", t("anchored")),
+                    Style::default().fg(Color::Magenta),
+                ));
+            }
+            if !app.is_managed(&monitor.name) {
+                spans.push(Span::styled(
+                    format!("  {UNMANAGED} {}", t("unmanaged")),
+                    Style::default().fg(Color::Magenta),
+                ));
+            }
+            if monitor.modes.is_empty() {
+                spans.push(Span::styled(
+                    format!("  {}", t("⚠ no modes")),
+                    Style::default().fg(Color::Yellow),
+                ));
+            }
+            let resolution = monitor_resolution(monitor);
+            if scale::lint(resolution, monitor.transform, monitor.scale)
+                .is_some()
+            {
+                spans.push(Span::styled(
+                    format!("  {}", t("⚠ fractional")),
+                    Style::default().fg(Color::Yellow),
+                ));
+            }
             if has_pending {
                 spans.push(Span::styled(
-                    "  Enter to apply",
+                    format!("  {}", t("Enter to apply")),
                     Style::default().fg(Color::Yellow),
                 ));
             }
-            lines.push(Line::from(spans));
+            lines.push(Line::from(fit(spans, inner.width as usize)));
+            if let Some(gap) = focus_gap {
+                let warning = Span::styled(
+                    format!(
+                        "  {}",
+                        tf(
+                            "⚠ focus {} misses {}, {} px off",
+                            &[
+                                &t(gap.direction.label()),
+                                &gap.toward,
+                                &gap.offset,
+                            ],
+                        )
+                    ),
+                    Style::default().fg(Color::Yellow),
+                );
+                let key = Span::styled(
+                    format!("  g {}", t("aligns")),
+                    Style::default().fg(Color::Cyan),
+                );
+                lines.push(Line::from(fit(
+                    vec![warning, key],
+                    inner.width as usize,
+                )));
+            }
         } else {
             lines.push(Line::from(vec![
                 Span::styled("  ○ ", Style::default().fg(Color::Red)),
@@ -135,104 +273,287 @@ fn render_map(frame: &mut Frame, app: &App, area: Rect) {
                     format!("{}×{}  ", ew, eh),
                     Style::default().fg(Color::DarkGray),
                 ),
-                Span::styled(
-                    "OFF ",
-                    Style::default()
-                        .fg(Color::Red)
-                        .add_modifier(Modifier::BOLD),
-                ),
-                Span::styled(
-                    "— t to enable",
-                    Style::default().fg(Color::DarkGray),
-                ),
+                if staged {
+                    Span::styled(
+                        format!("{} ", t("OFF → ON")),
+                        Style::default()
+                            .fg(Color::Yellow)
+                            .add_modifier(Modifier::BOLD),
+                    )
+                } else {
+                    Span::styled(
+                        format!("{} ", t("OFF")),
+                        Style::default()
+                            .fg(Color::Red)
+                            .add_modifier(Modifier::BOLD),
+                    )
+                },
+                if staged {
+                    Span::styled(
+                        format!("— {}", t("Enter to apply")),
+                        Style::default().fg(Color::Yellow),
+                    )
+                } else {
+                    Span::styled(
+                        t("— t to enable"),
+                        Style::default().fg(Color::DarkGray),
+                    )
+                },
             ]));
         }
     } else {
-        lines.push(Line::from("  No monitor selected"));
+        lines.push(Line::from(format!("  {}", t("No monitor selected"))));
     }
 
     frame.render_widget(Paragraph::new(lines), inner);
 }
 
+/// A monitor as drawn on the layout map. Disabled monitors are placed in
+/// a row under the enabled ones, so only their size is used.
+#[derive(Hash)]
+pub struct MapRect {
+    pub name: String,
+    pub px: i32,
+    pub py: i32,
+    pub pw: i32,
+    pub ph: i32,
+    pub is_selected: bool,
+    pub is_enabled: bool,
+    pub is_powered: bool,
+    /// Has a staged enable/disable toggle, or a mode or scale staged by
+    /// `c`.
+    pub is_pending: bool,
+    /// Shown with [`UNMANAGED`] before its name.
+    pub is_managed: bool,
+    pub res_label: String,
+    pub pos_label: String,
+    /// Written into the top border, e.g. "SELECTED" in high contrast mode.
+    pub tag: Option<String>,
+}
+
+/// Marks monitors xwlm leaves alone, on the map and in the info line.
+const UNMANAGED: char = '⊘';
+
+type Cell = (char, Color, bool);
+
+const BLANK: Cell = (' ', Color::Reset, false);
+
+/// The last painted map. Frames where nothing moved reuse it as is, and a
+/// repaint reuses the cell grid, text and run buffers instead of
+/// allocating new ones every 50ms.
+#[derive(Default)]
+pub struct MapCache {
+    key: Option<u64>,
+    message: Option<&'static str>,
+    grid: Vec<Cell>,
+    text: String,
+    rows: Vec<Vec<(Range<usize>, Style)>>,
+}
+
+impl MapCache {
+    pub fn update(
+        &mut self,
+        rects: Vec<MapRect>,
+        width: usize,
+        height: usize,
+        zoom: f64,
+    ) {
+        let mut hasher = DefaultHasher::new();
+        (&rects, width, height, zoom.to_bits()).hash(&mut hasher);
+        let key = hasher.finish();
+        if self.key == Some(key) {
+            return;
+        }
+        self.key = Some(key);
+
+        self.message = None;
+        self.rows.clear();
+        if rects.is_empty() {
+            self.message = Some("No monitors");
+        } else if width < 5 || height < 3 {
+            self.message = Some("Panel too small");
+        } else if paint(&mut self.grid, rects, width, height, zoom) {
+            self.collect_runs(width, height);
+        }
+    }
+
+    pub fn lines(&self) -> Vec<Line<'_>> {
+        if let Some(message) = self.message {
+            return vec![Line::from(format!("  {}", t(message)))];
+        }
+        self.rows
+            .iter()
+            .map(|runs| {
+                Line::from(
+                    runs.iter()
+                        .map(|(range, style)| {
+                            Span::styled(&self.text[range.clone()], *style)
+                        })
+                        .collect::<Vec<_>>(),
+                )
+            })
+            .collect()
+    }
+
+    /// Splits each grid row into same-style runs over one shared string.
+    fn collect_runs(&mut self, width: usize, height: usize) {
+        self.text.clear();
+        self.rows.resize_with(height, Vec::new);
+        for (y, runs) in self.rows.iter_mut().enumerate() {
+            runs.clear();
+            let row = &self.grid[y * width..(y + 1) * width];
+            let mut i = 0;
+            while i < width {
+                let (_, color, bold) = row[i];
+                let start = self.text.len();
+                let mut j = i;
+                while j < width && row[j].1 == color && row[j].2 == bold {
+                    self.text.push(row[j].0);
+                    j += 1;
+                }
+                let mut style = Style::default().fg(color);
+                if bold {
+                    style = style.add_modifier(Modifier::BOLD);
+                }
+                runs.push((start..self.text.len(), style));
+                i = j;
+            }
+        }
+    }
+}
+
 fn build_layout_map<'a>(
     app: &App,
+    cache: &'a mut MapCache,
     width: usize,
     height: usize,
 ) -> Vec<Line<'a>> {
     let monitors = &app.monitors;
     let selected_idx = app.selected_monitor;
-    let zoom = app.map_zoom;
-
-    if monitors.is_empty() {
-        return vec![Line::from("  No monitors")];
-    }
-    if width < 5 || height < 3 {
-        return vec![Line::from("  Panel too small")];
-    }
-
-    struct MonRect {
-        name: String,
-        px: i32,
-        py: i32,
-        pw: i32,
-        ph: i32,
-        is_selected: bool,
-        is_enabled: bool,
-        res_label: String,
-        pos_label: String,
-    }
+    let tag = |idx| {
+        (app.config.high_contrast && idx == selected_idx)
+            .then(|| t("SELECTED").to_string())
+    };
 
-    let mut monitor_rects: Vec<MonRect> = Vec::new();
+    let mut monitor_rects: Vec<MapRect> = Vec::new();
     for (idx, m) in monitors.iter().enumerate() {
-        if !m.enabled {
+        if !app.will_be_enabled(idx) {
             continue;
         }
-        let (w, h) = effective_dimensions(m);
-        let (rw, rh) = monitor_resolution(m);
-        let (px, py) = app.display_position(idx);
-        monitor_rects.push(MonRect {
-            name: m.name.clone(),
+        let (w, h) = app.display_dimensions(idx);
+        let copied = app.pending_modes.get(&m.name);
+        let (rw, rh) = copied
+            .map(|mode| (mode.width, mode.height))
+            .unwrap_or_else(|| monitor_resolution(m));
+        let settings_staged = copied.is_some()
+            || app.pending_scales.contains_key(&m.name)
+            || app.has_scale_edit(&m.name);
+        let staged = app.pending_toggles.get(&m.name).copied().flatten();
+        let (px, py) = staged.unwrap_or_else(|| app.display_position(idx));
+        let is_pending = !m.enabled || settings_staged;
+        let marker = if is_pending { pending_marker(app) } else { "" };
+        monitor_rects.push(MapRect {
+            name: format!("{}{marker}", m.name),
             px,
             py,
             pw: w.max(1),
             ph: h.max(1),
             is_selected: idx == selected_idx,
             is_enabled: true,
-            res_label: format!("{}×{}", rw, rh),
+            is_powered: app.is_powered(&m.name),
+            is_pending,
+            is_managed: app.is_managed(&m.name),
+            res_label: res_label(m, rw, rh),
             pos_label: format!("({},{})", px, py),
+            tag: tag(idx),
         });
     }
 
-    let bottom_y = if monitor_rects.is_empty() {
-        0
-    } else {
-        monitor_rects.iter().map(|r| r.py + r.ph).max().unwrap_or(0)
-    };
-    let disabled_y = bottom_y + 200;
-    let mut disabled_x = monitor_rects.iter().map(|r| r.px).min().unwrap_or(0);
-
-    for (idx, m) in monitors.iter().enumerate() {
-        if m.enabled {
-            continue;
-        }
+    // Parked in the order they sat in before they were switched off.
+    let mut parked: Vec<usize> = (0..monitors.len())
+        .filter(|&idx| !app.will_be_enabled(idx))
+        .collect();
+    parked.sort_by_key(|&idx| {
+        app.parked(&monitors[idx].name).map_or(i32::MAX, |kept| kept.x)
+    });
+    for idx in parked {
+        let m = &monitors[idx];
         let (w, h) = effective_dimensions(m);
         let (rw, rh) = monitor_resolution(m);
-        let pw = w.max(1);
-        let ph = h.max(1);
-        monitor_rects.push(MonRect {
-            name: m.name.clone(),
-            px: disabled_x,
-            py: disabled_y,
-            pw,
-            ph,
+        let marker = if m.enabled { pending_marker(app) } else { "" };
+        monitor_rects.push(MapRect {
+            name: format!("{}{marker}", m.name),
+            px: 0,
+            py: 0,
+            pw: w.max(1),
+            ph: h.max(1),
             is_selected: idx == selected_idx,
             is_enabled: false,
-            res_label: format!("{}×{}", rw, rh),
+            is_powered: false,
+            is_pending: m.enabled,
+            is_managed: app.is_managed(&m.name),
+            res_label: res_label(m, rw, rh),
             pos_label: "OFF".to_string(),
+            tag: tag(idx),
         });
-        disabled_x += pw + 100;
     }
 
+    cache.update(monitor_rects, width, height, app.map_zoom);
+    cache.lines()
+}
+
+/// `1920×1080`, with a warning for a monitor that reports no modes and
+/// so has only a guessed size.
+fn res_label(monitor: &WlMonitor, width: i32, height: i32) -> String {
+    if monitor.modes.is_empty() {
+        format!("⚠ {width}×{height}")
+    } else {
+        format!("{width}×{height}")
+    }
+}
+
+/// Parks disabled monitors in rows under the layout, wrapping at the
+/// layout's width (or three monitors when that is narrower).
+fn park_disabled(monitor_rects: &mut [MapRect]) {
+    let enabled = || monitor_rects.iter().filter(|r| r.is_enabled);
+    let bottom_y = enabled().map(|r| r.py + r.ph).max().unwrap_or(0);
+    let left_x = enabled().map(|r| r.px).min().unwrap_or(0);
+    let right_x = enabled().map(|r| r.px + r.pw).max().unwrap_or(left_x);
+    let widest = monitor_rects
+        .iter()
+        .filter(|r| !r.is_enabled)
+        .map(|r| r.pw + 100)
+        .max()
+        .unwrap_or(0);
+    let row_limit = left_x + (right_x - left_x).max(widest * 3);
+
+    let mut disabled_x = left_x;
+    let mut disabled_y = bottom_y + 200;
+    let mut row_height = 0;
+    for rect in monitor_rects.iter_mut().filter(|r| !r.is_enabled) {
+        if disabled_x > left_x && disabled_x + rect.pw > row_limit {
+            disabled_x = left_x;
+            disabled_y += row_height + 100;
+            row_height = 0;
+        }
+        rect.px = disabled_x;
+        rect.py = disabled_y;
+        disabled_x += rect.pw + 100;
+        row_height = row_height.max(rect.ph);
+    }
+}
+
+/// Paints the rects into `grid`, a row-major `width * height` buffer.
+/// Returns false when the layout has no area to draw.
+fn paint(
+    grid: &mut Vec<Cell>,
+    mut monitor_rects: Vec<MapRect>,
+    width: usize,
+    height: usize,
+    zoom: f64,
+) -> bool {
+    park_disabled(&mut monitor_rects);
+
     let min_x = monitor_rects.iter().map(|r| r.px).min().unwrap_or(0);
     let min_y = monitor_rects.iter().map(|r| r.py).min().unwrap_or(0);
     let max_x = monitor_rects.iter().map(|r| r.px + r.pw).max().unwrap_or(0);
@@ -242,7 +563,7 @@ fn build_layout_map<'a>(
     let total_h = (max_y - min_y) as f64;
 
     if total_w <= 0.0 || total_h <= 0.0 {
-        return vec![];
+        return false;
     }
 
     const CHAR_ASPECT: f64 = 2.0;
@@ -251,16 +572,17 @@ fn build_layout_map<'a>(
     let avail_w = width.saturating_sub(pad * 2) as f64;
     let avail_h = height.saturating_sub(1) as f64;
 
-    let ppc_x = total_w / (avail_w * 0.8);
-    let ppc_y = total_h / (avail_h * CHAR_ASPECT * 0.8);
+    let ppc_x = total_w / (avail_w * MAP_FILL);
+    let ppc_y = total_h / (avail_h * CHAR_ASPECT * MAP_FILL);
     let ppc = ppc_x.max(ppc_y) / zoom;
 
     if ppc <= 0.0 {
-        return vec![];
+        return false;
     }
 
-    let mut grid: Vec<Vec<(char, Color, bool)>> =
-        vec![vec![(' ', Color::Reset, false); width]; height];
+    grid.clear();
+    grid.resize(width * height, BLANK);
+    let at = |x: usize, y: usize| y * width + x;
 
     for rect in &monitor_rects {
         let cx = pad + ((rect.px - min_x) as f64 / ppc) as usize;
@@ -286,25 +608,29 @@ fn build_layout_map<'a>(
                 } else {
                     Color::DarkGray
                 };
-                grid[y1][x1] = (ch, fg, rect.is_selected);
+                grid[at(x1, y1)] = (ch, fg, rect.is_selected);
             }
             continue;
         }
 
-        let border_fg = if rect.is_selected && rect.is_enabled {
+        let border_fg = if rect.is_pending {
+            Color::Yellow
+        } else if rect.is_selected && rect.is_enabled {
             Color::Cyan
         } else if rect.is_selected {
             Color::Yellow
-        } else if rect.is_enabled {
+        } else if rect.is_enabled && rect.is_powered {
             Color::DarkGray
         } else {
             Color::Rgb(60, 60, 60)
         };
-        let text_fg = if rect.is_selected && rect.is_enabled {
-            Color::White
+        let text_fg = if rect.is_pending {
+            Color::Yellow
+        } else if rect.is_selected && rect.is_enabled {
+            if rect.is_powered { Color::White } else { Color::DarkGray }
         } else if rect.is_selected {
             Color::Yellow
-        } else if rect.is_enabled {
+        } else if rect.is_enabled && rect.is_powered {
             Color::Gray
         } else {
             Color::Rgb(80, 80, 80)
@@ -318,25 +644,31 @@ fn build_layout_map<'a>(
             ('┌', '┐', '└', '┘', '╌', '╎')
         };
 
-        grid[y1][x1] = (tl, border_fg, false);
-        grid[y1][x2 - 1] = (tr, border_fg, false);
-        grid[y2 - 1][x1] = (bl, border_fg, false);
-        grid[y2 - 1][x2 - 1] = (br, border_fg, false);
+        grid[at(x1, y1)] = (tl, border_fg, false);
+        grid[at(x2 - 1, y1)] = (tr, border_fg, false);
+        grid[at(x1, y2 - 1)] = (bl, border_fg, false);
+        grid[at(x2 - 1, y2 - 1)] = (br, border_fg, false);
 
-        for cell in grid[y1][(x1 + 1)..(x2 - 1)].iter_mut() {
-            *cell = (hc, border_fg, false);
-        }
-        for cell in grid[y2 - 1][(x1 + 1)..(x2 - 1)].iter_mut() {
-            *cell = (hc, border_fg, false);
+        for y in [y1, y2 - 1] {
+            for cell in &mut grid[at(x1 + 1, y)..at(x2 - 1, y)] {
+                *cell = (hc, border_fg, false);
+            }
         }
-
-        for row in grid[(y1 + 1)..(y2 - 1)].iter_mut() {
-            row[x1] = (vc, border_fg, false);
-            row[x2 - 1] = (vc, border_fg, false);
+        if let Some(tag) = &rect.tag {
+            let tag = format!(" {tag} ");
+            let len = tag.chars().count();
+            if len + 2 <= w {
+                let start = x1 + (w - len) / 2;
+                for (j, ch) in tag.chars().enumerate() {
+                    grid[at(start + j, y1)] = (ch, border_fg, true);
+                }
+            }
         }
 
-        for row in grid[(y1 + 1)..(y2 - 1)].iter_mut() {
-            for cell in row[(x1 + 1)..(x2 - 1)].iter_mut() {
+        for y in (y1 + 1)..(y2 - 1) {
+            grid[at(x1, y)] = (vc, border_fg, false);
+            grid[at(x2 - 1, y)] = (vc, border_fg, false);
+            for cell in &mut grid[at(x1 + 1, y)..at(x2 - 1, y)] {
                 *cell = (' ', text_fg, false);
             }
         }
@@ -345,8 +677,13 @@ fn build_layout_map<'a>(
         let inner_h = h.saturating_sub(2);
 
         if inner_w >= 1 && inner_h >= 1 {
-            let text_lines: Vec<(&str, bool)> = vec![
-                (&rect.name, true),
+            let name = if rect.is_managed {
+                rect.name.clone()
+            } else {
+                format!("{UNMANAGED} {}", rect.name)
+            };
+            let text_lines: [(&str, bool); 3] = [
+                (&name, true),
                 (&rect.res_label, false),
                 (&rect.pos_label, false),
             ];
@@ -358,13 +695,12 @@ fn build_layout_map<'a>(
                 if row >= y2 - 1 {
                     break;
                 }
-                let truncated: String = text.chars().take(inner_w).collect();
-                let text_start =
-                    x1 + 1 + inner_w.saturating_sub(truncated.len()) / 2;
-                for (j, ch) in truncated.chars().enumerate() {
+                let len = text.chars().take(inner_w).count();
+                let text_start = x1 + 1 + inner_w.saturating_sub(len) / 2;
+                for (j, ch) in text.chars().take(inner_w).enumerate() {
                     let col = text_start + j;
                     if col < x2 - 1 {
-                        grid[row][col] =
+                        grid[at(col, row)] =
                             (ch, text_fg, *bold || rect.is_selected);
                     }
                 }
@@ -372,275 +708,175 @@ fn build_layout_map<'a>(
         }
     }
 
-    let mut lines = Vec::new();
-    for row in &grid {
-        let mut spans = Vec::new();
-        let mut i = 0;
-        while i < width {
-            let (ch, color, bold) = row[i];
-            let mut run = String::new();
-            run.push(ch);
-            let mut j = i + 1;
-            while j < width && row[j].1 == color && row[j].2 == bold {
-                run.push(row[j].0);
-                j += 1;
-            }
-            let mut style = Style::default().fg(color);
-            if bold {
-                style = style.add_modifier(Modifier::BOLD);
-            }
-            spans.push(Span::styled(run, style));
-            i = j;
-        }
-        lines.push(Line::from(spans));
-    }
-
-    lines
-}
-
-fn render_scale(frame: &mut Frame, app: &App, area: Rect) {
-    let focused = app.panel == Panel::Scale;
-    let border_color = if focused {
-        Color::Blue
-    } else {
-        Color::DarkGray
-    };
-
-    let title = if focused {
-        let mut keys = Vec::new();
-        keys.push(Span::styled(" Scale | ", Style::default().fg(Color::Blue)));
-        get_scale_keybinds(&mut keys);
-        Line::from(keys)
-    } else {
-        Line::from(Span::styled(
-            " Scale ",
-            Style::default().fg(Color::DarkGray),
-        ))
-    };
-
-    let monitor = app.selected_monitor();
-    let current = monitor.map(|m| m.scale).unwrap_or(1.0);
-    let pending = app.pending_scale;
-    let changed = (current - pending).abs() > 0.001;
-
-    let bar_width = (area.width as usize).saturating_sub(6);
-    let max_scale = 10.0_f64;
-    let fill = ((pending / max_scale) * bar_width as f64)
-        .round()
-        .min(bar_width as f64) as usize;
-    let empty = bar_width.saturating_sub(fill);
-    let filled_part = "━".repeat(fill.saturating_sub(1));
-    let empty_part = "─".repeat(empty);
-
-    let pending_color = if changed { Color::Yellow } else { Color::White };
-
-    let lines = vec![
-        Line::from(""),
-        Line::from(vec![
-            Span::styled("  current ", Style::default().fg(Color::DarkGray)),
-            Span::styled(
-                format!("{:.2}x", current),
-                Style::default().fg(Color::White),
-            ),
-        ]),
-        Line::from(vec![
-            Span::styled("  pending ", Style::default().fg(Color::DarkGray)),
-            Span::styled(
-                format!("{:.2}x", pending),
-                Style::default().fg(pending_color),
-            ),
-        ]),
-        Line::from(""),
-        Line::from(vec![
-            Span::styled(
-                format!("  {}", filled_part),
-                Style::default().fg(Color::Cyan),
-            ),
-            Span::styled("●", Style::default().fg(Color::White)),
-            Span::styled(empty_part, Style::default().fg(Color::DarkGray)),
-        ]),
-        Line::from(""),
-        if changed {
-            Line::from(vec![Span::styled(
-                "  Enter to apply",
-                Style::default().fg(Color::Yellow),
-            )])
-        } else {
-            Line::from(vec![Span::styled(
-                "  ↑↓ or +/- adjust",
-                Style::default().fg(Color::DarkGray),
-            )])
-        },
-    ];
-
-    let block = Block::default()
-        .borders(Borders::ALL)
-        .border_type(BorderType::Rounded)
-        .border_style(Style::default().fg(border_color))
-        .title(title);
-
-    frame.render_widget(Paragraph::new(lines).block(block), area);
+    true
 }
 
-fn render_transform(frame: &mut Frame, app: &mut App, area: Rect) {
-    let focused = app.panel == Panel::Transform;
-    let border_color = if focused {
-        Color::Blue
-    } else {
-        Color::DarkGray
-    };
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{app, monitor, screen};
+    use std::time::Instant;
+
+    fn rects(selected: usize) -> Vec<MapRect> {
+        [("DP-1", 0, 0), ("DP-2", 2560, 0), ("HDMI-A-1", 0, 1440)]
+            .into_iter()
+            .enumerate()
+            .map(|(i, (name, px, py))| MapRect {
+                name: name.to_string(),
+                px,
+                py,
+                pw: 2560,
+                ph: 1440,
+                is_selected: i == selected,
+                is_enabled: true,
+                is_powered: true,
+                is_pending: false,
+                is_managed: true,
+                res_label: "2560×1440".to_string(),
+                pos_label: format!("({px},{py})"),
+                tag: None,
+            })
+            .collect()
+    }
 
-    let title = if focused {
-        let mut keys: Vec<Span> = Vec::new();
-        keys.push(Span::styled(
-            " Transform | ",
-            Style::default().fg(Color::Blue),
-        ));
-        get_transform_keybinds(&mut keys);
-        Line::from(keys)
-    } else {
-        Line::from(Span::styled(
-            " Transform ",
-            Style::default().fg(Color::DarkGray),
-        ))
-    };
+    fn fresh(selected: usize, width: usize, height: usize) -> MapCache {
+        let mut cache = MapCache::default();
+        cache.update(rects(selected), width, height, 1.0);
+        cache
+    }
 
-    let current_transform = app
-        .selected_monitor()
-        .map(|m| m.transform)
-        .unwrap_or(WlTransform::Normal);
+    #[test]
+    fn test_cached_map_matches_fresh_render() {
+        let mut cache = MapCache::default();
+        cache.update(rects(0), 120, 40, 1.0);
+        cache.update(rects(0), 120, 40, 1.0);
+        assert_eq!(cache.lines(), fresh(0, 120, 40).lines());
+
+        // A repaint into the reused buffers, including a smaller panel.
+        cache.update(rects(1), 120, 40, 1.0);
+        assert_eq!(cache.lines(), fresh(1, 120, 40).lines());
+        cache.update(rects(1), 60, 20, 1.0);
+        assert_eq!(cache.lines(), fresh(1, 60, 20).lines());
+        assert_eq!(cache.lines().len(), 20);
+    }
 
-    let items: Vec<ListItem> = TRANSFORMS
-        .iter()
-        .map(|&t| {
-            let is_current = t == current_transform;
-            let marker = if is_current { " ✓" } else { "" };
-            let style = if is_current {
-                Style::default().fg(Color::Cyan)
-            } else {
-                Style::default().fg(Color::White)
-            };
+    #[test]
+    fn test_labels_center_by_characters() {
+        let text = |cache: &MapCache| -> Vec<String> {
+            cache.lines().iter().map(ToString::to_string).collect()
+        };
+        for width in 60..66 {
+            let mut ascii = rects(0);
+            for rect in &mut ascii {
+                rect.res_label = "2560x1440".to_string();
+            }
+            let mut cache = MapCache::default();
+            cache.update(ascii, width, 20, 1.0);
+            let ascii = text(&cache);
+            let wide = text(&fresh(0, width, 20));
+            let wide: Vec<String> =
+                wide.iter().map(|l| l.replace('×', "x")).collect();
+            assert_eq!(wide, ascii, "width {width}");
+        }
+    }
 
-            Line::from(vec![
-                Span::styled(format!("  {}", transform_label(t)), style),
-                Span::styled(marker, Style::default().fg(Color::Green)),
-            ])
-            .into()
-        })
-        .collect();
+    #[test]
+    fn test_map_messages() {
+        let mut cache = MapCache::default();
+        cache.update(Vec::new(), 120, 40, 1.0);
+        assert_eq!(cache.lines(), vec![Line::from("  No monitors")]);
+        cache.update(rects(0), 4, 40, 1.0);
+        assert_eq!(cache.lines(), vec![Line::from("  Panel too small")]);
+    }
 
-    let block = Block::default()
-        .borders(Borders::ALL)
-        .border_type(BorderType::Rounded)
-        .border_style(Style::default().fg(border_color))
-        .title(title);
+    #[test]
+    fn test_disabled_monitors_wrap_under_the_layout() {
+        let mut all = rects(0);
+        for i in 0..9 {
+            all.push(MapRect {
+                is_enabled: false,
+                name: format!("HDMI-A-{i}"),
+                ..rects(0).remove(1)
+            });
+        }
+        park_disabled(&mut all);
+
+        let right = all.iter().map(|r| r.px + r.pw).max().unwrap();
+        assert!(right <= 3 * (2560 + 100));
+        for (i, a) in all.iter().enumerate() {
+            for b in &all[i + 1..] {
+                let apart = a.px + a.pw <= b.px
+                    || b.px + b.pw <= a.px
+                    || a.py + a.ph <= b.py
+                    || b.py + b.ph <= a.py;
+                assert!(apart, "{} overlaps {}", a.name, b.name);
+            }
+        }
+    }
 
-    let list = List::new(items)
-        .block(block)
-        .highlight_symbol(" › ")
-        .highlight_style(
-            Style::default()
-                .fg(Color::Cyan)
-                .add_modifier(Modifier::BOLD),
+    #[test]
+    fn test_map_panel_snapshot() {
+        let (mut app, _rx) = app();
+        app.set_monitors(vec![monitor("DP-1", 0, 0), monitor("DP-2", 1920, 0)]);
+        let mut panel = MapPanel::default();
+        let shown = screen(60, 12, |f| panel.render(f, f.area(), &app));
+        assert_eq!(
+            shown,
+            [
+                "╭ Monitor Layout — 100% | ↑↓ ←→ move  +/- zoom  [] switch…─╮",
+                "│  ╔════════════════════┌────────────────────┐             │",
+                "│  ║        DP-1        │        DP-2        │             │",
+                "│  ║     1920×1080      │     1920×1080      │             │",
+                "│  ║       (0,0)        │      (1920,0)      │             │",
+                "│  ║                    │                    │             │",
+                "│  ╚════════════════════└────────────────────┘             │",
+                "│                                                          │",
+                "│                                                          │",
+                "│                                                          │",
+                "│  ○ DP-1  1920×1080  (0,0)  1×  ON                        │",
+                "╰──────────────────────────────────────────────────────────╯",
+            ]
         );
+    }
 
-    frame.render_stateful_widget(list, area, &mut app.transform_state);
-}
-
-pub fn render_warning_modal(frame: &mut Frame, area: Rect, config_path: &str) {
-    let path_w = config_path.len() as u16 + 14;
-    let modal_w = path_w.max(48).min(area.width.saturating_sub(4));
-    let modal_h = 15u16.min(area.height.saturating_sub(2));
-    let x = (area.width.saturating_sub(modal_w)) / 2;
-    let y = (area.height.saturating_sub(modal_h)) / 2;
-    let modal_area = Rect::new(x, y, modal_w, modal_h);
-
-    frame.render_widget(Clear, modal_area);
+    #[test]
+    fn test_high_contrast_marks_focus_and_selection_in_text() {
+        let (mut app, _rx) = app();
+        app.config.high_contrast = true;
+        app.set_monitors(vec![monitor("DP-1", 0, 0), monitor("DP-2", 1920, 0)]);
+        let mut panel = MapPanel::default();
+        let shown = screen(60, 12, |f| panel.render(f, f.area(), &app));
+        assert!(shown[0].starts_with("╭ ▶ Monitor Layout"), "{}", shown[0]);
+        assert!(shown[1].contains(" SELECTED "), "{}", shown[1]);
+        assert_eq!(shown[1].matches("SELECTED").count(), 1);
+    }
 
-    let block = Block::default()
-        .borders(Borders::ALL)
-        .border_type(BorderType::Rounded)
-        .border_style(Style::default().fg(Color::Red))
-        .title(" Warning ");
+    /// `cargo test --release bench_map -- --ignored --nocapture`
+    #[test]
+    #[ignore]
+    fn bench_map_cache() {
+        const FRAMES: u32 = 2000;
+        let (width, height) = (300, 80);
+
+        let start = Instant::now();
+        for _ in 0..FRAMES {
+            let cache = fresh(0, width, height);
+            std::hint::black_box(cache.lines());
+        }
+        let uncached = start.elapsed();
 
-    let inner = block.inner(modal_area);
-    frame.render_widget(block, modal_area);
+        let mut cache = MapCache::default();
+        let start = Instant::now();
+        for _ in 0..FRAMES {
+            cache.update(rects(0), width, height, 1.0);
+            std::hint::black_box(cache.lines());
+        }
+        let cached = start.elapsed();
 
-    let layout = Layout::default()
-        .direction(Direction::Vertical)
-        .constraints([Constraint::Min(1), Constraint::Length(3)])
-        .split(inner);
-
-    let text = vec![
-        Line::from(vec![Span::styled(
-            " ⚠ Disable your last monitor?",
-            Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
-        )]),
-        Line::from(vec![Span::styled(
-            " No way to undo from here.",
-            Style::default().fg(Color::Yellow),
-        )]),
-        Line::from(""),
-        Line::from(vec![Span::styled(
-            " To recover, you'll need to:",
-            Style::default().fg(Color::White),
-        )]),
-        Line::from(vec![Span::styled(
-            " 1. Reboot your machine",
-            Style::default().fg(Color::DarkGray),
-        )]),
-        Line::from(vec![Span::styled(
-            " 2. Open a TTY session",
-            Style::default().fg(Color::DarkGray),
-        )]),
-        Line::from(vec![
-            Span::styled(" 3. Edit ", Style::default().fg(Color::DarkGray)),
-            Span::styled(config_path, Style::default().fg(Color::Cyan)),
-        ]),
-        Line::from(vec![Span::styled(
-            "    and remove the disable line",
-            Style::default().fg(Color::DarkGray),
-        )]),
-        Line::from(vec![Span::styled(
-            " 4. Reboot and log into your compositor",
-            Style::default().fg(Color::DarkGray),
-        )]),
-    ];
-
-    let buttons = vec![
-        Line::from(vec![
-            Span::styled(" ┌───────┐ ", Style::default().fg(Color::Red)),
-            Span::styled("┌──────┐", Style::default().fg(Color::Green)),
-        ]),
-        Line::from(vec![
-            Span::styled(" │ ", Style::default().fg(Color::Red)),
-            Span::styled(
-                "[Y]",
-                Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
-            ),
-            Span::styled("es ", Style::default().fg(Color::Red)),
-            Span::styled("│ ", Style::default().fg(Color::Red)),
-            Span::styled("│ ", Style::default().fg(Color::Green)),
-            Span::styled(
-                "[N]",
-                Style::default()
-                    .fg(Color::Green)
-                    .add_modifier(Modifier::BOLD),
-            ),
-            Span::styled("o ", Style::default().fg(Color::Green)),
-            Span::styled("│", Style::default().fg(Color::Green)),
-        ]),
-        Line::from(vec![
-            Span::styled(" └───────┘ ", Style::default().fg(Color::Red)),
-            Span::styled("└──────┘", Style::default().fg(Color::Green)),
-        ]),
-    ];
-
-    let text_widget =
-        Paragraph::new(text).style(Style::default().fg(Color::White));
-    frame.render_widget(text_widget, layout[0]);
-
-    let buttons_widget =
-        Paragraph::new(buttons).style(Style::default().fg(Color::White));
-    frame.render_widget(buttons_widget, layout[1]);
+        println!(
+            "{FRAMES} frames at {width}x{height}: \
+             repaint {uncached:?}, cached {cached:?}"
+        );
+        assert!(cached < uncached);
+    }
 }
@@ -1,3 +1,215 @@
+pub mod apply_all;
+pub mod clone_picker;
+pub mod config_check;
+pub mod confirm;
+pub mod fallbacks;
+pub mod history;
 pub mod left;
 pub mod mode;
+pub mod new_monitor;
+pub mod options;
+pub mod reconcile;
+pub mod revert_countdown;
+pub mod scale;
+pub mod settings;
+pub mod stray_rules;
+pub mod timeline;
+pub mod tour;
+pub mod transcript;
+pub mod transform;
+pub mod transform_preview;
+pub mod untouched;
 pub mod workspace;
+
+use crossterm::event::KeyEvent;
+use ratatui::{
+    Frame,
+    layout::Rect,
+    style::{Color, Modifier, Style},
+};
+
+use crate::state::{App, Panel};
+
+/// Whether a panel took a key, or it is left to the global bindings.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Handled {
+    Yes,
+    No,
+}
+
+/// One of the focusable panels: draws itself and takes the keys that only
+/// mean something while it has focus.
+pub trait PanelWidget {
+    fn panel(&self) -> Panel;
+    fn render(&mut self, frame: &mut Frame, area: Rect, app: &App);
+    fn handle_key(&mut self, key: KeyEvent, app: &mut App) -> Handled;
+}
+
+/// Every panel, in the order Tab moves through them.
+pub struct Panels {
+    widgets: Vec<Box<dyn PanelWidget>>,
+    /// Where the panel the tour shows was last drawn.
+    toured: Rect,
+}
+
+impl Default for Panels {
+    fn default() -> Self {
+        let widgets: Vec<Box<dyn PanelWidget>> = vec![
+            Box::new(left::MapPanel::default()),
+            Box::new(mode::ModePanel::default()),
+            Box::new(workspace::WorkspacePanel::default()),
+            Box::new(scale::ScalePanel),
+            Box::new(transform::TransformPanel::default()),
+            Box::new(options::OptionsPanel::default()),
+        ];
+        Self {
+            widgets,
+            toured: Rect::default(),
+        }
+    }
+}
+
+impl Panels {
+    fn get_mut(&mut self, panel: Panel) -> &mut dyn PanelWidget {
+        let widget = self.widgets.iter_mut().find(|w| w.panel() == panel);
+        widget.expect("every panel is registered").as_mut()
+    }
+
+    pub fn render(
+        &mut self,
+        panel: Panel,
+        frame: &mut Frame,
+        area: Rect,
+        app: &App,
+    ) {
+        self.get_mut(panel).render(frame, area, app);
+        if app.is_flashing(panel) {
+            paint_border(frame, area, Style::default().fg(Color::Red));
+        }
+        match app.touring() {
+            Some(toured) if toured == panel => {
+                self.toured = area;
+                let bold = Style::default()
+                    .fg(Color::Yellow)
+                    .add_modifier(Modifier::BOLD);
+                paint_border(frame, area, bold);
+            }
+            Some(_) => dim(frame, area),
+            None => {}
+        }
+    }
+
+    /// Where the panel the tour shows was drawn.
+    pub fn toured(&self) -> Rect {
+        self.toured
+    }
+
+    /// Lets the focused panel take `key` first.
+    pub fn handle_key(&mut self, key: KeyEvent, app: &mut App) -> Handled {
+        self.get_mut(app.panel).handle_key(key, app)
+    }
+
+    /// The panel Tab moves to from `panel`, past those `app` does not
+    /// show.
+    pub fn after(&self, panel: Panel, app: &App) -> Panel {
+        let widgets = &self.widgets;
+        let i = widgets.iter().position(|w| w.panel() == panel).unwrap_or(0);
+        (1..=widgets.len())
+            .map(|step| widgets[(i + step) % widgets.len()].panel())
+            .find(|&next| app.has_panel(next))
+            .unwrap_or(panel)
+    }
+}
+
+/// Put before the focused panel's title in high contrast mode, so focus
+/// does not rest on the border color alone.
+pub fn focus_marker(app: &App, focused: bool) -> &'static str {
+    if focused && app.config.high_contrast {
+        "▶ "
+    } else {
+        ""
+    }
+}
+
+/// Put after a value waiting to be applied in high contrast mode, next
+/// to its color.
+pub fn pending_marker(app: &App) -> &'static str {
+    if app.config.high_contrast { "*" } else { "" }
+}
+
+/// Drawn before the highlighted row of a list.
+pub fn highlight_symbol(app: &App) -> &str {
+    app.config.highlight_symbol.as_deref().unwrap_or(" › ")
+}
+
+/// Restyles the border around `area`, title included, e.g. red for a
+/// flash.
+pub fn paint_border(frame: &mut Frame, area: Rect, style: Style) {
+    if area.is_empty() {
+        return;
+    }
+    let buf = frame.buffer_mut();
+    buf.set_style(Rect { height: 1, ..area }, style);
+    buf.set_style(Rect { y: area.bottom() - 1, height: 1, ..area }, style);
+    buf.set_style(Rect { width: 1, ..area }, style);
+    buf.set_style(Rect { x: area.right() - 1, width: 1, ..area }, style);
+}
+
+/// Fades everything drawn in `area`, so what is next to it stands out.
+pub fn dim(frame: &mut Frame, area: Rect) {
+    let faded =
+        Style::default().fg(Color::DarkGray).add_modifier(Modifier::DIM);
+    frame.buffer_mut().set_style(area, faded);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{compositor::Compositor, test_support::app};
+    use crossterm::event::{KeyCode, KeyModifiers};
+
+    #[test]
+    fn test_tab_visits_every_panel_in_order() {
+        let (mut app, _rx) = app();
+        app.compositor = Compositor::Hyprland;
+        let panels = Panels::default();
+        let mut order = vec![Panel::Monitor];
+        while order.len() < 7 {
+            order.push(panels.after(*order.last().unwrap(), &app));
+        }
+        assert_eq!(
+            order,
+            [
+                Panel::Monitor,
+                Panel::Mode,
+                Panel::Workspace,
+                Panel::Scale,
+                Panel::Transform,
+                Panel::Options,
+                Panel::Monitor,
+            ]
+        );
+
+        // River takes no workspace rules, so there is no panel for them.
+        app.compositor = Compositor::River;
+        assert_eq!(panels.after(Panel::Mode, &app), Panel::Scale);
+        app.tour = Some(1);
+        app.tour_step();
+        assert_eq!(app.touring(), Some(Panel::Scale));
+    }
+
+    #[test]
+    fn test_only_the_focused_panel_takes_its_keys() {
+        let (mut app, _rx) = app();
+        let mut panels = Panels::default();
+        let plus = KeyEvent::new(KeyCode::Char('+'), KeyModifiers::NONE);
+
+        assert_eq!(panels.handle_key(plus, &mut app), Handled::Yes);
+        assert_eq!(app.zoom_percent(), 110);
+
+        // Elsewhere + is left to the global binding, which scales.
+        app.panel = Panel::Mode;
+        assert_eq!(panels.handle_key(plus, &mut app), Handled::No);
+        assert_eq!(app.zoom_percent(), 110);
+    }
+}
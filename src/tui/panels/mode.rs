@@ -1,17 +1,62 @@
 use crate::{
-    state::{App, Panel},
-    tui::key_binds::get_modes_keybinds,
+    aspect,
+    i18n::{t, tf},
+    refresh,
+    state::{App, ModeEntry, ModeSpec, Panel},
+    tui::{
+        key_binds::{fit, get_modes_keybinds, strike_unobservable},
+        panels::{
+            Handled, PanelWidget, focus_marker, highlight_symbol,
+            pending_marker,
+        },
+    },
 };
 
+use crossterm::event::{KeyCode, KeyEvent};
 use ratatui::{
     Frame,
     layout::Rect,
     style::{Color, Modifier, Style},
     text::{Line, Span},
-    widgets::{Block, BorderType, Borders, List, ListItem},
+    widgets::{Block, BorderType, Borders, List, ListItem, ListState},
 };
 
-pub fn panel(frame: &mut Frame, app: &mut App, area: Rect) {
+/// The selected monitor's modes, with its custom ones after them.
+#[derive(Default)]
+pub struct ModePanel {
+    state: ListState,
+}
+
+impl PanelWidget for ModePanel {
+    fn panel(&self) -> Panel {
+        Panel::Mode
+    }
+
+    fn render(&mut self, frame: &mut Frame, area: Rect, app: &App) {
+        self.state.select(app.mode_selection.selected());
+        panel(frame, app, &mut self.state, area);
+    }
+
+    fn handle_key(&mut self, key: KeyEvent, app: &mut App) -> Handled {
+        match key.code {
+            KeyCode::Backspace => {
+                if let Err(e) = app.restore_previous_mode() {
+                    app.set_error(tf("Failed to apply: {}", &[&e]));
+                }
+            }
+            KeyCode::Char('f') => app.toggle_aspect_filter(),
+            _ => return Handled::No,
+        }
+        Handled::Yes
+    }
+}
+
+fn panel(
+    frame: &mut Frame,
+    app: &App,
+    state: &mut ListState,
+    area: Rect,
+) {
     let focused = app.panel == Panel::Mode;
     let border_color = if focused {
         Color::Blue
@@ -19,49 +64,146 @@ pub fn panel(frame: &mut Frame, app: &mut App, area: Rect) {
         Color::DarkGray
     };
 
+    let hidden = app.hidden_modes();
+    let marker = focus_marker(app, focused);
+    let name = if hidden > 0 {
+        format!(" {marker}{} ", tf("Modes ({} hidden)", &[&hidden]))
+    } else {
+        format!(" {marker}{} ", t("Modes"))
+    };
     let title = if focused {
         let mut keys = Vec::new();
-        keys.push(Span::styled(" Modes ", Style::default().fg(Color::Blue)));
+        keys.push(Span::styled(name, Style::default().fg(Color::Blue)));
         get_modes_keybinds(&mut keys);
-        Line::from(keys)
+        strike_unobservable(&mut keys[1..], app);
+        Line::from(fit(keys, area.width.saturating_sub(2) as usize))
     } else {
-        Line::from(Span::styled(
-            " Modes ",
-            Style::default().fg(Color::DarkGray),
-        ))
+        Line::from(Span::styled(name, Style::default().fg(Color::DarkGray)))
     };
 
     let monitor = app.selected_monitor().cloned();
+    let tags = monitor
+        .as_ref()
+        .map(|m| aspect::tags(&m.modes))
+        .unwrap_or_default();
+    let modeless = monitor.as_ref().is_some_and(|m| m.modes.is_empty());
+    // The mode staged for the monitor, such as by `c`, waiting for Enter.
+    let staged = |name: &str, mode: ModeSpec| {
+        let is_staged = app.pending_modes.get(name) == Some(&mode);
+        Span::styled(
+            if is_staged { pending_marker(app) } else { "" },
+            Style::default().fg(Color::Yellow),
+        )
+    };
     let items: Vec<ListItem> = monitor
         .as_ref()
         .map(|m| {
-            m.modes
-                .iter()
-                .map(|mode| {
-                    let marker = if mode.is_current { "▸ " } else { "  " };
-                    let preferred = if mode.preferred { " ★" } else { "" };
-                    let style = if mode.is_current {
-                        Style::default().fg(Color::Cyan)
-                    } else {
-                        Style::default().fg(Color::White)
-                    };
-
-                    Line::from(vec![
-                        Span::styled(marker, style),
-                        Span::styled(
-                            format!(
-                                "{}x{}@{}",
-                                mode.resolution.width, mode.resolution.height, mode.refresh_rate,
+            app.mode_entries()
+                .into_iter()
+                .map(|entry| match entry {
+                    ModeEntry::Advertised(i) => {
+                        let mode = &m.modes[i];
+                        let marker =
+                            if mode.is_current { "▸ " } else { "  " };
+                        let preferred =
+                            if mode.preferred { " ★" } else { "" };
+                        let unlisted = if app.mode_supported(&m.name, mode) {
+                            String::new()
+                        } else {
+                            format!(" {}", t("may require custom modeline"))
+                        };
+                        let style = if mode.is_current {
+                            Style::default().fg(Color::Cyan)
+                        } else {
+                            Style::default().fg(Color::White)
+                        };
+                        let over_link = link_warning(
+                            app.exceeds_link(&m.name, ModeSpec::of(mode)),
+                        );
+                        let tag = tags[i];
+                        let aspect_style = if tag.native {
+                            Style::default().fg(Color::DarkGray)
+                        } else {
+                            Style::default().fg(Color::Yellow)
+                        };
+
+                        Line::from(vec![
+                            Span::styled(marker, style),
+                            Span::styled(
+                                format!(
+                                    "{}x{}@{}{}",
+                                    mode.resolution.width,
+                                    mode.resolution.height,
+                                    refresh::format(
+                                        app.refresh_mhz(&m.name, mode)
+                                    ),
+                                    if tag.interlaced { "i" } else { "" },
+                                ),
+                                style,
+                            ),
+                            staged(&m.name, ModeSpec::of(mode)),
+                            Span::styled(
+                                format!(" {}", tag.aspect),
+                                aspect_style,
+                            ),
+                            Span::styled(
+                                preferred,
+                                Style::default().fg(Color::Yellow),
+                            ),
+                            Span::styled(
+                                interlaced(tag.interlaced),
+                                Style::default().fg(Color::DarkGray),
+                            ),
+                            Span::styled(
+                                unlisted,
+                                Style::default().fg(Color::DarkGray),
+                            ),
+                            over_link,
+                        ])
+                        .into()
+                    }
+                    ModeEntry::Custom { mode, active } => {
+                        let marker = if active { "▸ " } else { "  " };
+                        Line::from(vec![
+                            Span::styled(
+                                marker,
+                                Style::default().fg(Color::Cyan),
+                            ),
+                            Span::styled(
+                                format!(
+                                    "{}x{}@{}",
+                                    mode.width, mode.height, mode.refresh_rate,
+                                ),
+                                Style::default().fg(Color::Magenta),
                             ),
-                            style,
-                        ),
-                        Span::styled(preferred, Style::default().fg(Color::Yellow)),
-                    ])
-                    .into()
+                            staged(&m.name, mode),
+                            Span::styled(
+                                format!(" {}", t("custom")),
+                                Style::default().fg(Color::DarkGray),
+                            ),
+                            link_warning(app.exceeds_link(&m.name, mode)),
+                        ])
+                        .into()
+                    }
+                    ModeEntry::AddCustom => Line::from(Span::styled(
+                        format!("  {}", t("+ custom…")),
+                        Style::default().fg(Color::DarkGray),
+                    ))
+                    .into(),
                 })
                 .collect()
         })
         .unwrap_or_default();
+    // The note is not a mode, so nothing is highlighted.
+    let items = if modeless {
+        state.select(None);
+        vec![ListItem::new(Span::styled(
+            format!("  {}", t("no modes reported")),
+            Style::default().fg(Color::Yellow),
+        ))]
+    } else {
+        items
+    };
 
     let block = Block::default()
         .borders(Borders::ALL)
@@ -71,12 +213,91 @@ pub fn panel(frame: &mut Frame, app: &mut App, area: Rect) {
 
     let list = List::new(items)
         .block(block)
-        .highlight_symbol(" › ")
+        .highlight_symbol(highlight_symbol(app))
         .highlight_style(
             Style::default()
                 .fg(Color::Cyan)
                 .add_modifier(Modifier::BOLD),
         );
 
-    frame.render_stateful_widget(list, area, &mut app.mode_state);
+    frame.render_stateful_widget(list, area, state);
+}
+
+fn interlaced(interlaced: bool) -> String {
+    if interlaced {
+        format!(" {}", t("interlaced"))
+    } else {
+        String::new()
+    }
+}
+
+fn link_warning(exceeds: bool) -> Span<'static> {
+    let text = if exceeds {
+        format!(" {}", t("may exceed link bandwidth"))
+    } else {
+        String::new()
+    };
+    Span::styled(text, Style::default().fg(Color::Yellow))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{app, mode, monitor, screen};
+
+    #[test]
+    fn test_modes_panel_snapshot() {
+        let (mut app, _rx) = app();
+        let mut dp1 = monitor("DP-1", 0, 0);
+        dp1.modes.push(mode(1280, 720, 60));
+        dp1.modes.push(mode(1024, 768, 60));
+        app.set_monitors(vec![dp1]);
+        app.panel = Panel::Mode;
+        app.next();
+        let mut panel = ModePanel::default();
+        let shown = screen(46, 6, |f| panel.render(f, f.area(), &app));
+        assert_eq!(
+            shown,
+            [
+                "╭ Modes ↑↓ select  Enter apply  ⌫ previous m…╮",
+                "│   ▸ 1920x1080@60 16:9 ★                    │",
+                "│ ›   1280x720@60 16:9                       │",
+                "│     1024x768@60 4:3                        │",
+                "│     + custom…                              │",
+                "╰────────────────────────────────────────────╯",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_a_monitor_without_modes_has_none_to_apply() {
+        let (mut app, rx) = app();
+        let mut virt = monitor("HEADLESS-1", 0, 0);
+        virt.modes.clear();
+        app.set_monitors(vec![virt]);
+        app.panel = Panel::Mode;
+        let mut panel = ModePanel::default();
+        let shown = screen(30, 3, |f| panel.render(f, f.area(), &app));
+        assert_eq!(shown[1], "│  no modes reported         │");
+
+        app.apply_action().unwrap();
+        app.set_mode_from_input("1920x1080@60").unwrap();
+        assert!(rx.try_recv().is_err());
+        assert!(app.config.custom_modes.is_empty());
+    }
+
+    #[test]
+    fn test_high_contrast_marks_the_staged_mode() {
+        let (mut app, _rx) = app();
+        app.config.high_contrast = true;
+        let mut dp1 = monitor("DP-1", 0, 0);
+        dp1.modes.push(mode(1280, 720, 60));
+        app.set_monitors(vec![dp1]);
+        let staged = ModeSpec::of(&mode(1280, 720, 60));
+        app.pending_modes.insert("DP-1".into(), staged);
+        let mut panel = ModePanel::default();
+        let shown = screen(46, 5, |f| panel.render(f, f.area(), &app));
+        assert!(shown[2].contains("1280x720@60* 16:9"), "{}", shown[2]);
+        assert!(!shown[1].contains('*'), "{}", shown[1]);
+    }
 }
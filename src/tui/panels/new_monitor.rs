@@ -0,0 +1,44 @@
+use crate::{
+    i18n::{t, tf},
+    tui::key_binds::{fit, hint},
+};
+
+use ratatui::{
+    Frame,
+    layout::Rect,
+    style::{Color, Style},
+    text::{Line, Span},
+    widgets::{Block, BorderType, Borders, Clear, Paragraph, Wrap},
+};
+
+pub fn render_modal(frame: &mut Frame, name: &str, area: Rect) {
+    let modal_w = 56.min(area.width.saturating_sub(4));
+    let modal_h = 5.min(area.height.saturating_sub(2));
+    let x = (area.width.saturating_sub(modal_w)) / 2;
+    let y = (area.height.saturating_sub(modal_h)) / 2;
+    let modal_area = Rect::new(x, y, modal_w, modal_h);
+
+    frame.render_widget(Clear, modal_area);
+
+    let mut title = vec![Span::styled(
+        format!(" {} | ", t("New monitor")),
+        Style::default().fg(Color::Yellow),
+    )];
+    hint(&mut title, "r", "enable right");
+    hint(&mut title, "s", "enable saved");
+    hint(&mut title, "Esc", "ignore");
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .border_style(Style::default().fg(Color::Yellow))
+        .title(Line::from(fit(title, modal_w.saturating_sub(2) as usize)));
+
+    let text = Line::from(Span::styled(
+        tf("{} connected without a place in the layout.", &[&name]),
+        Style::default().fg(Color::White),
+    ));
+    frame.render_widget(
+        Paragraph::new(text).block(block).wrap(Wrap { trim: true }),
+        modal_area,
+    );
+}
@@ -0,0 +1,165 @@
+use crate::{
+    i18n::t,
+    state::{App, MonitorOption, Panel},
+    tui::{
+        key_binds::{fit, get_options_keybinds, strike_unobservable},
+        panels::{
+            Handled, PanelWidget, focus_marker, highlight_symbol,
+            pending_marker,
+        },
+    },
+};
+
+use crossterm::event::KeyEvent;
+use ratatui::{
+    Frame,
+    layout::Rect,
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, BorderType, Borders, List, ListItem, ListState},
+};
+
+/// Per-monitor switches, flipped with ←→ and applied with Enter.
+#[derive(Default)]
+pub struct OptionsPanel {
+    state: ListState,
+}
+
+impl PanelWidget for OptionsPanel {
+    fn panel(&self) -> Panel {
+        Panel::Options
+    }
+
+    fn render(&mut self, frame: &mut Frame, area: Rect, app: &App) {
+        self.state.select(app.options_selection.selected());
+        panel(frame, app, &mut self.state, area);
+    }
+
+    fn handle_key(&mut self, _key: KeyEvent, _app: &mut App) -> Handled {
+        Handled::No
+    }
+}
+
+fn panel(
+    frame: &mut Frame,
+    app: &App,
+    state: &mut ListState,
+    area: Rect,
+) {
+    let focused = app.panel == Panel::Options;
+    let border_color = if focused {
+        Color::Blue
+    } else {
+        Color::DarkGray
+    };
+
+    let title = if focused {
+        let mut keys = Vec::new();
+        keys.push(Span::styled(
+            format!(" {}{} | ", focus_marker(app, true), t("Options")),
+            Style::default().fg(Color::Blue),
+        ));
+        get_options_keybinds(&mut keys);
+        strike_unobservable(&mut keys[1..], app);
+        Line::from(fit(keys, area.width.saturating_sub(2) as usize))
+    } else {
+        Line::from(Span::styled(
+            format!(" {} ", t("Options")),
+            Style::default().fg(Color::DarkGray),
+        ))
+    };
+
+    let name = app.selected_monitor().map(|m| m.name.clone());
+    let items: Vec<ListItem> = app
+        .monitor_options()
+        .into_iter()
+        .map(|option| {
+            let (current, pending) = match &name {
+                Some(name) => (
+                    app.option_value(name, option),
+                    app.pending_option(name, option),
+                ),
+                None => (false, false),
+            };
+            let mut spans = vec![
+                Span::styled(
+                    format!("  {:<9} ", option.label()),
+                    Style::default().fg(Color::White),
+                ),
+                Span::styled(
+                    value_label(option, current),
+                    Style::default().fg(Color::Cyan),
+                ),
+            ];
+            if pending != current {
+                spans.push(Span::styled(
+                    " → ",
+                    Style::default().fg(Color::DarkGray),
+                ));
+                spans.push(Span::styled(
+                    format!(
+                        "{}{}",
+                        value_label(option, pending),
+                        pending_marker(app)
+                    ),
+                    Style::default().fg(Color::Yellow),
+                ));
+            }
+            Line::from(spans).into()
+        })
+        .collect();
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .border_style(Style::default().fg(border_color))
+        .title(title);
+
+    let list = List::new(items)
+        .block(block)
+        .highlight_symbol(highlight_symbol(app))
+        .highlight_style(
+            Style::default()
+                .fg(Color::Cyan)
+                .add_modifier(Modifier::BOLD),
+        );
+
+    frame.render_stateful_widget(list, area, state);
+}
+
+fn value_label(option: MonitorOption, value: bool) -> &'static str {
+    match (option, value) {
+        (MonitorOption::Power, true) => t("on"),
+        (MonitorOption::Power, false) => t("off"),
+        (MonitorOption::Anchored, true) => t("yes"),
+        (MonitorOption::Anchored, false) => t("no"),
+        (MonitorOption::NightLight, true) => t("excluded"),
+        (MonitorOption::NightLight, false) => t("on"),
+        (MonitorOption::Managed, true) => t("yes"),
+        (MonitorOption::Managed, false) => t("no"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{app, monitor, screen};
+
+    #[test]
+    fn test_options_panel_snapshot() {
+        let (mut app, _rx) = app();
+        app.set_monitors(vec![monitor("DP-1", 0, 0), monitor("DP-2", 1920, 0)]);
+        app.panel = Panel::Options;
+        let mut panel = OptionsPanel::default();
+        let shown = screen(40, 4, |f| panel.render(f, f.area(), &app));
+        assert_eq!(
+            shown,
+            [
+                "╭ Options | ↑↓ select  ←→ change  Ente…╮",
+                "│ ›   Anchored  no                     │",
+                "│     Managed   yes                    │",
+                "╰──────────────────────────────────────╯",
+            ]
+        );
+    }
+}
@@ -0,0 +1,105 @@
+use crate::{
+    i18n::{t, tf},
+    state::App,
+    tui::{
+        key_binds::{fit, hint},
+        panels::highlight_symbol,
+    },
+};
+
+use ratatui::{
+    Frame,
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, BorderType, Borders, Clear, List, ListItem, Paragraph},
+};
+
+pub fn render_modal(frame: &mut Frame, app: &mut App, area: Rect) {
+    let symbol = highlight_symbol(app).to_string();
+    let path = app.comp_monitor_config_path.display().to_string();
+    let Some(view) = &mut app.reconcile else {
+        return;
+    };
+
+    let rows: usize = view.rows.iter().map(|r| r.mismatches.len() + 1).sum();
+    let modal_w = area.width.saturating_sub(8).clamp(20, 72).min(area.width);
+    let modal_h = (rows as u16 + 3).max(6).min(area.height.saturating_sub(2));
+    let x = (area.width.saturating_sub(modal_w)) / 2;
+    let y = (area.height.saturating_sub(modal_h)) / 2;
+    let modal_area = Rect::new(x, y, modal_w, modal_h);
+
+    frame.render_widget(Clear, modal_area);
+
+    let mut title = vec![Span::styled(
+        format!(" {} | ", t("Live vs config")),
+        Style::default().fg(Color::Yellow),
+    )];
+    hint(&mut title, "↑↓", "select");
+    hint(&mut title, "l/L", "keep live");
+    hint(&mut title, "c/C", "apply config");
+    hint(&mut title, "Esc", "close");
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .border_style(Style::default().fg(Color::Yellow))
+        .title(Line::from(fit(title, modal_w.saturating_sub(2) as usize)));
+
+    let inner = block.inner(modal_area);
+    frame.render_widget(block, modal_area);
+
+    let split = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(1), Constraint::Min(1)])
+        .split(inner);
+
+    let dim = Style::default().fg(Color::DarkGray);
+    let header = Span::styled(
+        format!("   {}", tf("What {} says  →  what runs now", &[&path])),
+        dim,
+    );
+    frame.render_widget(
+        Paragraph::new(Line::from(fit(vec![header], inner.width as usize))),
+        split[0],
+    );
+
+    let width = inner.width.saturating_sub(3) as usize;
+    let items: Vec<ListItem> = view
+        .rows
+        .iter()
+        .map(|row| {
+            let mut lines = vec![Line::from(Span::styled(
+                row.name.clone(),
+                Style::default().fg(Color::White),
+            ))];
+            lines.extend(row.mismatches.iter().map(|m| {
+                Line::from(fit(
+                    vec![
+                        Span::styled(
+                            format!("  {:<10} ", t(m.setting.label())),
+                            dim,
+                        ),
+                        Span::styled(
+                            m.config.clone(),
+                            Style::default().fg(Color::Cyan),
+                        ),
+                        Span::styled("  →  ", dim),
+                        Span::styled(
+                            m.live.clone(),
+                            Style::default().fg(Color::Yellow),
+                        ),
+                    ],
+                    width,
+                ))
+            }));
+            ListItem::new(lines)
+        })
+        .collect();
+
+    let list = List::new(items).highlight_symbol(symbol).highlight_style(
+        Style::default()
+            .fg(Color::Cyan)
+            .add_modifier(Modifier::BOLD),
+    );
+    frame.render_stateful_widget(list, split[1], &mut view.state);
+}
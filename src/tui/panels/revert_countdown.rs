@@ -0,0 +1,53 @@
+use std::time::Instant;
+
+use crate::{
+    i18n::{t, tf},
+    state::GuardedChange,
+    tui::key_binds::{fit, hint},
+};
+
+use ratatui::{
+    Frame,
+    layout::Rect,
+    style::{Color, Style},
+    text::{Line, Span},
+    widgets::{Block, BorderType, Borders, Clear, Paragraph},
+};
+
+pub fn render_overlay(frame: &mut Frame, guarded: &GuardedChange, area: Rect) {
+    let modal_w = 52.min(area.width.saturating_sub(4));
+    let modal_h = 3.min(area.height);
+    let x = (area.width.saturating_sub(modal_w)) / 2;
+    let y = (area.height.saturating_sub(modal_h)) / 2;
+    let modal_area = Rect::new(x, y, modal_w, modal_h);
+
+    frame.render_widget(Clear, modal_area);
+
+    let mut title = vec![Span::styled(
+        format!(" {} | ", t("Keep this change?")),
+        Style::default().fg(Color::Yellow),
+    )];
+    hint(&mut title, "any key", "keep");
+    hint(&mut title, "Esc", "revert");
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .border_style(Style::default().fg(Color::Yellow))
+        .title(Line::from(fit(title, modal_w.saturating_sub(2) as usize)));
+
+    let left = guarded.deadline.saturating_duration_since(Instant::now());
+    let secs = left.as_millis().div_ceil(1000);
+    let text = tf(
+        "{} holds this terminal, reverting in {}s",
+        &[&guarded.name, &secs],
+    );
+    let line = Line::from(Span::styled(
+        format!(" {text}"),
+        Style::default().fg(Color::White),
+    ));
+    let width = modal_w.saturating_sub(2) as usize;
+    frame.render_widget(
+        Paragraph::new(Line::from(fit(line.spans, width))).block(block),
+        modal_area,
+    );
+}
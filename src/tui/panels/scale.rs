@@ -0,0 +1,234 @@
+use crate::{
+    i18n::{t, tf},
+    state::{App, Panel},
+    tui::{
+        key_binds::{fit, get_scale_keybinds, strike_unobservable},
+        panels::{Handled, PanelWidget, focus_marker, pending_marker},
+    },
+};
+
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::{
+    Frame,
+    layout::Rect,
+    style::{Color, Style},
+    text::{Line, Span},
+    widgets::{Block, BorderType, Borders, Paragraph},
+};
+
+/// The selected monitor's scale, staged with the arrows or +/-.
+pub struct ScalePanel;
+
+impl PanelWidget for ScalePanel {
+    fn panel(&self) -> Panel {
+        Panel::Scale
+    }
+
+    fn render(&mut self, frame: &mut Frame, area: Rect, app: &App) {
+        render_scale(frame, app, area);
+    }
+
+    fn handle_key(&mut self, key: KeyEvent, app: &mut App) -> Handled {
+        match key.code {
+            KeyCode::Char('*') => app.use_recommended_scale(),
+            KeyCode::Char(',') => app.snap_scale(false),
+            KeyCode::Char('.') => app.snap_scale(true),
+            _ => return Handled::No,
+        }
+        Handled::Yes
+    }
+}
+
+fn render_scale(frame: &mut Frame, app: &App, area: Rect) {
+    let focused = app.panel == Panel::Scale;
+    let border_color = if focused {
+        Color::Blue
+    } else {
+        Color::DarkGray
+    };
+
+    let title = if focused {
+        let mut keys = Vec::new();
+        keys.push(Span::styled(
+            format!(" {}{} | ", focus_marker(app, true), t("Scale")),
+            Style::default().fg(Color::Blue),
+        ));
+        get_scale_keybinds(&mut keys);
+        strike_unobservable(&mut keys[1..], app);
+        Line::from(fit(keys, area.width.saturating_sub(2) as usize))
+    } else {
+        Line::from(Span::styled(
+            format!(" {} ", t("Scale")),
+            Style::default().fg(Color::DarkGray),
+        ))
+    };
+
+    let monitor = app.selected_monitor();
+    let current = monitor.map(|m| m.scale).unwrap_or(1.0);
+    let pending = app.pending_scale();
+    let changed = (current - pending).abs() > 0.001;
+
+    let bar_width = (area.width as usize).saturating_sub(6);
+    let max_scale = 10.0_f64;
+    let fill = ((pending / max_scale) * bar_width as f64)
+        .round()
+        .min(bar_width as f64) as usize;
+    let empty = bar_width.saturating_sub(fill);
+    let filled_part = "━".repeat(fill.saturating_sub(1));
+    let empty_part = "─".repeat(empty);
+
+    let over_limit = app.scale_over_limit();
+    let pending_color = if over_limit.is_some() {
+        Color::Red
+    } else if changed {
+        Color::Yellow
+    } else {
+        Color::White
+    };
+    let bar_color = if over_limit.is_some() {
+        Color::Red
+    } else {
+        Color::Cyan
+    };
+
+    let recommended = match app.recommended_scale(app.selected_monitor) {
+        Some(s) => Line::from(vec![
+            Span::styled(
+                format!("  {} ", t("recommended")),
+                Style::default().fg(Color::DarkGray),
+            ),
+            Span::styled(
+                format!("{:.2}x", s),
+                Style::default().fg(Color::Green),
+            ),
+        ]),
+        None => Line::from(""),
+    };
+
+    let lint = app.scale_lint();
+
+    let mut lines = vec![
+        Line::from(vec![
+            Span::styled(
+                format!("  {} ", t("current")),
+                Style::default().fg(Color::DarkGray),
+            ),
+            Span::styled(
+                format!("{:.2}x", current),
+                Style::default().fg(Color::White),
+            ),
+        ]),
+        Line::from(vec![
+            Span::styled(
+                format!("  {} ", t("pending")),
+                Style::default().fg(Color::DarkGray),
+            ),
+            Span::styled(
+                format!("{:.2}x{}", pending, pending_marker(app)),
+                Style::default().fg(pending_color),
+            ),
+        ]),
+        recommended,
+        Line::from(vec![
+            Span::styled(
+                format!("  {}", filled_part),
+                Style::default().fg(bar_color),
+            ),
+            Span::styled("●", Style::default().fg(Color::White)),
+            Span::styled(empty_part, Style::default().fg(Color::DarkGray)),
+        ]),
+    ];
+
+    if let Some(max) = over_limit {
+        lines.push(Line::from(Span::styled(
+            format!(
+                "  {}",
+                tf(
+                    "⚠ {} may reject scales above {}",
+                    &[&app.compositor.label(), &format!("{max:.2}x")],
+                )
+            ),
+            Style::default().fg(Color::Red),
+        )));
+    }
+
+    if let Some(lint) = lint {
+        let (lw, lh) = lint.logical;
+        lines.push(Line::from(Span::styled(
+            format!(
+                "  {}",
+                tf(
+                    "⚠ logical {}×{} is not whole pixels",
+                    &[&format!("{lw:.1}"), &format!("{lh:.1}")],
+                )
+            ),
+            Style::default().fg(Color::Yellow),
+        )));
+        let mut choices = vec![Span::raw("  ")];
+        for (key, scale) in [(", ", lint.lower), (". ", lint.higher)] {
+            if let Some(s) = scale {
+                choices
+                    .push(Span::styled(key, Style::default().fg(Color::Cyan)));
+                choices.push(Span::styled(
+                    format!("{s:.2}x  "),
+                    Style::default().fg(Color::Green),
+                ));
+            }
+        }
+        lines.push(Line::from(choices));
+    } else {
+        lines.insert(0, Line::from(""));
+        lines.push(Line::from(""));
+    }
+
+    lines.push(if changed {
+        Line::from(vec![Span::styled(
+            format!("  {}", t("Enter to apply")),
+            Style::default().fg(Color::Yellow),
+        )])
+    } else {
+        Line::from(vec![Span::styled(
+            format!("  {}", t("↑↓ or +/- adjust")),
+            Style::default().fg(Color::DarkGray),
+        )])
+    });
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .border_style(Style::default().fg(border_color))
+        .title(title);
+
+    frame.render_widget(Paragraph::new(lines).block(block), area);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{app, monitor, screen};
+
+    #[test]
+    fn test_scale_panel_snapshot() {
+        let (mut app, _rx) = app();
+        app.set_monitors(vec![monitor("DP-1", 0, 0), monitor("DP-2", 1920, 0)]);
+        app.panel = Panel::Scale;
+        app.next();
+        let mut panel = ScalePanel;
+        let shown = screen(34, 10, |f| panel.render(f, f.area(), &app));
+        assert_eq!(
+            shown,
+            [
+                "╭ Scale | ←→ adjust  * recommend…╮",
+                "│  current 1.00x                 │",
+                "│  pending 1.01x                 │",
+                "│                                │",
+                "│  ━━●─────────────────────────  │",
+                "│  ⚠ logical 1901.0×1069.3 is not│",
+                "│  , 1.00x  . 1.20x              │",
+                "│  Enter to apply                │",
+                "│                                │",
+                "╰────────────────────────────────╯",
+            ]
+        );
+    }
+}
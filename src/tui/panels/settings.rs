@@ -0,0 +1,149 @@
+use crate::{
+    config_fields::FieldKind,
+    i18n::t,
+    state::SettingsEditor,
+    tui::key_binds::{fit, hint},
+};
+
+use ratatui::{
+    Frame,
+    layout::Rect,
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, BorderType, Borders, Clear, List, ListItem},
+};
+
+pub fn render_modal(
+    frame: &mut Frame,
+    editor: &mut SettingsEditor,
+    symbol: &str,
+    area: Rect,
+) {
+    let rows = (editor.fields.len() + editor.error.iter().len()) as u16;
+    let modal_w = area.width.saturating_sub(8).clamp(20, 72).min(area.width);
+    let modal_h = (rows + 2).max(5).min(area.height.saturating_sub(2));
+    let x = (area.width.saturating_sub(modal_w)) / 2;
+    let y = (area.height.saturating_sub(modal_h)) / 2;
+    let modal_area = Rect::new(x, y, modal_w, modal_h);
+
+    frame.render_widget(Clear, modal_area);
+
+    let mut title = vec![Span::styled(
+        format!(" {} | ", t("Settings")),
+        Style::default().fg(Color::Blue),
+    )];
+    if editor.input.is_some() {
+        hint(&mut title, "Enter", "save");
+        hint(&mut title, "Esc", "cancel");
+    } else {
+        hint(&mut title, "Enter", "change");
+        hint(&mut title, "←→", "choose");
+        hint(&mut title, "Esc", "close");
+    }
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .border_style(Style::default().fg(Color::Blue))
+        .title(Line::from(fit(title, modal_w.saturating_sub(2) as usize)));
+
+    let inner = block.inner(modal_area);
+    frame.render_widget(block, modal_area);
+
+    let cursor = editor.state.selected().unwrap_or(0);
+    let key_w = editor.fields.iter().map(|f| f.key.len()).max().unwrap_or(0);
+    let width = inner.width.saturating_sub(3) as usize;
+    let dim = Style::default().fg(Color::DarkGray);
+    let items: Vec<ListItem> = editor
+        .fields
+        .iter()
+        .enumerate()
+        .map(|(i, field)| {
+            let mut spans = vec![Span::styled(
+                format!("{:<key_w$}  ", field.key),
+                Style::default().fg(Color::White),
+            )];
+            match (&editor.input, field.kind) {
+                (Some(input), _) if i == cursor => {
+                    spans.push(Span::styled(
+                        format!("{input}▏"),
+                        Style::default().fg(Color::Cyan),
+                    ));
+                }
+                (_, FieldKind::Toggle) => {
+                    let on = field.value == "true";
+                    spans.push(Span::styled(
+                        if on { t("yes") } else { t("no") },
+                        Style::default().fg(if on {
+                            Color::Green
+                        } else {
+                            Color::DarkGray
+                        }),
+                    ));
+                }
+                _ if field.value.is_empty() => {
+                    spans.push(Span::styled(t("default"), dim));
+                }
+                _ => spans.push(Span::styled(
+                    field.value.clone(),
+                    Style::default().fg(Color::Yellow),
+                )),
+            }
+            if field.restart {
+                spans.push(Span::styled(format!("  {}", t("on restart")), dim));
+            }
+            let mut lines = vec![Line::from(fit(spans, width))];
+            // Refused values are explained right under their setting.
+            if let Some((at, why)) = &editor.error
+                && *at == i
+            {
+                let why = Span::styled(
+                    format!("  {why}"),
+                    Style::default().fg(Color::Red),
+                );
+                lines.push(Line::from(fit(vec![why], width)));
+            }
+            ListItem::new(lines)
+        })
+        .collect();
+
+    let list = List::new(items).highlight_symbol(symbol).highlight_style(
+        Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD),
+    );
+    frame.render_stateful_widget(list, inner, &mut editor.state);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compositor::Compositor::Hyprland;
+    use crate::test_support::{harness, screen};
+
+    #[test]
+    fn test_refused_values_are_shown_next_to_their_setting() {
+        let (mut app, _recorder) = harness(Hyprland, None);
+        app.open_settings();
+        let editor = app.settings.as_mut().unwrap();
+        let at = editor.fields.iter().position(|f| f.key == "zoom_step");
+        editor.state.select(at);
+        app.settings_enter();
+        app.settings_backspace();
+        app.settings_input('0');
+        app.settings_enter();
+        let editor = app.settings.as_mut().unwrap();
+
+        let shown = screen(60, 7, |f| render_modal(f, editor, " › ", f.area()));
+        assert_eq!(
+            shown,
+            [
+                "                                                            ",
+                "    ╭ Settings | Enter change  ←→ choose  Esc close  ──╮    ",
+                "    │   zoom_min                    0.2                │    ",
+                "    │ › zoom_step                   1.1                │    ",
+                "    │     Must be at least 1.01                        │    ",
+                "    ╰──────────────────────────────────────────────────╯    ",
+                "                                                            ",
+            ]
+        );
+        assert_eq!(app.config.zoom_step, 1.1);
+    }
+}
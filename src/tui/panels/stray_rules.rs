@@ -0,0 +1,73 @@
+use crate::{
+    i18n::{t, tf},
+    state::App,
+    tui::key_binds::{fit, hint},
+};
+
+use ratatui::{
+    Frame,
+    layout::Rect,
+    style::{Color, Style},
+    text::{Line, Span},
+    widgets::{Block, BorderType, Borders, Clear, Paragraph},
+};
+
+pub fn render_modal(frame: &mut Frame, app: &App, area: Rect) {
+    let rows = app.stray_rules.len() as u16;
+    let modal_w = area.width.saturating_sub(8).max(20).min(area.width);
+    let modal_h = (rows + 6).min(area.height.saturating_sub(2));
+    let x = (area.width.saturating_sub(modal_w)) / 2;
+    let y = (area.height.saturating_sub(modal_h)) / 2;
+    let modal_area = Rect::new(x, y, modal_w, modal_h);
+
+    frame.render_widget(Clear, modal_area);
+
+    let mut title = vec![Span::styled(
+        format!(" {} | ", t("Conflicting monitor rules")),
+        Style::default().fg(Color::Yellow),
+    )];
+    hint(&mut title, "e", "extract");
+    hint(&mut title, "Esc", "continue");
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .border_style(Style::default().fg(Color::Yellow))
+        .title(Line::from(fit(title, modal_w.saturating_sub(2) as usize)));
+
+    let inner = block.inner(modal_area);
+    frame.render_widget(block, modal_area);
+
+    let intro = tf(
+        "These rules also configure monitors besides {}:",
+        &[&app.comp_monitor_config_path.display()],
+    );
+    let mut lines = vec![
+        Line::from(Span::styled(
+            format!(" {intro}"),
+            Style::default().fg(Color::White),
+        )),
+        Line::from(""),
+    ];
+    for rule in &app.stray_rules {
+        let first = rule.text.lines().next().unwrap_or_default();
+        lines.push(Line::from(vec![
+            Span::styled(
+                format!(" {}:{}  ", rule.path.display(), rule.line),
+                Style::default().fg(Color::Cyan),
+            ),
+            Span::styled(first.to_string(), Style::default().fg(Color::Red)),
+        ]));
+    }
+    lines.push(Line::from(""));
+    lines.push(Line::from(Span::styled(
+        format!(" {}", t("Whichever file is sourced last wins.")),
+        Style::default().fg(Color::DarkGray),
+    )));
+
+    let width = inner.width as usize;
+    let lines: Vec<Line> = lines
+        .into_iter()
+        .map(|l| Line::from(fit(l.spans, width)))
+        .collect();
+    frame.render_widget(Paragraph::new(lines), inner);
+}
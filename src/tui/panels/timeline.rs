@@ -0,0 +1,64 @@
+use crate::{
+    i18n::t,
+    state::App,
+    tui::{
+        key_binds::{fit, hint},
+        panels::highlight_symbol,
+    },
+};
+
+use ratatui::{
+    Frame,
+    layout::Rect,
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, BorderType, Borders, Clear, List, ListItem},
+};
+
+pub fn render_modal(frame: &mut Frame, app: &mut App, area: Rect) {
+    let symbol = highlight_symbol(app).to_string();
+    let Some(state) = &mut app.timeline_view else {
+        return;
+    };
+
+    let modal_w = area.width.saturating_sub(8).max(20).min(area.width);
+    let modal_h = area.height.saturating_sub(4).max(10).min(area.height);
+    let x = (area.width.saturating_sub(modal_w)) / 2;
+    let y = (area.height.saturating_sub(modal_h)) / 2;
+    let modal_area = Rect::new(x, y, modal_w, modal_h);
+
+    frame.render_widget(Clear, modal_area);
+
+    let mut title = vec![Span::styled(
+        format!(" {} | ", t("Session timeline")),
+        Style::default().fg(Color::Blue),
+    )];
+    hint(&mut title, "↑↓", "scroll");
+    hint(&mut title, "Esc", "close");
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .border_style(Style::default().fg(Color::Blue))
+        .title(Line::from(fit(title, modal_w.saturating_sub(2) as usize)));
+
+    let items: Vec<ListItem> = app
+        .timeline
+        .entries()
+        .rev()
+        .map(|e| {
+            ListItem::new(Line::from(Span::styled(
+                e.line(),
+                Style::default().fg(Color::White),
+            )))
+        })
+        .collect();
+    let list = List::new(items)
+        .block(block)
+        .highlight_symbol(symbol)
+        .highlight_style(
+            Style::default()
+                .fg(Color::Cyan)
+                .add_modifier(Modifier::BOLD),
+        );
+    frame.render_stateful_widget(list, modal_area, state);
+}
@@ -0,0 +1,119 @@
+use crate::{
+    i18n::{t, tf},
+    state::{App, Panel, TOUR},
+    tui::key_binds::{fit, hint},
+};
+
+use ratatui::{
+    Frame,
+    layout::Rect,
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, BorderType, Borders, Clear, Paragraph, Wrap},
+};
+
+/// What the tour says about `panel`, with the keys it names.
+fn explain(panel: Panel, keys: &mut Vec<Span<'static>>) -> &'static str {
+    match panel {
+        Panel::Monitor => {
+            hint(keys, "[]", "switch monitor");
+            hint(keys, "↑↓ ←→", "move");
+            hint(keys, "Tab", "switch panel");
+            t("The map shows your monitors; arrow keys move the selected one.")
+        }
+        Panel::Mode => {
+            hint(keys, "↑↓", "select");
+            hint(keys, "Enter", "apply");
+            t("Modes lists the resolutions and refresh rates the monitor \
+               offers.")
+        }
+        Panel::Workspace => {
+            hint(keys, "←→", "assign");
+            hint(keys, "D", "distribute");
+            t("Workspaces shows which monitor each workspace opens on.")
+        }
+        Panel::Scale => {
+            hint(keys, "←→", "adjust");
+            hint(keys, "*", "recommended");
+            hint(keys, "Enter", "apply");
+            t("Scale sets how large everything is drawn on the monitor.")
+        }
+        Panel::Transform => {
+            hint(keys, "↑↓", "rotate");
+            hint(keys, "p", "preview");
+            hint(keys, "Enter", "apply");
+            t("Transform rotates or flips the monitor.")
+        }
+        Panel::Options => {
+            hint(keys, "W", "apply all");
+            hint(keys, "q", "quit");
+            t("Options holds power and more; W applies all that is staged.")
+        }
+    }
+}
+
+/// The card for the tour step on screen, next to the panel it is about,
+/// which sits at `panel_area`.
+pub fn render_overlay(frame: &mut Frame, app: &App, panel_area: Rect) {
+    let (Some(step), Some(panel)) = (app.tour, app.touring()) else {
+        return;
+    };
+    let area = frame.area();
+    let mut keys = Vec::new();
+    let text = explain(panel, &mut keys);
+    let mut next = Vec::new();
+    let last = step + 1 == TOUR.len();
+    hint(&mut next, t("any key"), if last { "done" } else { "next" });
+    hint(&mut next, "Esc", "skip tour");
+
+    let card_w = 56.min(area.width.saturating_sub(2));
+    let inner = card_w.saturating_sub(2).max(1) as usize;
+    let rows = text.chars().count().div_ceil(inner) as u16;
+    let card_h = (rows + 4).min(area.height);
+    let card = Rect::new(
+        place(panel_area.x, panel_area.width, card_w, area.width),
+        beside(panel_area, card_h, area.height),
+        card_w,
+        card_h,
+    );
+
+    frame.render_widget(Clear, card);
+    let title = tf("Tour {}/{}", &[&(step + 1), &TOUR.len()]);
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .border_style(Style::default().fg(Color::Yellow))
+        .title(Span::styled(
+            format!(" {title} "),
+            Style::default()
+                .fg(Color::Yellow)
+                .add_modifier(Modifier::BOLD),
+        ));
+    let lines = vec![
+        Line::from(Span::styled(text, Style::default().fg(Color::White))),
+        Line::from(fit(keys, inner)),
+        Line::from(fit(next, inner)),
+    ];
+    frame.render_widget(
+        Paragraph::new(lines).block(block).wrap(Wrap { trim: true }),
+        card,
+    );
+}
+
+/// Centers `width` on the span at `start`, kept on a screen `screen` wide.
+fn place(start: u16, span: u16, width: u16, screen: u16) -> u16 {
+    let centered = start + span.saturating_sub(width) / 2;
+    centered.min(screen.saturating_sub(width))
+}
+
+/// Below `panel` when the card fits there, else above it, else at the
+/// bottom of the screen.
+fn beside(panel: Rect, height: u16, screen: u16) -> u16 {
+    if panel.bottom() + height <= screen {
+        panel.bottom()
+    } else if panel.y >= height {
+        panel.y - height
+    } else {
+        screen.saturating_sub(height)
+    }
+}
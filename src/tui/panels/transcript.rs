@@ -0,0 +1,62 @@
+use crate::{
+    i18n::t,
+    state::App,
+    tui::key_binds::{fit, hint},
+};
+
+use ratatui::{
+    Frame,
+    layout::Rect,
+    style::{Color, Style},
+    text::{Line, Span},
+    widgets::{Block, BorderType, Borders, Clear, Paragraph},
+};
+
+pub fn render_modal(frame: &mut Frame, app: &App, area: Rect) {
+    let modal_w = area.width.saturating_sub(8).max(20).min(area.width);
+    let modal_h = area.height.saturating_sub(4).max(10).min(area.height);
+    let x = (area.width.saturating_sub(modal_w)) / 2;
+    let y = (area.height.saturating_sub(modal_h)) / 2;
+    let modal_area = Rect::new(x, y, modal_w, modal_h);
+
+    frame.render_widget(Clear, modal_area);
+
+    let mut title = vec![Span::styled(
+        format!(" {} | ", t("Dry run log")),
+        Style::default().fg(Color::Red),
+    )];
+    hint(&mut title, "Esc", "close");
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .border_style(Style::default().fg(Color::Red))
+        .title(Line::from(fit(title, modal_w.saturating_sub(2) as usize)));
+
+    let inner = block.inner(modal_area);
+    frame.render_widget(block, modal_area);
+
+    let transcript = app.transcript();
+    if transcript.is_empty() {
+        let empty = Line::from(Span::styled(
+            format!(" {}", t("Nothing skipped yet.")),
+            Style::default().fg(Color::DarkGray),
+        ));
+        frame.render_widget(Paragraph::new(empty), inner);
+        return;
+    }
+
+    // The newest entries matter most, so older ones scroll off the top.
+    let skip = transcript.len().saturating_sub(inner.height as usize);
+    let width = inner.width as usize;
+    let lines: Vec<Line> = transcript[skip..]
+        .iter()
+        .map(|entry| {
+            let span = Span::styled(
+                format!(" {entry}"),
+                Style::default().fg(Color::White),
+            );
+            Line::from(fit(vec![span], width))
+        })
+        .collect();
+    frame.render_widget(Paragraph::new(lines), inner);
+}
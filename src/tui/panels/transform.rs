@@ -0,0 +1,181 @@
+use crate::{
+    constants::TRANSFORMS,
+    i18n::{t, tf},
+    state::{App, Panel},
+    tui::{
+        key_binds::{fit, get_transform_keybinds, strike_unobservable},
+        panels::{
+            Handled, PanelWidget, focus_marker, highlight_symbol,
+            pending_marker,
+        },
+    },
+    utils::transform_label,
+};
+
+use std::time::Instant;
+
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::{
+    Frame,
+    layout::Rect,
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, BorderType, Borders, List, ListItem, ListState},
+};
+use wlx_monitors::WlTransform;
+
+/// The rotations and flips, with `p` to try one out before keeping it.
+#[derive(Default)]
+pub struct TransformPanel {
+    state: ListState,
+}
+
+impl PanelWidget for TransformPanel {
+    fn panel(&self) -> Panel {
+        Panel::Transform
+    }
+
+    fn render(&mut self, frame: &mut Frame, area: Rect, app: &App) {
+        self.state.select(app.transform_selection.selected());
+        render_transform(frame, app, &mut self.state, area);
+    }
+
+    fn handle_key(&mut self, key: KeyEvent, app: &mut App) -> Handled {
+        if key.code != KeyCode::Char('p') {
+            return Handled::No;
+        }
+        if let Err(e) = app.preview_transform(Instant::now()) {
+            app.set_error(tf("Failed to apply: {}", &[&e]));
+        }
+        Handled::Yes
+    }
+}
+
+fn render_transform(
+    frame: &mut Frame,
+    app: &App,
+    state: &mut ListState,
+    area: Rect,
+) {
+    let focused = app.panel == Panel::Transform;
+    let border_color = if focused {
+        Color::Blue
+    } else {
+        Color::DarkGray
+    };
+
+    let title = if focused {
+        let mut keys: Vec<Span> = Vec::new();
+        keys.push(Span::styled(
+            format!(" {}{} | ", focus_marker(app, true), t("Transform")),
+            Style::default().fg(Color::Blue),
+        ));
+        get_transform_keybinds(&mut keys);
+        strike_unobservable(&mut keys[1..], app);
+        Line::from(fit(keys, area.width.saturating_sub(2) as usize))
+    } else {
+        Line::from(Span::styled(
+            format!(" {} ", t("Transform")),
+            Style::default().fg(Color::DarkGray),
+        ))
+    };
+
+    let current_transform = app
+        .selected_monitor()
+        .map(|m| m.transform)
+        .unwrap_or(WlTransform::Normal);
+    let staged_transform = app
+        .selected_monitor()
+        .and_then(|m| app.pending_transforms.get(&m.name))
+        .copied();
+
+    let items: Vec<ListItem> = TRANSFORMS
+        .iter()
+        .map(|&transform| {
+            let is_current = transform == current_transform;
+            let marker = if is_current { " ✓" } else { "" };
+            let style = if is_current {
+                Style::default().fg(Color::Cyan)
+            } else {
+                Style::default().fg(Color::White)
+            };
+
+            Line::from(vec![
+                Span::styled(
+                    format!("  {}", t(transform_label(transform))),
+                    style,
+                ),
+                Span::styled(marker, Style::default().fg(Color::Green)),
+                Span::styled(
+                    if staged_transform == Some(transform) {
+                        pending_marker(app)
+                    } else {
+                        ""
+                    },
+                    Style::default().fg(Color::Yellow),
+                ),
+            ])
+            .into()
+        })
+        .collect();
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .border_style(Style::default().fg(border_color))
+        .title(title);
+
+    let list = List::new(items)
+        .block(block)
+        .highlight_symbol(highlight_symbol(app))
+        .highlight_style(
+            Style::default()
+                .fg(Color::Cyan)
+                .add_modifier(Modifier::BOLD),
+        );
+
+    frame.render_stateful_widget(list, area, state);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{app, monitor, screen};
+
+    #[test]
+    fn test_transform_panel_snapshot() {
+        let (mut app, _rx) = app();
+        app.set_monitors(vec![monitor("DP-1", 0, 0), monitor("DP-2", 1920, 0)]);
+        app.panel = Panel::Transform;
+        app.next();
+        let mut panel = TransformPanel::default();
+        let shown = screen(30, 10, |f| panel.render(f, f.area(), &app));
+        assert_eq!(
+            shown,
+            [
+                "╭ Transform | ↑↓ rotate  p p…╮",
+                "│     Normal ✓               │",
+                "│ ›   Rotate 90              │",
+                "│     Rotate 180             │",
+                "│     Rotate 270             │",
+                "│     Flipped                │",
+                "│     Flipped 90             │",
+                "│     Flipped 180            │",
+                "│     Flipped 270            │",
+                "╰────────────────────────────╯",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_high_contrast_marks_the_staged_transform() {
+        let (mut app, _rx) = app();
+        app.config.high_contrast = true;
+        app.set_monitors(vec![monitor("DP-1", 0, 0)]);
+        app.pending_transforms
+            .insert("DP-1".into(), WlTransform::Rotate180);
+        let mut panel = TransformPanel::default();
+        let shown = screen(30, 10, |f| panel.render(f, f.area(), &app));
+        assert_eq!(shown[3], "│     Rotate 180*            │");
+    }
+}
@@ -0,0 +1,68 @@
+use crate::{
+    compositor::diff::MonitorChange,
+    i18n::t,
+    tui::key_binds::{fit, hint},
+};
+
+use ratatui::{
+    Frame,
+    layout::Rect,
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, BorderType, Borders, Clear, Paragraph},
+};
+
+pub fn render_modal(frame: &mut Frame, changes: &[MonitorChange], area: Rect) {
+    let modal_w = area.width.saturating_sub(8).max(20).min(area.width);
+    let modal_h = area.height.saturating_sub(4).max(10).min(area.height);
+    let x = (area.width.saturating_sub(modal_w)) / 2;
+    let y = (area.height.saturating_sub(modal_h)) / 2;
+    let modal_area = Rect::new(x, y, modal_w, modal_h);
+
+    frame.render_widget(Clear, modal_area);
+
+    let mut title = vec![Span::styled(
+        format!(" {} | ", t("Untouched monitors")),
+        Style::default().fg(Color::Yellow),
+    )];
+    hint(&mut title, "i", "include");
+    hint(&mut title, "s", "skip");
+    hint(&mut title, "a", "always include");
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .border_style(Style::default().fg(Color::Yellow))
+        .title(Line::from(fit(title, modal_w.saturating_sub(2) as usize)));
+
+    let inner = block.inner(modal_area);
+    frame.render_widget(block, modal_area);
+
+    let width = inner.width as usize;
+    let intro = t("Saving would also change monitors you did not touch:");
+    let mut lines = vec![
+        Line::from(Span::styled(
+            format!(" {intro}"),
+            Style::default().fg(Color::White),
+        )),
+        Line::from(""),
+    ];
+    for change in changes {
+        lines.push(Line::from(Span::styled(
+            format!(" {}", change.name),
+            Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD),
+        )));
+        let removed = change.old.iter().map(|l| ("-", l, Color::Red));
+        let added = change.new.iter().map(|l| ("+", l, Color::Green));
+        for (sign, line, color) in removed.chain(added) {
+            lines.push(Line::from(Span::styled(
+                format!("   {sign} {line}"),
+                Style::default().fg(color),
+            )));
+        }
+    }
+    let lines: Vec<Line> = lines
+        .into_iter()
+        .map(|l| Line::from(fit(l.spans, width)))
+        .collect();
+    frame.render_widget(Paragraph::new(lines), inner);
+}
@@ -1,17 +1,63 @@
 use crate::{
-    state::{App, Panel},
-    tui::key_binds::get_workspaces_keybinds,
+    i18n::{t, tf},
+    state::{App, Panel, PromptKind},
+    tui::{
+        key_binds::{fit, get_workspaces_keybinds, strike_unobservable},
+        panels::{Handled, PanelWidget, focus_marker, highlight_symbol},
+    },
 };
 
+use crossterm::event::{KeyCode, KeyEvent};
 use ratatui::{
     layout::Rect,
     style::{Color, Modifier, Style},
     text::{Line, Span},
-    widgets::{Block, BorderType, Borders, List, ListItem},
+    widgets::{Block, BorderType, Borders, List, ListItem, ListState},
     Frame,
 };
 
-pub fn panel(frame: &mut Frame, app: &mut App, area: Rect) {
+/// Which monitor each workspace opens on.
+#[derive(Default)]
+pub struct WorkspacePanel {
+    state: ListState,
+}
+
+impl PanelWidget for WorkspacePanel {
+    fn panel(&self) -> Panel {
+        Panel::Workspace
+    }
+
+    fn render(&mut self, frame: &mut Frame, area: Rect, app: &App) {
+        self.state.select(app.workspace_row());
+        panel(frame, app, &mut self.state, area);
+    }
+
+    fn handle_key(&mut self, key: KeyEvent, app: &mut App) -> Handled {
+        let caps = app.compositor.capabilities();
+        let defaults = caps.supports_workspace_defaults;
+        match key.code {
+            KeyCode::Char('d') if defaults => app.toggle_default(),
+            KeyCode::Char('p') if defaults => app.toggle_persistent(),
+            KeyCode::Enter if !app.has_pending_workspaces() => {
+                app.open_fallback_editor()
+            }
+            KeyCode::Char('e') => app.open_prompt(PromptKind::WorkspaceId),
+            KeyCode::Char('f') => app.toggle_workspace_filter(),
+            KeyCode::Char('P') => app.toggle_pinned(),
+            KeyCode::Char('D') => app.distribute_workspaces(),
+            KeyCode::Char('X') => app.ask_clear_workspaces(),
+            _ => return Handled::No,
+        }
+        Handled::Yes
+    }
+}
+
+fn panel(
+    frame: &mut Frame,
+    app: &App,
+    state: &mut ListState,
+    area: Rect,
+) {
     let focused = app.panel == Panel::Workspace;
     let border_color = if focused {
         Color::Blue
@@ -19,14 +65,30 @@ pub fn panel(frame: &mut Frame, app: &mut App, area: Rect) {
         Color::DarkGray
     };
 
+    let followed = app
+        .selected_monitor()
+        .filter(|_| app.workspaces_follow_monitor())
+        .map(|m| m.name.as_str());
     let title = if focused {
+        let name = match followed {
+            Some(monitor) => tf("Wkspc of {}", &[&monitor]),
+            None => t("Wkspc").to_string(),
+        };
         let mut keys = Vec::new();
-        keys.push(Span::styled(" Wkspc ", Style::default().fg(Color::Blue)));
+        keys.push(Span::styled(
+            format!(" {}{name} ", focus_marker(app, true)),
+            Style::default().fg(Color::Blue),
+        ));
         get_workspaces_keybinds(&mut keys, app.compositor);
-        Line::from(keys)
+        strike_unobservable(&mut keys[1..], app);
+        Line::from(fit(keys, area.width.saturating_sub(2) as usize))
     } else {
+        let name = match followed {
+            Some(monitor) => tf("Workspaces of {}", &[&monitor]),
+            None => t("Workspaces").to_string(),
+        };
         Line::from(Span::styled(
-            " Workspaces ",
+            format!(" {name} "),
             Style::default().fg(Color::DarkGray),
         ))
     };
@@ -37,28 +99,42 @@ pub fn panel(frame: &mut Frame, app: &mut App, area: Rect) {
     } else {
         Color::DarkGray
     };
-    let supports_defaults = app.compositor.supports_workspace_defaults();
-    let monitors = app.monitors.clone();
+    let supports_defaults =
+        app.compositor.capabilities().supports_workspace_defaults;
     let pending_keys: Vec<usize> = app.pending_workspaces.keys().copied().collect();
+    // layout.toml holds the rules with canonical_layout, not the file.
+    let source_file = app
+        .comp_monitor_config_path
+        .file_name()
+        .filter(|_| !app.config.canonical_layout)
+        .map(|name| name.to_string_lossy().into_owned());
 
+    let leaving = app.workspace_leaving.map(|(idx, _)| idx);
     let items: Vec<ListItem> = app
-        .workspace_assignments
-        .iter()
-        .enumerate()
+        .visible_workspaces()
+        .into_iter()
+        .map(|idx| (idx, &app.workspace_assignments[idx]))
         .map(|(idx, _ws)| {
             let effective = app
                 .get_effective_workspace(idx)
                 .unwrap_or_else(|| _ws.clone());
             let monitor_name = effective
-                .monitor_idx
-                .and_then(|i| monitors.get(i))
-                .map(|m| m.name.as_str())
-                .unwrap_or("unassigned");
+                .monitor
+                .clone()
+                .or_else(|| {
+                    let name = effective.reserved.as_ref()?;
+                    Some(tf("{} (not connected)", &[name]))
+                })
+                .unwrap_or_else(|| "unassigned".to_string());
 
-            let is_assigned = effective.monitor_idx.is_some();
+            let is_assigned = effective.monitor.is_some();
             let is_pending = pending_keys.contains(&idx);
             let name_style = if is_pending {
                 Style::default().fg(Color::Yellow)
+            } else if effective.reserved.is_some() {
+                Style::default()
+                    .fg(Color::DarkGray)
+                    .add_modifier(Modifier::ITALIC)
             } else if is_assigned {
                 Style::default().fg(Color::Cyan)
             } else {
@@ -73,6 +149,13 @@ pub fn panel(frame: &mut Frame, app: &mut App, area: Rect) {
                 Span::styled("\u{2192} ", Style::default().fg(pending_color)),
                 Span::styled(monitor_name, name_style),
             ];
+            if !effective.fallbacks.is_empty() {
+                let then = tf("then {}", &[&effective.fallbacks.join(", ")]);
+                spans.push(Span::styled(
+                    format!(" ({then})"),
+                    Style::default().fg(Color::DarkGray),
+                ));
+            }
 
             if effective.is_default && supports_defaults {
                 spans.push(Span::styled(" [D]", Style::default().fg(Color::Green)));
@@ -81,11 +164,30 @@ pub fn panel(frame: &mut Frame, app: &mut App, area: Rect) {
                 spans.push(Span::styled(" [P]", Style::default().fg(Color::Yellow)));
             }
 
+            if effective.is_pinned {
+                spans.push(Span::styled(" [pin]", Style::default().fg(Color::Magenta)));
+            }
+
+            if let Some(file) = &source_file
+                && (effective.monitor.is_some() || effective.reserved.is_some())
+            {
+                let origin = match _ws.line {
+                    Some(line) => format!(" ({file}:{line})"),
+                    None => format!(" ({})", t("new")),
+                };
+                spans.push(Span::styled(origin, Style::default().fg(Color::DarkGray)));
+            }
+
             if is_pending {
                 spans.push(Span::styled(" *", Style::default().fg(Color::Yellow)));
             }
 
-            Line::from(spans).into()
+            let item = ListItem::new(Line::from(spans));
+            if leaving == Some(idx) {
+                item.style(Style::default().add_modifier(Modifier::REVERSED))
+            } else {
+                item
+            }
         })
         .collect();
 
@@ -97,12 +199,67 @@ pub fn panel(frame: &mut Frame, app: &mut App, area: Rect) {
 
     let list = List::new(items)
         .block(block)
-        .highlight_symbol(" \u{203a} ")
+        .highlight_symbol(highlight_symbol(app))
         .highlight_style(
             Style::default()
                 .fg(Color::Cyan)
                 .add_modifier(Modifier::BOLD),
         );
 
-    frame.render_stateful_widget(list, area, &mut app.workspace_state);
+    frame.render_stateful_widget(list, area, state);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compositor::Compositor;
+    use crate::test_support::{app, harness, monitor, screen};
+    use ratatui::{Terminal, backend::TestBackend};
+
+    #[test]
+    fn test_unfocused_workspaces_panel_snapshot() {
+        let (mut app, _rx) = app();
+        app.set_monitors(vec![monitor("DP-1", 0, 0), monitor("DP-2", 1920, 0)]);
+        let mut panel = WorkspacePanel::default();
+        let shown = screen(40, 6, |f| panel.render(f, f.area(), &app));
+        assert_eq!(
+            shown,
+            [
+                "╭ Workspaces ──────────────────────────╮",
+                "│ ›   WS 1 → unassigned                │",
+                "│     WS 2 → unassigned                │",
+                "│     WS 3 → unassigned                │",
+                "│     WS 4 → unassigned                │",
+                "╰──────────────────────────────────────╯",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_followed_monitor_titles_the_list_and_leaving_rows_stand_out() {
+        let existing = "monitor = DP-1, 1920x1080@60, 0x0, 1\n\
+                        workspace = 1, monitor:DP-1\n\
+                        workspace = 2, monitor:DP-2\n";
+        let (mut app, _recorder) =
+            harness(Compositor::Hyprland, Some(existing));
+        app.set_monitors(vec![monitor("DP-1", 0, 0), monitor("DP-2", 1920, 0)]);
+        app.toggle_workspace_filter();
+        app.cycle_workspace_monitor(true);
+
+        let mut panel = WorkspacePanel::default();
+        let mut terminal = Terminal::new(TestBackend::new(40, 4)).unwrap();
+        terminal
+            .draw(|f| panel.render(f, f.area(), &app))
+            .unwrap();
+        let buffer = terminal.backend().buffer();
+        let row = |y: u16| -> String {
+            (0..40).map(|x| buffer[(x, y)].symbol()).collect()
+        };
+        assert!(row(0).starts_with("╭ Workspaces of DP-1 "));
+        assert!(row(1).contains("WS 1 → DP-2"));
+        assert!(row(2).contains("WS 3 → unassigned"));
+        let leaving = buffer[(6, 1)].modifier;
+        assert!(leaving.contains(Modifier::REVERSED));
+        assert!(!buffer[(6, 2)].modifier.contains(Modifier::REVERSED));
+    }
 }
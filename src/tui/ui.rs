@@ -1,13 +1,31 @@
 use std::sync::mpsc::SendError;
-use std::{io, sync::mpsc::Receiver, time::Duration};
+use std::{
+    io::{self, Write},
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+        mpsc::{self, Receiver, Sender},
+    },
+    thread,
+    time::{Duration, Instant},
+};
 
-use crossterm::event::{self, Event, KeyCode};
+use crossterm::event::{self, Event, KeyCode, KeyModifiers};
 use ratatui::{DefaultTerminal, Terminal, backend::CrosstermBackend};
 use thiserror::Error;
 use wlx_monitors::WlMonitorEvent;
 
-use crate::state::{App, Panel};
-use crate::tui::layout;
+use crate::compositor::check::ConfigStatus;
+use crate::constants::{ACTIVE_POLL_MS, ACTIVE_WINDOW_MS};
+use crate::control::{Replies, Server};
+use crate::i18n::{t, tf};
+use crate::setup;
+use crate::state::{App, Confirm, Panel, UntouchedChoice};
+use crate::tui::{
+    layout,
+    panels::{Handled, Panels},
+};
+use crate::xwlm_config::NewMonitorAction;
 
 #[derive(Error, Debug)]
 pub enum TuiLoopError {
@@ -18,102 +36,504 @@ pub enum TuiLoopError {
     WlxMonitorActionError(#[from] SendError<wlx_monitors::WlMonitorAction>),
 }
 
+/// Picks how long the loop waits for input: short right after a key press
+/// or monitor event, so bursts and key repeats stay snappy, and the
+/// configured idle timeout otherwise. Whatever arrives while idle wakes the
+/// loop through [`Wakes`] rather than waiting out the timeout.
+pub struct PollSchedule {
+    idle: Duration,
+    last_activity: Option<Instant>,
+}
+
+impl PollSchedule {
+    pub fn new(idle: Duration) -> Self {
+        Self {
+            idle: idle.max(Duration::from_millis(ACTIVE_POLL_MS)),
+            last_activity: None,
+        }
+    }
+
+    pub fn touch(&mut self, now: Instant) {
+        self.last_activity = Some(now);
+    }
+
+    pub fn timeout(&self, now: Instant) -> Duration {
+        let active = self.last_activity.is_some_and(|t| {
+            now.saturating_duration_since(t)
+                < Duration::from_millis(ACTIVE_WINDOW_MS)
+        });
+        if active {
+            Duration::from_millis(ACTIVE_POLL_MS)
+        } else {
+            self.idle
+        }
+    }
+}
+
+/// Wakes the waiting loop as soon as one of its sources has something:
+/// a monitor event, a control call or terminal input.
+pub struct Wakes {
+    tx: Sender<()>,
+    rx: Receiver<()>,
+    stopped: Arc<AtomicBool>,
+}
+
+impl Wakes {
+    pub fn new() -> Self {
+        let (tx, rx) = mpsc::channel();
+        Self {
+            tx,
+            rx,
+            stopped: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    /// Hands back a receiver that gets everything `rx` does, waking the
+    /// loop as each item arrives.
+    pub fn source<T: Send + 'static>(&self, rx: Receiver<T>) -> Receiver<T> {
+        let (tx, forwarded) = mpsc::channel();
+        let wake = self.tx.clone();
+        thread::spawn(move || {
+            for item in rx {
+                if tx.send(item).is_err() {
+                    break;
+                }
+                let _ = wake.send(());
+            }
+        });
+        forwarded
+    }
+
+    /// Wakes the loop when terminal input is waiting. The input itself is
+    /// left for the loop, or setup, to read.
+    pub fn terminal(&self) {
+        let wake = self.tx.clone();
+        let stopped = Arc::clone(&self.stopped);
+        let interval = Duration::from_millis(ACTIVE_POLL_MS);
+        thread::spawn(move || {
+            while !stopped.load(Ordering::Relaxed) {
+                match event::poll(interval) {
+                    Ok(true) => {
+                        if wake.send(()).is_err() {
+                            break;
+                        }
+                        // Not again until it has been read.
+                        while !stopped.load(Ordering::Relaxed)
+                            && matches!(event::poll(Duration::ZERO), Ok(true))
+                        {
+                            thread::sleep(interval);
+                        }
+                    }
+                    Ok(false) => {}
+                    Err(_) => break,
+                }
+            }
+        });
+    }
+
+    /// Waits up to `timeout` for a wake, and says whether one came.
+    /// Wakes that piled up meanwhile are taken along.
+    pub fn wait(&self, timeout: Duration) -> bool {
+        let woke = self.rx.recv_timeout(timeout).is_ok();
+        while self.rx.try_recv().is_ok() {}
+        woke
+    }
+}
+
+impl Drop for Wakes {
+    fn drop(&mut self) {
+        self.stopped.store(true, Ordering::Relaxed);
+    }
+}
+
 pub fn tui_loop(
     app: &mut App,
     wlx_events: Receiver<WlMonitorEvent>,
+    control: Option<&mut Server>,
     terminal: &mut DefaultTerminal,
 ) -> Result<(), TuiLoopError> {
+    let wakes = Wakes::new();
+    let wlx_events = wakes.source(wlx_events);
+    let control = control.map(|c| {
+        c.calls =
+            wakes.source(std::mem::replace(&mut c.calls, mpsc::channel().1));
+        &*c
+    });
+    wakes.terminal();
+    let mut panels = Panels::default();
+    let mut replies = Replies::default();
+    let mut schedule =
+        PollSchedule::new(Duration::from_millis(app.config.idle_timeout_ms));
+    let mut dirty = true;
+    let mut compared = false;
     loop {
         let mut had_events = false;
         while let Ok(event) = wlx_events.try_recv() {
             had_events = true;
-            match event {
-                WlMonitorEvent::InitialState(monitors) => {
-                    app.set_monitors(monitors);
-                }
-                WlMonitorEvent::Changed(monitor) => {
-                    app.update_monitor(*monitor);
-                }
-                WlMonitorEvent::Removed { name, .. } => {
-                    app.remove_monitor(&name);
-                }
-                WlMonitorEvent::ActionFailed { action: _, reason } => {
-                    app.needs_save = false;
-                    app.set_error(format!("Action failed: {}", reason));
-                }
+            app.handle_event(event);
+        }
+
+        // Once the monitors are known, say if they run differently from
+        // the config, unless the config itself has a problem to show.
+        if had_events && !compared && !app.monitors.is_empty() {
+            compared = true;
+            if app.config_check.is_none() && !app.observing {
+                app.open_reconcile(true);
             }
         }
 
         if had_events {
             app.save_config();
+            schedule.touch(Instant::now());
+            dirty = true;
+        }
+
+        // Requests from the control socket, which wake the loop like
+        // monitor events do.
+        replies.flush(app, Instant::now(), had_events);
+        for call in control.into_iter().flat_map(|c| c.calls.try_iter()) {
+            replies.handle(app, call, Instant::now());
+            schedule.touch(Instant::now());
+            dirty = true;
+        }
+
+        let focusing = app.startup_wait(Instant::now()).is_some();
+        app.apply_startup_focus(Instant::now());
+        dirty |= focusing != app.startup_wait(Instant::now()).is_some();
+
+        // The countdown needs redrawing while a transform is previewed or
+        // a risky change waits for a key, and once more when a highlight
+        // or flash is over.
+        let fading =
+            app.workspace_leaving.is_some() || app.failure_flash.is_some();
+        app.check_transform_preview(Instant::now());
+        app.check_guarded(Instant::now());
+        app.check_workspace_leaving(Instant::now());
+        app.check_failure_flash(Instant::now());
+        let previewing = app.transform_preview.is_some()
+            || app.guarded.is_some()
+            || app.workspace_leaving.is_some()
+            || app.failure_flash.is_some();
+        dirty |= previewing || fading;
+
+        if app.take_bell() {
+            let backend = terminal.backend_mut();
+            backend.write_all(b"\x07")?;
+            backend.flush()?;
         }
 
-        render(terminal, app)?;
+        let reloading = app.is_reloading();
+        app.flush_saves(Instant::now());
+        dirty |= reloading != app.is_reloading();
 
-        if event::poll(Duration::from_millis(50))?
-            && let Event::Key(k) = event::read()?
-        {
+        if dirty {
+            render(terminal, app, &mut panels)?;
+            dirty = false;
+        }
+
+        let mut timeout = schedule.timeout(Instant::now());
+        if previewing {
+            timeout = timeout.min(Duration::from_millis(ACTIVE_POLL_MS));
+        }
+        if let Some(settle) = app.settle_time(Instant::now()) {
+            timeout = timeout.min(settle);
+        }
+        if let Some(wait) = app.startup_wait(Instant::now()) {
+            timeout = timeout.min(wait);
+        }
+        if let Some(wait) = replies.timeout(Instant::now()) {
+            timeout = timeout.min(wait);
+        }
+        wakes.wait(timeout);
+        if !event::poll(Duration::ZERO)? {
+            continue;
+        }
+        let event = event::read()?;
+        dirty = true;
+        if let Event::Resize(..) = event {
+            // Drawn afresh at the new size, with the zoom re-clamped.
+            terminal.autoresize()?;
+            app.fit_map();
+            continue;
+        }
+        if let Event::Key(k) = event {
+            schedule.touch(Instant::now());
             app.clear_error();
+            app.clear_status();
+            if app.observing && !observable(app, k.code) {
+                app.set_status(t("Only observing: nothing can be changed"));
+                continue;
+            }
 
-            if app.pending_last_toggle_monitor {
+            if app.guarded.is_some() {
+                // The screen may be unreadable, so any key will do.
                 match k.code {
-                    KeyCode::Char('y') => {
-                        if let Err(e) = app.toggle_monitor() {
-                            app.set_error(format!("Failed to toggle monitor: {}", e));
+                    KeyCode::Esc => app.revert_guarded(),
+                    _ => app.keep_guarded(),
+                }
+            } else if app.tour.is_some() {
+                match k.code {
+                    KeyCode::Esc => app.end_tour(),
+                    _ => app.tour_step(),
+                }
+            } else if app.prompt.is_some() {
+                match k.code {
+                    KeyCode::Enter => app.submit_prompt(),
+                    KeyCode::Esc => app.cancel_prompt(),
+                    KeyCode::Backspace => app.prompt_backspace(),
+                    KeyCode::Char(c) => app.prompt_input(c),
+                    _ => {}
+                }
+            } else if let Some(status) = &app.config_check {
+                let missing = *status == ConfigStatus::Missing;
+                match k.code {
+                    KeyCode::Char('s') if !app.is_dry_run() => {
+                        let comp = app.compositor;
+                        match setup::init(terminal, comp) {
+                            Ok(Some(config)) => app.rerun_setup(config),
+                            Ok(None) => {}
+                            Err(e) => app.set_error(tf(
+                                "Setup failed: {}",
+                                &[&e],
+                            )),
                         }
+                        terminal.clear()?;
+                    }
+                    KeyCode::Char('c') if missing => {
+                        app.create_monitor_config()
                     }
-                    _ => app.dismiss_warning(),
+                    KeyCode::Esc => app.dismiss_config_check(),
+                    _ => {}
+                }
+            } else if app.transcript_open {
+                if matches!(
+                    k.code,
+                    KeyCode::Esc | KeyCode::Char('q') | KeyCode::Char('L')
+                ) {
+                    app.transcript_open = false;
                 }
-            } else {
+            } else if app.apply_all.is_some() {
                 match k.code {
-                    KeyCode::Char('q') | KeyCode::Esc => {
+                    KeyCode::Enter | KeyCode::Char('y') => {
+                        app.confirm_apply_all()
+                    }
+                    KeyCode::Esc | KeyCode::Char('q') | KeyCode::Char('W') => {
+                        app.close_apply_all()
+                    }
+                    _ => {}
+                }
+            } else if app.show_stray_rules {
+                match k.code {
+                    KeyCode::Char('e') => app.extract_stray_rules(),
+                    KeyCode::Esc => app.dismiss_stray_rules(),
+                    _ => {}
+                }
+            } else if app.untouched_changes.is_some() {
+                let choice = match k.code {
+                    KeyCode::Char('i') => UntouchedChoice::Include,
+                    KeyCode::Char('s') | KeyCode::Esc => UntouchedChoice::Skip,
+                    KeyCode::Char('a') => UntouchedChoice::Always,
+                    _ => continue,
+                };
+                app.resolve_untouched(choice);
+            } else if app.new_monitor.is_some() {
+                let action = match k.code {
+                    KeyCode::Char('r') => NewMonitorAction::EnableRight,
+                    KeyCode::Char('s') => NewMonitorAction::EnableSaved,
+                    KeyCode::Esc => NewMonitorAction::Ignore,
+                    _ => continue,
+                };
+                app.resolve_new_monitor(action);
+            } else if app.timeline_view.is_some() {
+                match k.code {
+                    KeyCode::Up | KeyCode::Char('k') => {
+                        app.timeline_step(false)
+                    }
+                    KeyCode::Down | KeyCode::Char('j') => {
+                        app.timeline_step(true)
+                    }
+                    KeyCode::Esc | KeyCode::Char('q') | KeyCode::Char('T') => {
+                        app.close_timeline()
+                    }
+                    _ => {}
+                }
+            } else if app.clone_picker.is_some() {
+                match k.code {
+                    KeyCode::Up | KeyCode::Char('k') => {
+                        app.clone_picker_step(false)
+                    }
+                    KeyCode::Down | KeyCode::Char('j') => {
+                        app.clone_picker_step(true)
+                    }
+                    KeyCode::Left
+                    | KeyCode::Right
+                    | KeyCode::Char('h')
+                    | KeyCode::Char('l') => app.toggle_clone_adjacent(),
+                    KeyCode::Enter => app.confirm_clone(),
+                    KeyCode::Esc | KeyCode::Char('q') => {
+                        app.close_clone_picker()
+                    }
+                    _ => {}
+                }
+            } else if app.fallback_editor.is_some() {
+                match k.code {
+                    KeyCode::Up | KeyCode::Char('k') => {
+                        app.fallback_step(false)
+                    }
+                    KeyCode::Down | KeyCode::Char('j') => {
+                        app.fallback_step(true)
+                    }
+                    KeyCode::Char('K') => app.move_fallback(true),
+                    KeyCode::Char('J') => app.move_fallback(false),
+                    KeyCode::Char(' ') => app.toggle_fallback(),
+                    KeyCode::Enter => app.confirm_fallbacks(),
+                    KeyCode::Esc | KeyCode::Char('q') => {
+                        app.close_fallback_editor()
+                    }
+                    _ => {}
+                }
+            } else if let Some(editor) = &app.settings {
+                let editing = editor.input.is_some();
+                match k.code {
+                    KeyCode::Enter => app.settings_enter(),
+                    KeyCode::Esc => app.close_settings(),
+                    KeyCode::Backspace => app.settings_backspace(),
+                    KeyCode::Char(c) if editing => app.settings_input(c),
+                    KeyCode::Up | KeyCode::Char('k') => {
+                        app.settings_step(false)
+                    }
+                    KeyCode::Down | KeyCode::Char('j') => {
+                        app.settings_step(true)
+                    }
+                    KeyCode::Left | KeyCode::Char('h') => {
+                        app.settings_cycle(false)
+                    }
+                    KeyCode::Right | KeyCode::Char('l') => {
+                        app.settings_cycle(true)
+                    }
+                    KeyCode::Char(' ') => app.settings_enter(),
+                    KeyCode::Char('q') | KeyCode::Char(',') => {
+                        app.close_settings()
+                    }
+                    _ => {}
+                }
+            } else if app.reconcile.is_some() {
+                match k.code {
+                    KeyCode::Up | KeyCode::Char('k') => {
+                        app.reconcile_step(false)
+                    }
+                    KeyCode::Down | KeyCode::Char('j') => {
+                        app.reconcile_step(true)
+                    }
+                    KeyCode::Char('l') => app.adopt_live(false),
+                    KeyCode::Char('L') => app.adopt_live(true),
+                    KeyCode::Char('c') | KeyCode::Char('C') => {
+                        let all = k.code == KeyCode::Char('C');
+                        if let Err(e) = app.apply_config(all) {
+                            app.set_error(tf("Failed to apply: {}", &[&e]));
+                        }
+                    }
+                    KeyCode::Esc | KeyCode::Char('q') | KeyCode::Char('=') => {
+                        app.close_reconcile()
+                    }
+                    _ => {}
+                }
+            } else if app.history.is_some() {
+                match k.code {
+                    KeyCode::Up | KeyCode::Char('k') => {
+                        app.history_step(false)
+                    }
+                    KeyCode::Down | KeyCode::Char('j') => {
+                        app.history_step(true)
+                    }
+                    KeyCode::Enter => {
+                        if let Err(e) = app.restore_selected_snapshot() {
+                            app.set_error(tf("Failed to restore: {}", &[&e]));
+                        }
+                    }
+                    KeyCode::Esc | KeyCode::Char('q') | KeyCode::Char('H') => {
+                        app.close_history()
+                    }
+                    _ => {}
+                }
+            } else if app.confirm.is_some() {
+                match k.code {
+                    KeyCode::Char('y')
+                        if app.confirm == Some(Confirm::Quit) =>
+                    {
+                        app.confirm = None;
                         app.reset_positions();
                         break;
                     }
+                    KeyCode::Char('y') => {
+                        if let Err(e) = app.accept_confirm() {
+                            app.set_error(tf("Failed to apply: {}", &[&e]));
+                        }
+                    }
+                    _ => app.dismiss_confirm(),
+                }
+            } else if panels.handle_key(k, app) == Handled::No {
+                match k.code {
+                    KeyCode::Esc if app.transform_preview.is_some() => {
+                        app.revert_transform_preview()
+                    }
+                    KeyCode::Char('q') | KeyCode::Esc => {
+                        app.revert_transform_preview();
+                        if app.request_quit() {
+                            app.reset_positions();
+                            break;
+                        }
+                    }
+                    KeyCode::Char('!') => app.show_reload_failure(),
                     KeyCode::Up | KeyCode::Char('k') => app.previous(),
                     KeyCode::Down | KeyCode::Char('j') => app.next(),
                     KeyCode::Left | KeyCode::Char('h') => app.nav_left(),
                     KeyCode::Right | KeyCode::Char('l') => app.nav_right(),
-                    KeyCode::Tab => app.toggle_panel(),
+                    KeyCode::Tab => app.panel = panels.after(app.panel, app),
                     KeyCode::Char('t') => {
                         if let Err(e) = app.toggle_monitor() {
-                            app.set_error(format!("Failed to toggle monitor: {}", e));
+                            app.set_error(tf(
+                                "Failed to toggle monitor: {}",
+                                &[&e],
+                            ));
                         }
                     }
-                    KeyCode::Char('r') => app.reset_positions(),
-                    KeyCode::Char(']') => app.select_next_monitor(),
-                    KeyCode::Char('[') => app.select_prev_monitor(),
-                    KeyCode::Char('+') => {
-                        if app.panel == Panel::Monitor {
-                            app.zoom_in();
-                        } else {
-                            app.scale_up();
+                    KeyCode::Char('z')
+                        if k.modifiers.contains(KeyModifiers::CONTROL) =>
+                    {
+                        if let Err(e) = app.undo() {
+                            app.set_error(tf("Failed to undo: {}", &[&e]));
                         }
                     }
-                    KeyCode::Char('-') => {
-                        if app.panel == Panel::Monitor {
-                            app.zoom_out();
-                        } else {
-                            app.scale_down();
+                    KeyCode::Char('r')
+                        if k.modifiers.contains(KeyModifiers::CONTROL) =>
+                    {
+                        if let Err(e) = app.redo() {
+                            app.set_error(tf("Failed to redo: {}", &[&e]));
                         }
                     }
-                    KeyCode::Char('d') => {
-                        if app.panel == Panel::Workspace
-                            && app.compositor.supports_workspace_defaults()
-                        {
-                            app.toggle_default();
-                        }
+                    KeyCode::Char('r') => app.reset_positions(),
+                    KeyCode::Char('H') => app.open_history(),
+                    KeyCode::Char('T') => app.open_timeline(),
+                    KeyCode::Char('=') => app.open_reconcile(false),
+                    KeyCode::Char('B') => app.write_bug_report_dump(),
+                    KeyCode::Char(',') => app.open_settings(),
+                    KeyCode::Char('W') => app.open_apply_all(),
+                    KeyCode::Enter
+                        if k.modifiers.contains(KeyModifiers::CONTROL) =>
+                    {
+                        app.open_apply_all()
                     }
-                    KeyCode::Char('p') => {
-                        if app.panel == Panel::Workspace
-                            && app.compositor.supports_workspace_defaults()
-                        {
-                            app.toggle_persistent();
-                        }
+                    KeyCode::Char('L') if app.is_dry_run() => {
+                        app.transcript_open = true
                     }
+                    KeyCode::Char(']') => app.select_next_monitor(),
+                    KeyCode::Char('[') => app.select_prev_monitor(),
+                    KeyCode::Char('+') => app.scale_up(),
+                    KeyCode::Char('-') => app.scale_down(),
                     KeyCode::Enter => {
                         if let Err(e) = app.apply_action() {
-                            app.set_error(format!("Failed to apply: {}", e));
+                            app.set_error(tf("Failed to apply: {}", &[&e]));
                         }
                     }
                     _ => {}
@@ -125,10 +545,143 @@ pub fn tui_loop(
     Ok(())
 }
 
+/// Whether `key` only looks around, so it works while observing. Moving
+/// through lists and panels does; what stages or sends a change does not.
+fn observable(app: &App, key: KeyCode) -> bool {
+    if app.tour.is_some() {
+        return true;
+    }
+    let browsing = app.timeline_view.is_some()
+        || app.history.is_some()
+        || app.reconcile.is_some();
+    // Up and down stage a move or a scale there instead of selecting.
+    let lists = !matches!(app.panel, Panel::Monitor | Panel::Scale);
+    match key {
+        KeyCode::Esc | KeyCode::Tab => true,
+        KeyCode::Char('q' | 'T' | 'H' | '=' | '[' | ']' | '!') => true,
+        KeyCode::Char('+' | '-') => app.panel == Panel::Monitor && !browsing,
+        KeyCode::Up | KeyCode::Down | KeyCode::Char('k' | 'j') => {
+            browsing || lists
+        }
+        // Filters the Modes or Workspaces list.
+        KeyCode::Char('f') => {
+            !browsing && matches!(app.panel, Panel::Mode | Panel::Workspace)
+        }
+        _ => false,
+    }
+}
+
 pub fn render(
     terminal: &mut Terminal<CrosstermBackend<io::Stdout>>,
     app: &mut App,
+    panels: &mut Panels,
 ) -> io::Result<()> {
-    terminal.draw(|f| layout::draw(f, app))?;
+    terminal.draw(|f| layout::draw(f, app, panels))?;
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_idle_schedule_waits_the_idle_timeout() {
+        let schedule = PollSchedule::new(Duration::from_millis(5000));
+        assert_eq!(
+            schedule.timeout(Instant::now()),
+            Duration::from_millis(5000)
+        );
+    }
+
+    #[test]
+    fn test_activity_shortens_the_poll_for_a_while() {
+        let start = Instant::now();
+        let mut schedule = PollSchedule::new(Duration::from_millis(1000));
+        schedule.touch(start);
+        let active = Duration::from_millis(ACTIVE_POLL_MS);
+        assert_eq!(schedule.timeout(start), active);
+        let window = Duration::from_millis(ACTIVE_WINDOW_MS);
+        assert_eq!(schedule.timeout(start + window / 2), active);
+        assert_eq!(
+            schedule.timeout(start + window),
+            Duration::from_millis(1000)
+        );
+    }
+
+    #[test]
+    fn test_a_monitor_event_wakes_an_idle_loop() {
+        let wakes = Wakes::new();
+        let (tx, rx) = mpsc::channel();
+        let events = wakes.source(rx);
+        let schedule = PollSchedule::new(Duration::from_secs(60));
+        let start = Instant::now();
+        tx.send(1).unwrap();
+        assert!(wakes.wait(schedule.timeout(start)));
+        assert!(start.elapsed() < Duration::from_secs(5));
+        assert_eq!(events.try_recv(), Ok(1));
+    }
+
+    #[test]
+    fn test_without_wakes_the_loop_waits_out_the_timeout() {
+        let wakes = Wakes::new();
+        let (_tx, rx) = mpsc::channel::<()>();
+        let _events = wakes.source(rx);
+        assert!(!wakes.wait(Duration::from_millis(10)));
+    }
+
+    #[test]
+    fn test_an_idle_loop_wakes_once_per_idle_timeout() {
+        let wakes = Wakes::new();
+        let idle = Duration::from_millis(60);
+        let window = idle * 10;
+        let mut schedule = PollSchedule::new(idle);
+        let start = Instant::now();
+        let mut wakeups = 0;
+        while start.elapsed() < window {
+            assert!(!wakes.wait(schedule.timeout(Instant::now())));
+            wakeups += 1;
+        }
+        assert!(wakeups <= window.div_duration_f64(idle) as u32, "{wakeups}");
+
+        // A burst of events switches to the active poll.
+        let (tx, rx) = mpsc::channel();
+        let events = wakes.source(rx);
+        for i in 0..5 {
+            tx.send(i).unwrap();
+        }
+        assert!(wakes.wait(schedule.timeout(Instant::now())));
+        schedule.touch(Instant::now());
+        assert_eq!(
+            schedule.timeout(Instant::now()),
+            Duration::from_millis(ACTIVE_POLL_MS)
+        );
+        assert_eq!(events.recv().unwrap(), 0);
+    }
+
+    #[test]
+    fn test_idle_timeout_is_never_shorter_than_the_active_poll() {
+        let schedule = PollSchedule::new(Duration::ZERO);
+        assert_eq!(
+            schedule.timeout(Instant::now()),
+            Duration::from_millis(ACTIVE_POLL_MS)
+        );
+    }
+
+    #[test]
+    fn test_an_observer_can_move_around_but_not_change_anything() {
+        let (mut app, _rx) = crate::test_support::app();
+        app.observe();
+        app.close_timeline();
+        app.panel = Panel::Mode;
+        assert!(observable(&app, KeyCode::Down));
+        assert!(observable(&app, KeyCode::Char('f')));
+        assert!(observable(&app, KeyCode::Tab));
+        assert!(!observable(&app, KeyCode::Enter));
+        app.panel = Panel::Scale;
+        assert!(!observable(&app, KeyCode::Down));
+        assert!(!observable(&app, KeyCode::Right));
+        app.panel = Panel::Monitor;
+        assert!(!observable(&app, KeyCode::Up));
+        assert!(!observable(&app, KeyCode::Char('t')));
+    }
+}
@@ -0,0 +1,78 @@
+use serde::{Deserialize, Serialize};
+use std::{fs, path::PathBuf};
+
+use crate::{history, xwlm_config::ConfigError};
+
+/// How the TUI was left, for the next run to start the same way. Unlike
+/// the settings in config.toml, nothing here is meant to be edited.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct UiState {
+    /// The Workspaces panel lists only the selected monitor's workspaces
+    /// and the unassigned ones.
+    pub workspaces_follow_monitor: bool,
+    /// The first-launch tour was shown through or skipped. Only a run
+    /// right after setup starts without it, so existing configs count as
+    /// toured.
+    pub toured: bool,
+}
+
+impl Default for UiState {
+    fn default() -> Self {
+        Self {
+            workspaces_follow_monitor: false,
+            toured: true,
+        }
+    }
+}
+
+pub fn ui_state_path() -> Option<PathBuf> {
+    history::state_path("ui.toml")
+}
+
+pub fn parse(content: &str) -> Result<UiState, ConfigError> {
+    Ok(toml::from_str(content)?)
+}
+
+pub fn save(state: &UiState) -> Result<(), ConfigError> {
+    let Some(path) = ui_state_path() else {
+        return Ok(());
+    };
+    save_to_path(&path, state)
+}
+
+fn save_to_path(path: &PathBuf, state: &UiState) -> Result<(), ConfigError> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| ConfigError::Write {
+            path: parent.to_string_lossy().into(),
+            source: e,
+        })?;
+    }
+    fs::write(path, toml::to_string(state)?).map_err(|e| ConfigError::Write {
+        path: path.to_string_lossy().into(),
+        source: e,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+
+    #[test]
+    fn test_ui_state_roundtrips_through_toml() {
+        let state = UiState {
+            workspaces_follow_monitor: true,
+            toured: true,
+        };
+        let dir = env::temp_dir()
+            .join(format!("xwlm-test-ui-state-{}", std::process::id()));
+        let path = dir.join("ui.toml");
+        save_to_path(&path, &state).unwrap();
+        let content = fs::read_to_string(&path).unwrap();
+        fs::remove_dir_all(dir).unwrap();
+        assert_eq!(parse(&content).unwrap(), state);
+        assert_eq!(parse("").unwrap(), UiState::default());
+        assert!(parse("workspaces_follow_monitor = true").unwrap().toured);
+    }
+}
@@ -0,0 +1,252 @@
+use std::fmt;
+
+use wlx_monitors::{WlMonitor, WlMonitorAction};
+
+use crate::effects::describe;
+
+/// How many monitor states [`UndoStack`] keeps to go back to.
+const DEPTH: usize = 20;
+
+/// Monitor states to go back to and forward again, newest last. Each is
+/// kept as the actions that bring the monitors back to it.
+#[derive(Default)]
+pub struct UndoStack {
+    undo: Vec<Vec<WlMonitorAction>>,
+    redo: Vec<Vec<WlMonitorAction>>,
+}
+
+impl fmt::Debug for UndoStack {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("UndoStack")
+            .field("undo", &self.undo.len())
+            .field("redo", &self.redo.len())
+            .finish()
+    }
+}
+
+impl UndoStack {
+    /// Remembers `monitors` before a change and forgets what was undone.
+    /// The same state twice in a row is kept once.
+    pub fn push(&mut self, monitors: &[WlMonitor]) {
+        self.redo.clear();
+        let actions = snapshot(monitors);
+        if self.undo.last().is_some_and(|last| same(last, &actions)) {
+            return;
+        }
+        keep(&mut self.undo, actions);
+    }
+
+    /// The state before the last change, remembering `monitors` for
+    /// [`UndoStack::redo`].
+    pub fn undo(
+        &mut self,
+        monitors: &[WlMonitor],
+    ) -> Option<Vec<WlMonitorAction>> {
+        let actions = self.undo.pop()?;
+        keep(&mut self.redo, snapshot(monitors));
+        Some(actions)
+    }
+
+    /// The state the last undo left, remembering `monitors` for
+    /// [`UndoStack::undo`].
+    pub fn redo(
+        &mut self,
+        monitors: &[WlMonitor],
+    ) -> Option<Vec<WlMonitorAction>> {
+        let actions = self.redo.pop()?;
+        keep(&mut self.undo, snapshot(monitors));
+        Some(actions)
+    }
+
+    /// What [`UndoStack::undo`], or with `redo` [`UndoStack::redo`], would
+    /// hand out next, leaving it in place.
+    pub fn peek(&self, redo: bool) -> Option<&[WlMonitorAction]> {
+        let stack = if redo { &self.redo } else { &self.undo };
+        stack.last().map(Vec::as_slice)
+    }
+
+    /// How many changes can be undone.
+    pub fn depth(&self) -> usize {
+        self.undo.len()
+    }
+}
+
+fn keep(stack: &mut Vec<Vec<WlMonitorAction>>, actions: Vec<WlMonitorAction>) {
+    if stack.len() == DEPTH {
+        stack.remove(0);
+    }
+    stack.push(actions);
+}
+
+fn same(a: &[WlMonitorAction], b: &[WlMonitorAction]) -> bool {
+    a.len() == b.len()
+        && a.iter().zip(b).all(|(a, b)| describe(a) == describe(b))
+}
+
+/// The actions that set every monitor as it is now. A monitor that is off
+/// gets only a toggle without mode or position.
+pub fn snapshot(monitors: &[WlMonitor]) -> Vec<WlMonitorAction> {
+    let mut actions = Vec::new();
+    for m in monitors {
+        let name = m.name.clone();
+        if !m.enabled {
+            actions.push(WlMonitorAction::Toggle {
+                name,
+                mode: None,
+                position: None,
+            });
+            continue;
+        }
+        let mode = m.modes.iter().find(|mode| mode.is_current).map(|mode| {
+            let (w, h) = (mode.resolution.width, mode.resolution.height);
+            (w, h, mode.refresh_rate)
+        });
+        actions.push(WlMonitorAction::Toggle {
+            name: name.clone(),
+            mode,
+            position: Some((m.position.x, m.position.y)),
+        });
+        if let Some((width, height, refresh_rate)) = mode {
+            actions.push(WlMonitorAction::SwitchMode {
+                name: name.clone(),
+                width,
+                height,
+                refresh_rate,
+            });
+        }
+        actions.push(WlMonitorAction::SetScale {
+            name: name.clone(),
+            scale: m.scale,
+        });
+        actions.push(WlMonitorAction::SetTransform {
+            name: name.clone(),
+            transform: m.transform,
+        });
+        actions.push(WlMonitorAction::SetPosition {
+            name,
+            x: m.position.x,
+            y: m.position.y,
+        });
+    }
+    actions
+}
+
+/// The monitor `action` is for.
+pub fn target(action: &WlMonitorAction) -> &str {
+    match action {
+        WlMonitorAction::Toggle { name, .. }
+        | WlMonitorAction::SwitchMode { name, .. }
+        | WlMonitorAction::SetScale { name, .. }
+        | WlMonitorAction::SetTransform { name, .. }
+        | WlMonitorAction::SetPosition { name, .. } => name,
+    }
+}
+
+/// Whether replaying `action` would change `monitor`. A toggle only
+/// counts when the monitor is on and should be off or the other way
+/// round.
+pub fn changes(action: &WlMonitorAction, monitor: &WlMonitor) -> bool {
+    match action {
+        WlMonitorAction::Toggle { position, .. } => {
+            monitor.enabled != position.is_some()
+        }
+        WlMonitorAction::SwitchMode {
+            width,
+            height,
+            refresh_rate,
+            ..
+        } => !monitor.modes.iter().any(|m| {
+            m.is_current
+                && (m.resolution.width, m.resolution.height, m.refresh_rate)
+                    == (*width, *height, *refresh_rate)
+        }),
+        WlMonitorAction::SetScale { scale, .. } => {
+            (monitor.scale - scale).abs() > 0.001
+        }
+        WlMonitorAction::SetTransform { transform, .. } => {
+            monitor.transform != *transform
+        }
+        WlMonitorAction::SetPosition { x, y, .. } => {
+            (monitor.position.x, monitor.position.y) != (*x, *y)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{disabled, monitor};
+
+    fn described(actions: &[WlMonitorAction]) -> Vec<String> {
+        actions.iter().map(describe).collect()
+    }
+
+    #[test]
+    fn test_undo_and_redo_walk_the_snapshots() {
+        let before = [monitor("DP-1", 0, 0), disabled("DP-2")];
+        let after = [monitor("DP-1", 1920, 0), monitor("DP-2", 0, 0)];
+        let mut stack = UndoStack::default();
+        stack.push(&before);
+        stack.push(&before);
+        assert_eq!(stack.depth(), 1);
+
+        let undone = stack.undo(&after).unwrap();
+        assert_eq!(
+            described(&undone),
+            [
+                "toggle DP-1 at 1920x1080@60 to 0,0",
+                "switch DP-1 to 1920x1080@60",
+                "scale DP-1 to 1.00",
+                "transform DP-1 to Normal",
+                "move DP-1 to 0,0",
+                "toggle DP-2",
+            ]
+        );
+        assert_eq!(stack.depth(), 0);
+        assert!(stack.undo(&before).is_none());
+
+        let redone = stack.redo(&before).unwrap();
+        assert!(described(&redone).contains(&"move DP-1 to 1920,0".into()));
+        assert_eq!(stack.depth(), 1);
+
+        // A new change forgets what was undone.
+        stack.undo(&after);
+        stack.push(&after);
+        assert!(stack.redo(&after).is_none());
+    }
+
+    #[test]
+    fn test_only_the_last_twenty_states_are_kept() {
+        let mut stack = UndoStack::default();
+        for x in 0..25 {
+            stack.push(&[monitor("DP-1", x, 0)]);
+        }
+        assert_eq!(stack.depth(), DEPTH);
+        let mut oldest = None;
+        while let Some(actions) = stack.undo(&[monitor("DP-1", 99, 0)]) {
+            oldest = Some(actions);
+        }
+        let oldest = described(&oldest.unwrap());
+        assert_eq!(oldest.last().unwrap(), "move DP-1 to 5,0");
+    }
+
+    #[test]
+    fn test_replay_skips_what_already_matches() {
+        let now = monitor("DP-1", 0, 0);
+        let [toggle, mode, scale, transform, position] =
+            snapshot(&[monitor("DP-1", 1920, 0)])
+                .try_into()
+                .ok()
+                .unwrap();
+        assert!(!changes(&toggle, &now));
+        assert!(!changes(&mode, &now));
+        assert!(!changes(&scale, &now));
+        assert!(!changes(&transform, &now));
+        assert!(changes(&position, &now));
+        assert_eq!(target(&position), "DP-1");
+
+        let [off] = snapshot(&[disabled("DP-1")]).try_into().ok().unwrap();
+        assert!(changes(&off, &now));
+        assert!(!changes(&off, &disabled("DP-1")));
+    }
+}
@@ -1,40 +1,78 @@
-use std::{env, io, path::PathBuf};
+use std::{
+    cmp::Ordering,
+    env,
+    ffi::OsString,
+    fs, io,
+    path::{Path, PathBuf},
+};
 
 use thiserror::Error;
-use wlx_monitors::{WlMonitor, WlTransform};
+use wlx_monitors::{WlMonitor, WlResolution, WlTransform};
+
+use crate::{constants::PLACEHOLDER_RESOLUTION, scale};
 
 #[derive(Error, Debug)]
 pub enum UtilsError {
     #[error("path must start with ~/")]
     NoTilde,
 
-    #[error("no home variable was found")]
-    NoHome,
+    #[error(
+        "HOME is not set and /etc/passwd has no home directory for this \
+         user, so {0} cannot be found"
+    )]
+    NoHome(String),
 
     #[error(transparent)]
     Io(#[from] io::Error),
 }
 
 pub fn expand_tilde(path: &str) -> Result<PathBuf, UtilsError> {
+    expand_tilde_in(path, home_dir())
+}
+
+fn expand_tilde_in(
+    path: &str,
+    home: Option<PathBuf>,
+) -> Result<PathBuf, UtilsError> {
     let Some(clean_path) = path.strip_prefix("~/") else {
         return Err(UtilsError::NoTilde);
     };
 
-    let home = env::var("HOME").map_err(|_| UtilsError::NoHome)?;
-    Ok(PathBuf::from(home).join(clean_path))
+    let home = home.ok_or_else(|| UtilsError::NoHome(path.to_string()))?;
+    Ok(home.join(clean_path))
 }
 
-pub fn monitor_config_exists(path: &str) -> bool {
-    let path_buf = if path.starts_with("~/") {
-        match expand_tilde(path) {
-            Ok(p) => p,
-            Err(_) => return false,
-        }
-    } else {
-        PathBuf::from(path)
-    };
+unsafe extern "C" {
+    safe fn getuid() -> u32;
+}
+
+/// The user's home directory: `HOME`, or the user's /etc/passwd entry
+/// when it is unset or empty, as under some systemd units.
+pub fn home_dir() -> Option<PathBuf> {
+    home_from(env::var_os("HOME"), getuid(), || {
+        fs::read_to_string("/etc/passwd").unwrap_or_default()
+    })
+}
 
-    path_buf.exists()
+/// `passwd` is only read when `home` is of no use.
+fn home_from(
+    home: Option<OsString>,
+    uid: u32,
+    passwd: impl FnOnce() -> String,
+) -> Option<PathBuf> {
+    if let Some(home) = home.filter(|h| !h.is_empty()) {
+        return Some(PathBuf::from(home));
+    }
+    let uid = uid.to_string();
+    passwd().lines().find_map(|line| {
+        let fields: Vec<&str> = line.split(':').collect();
+        match fields[..] {
+            [_, _, id, _, _, dir, ..] if id == uid && !dir.is_empty() => {
+                Some(PathBuf::from(dir))
+            }
+            _ => None,
+        }
+    })
 }
 
 pub fn monitor_resolution(monitor: &WlMonitor) -> (i32, i32) {
@@ -47,18 +85,21 @@ pub fn monitor_resolution(monitor: &WlMonitor) -> (i32, i32) {
     if let Some(mode) = monitor.modes.first() {
         return (mode.resolution.width, mode.resolution.height);
     }
-    (monitor.resolution.width, monitor.resolution.height)
+    let WlResolution { width, height } = monitor.resolution;
+    if width > 0 && height > 0 {
+        (width, height)
+    } else {
+        PLACEHOLDER_RESOLUTION
+    }
 }
 
+/// The size the monitor takes up in the layout, in logical pixels.
 pub fn effective_dimensions(monitor: &WlMonitor) -> (i32, i32) {
-    let (w, h) = monitor_resolution(monitor);
-    match monitor.transform {
-        WlTransform::Rotate90
-        | WlTransform::Rotate270
-        | WlTransform::Flipped90
-        | WlTransform::Flipped270 => (h, w),
-        _ => (w, h),
-    }
+    scale::logical_extent(
+        monitor_resolution(monitor),
+        monitor.transform,
+        monitor.scale,
+    )
 }
 
 pub fn transform_label(t: WlTransform) -> &'static str {
@@ -73,3 +114,169 @@ pub fn transform_label(t: WlTransform) -> &'static str {
         WlTransform::Flipped270 => "Flipped 270",
     }
 }
+
+/// Orders connector names so embedded numbers compare by value, e.g.
+/// `DP-2` before `DP-10`.
+pub fn connector_order(a: &str, b: &str) -> Ordering {
+    let (mut x, mut y) = (a, b);
+    loop {
+        let (Some(cx), Some(cy)) = (x.chars().next(), y.chars().next()) else {
+            return x.len().cmp(&y.len()).then_with(|| a.cmp(b));
+        };
+        if cx.is_ascii_digit() && cy.is_ascii_digit() {
+            let (nx, rx) = split_digits(x);
+            let (ny, ry) = split_digits(y);
+            let (nx, ny) =
+                (nx.trim_start_matches('0'), ny.trim_start_matches('0'));
+            let ord = nx.len().cmp(&ny.len()).then_with(|| nx.cmp(ny));
+            if ord != Ordering::Equal {
+                return ord;
+            }
+            (x, y) = (rx, ry);
+        } else if cx != cy {
+            return cx.cmp(&cy);
+        } else {
+            (x, y) = (&x[cx.len_utf8()..], &y[cy.len_utf8()..]);
+        }
+    }
+}
+
+/// Writes `content` to a `.xwlm_tmp_<pid>` file next to `path` and moves
+/// it over `path`, so a crash midway leaves the old file whole. The new
+/// file keeps the old one's permissions, such as a script's exec bit, and
+/// a symlinked config is written where the link points.
+pub fn write_atomic(path: &Path, content: &str) -> io::Result<()> {
+    let path = fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+    let mut name = path
+        .file_name()
+        .ok_or_else(|| io::Error::other("no file name to write to"))?
+        .to_os_string();
+    name.push(format!(".xwlm_tmp_{}", std::process::id()));
+    let tmp = path.with_file_name(name);
+    let written = fs::write(&tmp, content).and_then(|()| {
+        if let Ok(meta) = fs::metadata(&path) {
+            fs::set_permissions(&tmp, meta.permissions())?;
+        }
+        fs::rename(&tmp, &path)
+    });
+    if written.is_err() {
+        let _ = fs::remove_file(&tmp);
+    }
+    written
+}
+
+/// Lets whoever may read `path` also run it, as `chmod +x` does.
+pub fn set_executable(path: &Path) -> io::Result<()> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+
+        let mut perms = fs::metadata(path)?.permissions();
+        let mode = perms.mode() | (perms.mode() & 0o444) >> 2;
+        if mode != perms.mode() {
+            perms.set_mode(mode);
+            fs::set_permissions(path, perms)?;
+        }
+    }
+    #[cfg(not(unix))]
+    let _ = path;
+    Ok(())
+}
+
+fn split_digits(s: &str) -> (&str, &str) {
+    let end = s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len());
+    s.split_at(end)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PASSWD: &str = "root:x:0:0:root:/root:/bin/bash\n\
+                          svc:x:990:990::/:/usr/bin/nologin\n\
+                          ana:x:1000:1000:Ana:/home/ana:/bin/zsh\n\
+                          nohome:x:1001:1001:::/bin/sh\n";
+
+    #[test]
+    fn test_home_falls_back_to_the_passwd_entry() {
+        let home = |var: Option<&str>, uid| {
+            home_from(var.map(OsString::from), uid, || PASSWD.to_string())
+        };
+        let ana = Some(PathBuf::from("/home/ana"));
+        assert_eq!(home(Some("/tmp/h"), 1000), Some(PathBuf::from("/tmp/h")));
+        assert_eq!(home(None, 1000), ana);
+        assert_eq!(home(Some(""), 1000), ana);
+        assert_eq!(home(None, 1001), None);
+        assert_eq!(home(None, 4242), None);
+
+        let set = Some(OsString::from("/tmp/h"));
+        let unread = home_from(set, 1000, || panic!("read /etc/passwd"));
+        assert_eq!(unread, Some(PathBuf::from("/tmp/h")));
+    }
+
+    #[test]
+    fn test_no_home_names_what_needed_it() {
+        let path = "~/.config/xwlm/config.toml";
+        let home = home_from(None, 1001, || PASSWD.to_string());
+        let err = expand_tilde_in(path, home).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "HOME is not set and /etc/passwd has no home directory for this \
+             user, so ~/.config/xwlm/config.toml cannot be found"
+        );
+        // Never a path relative to wherever xwlm was started.
+        let found = expand_tilde_in(path, Some("/home/ana".into())).unwrap();
+        assert!(found.is_absolute());
+    }
+
+    #[test]
+    fn test_connector_order_compares_numbers_by_value() {
+        let mut names = vec!["DP-10", "HDMI-A-1", "DP-2", "eDP-1", "DP-1"];
+        names.sort_by(|a, b| connector_order(a, b));
+        assert_eq!(names, vec!["DP-1", "DP-2", "DP-10", "HDMI-A-1", "eDP-1"]);
+    }
+
+    #[test]
+    fn test_connector_order_is_total() {
+        assert_eq!(connector_order("DP-1", "DP-1"), Ordering::Equal);
+        assert_eq!(connector_order("DP-01", "DP-1"), Ordering::Less);
+        assert_eq!(connector_order("DP", "DP-1"), Ordering::Less);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_write_atomic_leaves_the_old_file_when_it_fails() {
+        use std::os::unix::fs::{PermissionsExt, symlink};
+
+        let dir = env::temp_dir()
+            .join(format!("xwlm-atomic-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("monitors.conf");
+        fs::write(&path, "monitor = DP-1, preferred, auto, 1\n").unwrap();
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o755)).unwrap();
+
+        // The temporary file cannot be created where a directory is.
+        let tmp = format!("monitors.conf.xwlm_tmp_{}", std::process::id());
+        let tmp = dir.join(tmp);
+        fs::create_dir(&tmp).unwrap();
+        assert!(write_atomic(&path, "half").is_err());
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            "monitor = DP-1, preferred, auto, 1\n"
+        );
+        fs::remove_dir(&tmp).unwrap();
+
+        let link = dir.join("link.conf");
+        symlink(&path, &link).unwrap();
+        write_atomic(&link, "monitor = DP-1, disable\n").unwrap();
+        assert!(fs::symlink_metadata(&link).unwrap().is_symlink());
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            "monitor = DP-1, disable\n"
+        );
+        let mode = fs::metadata(&path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o755);
+        assert!(!tmp.exists());
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -1,13 +1,13 @@
 use serde::Deserialize;
 use serde::Serialize;
-use std::{fs, io, path::PathBuf};
+use std::{collections::BTreeMap, fs, io, path::PathBuf};
 use thiserror::Error;
 
 use crate::utils;
 
 #[derive(Error, Debug)]
 pub enum ConfigError {
-    #[error("invalid config path: {0}")]
+    #[error(transparent)]
     Path(#[from] utils::UtilsError),
 
     #[error("failed to read config at {path}: {source}")]
@@ -34,11 +34,211 @@ pub enum ConfigError {
     Serialize(#[from] toml::ser::Error),
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+/// A mode the monitor doesn't advertise. `active` marks the one written
+/// to the compositor config as a modeline / custom mode.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CustomMode {
+    pub monitor: String,
+    pub width: i32,
+    pub height: i32,
+    pub refresh_rate: i32,
+    #[serde(default)]
+    pub active: bool,
+}
+
+/// What to do with a monitor that connects disabled or on top of another.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NewMonitorAction {
+    #[default]
+    Ignore,
+    /// Enable it at its preferred mode right of the layout.
+    EnableRight,
+    /// Enable it where the monitor config puts it, else right of the
+    /// layout.
+    EnableSaved,
+    Ask,
+}
+
+/// How a failed monitor action or compositor reload is signalled besides
+/// the status line.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FailureAlert {
+    #[default]
+    None,
+    /// The terminal bell.
+    Bell,
+    /// A short red flash of the affected panel's border.
+    Flash,
+    Both,
+}
+
+impl FailureAlert {
+    pub fn bell(self) -> bool {
+        matches!(self, FailureAlert::Bell | FailureAlert::Both)
+    }
+
+    pub fn flash(self) -> bool {
+        matches!(self, FailureAlert::Flash | FailureAlert::Both)
+    }
+}
+
+/// What a monitor of one model starts with the first time it shows up in
+/// a session, unless the monitor config says otherwise.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct MonitorDefaults {
+    /// Named the way sway names it: "normal", "90", "flipped-90", ...
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub transform: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scale: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Config {
     pub monitor_config_path: PathBuf,
     #[serde(default = "default_workspace_count")]
     pub workspace_count: usize,
+    #[serde(default)]
+    pub pinned_workspaces: Vec<usize>,
+    #[serde(default)]
+    pub anchored_monitors: Vec<String>,
+    #[serde(default)]
+    pub auto_reflow: bool,
+    #[serde(default)]
+    pub custom_modes: Vec<CustomMode>,
+    /// Keep the layout in ~/.config/xwlm/layout.toml and generate the
+    /// compositor monitor config from it.
+    #[serde(default)]
+    pub canonical_layout: bool,
+    /// Bound of the monitor event and action channels. Each head sends a
+    /// burst of events at startup, so setups with many outputs need room.
+    #[serde(default = "default_event_queue_size")]
+    pub event_queue_size: usize,
+    /// How long the TUI sleeps between checks while nothing is happening.
+    #[serde(default = "default_idle_timeout_ms")]
+    pub idle_timeout_ms: u64,
+    /// UI language such as "de"; unset follows LC_MESSAGES/LANG.
+    #[serde(default)]
+    pub language: Option<String>,
+    /// Write changes for monitors left untouched this session without
+    /// asking first.
+    #[serde(default)]
+    pub include_untouched_monitors: bool,
+    /// Send enable/disable toggles right away instead of staging them
+    /// until Enter.
+    #[serde(default)]
+    pub instant_toggle: bool,
+    /// Reconnects of one monitor within ten minutes that trigger a warning
+    /// about its cable or link; 0 turns the warning off.
+    #[serde(default = "default_flap_threshold")]
+    pub flap_threshold: usize,
+    #[serde(default)]
+    pub on_new_monitor: NewMonitorAction,
+    /// Before saving, move monitors onto the whole-pixel edges of their
+    /// neighbours' logical sizes, closing gaps left by fractional scales.
+    #[serde(default = "default_snap_layout")]
+    pub snap_layout: bool,
+    /// How long saves wait after a compositor reload, so a quick second
+    /// save cannot rewrite the file while it is still being read.
+    #[serde(default = "default_reload_cooldown_ms")]
+    pub reload_cooldown_ms: u64,
+    /// Outputs left out when wlsunset is restarted, so they keep their
+    /// colors at night.
+    #[serde(default)]
+    pub night_light_excluded: Vec<String>,
+    /// Outputs xwlm leaves alone: their lines in the monitor config are
+    /// kept as written and changes to them need a second go.
+    #[serde(default)]
+    pub unmanaged_monitors: Vec<String>,
+    /// Show the footer line with the config path and save state; off
+    /// leaves the row to the map.
+    #[serde(default = "default_status_line")]
+    pub status_line: bool,
+    /// Keep an error that stops the TUI from starting on screen until a
+    /// key is pressed, rather than printing it as the terminal closes.
+    #[serde(default = "default_hold_on_error")]
+    pub hold_on_error: bool,
+    /// How far the map zooms out and in, and the factor each `+`/`-`
+    /// multiplies or divides the zoom by.
+    #[serde(default = "default_zoom_min")]
+    pub zoom_min: f64,
+    #[serde(default = "default_zoom_max")]
+    pub zoom_max: f64,
+    #[serde(default = "default_zoom_step")]
+    pub zoom_step: f64,
+    /// Settings for new monitors by "make model", e.g.
+    /// `[defaults."LG Electronics LG HDR 4K"]`.
+    #[serde(default)]
+    pub defaults: BTreeMap<String, MonitorDefaults>,
+    #[serde(default)]
+    pub failure_alert: FailureAlert,
+    /// Ask before quitting once something was applied, listing what
+    /// changed since launch.
+    #[serde(default)]
+    pub confirm_quit: bool,
+    /// How many earlier versions of the monitor config to keep as
+    /// `<path>.bak.1` (newest) to `<path>.bak.N`; 0 keeps none.
+    #[serde(default = "default_backup_count")]
+    pub backup_count: usize,
+    /// Back colors up with text for palettes and eyes where they do not
+    /// tell apart: ▶ before the focused panel's title, a SELECTED tag on
+    /// the selected monitor and * after values waiting to be applied.
+    #[serde(default)]
+    pub high_contrast: bool,
+    /// Drawn before the highlighted row of every list instead of " › ",
+    /// for fonts that lack it.
+    #[serde(default)]
+    pub highlight_symbol: Option<String>,
+    /// On Hyprland with `misc:disable_autoreload` set, send a saved change
+    /// with `hyprctl keyword monitor` for just the monitors that changed,
+    /// and only reload the whole config when more than their lines
+    /// changed.
+    #[serde(default = "default_live_apply")]
+    pub live_apply: bool,
+    /// Moving a monitor on the map within this many pixels of another
+    /// monitor's edge lands it flush on that edge; 0 moves pixel by pixel.
+    #[serde(default = "default_snap_threshold")]
+    pub snap_threshold: i32,
+}
+
+impl Config {
+    pub fn new(monitor_config_path: PathBuf) -> Self {
+        Self {
+            monitor_config_path,
+            workspace_count: default_workspace_count(),
+            pinned_workspaces: Vec::new(),
+            anchored_monitors: Vec::new(),
+            auto_reflow: false,
+            custom_modes: Vec::new(),
+            canonical_layout: false,
+            event_queue_size: default_event_queue_size(),
+            idle_timeout_ms: default_idle_timeout_ms(),
+            language: None,
+            include_untouched_monitors: false,
+            instant_toggle: false,
+            flap_threshold: default_flap_threshold(),
+            on_new_monitor: NewMonitorAction::Ignore,
+            snap_layout: default_snap_layout(),
+            reload_cooldown_ms: default_reload_cooldown_ms(),
+            night_light_excluded: Vec::new(),
+            unmanaged_monitors: Vec::new(),
+            status_line: default_status_line(),
+            hold_on_error: default_hold_on_error(),
+            zoom_min: default_zoom_min(),
+            zoom_max: default_zoom_max(),
+            zoom_step: default_zoom_step(),
+            defaults: BTreeMap::new(),
+            failure_alert: FailureAlert::None,
+            confirm_quit: false,
+            backup_count: default_backup_count(),
+            high_contrast: false,
+            highlight_symbol: None,
+            live_apply: default_live_apply(),
+            snap_threshold: default_snap_threshold(),
+        }
+    }
 }
 
 pub fn load_config() -> Result<Config, ConfigError> {
@@ -90,6 +290,58 @@ fn default_workspace_count() -> usize {
     10
 }
 
+fn default_event_queue_size() -> usize {
+    256
+}
+
+fn default_flap_threshold() -> usize {
+    5
+}
+
+fn default_idle_timeout_ms() -> u64 {
+    1000
+}
+
+fn default_snap_layout() -> bool {
+    true
+}
+
+fn default_backup_count() -> usize {
+    3
+}
+
+fn default_reload_cooldown_ms() -> u64 {
+    500
+}
+
+fn default_status_line() -> bool {
+    true
+}
+
+fn default_hold_on_error() -> bool {
+    true
+}
+
+fn default_live_apply() -> bool {
+    true
+}
+
+fn default_snap_threshold() -> i32 {
+    50
+}
+
+fn default_zoom_min() -> f64 {
+    0.2
+}
+
+fn default_zoom_max() -> f64 {
+    5.0
+}
+
+fn default_zoom_step() -> f64 {
+    1.1
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -100,8 +352,29 @@ mod tests {
     #[test]
     fn save_then_load_config_works() {
         let config = Config {
-            monitor_config_path: PathBuf::from("/tmp/test.conf"),
             workspace_count: 5,
+            pinned_workspaces: vec![3, 7],
+            anchored_monitors: vec!["DP-1".into()],
+            night_light_excluded: vec!["DP-2".into()],
+            unmanaged_monitors: vec!["DP-3".into()],
+            auto_reflow: true,
+            custom_modes: vec![CustomMode {
+                monitor: "DP-1".into(),
+                width: 3840,
+                height: 1080,
+                refresh_rate: 120,
+                active: true,
+            }],
+            canonical_layout: true,
+            on_new_monitor: NewMonitorAction::EnableSaved,
+            defaults: BTreeMap::from([(
+                "LG Electronics LG HDR 4K".into(),
+                MonitorDefaults {
+                    transform: Some("90".into()),
+                    scale: Some(1.2),
+                },
+            )]),
+            ..Config::new(PathBuf::from("/tmp/test.conf"))
         };
 
         save_to_path(TEST_PATH, &config).unwrap();
@@ -110,9 +383,67 @@ mod tests {
 
         assert_eq!(loaded.workspace_count, config.workspace_count);
 
+        assert_eq!(loaded.pinned_workspaces, config.pinned_workspaces);
+
+        assert_eq!(loaded.anchored_monitors, config.anchored_monitors);
+
+        assert_eq!(loaded.night_light_excluded, config.night_light_excluded);
+
+        assert_eq!(loaded.unmanaged_monitors, config.unmanaged_monitors);
+
+        assert!(loaded.auto_reflow);
+
+        assert_eq!(loaded.custom_modes, config.custom_modes);
+
+        assert!(loaded.canonical_layout);
+
+        assert_eq!(loaded.on_new_monitor, NewMonitorAction::EnableSaved);
+
+        assert_eq!(loaded.defaults, config.defaults);
+
         assert_eq!(loaded.monitor_config_path, config.monitor_config_path);
     }
 
+    #[test]
+    fn load_defaults_missing_optional_fields() {
+        let path = "~/.config/test-xwlm/minimal.toml";
+
+        let expanded = utils::expand_tilde(path).unwrap();
+
+        if let Some(parent) = expanded.parent() {
+            std::fs::create_dir_all(parent).unwrap();
+        }
+
+        std::fs::write(&expanded, "monitor_config_path = \"/tmp/m.conf\"\n")
+            .unwrap();
+
+        let loaded = load_from_path(path).unwrap();
+
+        assert_eq!(loaded.workspace_count, 10);
+
+        assert!(loaded.pinned_workspaces.is_empty());
+
+        assert!(loaded.anchored_monitors.is_empty());
+
+        assert!(!loaded.auto_reflow);
+
+        assert!(!loaded.canonical_layout);
+
+        assert_eq!(loaded.event_queue_size, 256);
+
+        assert_eq!(loaded.idle_timeout_ms, 1000);
+
+        assert!(loaded.language.is_none());
+
+        assert_eq!(loaded.on_new_monitor, NewMonitorAction::Ignore);
+
+        assert!(loaded.snap_layout);
+
+        assert_eq!(loaded.reload_cooldown_ms, 500);
+
+        assert_eq!(loaded.zoom_step, 1.1);
+    }
+
     #[test]
     fn load_fails_when_file_missing() {
         let path = "~/.config/test-xwlm/missing.toml";